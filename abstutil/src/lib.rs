@@ -87,6 +87,13 @@ pub fn path_all_scenarios(map_name: &str) -> String {
     format!("../data/system/scenarios/{}", map_name)
 }
 
+pub fn path_walking_distances(map_name: &str, edits_name: &str) -> String {
+    format!(
+        "../data/system/walking_distances/{}/{}.bin",
+        map_name, edits_name
+    )
+}
+
 pub fn path_synthetic_map(map_name: &str) -> String {
     format!("../data/system/synthetic_maps/{}.json", map_name)
 }
@@ -120,6 +127,21 @@ pub fn path_all_saves(map_name: &str, edits_name: &str, run_name: &str) -> Strin
     )
 }
 
+pub fn path_prior_routes(map_name: &str, scenario_name: &str) -> String {
+    format!(
+        "../data/player/prior_routes/{}/{}.bin",
+        map_name, scenario_name
+    )
+}
+
+pub fn path_reports(map_name: &str, edits_name: &str) -> String {
+    format!("../data/player/reports/{}/{}", map_name, edits_name)
+}
+
+pub fn path_player_screenshot(map_name: &str, name: &str) -> String {
+    format!("../data/player/screenshots/{}/{}.png", map_name, name)
+}
+
 // Input data (For developers to build maps, not needed at runtime)
 
 pub fn path_pending_screenshots(map_name: &str) -> String {
@@ -130,6 +152,14 @@ pub fn path_popdat() -> String {
     format!("../data/input/seattle/popdat.bin")
 }
 
+pub fn path_intersection_geometry(map_name: &str) -> String {
+    format!("../data/input/intersection_geometry/{}.json", map_name)
+}
+
+pub fn path_qa_report(map_name: &str) -> String {
+    format!("../data/input/qa/{}.json", map_name)
+}
+
 pub fn path_raw_map(map_name: &str) -> String {
     format!("../data/input/raw_maps/{}.bin", map_name)
 }
@@ -115,6 +115,9 @@ pub fn clip_map(map: &mut RawMap, timer: &mut Timer) {
             .all(|pt| boundary_polygon.contains_pt(*pt))
     });
 
+    map.bus_stops
+        .retain(|stop| boundary_polygon.contains_pt(stop.pt));
+
     let mut result_areas = Vec::new();
     for orig_area in map.areas.drain(..) {
         for polygon in map.boundary_polygon.intersection(&orig_area.polygon) {
@@ -0,0 +1,152 @@
+use abstutil::{Counter, Timer};
+use geom::{Distance, Pt2D, EPSILON_DIST};
+use map_model::raw::{OriginalIntersection, OriginalRoad, RawMap};
+use map_model::IntersectionType;
+use std::collections::{BTreeSet, HashMap};
+
+// OSM often has a road ending a few centimeters away from another one, where they obviously should
+// connect. These dead-ends disconnect the network, and then pathfinding breaks. Snap a dead-end to
+// the closest intersection within the radius, and report everything that got merged.
+//
+// This only heals endpoints near other endpoints. A road ending just short of the middle of
+// another road isn't handled yet.
+pub fn snap_endpoints(map: &mut RawMap, radius: Distance, timer: &mut Timer) {
+    timer.start("snap almost-connected road endpoints");
+
+    let mut degree: Counter<OriginalIntersection> = Counter::new();
+    for r in map.roads.keys() {
+        degree.inc(r.i1);
+        if r.i1 != r.i2 {
+            degree.inc(r.i2);
+        }
+    }
+
+    // Bucket intersections into a grid, so finding nearby ones is quick.
+    let cell = |pt: Pt2D| {
+        (
+            (pt.x() / radius.inner_meters()).floor() as i64,
+            (pt.y() / radius.inner_meters()).floor() as i64,
+        )
+    };
+    let mut grid: HashMap<(i64, i64), Vec<OriginalIntersection>> = HashMap::new();
+    for (id, i) in &map.intersections {
+        grid.entry(cell(i.point)).or_insert_with(Vec::new).push(*id);
+    }
+
+    let dead_ends: Vec<OriginalIntersection> = map
+        .intersections
+        .iter()
+        .filter(|(id, i)| degree.get(**id) == 1 && i.intersection_type != IntersectionType::Border)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut merged: BTreeSet<OriginalIntersection> = BTreeSet::new();
+    let mut snapped = 0;
+    for i in dead_ends {
+        // Maybe something else already snapped here
+        if merged.contains(&i) || degree.get(i) != 1 {
+            continue;
+        }
+        let r = map.roads_per_intersection(i)[0];
+        if r.i1 == r.i2 {
+            continue;
+        }
+        let other_end = if r.i1 == i { r.i2 } else { r.i1 };
+        let pt = map.intersections[&i].point;
+
+        let (x, y) = cell(pt);
+        let mut best: Option<(Distance, OriginalIntersection)> = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for target in grid.get(&(x + dx, y + dy)).into_iter().flatten() {
+                    // Snapping to the road's other end would make a loop.
+                    if *target == i || *target == other_end || merged.contains(target) {
+                        continue;
+                    }
+                    let dist = pt.dist_to(map.intersections[target].point);
+                    if dist <= radius && best.map(|(d, _)| dist < d).unwrap_or(true) {
+                        best = Some((dist, *target));
+                    }
+                }
+            }
+        }
+        let (dist, target) = match best {
+            Some(pair) => pair,
+            None => {
+                continue;
+            }
+        };
+
+        let new_id = if r.i1 == i {
+            OriginalRoad {
+                osm_way_id: r.osm_way_id,
+                i1: target,
+                i2: r.i2,
+            }
+        } else {
+            OriginalRoad {
+                osm_way_id: r.osm_way_id,
+                i1: r.i1,
+                i2: target,
+            }
+        };
+        if map.roads.contains_key(&new_id) {
+            timer.warn(format!(
+                "Not snapping {} to {}, because {} already exists",
+                i, target, new_id
+            ));
+            continue;
+        }
+
+        let target_pt = map.intersections[&target].point;
+        let mut road = map.roads.remove(&r).unwrap();
+        if r.i1 == i {
+            road.center_points[0] = target_pt;
+            if road.center_points.len() > 2
+                && road.center_points[1].dist_to(target_pt) <= EPSILON_DIST
+            {
+                road.center_points.remove(1);
+            }
+        } else {
+            let last = road.center_points.len() - 1;
+            road.center_points[last] = target_pt;
+            if last > 1 && road.center_points[last - 1].dist_to(target_pt) <= EPSILON_DIST {
+                road.center_points.remove(last - 1);
+            }
+        }
+        map.roads.insert(new_id, road);
+        rename_road(map, r, new_id);
+        map.delete_intersection(i);
+
+        merged.insert(i);
+        degree.inc(target);
+        snapped += 1;
+        timer.note(format!(
+            "Snapped {} to {} ({} apart), so {} is now {}",
+            i, target, dist, r, new_id
+        ));
+    }
+
+    timer.note(format!(
+        "Snapped {} almost-connected road endpoints within {}",
+        snapped, radius
+    ));
+    timer.stop("snap almost-connected road endpoints");
+}
+
+// Fix up turn restrictions that refer to a road by its old ID.
+fn rename_road(map: &mut RawMap, old: OriginalRoad, new: OriginalRoad) {
+    let fix = |r: &mut OriginalRoad| {
+        if *r == old {
+            *r = new;
+        }
+    };
+    for road in map.roads.values_mut() {
+        for (_, to) in road.turn_restrictions.iter_mut() {
+            fix(to);
+        }
+        for (via, to) in road.complicated_turn_restrictions.iter_mut() {
+            fix(via);
+            fix(to);
+        }
+    }
+}
@@ -1,16 +1,20 @@
 mod clip;
+mod heal;
 mod osm_reader;
 mod split_ways;
 mod srtm;
 
-use abstutil::Timer;
+use abstutil::{Counter, Timer};
 use geom::{Distance, FindClosest, PolyLine, Pt2D};
 use kml::ExtraShapes;
 use map_model::osm;
 use map_model::raw::{DrivingSide, OriginalBuilding, OriginalRoad, RawMap};
+use std::collections::BTreeMap;
 
 // Just used for matching hints to different sides of a road.
 const DIRECTED_ROAD_THICKNESS: Distance = Distance::const_meters(2.5);
+// Marks roads whose sidewalks came from separately mapped footways
+const SEPARATE_SIDEWALKS: &str = "abst:sidewalks_separate";
 
 pub struct Options {
     pub osm_input: String,
@@ -25,6 +29,8 @@ pub struct Options {
     pub elevation: Option<String>,
     pub clip: Option<String>,
     pub drive_on_right: bool,
+    // Road endpoints closer than this get merged into one intersection. None skips this.
+    pub snap_radius: Option<Distance>,
 }
 
 // If a building doesn't have anything from public_offstreet_parking, how many private spots should
@@ -35,17 +41,38 @@ pub enum PrivateOffstreetParking {
 }
 
 pub fn convert(opts: Options, timer: &mut abstutil::Timer) -> RawMap {
+    let (
+        raw,
+        roads,
+        traffic_signals,
+        osm_node_ids,
+        simple_turn_restrictions,
+        complicated_turn_restrictions,
+        amenities,
+        separate_sidewalks,
+    ) = osm_reader::extract_osm(
+        &opts.osm_input,
+        &opts.clip,
+        &opts.city_name,
+        &opts.name,
+        timer,
+    );
     let (mut map, amenities) = split_ways::split_up_roads(
-        osm_reader::extract_osm(
-            &opts.osm_input,
-            &opts.clip,
-            &opts.city_name,
-            &opts.name,
-            timer,
+        (
+            raw,
+            roads,
+            traffic_signals,
+            osm_node_ids,
+            simple_turn_restrictions,
+            complicated_turn_restrictions,
+            amenities,
         ),
         timer,
     );
     clip::clip_map(&mut map, timer);
+    if let Some(radius) = opts.snap_radius {
+        heal::snap_endpoints(&mut map, radius, timer);
+    }
     map.driving_side = if opts.drive_on_right {
         DrivingSide::Right
     } else {
@@ -65,9 +92,11 @@ pub fn convert(opts: Options, timer: &mut abstutil::Timer) -> RawMap {
         use_offstreet_parking(&mut map, path.clone(), timer);
     }
     apply_private_offstreet_parking(&mut map, opts.private_offstreet_parking);
+    use_separate_sidewalks(&mut map, separate_sidewalks, timer);
     if let Some(ref path) = opts.sidewalks {
         use_sidewalk_hints(&mut map, path.clone(), timer);
     }
+    report_missing_sidewalks(&map, timer);
     if let Some(ref path) = opts.gtfs {
         timer.start("load GTFS");
         map.bus_routes = gtfs::load(path);
@@ -88,7 +117,13 @@ fn use_parking_hints(map: &mut RawMap, path: String, timer: &mut Timer) {
     let mut closest: FindClosest<(OriginalRoad, bool)> =
         FindClosest::new(&map.gps_bounds.to_bounds());
     for (id, r) in &map.roads {
-        let center = PolyLine::new(r.center_points.clone());
+        let center = match PolyLine::try_new(r.center_points.clone()) {
+            Ok(pl) => pl,
+            Err(err) => {
+                timer.warn(format!("Nothing can match {}: {}", id, err));
+                continue;
+            }
+        };
         closest.add(
             (*id, true),
             map.driving_side
@@ -243,27 +278,7 @@ fn apply_private_offstreet_parking(map: &mut RawMap, policy: PrivateOffstreetPar
 fn use_sidewalk_hints(map: &mut RawMap, path: String, timer: &mut Timer) {
     timer.start("apply sidewalk hints");
     let shapes: ExtraShapes = abstutil::read_binary(path, timer);
-
-    // Match shapes with the nearest road + direction (true for forwards)
-    let mut closest: FindClosest<(OriginalRoad, bool)> =
-        FindClosest::new(&map.gps_bounds.to_bounds());
-    for (id, r) in &map.roads {
-        let center = PolyLine::new(r.center_points.clone());
-        closest.add(
-            (*id, true),
-            map.driving_side
-                .right_shift(center.clone(), DIRECTED_ROAD_THICKNESS)
-                .get(timer)
-                .points(),
-        );
-        closest.add(
-            (*id, false),
-            map.driving_side
-                .left_shift(center, DIRECTED_ROAD_THICKNESS)
-                .get(timer)
-                .points(),
-        );
-    }
+    let closest = directed_road_sides(map, timer);
 
     for s in shapes.shapes.into_iter() {
         let pts = if let Some(pts) = map.gps_bounds.try_convert(&s.points) {
@@ -300,25 +315,148 @@ fn use_sidewalk_hints(map: &mut RawMap, path: String, timer: &mut Timer) {
                     continue;
                 }
 
-                if fwds {
-                    if osm_tags.get(osm::SIDEWALK) == Some(&"left".to_string()) {
-                        osm_tags.insert(osm::SIDEWALK.to_string(), "both".to_string());
-                    } else {
-                        osm_tags.insert(osm::SIDEWALK.to_string(), "right".to_string());
-                    }
-                } else {
-                    if osm_tags.get(osm::SIDEWALK) == Some(&"right".to_string()) {
-                        osm_tags.insert(osm::SIDEWALK.to_string(), "both".to_string());
-                    } else {
-                        osm_tags.insert(osm::SIDEWALK.to_string(), "left".to_string());
-                    }
-                }
+                add_sidewalk(osm_tags, fwds);
             }
         }
     }
     timer.stop("apply sidewalk hints");
 }
 
+// Sidewalks mapped as separate footways run alongside a road. Instead of importing them as more
+// paths, figure out which side of which road they belong to, and make sure that road has a
+// sidewalk there.
+fn use_separate_sidewalks(map: &mut RawMap, sidewalks: Vec<Vec<Pt2D>>, timer: &mut Timer) {
+    timer.start("match separately mapped sidewalks");
+    let closest = directed_road_sides(map, timer);
+
+    // Footways often span several blocks, so check points all along them. Near the ends, they
+    // tend to wrap around corners and get close to the cross street, so skip those.
+    let step = Distance::meters(20.0);
+    let mut matches: Counter<(OriginalRoad, bool)> = Counter::new();
+    for pts in sidewalks {
+        let pl = if let Some(pl) = PolyLine::maybe_new(pts) {
+            pl
+        } else {
+            continue;
+        };
+        let mut dist = step / 2.0;
+        while dist + step / 2.0 < pl.length() {
+            if let Some((key, _)) =
+                closest.closest_pt(pl.dist_along(dist).0, DIRECTED_ROAD_THICKNESS * 5.0)
+            {
+                matches.inc(key);
+            }
+            dist += step;
+        }
+    }
+
+    let mut applied = 0;
+    for ((r, fwds), cnt) in matches.consume() {
+        // One sample could just be a footway passing by.
+        if cnt < 2 {
+            continue;
+        }
+        let osm_tags = &mut map.roads.get_mut(&r).unwrap().osm_tags;
+        // Explicit tagging wins, except for "separate", which didn't say which sides.
+        if osm_tags.contains_key(osm::INFERRED_SIDEWALKS) {
+            // The footway replaces the guess entirely.
+            osm_tags.remove(osm::INFERRED_SIDEWALKS);
+            osm_tags.insert(osm::SIDEWALK.to_string(), "none".to_string());
+        } else if osm_tags.get(osm::SIDEWALK) == Some(&"separate".to_string()) {
+            osm_tags.insert(osm::SIDEWALK.to_string(), "none".to_string());
+        } else if !osm_tags.contains_key(SEPARATE_SIDEWALKS) {
+            continue;
+        }
+        osm_tags.insert(SEPARATE_SIDEWALKS.to_string(), "true".to_string());
+        add_sidewalk(osm_tags, fwds);
+        applied += 1;
+    }
+    timer.note(format!(
+        "{} road sides got sidewalks from separately mapped footways",
+        applied
+    ));
+    timer.stop("match separately mapped sidewalks");
+}
+
+// Roads where people would expect a sidewalk, but OSM explicitly says there's none. Worth a look
+// before trusting walking results there.
+fn report_missing_sidewalks(map: &RawMap, timer: &mut Timer) {
+    let mut cnt = 0;
+    for (id, r) in &map.roads {
+        let hwy = match r.osm_tags.get(osm::HIGHWAY) {
+            Some(hwy) => hwy.trim_end_matches("_link"),
+            None => continue,
+        };
+        if ![
+            "primary",
+            "secondary",
+            "tertiary",
+            "residential",
+            "unclassified",
+            "living_street",
+        ]
+        .contains(&hwy)
+        {
+            continue;
+        }
+        if r.osm_tags.get(osm::SIDEWALK) == Some(&"none".to_string())
+            || r.osm_tags.get(osm::SIDEWALK) == Some(&"no".to_string())
+        {
+            cnt += 1;
+            timer.warn(format!("{} ({}) has no sidewalks", id, hwy));
+        }
+    }
+    timer.note(format!("{} roads are missing sidewalks", cnt));
+}
+
+// Lines a bit to either side of every road, to match things that run alongside one. The key is
+// the road and direction (true for forwards).
+fn directed_road_sides(map: &RawMap, timer: &mut Timer) -> FindClosest<(OriginalRoad, bool)> {
+    let mut closest: FindClosest<(OriginalRoad, bool)> =
+        FindClosest::new(&map.gps_bounds.to_bounds());
+    for (id, r) in &map.roads {
+        let center = match PolyLine::try_new(r.center_points.clone()) {
+            Ok(pl) => pl,
+            Err(err) => {
+                timer.warn(format!("Nothing can match {}: {}", id, err));
+                continue;
+            }
+        };
+        closest.add(
+            (*id, true),
+            map.driving_side
+                .right_shift(center.clone(), DIRECTED_ROAD_THICKNESS)
+                .get(timer)
+                .points(),
+        );
+        closest.add(
+            (*id, false),
+            map.driving_side
+                .left_shift(center, DIRECTED_ROAD_THICKNESS)
+                .get(timer)
+                .points(),
+        );
+    }
+    closest
+}
+
+fn add_sidewalk(osm_tags: &mut BTreeMap<String, String>, fwds: bool) {
+    let (this_side, other_side) = if fwds {
+        ("right", "left")
+    } else {
+        ("left", "right")
+    };
+    match osm_tags.get(osm::SIDEWALK).map(|x| x.as_str()) {
+        Some("both") => {}
+        Some(x) if x == other_side => {
+            osm_tags.insert(osm::SIDEWALK.to_string(), "both".to_string());
+        }
+        _ => {
+            osm_tags.insert(osm::SIDEWALK.to_string(), this_side.to_string());
+        }
+    }
+}
+
 fn use_amenities(map: &mut RawMap, amenities: Vec<(Pt2D, String, String)>, timer: &mut Timer) {
     let mut closest: FindClosest<OriginalBuilding> = FindClosest::new(&map.gps_bounds.to_bounds());
     for (id, b) in &map.buildings {
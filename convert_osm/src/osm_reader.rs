@@ -1,9 +1,12 @@
 use abstutil::{FileWithProgress, Timer};
-use geom::{GPSBounds, HashablePt2D, LonLat, PolyLine, Polygon, Pt2D, Ring};
+use geom::{
+    Distance, GPSBounds, LonLat, PolyLine, Polygon, Pt2D, QuantizedPt2D, Ring, EPSILON_DIST,
+};
 use map_model::raw::{
-    OriginalBuilding, RawArea, RawBuilding, RawMap, RawParkingLot, RawRoad, RestrictionType,
+    OriginalBuilding, RawArea, RawBuilding, RawBusStop, RawMap, RawParkingLot, RawRoad,
+    RestrictionType,
 };
-use map_model::{osm, AreaType};
+use map_model::{osm, AreaType, BusStopAmenities};
 use osm_xml;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
@@ -18,15 +21,17 @@ pub fn extract_osm(
     // Un-split roads
     Vec<(i64, RawRoad)>,
     // Traffic signals
-    HashSet<HashablePt2D>,
+    HashSet<QuantizedPt2D>,
     // OSM Node IDs
-    HashMap<HashablePt2D, i64>,
+    HashMap<QuantizedPt2D, i64>,
     // Simple turn restrictions: (restriction type, from way ID, via node ID, to way ID)
     Vec<(RestrictionType, i64, i64, i64)>,
     // Complicated turn restrictions: (from way ID, via way ID, to way ID)
     Vec<(i64, i64, i64)>,
     // Amenities (location, name, amenity type)
     Vec<(Pt2D, String, String)>,
+    // Sidewalks mapped as their own ways
+    Vec<Vec<Pt2D>>,
 ) {
     let (reader, done) = FileWithProgress::new(osm_path).unwrap();
     let doc = osm_xml::OSM::parse(reader).expect("OSM parsing failed");
@@ -60,19 +65,26 @@ pub fn extract_osm(
 
     let mut id_to_way: HashMap<i64, Vec<Pt2D>> = HashMap::new();
     let mut roads: Vec<(i64, RawRoad)> = Vec::new();
-    let mut traffic_signals: HashSet<HashablePt2D> = HashSet::new();
+    let mut traffic_signals: HashSet<QuantizedPt2D> = HashSet::new();
     let mut osm_node_ids = HashMap::new();
     let mut node_amenities = Vec::new();
+    let mut separate_sidewalks = Vec::new();
 
     timer.start_iter("processing OSM nodes", doc.nodes.len());
     for node in doc.nodes.values() {
         timer.next();
         let pt = Pt2D::forcibly_from_gps(LonLat::new(node.lon, node.lat), &map.gps_bounds);
-        osm_node_ids.insert(pt.to_hashable(), node.id);
+        osm_node_ids.insert(pt.quantize(EPSILON_DIST), node.id);
 
         let tags = tags_to_map(&node.tags);
         if tags.get(osm::HIGHWAY) == Some(&"traffic_signals".to_string()) {
-            traffic_signals.insert(pt.to_hashable());
+            traffic_signals.insert(pt.quantize(EPSILON_DIST));
+        }
+        if tags.get(osm::HIGHWAY) == Some(&"bus_stop".to_string()) {
+            map.bus_stops.push(RawBusStop {
+                pt,
+                amenities: BusStopAmenities::from_osm(&tags),
+            });
         }
         if let Some(amenity) = tags.get("amenity") {
             node_amenities.push((
@@ -133,6 +145,7 @@ pub fn extract_osm(
                 tags.insert(osm::INFERRED_PARKING.to_string(), "true".to_string());
             }
 
+            normalize_sidewalk_tags(&mut tags);
             // If there's no sidewalk data in OSM already, then make an assumption and mark that
             // it's inferred.
             if !tags.contains_key(osm::SIDEWALK) {
@@ -168,12 +181,22 @@ pub fn extract_osm(
                     complicated_turn_restrictions: Vec::new(),
                 },
             ));
+        } else if tags.get(osm::HIGHWAY) == Some(&"footway".to_string())
+            && tags.get("footway") == Some(&"sidewalk".to_string())
+        {
+            // These get folded into the sidewalks of the road they run alongside, instead of
+            // becoming a parallel path of their own.
+            separate_sidewalks.push(pts);
         } else if is_bldg(&tags) {
             let mut deduped = pts.clone();
             deduped.dedup();
-            if deduped.len() < 3 {
-                continue;
-            }
+            let polygon = match Polygon::try_new(&deduped) {
+                Ok(p) => p,
+                Err(err) => {
+                    timer.warn(format!("Skipping building {}: {}", way.id, err));
+                    continue;
+                }
+            };
 
             let mut amenities = BTreeSet::new();
             if let Some(amenity) = tags.get("amenity") {
@@ -195,7 +218,8 @@ pub fn extract_osm(
             map.buildings.insert(
                 OriginalBuilding { osm_way_id: way.id },
                 RawBuilding {
-                    polygon: Polygon::new(&deduped),
+                    polygon,
+                    height: get_bldg_height(&tags),
                     osm_tags: tags,
                     public_garage_name: None,
                     num_parking_spots: 0,
@@ -203,23 +227,34 @@ pub fn extract_osm(
                 },
             );
         } else if let Some(at) = get_area_type(&tags) {
-            if pts.len() < 3 {
-                continue;
+            match Polygon::try_new(&pts) {
+                Ok(polygon) => {
+                    map.areas.push(RawArea {
+                        area_type: at,
+                        osm_id: way.id,
+                        polygon,
+                        osm_tags: tags,
+                    });
+                }
+                Err(err) => {
+                    timer.warn(format!("Skipping area {}: {}", way.id, err));
+                }
             }
-            map.areas.push(RawArea {
-                area_type: at,
-                osm_id: way.id,
-                polygon: Polygon::new(&pts),
-                osm_tags: tags,
-            });
         } else if tags.get("natural") == Some(&"coastline".to_string()) {
             coastline_groups.push((way.id, pts));
         } else if tags.get("amenity") == Some(&"parking".to_string()) {
             // TODO Verify parking = surface or handle other cases?
-            map.parking_lots.push(RawParkingLot {
-                polygon: Polygon::new(&pts),
-                osm_id: way.id,
-            });
+            match Polygon::try_new(&pts) {
+                Ok(polygon) => {
+                    map.parking_lots.push(RawParkingLot {
+                        polygon,
+                        osm_id: way.id,
+                    });
+                }
+                Err(err) => {
+                    timer.warn(format!("Skipping parking lot {}: {}", way.id, err));
+                }
+            }
         } else if tags.get("highway") == Some(&"service".to_string()) {
             map.parking_aisles.push(pts);
         } else {
@@ -351,13 +386,18 @@ pub fn extract_osm(
                         shop.clone(),
                     ));
                 }
-                if pts.len() < 3 {
-                    continue;
-                }
+                let polygon = match Polygon::try_new(pts) {
+                    Ok(p) => p,
+                    Err(err) => {
+                        timer.warn(format!("Skipping building {}: {}", rel.id, err));
+                        continue;
+                    }
+                };
                 map.buildings.insert(
                     OriginalBuilding { osm_way_id: rel.id },
                     RawBuilding {
-                        polygon: Polygon::new(pts),
+                        polygon,
+                        height: get_bldg_height(&tags),
                         osm_tags: tags,
                         public_garage_name: None,
                         num_parking_spots: 0,
@@ -401,9 +441,37 @@ pub fn extract_osm(
         simple_turn_restrictions,
         complicated_turn_restrictions,
         node_amenities,
+        separate_sidewalks,
     )
 }
 
+// Some ways describe each side with sidewalk:left, sidewalk:right, or sidewalk:both instead of a
+// single sidewalk tag. Fold those into the plain tag, which is all that lane_specs understands.
+// "separate" means the sidewalk exists, just as its own way.
+fn normalize_sidewalk_tags(tags: &mut BTreeMap<String, String>) {
+    if tags.contains_key(osm::SIDEWALK) {
+        return;
+    }
+    let present = |key: &str| match tags.get(key).map(|x| x.as_str()) {
+        Some("yes") | Some("separate") => Some(true),
+        Some("no") | Some("none") => Some(false),
+        _ => None,
+    };
+    let both = present("sidewalk:both");
+    let left = present("sidewalk:left").or(both);
+    let right = present("sidewalk:right").or(both);
+    let value = match (left, right) {
+        (None, None) => {
+            return;
+        }
+        (Some(true), Some(true)) => "both",
+        (Some(true), _) => "left",
+        (_, Some(true)) => "right",
+        _ => "none",
+    };
+    tags.insert(osm::SIDEWALK.to_string(), value.to_string());
+}
+
 fn tags_to_map(raw_tags: &[osm_xml::Tag]) -> BTreeMap<String, String> {
     raw_tags
         .iter()
@@ -471,6 +539,45 @@ fn is_bldg(tags: &BTreeMap<String, String>) -> bool {
     tags.contains_key("building")
 }
 
+// Prefers an explicit height. Values are meters unless they say otherwise, like "12 m", "40 ft",
+// or "40'". Otherwise, estimates from the number of levels, including any in the roof.
+fn get_bldg_height(tags: &BTreeMap<String, String>) -> Option<Distance> {
+    if let Some(height) = tags.get("height") {
+        let height = height.trim();
+        let (num, feet) = if height.ends_with("ft") {
+            (height.trim_end_matches("ft"), true)
+        } else if height.ends_with('\'') {
+            (height.trim_end_matches('\''), true)
+        } else {
+            (height.trim_end_matches('m'), false)
+        };
+        if let Ok(x) = num.trim().parse::<f64>() {
+            if x > 0.0 {
+                return Some(if feet {
+                    Distance::feet(x)
+                } else {
+                    Distance::meters(x)
+                });
+            }
+        }
+    }
+
+    // A typical floor-to-floor height
+    let level_height = Distance::meters(3.0);
+    let levels = tags
+        .get("building:levels")
+        .and_then(|x| x.trim().parse::<f64>().ok())?;
+    let roof_levels = tags
+        .get("roof:levels")
+        .and_then(|x| x.trim().parse::<f64>().ok())
+        .unwrap_or(0.0);
+    if levels + roof_levels > 0.0 {
+        Some(level_height * (levels + roof_levels))
+    } else {
+        None
+    }
+}
+
 fn get_area_type(tags: &BTreeMap<String, String>) -> Option<AreaType> {
     if tags.get("leisure") == Some(&"park".to_string()) {
         return Some(AreaType::Park);
@@ -516,7 +623,10 @@ fn glue_multipolygon(
     let mut polygons: Vec<Polygon> = Vec::new();
     pts_per_way.retain(|(_, pts)| {
         if pts[0] == *pts.last().unwrap() {
-            polygons.push(Polygon::new(pts));
+            match Polygon::try_new(pts) {
+                Ok(p) => polygons.push(p),
+                Err(err) => println!("Skipping part of relation {}: {}", rel_id, err),
+            }
             false
         } else {
             true
@@ -560,16 +670,20 @@ fn glue_multipolygon(
         }
     }
 
-    if result[0] == *result.last().unwrap() {
-        polygons.push(Polygon::new(&result));
-        return polygons;
-    }
-    if let Some(poly) = glue_to_boundary(PolyLine::new(result.clone()), boundary) {
-        polygons.push(poly);
-    } else {
+    if result[0] != *result.last().unwrap() {
+        if let Some(poly) = PolyLine::try_new(result.clone())
+            .ok()
+            .and_then(|pl| glue_to_boundary(pl, boundary))
+        {
+            polygons.push(poly);
+            return polygons;
+        }
         // Give up and just connect the ends directly.
         result.push(result[0]);
-        polygons.push(Polygon::new(&result));
+    }
+    match Polygon::try_new(&result) {
+        Ok(p) => polygons.push(p),
+        Err(err) => println!("Skipping relation {}: {}", rel_id, err),
     }
 
     polygons
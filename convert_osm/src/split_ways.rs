@@ -1,5 +1,5 @@
 use abstutil::{Counter, Timer};
-use geom::{Distance, HashablePt2D, Pt2D};
+use geom::{Distance, Pt2D, QuantizedPt2D, EPSILON_DIST};
 use map_model::raw::{
     OriginalIntersection, OriginalRoad, RawIntersection, RawMap, RawRoad, RestrictionType,
 };
@@ -18,8 +18,8 @@ pub fn split_up_roads(
     ): (
         RawMap,
         Vec<(i64, RawRoad)>,
-        HashSet<HashablePt2D>,
-        HashMap<HashablePt2D, i64>,
+        HashSet<QuantizedPt2D>,
+        HashMap<QuantizedPt2D, i64>,
         Vec<(RestrictionType, i64, i64, i64)>,
         Vec<(i64, i64, i64)>,
         Vec<(Pt2D, String, String)>,
@@ -28,11 +28,11 @@ pub fn split_up_roads(
 ) -> (RawMap, Vec<(Pt2D, String, String)>) {
     timer.start("splitting up roads");
 
-    let mut pt_to_intersection: HashMap<HashablePt2D, OriginalIntersection> = HashMap::new();
+    let mut pt_to_intersection: HashMap<QuantizedPt2D, OriginalIntersection> = HashMap::new();
     let mut counts_per_pt = Counter::new();
     for (_, r) in &roads {
         for (idx, raw_pt) in r.center_points.iter().enumerate() {
-            let pt = raw_pt.to_hashable();
+            let pt = raw_pt.quantize(EPSILON_DIST);
             let count = counts_per_pt.inc(pt);
 
             // All start and endpoints of ways are also intersections.
@@ -69,8 +69,12 @@ pub fn split_up_roads(
         timer.next();
         let mut r = orig_road.clone();
         let mut pts = Vec::new();
-        let endpt1 = pt_to_intersection[&orig_road.center_points[0].to_hashable()];
-        let endpt2 = pt_to_intersection[&orig_road.center_points.last().unwrap().to_hashable()];
+        let endpt1 = pt_to_intersection[&orig_road.center_points[0].quantize(EPSILON_DIST)];
+        let endpt2 = pt_to_intersection[&orig_road
+            .center_points
+            .last()
+            .unwrap()
+            .quantize(EPSILON_DIST)];
         let mut i1 = endpt1;
 
         for pt in &orig_road.center_points {
@@ -78,7 +82,7 @@ pub fn split_up_roads(
             if pts.len() == 1 {
                 continue;
             }
-            if let Some(i2) = pt_to_intersection.get(&pt.to_hashable()) {
+            if let Some(i2) = pt_to_intersection.get(&pt.quantize(EPSILON_DIST)) {
                 if i1 == endpt1 {
                     r.osm_tags
                         .insert(osm::ENDPT_BACK.to_string(), "true".to_string());
@@ -0,0 +1,19 @@
+// Only the CPU side of rendering can run without a window. Uploading and drawing are measured at
+// runtime instead; see "benchmark rendering" in the game's dev tools.
+//
+// cargo bench --no-default-features --features headless-backend
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn tessellation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tessellation");
+    for n in &[100, 1_000, 10_000] {
+        group.bench_with_input(BenchmarkId::from_parameter(n), n, |b, n| {
+            b.iter(|| ezgui::make_shapes(*n))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tessellation);
+criterion_main!(benches);
@@ -9,11 +9,12 @@
 // --features wasm-backend --example demo
 
 use ezgui::{
-    hotkey, lctrl, Btn, Checkbox, Color, Composite, Drawable, EventCtx, EventLoopMode, GeomBatch,
-    GfxCtx, HorizontalAlignment, Key, Line, LinePlot, Outcome, PlotOptions, Series, Text, TextExt,
+    hotkey, lctrl, Btn, Checkbox, Color, ColorPicker, Composite, Drawable, EventCtx, EventLoopMode,
+    GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, LinePlot, Outcome, PlotOptions, ScreenDims,
+    ScreenPt, ScreenRectangle, ScrollingPanel, Series, TabContainer, Text, TextExt,
     VerticalAlignment, Widget, GUI,
 };
-use geom::{Angle, Duration, Polygon, Pt2D, Time};
+use geom::{Angle, Duration, Polygon, Pt2D};
 use std::collections::HashSet;
 
 fn main() {
@@ -29,6 +30,8 @@ struct App {
     controls: Composite,
     timeseries_panel: Option<(Duration, Composite)>,
     scrollable_canvas: Drawable,
+    long_list: (ScrollingPanel, Drawable),
+    tabs: TabContainer,
     elapsed: Duration,
 }
 
@@ -38,6 +41,8 @@ impl App {
             controls: make_controls(ctx),
             timeseries_panel: None,
             scrollable_canvas: setup_scrollable_canvas(ctx),
+            long_list: setup_long_list(ctx),
+            tabs: setup_tabs(ctx),
             elapsed: Duration::ZERO,
         }
     }
@@ -90,18 +95,16 @@ impl App {
                         Series {
                             label: "Linear".to_string(),
                             color: Color::GREEN,
-                            // These points are (x axis = Time, y axis = usize)
+                            // These points are (x axis = Duration, y axis = usize)
                             pts: (0..(self.elapsed.inner_seconds() as usize))
-                                .map(|s| (Time::START_OF_DAY + Duration::seconds(s as f64), s))
+                                .map(|s| (Duration::seconds(s as f64), s))
                                 .collect(),
                         },
                         Series {
                             label: "Quadratic".to_string(),
                             color: Color::BLUE,
                             pts: (0..(self.elapsed.inner_seconds() as usize))
-                                .map(|s| {
-                                    (Time::START_OF_DAY + Duration::seconds(s as f64), s.pow(2))
-                                })
+                                .map(|s| (Duration::seconds(s as f64), s.pow(2)))
                                 .collect(),
                         },
                     ],
@@ -109,7 +112,7 @@ impl App {
                         filterable: false,
                         // Without this, the plot doesn't stretch to cover times in between whole
                         // seconds.
-                        max_x: Some(Time::START_OF_DAY + self.elapsed),
+                        max_x: Some(self.elapsed),
                         max_y: None,
                         disabled: HashSet::new(),
                     },
@@ -197,6 +200,22 @@ impl GUI for App {
             }
         }
 
+        if self.controls.is_checked("Show long list") {
+            if ctx.input.is_window_resized() {
+                self.long_list.0.set_rect(long_list_rect(ctx));
+            }
+            // Handles the mouse wheel and dragging
+            self.long_list.0.event(ctx);
+        }
+
+        if self.controls.is_checked("Show tabs") {
+            // Switching tabs is handled inside; only the active tab gets the event.
+            match self.tabs.event(ctx) {
+                Some(Outcome::Clicked(_)) => unreachable!(),
+                None => {}
+            }
+        }
+
         // If we're paused, only call event() again when there's some kind of input. If not, also
         // sprinkle in periodic update events as time passes.
         if self.controls.is_checked("paused") {
@@ -218,6 +237,16 @@ impl GUI for App {
         if let Some((_, ref p)) = self.timeseries_panel {
             p.draw(g);
         }
+
+        if self.controls.is_checked("Show long list") {
+            // Anything drawn here gets clipped and scrolled.
+            let (ref panel, ref draw) = self.long_list;
+            panel.draw(g, |g| g.redraw(draw));
+        }
+
+        if self.controls.is_checked("Show tabs") {
+            self.tabs.draw(g);
+        }
     }
 }
 
@@ -247,6 +276,58 @@ fn setup_scrollable_canvas(ctx: &mut EventCtx) -> Drawable {
     batch.upload(ctx)
 }
 
+// Too long to fit on the screen, so it lives in a ScrollingPanel.
+fn setup_long_list(ctx: &mut EventCtx) -> (ScrollingPanel, Drawable) {
+    let mut txt = Text::new().bg(Color::grey(0.2));
+    for i in 1..=100 {
+        txt.add(Line(format!("Row {} of a very long list", i)));
+    }
+    let batch = txt.render_ctx(ctx);
+    let panel = ScrollingPanel::new(long_list_rect(ctx), batch.get_dims());
+    (panel, batch.upload(ctx))
+}
+
+// A few panels in the same spot. Hidden tabs remember their state, like these checkboxes.
+fn setup_tabs(ctx: &mut EventCtx) -> TabContainer {
+    TabContainer::new(
+        ctx,
+        HorizontalAlignment::Right,
+        VerticalAlignment::Center,
+        vec![
+            (
+                "About",
+                "Only the active tab gets events and draws."
+                    .draw_text(ctx)
+                    .bg(Color::grey(0.4))
+                    .padding(10),
+            ),
+            (
+                "Options",
+                Widget::col(vec![
+                    Checkbox::text(ctx, "First option", None, false),
+                    Checkbox::text(ctx, "Second option", None, true),
+                ])
+                .bg(Color::grey(0.4))
+                .padding(10),
+            ),
+            (
+                "Color",
+                ColorPicker::new(ctx, Color::hex("#4E30A6"))
+                    .named("color")
+                    .bg(Color::grey(0.4))
+                    .padding(10),
+            ),
+        ],
+    )
+}
+
+fn long_list_rect(ctx: &EventCtx) -> ScreenRectangle {
+    ScreenRectangle::top_left(
+        ScreenPt::new(10.0, 0.3 * ctx.canvas.window_height),
+        ScreenDims::new(300.0, 0.4 * ctx.canvas.window_height),
+    )
+}
+
 fn make_controls(ctx: &mut EventCtx) -> Composite {
     Composite::new(
         Widget::col(vec![
@@ -271,6 +352,8 @@ fn make_controls(ctx: &mut EventCtx) -> Composite {
                     .margin(5),
                 Checkbox::text(ctx, "Draw scrollable canvas", None, true).margin(5),
                 Checkbox::text(ctx, "Show timeseries", lctrl(Key::T), false).margin(5),
+                Checkbox::text(ctx, "Show long list", None, false).margin(5),
+                Checkbox::text(ctx, "Show tabs", None, false).margin(5),
             ])
             .evenly_spaced(),
             "Stopwatch: ...".draw_text(ctx).named("stopwatch"),
@@ -1,10 +1,12 @@
+use crate::glyphs::GlyphCache;
 use crate::text::Font;
-use crate::{text, GeomBatch};
-use geom::Bounds;
+use crate::tools::translations::Translations;
+use crate::{svg, text, Color, GeomBatch};
+use abstutil::Timer;
+use geom::{Bounds, Polygon};
 use lru::LruCache;
 use std::cell::RefCell;
-use std::collections::HashMap;
-use usvg::Options;
+use std::collections::{BTreeMap, HashMap};
 
 // TODO We don't need refcell maybe? Can we take &mut Assets?
 pub struct Assets {
@@ -16,7 +18,10 @@ pub struct Assets {
     // Keyed by filename, then scale factor mangled into a hashable form. Tuple doesn't work
     // because of borrowing.
     svg_cache: RefCell<HashMap<String, HashMap<usize, (GeomBatch, Bounds)>>>,
-    pub text_opts: Options,
+    // Tessellated once at startup, at their original size
+    icons: RefCell<HashMap<String, GeomBatch>>,
+    pub(crate) glyphs: GlyphCache,
+    translations: RefCell<Translations>,
 }
 
 impl Assets {
@@ -28,50 +33,42 @@ impl Assets {
             text_cache: RefCell::new(LruCache::new(500)),
             line_height_cache: RefCell::new(HashMap::new()),
             svg_cache: RefCell::new(HashMap::new()),
-            text_opts: Options::default(),
+            icons: RefCell::new(HashMap::new()),
+            glyphs: GlyphCache::new(&font_dir),
+            translations: RefCell::new(Translations::english()),
         };
         *a.default_line_height.borrow_mut() =
             a.line_height(text::DEFAULT_FONT, *a.default_font_size.borrow());
-        a.text_opts.font_directories.push(font_dir);
         a
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
     pub fn line_height(&self, font: Font, font_size: usize) -> f64 {
         let key = (font, font_size);
         if let Some(height) = self.line_height_cache.borrow().get(&key) {
             return *height;
         }
 
-        // TODO This is expensive and hacky!
-        let mut db = usvg::Database::new();
-        db.populate(&self.text_opts);
-        // This seems to be missing line_gap, and line_gap is 0, so manually adjust here.
+        // Fonts aren't available on the web yet, so just guess there.
+        // The fonts have no line gap, so manually adjust here.
         let height = text::SCALE_LINE_HEIGHT
-            * *self.scale_factor.borrow()
-            * db.load_font_idx(match font {
-                Font::BungeeInlineRegular => 0,
-                Font::BungeeRegular => 1,
-                Font::OverpassBold => 2,
-                Font::OverpassRegular => 3,
-                Font::OverpassSemiBold => 4,
-            })
-            .unwrap()
-            .height(font_size as f64);
+            * self
+                .glyphs
+                .line_height(font, self.scaled_size(font_size))
+                .unwrap_or(30.0);
 
         self.line_height_cache.borrow_mut().insert(key, height);
         height
     }
 
-    // TODO No text in wasm yet
-    #[cfg(target_arch = "wasm32")]
-    pub fn line_height(&self, font: Font, font_size: usize) -> f64 {
-        let key = (font, font_size);
-        if let Some(height) = self.line_height_cache.borrow().get(&key) {
-            return *height;
-        }
+    // Font sizes are given before the scale factor is applied.
+    pub(crate) fn scaled_size(&self, font_size: usize) -> f64 {
+        (font_size as f64) * *self.scale_factor.borrow()
+    }
 
-        text::SCALE_LINE_HEIGHT * 30.0
+    // How wide the text will be when rendered, after translating it.
+    pub(crate) fn text_width(&self, font: Font, font_size: usize, text: &str) -> f64 {
+        self.glyphs
+            .width(font, self.scaled_size(font_size), &self.translate(text))
     }
 
     pub fn get_cached_text(&self, key: &String) -> Option<GeomBatch> {
@@ -95,6 +92,43 @@ impl Assets {
             .insert(key_scale_factor(scale_factor), (geom, bounds));
     }
 
+    // An icon sheet is a JSON object from icon names to SVG or PNG files, relative to the sheet's
+    // directory. Loading several sheets is fine, as long as the names don't collide.
+    pub(crate) fn load_icon_sheet(&self, path: &str) {
+        let sheet = abstutil::maybe_read_json::<BTreeMap<String, String>>(
+            path.to_string(),
+            &mut Timer::throwaway(),
+        )
+        .unwrap_or_else(|err| panic!("Can't load icon sheet {}: {}", path, err));
+        let dir = std::path::Path::new(path)
+            .parent()
+            .and_then(|p| p.to_str())
+            .unwrap_or(".");
+        let mut icons = self.icons.borrow_mut();
+        for (name, file) in sheet {
+            let file = format!("{}/{}", dir, file);
+            let batch = if file.ends_with(".png") {
+                load_png(&file)
+            } else {
+                svg::load_svg_uncached(&file, 1.0).0
+            };
+            if icons.insert(name.clone(), batch).is_some() {
+                panic!(
+                    "Icon {} is defined twice; the last sheet was {}",
+                    name, path
+                );
+            }
+        }
+    }
+
+    pub(crate) fn get_icon(&self, name: &str) -> GeomBatch {
+        self.icons
+            .borrow()
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("No icon named {}. Was the icon sheet loaded?", name))
+    }
+
     pub fn set_scale_factor(&self, scale_factor: f64) {
         *self.scale_factor.borrow_mut() = scale_factor;
         self.text_cache.borrow_mut().clear();
@@ -104,6 +138,86 @@ impl Assets {
     }
 }
 
+impl Assets {
+    // None means English
+    pub fn set_language(&self, lang: Option<String>) {
+        *self.translations.borrow_mut() = match lang {
+            Some(lang) => Translations::load(&lang),
+            None => Translations::english(),
+        };
+        self.text_cache.borrow_mut().clear();
+    }
+
+    pub fn get_language(&self) -> Option<String> {
+        self.translations.borrow().lang()
+    }
+
+    pub fn translate(&self, text: &str) -> String {
+        self.translations.borrow_mut().translate(text)
+    }
+
+    pub fn save_missing_translations(&self) -> Option<String> {
+        self.translations.borrow().save_missing()
+    }
+}
+
 fn key_scale_factor(x: f64) -> usize {
     (x * 100.0) as usize
 }
+
+// There are no textures, so every pixel becomes a colored rectangle. Runs of identical pixels in a
+// row are merged, which keeps flat icons cheap.
+fn load_png(filename: &str) -> GeomBatch {
+    let raw = abstutil::slurp_file(filename).unwrap_or_else(|_| panic!("Can't read {}", filename));
+    let img = image::load_from_memory(&raw)
+        .unwrap_or_else(|err| panic!("{}: {}", filename, err))
+        .to_rgba();
+    let mut batch = GeomBatch::new();
+    for y in 0..img.height() {
+        let mut x = 0;
+        while x < img.width() {
+            let px = img.get_pixel(x, y);
+            let mut len = 1;
+            while x + len < img.width() && img.get_pixel(x + len, y) == px {
+                len += 1;
+            }
+            if px[3] != 0 {
+                batch.push(
+                    Color::rgba(
+                        px[0] as usize,
+                        px[1] as usize,
+                        px[2] as usize,
+                        (px[3] as f32) / 255.0,
+                    ),
+                    Polygon::rectangle(len as f64, 1.0).translate(x as f64, y as f64),
+                );
+            }
+            x += len;
+        }
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_runs_become_rectangles() {
+        // A 4x2 image: the top row is red, red, transparent, blue; the bottom row is all green.
+        let mut img = image::RgbaImage::new(4, 2);
+        img.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, image::Rgba([255, 0, 0, 255]));
+        img.put_pixel(3, 0, image::Rgba([0, 0, 255, 255]));
+        for x in 0..4 {
+            img.put_pixel(x, 1, image::Rgba([0, 255, 0, 255]));
+        }
+        let path = std::env::temp_dir().join("ezgui_png_icon_test.png");
+        img.save(&path).unwrap();
+
+        let batch = load_png(path.to_str().unwrap());
+        assert_eq!(batch.clone().consume().len(), 3);
+        let dims = batch.get_dims();
+        assert_eq!((dims.width, dims.height), (4.0, 2.0));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::drawing::{BlendMode, Instance, Uniforms};
+use crate::{Canvas, Color, FancyColor, ScreenDims, ScreenRectangle};
+use geom::Polygon;
+
+// Exactly one backend is chosen at compile time with a cargo feature. Everything else in ezgui
+// only talks to it through these traits, so every backend has to provide the same things.
+#[cfg(feature = "glium-backend")]
+pub use crate::backend_glium::{Drawable, PrerenderInnards};
+#[cfg(feature = "glow-backend")]
+pub use crate::backend_glow::{Drawable, PrerenderInnards};
+#[cfg(feature = "headless-backend")]
+pub use crate::backend_headless::{Drawable, PrerenderInnards};
+#[cfg(feature = "wasm-backend")]
+pub use crate::backend_wasm::{Drawable, PrerenderInnards};
+#[cfg(feature = "wgpu-backend")]
+pub use crate::backend_wgpu::{Drawable, PrerenderInnards};
+
+pub type GfxCtxInnards<'a> = <PrerenderInnards as DrawFrame<'a>>::GfxCtx;
+
+// Owns the window and GPU context, and uploads geometry.
+pub trait RenderBackend: Sized {
+    type Drawable;
+
+    fn setup(window_title: &str) -> (Self, winit::event_loop::EventLoop<()>, ScreenDims);
+
+    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>)
+        -> Self::Drawable;
+    // Overwrites the buffers in place when the new geometry fits; otherwise they grow.
+    fn update(&self, obj: &Self::Drawable, list: Vec<(FancyColor, &Polygon)>);
+    fn free(&self, obj: Self::Drawable);
+    fn total_bytes_uploaded(&self) -> usize;
+
+    fn request_redraw(&self);
+    fn set_cursor_icon(&self, icon: winit::window::CursorIcon);
+    fn window_resized(&self, width: f64, height: f64);
+    fn get_inner_size(&self) -> (f64, f64);
+    fn set_window_icon(&self, icon: winit::window::Icon);
+    fn monitor_scale_factor(&self) -> f64;
+    // None if this backend can't read back what it drew
+    fn read_front_buffer(&self) -> Option<(u32, u32, Vec<u8>)>;
+}
+
+// Split out of RenderBackend, since some backends borrow themselves for the whole frame.
+pub trait DrawFrame<'a>: RenderBackend {
+    type GfxCtx: GfxCtxBackend<Self>;
+
+    fn draw_new_frame(&'a self) -> Self::GfxCtx;
+}
+
+// Issues the draw calls for one frame.
+pub trait GfxCtxBackend<B: RenderBackend> {
+    // Whatever the backend needs to remember about the current scissor rectangle
+    type Clip;
+
+    fn clear(&mut self, color: Color);
+    fn set_blend_mode(&mut self, mode: BlendMode);
+    fn redraw(&mut self, obj: &B::Drawable, uniforms: &Uniforms, prerender: &B);
+    fn redraw_instances(
+        &mut self,
+        obj: &B::Drawable,
+        instances: &[Instance],
+        uniforms: &Uniforms,
+        prerender: &B,
+    );
+
+    fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas);
+    fn disable_clipping(&mut self, canvas: &Canvas);
+    fn take_clip(&mut self) -> Option<Self::Clip>;
+    fn restore_clip(&mut self, clip: Option<Self::Clip>);
+
+    fn finish(self);
+}
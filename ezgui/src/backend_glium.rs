@@ -1,87 +1,10 @@
-use crate::drawing::Uniforms;
+use crate::backend::{DrawFrame, GfxCtxBackend, RenderBackend};
+use crate::drawing::{vertices_and_indices, BlendMode, Instance, Uniforms};
 use crate::{Canvas, Color, FancyColor, ScreenDims, ScreenRectangle};
 use geom::Polygon;
 use glium::uniforms::UniformValue;
 use glium::Surface;
-use std::cell::Cell;
-
-pub fn setup(
-    window_title: &str,
-) -> (
-    PrerenderInnards,
-    winit::event_loop::EventLoop<()>,
-    ScreenDims,
-) {
-    let event_loop = winit::event_loop::EventLoop::new();
-    let window = winit::window::WindowBuilder::new()
-        .with_title(window_title)
-        .with_maximized(true);
-    // multisampling: 2 looks bad, 4 looks fine
-    let context = glutin::ContextBuilder::new()
-        .with_multisampling(4)
-        .with_depth_buffer(2);
-    let display = glium::Display::new(window, context, &event_loop).unwrap();
-
-    let (vertex_shader, fragment_shader) =
-        if display.is_glsl_version_supported(&glium::Version(glium::Api::Gl, 1, 4)) {
-            (
-                include_str!("shaders/vertex_140.glsl"),
-                include_str!("shaders/fragment_140.glsl"),
-            )
-        } else {
-            panic!(
-                "GLSL 140 not supported. Try {:?} or {:?}",
-                display.get_opengl_version(),
-                display.get_supported_glsl_version()
-            );
-        };
-
-    // To quickly iterate on shaders without recompiling...
-    /*let mut vert = String::new();
-    let mut frag = String::new();
-    let (vertex_shader, fragment_shader) = {
-        use std::io::Read;
-
-        let mut f1 = std::fs::File:: open("../ezgui/src/shaders/vertex_140.glsl").unwrap();
-        f1.read_to_string(&mut vert).unwrap();
-
-        let mut f2 = std::fs::File:: open("../ezgui/src/shaders/fragment_140.glsl").unwrap();
-        f2.read_to_string(&mut frag).unwrap();
-
-        (&vert, &frag)
-    };*/
-
-    let program = glium::Program::new(
-        &display,
-        glium::program::ProgramCreationInput::SourceCode {
-            vertex_shader,
-            tessellation_control_shader: None,
-            tessellation_evaluation_shader: None,
-            geometry_shader: None,
-            fragment_shader,
-            transform_feedback_varyings: None,
-            // Without this, SRGB gets enabled and post-processes the color from the fragment
-            // shader.
-            outputs_srgb: true,
-            uses_point_size: false,
-        },
-    )
-    .unwrap();
-
-    // TODO Should this be display.gl_window().window().inner_size()? I think some resize events
-    // always happen soon after startup, so it probably doesn't matter much. Changing this on Linux
-    // messes up the loading screen size (which happens before resize events get processed).
-    let window_size = event_loop.primary_monitor().size();
-    (
-        PrerenderInnards {
-            display,
-            program,
-            total_bytes_uploaded: Cell::new(0),
-        },
-        event_loop,
-        ScreenDims::new(window_size.width.into(), window_size.height.into()),
-    )
-}
+use std::cell::{Cell, RefCell};
 
 struct InnerUniforms<'a> {
     values: &'a Uniforms,
@@ -91,6 +14,7 @@ impl<'b> glium::uniforms::Uniforms for InnerUniforms<'b> {
     fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
         output("transform", UniformValue::Vec3(self.values.transform));
         output("window", UniformValue::Vec3(self.values.window));
+        output("model", UniformValue::Vec4(self.values.model));
     }
 }
 
@@ -100,19 +24,40 @@ pub struct GfxCtxInnards<'a> {
     params: glium::DrawParameters<'a>,
 }
 
-impl<'a> GfxCtxInnards<'a> {
-    pub fn clear(&mut self, c: Color) {
+impl<'a> GfxCtxBackend<PrerenderInnards> for GfxCtxInnards<'a> {
+    type Clip = glium::Rect;
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.params.blend = match mode {
+            BlendMode::Normal => glium::Blend::alpha_blending(),
+            BlendMode::Additive => {
+                let add = glium::BlendingFunction::Addition {
+                    source: glium::LinearBlendingFactor::SourceAlpha,
+                    destination: glium::LinearBlendingFactor::One,
+                };
+                glium::Blend {
+                    color: add,
+                    alpha: add,
+                    constant_value: (0.0, 0.0, 0.0, 0.0),
+                }
+            }
+        };
+    }
+
+    fn clear(&mut self, c: Color) {
         // Without this, SRGB gets enabled and post-processes the color from the fragment
         // shader.
         self.target
             .clear_color_srgb_and_depth((c.r, c.g, c.b, c.a), 1.0);
     }
 
-    pub fn redraw(&mut self, obj: &Drawable, uniforms: &Uniforms, prerender: &PrerenderInnards) {
+    fn redraw(&mut self, obj: &Drawable, uniforms: &Uniforms, prerender: &PrerenderInnards) {
+        let vertex_buffer = obj.vertex_buffer.borrow();
+        let index_buffer = obj.index_buffer.borrow();
         self.target
             .draw(
-                &obj.vertex_buffer,
-                &obj.index_buffer,
+                &*vertex_buffer,
+                &index_buffer.slice(0..obj.num_indices.get()).unwrap(),
                 &prerender.program,
                 &InnerUniforms { values: uniforms },
                 &self.params,
@@ -120,7 +65,39 @@ impl<'a> GfxCtxInnards<'a> {
             .unwrap();
     }
 
-    pub fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas) {
+    fn redraw_instances(
+        &mut self,
+        obj: &Drawable,
+        instances: &[Instance],
+        uniforms: &Uniforms,
+        prerender: &PrerenderInnards,
+    ) {
+        let attribs: Vec<InstanceAttribs> = instances
+            .iter()
+            .map(|i| {
+                let raw = i.raw();
+                InstanceAttribs {
+                    instance_pos: [raw[0], raw[1]],
+                    instance_angle: raw[2],
+                    instance_color: [raw[3], raw[4], raw[5], raw[6]],
+                }
+            })
+            .collect();
+        let instance_buffer = glium::VertexBuffer::new(&prerender.display, &attribs).unwrap();
+        let vertex_buffer = obj.vertex_buffer.borrow();
+        let index_buffer = obj.index_buffer.borrow();
+        self.target
+            .draw(
+                (&*vertex_buffer, instance_buffer.per_instance().unwrap()),
+                &index_buffer.slice(0..obj.num_indices.get()).unwrap(),
+                &prerender.instanced_program,
+                &InnerUniforms { values: uniforms },
+                &self.params,
+            )
+            .unwrap();
+    }
+
+    fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas) {
         assert!(self.params.scissor.is_none());
         // The scissor rectangle has to be in device coordinates, so you would think some transform
         // by scale factor (previously called HiDPI factor) has to happen here. But actually,
@@ -135,27 +112,30 @@ impl<'a> GfxCtxInnards<'a> {
         });
     }
 
-    pub fn disable_clipping(&mut self, _: &Canvas) {
+    fn disable_clipping(&mut self, _: &Canvas) {
         assert!(self.params.scissor.is_some());
         self.params.scissor = None;
     }
 
-    pub fn take_clip(&mut self) -> Option<glium::Rect> {
+    fn take_clip(&mut self) -> Option<glium::Rect> {
         self.params.scissor.take()
     }
-    pub fn restore_clip(&mut self, clip: Option<glium::Rect>) {
+
+    fn restore_clip(&mut self, clip: Option<glium::Rect>) {
         self.params.scissor = clip;
     }
 
-    pub fn finish(self) {
+    fn finish(self) {
         self.target.finish().unwrap();
     }
 }
 
 // Something that's been sent to the GPU already.
 pub struct Drawable {
-    vertex_buffer: glium::VertexBuffer<Vertex>,
-    index_buffer: glium::IndexBuffer<u32>,
+    // These change with update. The buffers may hold more than num_indices.
+    vertex_buffer: RefCell<glium::VertexBuffer<Vertex>>,
+    index_buffer: RefCell<glium::IndexBuffer<u32>>,
+    num_indices: Cell<usize>,
 }
 
 #[derive(Copy, Clone)]
@@ -169,33 +149,121 @@ struct Vertex {
 
 glium::implement_vertex!(Vertex, position, style);
 
+// See Instance::raw
+#[derive(Copy, Clone)]
+struct InstanceAttribs {
+    instance_pos: [f32; 2],
+    instance_angle: f32,
+    instance_color: [f32; 4],
+}
+
+glium::implement_vertex!(
+    InstanceAttribs,
+    instance_pos,
+    instance_angle,
+    instance_color
+);
+
 pub struct PrerenderInnards {
     display: glium::Display,
     program: glium::Program,
+    instanced_program: glium::Program,
 
     // TODO Prerender doesn't know what things are temporary and permanent. Could make the API more
     // detailed.
-    pub total_bytes_uploaded: Cell<usize>,
+    total_bytes_uploaded: Cell<usize>,
 }
 
-impl PrerenderInnards {
-    pub fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
-        let mut vertices: Vec<Vertex> = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-
-        for (color, poly) in list {
-            let idx_offset = vertices.len();
-            let (pts, raw_indices) = poly.raw_for_rendering();
-            for pt in pts {
-                vertices.push(Vertex {
-                    position: [pt.x() as f32, pt.y() as f32],
-                    style: color.style(*pt),
-                });
-            }
-            for idx in raw_indices {
-                indices.push((idx_offset + *idx) as u32);
-            }
-        }
+impl RenderBackend for PrerenderInnards {
+    type Drawable = Drawable;
+
+    fn setup(
+        window_title: &str,
+    ) -> (
+        PrerenderInnards,
+        winit::event_loop::EventLoop<()>,
+        ScreenDims,
+    ) {
+        let event_loop = winit::event_loop::EventLoop::new();
+        let window = winit::window::WindowBuilder::new()
+            .with_title(window_title)
+            .with_maximized(true);
+        // multisampling: 2 looks bad, 4 looks fine. The depth buffer has to tell apart every map layer
+        // from GfxCtx::set_z.
+        let context = glutin::ContextBuilder::new()
+            .with_multisampling(4)
+            .with_depth_buffer(16);
+        let display = glium::Display::new(window, context, &event_loop).unwrap();
+
+        let (vertex_shader, instanced_vertex_shader, fragment_shader) =
+            if display.is_glsl_version_supported(&glium::Version(glium::Api::Gl, 1, 4)) {
+                (
+                    include_str!("shaders/vertex_140.glsl"),
+                    include_str!("shaders/vertex_instanced_140.glsl"),
+                    include_str!("shaders/fragment_140.glsl"),
+                )
+            } else {
+                panic!(
+                    "GLSL 140 not supported. Try {:?} or {:?}",
+                    display.get_opengl_version(),
+                    display.get_supported_glsl_version()
+                );
+            };
+
+        // To quickly iterate on shaders without recompiling...
+        /*let mut vert = String::new();
+        let mut frag = String::new();
+        let (vertex_shader, fragment_shader) = {
+            use std::io::Read;
+
+            let mut f1 = std::fs::File:: open("../ezgui/src/shaders/vertex_140.glsl").unwrap();
+            f1.read_to_string(&mut vert).unwrap();
+
+            let mut f2 = std::fs::File:: open("../ezgui/src/shaders/fragment_140.glsl").unwrap();
+            f2.read_to_string(&mut frag).unwrap();
+
+            (&vert, &frag)
+        };*/
+
+        let make_program = |vertex_shader| {
+            glium::Program::new(
+                &display,
+                glium::program::ProgramCreationInput::SourceCode {
+                    vertex_shader,
+                    tessellation_control_shader: None,
+                    tessellation_evaluation_shader: None,
+                    geometry_shader: None,
+                    fragment_shader,
+                    transform_feedback_varyings: None,
+                    // Without this, SRGB gets enabled and post-processes the color from the fragment
+                    // shader.
+                    outputs_srgb: true,
+                    uses_point_size: false,
+                },
+            )
+            .unwrap()
+        };
+        let program = make_program(vertex_shader);
+        let instanced_program = make_program(instanced_vertex_shader);
+
+        // TODO Should this be display.gl_window().window().inner_size()? I think some resize events
+        // always happen soon after startup, so it probably doesn't matter much. Changing this on Linux
+        // messes up the loading screen size (which happens before resize events get processed).
+        let window_size = event_loop.primary_monitor().size();
+        (
+            PrerenderInnards {
+                display,
+                program,
+                instanced_program,
+                total_bytes_uploaded: Cell::new(0),
+            },
+            event_loop,
+            ScreenDims::new(window_size.width.into(), window_size.height.into()),
+        )
+    }
+
+    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
+        let (vertices, indices) = glium_vertices_and_indices(list);
 
         let vertex_buffer = if permanent {
             glium::VertexBuffer::immutable(&self.display, &vertices).unwrap()
@@ -227,20 +295,94 @@ impl PrerenderInnards {
         }
 
         Drawable {
-            vertex_buffer,
-            index_buffer,
+            vertex_buffer: RefCell::new(vertex_buffer),
+            index_buffer: RefCell::new(index_buffer),
+            num_indices: Cell::new(indices.len()),
+        }
+    }
+
+    fn update(&self, obj: &Drawable, list: Vec<(FancyColor, &Polygon)>) {
+        let (vertices, indices) = glium_vertices_and_indices(list);
+
+        if vertices.len() <= obj.vertex_buffer.borrow().len() {
+            if !vertices.is_empty() {
+                obj.vertex_buffer
+                    .borrow()
+                    .slice(0..vertices.len())
+                    .unwrap()
+                    .write(&vertices);
+            }
+        } else {
+            *obj.vertex_buffer.borrow_mut() =
+                glium::VertexBuffer::dynamic(&self.display, &vertices).unwrap();
+        }
+
+        if indices.len() <= obj.index_buffer.borrow().len() {
+            if !indices.is_empty() {
+                obj.index_buffer
+                    .borrow()
+                    .slice(0..indices.len())
+                    .unwrap()
+                    .write(&indices);
+            }
+        } else {
+            *obj.index_buffer.borrow_mut() = glium::IndexBuffer::dynamic(
+                &self.display,
+                glium::index::PrimitiveType::TrianglesList,
+                &indices,
+            )
+            .unwrap();
         }
+
+        obj.num_indices.set(indices.len());
     }
 
-    pub fn request_redraw(&self) {
+    // glium frees the buffers itself.
+    // Dropping the buffers frees them
+    fn free(&self, _: Drawable) {}
+
+    fn request_redraw(&self) {
         self.display.gl_window().window().request_redraw();
     }
 
-    pub fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
+    fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
         self.display.gl_window().window().set_cursor_icon(icon);
     }
 
-    pub fn draw_new_frame<'a>(&self) -> GfxCtxInnards<'a> {
+    fn window_resized(&self, _: f64, _: f64) {}
+
+    fn get_inner_size(&self) -> (f64, f64) {
+        let size = self.display.gl_window().window().inner_size();
+        (size.width.into(), size.height.into())
+    }
+
+    fn set_window_icon(&self, icon: winit::window::Icon) {
+        self.display
+            .gl_window()
+            .window()
+            .set_window_icon(Some(icon));
+    }
+
+    fn monitor_scale_factor(&self) -> f64 {
+        self.display.gl_window().window().scale_factor()
+    }
+
+    // After finishing a frame, it's in the front buffer. Returns (width, height, RGBA pixels),
+    // with the bottom row first.
+    fn read_front_buffer(&self) -> Option<(u32, u32, Vec<u8>)> {
+        let img: glium::texture::RawImage2d<u8> = self.display.read_front_buffer().ok()?;
+        Some((img.width, img.height, img.data.into_owned()))
+    }
+
+    fn total_bytes_uploaded(&self) -> usize {
+        self.total_bytes_uploaded.get()
+    }
+}
+
+impl<'a> DrawFrame<'a> for PrerenderInnards {
+    type GfxCtx = GfxCtxInnards<'a>;
+
+    fn draw_new_frame(&'a self) -> GfxCtxInnards<'a> {
         GfxCtxInnards {
             target: self.display.draw(),
             params: glium::DrawParameters {
@@ -254,22 +396,17 @@ impl PrerenderInnards {
             },
         }
     }
+}
 
-    pub fn window_resized(&self, _: f64, _: f64) {}
-
-    pub fn get_inner_size(&self) -> (f64, f64) {
-        let size = self.display.gl_window().window().inner_size();
-        (size.width.into(), size.height.into())
-    }
-
-    pub fn set_window_icon(&self, icon: winit::window::Icon) {
-        self.display
-            .gl_window()
-            .window()
-            .set_window_icon(Some(icon));
-    }
-
-    pub fn monitor_scale_factor(&self) -> f64 {
-        self.display.gl_window().window().scale_factor()
-    }
+// glium wants its own vertex type. See Color::vertex for the layout.
+fn glium_vertices_and_indices(list: Vec<(FancyColor, &Polygon)>) -> (Vec<Vertex>, Vec<u32>) {
+    let (raw, indices) = vertices_and_indices(list);
+    let vertices = raw
+        .into_iter()
+        .map(|v| Vertex {
+            position: [v[0], v[1]],
+            style: [v[2], v[3], v[4], v[5]],
+        })
+        .collect();
+    (vertices, indices)
 }
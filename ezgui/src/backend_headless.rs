@@ -0,0 +1,144 @@
+use crate::backend::{DrawFrame, GfxCtxBackend, RenderBackend};
+use crate::color::FLOATS_PER_VERTEX;
+use crate::drawing::{BlendMode, Instance, Uniforms};
+use crate::{Canvas, Color, FancyColor, ScreenDims, ScreenRectangle};
+use geom::Polygon;
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+// Draws nothing and needs no window or GPU, so rendering code and widget layout can run in tests
+// and CI. Uploading, text, layout, and events all work as usual. There's no event loop to run;
+// drive a GUI with Headless instead.
+
+// Represents one frame that's gonna be drawn
+pub struct GfxCtxInnards<'a> {
+    current_clip: Option<ScreenRectangle>,
+    _lifetime: PhantomData<&'a ()>,
+}
+
+impl<'a> GfxCtxBackend<PrerenderInnards> for GfxCtxInnards<'a> {
+    type Clip = ScreenRectangle;
+
+    fn clear(&mut self, _: Color) {}
+
+    fn set_blend_mode(&mut self, _: BlendMode) {}
+
+    fn redraw(&mut self, _: &Drawable, _: &Uniforms, _: &PrerenderInnards) {}
+
+    fn redraw_instances(
+        &mut self,
+        _: &Drawable,
+        _: &[Instance],
+        _: &Uniforms,
+        _: &PrerenderInnards,
+    ) {
+    }
+
+    fn enable_clipping(&mut self, rect: ScreenRectangle, _: &Canvas) {
+        assert!(self.current_clip.is_none());
+        self.current_clip = Some(rect);
+    }
+
+    fn disable_clipping(&mut self, _: &Canvas) {
+        assert!(self.current_clip.is_some());
+        self.current_clip = None;
+    }
+
+    fn take_clip(&mut self) -> Option<ScreenRectangle> {
+        self.current_clip.take()
+    }
+
+    fn restore_clip(&mut self, clip: Option<ScreenRectangle>) {
+        self.current_clip = clip;
+    }
+
+    fn finish(self) {}
+}
+
+// Nothing's actually uploaded anywhere.
+pub struct Drawable {}
+
+pub struct PrerenderInnards {
+    window_size: Cell<(f64, f64)>,
+
+    // Counted as if the other backends' vertex format was used, so memory budgets behave the same
+    total_bytes_uploaded: Cell<usize>,
+}
+
+impl RenderBackend for PrerenderInnards {
+    type Drawable = Drawable;
+
+    fn setup(
+        _: &str,
+    ) -> (
+        PrerenderInnards,
+        winit::event_loop::EventLoop<()>,
+        ScreenDims,
+    ) {
+        panic!("The headless backend can't open a window; use ezgui::Headless instead");
+    }
+
+    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
+        if permanent {
+            let mut bytes = 0;
+            for (_, poly) in list {
+                let (pts, indices) = poly.raw_for_rendering();
+                // Same as the real backends
+                bytes += 4 * FLOATS_PER_VERTEX * pts.len() + 4 * indices.len();
+            }
+            self.total_bytes_uploaded
+                .set(self.total_bytes_uploaded.get() + bytes);
+        }
+        Drawable {}
+    }
+
+    fn update(&self, _: &Drawable, _: Vec<(FancyColor, &Polygon)>) {}
+
+    fn free(&self, _: Drawable) {}
+
+    fn request_redraw(&self) {}
+
+    fn set_cursor_icon(&self, _: winit::window::CursorIcon) {}
+
+    fn window_resized(&self, width: f64, height: f64) {
+        self.window_size.set((width, height));
+    }
+
+    fn get_inner_size(&self) -> (f64, f64) {
+        self.window_size.get()
+    }
+
+    fn set_window_icon(&self, _: winit::window::Icon) {}
+
+    fn monitor_scale_factor(&self) -> f64 {
+        1.0
+    }
+
+    fn read_front_buffer(&self) -> Option<(u32, u32, Vec<u8>)> {
+        None
+    }
+
+    fn total_bytes_uploaded(&self) -> usize {
+        self.total_bytes_uploaded.get()
+    }
+}
+
+impl<'a> DrawFrame<'a> for PrerenderInnards {
+    type GfxCtx = GfxCtxInnards<'a>;
+
+    fn draw_new_frame(&'a self) -> GfxCtxInnards<'a> {
+        GfxCtxInnards {
+            current_clip: None,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl PrerenderInnards {
+    pub fn new(width: f64, height: f64) -> PrerenderInnards {
+        PrerenderInnards {
+            window_size: Cell::new((width, height)),
+            total_bytes_uploaded: Cell::new(0),
+        }
+    }
+}
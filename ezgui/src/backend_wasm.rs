@@ -1,4 +1,6 @@
-use crate::drawing::Uniforms;
+use crate::backend::{DrawFrame, GfxCtxBackend, RenderBackend};
+use crate::color::FLOATS_PER_VERTEX;
+use crate::drawing::{vertices_and_indices, BlendMode, Instance, Uniforms, FLOATS_PER_INSTANCE};
 use crate::{Canvas, Color, FancyColor, ScreenDims, ScreenRectangle};
 use geom::Polygon;
 use glow::HasContext;
@@ -7,115 +9,23 @@ use stdweb::traits::INode;
 use webgl_stdweb::WebGL2RenderingContext;
 use winit::platform::web::WindowExtStdweb;
 
-pub fn setup(
-    window_title: &str,
-) -> (
-    PrerenderInnards,
-    winit::event_loop::EventLoop<()>,
-    ScreenDims,
-) {
-    stdweb::console!(log, "Setting up ezgui");
-
-    // This doesn't seem to work for the shader panics here, but later it does work. Huh.
-    std::panic::set_hook(Box::new(|info| {
-        stdweb::console!(log, "panicked: %s", format!("{}", info));
-    }));
-
-    let event_loop = winit::event_loop::EventLoop::new();
-    let size = {
-        // TODO Not sure how to get scrollbar dims
-        let scrollbars = 30;
-        let win = stdweb::web::window();
-        winit::dpi::PhysicalSize::new(
-            win.inner_width() - scrollbars,
-            win.inner_height() - scrollbars,
-        )
-    };
-    let window = winit::window::WindowBuilder::new()
-        .with_title(window_title)
-        .with_inner_size(size)
-        .build(&event_loop)
-        .unwrap();
-    let canvas = window.canvas();
-    let document = stdweb::web::document();
-    let body: stdweb::web::Node = document.body().expect("Get HTML body").into();
-    body.append_child(&canvas);
-
-    let webgl2_context: WebGL2RenderingContext = canvas.get_context().unwrap();
-    let gl = glow::Context::from_webgl2_context(webgl2_context);
-
-    let program = unsafe { gl.create_program().expect("Cannot create program") };
-
-    unsafe {
-        let shaders = [
-            (glow::VERTEX_SHADER, include_str!("shaders/vertex_300.glsl")),
-            (
-                glow::FRAGMENT_SHADER,
-                include_str!("shaders/fragment_300.glsl"),
-            ),
-        ]
-        .iter()
-        .map(|(shader_type, source)| {
-            let shader = gl
-                .create_shader(*shader_type)
-                .expect("Cannot create shader");
-            gl.shader_source(shader, source);
-            gl.compile_shader(shader);
-            if !gl.get_shader_compile_status(shader) {
-                stdweb::console!(log, "Shader error: %s", gl.get_shader_info_log(shader));
-                panic!(gl.get_shader_info_log(shader));
-            }
-            gl.attach_shader(program, shader);
-            shader
-        })
-        .collect::<Vec<_>>();
-        gl.link_program(program);
-        if !gl.get_program_link_status(program) {
-            stdweb::console!(log, "Linking error: %s", gl.get_program_info_log(program));
-            panic!(gl.get_program_info_log(program));
-        }
-        for shader in shaders {
-            gl.detach_shader(program, shader);
-            gl.delete_shader(shader);
-        }
-        gl.use_program(Some(program));
-
-        gl.enable(glow::SCISSOR_TEST);
-
-        gl.enable(glow::DEPTH_TEST);
-        gl.depth_func(glow::LEQUAL);
-
-        gl.enable(glow::BLEND);
-        gl.blend_func_separate(
-            glow::SRC_ALPHA,
-            glow::ONE_MINUS_SRC_ALPHA,
-            glow::SRC_ALPHA,
-            glow::ONE_MINUS_SRC_ALPHA,
-        );
-    }
-
-    (
-        PrerenderInnards {
-            gl,
-            program,
-            window,
-            total_bytes_uploaded: Cell::new(0),
-        },
-        event_loop,
-        ScreenDims::new(canvas.width().into(), canvas.height().into()),
-    )
-}
-
 // Represents one frame that's gonna be drawn
 pub struct GfxCtxInnards<'a> {
     gl: &'a glow::Context,
     program: &'a <glow::Context as glow::HasContext>::Program,
+    instanced_program: &'a <glow::Context as glow::HasContext>::Program,
 
     current_clip: Option<[i32; 4]>,
 }
 
-impl<'a> GfxCtxInnards<'a> {
-    pub fn clear(&mut self, color: Color) {
+impl<'a> GfxCtxBackend<PrerenderInnards> for GfxCtxInnards<'a> {
+    type Clip = [i32; 4];
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        set_blend_mode(self.gl, mode);
+    }
+
+    fn clear(&mut self, color: Color) {
         unsafe {
             self.gl.clear_color(color.r, color.g, color.b, color.a);
             self.gl.clear(glow::COLOR_BUFFER_BIT);
@@ -125,29 +35,76 @@ impl<'a> GfxCtxInnards<'a> {
         }
     }
 
-    pub fn redraw(&mut self, obj: &Drawable, uniforms: &Uniforms, _: &PrerenderInnards) {
+    fn redraw(&mut self, obj: &Drawable, uniforms: &Uniforms, _: &PrerenderInnards) {
         unsafe {
-            let transform_loc = self
-                .gl
-                .get_uniform_location(*self.program, "transform")
-                .unwrap();
-            self.gl
-                .uniform_3_f32_slice(Some(transform_loc), &uniforms.transform);
-            let window_loc = self
-                .gl
-                .get_uniform_location(*self.program, "window")
-                .unwrap();
-            self.gl
-                .uniform_3_f32_slice(Some(window_loc), &uniforms.window);
+            set_uniforms(self.gl, *self.program, uniforms);
 
             self.gl.bind_vertex_array(Some(obj.vert_array));
+            self.gl.draw_elements(
+                glow::TRIANGLES,
+                obj.num_indices.get(),
+                glow::UNSIGNED_INT,
+                0,
+            );
+            self.gl.bind_vertex_array(None);
+        }
+    }
+
+    fn redraw_instances(
+        &mut self,
+        obj: &Drawable,
+        instances: &[Instance],
+        uniforms: &Uniforms,
+        _: &PrerenderInnards,
+    ) {
+        let raw: Vec<[f32; FLOATS_PER_INSTANCE]> = instances.iter().map(|i| i.raw()).collect();
+        unsafe {
+            self.gl.use_program(Some(*self.instanced_program));
+            set_uniforms(self.gl, *self.instanced_program, uniforms);
+
+            self.gl.bind_vertex_array(Some(obj.vert_array));
+            let instance_buffer = self.gl.create_buffer().unwrap();
             self.gl
-                .draw_elements(glow::TRIANGLES, obj.num_indices, glow::UNSIGNED_INT, 0);
+                .bind_buffer(glow::ARRAY_BUFFER, Some(instance_buffer));
+            self.gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                &raw.align_to::<u8>().1,
+                glow::STREAM_DRAW,
+            );
+            let stride = (FLOATS_PER_INSTANCE * std::mem::size_of::<f32>()) as i32;
+            // instance_pos is vec2, instance_angle is float, instance_color is vec4
+            for (idx, size, offset) in &[(2, 2, 0), (3, 1, 2), (4, 4, 3)] {
+                self.gl.enable_vertex_attrib_array(*idx);
+                self.gl.vertex_attrib_pointer_f32(
+                    *idx,
+                    *size,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset * std::mem::size_of::<f32>() as i32,
+                );
+                self.gl.vertex_attrib_divisor(*idx, 1);
+            }
+            self.gl.draw_elements_instanced(
+                glow::TRIANGLES,
+                obj.num_indices.get(),
+                glow::UNSIGNED_INT,
+                0,
+                instances.len() as i32,
+            );
+
+            // Leave the Drawable's vertex array like it was
+            for idx in 2..5 {
+                self.gl.disable_vertex_attrib_array(idx);
+            }
             self.gl.bind_vertex_array(None);
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            self.gl.delete_buffer(instance_buffer);
+            self.gl.use_program(Some(*self.program));
         }
     }
 
-    pub fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas) {
+    fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas) {
         assert!(self.current_clip.is_none());
         // The scissor rectangle has to be in device coordinates, so you would think some transform
         // by scale factor (previously called HiDPI factor) has to happen here. But actually,
@@ -164,7 +121,7 @@ impl<'a> GfxCtxInnards<'a> {
         self.current_clip = Some([left, bottom, width, height]);
     }
 
-    pub fn disable_clipping(&mut self, canvas: &Canvas) {
+    fn disable_clipping(&mut self, canvas: &Canvas) {
         assert!(self.current_clip.is_some());
         self.current_clip = None;
         unsafe {
@@ -177,10 +134,11 @@ impl<'a> GfxCtxInnards<'a> {
         }
     }
 
-    pub fn take_clip(&mut self) -> Option<[i32; 4]> {
+    fn take_clip(&mut self) -> Option<[i32; 4]> {
         self.current_clip.take()
     }
-    pub fn restore_clip(&mut self, clip: Option<[i32; 4]>) {
+
+    fn restore_clip(&mut self, clip: Option<[i32; 4]>) {
         self.current_clip = clip;
         if let Some(c) = clip {
             unsafe {
@@ -189,52 +147,115 @@ impl<'a> GfxCtxInnards<'a> {
         }
     }
 
-    pub fn finish(self) {}
+    fn finish(self) {}
 }
 
 // Something that's been sent to the GPU already.
 // TODO Implement Drop; have to keep a reference to gl.
 pub struct Drawable {
-    _vert_buffer: glow::WebBufferKey,
+    vert_buffer: glow::WebBufferKey,
     vert_array: glow::WebVertexArrayKey,
-    _elem_buffer: glow::WebBufferKey,
-    num_indices: i32,
+    elem_buffer: glow::WebBufferKey,
+    // These change with update. The capacities are how many floats and indices the buffers hold.
+    num_indices: Cell<i32>,
+    vert_capacity: Cell<usize>,
+    elem_capacity: Cell<usize>,
 }
 
 pub struct PrerenderInnards {
     gl: glow::Context,
     window: winit::window::Window,
     program: <glow::Context as glow::HasContext>::Program,
+    instanced_program: <glow::Context as glow::HasContext>::Program,
 
     // TODO Prerender doesn't know what things are temporary and permanent. Could make the API more
     // detailed.
-    pub total_bytes_uploaded: Cell<usize>,
+    total_bytes_uploaded: Cell<usize>,
 }
 
-impl PrerenderInnards {
-    pub fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
-        let mut vertices: Vec<[f32; 6]> = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-
-        for (color, poly) in list {
-            let idx_offset = vertices.len();
-            let (pts, raw_indices) = poly.raw_for_rendering();
-            for pt in pts {
-                let style = color.style(*pt);
-                vertices.push([
-                    pt.x() as f32,
-                    pt.y() as f32,
-                    style[0],
-                    style[1],
-                    style[2],
-                    style[3],
-                ]);
-            }
-            for idx in raw_indices {
-                indices.push((idx_offset + *idx) as u32);
-            }
+impl RenderBackend for PrerenderInnards {
+    type Drawable = Drawable;
+
+    fn setup(
+        window_title: &str,
+    ) -> (
+        PrerenderInnards,
+        winit::event_loop::EventLoop<()>,
+        ScreenDims,
+    ) {
+        stdweb::console!(log, "Setting up ezgui");
+
+        // This doesn't seem to work for the shader panics here, but later it does work. Huh.
+        std::panic::set_hook(Box::new(|info| {
+            stdweb::console!(log, "panicked: %s", format!("{}", info));
+        }));
+
+        let event_loop = winit::event_loop::EventLoop::new();
+        let size = {
+            // TODO Not sure how to get scrollbar dims
+            let scrollbars = 30;
+            let win = stdweb::web::window();
+            winit::dpi::PhysicalSize::new(
+                win.inner_width() - scrollbars,
+                win.inner_height() - scrollbars,
+            )
+        };
+        let window = winit::window::WindowBuilder::new()
+            .with_title(window_title)
+            .with_inner_size(size)
+            .build(&event_loop)
+            .unwrap();
+        let canvas = window.canvas();
+        let document = stdweb::web::document();
+        let body: stdweb::web::Node = document.body().expect("Get HTML body").into();
+        body.append_child(&canvas);
+
+        let webgl2_context: WebGL2RenderingContext = canvas.get_context().unwrap();
+        let gl = glow::Context::from_webgl2_context(webgl2_context);
+
+        let (program, instanced_program) = unsafe {
+            (
+                make_program(
+                    &gl,
+                    include_str!("shaders/vertex_300.glsl"),
+                    include_str!("shaders/fragment_300.glsl"),
+                ),
+                make_program(
+                    &gl,
+                    include_str!("shaders/vertex_instanced_300.glsl"),
+                    include_str!("shaders/fragment_300.glsl"),
+                ),
+            )
+        };
+
+        unsafe {
+            gl.use_program(Some(program));
+
+            gl.enable(glow::SCISSOR_TEST);
+
+            gl.enable(glow::DEPTH_TEST);
+            gl.depth_func(glow::LEQUAL);
+
+            gl.enable(glow::BLEND);
+            set_blend_mode(&gl, BlendMode::Normal);
         }
 
+        (
+            PrerenderInnards {
+                gl,
+                program,
+                instanced_program,
+                window,
+                total_bytes_uploaded: Cell::new(0),
+            },
+            event_loop,
+            ScreenDims::new(canvas.width().into(), canvas.height().into()),
+        )
+    }
+
+    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
+        let (vertices, indices) = vertices_and_indices(list);
+
         let (vert_buffer, vert_array, elem_buffer) = unsafe {
             let vert_array = self.gl.create_vertex_array().unwrap();
             let vert_buffer = self.gl.create_buffer().unwrap();
@@ -260,7 +281,7 @@ impl PrerenderInnards {
 
             // TODO Can we have a single vertex array for everything, since there's an uber shader?
 
-            let stride = 6 * std::mem::size_of::<f32>() as i32;
+            let stride = (FLOATS_PER_VERTEX * std::mem::size_of::<f32>()) as i32;
             // position is vec2
             self.gl.enable_vertex_attrib_array(0);
             self.gl
@@ -294,30 +315,72 @@ impl PrerenderInnards {
         }
 
         Drawable {
-            _vert_buffer: vert_buffer,
+            vert_buffer,
             vert_array,
-            _elem_buffer: elem_buffer,
-            num_indices,
+            elem_buffer,
+            num_indices: Cell::new(num_indices),
+            vert_capacity: Cell::new(vertices.len() * FLOATS_PER_VERTEX),
+            elem_capacity: Cell::new(indices.len()),
         }
     }
 
-    pub fn request_redraw(&self) {
-        self.window.request_redraw();
-    }
+    fn update(&self, obj: &Drawable, list: Vec<(FancyColor, &Polygon)>) {
+        let (vertices, indices) = vertices_and_indices(list);
+        unsafe {
+            // The element buffer is part of the vertex array's state
+            self.gl.bind_vertex_array(Some(obj.vert_array));
 
-    pub fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
-        self.window.set_cursor_icon(icon);
+            self.gl
+                .bind_buffer(glow::ARRAY_BUFFER, Some(obj.vert_buffer));
+            let vertex_bytes = vertices.align_to::<u8>().1;
+            if vertices.len() * FLOATS_PER_VERTEX <= obj.vert_capacity.get() {
+                self.gl
+                    .buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, vertex_bytes);
+            } else {
+                // Growing the same buffer keeps the vertex array pointing at it
+                self.gl
+                    .buffer_data_u8_slice(glow::ARRAY_BUFFER, vertex_bytes, glow::DYNAMIC_DRAW);
+                obj.vert_capacity.set(vertices.len() * FLOATS_PER_VERTEX);
+            }
+
+            self.gl
+                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(obj.elem_buffer));
+            let index_bytes = indices.align_to::<u8>().1;
+            if indices.len() <= obj.elem_capacity.get() {
+                self.gl
+                    .buffer_sub_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, 0, index_bytes);
+            } else {
+                self.gl.buffer_data_u8_slice(
+                    glow::ELEMENT_ARRAY_BUFFER,
+                    index_bytes,
+                    glow::DYNAMIC_DRAW,
+                );
+                obj.elem_capacity.set(indices.len());
+            }
+
+            self.gl.bind_vertex_array(None);
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+        obj.num_indices.set(indices.len() as i32);
     }
 
-    pub fn draw_new_frame(&self) -> GfxCtxInnards {
-        GfxCtxInnards {
-            gl: &self.gl,
-            program: &self.program,
-            current_clip: None,
+    fn free(&self, obj: Drawable) {
+        unsafe {
+            self.gl.delete_vertex_array(obj.vert_array);
+            self.gl.delete_buffer(obj.vert_buffer);
+            self.gl.delete_buffer(obj.elem_buffer);
         }
     }
 
-    pub fn window_resized(&self, width: f64, height: f64) {
+    fn request_redraw(&self) {
+        self.window.request_redraw();
+    }
+
+    fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
+        self.window.set_cursor_icon(icon);
+    }
+
+    fn window_resized(&self, width: f64, height: f64) {
         unsafe {
             self.gl.viewport(0, 0, width as i32, height as i32);
             // I think it's safe to assume there's not a clip right now.
@@ -325,16 +388,100 @@ impl PrerenderInnards {
         }
     }
 
-    pub fn get_inner_size(&self) -> (f64, f64) {
+    fn get_inner_size(&self) -> (f64, f64) {
         let size = self.window.inner_size();
         (size.width.into(), size.height.into())
     }
 
-    pub fn set_window_icon(&self, icon: winit::window::Icon) {
+    fn set_window_icon(&self, icon: winit::window::Icon) {
         self.window.set_window_icon(Some(icon));
     }
 
-    pub fn monitor_scale_factor(&self) -> f64 {
+    fn monitor_scale_factor(&self) -> f64 {
         self.window.scale_factor()
     }
+
+    // TODO Not implemented yet
+    fn read_front_buffer(&self) -> Option<(u32, u32, Vec<u8>)> {
+        None
+    }
+
+    fn total_bytes_uploaded(&self) -> usize {
+        self.total_bytes_uploaded.get()
+    }
+}
+
+impl<'a> DrawFrame<'a> for PrerenderInnards {
+    type GfxCtx = GfxCtxInnards<'a>;
+
+    fn draw_new_frame(&'a self) -> GfxCtxInnards<'a> {
+        GfxCtxInnards {
+            gl: &self.gl,
+            program: &self.program,
+            instanced_program: &self.instanced_program,
+            current_clip: None,
+        }
+    }
+}
+
+unsafe fn make_program(
+    gl: &glow::Context,
+    vertex_shader: &str,
+    fragment_shader: &str,
+) -> <glow::Context as glow::HasContext>::Program {
+    let program = gl.create_program().expect("Cannot create program");
+    let shaders = [
+        (glow::VERTEX_SHADER, vertex_shader),
+        (glow::FRAGMENT_SHADER, fragment_shader),
+    ]
+    .iter()
+    .map(|(shader_type, source)| {
+        let shader = gl
+            .create_shader(*shader_type)
+            .expect("Cannot create shader");
+        gl.shader_source(shader, source);
+        gl.compile_shader(shader);
+        if !gl.get_shader_compile_status(shader) {
+            stdweb::console!(log, "Shader error: %s", gl.get_shader_info_log(shader));
+            panic!(gl.get_shader_info_log(shader));
+        }
+        gl.attach_shader(program, shader);
+        shader
+    })
+    .collect::<Vec<_>>();
+    gl.link_program(program);
+    if !gl.get_program_link_status(program) {
+        stdweb::console!(log, "Linking error: %s", gl.get_program_info_log(program));
+        panic!(gl.get_program_info_log(program));
+    }
+    for shader in shaders {
+        gl.detach_shader(program, shader);
+        gl.delete_shader(shader);
+    }
+    program
+}
+
+unsafe fn set_uniforms(
+    gl: &glow::Context,
+    program: <glow::Context as glow::HasContext>::Program,
+    uniforms: &Uniforms,
+) {
+    let transform_loc = gl.get_uniform_location(program, "transform").unwrap();
+    gl.uniform_3_f32_slice(Some(transform_loc), &uniforms.transform);
+    let window_loc = gl.get_uniform_location(program, "window").unwrap();
+    gl.uniform_3_f32_slice(Some(window_loc), &uniforms.window);
+    // The instanced shader doesn't use this
+    if let Some(model_loc) = gl.get_uniform_location(program, "model") {
+        gl.uniform_4_f32_slice(Some(model_loc), &uniforms.model);
+    }
+}
+
+fn set_blend_mode(gl: &glow::Context, mode: BlendMode) {
+    let dst = match mode {
+        BlendMode::Normal => glow::ONE_MINUS_SRC_ALPHA,
+        BlendMode::Additive => glow::ONE,
+    };
+    unsafe {
+        gl.blend_func_separate(glow::SRC_ALPHA, dst, glow::SRC_ALPHA, dst);
+    }
 }
@@ -0,0 +1,663 @@
+use crate::backend::{DrawFrame, GfxCtxBackend, RenderBackend};
+use crate::color::FLOATS_PER_VERTEX;
+use crate::drawing::{vertices_and_indices, BlendMode, Instance, Uniforms, FLOATS_PER_INSTANCE};
+use crate::{Canvas, Color, FancyColor, ScreenDims, ScreenRectangle};
+use geom::Polygon;
+use std::cell::{Cell, RefCell};
+use std::io::Read;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+
+// Two vec3's, each padded out to a vec4 by the std140 layout rules, then the model vec4
+const UNIFORM_SIZE: u64 = 48;
+// Every draw call gets its own Uniforms at this offset in one buffer
+const UNIFORM_STRIDE: u64 = wgpu::BIND_BUFFER_ALIGNMENT;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+fn compile_shader(
+    device: &wgpu::Device,
+    source: &str,
+    shader_type: glsl_to_spirv::ShaderType,
+) -> wgpu::ShaderModule {
+    let mut spirv = glsl_to_spirv::compile(source, shader_type).unwrap();
+    let mut bytes = Vec::new();
+    spirv.read_to_end(&mut bytes).unwrap();
+    device.create_shader_module(wgpu::util::make_spirv(&bytes))
+}
+
+fn make_depth_view(
+    device: &wgpu::Device,
+    sc_desc: &wgpu::SwapChainDescriptor,
+) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: sc_desc.width,
+                height: sc_desc.height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Represents one frame that's gonna be drawn. Unlike OpenGL, nothing happens immediately; all of
+// the draw calls are recorded, then submitted at the end.
+pub struct GfxCtxInnards<'a> {
+    prerender: &'a PrerenderInnards,
+    // Each clear starts a new render pass
+    passes: Vec<Pass>,
+    uniforms: Vec<[f32; 12]>,
+
+    current_clip: Option<[u32; 4]>,
+    current_blend: BlendMode,
+}
+
+struct Pass {
+    clear: Option<Color>,
+    draws: Vec<Draw>,
+}
+
+struct Draw {
+    vertex_buffer: Rc<wgpu::Buffer>,
+    index_buffer: Rc<wgpu::Buffer>,
+    num_indices: u32,
+    // Index into uniforms
+    uniforms: usize,
+    clip: Option<[u32; 4]>,
+    blend: BlendMode,
+    // The per-instance buffer and number of instances, if this draws many copies
+    instances: Option<(wgpu::Buffer, u32)>,
+}
+
+impl<'a> GfxCtxBackend<PrerenderInnards> for GfxCtxInnards<'a> {
+    type Clip = [u32; 4];
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.current_blend = mode;
+    }
+
+    fn clear(&mut self, color: Color) {
+        let pass = self.passes.last_mut().unwrap();
+        if pass.draws.is_empty() {
+            pass.clear = Some(color);
+        } else {
+            self.passes.push(Pass {
+                clear: Some(color),
+                draws: Vec::new(),
+            });
+        }
+    }
+
+    fn redraw(&mut self, obj: &Drawable, uniforms: &Uniforms, _: &PrerenderInnards) {
+        if obj.num_indices.get() == 0 {
+            return;
+        }
+        self.push_draw(obj, uniforms, None);
+    }
+
+    fn redraw_instances(
+        &mut self,
+        obj: &Drawable,
+        instances: &[Instance],
+        uniforms: &Uniforms,
+        _: &PrerenderInnards,
+    ) {
+        if obj.num_indices.get() == 0 {
+            return;
+        }
+        let raw: Vec<[f32; FLOATS_PER_INSTANCE]> = instances.iter().map(|i| i.raw()).collect();
+        let instance_buffer =
+            self.prerender
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: unsafe { raw.align_to::<u8>().1 },
+                    usage: wgpu::BufferUsage::VERTEX,
+                });
+        self.push_draw(
+            obj,
+            uniforms,
+            Some((instance_buffer, instances.len() as u32)),
+        );
+    }
+
+    fn enable_clipping(&mut self, rect: ScreenRectangle, canvas: &Canvas) {
+        assert!(self.current_clip.is_none());
+        // Like the glow backend, the rectangle is already in device coordinates. The scissor
+        // rectangle starts at the top-left, so no need to flip y.
+        let x1 = rect.x1.max(0.0).min(canvas.window_width);
+        let y1 = rect.y1.max(0.0).min(canvas.window_height);
+        let x2 = rect.x2.max(x1).min(canvas.window_width);
+        let y2 = rect.y2.max(y1).min(canvas.window_height);
+        self.current_clip = Some([x1 as u32, y1 as u32, (x2 - x1) as u32, (y2 - y1) as u32]);
+    }
+
+    fn disable_clipping(&mut self, _: &Canvas) {
+        assert!(self.current_clip.is_some());
+        self.current_clip = None;
+    }
+
+    fn take_clip(&mut self) -> Option<[u32; 4]> {
+        self.current_clip.take()
+    }
+
+    fn restore_clip(&mut self, clip: Option<[u32; 4]>) {
+        self.current_clip = clip;
+    }
+
+    fn finish(self) {
+        let p = self.prerender;
+
+        let mut bytes: Vec<u8> = Vec::new();
+        for raw in &self.uniforms {
+            for x in raw {
+                bytes.extend_from_slice(&x.to_ne_bytes());
+            }
+            bytes.resize(bytes.len() + (UNIFORM_STRIDE - UNIFORM_SIZE) as usize, 0);
+        }
+        if bytes.is_empty() {
+            bytes.resize(UNIFORM_STRIDE as usize, 0);
+        }
+        let uniform_buffer = p
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &bytes,
+                usage: wgpu::BufferUsage::UNIFORM,
+            });
+        let bind_group = p.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &p.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(uniform_buffer.slice(0..UNIFORM_SIZE)),
+            }],
+        });
+
+        let frame = match p.swap_chain.borrow_mut().get_current_frame() {
+            Ok(frame) => frame,
+            Err(err) => {
+                println!("Skipping a frame: {:?}", err);
+                return;
+            }
+        };
+        let depth_view = p.depth_view.borrow();
+        let (width, height) = {
+            let sc_desc = p.sc_desc.borrow();
+            (sc_desc.width, sc_desc.height)
+        };
+
+        let mut encoder = p
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        for pass in &self.passes {
+            let (color_load, depth_load) = match pass.clear {
+                Some(c) => (
+                    wgpu::LoadOp::Clear(wgpu::Color {
+                        r: c.r as f64,
+                        g: c.g as f64,
+                        b: c.b as f64,
+                        a: c.a as f64,
+                    }),
+                    wgpu::LoadOp::Clear(1.0),
+                ),
+                None => (wgpu::LoadOp::Load, wgpu::LoadOp::Load),
+            };
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &frame.output.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: color_load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                    attachment: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: depth_load,
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            let mut pipeline = None;
+            for draw in &pass.draws {
+                let instanced = draw.instances.is_some();
+                if pipeline != Some((draw.blend, instanced)) {
+                    pipeline = Some((draw.blend, instanced));
+                    if instanced {
+                        rpass.set_pipeline(&p.instanced_pipelines[draw.blend as usize]);
+                    } else {
+                        rpass.set_pipeline(&p.pipelines[draw.blend as usize]);
+                    }
+                }
+                let [x, y, w, h] = draw.clip.unwrap_or([0, 0, width, height]);
+                // The window might've been resized since clipping started
+                let x = x.min(width);
+                let y = y.min(height);
+                rpass.set_scissor_rect(x, y, w.min(width - x), h.min(height - y));
+                rpass.set_bind_group(
+                    0,
+                    &bind_group,
+                    &[(draw.uniforms as u64 * UNIFORM_STRIDE) as u32],
+                );
+                rpass.set_vertex_buffer(0, draw.vertex_buffer.slice(..));
+                rpass.set_index_buffer(draw.index_buffer.slice(..));
+                if let Some((ref instance_buffer, num_instances)) = draw.instances {
+                    rpass.set_vertex_buffer(1, instance_buffer.slice(..));
+                    rpass.draw_indexed(0..draw.num_indices, 0, 0..num_instances);
+                } else {
+                    rpass.draw_indexed(0..draw.num_indices, 0, 0..1);
+                }
+            }
+        }
+        p.queue.submit(Some(encoder.finish()));
+        // The frame is presented when it's dropped
+    }
+}
+
+impl<'a> GfxCtxInnards<'a> {
+    fn push_draw(
+        &mut self,
+        obj: &Drawable,
+        uniforms: &Uniforms,
+        instances: Option<(wgpu::Buffer, u32)>,
+    ) {
+        let t = uniforms.transform;
+        let w = uniforms.window;
+        let m = uniforms.model;
+        let raw = [
+            t[0], t[1], t[2], 0.0, w[0], w[1], w[2], 0.0, m[0], m[1], m[2], m[3],
+        ];
+        if self.uniforms.last() != Some(&raw) {
+            self.uniforms.push(raw);
+        }
+        let draw = Draw {
+            vertex_buffer: obj.vertex_buffer.borrow().clone(),
+            index_buffer: obj.index_buffer.borrow().clone(),
+            num_indices: obj.num_indices.get(),
+            uniforms: self.uniforms.len() - 1,
+            clip: self.current_clip,
+            blend: self.current_blend,
+            instances,
+        };
+        self.passes.last_mut().unwrap().draws.push(draw);
+    }
+}
+
+// Something that's been sent to the GPU already.
+pub struct Drawable {
+    // Shared with frames that are still being recorded. These change with update; the buffers may
+    // hold more than num_indices.
+    vertex_buffer: RefCell<Rc<wgpu::Buffer>>,
+    index_buffer: RefCell<Rc<wgpu::Buffer>>,
+    num_indices: Cell<u32>,
+    vertex_bytes: Cell<usize>,
+    index_bytes: Cell<usize>,
+}
+
+pub struct PrerenderInnards {
+    window: winit::window::Window,
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipelines: Vec<wgpu::RenderPipeline>,
+    instanced_pipelines: Vec<wgpu::RenderPipeline>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // These all change when the window is resized
+    sc_desc: RefCell<wgpu::SwapChainDescriptor>,
+    swap_chain: RefCell<wgpu::SwapChain>,
+    depth_view: RefCell<wgpu::TextureView>,
+
+    // TODO Prerender doesn't know what things are temporary and permanent. Could make the API more
+    // detailed.
+    total_bytes_uploaded: Cell<usize>,
+}
+
+impl RenderBackend for PrerenderInnards {
+    type Drawable = Drawable;
+
+    fn setup(
+        window_title: &str,
+    ) -> (
+        PrerenderInnards,
+        winit::event_loop::EventLoop<()>,
+        ScreenDims,
+    ) {
+        let event_loop = winit::event_loop::EventLoop::new();
+        let window = winit::window::WindowBuilder::new()
+            .with_title(window_title)
+            .with_maximized(true)
+            .build(&event_loop)
+            .unwrap();
+
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let surface = unsafe { instance.create_surface(&window) };
+        let adapter =
+            futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::Default,
+                compatible_surface: Some(&surface),
+            }))
+            .expect("No graphics adapter supports this window");
+        let (device, queue) = futures::executor::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+                shader_validation: true,
+            },
+            None,
+        ))
+        .unwrap();
+
+        let vertex_shader = compile_shader(
+            &device,
+            include_str!("shaders/vertex_450.glsl"),
+            glsl_to_spirv::ShaderType::Vertex,
+        );
+        let instanced_vertex_shader = compile_shader(
+            &device,
+            include_str!("shaders/vertex_instanced_450.glsl"),
+            glsl_to_spirv::ShaderType::Vertex,
+        );
+        let fragment_shader = compile_shader(
+            &device,
+            include_str!("shaders/fragment_450.glsl"),
+            glsl_to_spirv::ShaderType::Fragment,
+        );
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: true,
+                    min_binding_size: wgpu::BufferSize::new(UNIFORM_SIZE),
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        // Like the glium backend's outputs_srgb, don't post-process the colors from the shader.
+        let format = wgpu::TextureFormat::Bgra8Unorm;
+        let vertex_buffers = [
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<[f32; FLOATS_PER_VERTEX]>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                // position is vec2, style is vec4
+                attributes: &wgpu::vertex_attr_array![0 => Float2, 1 => Float4],
+            },
+            // Only used by the instanced pipelines
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<[f32; FLOATS_PER_INSTANCE]>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                // instance_pos is vec2, instance_angle is float, instance_color is vec4
+                attributes: &wgpu::vertex_attr_array![
+                    2 => Float2,
+                    3 => Float,
+                    4 => Float4
+                ],
+            },
+        ];
+        // TODO The OpenGL backends use 4x multisampling.
+        let make_pipeline = |instanced: bool, dst_factor| {
+            let blend = wgpu::BlendDescriptor {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            };
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex_stage: wgpu::ProgrammableStageDescriptor {
+                    module: if instanced {
+                        &instanced_vertex_shader
+                    } else {
+                        &vertex_shader
+                    },
+                    entry_point: "main",
+                },
+                fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                    module: &fragment_shader,
+                    entry_point: "main",
+                }),
+                rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: wgpu::CullMode::None,
+                    ..Default::default()
+                }),
+                primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+                color_states: &[wgpu::ColorStateDescriptor {
+                    format,
+                    color_blend: blend.clone(),
+                    alpha_blend: blend,
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+                depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    stencil: wgpu::StencilStateDescriptor::default(),
+                }),
+                vertex_state: wgpu::VertexStateDescriptor {
+                    index_format: wgpu::IndexFormat::Uint32,
+                    vertex_buffers: if instanced {
+                        &vertex_buffers
+                    } else {
+                        &vertex_buffers[..1]
+                    },
+                },
+                sample_count: 1,
+                sample_mask: !0,
+                alpha_to_coverage_enabled: false,
+            })
+        };
+        // Blend pipelines are indexed the same as BlendMode
+        let pipelines = vec![
+            make_pipeline(false, wgpu::BlendFactor::OneMinusSrcAlpha),
+            make_pipeline(false, wgpu::BlendFactor::One),
+        ];
+        let instanced_pipelines = vec![
+            make_pipeline(true, wgpu::BlendFactor::OneMinusSrcAlpha),
+            make_pipeline(true, wgpu::BlendFactor::One),
+        ];
+
+        let size = window.inner_size();
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+        let depth_view = make_depth_view(&device, &sc_desc);
+
+        let window_size = event_loop.primary_monitor().size();
+        (
+            PrerenderInnards {
+                window,
+                surface,
+                device,
+                queue,
+                pipelines,
+                instanced_pipelines,
+                bind_group_layout,
+                sc_desc: RefCell::new(sc_desc),
+                swap_chain: RefCell::new(swap_chain),
+                depth_view: RefCell::new(depth_view),
+                total_bytes_uploaded: Cell::new(0),
+            },
+            event_loop,
+            ScreenDims::new(window_size.width.into(), window_size.height.into()),
+        )
+    }
+
+    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
+        let (mut vertices, mut indices) = vertices_and_indices(list);
+
+        let num_indices = indices.len() as u32;
+        // Empty buffers aren't allowed, but nothing gets drawn anyway.
+        if vertices.is_empty() || indices.is_empty() {
+            vertices = vec![[0.0; FLOATS_PER_VERTEX]];
+            indices = vec![0];
+        }
+        let (vertex_bytes, index_bytes) =
+            unsafe { (vertices.align_to::<u8>().1, indices.align_to::<u8>().1) };
+
+        let vertex_buffer = self.create_buffer(vertex_bytes, wgpu::BufferUsage::VERTEX);
+        let index_buffer = self.create_buffer(index_bytes, wgpu::BufferUsage::INDEX);
+
+        if permanent {
+            self.total_bytes_uploaded
+                .set(self.total_bytes_uploaded.get() + vertex_bytes.len() + index_bytes.len());
+        }
+
+        Drawable {
+            vertex_buffer: RefCell::new(Rc::new(vertex_buffer)),
+            index_buffer: RefCell::new(Rc::new(index_buffer)),
+            num_indices: Cell::new(num_indices),
+            vertex_bytes: Cell::new(vertex_bytes.len()),
+            index_bytes: Cell::new(index_bytes.len()),
+        }
+    }
+
+    fn update(&self, obj: &Drawable, list: Vec<(FancyColor, &Polygon)>) {
+        let (vertices, indices) = vertices_and_indices(list);
+        obj.num_indices.set(indices.len() as u32);
+        if indices.is_empty() {
+            return;
+        }
+        let (vertex_bytes, index_bytes) =
+            unsafe { (vertices.align_to::<u8>().1, indices.align_to::<u8>().1) };
+
+        if vertex_bytes.len() <= obj.vertex_bytes.get() {
+            self.queue
+                .write_buffer(&obj.vertex_buffer.borrow(), 0, vertex_bytes);
+        } else {
+            *obj.vertex_buffer.borrow_mut() =
+                Rc::new(self.create_buffer(vertex_bytes, wgpu::BufferUsage::VERTEX));
+            obj.vertex_bytes.set(vertex_bytes.len());
+        }
+
+        if index_bytes.len() <= obj.index_bytes.get() {
+            self.queue
+                .write_buffer(&obj.index_buffer.borrow(), 0, index_bytes);
+        } else {
+            *obj.index_buffer.borrow_mut() =
+                Rc::new(self.create_buffer(index_bytes, wgpu::BufferUsage::INDEX));
+            obj.index_bytes.set(index_bytes.len());
+        }
+    }
+
+    // wgpu frees the buffers when they're dropped, once no frame being recorded needs them.
+    // Dropping the buffers frees them
+    fn free(&self, _: Drawable) {}
+
+    fn request_redraw(&self) {
+        self.window.request_redraw();
+    }
+
+    fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
+        self.window.set_cursor_icon(icon);
+    }
+
+    fn window_resized(&self, width: f64, height: f64) {
+        // Minimized
+        if width < 1.0 || height < 1.0 {
+            return;
+        }
+        let mut sc_desc = self.sc_desc.borrow_mut();
+        sc_desc.width = width as u32;
+        sc_desc.height = height as u32;
+        *self.swap_chain.borrow_mut() = self.device.create_swap_chain(&self.surface, &sc_desc);
+        *self.depth_view.borrow_mut() = make_depth_view(&self.device, &sc_desc);
+    }
+
+    fn get_inner_size(&self) -> (f64, f64) {
+        let size = self.window.inner_size();
+        (size.width.into(), size.height.into())
+    }
+
+    fn set_window_icon(&self, icon: winit::window::Icon) {
+        self.window.set_window_icon(Some(icon));
+    }
+
+    fn monitor_scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+
+    // TODO Not implemented yet
+    fn read_front_buffer(&self) -> Option<(u32, u32, Vec<u8>)> {
+        None
+    }
+
+    fn total_bytes_uploaded(&self) -> usize {
+        self.total_bytes_uploaded.get()
+    }
+}
+
+impl<'a> DrawFrame<'a> for PrerenderInnards {
+    type GfxCtx = GfxCtxInnards<'a>;
+
+    fn draw_new_frame(&'a self) -> GfxCtxInnards<'a> {
+        GfxCtxInnards {
+            prerender: self,
+            passes: vec![Pass {
+                clear: None,
+                draws: Vec::new(),
+            }],
+            uniforms: Vec::new(),
+            current_clip: None,
+            current_blend: BlendMode::Normal,
+        }
+    }
+}
+
+impl PrerenderInnards {
+    // COPY_DST lets update overwrite the contents later.
+    fn create_buffer(&self, contents: &[u8], usage: wgpu::BufferUsage) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents,
+                usage: usage | wgpu::BufferUsage::COPY_DST,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glsl_to_spirv::ShaderType;
+
+    // Creating a device needs a GPU, but checking that the shaders compile doesn't.
+    #[test]
+    fn shaders_compile() {
+        for (source, shader_type) in vec![
+            (include_str!("shaders/vertex_450.glsl"), ShaderType::Vertex),
+            (
+                include_str!("shaders/vertex_instanced_450.glsl"),
+                ShaderType::Vertex,
+            ),
+            (
+                include_str!("shaders/fragment_450.glsl"),
+                ShaderType::Fragment,
+            ),
+        ] {
+            if let Err(err) = glsl_to_spirv::compile(source, shader_type) {
+                panic!("{}", err);
+            }
+        }
+    }
+}
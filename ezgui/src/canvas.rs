@@ -1,9 +1,11 @@
 use crate::assets::Assets;
 use crate::{hotkey, Key, ScreenDims, ScreenPt, ScreenRectangle, UserInput};
 use abstutil::Timer;
-use geom::{Bounds, Pt2D};
+use geom::{Bounds, Duration, Pt2D};
+use instant::Instant;
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
 
 // Click and release counts as a normal click, not a drag, if the distance between click and
 // release is less than this.
@@ -24,6 +26,14 @@ pub struct Canvas {
     pub(crate) cursor_x: f64,
     pub(crate) cursor_y: f64,
     pub(crate) window_has_cursor: bool,
+    // Until somebody picks a scale factor themselves, follow the monitor's DPI as the window
+    // moves around.
+    pub(crate) follow_monitor_scale_factor: bool,
+    // When the cursor last moved, to delay tooltips until it's been still for a bit
+    pub(crate) cursor_still_since: Instant,
+    // Set while drawing if a tooltip is waiting for the delay, so the runner knows to wake up and
+    // draw it later.
+    pub(crate) tooltip_waiting: Cell<bool>,
 
     // Only for drags starting on the map. Only used to pan the map. (Last event, original)
     pub(crate) drag_canvas_from: Option<(ScreenPt, ScreenPt)>,
@@ -35,9 +45,15 @@ pub struct Canvas {
     // TODO Proper API for setting these
     pub map_dims: (f64, f64),
     pub invert_scroll: bool,
+    pub invert_horiz_scroll: bool,
+    // Multiplies how much one scroll event zooms. Some trackpads send many tiny events, others a
+    // few huge ones.
+    pub zoom_sensitivity: f64,
     pub touchpad_to_move: bool,
     pub edge_auto_panning: bool,
     pub keys_to_pan: bool,
+    // How long the cursor has to rest somewhere before tooltips appear
+    pub tooltip_delay: Duration,
 
     // TODO Bit weird and hacky to mutate inside of draw() calls.
     pub(crate) covered_areas: RefCell<Vec<ScreenRectangle>>,
@@ -45,6 +61,10 @@ pub struct Canvas {
     // Kind of just ezgui state awkwardly stuck here...
     pub(crate) lctrl_held: bool,
     pub(crate) lshift_held: bool,
+    // The last key pressed besides modifiers, and when. For MultiKey::Sequence.
+    pub(crate) prev_key: Option<(Key, Instant)>,
+    // Every MultiKey::Sequence checked while handling the last event
+    pub(crate) sequences: BTreeSet<(Key, Key)>,
 }
 
 impl Canvas {
@@ -57,6 +77,9 @@ impl Canvas {
             cursor_x: 0.0,
             cursor_y: 0.0,
             window_has_cursor: true,
+            follow_monitor_scale_factor: true,
+            cursor_still_since: Instant::now(),
+            tooltip_waiting: Cell::new(false),
 
             drag_canvas_from: None,
             drag_just_ended: false,
@@ -66,14 +89,19 @@ impl Canvas {
 
             map_dims: (0.0, 0.0),
             invert_scroll: false,
+            invert_horiz_scroll: false,
+            zoom_sensitivity: 1.0,
             touchpad_to_move: false,
             edge_auto_panning: false,
             keys_to_pan: false,
+            tooltip_delay: Duration::seconds(0.5),
 
             covered_areas: RefCell::new(Vec::new()),
 
             lctrl_held: false,
             lshift_held: false,
+            prev_key: None,
+            sequences: BTreeSet::new(),
         }
     }
 
@@ -88,8 +116,12 @@ impl Canvas {
         if self.get_cursor_in_map_space().is_some() {
             if self.touchpad_to_move {
                 if let Some((scroll_x, scroll_y)) = input.get_mouse_scroll() {
+                    // Most platforms send pinching on a trackpad as scrolling with Control held.
                     if self.lctrl_held {
-                        self.zoom(scroll_y, (self.cursor_x, self.cursor_y));
+                        self.zoom(
+                            scroll_y * self.zoom_sensitivity,
+                            (self.cursor_x, self.cursor_y),
+                        );
                     } else {
                         // Woo, inversion is different for the two. :P
                         self.cam_x += scroll_x * PAN_SPEED;
@@ -101,8 +133,13 @@ impl Canvas {
                     self.drag_canvas_from = Some((self.get_cursor(), self.get_cursor()));
                 }
 
-                if let Some((_, scroll)) = input.get_mouse_scroll() {
-                    self.zoom(scroll, (self.cursor_x, self.cursor_y));
+                // Tilting the wheel or scrolling sideways on a trackpad pans
+                if let Some((scroll_x, scroll_y)) = input.get_mouse_scroll() {
+                    self.cam_x += scroll_x * PAN_SPEED;
+                    self.zoom(
+                        scroll_y * self.zoom_sensitivity,
+                        (self.cursor_x, self.cursor_y),
+                    );
                 }
             }
 
@@ -183,6 +220,13 @@ impl Canvas {
 
     pub(crate) fn start_drawing(&self) {
         self.covered_areas.borrow_mut().clear();
+        self.tooltip_waiting.set(false);
+    }
+
+    // True when the last frame held back a tooltip that's now ready to appear.
+    pub(crate) fn tooltip_ready(&self) -> bool {
+        self.tooltip_waiting.get()
+            && Duration::realtime_elapsed(self.cursor_still_since) >= self.tooltip_delay
     }
 
     // TODO Only public for the OSD. :(
@@ -190,6 +234,16 @@ impl Canvas {
         self.covered_areas.borrow_mut().push(rect);
     }
 
+    // For modal dialogs, which block the map everywhere, not just where they're drawn.
+    pub fn cover_whole_window(&self) {
+        self.mark_covered_area(ScreenRectangle {
+            x1: 0.0,
+            y1: 0.0,
+            x2: self.window_width,
+            y2: self.window_height,
+        });
+    }
+
     // Might be hovering anywhere.
     pub fn get_cursor(&self) -> ScreenPt {
         ScreenPt::new(self.cursor_x, self.cursor_y)
@@ -324,6 +378,7 @@ impl Canvas {
             VerticalAlignment::Percent(pct) => pct * self.window_height,
             VerticalAlignment::Above(y) => y - dims.height,
             VerticalAlignment::Below(y) => y,
+            VerticalAlignment::Centered(y) => y - (dims.height / 2.0),
         };
         ScreenPt::new(x1, y1)
     }
@@ -347,6 +402,7 @@ pub enum VerticalAlignment {
     Percent(f64),
     Above(f64),
     Below(f64),
+    Centered(f64),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
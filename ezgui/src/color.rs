@@ -1,4 +1,4 @@
-use geom::{Line, Pt2D};
+use geom::{Distance, Line, Pt2D};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -26,8 +26,33 @@ pub enum FancyColor {
     RGBA(Color),
     Hatching,
     LinearGradient(LinearGradient),
+    // Two colors blended per pixel by the fragment shader. Unlike LinearGradient, which just sets
+    // the color of each vertex, this doesn't need lots of thin polygons to look smooth. Both
+    // colors are rounded to 64 levels per channel.
+    Gradient {
+        from: Color,
+        to: Color,
+        axis: GradientAxis,
+    },
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum GradientAxis {
+    // From the first point to the second, with bands perpendicular to the line between them.
+    // Beyond either end, the color stays the same.
+    Linear(Pt2D, Pt2D),
+    // From the center out to the radius. Past that, the color stays the same.
+    Radial(Pt2D, Distance),
+}
+
+// Every backend uploads this many floats per vertex: the position and style (see
+// FancyColor::vertex).
+pub(crate) const FLOATS_PER_VERTEX: usize = 6;
+
+// Gradient colors are packed into one float each, with this many levels per channel. 4 channels
+// of 6 bits fill the 24 bits a float can hold exactly.
+const GRADIENT_LEVELS: f32 = 63.0;
+
 impl Color {
     // TODO Won't this confuse the shader? :P
     pub const INVISIBLE: Color = Color::rgba_f(1.0, 0.0, 0.0, 0.0);
@@ -75,6 +100,42 @@ impl Color {
         Color::rgba_f(self.r, self.g, self.b, a)
     }
 
+    // Hue in degrees, saturation and value from 0 to 1
+    pub fn hsv(hue: f64, sat: f64, val: f64) -> Color {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let c = val * sat;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as usize {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = val - c;
+        Color::rgb_f((r + m) as f32, (g + m) as f32, (b + m) as f32)
+    }
+
+    // (hue in degrees, saturation, value), ignoring alpha
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (f64::from(self.r), f64::from(self.g), f64::from(self.b));
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let sat = if max == 0.0 { 0.0 } else { delta / max };
+        (hue, sat, max)
+    }
+
     pub fn hex(raw: &str) -> Color {
         // Skip the leading '#'
         let r = usize::from_str_radix(&raw[1..3], 16).unwrap();
@@ -92,6 +153,11 @@ impl Color {
         )
     }
 
+    // Relative luminance, from 0 (black) to 1 (white), ignoring alpha
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     pub fn lerp(self, other: Color, pct: f64) -> Color {
         Color::rgba_f(
             lerp(pct, (self.r, other.r)),
@@ -127,7 +193,7 @@ impl LinearGradient {
         FancyColor::LinearGradient(LinearGradient { line, stops })
     }
 
-    fn interp(&self, pt: Pt2D) -> Color {
+    pub(crate) fn interp(&self, pt: Pt2D) -> Color {
         let pct = self
             .line
             .percent_along_of_point(self.line.project_pt(pt))
@@ -160,6 +226,11 @@ fn lerp(pct: f64, (x1, x2): (f32, f32)) -> f32 {
 }
 
 impl FancyColor {
+    // Every vertex carries one of these, decoded by the fragment shaders. There are no textures
+    // yet; SVGs are tessellated into plain colored triangles instead. If textures are ever added,
+    // don't bind each one as its own sampler uniform -- that runs into the shader's sampler limit.
+    // Pack them into an atlas owned by Prerender and encode atlas coordinates here, next to the
+    // special value used for hatching.
     pub(crate) fn style(&self, pt: Pt2D) -> [f32; 4] {
         match self {
             FancyColor::RGBA(c) => [c.r, c.g, c.b, c.a],
@@ -168,6 +239,210 @@ impl FancyColor {
                 let c = lg.interp(pt);
                 [c.r, c.g, c.b, c.a]
             }
+            // Normal colors are never negative, so that marks a gradient. The sign of the second
+            // value says which kind. Both coordinates are linear in the position, so the GPU can
+            // interpolate them between vertices, then the fragment shader clamps and blends them
+            // per pixel. See vertex_140.glsl for the decoding.
+            FancyColor::Gradient { from, to, axis } => {
+                let (sign, u, v) = match gradient_coords(*axis, pt) {
+                    GradientCoords::Linear(pct) => (1.0, pct, 0.0),
+                    GradientCoords::Radial(u, v) => (-1.0, u, v),
+                };
+                [
+                    -1.0 - pack_color(*from),
+                    sign * (1.0 + pack_color(*to)),
+                    u as f32,
+                    v as f32,
+                ]
+            }
+        }
+    }
+
+    // Keeps a gradient lined up with the polygon it fills, when that moves.
+    pub(crate) fn translate(&mut self, dx: f64, dy: f64) {
+        if let FancyColor::Gradient { ref mut axis, .. } = self {
+            *axis = match *axis {
+                GradientAxis::Linear(pt1, pt2) => {
+                    GradientAxis::Linear(pt1.offset(dx, dy), pt2.offset(dx, dy))
+                }
+                GradientAxis::Radial(center, radius) => {
+                    GradientAxis::Radial(center.offset(dx, dy), radius)
+                }
+            };
+        }
+    }
+
+    pub(crate) fn scale(&mut self, factor: f64) {
+        if let FancyColor::Gradient { ref mut axis, .. } = self {
+            let scale = |pt: Pt2D| Pt2D::new(pt.x() * factor, pt.y() * factor);
+            *axis = match *axis {
+                GradientAxis::Linear(pt1, pt2) => GradientAxis::Linear(scale(pt1), scale(pt2)),
+                GradientAxis::Radial(center, radius) => {
+                    GradientAxis::Radial(scale(center), radius * factor)
+                }
+            };
+        }
+    }
+
+    // Everything uploaded for one vertex: the position and style.
+    pub(crate) fn vertex(&self, pt: Pt2D) -> [f32; FLOATS_PER_VERTEX] {
+        let style = self.style(pt);
+        [
+            pt.x() as f32,
+            pt.y() as f32,
+            style[0],
+            style[1],
+            style[2],
+            style[3],
+        ]
+    }
+
+    // What the shader would draw at this point. Only used for rendering without a GPU.
+    pub(crate) fn color_at(&self, pt: Pt2D) -> Color {
+        match self {
+            FancyColor::RGBA(c) => *c,
+            FancyColor::Hatching => Color::grey(0.5),
+            FancyColor::LinearGradient(lg) => lg.interp(pt),
+            // Decode what would be uploaded, so this matches the shader, rounding included
+            FancyColor::Gradient { .. } => shade_gradient(self.style(pt)),
+        }
+    }
+}
+
+enum GradientCoords {
+    // Percent along the axis
+    Linear(f64),
+    // Offset from the center, in units of the radius
+    Radial(f64, f64),
+}
+
+fn gradient_coords(axis: GradientAxis, pt: Pt2D) -> GradientCoords {
+    match axis {
+        GradientAxis::Linear(pt1, pt2) => {
+            let (dx, dy) = (pt2.x() - pt1.x(), pt2.y() - pt1.y());
+            let len_squared = dx * dx + dy * dy;
+            // With no length, there's nothing to blend across. Use the end color everywhere.
+            if len_squared == 0.0 {
+                return GradientCoords::Linear(1.0);
+            }
+            GradientCoords::Linear(
+                ((pt.x() - pt1.x()) * dx + (pt.y() - pt1.y()) * dy) / len_squared,
+            )
+        }
+        GradientAxis::Radial(center, radius) => {
+            // Everything is past the edge of an empty circle
+            if radius <= Distance::ZERO {
+                return GradientCoords::Radial(1.0, 0.0);
+            }
+            GradientCoords::Radial(
+                (pt.x() - center.x()) / radius.inner_meters(),
+                (pt.y() - center.y()) / radius.inner_meters(),
+            )
+        }
+    }
+}
+
+// Every channel is rounded to one of GRADIENT_LEVELS + 1 values, then packed into an integer below
+// 2^24.
+fn pack_color(c: Color) -> f32 {
+    let level = |x: f32| (x.max(0.0).min(1.0) * GRADIENT_LEVELS).round();
+    let base = GRADIENT_LEVELS + 1.0;
+    ((level(c.r) * base + level(c.g)) * base + level(c.b)) * base + level(c.a)
+}
+
+// The inverse of pack_color, written the same way as unpack_color in the vertex shaders.
+fn unpack_color(packed: f32) -> Color {
+    let base = GRADIENT_LEVELS + 1.0;
+    let mut x = (packed + 0.5).floor();
+    let r = (x / (base * base * base)).floor();
+    x -= r * base * base * base;
+    let g = (x / (base * base)).floor();
+    x -= g * base * base;
+    let b = (x / base).floor();
+    let a = x - b * base;
+    Color::rgba_f(
+        r / GRADIENT_LEVELS,
+        g / GRADIENT_LEVELS,
+        b / GRADIENT_LEVELS,
+        a / GRADIENT_LEVELS,
+    )
+}
+
+// What the vertex and fragment shaders draw for a gradient's (interpolated) style.
+fn shade_gradient(style: [f32; 4]) -> Color {
+    let from = unpack_color(-style[0] - 1.0);
+    let to = unpack_color(style[1].abs() - 1.0);
+    let pct = if style[1] > 0.0 {
+        style[2]
+    } else {
+        style[2].hypot(style[3])
+    };
+    from.lerp(to, f64::from(pct.max(0.0).min(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_style() {
+        let color = FancyColor::Gradient {
+            from: Color::rgba(255, 0, 128, 0.5),
+            to: Color::rgba_f(0.2, 0.4, 0.6, 1.0),
+            axis: GradientAxis::Linear(Pt2D::new(0.0, 0.0), Pt2D::new(10.0, 0.0)),
+        };
+        let style = color.style(Pt2D::new(5.0, 3.0));
+        assert!(style[0] < 0.0 && style[1] > 0.0);
+        assert_eq!(style[2], 0.5);
+
+        // The packed colors survive, rounded to the nearest level
+        let close = |c1: Color, c2: Color| {
+            (c1.r - c2.r).abs() <= 0.5 / GRADIENT_LEVELS
+                && (c1.g - c2.g).abs() <= 0.5 / GRADIENT_LEVELS
+                && (c1.b - c2.b).abs() <= 0.5 / GRADIENT_LEVELS
+                && (c1.a - c2.a).abs() <= 0.5 / GRADIENT_LEVELS
+        };
+        assert!(close(
+            unpack_color(-style[0] - 1.0),
+            Color::rgba(255, 0, 128, 0.5)
+        ));
+        assert!(close(
+            unpack_color(style[1] - 1.0),
+            Color::rgba_f(0.2, 0.4, 0.6, 1.0)
+        ));
+        // Pure colors are exact
+        assert_eq!(unpack_color(pack_color(Color::WHITE)), Color::WHITE);
+        assert_eq!(unpack_color(pack_color(Color::INVISIBLE)), Color::INVISIBLE);
+
+        // What the GPU interpolates between two vertices decodes to the blend at that point
+        let left = color.style(Pt2D::new(0.0, 0.0));
+        let right = color.style(Pt2D::new(10.0, 0.0));
+        let mut middle = [0.0; 4];
+        for i in 0..4 {
+            middle[i] = (left[i] + right[i]) / 2.0;
+        }
+        assert_eq!(shade_gradient(middle), color.color_at(Pt2D::new(5.0, 0.0)));
+
+        // Hatching and plain colors don't look like gradients
+        assert!(FancyColor::Hatching.style(Pt2D::new(0.0, 0.0))[0] >= 0.0);
+        assert!(FancyColor::RGBA(Color::BLACK).style(Pt2D::new(0.0, 0.0))[0] >= 0.0);
+    }
+
+    #[test]
+    fn degenerate_gradients() {
+        for axis in vec![
+            GradientAxis::Linear(Pt2D::new(3.0, 3.0), Pt2D::new(3.0, 3.0)),
+            GradientAxis::Radial(Pt2D::new(3.0, 3.0), Distance::ZERO),
+        ] {
+            let color = FancyColor::Gradient {
+                from: Color::RED,
+                to: Color::BLUE,
+                axis,
+            };
+            for pt in vec![Pt2D::new(3.0, 3.0), Pt2D::new(10.0, 0.0)] {
+                assert!(color.style(pt).iter().all(|x| x.is_finite()));
+                assert_eq!(color.color_at(pt), Color::BLUE);
+            }
         }
     }
 }
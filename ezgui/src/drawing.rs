@@ -1,22 +1,70 @@
 use crate::assets::Assets;
-use crate::backend::{GfxCtxInnards, PrerenderInnards};
+use crate::backend::{DrawFrame, GfxCtxBackend, GfxCtxInnards, PrerenderInnards, RenderBackend};
+use crate::color::FLOATS_PER_VERTEX;
+use crate::gpu_cache::GpuCache;
+use crate::retained::RetainedPool;
 use crate::{
-    Canvas, Color, Drawable, FancyColor, GeomBatch, ScreenDims, ScreenPt, ScreenRectangle, Style,
-    Text,
+    CachedDrawable, Canvas, Color, Drawable, FancyColor, GeomBatch, ScreenDims, ScreenPt,
+    ScreenRectangle, Style, Text,
 };
-use geom::{ArrowCap, Bounds, Circle, Distance, Line, Polygon, Pt2D};
-use std::cell::Cell;
+use geom::{Angle, ArrowCap, Bounds, Circle, Distance, Duration, Line, LineStyle, Polygon, Pt2D};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 // Lower is more on top
 const MAPSPACE_Z: f32 = 1.0;
 const SCREENSPACE_Z: f32 = 0.5;
 const TOOLTIP_Z: f32 = 0.0;
 
+// Map-space drawing can be split into this many layers, all between MAPSPACE_Z and SCREENSPACE_Z.
+// See GfxCtx::set_z.
+pub const NUM_MAP_LAYERS: usize = 100;
+
+// How colors being drawn combine with what's already there
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    // Paint over, according to alpha
+    Normal,
+    // Brighten what's there, according to alpha. Overlapping areas stand out instead of hiding
+    // each other.
+    Additive,
+}
+
+const IDENTITY_MODEL: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+// Every backend uploads this many floats per instance: the position, angle, and color.
+pub(crate) const FLOATS_PER_INSTANCE: usize = 7;
+
+// One copy of a shape drawn by GfxCtx::redraw_instances.
+#[derive(Clone, Copy, Debug)]
+pub struct Instance {
+    pub pos: Pt2D,
+    pub angle: Angle,
+    // Multiplies every color in the shape, so parts drawn in white take on exactly this color.
+    pub color: Color,
+}
+
+impl Instance {
+    pub(crate) fn raw(&self) -> [f32; FLOATS_PER_INSTANCE] {
+        [
+            self.pos.x() as f32,
+            self.pos.y() as f32,
+            self.angle.normalized_radians() as f32,
+            self.color.r,
+            self.color.g,
+            self.color.b,
+            self.color.a,
+        ]
+    }
+}
+
 pub struct Uniforms {
     // (cam_x, cam_y, cam_zoom)
     pub transform: [f32; 3],
     // (window_width, window_height, Z values)
     pub window: [f32; 3],
+    // (dx, dy, rotation in radians, scale), applied to each vertex before the camera
+    pub model: [f32; 4],
 }
 
 impl Uniforms {
@@ -32,6 +80,7 @@ impl Uniforms {
                 canvas.window_height as f32,
                 MAPSPACE_Z,
             ],
+            model: IDENTITY_MODEL,
         }
     }
 }
@@ -39,9 +88,12 @@ impl Uniforms {
 pub struct GfxCtx<'a> {
     pub(crate) inner: GfxCtxInnards<'a>,
     uniforms: Uniforms,
+    // Map-space draws use this, and unfork goes back to it
+    map_z: f32,
 
     screencap_mode: bool,
     pub(crate) naming_hint: Option<String>,
+    pub(crate) screenshot_path: Option<String>,
 
     // TODO Don't be pub. Delegate everything.
     pub canvas: &'a Canvas,
@@ -63,6 +115,7 @@ impl<'a> GfxCtx<'a> {
         GfxCtx {
             inner: prerender.inner.draw_new_frame(),
             uniforms,
+            map_z: MAPSPACE_Z,
             canvas,
             style,
             prerender,
@@ -70,6 +123,7 @@ impl<'a> GfxCtx<'a> {
             num_forks: 0,
             screencap_mode,
             naming_hint: None,
+            screenshot_path: None,
         }
     }
 
@@ -106,11 +160,39 @@ impl<'a> GfxCtx<'a> {
         self.num_forks += 1;
     }
 
+    // Like fork_screenspace, but coordinates are in logical pixels, multiplied by the scale factor
+    // to get physical pixels. Use this to draw something by hand that should be the same size as
+    // widgets and text on any display.
+    pub fn fork_screenspace_scaled(&mut self) {
+        self.fork_screenspace();
+        self.uniforms.transform[2] = self.get_scale_factor() as f32;
+    }
+
     pub fn unfork(&mut self) {
         self.uniforms = Uniforms::new(&self.canvas);
+        self.uniforms.window[2] = self.map_z;
         self.num_forks += 1;
     }
 
+    // Map-space things drawn in a higher layer appear on top of lower layers, no matter what order
+    // they're drawn in. Within one layer, later draws cover earlier ones. Everything starts in
+    // layer 0, and the layer lasts until it's changed again, even across fork and unfork. Don't
+    // call this while forked.
+    //
+    // Translucent things only blend with what's already been drawn, so draw a higher layer last
+    // if it needs to show what's underneath.
+    pub fn set_z(&mut self, layer: usize) {
+        assert!(
+            layer < NUM_MAP_LAYERS,
+            "set_z({}), but there are only {} layers",
+            layer,
+            NUM_MAP_LAYERS
+        );
+        self.map_z =
+            MAPSPACE_Z - (layer as f32) * (MAPSPACE_Z - SCREENSPACE_Z) / (NUM_MAP_LAYERS as f32);
+        self.uniforms.window[2] = self.map_z;
+    }
+
     pub fn clear(&mut self, color: Color) {
         self.inner.clear(color);
     }
@@ -120,13 +202,21 @@ impl<'a> GfxCtx<'a> {
     }
 
     pub fn draw_rounded_line(&mut self, color: Color, thickness: Distance, line: &Line) {
-        self.draw_polygons(
+        self.draw_line_with_style(color, thickness, line, LineStyle::ROUND);
+    }
+
+    pub fn draw_line_with_style(
+        &mut self,
+        color: Color,
+        thickness: Distance,
+        line: &Line,
+        style: LineStyle,
+    ) {
+        self.draw_polygon(
             color,
-            &vec![
-                line.make_polygons(thickness),
-                Circle::new(line.pt1(), thickness / 2.0).to_polygon(),
-                Circle::new(line.pt2(), thickness / 2.0).to_polygon(),
-            ],
+            &line
+                .to_polyline()
+                .make_polygons_with_style(thickness, style),
         );
     }
 
@@ -140,6 +230,29 @@ impl<'a> GfxCtx<'a> {
         );
     }
 
+    // If radius is None, the short ends are completely round.
+    pub fn draw_rounded_rectangle(
+        &mut self,
+        color: Color,
+        rect: &ScreenRectangle,
+        radius: Option<f64>,
+    ) {
+        self.draw_polygon(
+            color,
+            &Polygon::rounded_rectangle(rect.width(), rect.height(), radius)
+                .translate(rect.x1, rect.y1),
+        );
+    }
+
+    // Draws an icon from a sheet loaded at startup, centered on a point. Works in map-space or
+    // screen-space, depending on the current fork.
+    pub fn draw_icon(&mut self, name: &str, center: Pt2D, scale: f64) {
+        GeomBatch::mapspace_icon(self.prerender, name)
+            .scale(scale)
+            .centered_on(center)
+            .draw(self);
+    }
+
     pub fn draw_circle(&mut self, color: Color, circle: &Circle) {
         self.draw_polygon(color, &circle.to_polygon());
     }
@@ -147,12 +260,12 @@ impl<'a> GfxCtx<'a> {
     pub fn draw_polygon(&mut self, color: Color, poly: &Polygon) {
         let obj = self
             .prerender
-            .upload_temporary(vec![(FancyColor::RGBA(color), poly)]);
+            .upload_retained(vec![(FancyColor::RGBA(color), poly)]);
         self.redraw(&obj);
     }
 
     pub fn draw_polygons(&mut self, color: Color, polygons: &Vec<Polygon>) {
-        let obj = self.prerender.upload_temporary(
+        let obj = self.prerender.upload_retained(
             polygons
                 .iter()
                 .map(|p| (FancyColor::RGBA(color), p))
@@ -169,12 +282,53 @@ impl<'a> GfxCtx<'a> {
         // println!("{:?}", backtrace::Backtrace::new());
     }
 
+    // Applies to everything drawn until it's changed again. Set it back to Normal when done.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.inner.set_blend_mode(mode);
+    }
+
+    pub fn redraw_cached(&mut self, obj: &CachedDrawable) {
+        let draw = self.prerender.get_cached(obj);
+        self.redraw(&draw);
+    }
+
     pub fn redraw_at(&mut self, top_left: ScreenPt, obj: &Drawable) {
         self.fork(Pt2D::new(0.0, 0.0), top_left, 1.0, None);
         self.redraw(obj);
         self.unfork();
     }
 
+    // Draws something uploaded once around the origin, but rotated, scaled, and then moved to
+    // offset in map-space. The shader does the work, so moving agents around doesn't require
+    // tessellating and uploading them again every frame.
+    pub fn redraw_transformed(
+        &mut self,
+        obj: &Drawable,
+        offset: Pt2D,
+        rotation: Angle,
+        scale: f64,
+    ) {
+        self.uniforms.model = [
+            offset.x() as f32,
+            offset.y() as f32,
+            rotation.normalized_radians() as f32,
+            scale as f32,
+        ];
+        self.redraw(obj);
+        self.uniforms.model = IDENTITY_MODEL;
+    }
+
+    // Draws a copy of obj for every instance in one draw call, instead of uploading each copy.
+    // Like redraw_transformed, obj should be uploaded around the origin, facing Angle::ZERO.
+    pub fn redraw_instances(&mut self, obj: &Drawable, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+        self.inner
+            .redraw_instances(obj, instances, &self.uniforms, &self.prerender.inner);
+        self.num_draw_calls += 1;
+    }
+
     // TODO Stateful API :(
     pub fn enable_clipping(&mut self, rect: ScreenRectangle) {
         self.inner.enable_clipping(rect, self.canvas);
@@ -186,19 +340,38 @@ impl<'a> GfxCtx<'a> {
 
     // Canvas stuff.
 
+    // Only appears once the cursor has rested for the canvas's tooltip_delay. The text can have
+    // many lines and styles. Normally it's below and to the right of the cursor, but it flips to
+    // the other side near the edges of the window.
     pub fn draw_mouse_tooltip(&mut self, txt: Text) {
+        if Duration::realtime_elapsed(self.canvas.cursor_still_since) < self.canvas.tooltip_delay {
+            self.canvas.tooltip_waiting.set(true);
+            return;
+        }
+
+        // The text is already scaled; make the rest match.
+        let scale = self.get_scale_factor();
         // Add some padding
-        let pad = 5.0;
+        let pad = 5.0 * scale;
 
         let txt_batch = txt.render_g(self);
         let raw_dims = txt_batch.get_dims();
         let dims = ScreenDims::new(raw_dims.width + 2.0 * pad, raw_dims.height + 2.0 * pad);
 
-        // TODO Maybe also consider the cursor as a valid center
-        let pt = dims.top_left_for_corner(
-            ScreenPt::new(self.canvas.cursor_x, self.canvas.cursor_y + 20.0),
-            &self.canvas,
-        );
+        // Don't cover up the cursor itself
+        let (cursor_x, cursor_y) = (self.canvas.cursor_x, self.canvas.cursor_y);
+        let x = if cursor_x + 10.0 * scale + dims.width <= self.canvas.window_width {
+            cursor_x + 10.0 * scale
+        } else {
+            cursor_x - 10.0 * scale - dims.width
+        };
+        let y = if cursor_y + 20.0 * scale + dims.height <= self.canvas.window_height {
+            cursor_y + 20.0 * scale
+        } else {
+            cursor_y - 5.0 * scale - dims.height
+        };
+        // Huge tooltips might not fit anywhere; at least show the beginning.
+        let pt = ScreenPt::new(x.max(0.0), y.max(0.0));
         let mut batch = GeomBatch::new();
         // TODO Outline?
         batch.push(
@@ -248,6 +421,12 @@ impl<'a> GfxCtx<'a> {
         self.naming_hint = Some(hint);
     }
 
+    // Once this frame is done drawing, save exactly what's in the window to a PNG file. Unlike
+    // ScreenCaptureEverything, this doesn't need any external tools or move the camera.
+    pub fn screenshot_current_frame(&mut self, path: String) {
+        self.screenshot_path = Some(path);
+    }
+
     pub fn upload(&mut self, batch: GeomBatch) -> Drawable {
         self.prerender.upload(batch)
     }
@@ -257,6 +436,10 @@ impl<'a> GfxCtx<'a> {
         *self.prerender.assets.default_line_height.borrow()
     }
 
+    pub fn get_scale_factor(&self) -> f64 {
+        *self.prerender.assets.scale_factor.borrow()
+    }
+
     pub fn style(&self) -> &Style {
         &self.style
     }
@@ -268,6 +451,8 @@ pub struct Prerender {
     pub(crate) inner: PrerenderInnards,
     pub(crate) assets: Assets,
     pub(crate) num_uploads: Cell<usize>,
+    pub(crate) gpu_cache: Rc<RefCell<GpuCache>>,
+    pub(crate) retained: RefCell<RetainedPool>,
 }
 
 impl Prerender {
@@ -277,14 +462,18 @@ impl Prerender {
     }
 
     pub fn get_total_bytes_uploaded(&self) -> usize {
-        self.inner.total_bytes_uploaded.get()
+        self.inner.total_bytes_uploaded()
     }
 
     pub(crate) fn upload_temporary(&self, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
         self.actually_upload(false, list)
     }
 
-    fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
+    pub(crate) fn actually_upload(
+        &self,
+        permanent: bool,
+        list: Vec<(FancyColor, &Polygon)>,
+    ) -> Drawable {
         // println!("{:?}", backtrace::Backtrace::new());
         self.num_uploads.set(self.num_uploads.get() + 1);
         self.inner.actually_upload(permanent, list)
@@ -294,3 +483,44 @@ impl Prerender {
         self.inner.request_redraw()
     }
 }
+
+impl Drawable {
+    // Replaces what gets drawn, for things that change often, like an animated traffic signal.
+    // When the new geometry fits in the buffers already on the GPU, they're overwritten in place;
+    // otherwise they grow.
+    pub fn update(&self, prerender: &Prerender, batch: GeomBatch) {
+        let borrows = batch.list.iter().map(|(c, p)| (c.clone(), p)).collect();
+        prerender.num_uploads.set(prerender.num_uploads.get() + 1);
+        prerender.inner.update(self, borrows);
+    }
+}
+
+// What the backends upload for a list of polygons: the vertices, then indices into them.
+pub(crate) fn vertices_and_indices(
+    list: Vec<(FancyColor, &Polygon)>,
+) -> (Vec<[f32; FLOATS_PER_VERTEX]>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (color, poly) in list {
+        let idx_offset = vertices.len();
+        let (pts, raw_indices) = poly.raw_for_rendering();
+        for pt in pts {
+            vertices.push(color.vertex(*pt));
+        }
+        for idx in raw_indices {
+            indices.push((idx_offset + *idx) as u32);
+        }
+    }
+    (vertices, indices)
+}
+
+// Every backend uploads FLOATS_PER_VERTEX floats per vertex and one u32 per index.
+pub(crate) fn bytes_for(list: &[(FancyColor, &Polygon)]) -> usize {
+    let mut bytes = 0;
+    for (_, poly) in list {
+        let (pts, indices) = poly.raw_for_rendering();
+        bytes += pts.len() * FLOATS_PER_VERTEX * std::mem::size_of::<f32>()
+            + indices.len() * std::mem::size_of::<u32>();
+    }
+    bytes
+}
@@ -18,13 +18,15 @@ pub enum Event {
     KeyPress(Key),
     KeyRelease(Key),
     // Some real amount of time has passed since the last update; EventLoopMode::Animation is
-    // active
+    // active. Call use_update_event() if anything changed, or the screen won't be redrawn.
     Update(Duration),
     MouseMovedTo(ScreenPt),
     WindowLostCursor,
     WindowGainedCursor,
     MouseWheelScroll(f64, f64),
     WindowResized(f64, f64),
+    // Like when the window moves to a monitor with a different DPI
+    ScaleFactorChanged(f64),
 }
 
 impl Event {
@@ -56,7 +58,6 @@ impl Event {
                     if dx == 0.0 && dy == 0.0 {
                         None
                     } else {
-                        // TODO Also x?
                         Some(Event::MouseWheelScroll(
                             f64::from(dx),
                             scroll_wheel_multiplier() * f64::from(dy),
@@ -73,6 +74,9 @@ impl Event {
             WindowEvent::Resized(size) => {
                 Some(Event::WindowResized(size.width.into(), size.height.into()))
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                Some(Event::ScaleFactorChanged(scale_factor))
+            }
             WindowEvent::Focused(gained) => Some(if gained {
                 Event::WindowGainedCursor
             } else {
@@ -385,7 +389,11 @@ impl Key {
 pub enum MultiKey {
     Normal(Key),
     LCtrl(Key),
+    LShift(Key),
     Any(Vec<Key>),
+    // Press the first key, then the second one within a second. Nothing else can be pressed in
+    // between. While a sequence is being checked, its first key doesn't do anything else.
+    Sequence(Key, Key),
 }
 
 impl MultiKey {
@@ -393,11 +401,15 @@ impl MultiKey {
         match self {
             MultiKey::Normal(key) => key.describe(),
             MultiKey::LCtrl(key) => format!("Ctrl+{}", key.describe()),
+            MultiKey::LShift(key) => format!("Shift+{}", key.describe()),
             MultiKey::Any(ref keys) => keys
                 .iter()
                 .map(|k| k.describe())
                 .collect::<Vec<_>>()
                 .join(", "),
+            MultiKey::Sequence(first, second) => {
+                format!("{} then {}", first.describe(), second.describe())
+            }
         }
     }
 }
@@ -410,6 +422,14 @@ pub fn lctrl(key: Key) -> Option<MultiKey> {
     Some(MultiKey::LCtrl(key))
 }
 
+pub fn lshift(key: Key) -> Option<MultiKey> {
+    Some(MultiKey::LShift(key))
+}
+
+pub fn key_sequence(first: Key, second: Key) -> Option<MultiKey> {
+    Some(MultiKey::Sequence(first, second))
+}
+
 pub fn hotkeys(keys: Vec<Key>) -> Option<MultiKey> {
     Some(MultiKey::Any(keys))
 }
@@ -1,5 +1,6 @@
+use crate::backend::{GfxCtxBackend, RenderBackend};
 use crate::{
-    svg, text, Canvas, Color, Drawable, Event, GeomBatch, GfxCtx, Line, Prerender, ScreenPt, Style,
+    text, Canvas, Color, Drawable, Event, GeomBatch, GfxCtx, Line, Prerender, ScreenPt, Style,
     Text, UserInput,
 };
 use abstutil::{elapsed_seconds, Timer, TimerSink};
@@ -77,6 +78,17 @@ impl<'a> EventCtx<'a> {
         false
     }
 
+    pub fn normal_right_click(&mut self) -> bool {
+        if self.input.has_been_consumed() {
+            return false;
+        }
+        if self.input.right_mouse_button_released() {
+            self.input.consume_event();
+            return true;
+        }
+        false
+    }
+
     fn is_dragging(&self) -> bool {
         self.canvas.drag_canvas_from.is_some() || self.canvas.drag_just_ended
     }
@@ -91,7 +103,9 @@ impl<'a> EventCtx<'a> {
         self.prerender.upload(batch)
     }
 
-    pub fn set_scale_factor(&self, scale: f64) {
+    // Sticks, even if the window later moves to a monitor with a different DPI.
+    pub fn set_scale_factor(&mut self, scale: f64) {
+        self.canvas.follow_monitor_scale_factor = false;
         self.prerender.assets.set_scale_factor(scale)
     }
 
@@ -99,6 +113,18 @@ impl<'a> EventCtx<'a> {
         *self.prerender.assets.scale_factor.borrow()
     }
 
+    pub fn set_language(&self, lang: Option<String>) {
+        self.prerender.assets.set_language(lang)
+    }
+
+    pub fn get_language(&self) -> Option<String> {
+        self.prerender.assets.get_language()
+    }
+
+    pub fn save_missing_translations(&self) -> Option<String> {
+        self.prerender.assets.save_missing_translations()
+    }
+
     pub fn monitor_scale_factor(&self) -> f64 {
         self.prerender.inner.monitor_scale_factor()
     }
@@ -180,7 +206,7 @@ impl<'a> LoadingScreen<'a> {
             text::BG_COLOR,
             Polygon::rectangle(0.8 * g.canvas.window_width, 0.8 * g.canvas.window_height),
         )]);
-        batch.append(txt.inner_render(&g.prerender.assets, svg::LOW_QUALITY));
+        batch.append(txt.render(&g.prerender.assets));
         let draw = g.upload(batch);
         g.redraw_at(
             ScreenPt::new(0.1 * g.canvas.window_width, 0.1 * g.canvas.window_height),
@@ -61,7 +61,7 @@ impl GeomBatch {
             .iter()
             .map(|(color, p)| (color.clone(), p))
             .collect();
-        let obj = g.prerender.upload_temporary(refs);
+        let obj = g.prerender.upload_retained(refs);
         g.redraw(&obj);
     }
 
@@ -73,15 +73,13 @@ impl GeomBatch {
 
     /// Sets the top-left to 0, 0. Not sure exactly when this should be used.
     pub fn autocrop(mut self) -> GeomBatch {
-        let mut bounds = Bounds::new();
-        for (_, poly) in &self.list {
-            bounds.union(poly.get_bounds());
-        }
+        let bounds: Bounds = self.list.iter().map(|(_, poly)| poly).collect();
         if bounds.min_x == 0.0 && bounds.min_y == 0.0 {
             return self;
         }
-        for (_, poly) in &mut self.list {
+        for (color, poly) in &mut self.list {
             *poly = poly.translate(-bounds.min_x, -bounds.min_y);
+            color.translate(-bounds.min_x, -bounds.min_y);
         }
         self
     }
@@ -107,10 +105,7 @@ impl GeomBatch {
         if self.is_empty() {
             return ScreenDims::new(0.0, 0.0);
         }
-        let mut bounds = Bounds::new();
-        for (_, poly) in &self.list {
-            bounds.union(poly.get_bounds());
-        }
+        let bounds: Bounds = self.list.iter().map(|(_, poly)| poly).collect();
         if self.autocrop_dims {
             ScreenDims::new(bounds.width(), bounds.height())
         } else {
@@ -126,6 +121,12 @@ impl GeomBatch {
         batch
     }
 
+    /// Returns a batch containing an SVG from a file, scaled up. Nothing is cached, so this works
+    /// without a Prerender, like when building up geometry ahead of time.
+    pub fn from_svg(filename: &str, scale: f64) -> GeomBatch {
+        svg::load_svg_uncached(filename, scale).0
+    }
+
     /// Returns a batch containing an SVG from a file.
     pub fn mapspace_svg(prerender: &Prerender, filename: &str) -> GeomBatch {
         svg::load_svg(prerender, filename, 1.0).0
@@ -136,11 +137,36 @@ impl GeomBatch {
         svg::load_svg(prerender, filename, *prerender.assets.scale_factor.borrow()).0
     }
 
+    /// Returns a batch containing an icon from a sheet loaded at startup, at its original size.
+    pub fn mapspace_icon(prerender: &Prerender, name: &str) -> GeomBatch {
+        prerender.assets.get_icon(name)
+    }
+
+    /// Returns a batch containing an icon from a sheet loaded at startup. Uses the current
+    /// screen's scale factor.
+    pub fn screenspace_icon(prerender: &Prerender, name: &str) -> GeomBatch {
+        prerender
+            .assets
+            .get_icon(name)
+            .scale(*prerender.assets.scale_factor.borrow())
+    }
+
     /// Transforms all colors in a batch.
     pub fn color(mut self, transformation: RewriteColor) -> GeomBatch {
         for (fancy, _) in &mut self.list {
-            if let FancyColor::RGBA(ref mut c) = fancy {
-                *c = transformation.apply(*c);
+            match fancy {
+                FancyColor::RGBA(ref mut c) => {
+                    *c = transformation.apply(*c);
+                }
+                FancyColor::Gradient {
+                    ref mut from,
+                    ref mut to,
+                    ..
+                } => {
+                    *from = transformation.apply(*from);
+                    *to = transformation.apply(*to);
+                }
+                FancyColor::Hatching | FancyColor::LinearGradient(_) => {}
             }
         }
         self
@@ -156,13 +182,15 @@ impl GeomBatch {
 
     /// Translates the batch by some offset.
     pub fn translate(mut self, dx: f64, dy: f64) -> GeomBatch {
-        for (_, poly) in &mut self.list {
+        for (color, poly) in &mut self.list {
             *poly = poly.translate(dx, dy);
+            color.translate(dx, dy);
         }
         self
     }
 
-    /// Rotates each polygon in the batch relative to the center of that polygon.
+    /// Rotates each polygon in the batch relative to the center of that polygon. Gradients don't
+    /// rotate along with them.
     pub fn rotate(mut self, angle: Angle) -> GeomBatch {
         for (_, poly) in &mut self.list {
             *poly = poly.rotate(angle);
@@ -172,8 +200,9 @@ impl GeomBatch {
 
     /// Scales the batch by some factor.
     pub fn scale(mut self, factor: f64) -> GeomBatch {
-        for (_, poly) in &mut self.list {
+        for (color, poly) in &mut self.list {
             *poly = poly.scale(factor);
+            color.scale(factor);
         }
         self
     }
@@ -0,0 +1,237 @@
+use crate::text::Font;
+use geom::{Polygon, Pt2D};
+use lyon::math::Point;
+use lyon::path::PathEvent;
+use lyon::tessellation;
+use lyon::tessellation::geometry_builder::{simple_builder, VertexBuffers};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// The halo is the outline of each glyph stroked this thick, relative to the font size.
+const HALO_WIDTH: f64 = 0.2;
+// Relative to the font size. Small enough that glyphs still look smooth when zoomed way in.
+const TOLERANCE: f64 = 0.002;
+
+// Lays out text glyph-by-glyph and caches the tessellated outline of every glyph, so each glyph is
+// only tessellated once no matter what size or position it's drawn at. Positions aren't rounded to
+// whole pixels, and kerning is applied between pairs of glyphs, so widths come out exactly as
+// drawn.
+pub(crate) struct GlyphCache {
+    // The raw TTF files. Parsing a face from these is cheap.
+    fonts: HashMap<Font, Vec<u8>>,
+    // In font units, with y pointing down and the baseline at y = 0. None for glyphs without an
+    // outline, like spaces.
+    glyphs: RefCell<HashMap<(Font, u16), Option<Glyph>>>,
+}
+
+struct Glyph {
+    fill: Polygon,
+    halo: Polygon,
+}
+
+impl GlyphCache {
+    pub fn new(font_dir: &str) -> GlyphCache {
+        let mut fonts = HashMap::new();
+        for (font, filename) in vec![
+            (Font::BungeeInlineRegular, "BungeeInline-Regular.ttf"),
+            (Font::BungeeRegular, "Bungee-Regular.ttf"),
+            (Font::OverpassBold, "Overpass-Bold.ttf"),
+            (Font::OverpassRegular, "Overpass-Regular.ttf"),
+            (Font::OverpassSemiBold, "Overpass-SemiBold.ttf"),
+        ] {
+            // Fonts can't be read from the filesystem on the web yet. Text just won't show up.
+            if let Ok(raw) = abstutil::slurp_file(&format!("{}/{}", font_dir, filename)) {
+                fonts.insert(font, raw);
+            }
+        }
+        GlyphCache {
+            fonts,
+            glyphs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn face(&self, font: Font) -> Option<ttf_parser::Face> {
+        ttf_parser::Face::from_slice(self.fonts.get(&font)?, 0).ok()
+    }
+
+    // Doesn't include the line gap
+    pub fn line_height(&self, font: Font, size: f64) -> Option<f64> {
+        let face = self.face(font)?;
+        let height = f64::from(face.ascender()) - f64::from(face.descender());
+        Some(height * size / units_per_em(&face))
+    }
+
+    // How far the text advances horizontally, without tessellating anything.
+    pub fn width(&self, font: Font, size: f64, text: &str) -> f64 {
+        if let Some(face) = self.face(font) {
+            layout(&face, size, text).1
+        } else {
+            0.0
+        }
+    }
+
+    // Calls the callback with the fill and halo of every glyph, scaled to the font size and
+    // placed along the baseline starting at start_x. Returns where the next glyph would go.
+    pub fn render<F: FnMut(Polygon, Polygon)>(
+        &self,
+        font: Font,
+        size: f64,
+        start_x: f64,
+        text: &str,
+        mut cb: F,
+    ) -> f64 {
+        let face = if let Some(face) = self.face(font) {
+            face
+        } else {
+            return start_x;
+        };
+        let upem = units_per_em(&face);
+        let scale = size / upem;
+        let (placements, width) = layout(&face, size, text);
+
+        let mut cache = self.glyphs.borrow_mut();
+        for (id, x) in placements {
+            let glyph = cache
+                .entry((font, id.0))
+                .or_insert_with(|| tessellate(&face, id, upem));
+            if let Some(glyph) = glyph {
+                cb(
+                    glyph.fill.scale(scale).translate(start_x + x, 0.0),
+                    glyph.halo.scale(scale).translate(start_x + x, 0.0),
+                );
+            }
+        }
+        start_x + width
+    }
+}
+
+fn units_per_em(face: &ttf_parser::Face) -> f64 {
+    f64::from(face.units_per_em().unwrap_or(1000))
+}
+
+// Returns each glyph with its x offset, and the total advance.
+fn layout(
+    face: &ttf_parser::Face,
+    size: f64,
+    text: &str,
+) -> (Vec<(ttf_parser::GlyphId, f64)>, f64) {
+    let scale = size / units_per_em(face);
+    let mut placements = Vec::new();
+    let mut x = 0.0;
+    let mut prev: Option<ttf_parser::GlyphId> = None;
+    for c in text.chars() {
+        // Skip anything missing from the font, rather than drawing a box
+        let id = if let Some(id) = face.glyph_index(c) {
+            id
+        } else {
+            continue;
+        };
+        if let Some(prev) = prev {
+            if let Some(kern) = face.glyphs_kerning(prev, id) {
+                x += f64::from(kern) * scale;
+            }
+        }
+        placements.push((id, x));
+        x += f64::from(face.glyph_hor_advance(id).unwrap_or(0)) * scale;
+        prev = Some(id);
+    }
+    (placements, x)
+}
+
+fn tessellate(face: &ttf_parser::Face, id: ttf_parser::GlyphId, upem: f64) -> Option<Glyph> {
+    let mut outline = Outline {
+        events: Vec::new(),
+        first: Point::new(0.0, 0.0),
+        prev: Point::new(0.0, 0.0),
+    };
+    face.outline_glyph(id, &mut outline)?;
+
+    let tolerance = (TOLERANCE * upem) as f32;
+    let mut fill = VertexBuffers::new();
+    tessellation::FillTessellator::new()
+        .tessellate(
+            outline.events.iter().cloned(),
+            &tessellation::FillOptions::tolerance(tolerance)
+                .with_fill_rule(tessellation::FillRule::NonZero),
+            &mut simple_builder(&mut fill),
+        )
+        .ok()?;
+    let mut halo = VertexBuffers::new();
+    tessellation::StrokeTessellator::new()
+        .tessellate(
+            outline.events.iter().cloned(),
+            &tessellation::StrokeOptions::tolerance(tolerance)
+                .with_line_width((HALO_WIDTH * upem) as f32)
+                .with_line_join(tessellation::LineJoin::Round),
+            &mut simple_builder(&mut halo),
+        )
+        .ok()?;
+    Some(Glyph {
+        fill: to_polygon(fill),
+        halo: to_polygon(halo),
+    })
+}
+
+fn to_polygon(mesh: VertexBuffers<Point, u16>) -> Polygon {
+    Polygon::precomputed(
+        mesh.vertices
+            .into_iter()
+            .map(|v| Pt2D::new(f64::from(v.x), f64::from(v.y)))
+            .collect(),
+        mesh.indices.into_iter().map(|idx| idx as usize).collect(),
+    )
+}
+
+// Fonts have y pointing up; flip it here.
+struct Outline {
+    events: Vec<PathEvent>,
+    first: Point,
+    prev: Point,
+}
+
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.first = Point::new(x, -y);
+        self.prev = self.first;
+        self.events.push(PathEvent::Begin { at: self.first });
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = Point::new(x, -y);
+        self.events.push(PathEvent::Line {
+            from: self.prev,
+            to,
+        });
+        self.prev = to;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let to = Point::new(x, -y);
+        self.events.push(PathEvent::Quadratic {
+            from: self.prev,
+            ctrl: Point::new(x1, -y1),
+            to,
+        });
+        self.prev = to;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let to = Point::new(x, -y);
+        self.events.push(PathEvent::Cubic {
+            from: self.prev,
+            ctrl1: Point::new(x1, -y1),
+            ctrl2: Point::new(x2, -y2),
+            to,
+        });
+        self.prev = to;
+    }
+
+    fn close(&mut self) {
+        self.events.push(PathEvent::End {
+            last: self.prev,
+            first: self.first,
+            close: true,
+        });
+        self.prev = self.first;
+    }
+}
@@ -0,0 +1,167 @@
+use crate::backend::RenderBackend;
+use crate::{Drawable, GeomBatch, Prerender};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::{Rc, Weak};
+
+// Like a Drawable, but the GPU copy can be thrown away when memory gets tight. The geometry stays
+// on the CPU, and gets uploaded again the next time it's drawn. Meant for big things that aren't
+// drawn all the time, like everything only visible when zoomed in.
+pub struct CachedDrawable {
+    id: usize,
+    batch: GeomBatch,
+    cache: Weak<RefCell<GpuCache>>,
+}
+
+impl CachedDrawable {
+    // Nothing is uploaded until the first draw.
+    pub fn new(prerender: &Prerender, batch: GeomBatch) -> CachedDrawable {
+        let mut cache = prerender.gpu_cache.borrow_mut();
+        cache.next_id += 1;
+        CachedDrawable {
+            id: cache.next_id,
+            batch,
+            cache: Rc::downgrade(&prerender.gpu_cache),
+        }
+    }
+}
+
+impl Drop for CachedDrawable {
+    fn drop(&mut self) {
+        // There's no backend here to free the GPU copy with, so hand it to the cache. Some
+        // backends don't free anything when a Drawable is just dropped.
+        if let Some(cache) = self.cache.upgrade() {
+            let mut cache = cache.borrow_mut();
+            if let Some(r) = cache.resident.remove(&self.id) {
+                if let Ok(draw) = Rc::try_unwrap(r.draw) {
+                    cache.dropped.push(draw);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct GpuCache {
+    // In bytes. None means never evict anything.
+    budget: Option<usize>,
+    next_id: usize,
+    // Counts up every time the screen is drawn
+    frame: usize,
+    resident: BTreeMap<usize, Resident>,
+    num_evictions: usize,
+    // From CachedDrawables that no longer exist, waiting to be freed
+    dropped: Vec<Drawable>,
+}
+
+struct Resident {
+    draw: Rc<Drawable>,
+    bytes: usize,
+    last_drawn: usize,
+}
+
+impl GpuCache {
+    pub fn new(budget: Option<usize>) -> GpuCache {
+        GpuCache {
+            budget,
+            next_id: 0,
+            frame: 0,
+            resident: BTreeMap::new(),
+            num_evictions: 0,
+            dropped: Vec::new(),
+        }
+    }
+
+    // Returns everything that should be freed now.
+    pub fn next_frame(&mut self) -> Vec<Drawable> {
+        self.frame += 1;
+        std::mem::replace(&mut self.dropped, Vec::new())
+    }
+
+    pub fn bytes_resident(&self) -> usize {
+        self.resident.values().map(|r| r.bytes).sum()
+    }
+
+    // Throw away the least recently drawn things until we're under budget. Anything drawn this
+    // frame stays, even if that means going over.
+    fn evict(&mut self) -> Vec<Drawable> {
+        let mut freed = Vec::new();
+        let budget = match self.budget {
+            Some(b) => b,
+            None => {
+                return freed;
+            }
+        };
+        let mut total = self.bytes_resident();
+        while total > budget {
+            let (id, last_drawn) = self
+                .resident
+                .iter()
+                .map(|(id, r)| (*id, r.last_drawn))
+                .min_by_key(|(_, last_drawn)| *last_drawn)
+                .unwrap();
+            if last_drawn == self.frame {
+                break;
+            }
+            let r = self.resident.remove(&id).unwrap();
+            total -= r.bytes;
+            self.num_evictions += 1;
+            if let Ok(draw) = Rc::try_unwrap(r.draw) {
+                freed.push(draw);
+            }
+        }
+        freed
+    }
+}
+
+impl Prerender {
+    // Uploads if needed.
+    pub(crate) fn get_cached(&self, obj: &CachedDrawable) -> Rc<Drawable> {
+        let frame = self.gpu_cache.borrow().frame;
+        if let Some(r) = self.gpu_cache.borrow_mut().resident.get_mut(&obj.id) {
+            r.last_drawn = frame;
+            return r.draw.clone();
+        }
+
+        let list: Vec<_> = obj.batch.list.iter().map(|(c, p)| (c.clone(), p)).collect();
+        let bytes = crate::drawing::bytes_for(&list);
+        let draw = Rc::new(self.actually_upload(true, list));
+        let freed = {
+            let mut cache = self.gpu_cache.borrow_mut();
+            cache.resident.insert(
+                obj.id,
+                Resident {
+                    draw: draw.clone(),
+                    bytes,
+                    last_drawn: frame,
+                },
+            );
+            cache.evict()
+        };
+        for obj in freed {
+            self.inner.free(obj);
+        }
+        draw
+    }
+
+    pub fn set_gpu_memory_budget(&self, bytes: Option<usize>) {
+        let freed = {
+            let mut cache = self.gpu_cache.borrow_mut();
+            cache.budget = bytes;
+            cache.evict()
+        };
+        for obj in freed {
+            self.inner.free(obj);
+        }
+    }
+
+    pub fn get_gpu_memory_budget(&self) -> Option<usize> {
+        self.gpu_cache.borrow().budget
+    }
+
+    // How much CachedDrawables take up on the GPU right now, and how many times something's been
+    // evicted
+    pub fn get_gpu_cache_stats(&self) -> (usize, usize) {
+        let cache = self.gpu_cache.borrow();
+        (cache.bytes_resident(), cache.num_evictions)
+    }
+}
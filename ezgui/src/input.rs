@@ -1,6 +1,10 @@
 use crate::{Canvas, Event, Key, MultiKey, ScreenPt};
+use abstutil::elapsed_seconds;
 use geom::Duration;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+
+// The second key of a MultiKey::Sequence has to be pressed within this many seconds of the first.
+const SEQUENCE_TIMEOUT: f64 = 1.0;
 
 // As we check for user input, record the input and the thing that would happen. This will let us
 // build up some kind of OSD of possible actions.
@@ -12,16 +16,53 @@ pub struct UserInput {
     reserved_keys: HashMap<Key, String>,
 
     lctrl_held: bool,
+    lshift_held: bool,
+    prev_key: Option<Key>,
+    // This key press finishes a sequence, so only that sequence can use it.
+    finishes_sequence: bool,
+    // Some sequence was pressed while handling this event.
+    pub(crate) sequence_done: bool,
+    // Every sequence checked while handling this event. Their first keys are reserved for the
+    // next event.
+    pub(crate) sequences: BTreeSet<(Key, Key)>,
 }
 
 impl UserInput {
     pub(crate) fn new(event: Event, canvas: &Canvas) -> UserInput {
+        let prev_key = canvas
+            .prev_key
+            .filter(|(_, when)| elapsed_seconds(*when) < SEQUENCE_TIMEOUT)
+            .map(|(key, _)| key);
+
+        // Sequences take priority over everything else. The first key of a sequence is swallowed
+        // here, so it can't also trigger a plain hotkey.
+        let mut event_consumed = false;
+        let mut finishes_sequence = false;
+        if let Event::KeyPress(pressed) = event {
+            if !canvas.lctrl_held && !canvas.lshift_held {
+                if canvas
+                    .sequences
+                    .iter()
+                    .any(|(first, second)| prev_key == Some(*first) && pressed == *second)
+                {
+                    finishes_sequence = true;
+                } else if canvas.sequences.iter().any(|(first, _)| pressed == *first) {
+                    event_consumed = true;
+                }
+            }
+        }
+
         UserInput {
             event,
-            event_consumed: false,
+            event_consumed,
             important_actions: Vec::new(),
             reserved_keys: HashMap::new(),
             lctrl_held: canvas.lctrl_held,
+            lshift_held: canvas.lshift_held,
+            prev_key,
+            finishes_sequence,
+            sequence_done: false,
+            sequences: BTreeSet::new(),
         }
     }
 
@@ -30,7 +71,7 @@ impl UserInput {
 
         self.important_actions.push((key, action.to_string()));
 
-        if self.event_consumed {
+        if self.event_consumed || self.finishes_sequence {
             return false;
         }
 
@@ -42,7 +83,7 @@ impl UserInput {
     }
 
     pub fn any_key_pressed(&mut self) -> Option<Key> {
-        if self.event_consumed {
+        if self.event_consumed || self.finishes_sequence {
             return None;
         }
 
@@ -56,7 +97,7 @@ impl UserInput {
     pub fn unimportant_key_pressed(&mut self, key: Key, action: &str) -> bool {
         self.reserve_key(key, action);
 
-        if self.event_consumed {
+        if self.event_consumed || self.finishes_sequence {
             return false;
         }
 
@@ -69,19 +110,33 @@ impl UserInput {
 
     pub fn new_was_pressed(&mut self, multikey: &MultiKey) -> bool {
         // TODO Reserve?
+        // Remember sequences even if the event is already used, so their first key is still
+        // swallowed next time.
+        if let MultiKey::Sequence(first, second) = multikey {
+            self.sequences.insert((*first, *second));
+        }
 
         if self.event_consumed {
             return false;
         }
 
         if let Event::KeyPress(pressed) = self.event {
+            let no_modifiers = !self.lctrl_held && !self.lshift_held;
+            let plain = no_modifiers && !self.finishes_sequence;
             let same = match multikey {
-                MultiKey::Normal(key) => pressed == *key && !self.lctrl_held,
+                MultiKey::Normal(key) => pressed == *key && plain,
                 MultiKey::LCtrl(key) => pressed == *key && self.lctrl_held,
-                MultiKey::Any(ref keys) => !self.lctrl_held && keys.contains(&pressed),
+                MultiKey::LShift(key) => pressed == *key && self.lshift_held && !self.lctrl_held,
+                MultiKey::Any(ref keys) => plain && keys.contains(&pressed),
+                MultiKey::Sequence(first, second) => {
+                    pressed == *second && self.prev_key == Some(*first) && no_modifiers
+                }
             };
             if same {
                 self.consume_event();
+                if let MultiKey::Sequence(_, _) = multikey {
+                    self.sequence_done = true;
+                }
                 return true;
             }
         }
@@ -109,6 +164,9 @@ impl UserInput {
     pub fn left_mouse_button_released(&mut self) -> bool {
         self.event == Event::LeftMouseButtonUp
     }
+    pub fn right_mouse_button_released(&mut self) -> bool {
+        self.event == Event::RightMouseButtonUp
+    }
 
     pub fn window_lost_cursor(&self) -> bool {
         self.event == Event::WindowLostCursor
@@ -128,9 +186,10 @@ impl UserInput {
         None
     }
 
+    // Also true when the scale factor changes, since everything needs to be laid out again.
     pub fn is_window_resized(&self) -> bool {
         match self.event {
-            Event::WindowResized(_, _) => true,
+            Event::WindowResized(_, _) | Event::ScaleFactorChanged(_) => true,
             _ => false,
         }
     }
@@ -12,6 +12,7 @@
 //! * [`DrawWithTooltips`] - draw static geometry, with mouse tooltips in certain regions
 //! * [`Dropdown`] - a button that expands into a menu
 //! * [`Filler`] - just carve out space in the layout for something else
+//! * [`Histogram`] - bucket and compare distributions
 //! * [`JustDraw`] (argh private) - just draw text, `GeomBatch`es, SVGs
 //! * [`LinePlot`] - visualize 2 variables with a line plot
 //! * [`Menu`] - select something from a menu, with keybindings
@@ -19,23 +20,33 @@
 //! * [`ScatterPlot`] - visualize 2 variables with a scatter plot
 //! * [`Slider`] - horizontal and vertical sliders
 //! * [`Spinner`] - numeric input with up/down buttons
+//! * [`Table`] - rows with sortable columns, split into pages
 //! * [`TexBox`] - single line text entry
+//! * [`UnitSpinner`] - like a spinner, but for values with units, and allowing text entry
 
 mod assets;
+mod backend;
 #[cfg(feature = "glium-backend")]
 mod backend_glium;
 #[cfg(feature = "glow-backend")]
 mod backend_glow;
+#[cfg(feature = "headless-backend")]
+mod backend_headless;
 #[cfg(feature = "wasm-backend")]
 mod backend_wasm;
+#[cfg(feature = "wgpu-backend")]
+mod backend_wgpu;
 mod canvas;
 mod color;
 mod drawing;
 mod event;
 mod event_ctx;
 mod geom;
+mod glyphs;
+mod gpu_cache;
 mod input;
 mod managed;
+mod retained;
 mod runner;
 mod screen_geom;
 mod style;
@@ -46,35 +57,52 @@ mod widgets;
 
 pub use crate::backend::Drawable;
 pub use crate::canvas::{Canvas, HorizontalAlignment, VerticalAlignment};
-pub use crate::color::{Color, FancyColor, LinearGradient};
-pub use crate::drawing::{GfxCtx, Prerender};
-pub use crate::event::{hotkey, hotkeys, lctrl, Event, Key, MultiKey};
+pub use crate::color::{Color, FancyColor, GradientAxis, LinearGradient};
+pub use crate::drawing::{BlendMode, GfxCtx, Instance, Prerender, NUM_MAP_LAYERS};
+pub use crate::event::{hotkey, hotkeys, key_sequence, lctrl, lshift, Event, Key, MultiKey};
 pub use crate::event_ctx::EventCtx;
 pub use crate::geom::{GeomBatch, RewriteColor};
+pub use crate::gpu_cache::CachedDrawable;
 pub use crate::input::UserInput;
 pub use crate::managed::{Composite, Widget};
 pub use crate::runner::{run, EventLoopMode, Settings, GUI};
 pub use crate::screen_geom::{ScreenDims, ScreenPt, ScreenRectangle};
 pub use crate::style::Style;
-pub use crate::text::{Line, Text, TextExt, TextSpan};
+pub use crate::text::{Line, Text, TextBatch, TextExt, TextSpan};
+pub use crate::tools::bench::{
+    bench_tessellation, bench_upload, make_shapes, BenchResult, DrawBench,
+};
+pub use crate::tools::golden::{compare_to_golden, rasterize};
+#[cfg(feature = "headless-backend")]
+pub use crate::tools::headless::Headless;
+pub use crate::tools::modal_stack::{Modal, ModalStack};
+pub use crate::tools::translations::available_languages;
 pub use crate::tools::warper::Warper;
 pub use crate::tools::wizard::{Choice, Wizard, WrappedWizard};
 pub use crate::widgets::autocomplete::Autocomplete;
 pub use crate::widgets::button::Btn;
 pub(crate) use crate::widgets::button::Button;
 pub use crate::widgets::checkbox::Checkbox;
+pub use crate::widgets::color_picker::ColorPicker;
 pub use crate::widgets::compare_times::CompareTimes;
 pub(crate) use crate::widgets::dropdown::Dropdown;
 pub use crate::widgets::filler::Filler;
+pub use crate::widgets::histogram::Histogram;
 pub use crate::widgets::just_draw::DrawWithTooltips;
 pub(crate) use crate::widgets::just_draw::JustDraw;
-pub use crate::widgets::line_plot::{LinePlot, PlotOptions, Series};
+pub use crate::widgets::line_plot::{Axis, LinePlot, PlotOptions, Series};
 pub(crate) use crate::widgets::menu::Menu;
+pub use crate::widgets::modal_dialog::ModalDialog;
 pub use crate::widgets::persistent_split::PersistentSplit;
+pub use crate::widgets::radial_menu::RadialMenu;
 pub use crate::widgets::scatter_plot::ScatterPlot;
+pub use crate::widgets::scrolling_panel::ScrollingPanel;
 pub use crate::widgets::slider::{AreaSlider, Slider};
 pub use crate::widgets::spinner::Spinner;
+pub use crate::widgets::table::{Table, TableRow};
+pub use crate::widgets::tabs::TabContainer;
 pub(crate) use crate::widgets::text_box::TextBox;
+pub use crate::widgets::unit_spinner::{SpinnerValue, UnitSpinner};
 pub use crate::widgets::{Outcome, WidgetImpl, WidgetOutput};
 
 pub(crate) enum InputResult<T: Clone> {
@@ -82,14 +110,3 @@ pub(crate) enum InputResult<T: Clone> {
     StillActive,
     Done(String, T),
 }
-
-mod backend {
-    #[cfg(feature = "glium-backend")]
-    pub use crate::backend_glium::*;
-
-    #[cfg(feature = "glow-backend")]
-    pub use crate::backend_glow::*;
-
-    #[cfg(feature = "wasm-backend")]
-    pub use crate::backend_wasm::*;
-}
@@ -1,9 +1,9 @@
 use crate::widgets::containers::{Container, Nothing};
 use crate::{
-    AreaSlider, Autocomplete, Button, Checkbox, Choice, Color, Drawable, Dropdown, EventCtx,
-    GeomBatch, GfxCtx, HorizontalAlignment, JustDraw, Menu, Outcome, PersistentSplit, RewriteColor,
-    ScreenDims, ScreenPt, ScreenRectangle, Slider, Spinner, TextBox, VerticalAlignment, WidgetImpl,
-    WidgetOutput,
+    AreaSlider, Autocomplete, Button, Checkbox, Choice, Color, ColorPicker, Drawable, Dropdown,
+    EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, JustDraw, Menu, Outcome, PersistentSplit,
+    RewriteColor, ScreenDims, ScreenPt, ScreenRectangle, Slider, Spinner, SpinnerValue, TextBox,
+    UnitSpinner, VerticalAlignment, WidgetImpl, WidgetOutput,
 };
 use geom::{Distance, Polygon};
 use std::collections::HashSet;
@@ -249,6 +249,9 @@ impl Widget {
     pub fn draw_svg_transform(ctx: &EventCtx, filename: &str, rewrite: RewriteColor) -> Widget {
         JustDraw::svg_transform(ctx, filename, rewrite)
     }
+    pub fn draw_icon(ctx: &EventCtx, name: &str) -> Widget {
+        Widget::draw_batch(ctx, GeomBatch::screenspace_icon(ctx.prerender, name))
+    }
 
     // TODO Likewise
     pub fn text_entry(ctx: &EventCtx, prefilled: String, exclusive_focus: bool) -> Widget {
@@ -779,6 +782,20 @@ impl Composite {
         self.find::<Checkbox>(name).enabled
     }
 
+    // The new state, if the last call to event() flipped the checkbox. Only meaningful when that
+    // event() returned None, since a click elsewhere stops other widgets from seeing the event.
+    pub fn checkbox_toggled(&self, name: &str) -> Option<bool> {
+        let cb = self.find::<Checkbox>(name);
+        if cb.changed {
+            Some(cb.enabled)
+        } else {
+            None
+        }
+    }
+
+    pub fn color_picker(&self, name: &str) -> Color {
+        self.find::<ColorPicker>(name).color()
+    }
     pub fn text_box(&self, name: &str) -> String {
         self.find::<TextBox>(name).get_line()
     }
@@ -787,6 +804,10 @@ impl Composite {
         self.find::<Spinner>(name).current
     }
 
+    pub fn unit_spinner<T: SpinnerValue>(&self, name: &str) -> T {
+        self.find::<UnitSpinner<T>>(name).current
+    }
+
     pub fn dropdown_value<T: 'static + PartialEq + Clone>(&self, name: &str) -> T {
         self.find::<Dropdown<T>>(name).current_value()
     }
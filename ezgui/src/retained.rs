@@ -0,0 +1,102 @@
+use crate::backend::RenderBackend;
+use crate::{Drawable, FancyColor, Prerender};
+use geom::Polygon;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+// GfxCtx::draw_polygon and GeomBatch::draw are immediate-mode, but usually the same geometry gets
+// drawn again the next frame. Rather than uploading fresh buffers every time, remember recent
+// uploads by their contents and reuse them. Whatever wasn't drawn last frame is freed, so anything
+// that changes just gets uploaded again.
+pub(crate) struct RetainedPool {
+    entries: HashMap<u64, Entry>,
+    frame: usize,
+}
+
+struct Entry {
+    draw: Rc<Drawable>,
+    last_used: usize,
+}
+
+impl RetainedPool {
+    pub fn new() -> RetainedPool {
+        RetainedPool {
+            entries: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    // Returns everything that wasn't used this frame.
+    fn end_frame(&mut self) -> Vec<Drawable> {
+        let frame = self.frame;
+        let stale: Vec<u64> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.last_used != frame)
+            .map(|(key, _)| *key)
+            .collect();
+        self.frame += 1;
+        stale
+            .into_iter()
+            .filter_map(|key| Rc::try_unwrap(self.entries.remove(&key).unwrap().draw).ok())
+            .collect()
+    }
+}
+
+impl Prerender {
+    pub(crate) fn upload_retained(&self, list: Vec<(FancyColor, &Polygon)>) -> Rc<Drawable> {
+        let key = fingerprint(&list);
+        {
+            let mut pool = self.retained.borrow_mut();
+            let frame = pool.frame;
+            if let Some(e) = pool.entries.get_mut(&key) {
+                e.last_used = frame;
+                return e.draw.clone();
+            }
+        }
+
+        let draw = Rc::new(self.upload_temporary(list));
+        let mut pool = self.retained.borrow_mut();
+        let last_used = pool.frame;
+        pool.entries.insert(
+            key,
+            Entry {
+                draw: draw.clone(),
+                last_used,
+            },
+        );
+        draw
+    }
+
+    // Called after every frame is drawn
+    pub(crate) fn end_frame(&self) {
+        let dropped = self.gpu_cache.borrow_mut().next_frame();
+        let stale = self.retained.borrow_mut().end_frame();
+        for obj in dropped.into_iter().chain(stale) {
+            self.inner.free(obj);
+        }
+    }
+}
+
+// Exactly what would be uploaded, so any change at all means uploading again.
+fn fingerprint(list: &[(FancyColor, &Polygon)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (color, poly) in list {
+        let (pts, indices) = poly.raw_for_rendering();
+        hasher.write_usize(pts.len());
+        for pt in pts {
+            hasher.write_u64(pt.x().to_bits());
+            hasher.write_u64(pt.y().to_bits());
+            for x in &color.style(*pt) {
+                hasher.write_u32(x.to_bits());
+            }
+        }
+        hasher.write_usize(indices.len());
+        for idx in indices {
+            hasher.write_usize(*idx);
+        }
+    }
+    hasher.finish()
+}
@@ -1,11 +1,15 @@
 use crate::assets::Assets;
-use crate::tools::screenshot::screenshot_everything;
+use crate::backend::{GfxCtxBackend, PrerenderInnards, RenderBackend};
+use crate::gpu_cache::GpuCache;
+use crate::retained::RetainedPool;
+use crate::tools::screenshot::{save_frame, screenshot_everything};
 use crate::{text, Canvas, Event, EventCtx, GfxCtx, Key, Prerender, Style, UserInput};
 use geom::Duration;
 use image::{GenericImageView, Pixel};
 use instant::Instant;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::panic;
+use std::rc::Rc;
 use winit::window::Icon;
 
 const UPDATE_FREQUENCY: std::time::Duration = std::time::Duration::from_millis(1000 / 30);
@@ -34,16 +38,21 @@ pub enum EventLoopMode {
 pub(crate) struct State<G: GUI> {
     pub(crate) gui: G,
     pub(crate) canvas: Canvas,
-    style: Style,
+    pub(crate) style: Style,
 }
 
 impl<G: GUI> State<G> {
     // The bool indicates if the input was actually used.
-    fn event(&mut self, mut ev: Event, prerender: &Prerender) -> (EventLoopMode, bool) {
+    pub(crate) fn event(&mut self, mut ev: Event, prerender: &Prerender) -> (EventLoopMode, bool) {
         if let Event::MouseWheelScroll(dx, dy) = ev {
-            if self.canvas.invert_scroll {
-                ev = Event::MouseWheelScroll(-dx, -dy);
-            }
+            ev = Event::MouseWheelScroll(
+                if self.canvas.invert_horiz_scroll {
+                    -dx
+                } else {
+                    dx
+                },
+                if self.canvas.invert_scroll { -dy } else { dy },
+            );
         }
 
         // Always reset the cursor, unless we're handling an update event. If we're hovering on a
@@ -71,6 +80,18 @@ impl<G: GUI> State<G> {
 
         // Update some ezgui state that's stashed in Canvas for sad reasons.
         {
+            if let Event::ScaleFactorChanged(scale) = input.event {
+                if self.canvas.follow_monitor_scale_factor {
+                    println!("Monitor scale factor changed to {}", scale);
+                    prerender.assets.set_scale_factor(scale);
+                }
+                // The physical size of the window changes too, but there might not be a separate
+                // resize event.
+                let (width, height) = prerender.inner.get_inner_size();
+                prerender.inner.window_resized(width, height);
+                self.canvas.window_width = width;
+                self.canvas.window_height = height;
+            }
             if let Event::WindowResized(width, height) = input.event {
                 let inner_size = prerender.inner.get_inner_size();
                 println!(
@@ -105,6 +126,7 @@ impl<G: GUI> State<G> {
             if let Some(pt) = input.get_moved_mouse() {
                 self.canvas.cursor_x = pt.x;
                 self.canvas.cursor_y = pt.y;
+                self.canvas.cursor_still_since = Instant::now();
             }
 
             if input.event == Event::WindowGainedCursor {
@@ -124,11 +146,28 @@ impl<G: GUI> State<G> {
                 style: &mut self.style,
             };
             let evloop = self.gui.event(&mut ctx);
+            if let Event::KeyPress(key) = ev {
+                if key != Key::LeftControl && key != Key::LeftShift {
+                    // A finished sequence shouldn't start another one
+                    ctx.canvas.prev_key = if ctx.input.sequence_done {
+                        None
+                    } else {
+                        Some((key, Instant::now()))
+                    };
+                }
+            }
+            ctx.canvas.sequences = std::mem::take(&mut ctx.input.sequences);
             // TODO We should always do has_been_consumed, but various hacks prevent this from being
             // true. For now, just avoid the specific annoying redraw case when a KeyRelease event
             // is unused.
+            //
+            // Update events are just time passing. Anything that animates or steps the sim uses
+            // the event up, so if nobody did, nothing changed and the frame can be skipped. This
+            // matters when something asks for Animation mode but is effectively idle. A tooltip
+            // that finished waiting still needs a frame, though.
             let input_used = match ev {
                 Event::KeyRelease(_) => ctx.input.has_been_consumed(),
+                Event::Update(_) => ctx.input.has_been_consumed() || ctx.canvas.tooltip_ready(),
                 _ => true,
             };
             (evloop, input_used)
@@ -164,7 +203,12 @@ impl<G: GUI> State<G> {
             );
         }
 
+        let screenshot_path = g.screenshot_path.take();
         g.inner.finish();
+        if let Some(path) = screenshot_path {
+            save_frame(prerender, &path);
+        }
+        prerender.end_frame();
         naming_hint
     }
 }
@@ -177,6 +221,9 @@ pub struct Settings {
     dump_raw_events: bool,
     scale_factor: Option<f64>,
     window_icon: Option<String>,
+    icon_sheets: Vec<String>,
+    language: Option<String>,
+    gpu_memory_budget: Option<usize>,
 }
 
 impl Settings {
@@ -189,6 +236,9 @@ impl Settings {
             dump_raw_events: false,
             scale_factor: None,
             window_icon: None,
+            icon_sheets: Vec::new(),
+            language: None,
+            gpu_memory_budget: None,
         }
     }
 
@@ -213,13 +263,52 @@ impl Settings {
     pub fn window_icon(&mut self, path: &str) {
         self.window_icon = Some(path.to_string());
     }
+
+    // Icons from this sheet can be drawn by name. See Assets::load_icon_sheet for the format.
+    pub fn icon_sheet(&mut self, path: &str) {
+        self.icon_sheets.push(path.to_string());
+    }
+
+    pub fn language(&mut self, lang: String) {
+        self.language = Some(lang);
+    }
+
+    // Past this, the least recently drawn CachedDrawables are evicted from the GPU.
+    pub fn gpu_memory_budget_mb(&mut self, mb: usize) {
+        self.gpu_memory_budget = Some(mb * 1024 * 1024);
+    }
+}
+
+// Everything that doesn't depend on having a window
+pub(crate) fn make_prerender(settings: &Settings, inner: PrerenderInnards) -> Prerender {
+    let prerender = Prerender {
+        assets: Assets::new(
+            settings.default_font_size,
+            settings.font_dir.clone(),
+            settings
+                .scale_factor
+                .unwrap_or_else(|| inner.monitor_scale_factor()),
+        ),
+        num_uploads: Cell::new(0),
+        gpu_cache: Rc::new(RefCell::new(GpuCache::new(settings.gpu_memory_budget))),
+        retained: RefCell::new(RetainedPool::new()),
+        inner,
+    };
+    for path in &settings.icon_sheets {
+        prerender.assets.load_icon_sheet(path);
+    }
+    if settings.language.is_some() {
+        prerender.assets.set_language(settings.language.clone());
+    }
+    prerender
 }
 
 pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings, make_gui: F) -> ! {
     let (prerender_innards, event_loop, window_size) =
-        crate::backend::setup(&settings.window_title);
+        PrerenderInnards::setup(&settings.window_title);
 
     let mut canvas = Canvas::new(window_size.width, window_size.height);
+    canvas.follow_monitor_scale_factor = settings.scale_factor.is_none();
     prerender_innards.window_resized(canvas.window_width, canvas.window_height);
     if let Some(ref path) = settings.window_icon {
         let image = image::open(path).unwrap();
@@ -231,17 +320,7 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
         let icon = Icon::from_rgba(rgba, width, height).unwrap();
         prerender_innards.set_window_icon(icon);
     }
-    let prerender = Prerender {
-        assets: Assets::new(
-            settings.default_font_size,
-            settings.font_dir,
-            settings
-                .scale_factor
-                .unwrap_or_else(|| prerender_innards.monitor_scale_factor()),
-        ),
-        num_uploads: Cell::new(0),
-        inner: prerender_innards,
-    };
+    let prerender = make_prerender(&settings, prerender_innards);
     let mut style = Style::standard();
 
     let gui = make_gui(&mut EventCtx {
@@ -292,6 +371,16 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
             winit::event::Event::RedrawRequested(_) => {
                 state.draw(&prerender, false);
                 prerender.num_uploads.set(0);
+                // If nothing's animating, nothing would redraw the screen once the tooltip is
+                // ready, so wake up for it.
+                if !running && state.canvas.tooltip_waiting.get() {
+                    let wait = (state.canvas.tooltip_delay
+                        - Duration::realtime_elapsed(state.canvas.cursor_still_since))
+                    .max(Duration::ZERO);
+                    *control_flow = winit::event_loop::ControlFlow::WaitUntil(
+                        Instant::now() + std::time::Duration::from_secs_f64(wait.inner_seconds()),
+                    );
+                }
                 return;
             }
             winit::event::Event::MainEventsCleared => {
@@ -299,6 +388,9 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
                 if running {
                     Event::Update(Duration::realtime_elapsed(last_update))
                 } else {
+                    if state.canvas.tooltip_ready() {
+                        prerender.request_redraw();
+                    }
                     return;
                 }
             }
@@ -9,6 +9,8 @@ uniform vec3 transform;
 uniform vec3 window;
 
 in vec4 pass_style;
+in vec4 pass_gradient_to;
+in vec3 pass_gradient_pos;
 out vec4 f_color;
 
 void main() {
@@ -22,6 +24,16 @@ void main() {
             // Let the polygon with its original colors show instead.
             discard;
 	}
+    } else if (pass_gradient_pos[0] > 0.5) {
+        // See unpack_style in vertex_300.glsl. The kind is the same at every vertex, but compare
+        // loosely in case interpolating it isn't exact.
+        float pct;
+        if (pass_gradient_pos[0] < 1.5) {
+            pct = pass_gradient_pos[1];
+        } else {
+            pct = length(pass_gradient_pos.yz);
+        }
+        f_color = mix(pass_style, pass_gradient_to, clamp(pct, 0.0, 1.0));
     } else {
         // https://en.wikipedia.org/wiki/Grayscale#Luma_coding_in_video_systems
         //float gray = dot(pass_style.rgb, vec3(0.299, 0.587, 0.114));
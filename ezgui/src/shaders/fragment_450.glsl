@@ -0,0 +1,42 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    // (x offset, y offset, zoom)
+    vec3 transform;
+    // (window width, window height, _)
+    vec3 window;
+    // Only used by the vertex shader
+    vec4 model;
+};
+
+layout(location = 0) in vec4 pass_style;
+layout(location = 1) in vec4 pass_gradient_to;
+layout(location = 2) in vec3 pass_gradient_pos;
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    // See actually_upload in drawing.rs to understand the different things encoded.
+    if (pass_style[0] == 100.0) {
+        // Unlike OpenGL, gl_FragCoord starts at the top-left here, so no need to flip y.
+        float map_x = (gl_FragCoord.x + transform[0]) / transform[2];
+        float map_y = (gl_FragCoord.y + transform[1]) / transform[2];
+        if (mod(map_x + map_y, 2.0) <= 0.5) {
+            f_color = vec4(1.0, 1.0, 1.0, 1.0);
+        } else {
+            // Let the polygon with its original colors show instead.
+            discard;
+        }
+    } else if (pass_gradient_pos[0] > 0.5) {
+        // See unpack_style in vertex_450.glsl. The kind is the same at every vertex, but compare
+        // loosely in case interpolating it isn't exact.
+        float pct;
+        if (pass_gradient_pos[0] < 1.5) {
+            pct = pass_gradient_pos[1];
+        } else {
+            pct = length(pass_gradient_pos.yz);
+        }
+        f_color = mix(pass_style, pass_gradient_to, clamp(pct, 0.0, 1.0));
+    } else {
+        f_color = pass_style;
+    }
+}
@@ -4,17 +4,53 @@
 uniform vec3 transform;
 // (window width, window height, z value)
 uniform vec3 window;
+// (x offset, y offset, rotation in radians, scale) applied to the object first
+uniform vec4 model;
 
 in vec2 position;
 in vec4 style;
 out vec4 pass_style;
+out vec4 pass_gradient_to;
+out vec3 pass_gradient_pos;
+
+// See pack_color in color.rs. Everything here is a whole number below 2^24, so it's exact.
+vec4 unpack_color(float packed) {
+    float x = floor(packed + 0.5);
+    float r = floor(x / 262144.0);
+    x -= r * 262144.0;
+    float g = floor(x / 4096.0);
+    x -= g * 4096.0;
+    float b = floor(x / 64.0);
+    float a = x - b * 64.0;
+    return vec4(r, g, b, a) / 63.0;
+}
+
+// Gradients are packed into style; see FancyColor::style. Unpack the two colors here, so the
+// fragment shader only has to blend them.
+void unpack_style() {
+    if (style[0] < 0.0) {
+        pass_style = unpack_color(-style[0] - 1.0);
+        pass_gradient_to = unpack_color(abs(style[1]) - 1.0);
+        pass_gradient_pos = vec3(style[1] > 0.0 ? 1.0 : 2.0, style[2], style[3]);
+    } else {
+        pass_style = style;
+        pass_gradient_to = vec4(0.0);
+        pass_gradient_pos = vec3(0.0);
+    }
+}
 
 void main() {
-    pass_style = style;
+    unpack_style();
+
+    // Rotate and scale the object around its origin, then move it into place
+    float c = cos(model[2]);
+    float s = sin(model[2]);
+    float pos_x = (position[0] * c - position[1] * s) * model[3] + model[0];
+    float pos_y = (position[0] * s + position[1] * c) * model[3] + model[1];
 
     // This is map_to_screen
-    float screen_x = (position[0] * transform[2]) - transform[0];
-    float screen_y = (position[1] * transform[2]) - transform[1];
+    float screen_x = (pos_x * transform[2]) - transform[0];
+    float screen_y = (pos_y * transform[2]) - transform[1];
     // Translate that to clip-space or whatever it's called
     float x = (screen_x / window[0] * 2.0) - 1.0;
     float y = (screen_y / window[1] * 2.0) - 1.0;
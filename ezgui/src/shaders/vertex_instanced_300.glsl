@@ -0,0 +1,72 @@
+#version 300 es
+
+// Packed gradient colors need every bit of a float
+precision highp float;
+
+// Like vertex_300.glsl, but draws many copies of one shape. Each instance has its own position,
+// rotation, and a color multiplying the shape's colors.
+
+// (x offset, y offset, zoom)
+uniform vec3 transform;
+// (window width, window height, z value)
+uniform vec3 window;
+
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec4 style;
+layout (location = 2) in vec2 instance_pos;
+layout (location = 3) in float instance_angle;
+layout (location = 4) in vec4 instance_color;
+out vec4 pass_style;
+out vec4 pass_gradient_to;
+out vec3 pass_gradient_pos;
+
+// See pack_color in color.rs. Everything here is a whole number below 2^24, so it's exact.
+vec4 unpack_color(float packed) {
+    float x = floor(packed + 0.5);
+    float r = floor(x / 262144.0);
+    x -= r * 262144.0;
+    float g = floor(x / 4096.0);
+    x -= g * 4096.0;
+    float b = floor(x / 64.0);
+    float a = x - b * 64.0;
+    return vec4(r, g, b, a) / 63.0;
+}
+
+// Gradients are packed into style; see FancyColor::style. Unpack the two colors here, so the
+// fragment shader only has to blend them.
+void unpack_style() {
+    if (style[0] < 0.0) {
+        pass_style = unpack_color(-style[0] - 1.0);
+        pass_gradient_to = unpack_color(abs(style[1]) - 1.0);
+        pass_gradient_pos = vec3(style[1] > 0.0 ? 1.0 : 2.0, style[2], style[3]);
+    } else {
+        pass_style = style;
+        pass_gradient_to = vec4(0.0);
+        pass_gradient_pos = vec3(0.0);
+    }
+}
+
+void main() {
+    unpack_style();
+    // Hatching isn't a color, so don't tint it. See fragment_300.glsl.
+    if (pass_style[0] != 100.0) {
+        pass_style *= instance_color;
+    }
+    pass_gradient_to *= instance_color;
+
+    // Rotate the shape around its origin, then move it into place
+    float c = cos(instance_angle);
+    float s = sin(instance_angle);
+    float pos_x = position[0] * c - position[1] * s + instance_pos[0];
+    float pos_y = position[0] * s + position[1] * c + instance_pos[1];
+
+    // This is map_to_screen
+    float screen_x = (pos_x * transform[2]) - transform[0];
+    float screen_y = (pos_y * transform[2]) - transform[1];
+    // Translate that to clip-space or whatever it's called
+    float x = (screen_x / window[0] * 2.0) - 1.0;
+    float y = (screen_y / window[1] * 2.0) - 1.0;
+
+    // Note the y inversion
+    gl_Position = vec4(x, -y, window[2], 1.0);
+}
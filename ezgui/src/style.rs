@@ -5,6 +5,7 @@ pub struct Style {
     pub outline_thickness: f64,
     pub outline_color: Color,
     pub panel_bg: Color,
+    pub section_bg: Color,
     pub hotkey_color: Color,
     pub hovering_color: Color,
 }
@@ -15,6 +16,7 @@ impl Style {
             outline_thickness: 2.0,
             outline_color: Color::WHITE,
             panel_bg: Color::grey(0.4),
+            section_bg: Color::grey(0.5),
             hotkey_color: Color::GREEN,
             hovering_color: Color::ORANGE,
         }
@@ -1,10 +1,11 @@
 use crate::assets::Assets;
 use crate::{
-    svg, Color, EventCtx, GeomBatch, GfxCtx, JustDraw, MultiKey, Prerender, ScreenDims, Widget,
+    CachedDrawable, Color, EventCtx, GeomBatch, GfxCtx, JustDraw, MultiKey, Prerender, ScreenDims,
+    Widget,
 };
-use geom::Polygon;
+use geom::{Polygon, Pt2D};
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::Write;
 use std::hash::Hasher;
 
 // Same as body()
@@ -106,6 +107,8 @@ pub struct Text {
     lines: Vec<(Option<Color>, Vec<TextSpan>)>,
     // TODO Stop using this as much as possible.
     bg_color: Option<Color>,
+    // Outline every glyph in this color, so text stays readable over busy map geometry.
+    halo: Option<Color>,
 }
 
 impl Text {
@@ -113,6 +116,7 @@ impl Text {
         Text {
             lines: Vec::new(),
             bg_color: None,
+            halo: None,
         }
     }
 
@@ -151,6 +155,27 @@ impl Text {
         self
     }
 
+    pub fn halo(mut self, color: Color) -> Text {
+        self.halo = Some(color);
+        self
+    }
+
+    // Pick a halo contrasting with the first line's color: dark around light text, light around
+    // dark text.
+    pub fn auto_halo(self) -> Text {
+        let fg = self
+            .lines
+            .get(0)
+            .and_then(|(_, spans)| spans.get(0))
+            .map(|span| span.fg_color)
+            .unwrap_or(DEFAULT_FG_COLOR);
+        if fg.luminance() > 0.5 {
+            self.halo(Color::BLACK.alpha(0.8))
+        } else {
+            self.halo(Color::WHITE.alpha(0.8))
+        }
+    }
+
     // TODO Not exactly sure this is the right place for this, but better than code duplication
     pub fn tooltip(ctx: &EventCtx, hotkey: Option<MultiKey>, action: &str) -> Text {
         if let Some(ref key) = hotkey {
@@ -228,17 +253,6 @@ impl Text {
     }
 
     pub fn render(self, assets: &Assets) -> GeomBatch {
-        self.inner_render(assets, svg::HIGH_QUALITY)
-    }
-
-    pub fn render_g(self, g: &GfxCtx) -> GeomBatch {
-        self.render(&g.prerender.assets)
-    }
-    pub fn render_ctx(self, ctx: &EventCtx) -> GeomBatch {
-        self.render(&ctx.prerender.assets)
-    }
-
-    pub(crate) fn inner_render(self, assets: &Assets, tolerance: f32) -> GeomBatch {
         let hash_key = self.hash_key();
         if let Some(batch) = assets.get_cached_text(&hash_key) {
             return batch;
@@ -254,14 +268,8 @@ impl Text {
             // size.
             let line_height = assets.line_height(line[0].font, line[0].size);
 
-            let line_batch = render_line(line, tolerance, assets);
-            let line_dims = if line_batch.is_empty() {
-                ScreenDims::new(0.0, line_height)
-            } else {
-                // Also lie a little about width to make things look reasonable. TODO Probably
-                // should tune based on font size.
-                ScreenDims::new(line_batch.get_dims().width + 5.0, line_height)
-            };
+            let (line_batch, line_width) = render_line(line, self.halo, assets);
+            let line_dims = ScreenDims::new(line_width, line_height);
 
             if let Some(c) = line_color {
                 master_batch.push(
@@ -289,6 +297,13 @@ impl Text {
         output_batch
     }
 
+    pub fn render_g(self, g: &GfxCtx) -> GeomBatch {
+        self.render(&g.prerender.assets)
+    }
+    pub fn render_ctx(self, ctx: &EventCtx) -> GeomBatch {
+        self.render(&ctx.prerender.assets)
+    }
+
     pub fn render_to_batch(self, prerender: &Prerender) -> GeomBatch {
         let mut batch = self.render(&prerender.assets);
         batch.autocrop_dims = true;
@@ -316,11 +331,7 @@ impl Text {
         let mut lines = Vec::new();
         for (bg, spans) in self.lines.drain(..) {
             // First optimistically assume everything just fits.
-            if render_line(spans.clone(), svg::LOW_QUALITY, assets)
-                .get_dims()
-                .width
-                < limit
-            {
+            if line_width(&spans, assets) < limit {
                 lines.push((bg, spans));
                 continue;
             }
@@ -333,18 +344,7 @@ impl Text {
                 let mut current_span = span.clone();
                 current_span.text = String::new();
                 for word in span.text.split_whitespace() {
-                    let width = render_line(
-                        vec![TextSpan {
-                            text: word.to_string(),
-                            size: span.size,
-                            font: span.font,
-                            fg_color: span.fg_color,
-                        }],
-                        svg::LOW_QUALITY,
-                        assets,
-                    )
-                    .get_dims()
-                    .width;
+                    let width = assets.text_width(span.font, span.size, word);
                     if width_left > width {
                         current_span.text.push(' ');
                         current_span.text.push_str(word);
@@ -371,55 +371,35 @@ impl Text {
     }
 }
 
-fn render_line(spans: Vec<TextSpan>, tolerance: f32, assets: &Assets) -> GeomBatch {
-    // TODO This assumes size and font don't change mid-line. We might be able to support that now,
-    // actually.
-    // https://www.oreilly.com/library/view/svg-text-layout/9781491933817/ch04.html
-
-    // Just set a sufficiently large view box
-    let mut svg = r##"<svg width="9999" height="9999" viewBox="0 0 9999 9999" xmlns="http://www.w3.org/2000/svg">"##.to_string();
-
-    write!(
-        &mut svg,
-        r##"<text x="0" y="0" font-size="{}" {}>"##,
-        spans[0].size,
-        match spans[0].font {
-            Font::BungeeInlineRegular => "font-family=\"Bungee Inline\"",
-            Font::BungeeRegular => "font-family=\"Bungee\"",
-            Font::OverpassBold => "font-family=\"Overpass\" font-weight=\"bold\"",
-            Font::OverpassRegular => "font-family=\"Overpass\"",
-            Font::OverpassSemiBold => "font-family=\"Overpass\" font-weight=\"600\"",
-        }
-    )
-    .unwrap();
-
-    let mut contents = String::new();
+// Returns the line and how far it advances. Glyphs sit on a baseline at y = 0.
+fn render_line(spans: Vec<TextSpan>, halo: Option<Color>, assets: &Assets) -> (GeomBatch, f64) {
+    // The halo is the outline of the same glyphs, stroked thickly and drawn first.
+    let mut halos = GeomBatch::new();
+    let mut fills = GeomBatch::new();
+    let mut x = 0.0;
     for span in spans {
-        write!(
-            &mut contents,
-            r##"<tspan fill="{}">{}</tspan>"##,
-            // TODO Doesn't support alpha
-            span.fg_color.to_hex(),
-            htmlescape::encode_minimal(&span.text)
-        )
-        .unwrap();
-    }
-    write!(&mut svg, "{}</text></svg>", contents).unwrap();
-
-    let svg_tree = match usvg::Tree::from_str(&svg, &assets.text_opts) {
-        Ok(t) => t,
-        Err(err) => panic!("render_line({}): {}", contents, err),
-    };
-    let mut batch = GeomBatch::new();
-    match crate::svg::add_svg_inner(
-        &mut batch,
-        svg_tree,
-        tolerance,
-        *assets.scale_factor.borrow(),
-    ) {
-        Ok(_) => batch,
-        Err(err) => panic!("render_line({}): {}", contents, err),
+        x = assets.glyphs.render(
+            span.font,
+            assets.scaled_size(span.size),
+            x,
+            &assets.translate(&span.text),
+            |fill, outline| {
+                if let Some(color) = halo {
+                    halos.push(color, outline);
+                }
+                fills.push(span.fg_color, fill);
+            },
+        );
     }
+    halos.append(fills);
+    (halos, x)
+}
+
+fn line_width(spans: &[TextSpan], assets: &Assets) -> f64 {
+    spans
+        .iter()
+        .map(|span| assets.text_width(span.font, span.size, &span.text))
+        .sum()
 }
 
 pub trait TextExt {
@@ -437,3 +417,47 @@ impl TextExt for String {
         Line(self).draw(ctx)
     }
 }
+
+// Lots of text placed in map-space, like labels. Uploading each Text separately costs an upload
+// and a draw call apiece, which adds up with hundreds of them, so these all go into one batch.
+// Nothing is laid out or uploaded until the first draw, so labels nobody looks at are cheap.
+pub struct TextBatch {
+    // Each Text is scaled, then centered on the point
+    labels: Vec<(Text, f64, Pt2D)>,
+    draw: RefCell<Option<CachedDrawable>>,
+}
+
+impl TextBatch {
+    pub fn new() -> TextBatch {
+        TextBatch {
+            labels: Vec::new(),
+            draw: RefCell::new(None),
+        }
+    }
+
+    pub fn add(&mut self, txt: Text, scale: f64, center: Pt2D) {
+        self.labels.push((txt, scale, center));
+        *self.draw.get_mut() = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        let mut draw = self.draw.borrow_mut();
+        if draw.is_none() {
+            let mut batch = GeomBatch::new();
+            for (txt, scale, center) in &self.labels {
+                batch.append(
+                    txt.clone()
+                        .render_to_batch(g.prerender)
+                        .scale(*scale)
+                        .centered_on(*center),
+                );
+            }
+            *draw = Some(CachedDrawable::new(g.prerender, batch));
+        }
+        g.redraw_cached(draw.as_ref().unwrap());
+    }
+}
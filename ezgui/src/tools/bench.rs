@@ -0,0 +1,157 @@
+use crate::{Color, Drawable, FancyColor, GeomBatch, GfxCtx, Prerender};
+use abstutil::{elapsed_seconds, prettyprint_usize};
+use geom::{Circle, Distance, PolyLine, Polygon, Pt2D};
+use instant::Instant;
+
+// Synthetic workloads for measuring the rendering pipeline, so redesigns can be compared with
+// numbers. Every workload uses n small shapes spread over a grid, about as detailed as the agents
+// and map objects drawn every frame.
+
+// How long one workload took
+pub struct BenchResult {
+    pub name: String,
+    // How many shapes, uploads, or draw calls
+    pub count: usize,
+    pub seconds: f64,
+}
+
+impl BenchResult {
+    fn measure<F: FnOnce()>(name: String, count: usize, f: F) -> BenchResult {
+        let started = Instant::now();
+        f();
+        BenchResult {
+            name,
+            count,
+            seconds: elapsed_seconds(started),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let per_sec = if self.seconds > 0.0 {
+            prettyprint_usize((self.count as f64 / self.seconds) as usize)
+        } else {
+            "∞".to_string()
+        };
+        format!(
+            "{}: {:.2}ms ({}/s)",
+            self.name,
+            self.seconds * 1000.0,
+            per_sec
+        )
+    }
+}
+
+// Alternates between circles and thick, bent lines, so both tessellation paths get used.
+pub fn make_shapes(n: usize) -> GeomBatch {
+    let per_row = (n as f64).sqrt().ceil().max(1.0) as usize;
+    let mut batch = GeomBatch::new();
+    for i in 0..n {
+        let x = 10.0 * (i % per_row) as f64;
+        let y = 10.0 * (i / per_row) as f64;
+        if i % 2 == 0 {
+            batch.push(
+                Color::RED,
+                Circle::new(Pt2D::new(x, y), Distance::meters(3.0)).to_polygon(),
+            );
+        } else {
+            batch.push(
+                Color::BLUE,
+                PolyLine::new(vec![
+                    Pt2D::new(x, y),
+                    Pt2D::new(x + 4.0, y + 2.0),
+                    Pt2D::new(x + 8.0, y),
+                ])
+                .make_polygons(Distance::meters(1.5)),
+            );
+        }
+    }
+    batch
+}
+
+pub fn bench_tessellation(n: usize) -> BenchResult {
+    BenchResult::measure(
+        format!("tessellate {} shapes", prettyprint_usize(n)),
+        n,
+        || {
+            make_shapes(n);
+        },
+    )
+}
+
+// Uploading everything as one Drawable, then each shape as its own Drawable
+pub fn bench_upload(prerender: &Prerender, n: usize) -> Vec<BenchResult> {
+    let batch = make_shapes(n);
+    let singles: Vec<GeomBatch> = batch.clone().consume().into_iter().map(single).collect();
+    vec![
+        BenchResult::measure(
+            format!("upload {} shapes at once", prettyprint_usize(n)),
+            n,
+            || {
+                prerender.upload(batch);
+            },
+        ),
+        BenchResult::measure(
+            format!("upload {} shapes separately", prettyprint_usize(n)),
+            n,
+            || {
+                for b in singles {
+                    prerender.upload(b);
+                }
+            },
+        ),
+    ]
+}
+
+// The same shapes, uploaded once as one Drawable and once as n. Only the time to issue the draw
+// calls gets measured; the GPU finishes the work later.
+pub struct DrawBench {
+    one: Drawable,
+    many: Vec<Drawable>,
+}
+
+impl DrawBench {
+    pub fn new(prerender: &Prerender, n: usize) -> DrawBench {
+        let batch = make_shapes(n);
+        DrawBench {
+            many: batch
+                .clone()
+                .consume()
+                .into_iter()
+                .map(|pair| prerender.upload(single(pair)))
+                .collect(),
+            one: prerender.upload(batch),
+        }
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) -> Vec<BenchResult> {
+        let n = self.many.len();
+        vec![
+            BenchResult::measure(
+                format!("draw {} shapes in 1 call", prettyprint_usize(n)),
+                1,
+                || {
+                    g.redraw(&self.one);
+                },
+            ),
+            BenchResult::measure(
+                format!(
+                    "draw {} shapes in {} calls",
+                    prettyprint_usize(n),
+                    prettyprint_usize(n)
+                ),
+                n,
+                || {
+                    for obj in &self.many {
+                        g.redraw(obj);
+                    }
+                },
+            ),
+        ]
+    }
+}
+
+fn single((color, poly): (FancyColor, Polygon)) -> GeomBatch {
+    let mut batch = GeomBatch::new();
+    batch.fancy_push(color, poly);
+    batch
+}
@@ -0,0 +1,240 @@
+use crate::{Color, FancyColor, GeomBatch};
+use geom::{Pt2D, Triangle};
+use image::{Rgba, RgbaImage};
+
+// Golden-image regression tests for anything that produces a GeomBatch. Rendering happens on the
+// CPU, so this works without a window or GPU, and the output doesn't depend on the graphics
+// driver.
+
+// Two pixels differing by less than this (in perceived brightness and color, 0 to 1) are
+// considered the same. Absorbs small antialiasing and float differences.
+const PIXEL_TOLERANCE: f64 = 0.1;
+
+// Rasterizes a batch into an image of the given size. The batch's coordinates are treated as
+// pixels, with the origin at the top-left. Hatching isn't supported and renders as grey.
+pub fn rasterize(batch: &GeomBatch, width: u32, height: u32, bg: Color) -> RgbaImage {
+    let mut img = RgbaImage::from_pixel(width, height, to_rgba(bg));
+    for (color, poly) in &batch.list {
+        for tri in poly.triangles() {
+            fill_triangle(&mut img, &tri, color);
+        }
+    }
+    img
+}
+
+// Renders the batch and compares it against a stored PNG. If more than max_pct_different of the
+// pixels differ perceptibly, returns an error and writes the actual and diff images next to the
+// golden file. A missing golden file is an error too, so a renamed fixture can't silently pass.
+// Set UPDATE_GOLDEN to write the golden file instead of comparing.
+pub fn compare_to_golden(
+    batch: &GeomBatch,
+    width: u32,
+    height: u32,
+    golden_path: &str,
+    max_pct_different: f64,
+) -> Result<(), String> {
+    let actual = rasterize(batch, width, height, Color::BLACK);
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        actual
+            .save(golden_path)
+            .map_err(|err| format!("Couldn't write {}: {}", golden_path, err))?;
+        println!("Wrote new golden image {}", golden_path);
+        return Ok(());
+    }
+    if !std::path::Path::new(golden_path).exists() {
+        return Err(format!(
+            "{} doesn't exist. Run with UPDATE_GOLDEN=1 to create it",
+            golden_path
+        ));
+    }
+
+    let golden = image::open(golden_path)
+        .map_err(|err| format!("Couldn't read {}: {}", golden_path, err))?
+        .to_rgba();
+    if golden.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "{} is {:?}, but the rendered image is {:?}",
+            golden_path,
+            golden.dimensions(),
+            actual.dimensions()
+        ));
+    }
+
+    let mut diff = RgbaImage::new(width, height);
+    let mut num_different = 0;
+    for (x, y, expected) in golden.enumerate_pixels() {
+        let got = actual.get_pixel(x, y);
+        if perceptual_diff(expected, got) > PIXEL_TOLERANCE {
+            num_different += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            // Faded version of the expected image, for context
+            let Rgba([r, g, b, _]) = *expected;
+            diff.put_pixel(x, y, Rgba([r / 4, g / 4, b / 4, 255]));
+        }
+    }
+
+    let pct = (num_different as f64) / ((width * height) as f64);
+    if pct <= max_pct_different {
+        return Ok(());
+    }
+
+    let actual_path = format!("{}.actual.png", golden_path);
+    let diff_path = format!("{}.diff.png", golden_path);
+    actual
+        .save(&actual_path)
+        .map_err(|err| format!("Couldn't write {}: {}", actual_path, err))?;
+    diff.save(&diff_path)
+        .map_err(|err| format!("Couldn't write {}: {}", diff_path, err))?;
+    Err(format!(
+        "{:.2}% of pixels differ from {} (allowed {:.2}%). See {} and {}",
+        pct * 100.0,
+        golden_path,
+        max_pct_different * 100.0,
+        actual_path,
+        diff_path
+    ))
+}
+
+fn fill_triangle(img: &mut RgbaImage, tri: &Triangle, color: &FancyColor) {
+    let min_x = tri
+        .pt1
+        .x()
+        .min(tri.pt2.x())
+        .min(tri.pt3.x())
+        .floor()
+        .max(0.0) as u32;
+    let min_y = tri
+        .pt1
+        .y()
+        .min(tri.pt2.y())
+        .min(tri.pt3.y())
+        .floor()
+        .max(0.0) as u32;
+    let max_x = tri.pt1.x().max(tri.pt2.x()).max(tri.pt3.x()).ceil() as u32;
+    let max_y = tri.pt1.y().max(tri.pt2.y()).max(tri.pt3.y()).ceil() as u32;
+
+    for y in min_y..max_y.min(img.height()) {
+        for x in min_x..max_x.min(img.width()) {
+            // Sample the pixel center
+            let pt = Pt2D::new((x as f64) + 0.5, (y as f64) + 0.5);
+            if !contains(tri, pt) {
+                continue;
+            }
+            let blended = blend(*img.get_pixel(x, y), color.color_at(pt));
+            img.put_pixel(x, y, blended);
+        }
+    }
+}
+
+// Works for either winding order
+fn contains(tri: &Triangle, pt: Pt2D) -> bool {
+    fn cross(a: Pt2D, b: Pt2D, c: Pt2D) -> f64 {
+        (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+    }
+    let d1 = cross(tri.pt1, tri.pt2, pt);
+    let d2 = cross(tri.pt2, tri.pt3, pt);
+    let d3 = cross(tri.pt3, tri.pt1, pt);
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+fn to_rgba(c: Color) -> Rgba<u8> {
+    Rgba([
+        (c.r * 255.0) as u8,
+        (c.g * 255.0) as u8,
+        (c.b * 255.0) as u8,
+        (c.a * 255.0) as u8,
+    ])
+}
+
+// Standard "over" alpha compositing onto an opaque background
+fn blend(under: Rgba<u8>, over: Color) -> Rgba<u8> {
+    let Rgba([r, g, b, _]) = under;
+    let mix = |bg: u8, fg: f32| -> u8 {
+        ((fg * over.a + (bg as f32) / 255.0 * (1.0 - over.a)) * 255.0) as u8
+    };
+    Rgba([mix(r, over.r), mix(g, over.g), mix(b, over.b), 255])
+}
+
+// Differences in green matter more to the eye than red, and red more than blue. Roughly the
+// "redmean" approximation, scaled to [0, 1].
+fn perceptual_diff(a: &Rgba<u8>, b: &Rgba<u8>) -> f64 {
+    let dr = (a[0] as f64 - b[0] as f64) / 255.0;
+    let dg = (a[1] as f64 - b[1] as f64) / 255.0;
+    let db = (a[2] as f64 - b[2] as f64) / 255.0;
+    let rmean = (a[0] as f64 + b[0] as f64) / 2.0 / 255.0;
+    let dist = ((2.0 + rmean) * dr * dr + 4.0 * dg * dg + (3.0 - rmean) * db * db).sqrt();
+    // The max possible distance is 3.0
+    dist / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::Polygon;
+
+    #[test]
+    fn golden_roundtrip() {
+        let path = std::env::temp_dir().join("ezgui_golden_roundtrip.png");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let square = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(20.0, 20.0).translate(10.0, 10.0),
+        )]);
+        // Nothing to compare against yet
+        assert!(compare_to_golden(&square, 50, 50, path, 0.0).is_err());
+        assert!(!std::path::Path::new(path).exists());
+
+        // Setting UPDATE_GOLDEN here would leak into other tests, so write the golden file directly
+        rasterize(&square, 50, 50, Color::BLACK).save(path).unwrap();
+        assert!(compare_to_golden(&square, 50, 50, path, 0.0).is_ok());
+
+        let moved = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(20.0, 20.0).translate(25.0, 25.0),
+        )]);
+        assert!(compare_to_golden(&moved, 50, 50, path, 0.01).is_err());
+        assert!(std::path::Path::new(&format!("{}.diff.png", path)).exists());
+    }
+
+    #[test]
+    fn gradients() {
+        use crate::GradientAxis;
+        use geom::{Distance, Pt2D};
+
+        // One rectangle is enough; the colors vary inside the triangles.
+        let mut batch = GeomBatch::new();
+        batch.fancy_push(
+            FancyColor::Gradient {
+                from: Color::RED,
+                to: Color::BLUE,
+                axis: GradientAxis::Linear(Pt2D::new(10.0, 0.0), Pt2D::new(40.0, 0.0)),
+            },
+            Polygon::rectangle(50.0, 10.0),
+        );
+        batch.fancy_push(
+            FancyColor::Gradient {
+                from: Color::WHITE,
+                to: Color::BLACK,
+                axis: GradientAxis::Radial(Pt2D::new(25.0, 35.0), Distance::meters(20.0)),
+            },
+            Polygon::rectangle(50.0, 50.0).translate(0.0, 10.0),
+        );
+        let img = rasterize(&batch, 50, 60, Color::BLACK);
+
+        // Clamped before the start and after the end
+        assert_eq!(img.get_pixel(2, 5), &to_rgba(Color::RED));
+        assert_eq!(img.get_pixel(47, 5), &to_rgba(Color::BLUE));
+        let middle = img.get_pixel(25, 5);
+        assert!(middle[0] > 100 && middle[0] < 155 && middle[2] > 100 && middle[2] < 155);
+
+        // Bright at the center, dark in the corners
+        assert!(img.get_pixel(25, 35)[0] > 240);
+        assert_eq!(img.get_pixel(1, 11), &to_rgba(Color::BLACK));
+    }
+}
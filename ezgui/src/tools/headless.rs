@@ -0,0 +1,169 @@
+use crate::backend::PrerenderInnards;
+use crate::runner::{make_prerender, State, GUI};
+use crate::{Canvas, Event, EventCtx, EventLoopMode, Prerender, Settings, Style, UserInput};
+
+// Drives a GUI without a window, for tests. Events are fed in by hand and go through the same
+// handling as a real window, so hovering, clicking, and layout all behave normally. Drawing runs
+// all of the usual code, but nothing is rendered. Only available with the headless backend:
+//
+// cargo test --no-default-features --features headless-backend
+pub struct Headless<G: GUI> {
+    state: State<G>,
+    prerender: Prerender,
+}
+
+impl<G: GUI> Headless<G> {
+    pub fn new<F: FnOnce(&mut EventCtx) -> G>(
+        settings: Settings,
+        width: f64,
+        height: f64,
+        make_gui: F,
+    ) -> Headless<G> {
+        let prerender = make_prerender(&settings, PrerenderInnards::new(width, height));
+        let mut canvas = Canvas::new(width, height);
+        let mut style = Style::standard();
+        let gui = make_gui(&mut EventCtx {
+            fake_mouseover: true,
+            input: UserInput::new(Event::NoOp, &canvas),
+            canvas: &mut canvas,
+            prerender: &prerender,
+            style: &mut style,
+        });
+        Headless {
+            state: State { gui, canvas, style },
+            prerender,
+        }
+    }
+
+    // Returns whatever the GUI asked for next.
+    pub fn event(&mut self, ev: Event) -> EventLoopMode {
+        self.state.event(ev, &self.prerender).0
+    }
+
+    // Moves the mouse there and clicks.
+    pub fn click_at(&mut self, pt: crate::ScreenPt) {
+        self.event(Event::MouseMovedTo(pt));
+        self.event(Event::LeftMouseButtonDown);
+        self.event(Event::LeftMouseButtonUp);
+    }
+
+    pub fn draw(&mut self) {
+        self.state.draw(&self.prerender, false);
+        self.prerender.num_uploads.set(0);
+    }
+
+    pub fn gui(&self) -> &G {
+        &self.state.gui
+    }
+
+    pub fn gui_mut(&mut self) -> &mut G {
+        &mut self.state.gui
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.state.canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hotkey, key_sequence, Btn, Composite, GfxCtx, Key, Outcome, Widget};
+
+    struct Clicker {
+        composite: Composite,
+        clicked: Vec<String>,
+    }
+
+    impl GUI for Clicker {
+        fn event(&mut self, ctx: &mut EventCtx) -> EventLoopMode {
+            if let Some(Outcome::Clicked(x)) = self.composite.event(ctx) {
+                self.clicked.push(x);
+            }
+            EventLoopMode::InputOnly
+        }
+
+        fn draw(&self, g: &mut GfxCtx) {
+            self.composite.draw(g);
+        }
+    }
+
+    #[test]
+    fn click_button() {
+        let mut headless = Headless::new(
+            Settings::new("test", "../data/system/fonts"),
+            800.0,
+            600.0,
+            |ctx| Clicker {
+                composite: Composite::new(Widget::row(vec![
+                    Btn::text_fg("first").build_def(ctx, None),
+                    Btn::text_fg("second").build_def(ctx, None),
+                ]))
+                .build(ctx),
+                clicked: Vec::new(),
+            },
+        );
+        headless.draw();
+
+        let rect = headless.gui().composite.rect_of("second").clone();
+        headless.click_at(rect.center());
+        headless.draw();
+        assert_eq!(headless.gui().clicked, vec!["second".to_string()]);
+    }
+
+    struct Keys {
+        pressed: Vec<&'static str>,
+    }
+
+    impl GUI for Keys {
+        fn event(&mut self, ctx: &mut EventCtx) -> EventLoopMode {
+            if ctx.input.new_was_pressed(&hotkey(Key::G).unwrap()) {
+                self.pressed.push("G");
+            }
+            if ctx.input.new_was_pressed(&hotkey(Key::I).unwrap()) {
+                self.pressed.push("I");
+            }
+            if ctx
+                .input
+                .new_was_pressed(&key_sequence(Key::G, Key::I).unwrap())
+            {
+                self.pressed.push("G then I");
+            }
+            EventLoopMode::InputOnly
+        }
+
+        fn draw(&self, _: &mut GfxCtx) {}
+    }
+
+    #[test]
+    fn key_sequence_takes_priority() {
+        let mut headless = Headless::new(
+            Settings::new("test", "../data/system/fonts"),
+            800.0,
+            600.0,
+            |_| Keys {
+                pressed: Vec::new(),
+            },
+        );
+        // The sequence has to be seen once before its first key is reserved
+        headless.event(Event::NoOp);
+
+        headless.event(Event::KeyPress(Key::G));
+        headless.event(Event::KeyPress(Key::I));
+        assert_eq!(headless.gui().pressed, vec!["G then I"]);
+
+        // Without the first key, the second one works normally
+        headless.event(Event::KeyPress(Key::I));
+        assert_eq!(headless.gui().pressed, vec!["G then I", "I"]);
+
+        // Too slow, so the first key is forgotten
+        headless.event(Event::KeyPress(Key::G));
+        headless.state.canvas.prev_key = headless
+            .state
+            .canvas
+            .prev_key
+            .map(|(key, when)| (key, when - std::time::Duration::from_secs(2)));
+        headless.event(Event::KeyPress(Key::I));
+        assert_eq!(headless.gui().pressed, vec!["G then I", "I", "I"]);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod bench;
+pub mod golden;
+#[cfg(feature = "headless-backend")]
+pub mod headless;
+pub mod modal_stack;
 pub mod screenshot;
+pub mod translations;
 pub mod warper;
 pub mod wizard;
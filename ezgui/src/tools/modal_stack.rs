@@ -0,0 +1,138 @@
+use crate::{Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, Line, ScreenDims, ScreenPt, Text};
+use geom::Polygon;
+use std::cell::RefCell;
+
+// One level of a nested UI mode, like editing a traffic signal inside of map editing. Generic over
+// whatever application state the modes need. How modes handle events and draw is up to the
+// caller; the stack only tracks nesting.
+pub trait Modal<A> {
+    // Major modes name themselves for the breadcrumb line. Minor ones, like popups, don't.
+    fn breadcrumb(&self) -> Option<String> {
+        None
+    }
+    // Before this mode is popped or replaced, call this.
+    fn on_destroy(&mut self, _: &mut EventCtx, _: &mut A) {}
+}
+
+// A stack of modes. Only the topmost one should receive input; the caller's event loop just
+// forwards to top_mut(). Once named modes are nested, a breadcrumb line shows where you are.
+pub struct ModalStack<M> {
+    stack: Vec<M>,
+    // The breadcrumbs drawn last time, so they're only uploaded again when they change
+    crumbs: RefCell<Option<(Vec<String>, ScreenDims, Drawable)>>,
+}
+
+impl<M> ModalStack<M> {
+    pub fn new(stack: Vec<M>) -> ModalStack<M> {
+        ModalStack {
+            stack,
+            crumbs: RefCell::new(None),
+        }
+    }
+
+    pub fn push(&mut self, modal: M) {
+        self.stack.push(modal);
+    }
+
+    // Returns whatever was on top, after calling its on_destroy.
+    pub fn pop<A>(&mut self, ctx: &mut EventCtx, app: &mut A) -> Option<M>
+    where
+        M: Modal<A>,
+    {
+        let mut modal = self.stack.pop()?;
+        modal.on_destroy(ctx, app);
+        Some(modal)
+    }
+
+    pub fn clear<A>(&mut self, ctx: &mut EventCtx, app: &mut A)
+    where
+        M: Modal<A>,
+    {
+        while self.pop(ctx, app).is_some() {}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    // Panics if the stack is empty.
+    pub fn top(&self) -> &M {
+        self.stack.last().unwrap()
+    }
+
+    pub fn top_mut(&mut self) -> &mut M {
+        self.stack.last_mut().unwrap()
+    }
+
+    // The mode right beneath the top one
+    pub fn previous(&self) -> Option<&M> {
+        if self.stack.len() < 2 {
+            return None;
+        }
+        self.stack.get(self.stack.len() - 2)
+    }
+
+    pub fn breadcrumbs<A>(&self) -> Vec<String>
+    where
+        M: Modal<A>,
+    {
+        self.stack.iter().filter_map(|m| m.breadcrumb()).collect()
+    }
+
+    // Nothing is drawn until at least two named modes are nested; at the root, there's nothing to
+    // back out of.
+    pub fn draw_breadcrumbs<A>(&self, g: &mut GfxCtx)
+    where
+        M: Modal<A>,
+    {
+        let crumbs = self.breadcrumbs();
+        if crumbs.len() < 2 {
+            return;
+        }
+        let mut cache = self.crumbs.borrow_mut();
+        if cache.as_ref().map(|(c, _, _)| c != &crumbs).unwrap_or(true) {
+            let (dims, draw) = render_breadcrumbs(g, crumbs.clone());
+            *cache = Some((crumbs, dims, draw));
+        }
+        let (_, dims, draw) = cache.as_ref().unwrap();
+        g.redraw_at(
+            ScreenPt::new((g.canvas.window_width - dims.width) / 2.0, 0.0),
+            draw,
+        );
+    }
+}
+
+// Renders "first > second > third" on a dark background, along with a reminder of how to back
+// out.
+fn render_breadcrumbs(g: &mut GfxCtx, crumbs: Vec<String>) -> (ScreenDims, Drawable) {
+    let mut txt = Text::new();
+    let num = crumbs.len();
+    for (idx, crumb) in crumbs.into_iter().enumerate() {
+        if idx == num - 1 {
+            txt.append(Line(crumb));
+        } else {
+            txt.append(Line(crumb).fg(Color::grey(0.6)));
+            txt.append(Line(" > ").fg(Color::grey(0.6)));
+        }
+    }
+    txt.append_all(vec![
+        Line("   ("),
+        Line(Key::Escape.describe()).fg(g.style().hotkey_color),
+        Line(" to go back)"),
+    ]);
+
+    let pad = 5.0;
+    let txt_batch = txt.render_g(g);
+    let dims = txt_batch.get_dims();
+    let total = ScreenDims::new(dims.width + 2.0 * pad, dims.height + 2.0 * pad);
+    let mut batch = GeomBatch::from(vec![(
+        Color::BLACK.alpha(0.7),
+        Polygon::rectangle(total.width, total.height),
+    )]);
+    batch.append(txt_batch.translate(pad, pad));
+    (total, g.upload(batch))
+}
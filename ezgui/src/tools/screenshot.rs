@@ -1,3 +1,4 @@
+use crate::backend::RenderBackend;
 use crate::runner::{State, GUI};
 use crate::Prerender;
 use abstutil::Timer;
@@ -89,6 +90,37 @@ fn screencap(filename: &str) -> bool {
     true
 }
 
+// Saves whatever was just drawn to the window as a PNG.
+pub(crate) fn save_frame(prerender: &Prerender, path: &str) {
+    let (width, height, mut pixels) = match prerender.inner.read_front_buffer() {
+        Some(x) => x,
+        None => {
+            println!("Can't save {}; this backend can't read back frames", path);
+            return;
+        }
+    };
+    // Translucent things blended over the background can leave alpha below 1, but the window
+    // itself is opaque.
+    for px in pixels.chunks_mut(4) {
+        px[3] = 255;
+    }
+    let img = match image::RgbaImage::from_raw(width, height, pixels) {
+        Some(img) => img,
+        None => {
+            println!("Can't save {}; the frame is the wrong size", path);
+            return;
+        }
+    };
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    // OpenGL's rows start at the bottom
+    match image::imageops::flip_vertical(&img).save(path) {
+        Ok(()) => println!("Saved screenshot to {}", path),
+        Err(err) => println!("Couldn't save {}: {}", path, err),
+    }
+}
+
 fn finish(dir_path: &str, filenames: Vec<String>, num_tiles_x: usize, num_tiles_y: usize) {
     let mut args = filenames;
     args.push("-mode".to_string());
@@ -0,0 +1,97 @@
+use abstutil::Timer;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DIR: &str = "../data/system/translations";
+
+// A message catalog for one language, mapping the English text of a span to its translation.
+// Catalogs are JSON files in data/system/translations, named like "es" or "pt-BR". Anything not in
+// the catalog is shown in English.
+pub struct Translations {
+    lang: Option<String>,
+    messages: BTreeMap<String, String>,
+    // Text that was drawn, but isn't in the catalog yet
+    missing: BTreeSet<String>,
+}
+
+impl Translations {
+    pub fn english() -> Translations {
+        Translations {
+            lang: None,
+            messages: BTreeMap::new(),
+            missing: BTreeSet::new(),
+        }
+    }
+
+    // If there's no catalog for a regional variant like "pt-BR", falls back to "pt", then to
+    // English.
+    pub fn load(lang: &str) -> Translations {
+        let mut candidates = vec![lang.to_string()];
+        if let Some(base) = lang.split(|c| c == '-' || c == '_').next() {
+            if base != lang {
+                candidates.push(base.to_string());
+            }
+        }
+        for candidate in candidates {
+            if let Ok(messages) = abstutil::maybe_read_json::<BTreeMap<String, String>>(
+                format!("{}/{}.json", DIR, candidate),
+                &mut Timer::throwaway(),
+            ) {
+                return Translations {
+                    lang: Some(candidate),
+                    messages,
+                    missing: BTreeSet::new(),
+                };
+            }
+        }
+        println!("No translations for {}, using English", lang);
+        Translations::english()
+    }
+
+    pub fn lang(&self) -> Option<String> {
+        self.lang.clone()
+    }
+
+    pub fn translate(&mut self, text: &str) -> String {
+        if self.lang.is_none() {
+            return text.to_string();
+        }
+        // Spans often have padding, which the catalog doesn't need to repeat.
+        let trimmed = text.trim();
+        if let Some(x) = self.messages.get(trimmed) {
+            return text.replacen(trimmed, x, 1);
+        }
+        // Numbers and such don't need translating.
+        if trimmed.chars().any(|c| c.is_alphabetic()) {
+            self.missing.insert(trimmed.to_string());
+        }
+        text.to_string()
+    }
+
+    // Writes everything drawn so far that the catalog doesn't cover, as a starting point for
+    // translators. Returns the path.
+    pub fn save_missing(&self) -> Option<String> {
+        let lang = self.lang.as_ref()?;
+        let path = format!("{}/missing_{}.json", DIR, lang);
+        let todo: BTreeMap<String, String> = self
+            .missing
+            .iter()
+            .map(|x| (x.clone(), String::new()))
+            .collect();
+        abstutil::write_json(path.clone(), &todo);
+        Some(path)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn available_languages() -> Vec<String> {
+    abstutil::list_all_objects(DIR.to_string())
+        .into_iter()
+        .filter(|x| !x.starts_with("missing_"))
+        .collect()
+}
+
+// TODO Catalogs aren't bundled for the web yet
+#[cfg(target_arch = "wasm32")]
+pub fn available_languages() -> Vec<String> {
+    Vec::new()
+}
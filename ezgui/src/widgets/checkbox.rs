@@ -1,27 +1,35 @@
 use crate::{
-    Btn, Button, Color, EventCtx, GeomBatch, GfxCtx, MultiKey, ScreenDims, ScreenPt, Widget,
-    WidgetImpl, WidgetOutput,
+    Btn, Button, Color, EventCtx, GeomBatch, GfxCtx, Line, MultiKey, ScreenDims, ScreenPt, Text,
+    Widget, WidgetImpl, WidgetOutput,
 };
-use geom::{Polygon, Pt2D};
+use geom::{Circle, Distance, Polygon, Pt2D};
 
 pub struct Checkbox {
     pub(crate) enabled: bool,
+    // Did the last event flip this?
+    pub(crate) changed: bool,
     btn: Button,
     other_btn: Button,
 }
 
+const TOGGLE_WIDTH: f64 = 40.0;
+const TOGGLE_HEIGHT: f64 = 20.0;
+const TOGGLE_KNOB_PADDING: f64 = 2.0;
+
 impl Checkbox {
     // TODO Not typesafe! Gotta pass a button. Also, make sure to give an ID.
     pub fn new(enabled: bool, false_btn: Widget, true_btn: Widget) -> Widget {
         if enabled {
             Widget::new(Box::new(Checkbox {
                 enabled,
+                changed: false,
                 btn: true_btn.take_btn(),
                 other_btn: false_btn.take_btn(),
             }))
         } else {
             Widget::new(Box::new(Checkbox {
                 enabled,
+                changed: false,
                 btn: false_btn.take_btn(),
                 other_btn: true_btn.take_btn(),
             }))
@@ -44,6 +52,68 @@ impl Checkbox {
         .named(label)
     }
 
+    // A switch with the label to its right. The whole thing is clickable.
+    pub fn toggle<I: Into<String>>(
+        ctx: &EventCtx,
+        label: I,
+        hotkey: Option<MultiKey>,
+        enabled: bool,
+    ) -> Widget {
+        let label = label.into();
+        let draw = |on: bool, hovering: bool| -> (GeomBatch, Polygon) {
+            let mut batch = GeomBatch::new();
+            batch.push(
+                if on {
+                    Color::hex("#F4DF4D")
+                } else {
+                    Color::grey(0.6)
+                },
+                Polygon::pill(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+            );
+            let radius = TOGGLE_HEIGHT / 2.0 - TOGGLE_KNOB_PADDING;
+            let knob_x = if on {
+                TOGGLE_WIDTH - TOGGLE_HEIGHT / 2.0
+            } else {
+                TOGGLE_HEIGHT / 2.0
+            };
+            batch.push(
+                if hovering {
+                    ctx.style().hovering_color
+                } else {
+                    Color::WHITE
+                },
+                Circle::new(
+                    Pt2D::new(knob_x, TOGGLE_HEIGHT / 2.0),
+                    Distance::meters(radius),
+                )
+                .to_polygon(),
+            );
+
+            let mut line = Line(&label);
+            if hovering {
+                line = line.fg(ctx.style().hovering_color);
+            }
+            let txt = Text::from(line).render_ctx(ctx);
+            let dims = txt.get_dims();
+            let height = dims.height.max(TOGGLE_HEIGHT);
+            batch = batch.translate(0.0, (height - TOGGLE_HEIGHT) / 2.0);
+            batch.append(txt.translate(TOGGLE_WIDTH + 8.0, (height - dims.height) / 2.0));
+            let hitbox = Polygon::rectangle(TOGGLE_WIDTH + 8.0 + dims.width, height);
+            (batch, hitbox)
+        };
+
+        let (off_normal, hitbox) = draw(false, false);
+        let (off_hovered, _) = draw(false, true);
+        let (on_normal, _) = draw(true, false);
+        let (on_hovered, _) = draw(true, true);
+        Checkbox::new(
+            enabled,
+            Btn::custom(off_normal, off_hovered, hitbox.clone()).build(ctx, &label, hotkey.clone()),
+            Btn::custom(on_normal, on_hovered, hitbox).build(ctx, &label, hotkey),
+        )
+        .named(label)
+    }
+
     pub fn colored(ctx: &EventCtx, label: &str, color: Color, enabled: bool) -> Widget {
         let vert_pad = 4.0;
         let horiz_pad = 4.0;
@@ -98,11 +168,13 @@ impl WidgetImpl for Checkbox {
     }
 
     fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        self.changed = false;
         self.btn.event(ctx, output);
         if output.outcome.take().is_some() {
             std::mem::swap(&mut self.btn, &mut self.other_btn);
             self.btn.set_pos(self.other_btn.top_left);
             self.enabled = !self.enabled;
+            self.changed = true;
             output.redo_layout = true;
         }
     }
@@ -0,0 +1,174 @@
+use crate::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt, Widget, WidgetImpl,
+    WidgetOutput,
+};
+use geom::{Circle, Distance, Polygon, Pt2D};
+
+const SQUARE_SIZE: f64 = 150.0;
+const STRIP_WIDTH: f64 = 20.0;
+const SWATCH_WIDTH: f64 = 40.0;
+const GAP: f64 = 10.0;
+// The gradients are approximated by this many bands in each direction
+const RESOLUTION: usize = 30;
+
+// Picks a color by hue, saturation, and value. Dragging around the square changes saturation
+// (left to right) and value (bottom to top), and dragging along the strip changes the hue. The
+// current color is shown on the right.
+pub struct ColorPicker {
+    hue: f64,
+    sat: f64,
+    val: f64,
+    dragging: Option<Part>,
+
+    draw: Drawable,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Part {
+    Square,
+    Strip,
+}
+
+impl ColorPicker {
+    pub fn new(ctx: &EventCtx, color: Color) -> Widget {
+        let (hue, sat, val) = color.to_hsv();
+        let mut picker = ColorPicker {
+            hue,
+            sat,
+            val,
+            dragging: None,
+
+            draw: ctx.upload(GeomBatch::new()),
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims: ScreenDims::new(
+                SQUARE_SIZE + GAP + STRIP_WIDTH + GAP + SWATCH_WIDTH,
+                SQUARE_SIZE,
+            ),
+        };
+        picker.recalc(ctx);
+        Widget::new(Box::new(picker))
+    }
+
+    pub fn color(&self) -> Color {
+        Color::hsv(self.hue, self.sat, self.val)
+    }
+
+    fn recalc(&mut self, ctx: &EventCtx) {
+        let mut batch = GeomBatch::new();
+        let n = RESOLUTION as f64;
+        let cell = SQUARE_SIZE / n;
+
+        for x in 0..RESOLUTION {
+            for y in 0..RESOLUTION {
+                let sat = (x as f64 + 0.5) / n;
+                let val = 1.0 - (y as f64 + 0.5) / n;
+                batch.push(
+                    Color::hsv(self.hue, sat, val),
+                    Polygon::rectangle(cell, cell).translate(x as f64 * cell, y as f64 * cell),
+                );
+            }
+        }
+        batch.push(
+            Color::WHITE,
+            Circle::outline(
+                Pt2D::new(self.sat * SQUARE_SIZE, (1.0 - self.val) * SQUARE_SIZE),
+                Distance::meters(5.0),
+                Distance::meters(2.0),
+            ),
+        );
+
+        let strip_x = SQUARE_SIZE + GAP;
+        for y in 0..RESOLUTION {
+            batch.push(
+                Color::hsv(360.0 * (y as f64 + 0.5) / n, 1.0, 1.0),
+                Polygon::rectangle(STRIP_WIDTH, cell).translate(strip_x, y as f64 * cell),
+            );
+        }
+        batch.push(
+            Color::WHITE,
+            Polygon::rectangle(STRIP_WIDTH, 4.0)
+                .translate(strip_x, self.hue / 360.0 * SQUARE_SIZE - 2.0),
+        );
+
+        batch.push(
+            self.color(),
+            Polygon::rectangle(SWATCH_WIDTH, SQUARE_SIZE)
+                .translate(strip_x + STRIP_WIDTH + GAP, 0.0),
+        );
+
+        self.draw = ctx.upload(batch);
+    }
+
+    // Relative to the top-left of the widget, clamped to the square
+    fn cursor(&self, ctx: &EventCtx) -> (f64, f64) {
+        let pt = ctx.canvas.get_cursor();
+        (
+            abstutil::clamp(pt.x - self.top_left.x, 0.0, SQUARE_SIZE),
+            abstutil::clamp(pt.y - self.top_left.y, 0.0, SQUARE_SIZE),
+        )
+    }
+
+    fn part_at(&self, pt: ScreenPt) -> Option<Part> {
+        let x = pt.x - self.top_left.x;
+        let y = pt.y - self.top_left.y;
+        if !(0.0..=SQUARE_SIZE).contains(&y) {
+            return None;
+        }
+        if (0.0..=SQUARE_SIZE).contains(&x) {
+            Some(Part::Square)
+        } else if (SQUARE_SIZE + GAP..=SQUARE_SIZE + GAP + STRIP_WIDTH).contains(&x) {
+            Some(Part::Strip)
+        } else {
+            None
+        }
+    }
+}
+
+impl WidgetImpl for ColorPicker {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, _output: &mut WidgetOutput) {
+        if self.dragging.is_some() && ctx.input.left_mouse_button_released() {
+            self.dragging = None;
+            return;
+        }
+        if self.dragging.is_none() && ctx.input.left_mouse_button_pressed() {
+            self.dragging = ctx
+                .canvas
+                .get_cursor_in_screen_space()
+                .and_then(|pt| self.part_at(pt));
+        } else if ctx.input.get_moved_mouse().is_none() {
+            return;
+        }
+
+        let (x, y) = self.cursor(ctx);
+        match self.dragging {
+            Some(Part::Square) => {
+                self.sat = x / SQUARE_SIZE;
+                self.val = 1.0 - y / SQUARE_SIZE;
+            }
+            // Stay just shy of 360, which would wrap around to red at the top
+            Some(Part::Strip) => {
+                self.hue = (y / SQUARE_SIZE * 360.0).min(359.9);
+            }
+            None => {
+                return;
+            }
+        }
+        self.recalc(ctx);
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.redraw_at(self.top_left, &self.draw);
+    }
+}
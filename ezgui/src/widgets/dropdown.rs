@@ -4,6 +4,8 @@ use crate::{
 };
 use geom::{Distance, Polygon, Pt2D};
 
+const MENU_PADDING: f64 = 5.0;
+
 pub struct Dropdown<T: Clone> {
     current_idx: usize,
     btn: Button,
@@ -49,7 +51,6 @@ impl<T: 'static + PartialEq + Clone> Dropdown<T> {
 
 impl<T: 'static + Clone> Dropdown<T> {
     fn open_menu(&mut self, ctx: &mut EventCtx) {
-        // TODO set current idx in menu
         let mut menu = Menu::new(
             ctx,
             self.choices
@@ -59,6 +60,7 @@ impl<T: 'static + Clone> Dropdown<T> {
                 .collect(),
         )
         .take_menu();
+        menu.set_current_idx(self.current_idx);
         let y1_below = self.btn.top_left.y + self.btn.dims.height + 15.0;
 
         menu.set_pos(ScreenPt::new(
@@ -118,9 +120,8 @@ impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
         if let Some(ref m) = self.menu {
             // We need a background too! Add some padding and an outline.
             // TODO Little embedded Composite could make more sense?
-            let pad = 5.0;
-            let width = m.get_dims().width + 2.0 * pad;
-            let height = m.get_dims().height + 2.0 * pad;
+            let width = m.get_dims().width + 2.0 * MENU_PADDING;
+            let height = m.get_dims().height + 2.0 * MENU_PADDING;
             let rect = Polygon::rounded_rectangle(width, height, Some(5.0));
             let draw_bg = g.upload(GeomBatch::from(vec![
                 (Color::grey(0.3), rect.clone()),
@@ -128,7 +129,7 @@ impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
             ]));
             g.fork(
                 Pt2D::new(0.0, 0.0),
-                ScreenPt::new(m.top_left.x - pad, m.top_left.y - pad),
+                ScreenPt::new(m.top_left.x - MENU_PADDING, m.top_left.y - MENU_PADDING),
                 1.0,
                 // Between SCREENSPACE_Z and TOOLTIP_Z
                 Some(0.1),
@@ -138,9 +139,12 @@ impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
 
             m.draw(g);
 
-            // Dropdown menus often leak out of their Composite
-            g.canvas
-                .mark_covered_area(ScreenRectangle::top_left(m.top_left, m.get_dims()));
+            // Dropdown menus often leak out of their Composite. Include the background, so clicking
+            // on the padding doesn't hit whatever's underneath.
+            g.canvas.mark_covered_area(ScreenRectangle::top_left(
+                ScreenPt::new(m.top_left.x - MENU_PADDING, m.top_left.y - MENU_PADDING),
+                ScreenDims::new(width, height),
+            ));
         }
     }
 
@@ -0,0 +1,128 @@
+use crate::widgets::line_plot::{make_axes, round_up, Axis};
+use crate::{Color, DrawWithTooltips, EventCtx, GeomBatch, Line, Text, TextExt, Widget};
+use abstutil::prettyprint_usize;
+use geom::{Circle, Distance, Polygon, Pt2D};
+
+// Buckets one or more distributions of values, like trip durations, and draws a bar for each
+// bucket. Multiple distributions, like the same trips before and after some change, are bucketed
+// the same way and drawn side by side. Hovering on a bar shows its count.
+pub struct Histogram {}
+
+impl Histogram {
+    // Each distribution has a label and color. The range is split into about num_buckets buckets
+    // of the same round width.
+    pub fn new<T: Axis<T>>(
+        ctx: &EventCtx,
+        distributions: Vec<(&str, Color, Vec<T>)>,
+        num_buckets: usize,
+    ) -> Widget {
+        let legend = make_legend(ctx, &distributions);
+
+        let max = distributions
+            .iter()
+            .filter_map(|(_, _, values)| values.iter().max().cloned())
+            .max()
+            .unwrap_or(T::zero());
+        let rough = max.to_f64() / (num_buckets.max(1) as f64);
+        let bucket_width = if rough > 0.0 {
+            T::round_step(rough)
+        } else {
+            1.0
+        };
+        // Values exactly at the max still need a bucket
+        let num_buckets = ((max.to_f64() / bucket_width).floor() as usize + 1).max(1);
+        let max_x = max.from_f64((num_buckets as f64) * bucket_width);
+
+        let counts: Vec<Vec<usize>> = distributions
+            .iter()
+            .map(|(_, _, values)| {
+                let mut counts = vec![0; num_buckets];
+                for x in values {
+                    let idx = ((x.to_f64() / bucket_width).floor() as usize).min(num_buckets - 1);
+                    counts[idx] += 1;
+                }
+                counts
+            })
+            .collect();
+        let max_y = round_up(
+            counts
+                .iter()
+                .flat_map(|c| c.iter().cloned())
+                .max()
+                .unwrap_or(0),
+        );
+
+        // TODO Tuned to fit the info panel, like the other plots
+        let width = 0.23 * ctx.canvas.window_width;
+        let height = 0.2 * ctx.canvas.window_height;
+
+        let (mut batch, plot_area) = make_axes(ctx, max_x, max_y, width, height);
+        let mut tooltips = Vec::new();
+        let bucket_px = width / (num_buckets as f64);
+        // Leave a little gap between buckets, and split the rest between the distributions
+        let bar_px = 0.8 * bucket_px / (distributions.len().max(1) as f64);
+        for (idx, ((label, color, values), counts)) in
+            distributions.iter().zip(counts.iter()).enumerate()
+        {
+            for (bucket, cnt) in counts.iter().enumerate() {
+                if *cnt == 0 {
+                    continue;
+                }
+                let bar_height = cnt.to_percent(max_y) * height;
+                let x1 = plot_area.x1
+                    + (bucket as f64) * bucket_px
+                    + 0.1 * bucket_px
+                    + (idx as f64) * bar_px;
+                let y1 = plot_area.y1 + height - bar_height;
+                let bar = Polygon::rectangle(bar_px, bar_height).translate(x1, y1);
+                batch.push(*color, bar.clone());
+
+                let low = max.from_f64((bucket as f64) * bucket_width);
+                let high = max.from_f64(((bucket + 1) as f64) * bucket_width);
+                let mut txt = Text::from(Line(label.to_string()).fg(*color));
+                txt.add(Line(format!(
+                    "{} ({}%) between {} and {}",
+                    prettyprint_usize(*cnt),
+                    ((*cnt as f64) / (values.len() as f64) * 100.0).round(),
+                    low.prettyprint(),
+                    high.prettyprint()
+                )));
+                tooltips.push((bar, txt));
+            }
+        }
+
+        // Don't let the plot fill the parent container
+        Widget::row(vec![Widget::col(vec![
+            legend.margin_below(10),
+            DrawWithTooltips::new(ctx, batch, tooltips),
+        ])])
+    }
+}
+
+fn make_legend<T>(ctx: &EventCtx, distributions: &[(&str, Color, Vec<T>)]) -> Widget {
+    // Nothing to tell apart
+    if distributions.len() < 2 {
+        return Widget::nothing();
+    }
+    let radius = 15.0;
+    Widget::row(
+        distributions
+            .iter()
+            .map(|(label, color, _)| {
+                Widget::row(vec![
+                    Widget::draw_batch(
+                        ctx,
+                        GeomBatch::from(vec![(
+                            *color,
+                            Circle::new(Pt2D::new(radius, radius), Distance::meters(radius))
+                                .to_polygon(),
+                        )]),
+                    )
+                    .margin(5),
+                    label.to_string().draw_text(ctx),
+                ])
+            })
+            .collect(),
+    )
+    .flex_wrap(ctx, 24)
+}
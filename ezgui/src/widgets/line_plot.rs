@@ -1,33 +1,35 @@
 use crate::{
-    Checkbox, Color, Drawable, EventCtx, GeomBatch, GfxCtx, JustDraw, Line, ScreenDims, ScreenPt,
+    Checkbox, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, ScreenDims, ScreenPt,
     ScreenRectangle, Text, TextExt, Widget, WidgetImpl, WidgetOutput,
 };
 use abstutil::prettyprint_usize;
-use geom::{Angle, Bounds, Circle, Distance, Duration, FindClosest, PolyLine, Polygon, Pt2D, Time};
+use geom::{Bounds, Circle, Distance, Duration, FindClosest, PolyLine, Polygon, Pt2D, Time};
 use std::collections::HashSet;
 
-// The X is always time
-pub struct LinePlot<T: Yvalue<T>> {
+// Plots some series of (X, Y) points, where X is usually a Time or Duration. Both axes start at
+// zero.
+pub struct LinePlot<X: Axis<X>, Y: Axis<Y>> {
     draw: Drawable,
 
-    // The geometry here is in screen-space.
-    max_x: Time,
-    max_y: T,
+    // The geometry here is in screen-space, relative to the top-left of the plot area.
+    max_x: X,
+    max_y: Y,
     closest: FindClosest<String>,
+    plot_area: ScreenRectangle,
 
     top_left: ScreenPt,
     dims: ScreenDims,
 }
 
-pub struct PlotOptions<T: Yvalue<T>> {
+pub struct PlotOptions<X: Axis<X>, Y: Axis<Y>> {
     pub filterable: bool,
-    pub max_x: Option<Time>,
-    pub max_y: Option<T>,
+    pub max_x: Option<X>,
+    pub max_y: Option<Y>,
     pub disabled: HashSet<String>,
 }
 
-impl<T: Yvalue<T>> PlotOptions<T> {
-    pub fn filterable() -> PlotOptions<T> {
+impl<X: Axis<X>, Y: Axis<Y>> PlotOptions<X, Y> {
+    pub fn filterable() -> PlotOptions<X, Y> {
         PlotOptions {
             filterable: true,
             max_x: None,
@@ -36,7 +38,7 @@ impl<T: Yvalue<T>> PlotOptions<T> {
         }
     }
 
-    pub fn fixed() -> PlotOptions<T> {
+    pub fn fixed() -> PlotOptions<X, Y> {
         PlotOptions {
             filterable: false,
             max_x: None,
@@ -46,157 +48,67 @@ impl<T: Yvalue<T>> PlotOptions<T> {
     }
 }
 
-impl<T: Yvalue<T>> LinePlot<T> {
-    pub fn new(ctx: &EventCtx, mut series: Vec<Series<T>>, opts: PlotOptions<T>) -> Widget {
+impl<X: Axis<X>, Y: Axis<Y>> LinePlot<X, Y> {
+    pub fn new(ctx: &EventCtx, mut series: Vec<Series<X, Y>>, opts: PlotOptions<X, Y>) -> Widget {
         let legend = make_legend(ctx, &series, &opts);
         series.retain(|s| !opts.disabled.contains(&s.label));
-
-        // Assume min_x is Time::START_OF_DAY and min_y is T::zero()
-        let max_x = opts.max_x.unwrap_or_else(|| {
-            series
-                .iter()
-                .map(|s| {
-                    s.pts
-                        .iter()
-                        .map(|(t, _)| *t)
-                        .max()
-                        .unwrap_or(Time::START_OF_DAY)
-                })
-                .max()
-                .unwrap_or(Time::START_OF_DAY)
-        });
-        let max_y = opts.max_y.unwrap_or_else(|| {
-            series
-                .iter()
-                .map(|s| {
-                    s.pts
-                        .iter()
-                        .map(|(_, value)| *value)
-                        .max()
-                        .unwrap_or(T::zero())
-                })
-                .max()
-                .unwrap_or(T::zero())
-        });
+        let (max_x, max_y) = find_max(&series, &opts);
 
         // TODO Tuned to fit the info panel. Instead these should somehow stretch to fill their
         // container.
         let width = 0.23 * ctx.canvas.window_width;
         let height = 0.2 * ctx.canvas.window_height;
 
-        let mut batch = GeomBatch::new();
-        // Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of
-        // the range.
-        // TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid
-        // lines.
-        {
-            let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
-            for i in 0..10 {
-                let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = y.to_percent(max_y);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(0.0, (1.0 - pct) * height),
-                        Pt2D::new(width, (1.0 - pct) * height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
-        // X axis grid
-        if max_x != Time::START_OF_DAY {
-            let order_of_mag = 10.0_f64.powf(max_x.inner_seconds().log10().ceil());
-            for i in 0..10 {
-                let x = Time::START_OF_DAY + Duration::seconds(order_of_mag / 10.0 * (i as f64));
-                let pct = x.to_percent(max_x);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(pct * width, 0.0),
-                        Pt2D::new(pct * width, height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
-
+        let (mut batch, plot_area) = make_axes(ctx, max_x, max_y, width, height);
         let mut closest = FindClosest::new(&Bounds::from(&vec![
             Pt2D::new(0.0, 0.0),
             Pt2D::new(width, height),
         ]));
         for s in series {
-            if max_x == Time::START_OF_DAY {
+            if max_x == X::zero() {
                 continue;
             }
 
             let mut pts = Vec::new();
-            for (t, y) in s.pts {
-                let percent_x = t.to_percent(max_x);
-                let percent_y = y.to_percent(max_y);
+            for (x, y) in s.pts {
                 pts.push(Pt2D::new(
-                    percent_x * width,
+                    x.to_percent(max_x) * width,
                     // Y inversion! :D
-                    (1.0 - percent_y) * height,
+                    (1.0 - y.to_percent(max_y)) * height,
                 ));
             }
             pts.dedup();
             if pts.len() >= 2 {
                 closest.add(s.label.clone(), &pts);
-                batch.push(s.color, thick_lineseries(pts, Distance::meters(5.0)));
+                batch.push(
+                    s.color,
+                    thick_lineseries(pts, Distance::meters(5.0))
+                        .translate(plot_area.x1, plot_area.y1),
+                );
             }
         }
 
+        let dims = batch.get_dims();
         let plot = LinePlot {
             draw: ctx.upload(batch),
             closest,
             max_x,
             max_y,
+            plot_area,
 
             top_left: ScreenPt::new(0.0, 0.0),
-            dims: ScreenDims::new(width, height),
+            dims,
         };
 
-        let num_x_labels = 3;
-        let mut row = Vec::new();
-        for i in 0..num_x_labels {
-            let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
-            let t = max_x.percent_of(percent_x);
-            // TODO Need ticks now to actually see where this goes
-            let batch = Text::from(Line(t.to_string()))
-                .render_ctx(ctx)
-                .rotate(Angle::new_degs(-15.0))
-                .autocrop();
-            // The text is already scaled; don't use Widget::draw_batch and scale it again.
-            row.push(JustDraw::wrap(ctx, batch));
-        }
-        let x_axis = Widget::row(row).padding(10);
-
-        let num_y_labels = 4;
-        let mut col = Vec::new();
-        for i in 0..num_y_labels {
-            let percent_y = (i as f64) / ((num_y_labels - 1) as f64);
-            col.push(max_y.from_percent(percent_y).prettyprint().draw_text(ctx));
-        }
-        col.reverse();
-        let y_axis = Widget::col(col).padding(10);
-
-        // Don't let the x-axis fill the parent container
+        // Don't let the plot fill the parent container
         Widget::row(vec![Widget::col(vec![
             legend.margin_below(10),
-            Widget::row(vec![y_axis.evenly_spaced(), Widget::new(Box::new(plot))]),
-            x_axis.evenly_spaced(),
+            Widget::new(Box::new(plot)),
         ])])
     }
 }
 
-impl<T: Yvalue<T>> WidgetImpl for LinePlot<T> {
+impl<X: Axis<X>, Y: Axis<Y>> WidgetImpl for LinePlot<X, Y> {
     fn get_dims(&self) -> ScreenDims {
         self.dims
     }
@@ -211,22 +123,28 @@ impl<T: Yvalue<T>> WidgetImpl for LinePlot<T> {
         g.redraw_at(self.top_left, &self.draw);
 
         if let Some(cursor) = g.canvas.get_cursor_in_screen_space() {
-            if ScreenRectangle::top_left(self.top_left, self.dims).contains(cursor) {
+            let x1 = self.top_left.x + self.plot_area.x1;
+            let y1 = self.top_left.y + self.plot_area.y1;
+            let width = self.plot_area.width();
+            let height = self.plot_area.height();
+            if ScreenRectangle::top_left(ScreenPt::new(x1, y1), ScreenDims::new(width, height))
+                .contains(cursor)
+            {
                 let radius = Distance::meters(15.0);
                 let mut txt = Text::new();
-                for (label, pt, _) in self.closest.all_close_pts(
-                    Pt2D::new(cursor.x - self.top_left.x, cursor.y - self.top_left.y),
-                    radius,
-                ) {
-                    // TODO If some/all of the matches have the same t, write it once?
-                    let t = self.max_x.percent_of(pt.x() / self.dims.width);
-                    let y_percent = 1.0 - (pt.y() / self.dims.height);
+                for (label, pt, _) in self
+                    .closest
+                    .all_close_pts(Pt2D::new(cursor.x - x1, cursor.y - y1), radius)
+                {
+                    // TODO If some/all of the matches have the same x, write it once?
+                    let x = self.max_x.from_percent(pt.x() / width);
+                    let y_percent = 1.0 - (pt.y() / height);
 
                     // TODO Draw this info in the ColorLegend
                     txt.add(Line(format!(
                         "{}: at {}, {}",
                         label,
-                        t.ampm_tostring(),
+                        x.prettyprint(),
                         self.max_y.from_percent(y_percent).prettyprint()
                     )));
                 }
@@ -241,7 +159,187 @@ impl<T: Yvalue<T>> WidgetImpl for LinePlot<T> {
     }
 }
 
-pub trait Yvalue<T>: 'static + Copy + std::cmp::Ord {
+// Unless the options say otherwise, round the max of each axis up to the next tick.
+pub(crate) fn find_max<X: Axis<X>, Y: Axis<Y>>(
+    series: &[Series<X, Y>],
+    opts: &PlotOptions<X, Y>,
+) -> (X, Y) {
+    let max_x = opts.max_x.unwrap_or_else(|| {
+        round_up(
+            series
+                .iter()
+                .filter_map(|s| s.pts.iter().map(|(x, _)| *x).max())
+                .max()
+                .unwrap_or(X::zero()),
+        )
+    });
+    let max_y = opts.max_y.unwrap_or_else(|| {
+        round_up(
+            series
+                .iter()
+                .filter_map(|s| s.pts.iter().map(|(_, y)| *y).max())
+                .max()
+                .unwrap_or(Y::zero()),
+        )
+    });
+    (max_x, max_y)
+}
+
+pub(crate) fn round_up<T: Axis<T>>(max: T) -> T {
+    let x = max.to_f64();
+    if x <= 0.0 {
+        return max;
+    }
+    let step = T::round_step(x / MAX_TICKS);
+    max.from_f64((x / step).ceil() * step)
+}
+
+// Draws gridlines and labeled ticks for both axes. The plot area is width by height; the
+// returned rectangle says where it wound up, leaving room for the labels to the left and below.
+pub(crate) fn make_axes<X: Axis<X>, Y: Axis<Y>>(
+    ctx: &EventCtx,
+    max_x: X,
+    max_y: Y,
+    width: f64,
+    height: f64,
+) -> (GeomBatch, ScreenRectangle) {
+    let tick_len = 5.0;
+    let gap = 5.0;
+
+    let x_labels: Vec<(f64, GeomBatch)> = ticks(max_x)
+        .into_iter()
+        .map(|x| {
+            (
+                x.to_percent(max_x) * width,
+                Text::from(Line(x.prettyprint()).small())
+                    .render_ctx(ctx)
+                    .autocrop(),
+            )
+        })
+        .collect();
+    let y_labels: Vec<(f64, GeomBatch)> = ticks(max_y)
+        .into_iter()
+        .map(|y| {
+            (
+                (1.0 - y.to_percent(max_y)) * height,
+                Text::from(Line(y.prettyprint()).small())
+                    .render_ctx(ctx)
+                    .autocrop(),
+            )
+        })
+        .collect();
+
+    // Leave room for labels sticking out past the plot area
+    let left = y_labels
+        .iter()
+        .map(|(_, b)| b.get_dims().width)
+        .fold(0.0, f64::max)
+        + tick_len
+        + gap;
+    let top = y_labels
+        .iter()
+        .map(|(_, b)| b.get_dims().height / 2.0)
+        .fold(0.0, f64::max);
+    let right = x_labels
+        .last()
+        .map(|(_, b)| b.get_dims().width / 2.0)
+        .unwrap_or(0.0);
+    let label_height = x_labels
+        .iter()
+        .map(|(_, b)| b.get_dims().height)
+        .fold(0.0, f64::max);
+
+    let mut batch = GeomBatch::new();
+    // Fill out the full dimensions, even when labels don't reach the corners
+    batch.push(
+        Color::INVISIBLE,
+        Polygon::rectangle(
+            left + width + right,
+            top + height + tick_len + gap + label_height,
+        ),
+    );
+    let grid = Color::hex("#7C7C7C");
+    for (x, label) in x_labels {
+        batch.push(
+            grid,
+            PolyLine::new(vec![
+                Pt2D::new(left + x, top),
+                Pt2D::new(left + x, top + height + tick_len),
+            ])
+            .make_polygons(Distance::meters(1.0)),
+        );
+        let dims = label.get_dims();
+        batch.append(label.translate(left + x - dims.width / 2.0, top + height + tick_len + gap));
+    }
+    for (y, label) in y_labels {
+        batch.push(
+            grid,
+            PolyLine::new(vec![
+                Pt2D::new(left - tick_len, top + y),
+                Pt2D::new(left + width, top + y),
+            ])
+            .make_polygons(Distance::meters(1.0)),
+        );
+        let dims = label.get_dims();
+        batch.append(label.translate(
+            left - tick_len - gap - dims.width,
+            top + y - dims.height / 2.0,
+        ));
+    }
+
+    (
+        batch,
+        ScreenRectangle::top_left(ScreenPt::new(left, top), ScreenDims::new(width, height)),
+    )
+}
+
+// Evenly spaced values from zero up to max, at a round step
+fn ticks<T: Axis<T>>(max: T) -> Vec<T> {
+    let x = max.to_f64();
+    if x <= 0.0 {
+        return vec![max];
+    }
+    let step = T::round_step(x / MAX_TICKS);
+    let mut result = Vec::new();
+    let mut i = 0.0;
+    // Tolerate a bit of floating point error at the end
+    while i * step <= x * 1.0001 {
+        result.push(max.from_f64(i * step));
+        i += 1.0;
+    }
+    result
+}
+
+// Aim for at most this many ticks along an axis
+const MAX_TICKS: f64 = 5.0;
+
+// The smallest of 1, 2, or 5 times some power of 10 that's at least rough.
+fn round_step(rough: f64) -> f64 {
+    let magnitude = 10.0_f64.powf(rough.log10().floor());
+    for mult in &[1.0, 2.0, 5.0] {
+        if magnitude * mult >= rough {
+            return magnitude * mult;
+        }
+    }
+    magnitude * 10.0
+}
+
+// For times, step by whole minutes and hours instead.
+fn round_step_seconds(rough: f64) -> f64 {
+    for step in &[
+        1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0,
+        10800.0, 21600.0, 43200.0,
+    ] {
+        if *step >= rough {
+            return *step;
+        }
+    }
+    // Whole days
+    86400.0 * (rough / 86400.0).ceil()
+}
+
+// Something that can go along either axis of a plot.
+pub trait Axis<T>: 'static + Copy + std::cmp::Ord {
     // percent is [0.0, 1.0]
     fn from_percent(&self, percent: f64) -> T;
     fn to_percent(self, max: T) -> f64;
@@ -250,9 +348,14 @@ pub trait Yvalue<T>: 'static + Copy + std::cmp::Ord {
     fn to_f64(self) -> f64;
     fn from_f64(&self, x: f64) -> T;
     fn zero() -> T;
+    // The smallest round distance at least this big, in the units of to_f64. Used to space out
+    // ticks.
+    fn round_step(rough: f64) -> f64 {
+        round_step(rough)
+    }
 }
 
-impl Yvalue<usize> for usize {
+impl Axis<usize> for usize {
     fn from_percent(&self, percent: f64) -> usize {
         ((*self as f64) * percent) as usize
     }
@@ -275,8 +378,11 @@ impl Yvalue<usize> for usize {
     fn zero() -> usize {
         0
     }
+    fn round_step(rough: f64) -> f64 {
+        round_step(rough).max(1.0)
+    }
 }
-impl Yvalue<Duration> for Duration {
+impl Axis<Duration> for Duration {
     fn from_percent(&self, percent: f64) -> Duration {
         *self * percent
     }
@@ -299,19 +405,49 @@ impl Yvalue<Duration> for Duration {
     fn zero() -> Duration {
         Duration::ZERO
     }
+    fn round_step(rough: f64) -> f64 {
+        round_step_seconds(rough)
+    }
+}
+impl Axis<Time> for Time {
+    fn from_percent(&self, percent: f64) -> Time {
+        self.percent_of(percent)
+    }
+    fn to_percent(self, max: Time) -> f64 {
+        if max == Time::START_OF_DAY {
+            0.0
+        } else {
+            Time::to_percent(self, max)
+        }
+    }
+    fn prettyprint(self) -> String {
+        self.ampm_tostring()
+    }
+    fn to_f64(self) -> f64 {
+        self.inner_seconds()
+    }
+    fn from_f64(&self, x: f64) -> Time {
+        Time::START_OF_DAY + Duration::seconds(x)
+    }
+    fn zero() -> Time {
+        Time::START_OF_DAY
+    }
+    fn round_step(rough: f64) -> f64 {
+        round_step_seconds(rough)
+    }
 }
 
-pub struct Series<T> {
+pub struct Series<X, Y> {
     pub label: String,
     pub color: Color,
-    // X-axis is time. Assume this is sorted by X.
-    pub pts: Vec<(Time, T)>,
+    // Assume this is sorted by X.
+    pub pts: Vec<(X, Y)>,
 }
 
-pub fn make_legend<T: Yvalue<T>>(
+pub fn make_legend<X: Axis<X>, Y: Axis<Y>>(
     ctx: &EventCtx,
-    series: &Vec<Series<T>>,
-    opts: &PlotOptions<T>,
+    series: &Vec<Series<X, Y>>,
+    opts: &PlotOptions<X, Y>,
 ) -> Widget {
     let mut row = Vec::new();
     let mut seen = HashSet::new();
@@ -33,6 +33,12 @@ impl<T: 'static + Clone> Menu<T> {
         &self.choices[self.current_idx].data
     }
 
+    pub(crate) fn set_current_idx(&mut self, idx: usize) {
+        if idx < self.choices.len() {
+            self.current_idx = idx;
+        }
+    }
+
     fn calculate_txt(&self) -> Text {
         let mut txt = Text::new();
 
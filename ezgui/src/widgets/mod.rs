@@ -1,18 +1,26 @@
 pub mod autocomplete;
 pub mod button;
 pub mod checkbox;
+pub mod color_picker;
 pub mod compare_times;
 pub mod containers;
 pub mod dropdown;
 pub mod filler;
+pub mod histogram;
 pub mod just_draw;
 pub mod line_plot;
 pub mod menu;
+pub mod modal_dialog;
 pub mod persistent_split;
+pub mod radial_menu;
 pub mod scatter_plot;
+pub mod scrolling_panel;
 pub mod slider;
 pub mod spinner;
+pub mod table;
+pub mod tabs;
 pub mod text_box;
+pub mod unit_spinner;
 
 use crate::{EventCtx, GfxCtx, ScreenDims, ScreenPt};
 
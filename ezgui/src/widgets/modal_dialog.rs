@@ -0,0 +1,91 @@
+use crate::{
+    hotkey, Btn, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, MultiKey,
+    Outcome, Text, VerticalAlignment, Widget,
+};
+use geom::Polygon;
+
+// A panel in the middle of the screen that has to be answered before anything else happens.
+// Everything behind it is dimmed, and the map ignores the mouse. While it's open, callers should
+// skip their own event handling, same as with a ModalStack.
+pub struct ModalDialog {
+    composite: Composite,
+    // Pressing escape picks this choice
+    escape: Option<String>,
+}
+
+impl ModalDialog {
+    // Each choice becomes a button, producing its label when clicked or when its key is pressed.
+    pub fn new(
+        ctx: &mut EventCtx,
+        title: &str,
+        body: Widget,
+        choices: Vec<(&str, Option<MultiKey>)>,
+    ) -> ModalDialog {
+        let buttons = choices
+            .into_iter()
+            .map(|(label, key)| Btn::text_bg2(label).build_def(ctx, key).margin_left(10))
+            .collect();
+        ModalDialog {
+            composite: Composite::new(
+                Widget::col(vec![
+                    Line(title).small_heading().draw(ctx),
+                    body.margin_vert(10),
+                    Widget::row(buttons).align_right(),
+                ])
+                .bg(ctx.style().panel_bg)
+                .outline(2.0, Color::WHITE)
+                .padding(16),
+            )
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Center)
+            .build(ctx),
+            escape: None,
+        }
+    }
+
+    // Just an "OK" button, chosen with enter or escape.
+    pub fn message<S: Into<String>>(ctx: &mut EventCtx, title: &str, lines: Vec<S>) -> ModalDialog {
+        let mut txt = Text::new();
+        for l in lines {
+            txt.add(Line(l));
+        }
+        let mut dialog =
+            ModalDialog::new(ctx, title, txt.draw(ctx), vec![("OK", hotkey(Key::Enter))]);
+        dialog.escape = Some("OK".to_string());
+        dialog
+    }
+
+    // Produces "OK" with enter or "Cancel" with escape.
+    pub fn ok_cancel(ctx: &mut EventCtx, title: &str, body: Widget) -> ModalDialog {
+        ModalDialog::new(
+            ctx,
+            title,
+            body,
+            vec![("OK", hotkey(Key::Enter)), ("Cancel", hotkey(Key::Escape))],
+        )
+    }
+
+    // The label of the chosen button, once there is one.
+    pub fn event(&mut self, ctx: &mut EventCtx) -> Option<String> {
+        if let Some(ref label) = self.escape {
+            if ctx.input.key_pressed(Key::Escape, label) {
+                return Some(label.clone());
+            }
+        }
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => Some(x),
+            None => None,
+        }
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        g.fork_screenspace();
+        g.draw_polygon(
+            Color::BLACK.alpha(0.5),
+            &Polygon::rectangle(g.canvas.window_width, g.canvas.window_height),
+        );
+        g.unfork();
+        g.canvas.cover_whole_window();
+
+        self.composite.draw(g);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    Btn, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, MultiKey, Outcome, ScreenDims,
+    ScreenPt, Text, Widget, WidgetImpl, WidgetOutput,
+};
+use geom::{Angle, Circle, Distance, Line as GeomLine, Polygon, Pt2D};
+
+const INNER_RADIUS: f64 = 35.0;
+const OUTER_RADIUS: f64 = 130.0;
+// How many points to use per degree of arc
+const ARC_RESOLUTION: f64 = 0.25;
+
+// A pie menu, usually opened around the cursor. Each choice is a slice; the first one is at the
+// top, and the rest go clockwise. Clicking a slice or pressing its hotkey produces
+// Outcome::Clicked with the choice's label.
+pub struct RadialMenu {
+    choices: Vec<(Option<MultiKey>, String)>,
+    // Relative to the top-left
+    slices: Vec<Polygon>,
+
+    draw: Drawable,
+    draw_hovered: Vec<Drawable>,
+    hovering: Option<usize>,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+impl RadialMenu {
+    // Past this many choices, the slices get too thin to read or hit, so just make a list.
+    pub const MAX_SLICES: usize = 8;
+
+    // Falls back to a plain column of buttons when there are too many (or too few) choices for a
+    // pie.
+    pub fn new(ctx: &EventCtx, choices: Vec<(Option<MultiKey>, String)>) -> Widget {
+        if choices.len() < 2 || choices.len() > RadialMenu::MAX_SLICES {
+            return Widget::col(
+                choices
+                    .into_iter()
+                    .map(|(key, label)| Btn::text_fg(label).build_def(ctx, key))
+                    .collect(),
+            );
+        }
+
+        let center = Pt2D::new(OUTER_RADIUS, OUTER_RADIUS);
+        let span = 360.0 / (choices.len() as f64);
+        let mut slices = Vec::new();
+        let mut batch = GeomBatch::new();
+        batch.push(
+            Color::grey(0.2).alpha(0.9),
+            Circle::new(center, Distance::meters(OUTER_RADIUS)).to_polygon(),
+        );
+        for (idx, (key, label)) in choices.iter().enumerate() {
+            // Angles are clockwise from the positive x axis, so -90 is straight up.
+            let mid = -90.0 + (idx as f64) * span;
+            slices.push(annular_sector(center, mid - span / 2.0, mid + span / 2.0));
+            batch.push(Color::WHITE.alpha(0.5), divider(center, mid - span / 2.0));
+
+            let mut txt = Text::from(Line(label));
+            if let Some(ref key) = key {
+                txt.add(Line(key.describe()).small().fg(Color::YELLOW));
+            }
+            let label_pt = center.project_away(
+                Distance::meters((INNER_RADIUS + OUTER_RADIUS) / 2.0),
+                Angle::new_degs(mid),
+            );
+            batch.append(txt.render_ctx(ctx).centered_on(label_pt));
+        }
+        // Leave the hub empty, so it's obvious where the slices start.
+        batch.push(
+            Color::grey(0.4),
+            Circle::new(center, Distance::meters(INNER_RADIUS)).to_polygon(),
+        );
+
+        let draw_hovered = slices
+            .iter()
+            .map(|s| ctx.upload(GeomBatch::from(vec![(Color::WHITE.alpha(0.3), s.clone())])))
+            .collect();
+
+        Widget::new(Box::new(RadialMenu {
+            choices,
+            slices,
+
+            draw: ctx.upload(batch),
+            draw_hovered,
+            hovering: None,
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims: ScreenDims::new(2.0 * OUTER_RADIUS, 2.0 * OUTER_RADIUS),
+        }))
+    }
+}
+
+impl WidgetImpl for RadialMenu {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        if ctx.redo_mouseover() {
+            self.hovering = None;
+            if let Some(pt) = ctx.canvas.get_cursor_in_screen_space() {
+                let pt = Pt2D::new(pt.x - self.top_left.x, pt.y - self.top_left.y);
+                self.hovering = self.slices.iter().position(|s| s.contains_pt(pt));
+            }
+        }
+        if let Some(idx) = self.hovering {
+            if ctx.normal_left_click() {
+                self.hovering = None;
+                output.outcome = Some(Outcome::Clicked(self.choices[idx].1.clone()));
+                return;
+            }
+        }
+
+        for (key, label) in &self.choices {
+            if let Some(ref key) = key {
+                if ctx.input.new_was_pressed(key) {
+                    self.hovering = None;
+                    output.outcome = Some(Outcome::Clicked(label.clone()));
+                    return;
+                }
+            }
+        }
+
+        if self.hovering.is_some() {
+            ctx.cursor_clickable();
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.redraw_at(self.top_left, &self.draw);
+        if let Some(idx) = self.hovering {
+            g.redraw_at(self.top_left, &self.draw_hovered[idx]);
+        }
+    }
+}
+
+// Points along a circle, from one angle to another (in degrees, clockwise).
+fn arc(center: Pt2D, radius: f64, from: f64, to: f64) -> Vec<Pt2D> {
+    let steps = (((to - from) * ARC_RESOLUTION).ceil() as usize).max(2);
+    (0..=steps)
+        .map(|i| {
+            let degs = from + (to - from) * (i as f64) / (steps as f64);
+            center.project_away(Distance::meters(radius), Angle::new_degs(degs))
+        })
+        .collect()
+}
+
+fn annular_sector(center: Pt2D, from: f64, to: f64) -> Polygon {
+    let mut pts = arc(center, OUTER_RADIUS, from, to);
+    let mut inner = arc(center, INNER_RADIUS, from, to);
+    inner.reverse();
+    pts.extend(inner);
+    Polygon::new(&pts)
+}
+
+// The straight edge between two neighboring slices
+fn divider(center: Pt2D, degs: f64) -> Polygon {
+    let angle = Angle::new_degs(degs);
+    GeomLine::new(
+        center.project_away(Distance::meters(INNER_RADIUS), angle),
+        center.project_away(Distance::meters(OUTER_RADIUS), angle),
+    )
+    .make_polygons(Distance::meters(1.0))
+}
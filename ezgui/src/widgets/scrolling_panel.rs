@@ -0,0 +1,164 @@
+use crate::{Color, EventCtx, GfxCtx, ScreenDims, ScreenPt, ScreenRectangle};
+use geom::Pt2D;
+
+const SCROLL_SPEED: f64 = 5.0;
+const SCROLLBAR_THICKNESS: f64 = 5.0;
+
+// Clips whatever's drawn inside to a rectangle on the screen, and scrolls with the mouse wheel or
+// by dragging. Unlike a scrollable Composite, the contents don't have to be widgets; draw anything
+// with the usual GfxCtx methods, using coordinates relative to the top-left of the contents.
+pub struct ScrollingPanel {
+    rect: ScreenRectangle,
+    contents: ScreenDims,
+    offset: (f64, f64),
+    // Where the cursor was last, while dragging
+    dragging: Option<ScreenPt>,
+}
+
+impl ScrollingPanel {
+    pub fn new(rect: ScreenRectangle, contents: ScreenDims) -> ScrollingPanel {
+        ScrollingPanel {
+            rect,
+            contents,
+            offset: (0.0, 0.0),
+            dragging: None,
+        }
+    }
+
+    // Keeps the current offset, as long as it's still valid.
+    pub fn set_contents_dims(&mut self, contents: ScreenDims) {
+        self.contents = contents;
+        let offset = self.offset;
+        self.set_offset(offset);
+    }
+
+    pub fn set_rect(&mut self, rect: ScreenRectangle) {
+        self.rect = rect;
+        let offset = self.offset;
+        self.set_offset(offset);
+    }
+
+    pub fn get_offset(&self) -> (f64, f64) {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: (f64, f64)) {
+        let (max_x, max_y) = self.max_offset();
+        self.offset = (
+            abstutil::clamp(offset.0, 0.0, max_x),
+            abstutil::clamp(offset.1, 0.0, max_y),
+        );
+    }
+
+    // Scrolls just enough to show the point, given in the coordinates of the contents.
+    pub fn scroll_to(&mut self, pt: ScreenPt) {
+        let (mut x, mut y) = self.offset;
+        if pt.x < x {
+            x = pt.x;
+        } else if pt.x > x + self.rect.width() {
+            x = pt.x - self.rect.width();
+        }
+        if pt.y < y {
+            y = pt.y;
+        } else if pt.y > y + self.rect.height() {
+            y = pt.y - self.rect.height();
+        }
+        self.set_offset((x, y));
+    }
+
+    // True if the contents moved.
+    pub fn event(&mut self, ctx: &mut EventCtx) -> bool {
+        let before = self.offset;
+        let cursor = ctx.canvas.get_cursor_in_screen_space();
+        let hovering = cursor.map(|pt| self.rect.contains(pt)).unwrap_or(false);
+
+        if let Some(last) = self.dragging {
+            let pt = ctx.canvas.get_cursor();
+            self.set_offset((self.offset.0 + last.x - pt.x, self.offset.1 + last.y - pt.y));
+            self.dragging = Some(pt);
+            if ctx.input.left_mouse_button_released() {
+                self.dragging = None;
+            }
+        } else if hovering {
+            if let Some((dx, dy)) = ctx.input.get_mouse_scroll() {
+                self.set_offset((
+                    self.offset.0 + dx * SCROLL_SPEED,
+                    self.offset.1 - dy * SCROLL_SPEED,
+                ));
+            } else if ctx.input.left_mouse_button_pressed() {
+                self.dragging = cursor;
+            }
+        }
+
+        self.offset != before
+    }
+
+    // The cursor, in the coordinates of the contents. None if it's outside the panel.
+    pub fn get_cursor_in_contents(&self, ctx: &EventCtx) -> Option<ScreenPt> {
+        let pt = ctx.canvas.get_cursor_in_screen_space()?;
+        if !self.rect.contains(pt) {
+            return None;
+        }
+        Some(ScreenPt::new(
+            pt.x - self.rect.x1 + self.offset.0,
+            pt.y - self.rect.y1 + self.offset.1,
+        ))
+    }
+
+    pub fn draw<F: FnOnce(&mut GfxCtx)>(&self, g: &mut GfxCtx, draw_contents: F) {
+        g.canvas.mark_covered_area(self.rect.clone());
+
+        g.enable_clipping(self.rect.clone());
+        g.fork(
+            Pt2D::new(0.0, 0.0),
+            ScreenPt::new(self.rect.x1 - self.offset.0, self.rect.y1 - self.offset.1),
+            1.0,
+            None,
+        );
+        draw_contents(g);
+        g.unfork();
+        g.disable_clipping();
+
+        // Just show where we are; the scrollbars aren't interactive.
+        let (max_x, max_y) = self.max_offset();
+        g.fork_screenspace();
+        if max_x > 0.0 {
+            let pct_shown = self.rect.width() / self.contents.width;
+            let len = pct_shown * self.rect.width();
+            g.draw_rounded_rectangle(
+                Color::grey(0.7),
+                &ScreenRectangle::top_left(
+                    ScreenPt::new(
+                        self.rect.x1 + self.offset.0 / max_x * (self.rect.width() - len),
+                        self.rect.y2 - SCROLLBAR_THICKNESS,
+                    ),
+                    ScreenDims::new(len, SCROLLBAR_THICKNESS),
+                ),
+                None,
+            );
+        }
+        if max_y > 0.0 {
+            let pct_shown = self.rect.height() / self.contents.height;
+            let len = pct_shown * self.rect.height();
+            g.draw_rounded_rectangle(
+                Color::grey(0.7),
+                &ScreenRectangle::top_left(
+                    ScreenPt::new(
+                        self.rect.x2 - SCROLLBAR_THICKNESS,
+                        self.rect.y1 + self.offset.1 / max_y * (self.rect.height() - len),
+                    ),
+                    ScreenDims::new(SCROLLBAR_THICKNESS, len),
+                ),
+                None,
+            );
+        }
+        g.unfork();
+    }
+
+    fn max_offset(&self) -> (f64, f64) {
+        (
+            (self.contents.width - self.rect.width()).max(0.0),
+            (self.contents.height - self.rect.height()).max(0.0),
+        )
+    }
+}
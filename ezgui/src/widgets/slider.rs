@@ -1,6 +1,6 @@
 use crate::{
-    Color, Drawable, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt, ScreenRectangle, Widget,
-    WidgetImpl, WidgetOutput,
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, MultiKey, ScreenDims, ScreenPt,
+    ScreenRectangle, Widget, WidgetImpl, WidgetOutput,
 };
 use geom::{Circle, Distance, Polygon, Pt2D};
 
@@ -12,6 +12,8 @@ pub struct Slider {
     horiz: bool,
     main_bg_len: f64,
     dragger_len: f64,
+    // If set, the dragger snaps to this many evenly spaced values
+    steps: Option<usize>,
 
     draw: Drawable,
 
@@ -20,6 +22,8 @@ pub struct Slider {
 }
 
 const BG_CROSS_AXIS_LEN: f64 = 20.0;
+// How far the arrow keys move a slider without steps, while the mouse is over it
+const NUDGE_PERCENT: f64 = 0.01;
 
 impl Slider {
     pub fn horizontal(
@@ -28,33 +32,43 @@ impl Slider {
         dragger_len: f64,
         current_percent: f64,
     ) -> Widget {
-        let mut s = Slider {
-            current_percent,
-            mouse_on_slider: false,
-            dragging: false,
-
-            horiz: true,
-            main_bg_len: width,
-            dragger_len,
+        Slider::new(ctx, true, width, dragger_len, current_percent, None)
+    }
 
-            draw: ctx.upload(GeomBatch::new()),
+    pub fn vertical(ctx: &EventCtx, height: f64, dragger_len: f64, current_percent: f64) -> Widget {
+        Slider::new(ctx, false, height, dragger_len, current_percent, None)
+    }
 
-            top_left: ScreenPt::new(0.0, 0.0),
-            dims: ScreenDims::new(0.0, 0.0),
-        };
-        s.recalc(ctx);
-        Widget::new(Box::new(s))
+    // Picks one of num_steps values. Use get_value to find out which.
+    pub fn horizontal_steps(
+        ctx: &EventCtx,
+        width: f64,
+        dragger_len: f64,
+        num_steps: usize,
+        current_step: usize,
+    ) -> Widget {
+        assert!(num_steps >= 2 && current_step < num_steps);
+        let percent = (current_step as f64) / ((num_steps - 1) as f64);
+        Slider::new(ctx, true, width, dragger_len, percent, Some(num_steps))
     }
 
-    pub fn vertical(ctx: &EventCtx, height: f64, dragger_len: f64, current_percent: f64) -> Widget {
+    fn new(
+        ctx: &EventCtx,
+        horiz: bool,
+        main_bg_len: f64,
+        dragger_len: f64,
+        current_percent: f64,
+        steps: Option<usize>,
+    ) -> Widget {
         let mut s = Slider {
             current_percent,
             mouse_on_slider: false,
             dragging: false,
 
-            horiz: false,
-            main_bg_len: height,
+            horiz,
+            main_bg_len,
             dragger_len,
+            steps,
 
             draw: ctx.upload(GeomBatch::new()),
 
@@ -114,7 +128,18 @@ impl Slider {
     }
 
     pub fn get_value(&self, num_items: usize) -> usize {
-        (self.current_percent * (num_items as f64 - 1.0)) as usize
+        (self.current_percent * (num_items as f64 - 1.0)).round() as usize
+    }
+
+    // Clamps, and snaps to the nearest step
+    fn set_from_input(&mut self, percent: f64) {
+        let percent = percent.min(1.0).max(0.0);
+        self.current_percent = if let Some(n) = self.steps {
+            let max = (n - 1) as f64;
+            (percent * max).round() / max
+        } else {
+            percent
+        };
     }
 
     pub fn set_percent(&mut self, ctx: &EventCtx, percent: f64) {
@@ -143,7 +168,7 @@ impl Slider {
                     (ctx.canvas.get_cursor().y - self.top_left.y - (self.dragger_len / 2.0))
                         / (self.main_bg_len - self.dragger_len)
                 };
-                self.current_percent = percent.min(1.0).max(0.0);
+                self.set_from_input(percent);
                 return true;
             }
             if ctx.input.left_mouse_button_released() {
@@ -153,6 +178,16 @@ impl Slider {
             return false;
         }
 
+        if let Some(dir) = nudge_direction(ctx, self.top_left, self.dims, self.horiz) {
+            let step = if let Some(n) = self.steps {
+                1.0 / ((n - 1) as f64)
+            } else {
+                NUDGE_PERCENT
+            };
+            self.set_from_input(self.current_percent + dir * step);
+            return true;
+        }
+
         if ctx.redo_mouseover() {
             let old = self.mouse_on_slider;
             if let Some(pt) = ctx.canvas.get_cursor_in_screen_space() {
@@ -184,7 +219,7 @@ impl Slider {
                         (pt.y - self.top_left.y - (self.dragger_len / 2.0))
                             / (self.main_bg_len - self.dragger_len)
                     };
-                    self.current_percent = percent.min(1.0).max(0.0);
+                    self.set_from_input(percent);
                     self.mouse_on_slider = true;
                     self.dragging = true;
                     return true;
@@ -258,16 +293,12 @@ impl AreaSlider {
         // The background
         batch.push(
             Color::hex("#F2F2F2"),
-            Polygon::rounded_rectangle(self.dims.width, self.dims.height, None),
+            Polygon::pill(self.dims.width, self.dims.height),
         );
         // So far
         batch.push(
             Color::hex("#F4DF4D"),
-            Polygon::rounded_rectangle(
-                self.current_percent * self.dims.width,
-                self.dims.height,
-                None,
-            ),
+            Polygon::pill(self.current_percent * self.dims.width, self.dims.height),
         );
 
         // The circle dragger
@@ -326,6 +357,13 @@ impl AreaSlider {
             return false;
         }
 
+        if let Some(dir) = nudge_direction(ctx, self.top_left, self.dims, true) {
+            self.current_percent = (self.current_percent + dir * NUDGE_PERCENT)
+                .min(1.0)
+                .max(0.0);
+            return true;
+        }
+
         if ctx.redo_mouseover() {
             let old = self.mouse_on_slider;
             if let Some(pt) = ctx.canvas.get_cursor_in_screen_space() {
@@ -381,3 +419,29 @@ impl WidgetImpl for AreaSlider {
         g.redraw_at(self.top_left, &self.draw);
     }
 }
+
+// While the cursor is over a slider, the arrow keys along its axis nudge it. -1 for left or up, 1
+// for right or down.
+fn nudge_direction(
+    ctx: &mut EventCtx,
+    top_left: ScreenPt,
+    dims: ScreenDims,
+    horiz: bool,
+) -> Option<f64> {
+    let pt = ctx.canvas.get_cursor_in_screen_space()?;
+    if !ScreenRectangle::top_left(top_left, dims).contains(pt) {
+        return None;
+    }
+    let (back, fwds) = if horiz {
+        (Key::LeftArrow, Key::RightArrow)
+    } else {
+        (Key::UpArrow, Key::DownArrow)
+    };
+    if ctx.input.new_was_pressed(&MultiKey::Normal(back)) {
+        Some(-1.0)
+    } else if ctx.input.new_was_pressed(&MultiKey::Normal(fwds)) {
+        Some(1.0)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{Btn, EventCtx, GeomBatch, Line, Text, TextExt, Widget};
+use abstutil::prettyprint_usize;
+use geom::Polygon;
+use std::cmp::Ordering;
+
+// One row of a Table. The caller decides how each cell looks and how rows compare.
+pub trait TableRow {
+    // Clicking the row produces this label, so it has to be unique in the table.
+    fn label(&self) -> String;
+    // One batch per column, in the same order as the headers.
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch>;
+    // Only called for sortable columns.
+    fn cmp_by(&self, other: &Self, col: usize) -> Ordering;
+}
+
+// Rows with column headers, split into pages. Clicking a sortable header sorts by that column,
+// and clicking it again flips the order. The table doesn't own a Composite; the caller puts the
+// result of render() in their own panel, passes along anything clicked, and renders again when
+// the table says something changed.
+pub struct Table<R: TableRow> {
+    headers: Vec<(String, bool)>,
+    rows: Vec<R>,
+    sort_by: Option<usize>,
+    descending: bool,
+    skip: usize,
+    rows_per_page: usize,
+    selected: Option<String>,
+}
+
+impl<R: TableRow> Table<R> {
+    // Each header has a name and whether it's sortable. Rows start in the order given.
+    pub fn new(headers: Vec<(&str, bool)>, rows: Vec<R>, rows_per_page: usize) -> Table<R> {
+        Table {
+            headers: headers
+                .into_iter()
+                .map(|(name, sortable)| (name.to_string(), sortable))
+                .collect(),
+            rows,
+            sort_by: None,
+            descending: true,
+            skip: 0,
+            rows_per_page,
+            selected: None,
+        }
+    }
+
+    pub fn sort_by(&mut self, col: usize, descending: bool) {
+        assert!(self.headers[col].1);
+        self.sort_by = Some(col);
+        self.descending = descending;
+        self.skip = 0;
+        self.sort();
+    }
+
+    // Keeps the current sort order, but goes back to the first page.
+    pub fn set_rows(&mut self, rows: Vec<R>) {
+        self.rows = rows;
+        self.skip = 0;
+        self.sort();
+    }
+
+    pub fn rows(&self) -> &Vec<R> {
+        &self.rows
+    }
+
+    // Highlights one row, until something else is selected.
+    pub fn select(&mut self, label: Option<String>) {
+        self.selected = label;
+    }
+
+    // Call with anything clicked in the panel holding the table. True if the table handled it and
+    // needs to be rendered again. Otherwise, it might be the label of a row.
+    pub fn clicked(&mut self, action: &str) -> bool {
+        if action == "previous page" {
+            self.skip -= self.rows_per_page;
+            return true;
+        }
+        if action == "next page" {
+            self.skip += self.rows_per_page;
+            return true;
+        }
+        if let Some(col) = self
+            .headers
+            .iter()
+            .position(|(name, sortable)| *sortable && name == action)
+        {
+            if self.sort_by == Some(col) {
+                self.descending = !self.descending;
+            } else {
+                self.sort_by = Some(col);
+                self.descending = true;
+            }
+            self.skip = 0;
+            self.sort();
+            return true;
+        }
+        false
+    }
+
+    // The page controls, headers, and the current page of rows, stretched to about total_width.
+    pub fn render(&self, ctx: &mut EventCtx, total_width: f64) -> Widget {
+        let headers: Vec<Widget> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, sortable))| {
+                if !sortable {
+                    Line(name).draw(ctx)
+                } else if self.sort_by == Some(idx) {
+                    Btn::text_bg2(format!(
+                        "{} {}",
+                        name,
+                        if self.descending { "↓" } else { "↑" }
+                    ))
+                    .build(ctx, name, None)
+                } else {
+                    Btn::text_bg2(name).build_def(ctx, None)
+                }
+            })
+            .collect();
+        let rows: Vec<(String, Vec<GeomBatch>)> = self
+            .rows
+            .iter()
+            .skip(self.skip)
+            .take(self.rows_per_page)
+            .map(|r| (r.label(), r.render(ctx)))
+            .collect();
+
+        let mut col = vec![self.page_controls(ctx).margin_below(5)];
+        col.extend(layout(ctx, headers, rows, total_width, &self.selected));
+        Widget::col(col)
+    }
+
+    fn page_controls(&self, ctx: &mut EventCtx) -> Widget {
+        let total = self.rows.len();
+        Widget::row(vec![
+            if self.skip > 0 {
+                Btn::text_fg("<").build(ctx, "previous page", None)
+            } else {
+                Btn::text_fg("<").inactive(ctx)
+            }
+            .margin_right(10),
+            format!(
+                "{}-{} of {}",
+                if total > 0 {
+                    prettyprint_usize(self.skip + 1)
+                } else {
+                    "0".to_string()
+                },
+                prettyprint_usize((self.skip + self.rows_per_page).min(total)),
+                prettyprint_usize(total)
+            )
+            .draw_text(ctx)
+            .margin_right(10),
+            if self.skip + self.rows_per_page < total {
+                Btn::text_fg(">").build(ctx, "next page", None)
+            } else {
+                Btn::text_fg(">").inactive(ctx)
+            },
+        ])
+    }
+
+    fn sort(&mut self) {
+        if let Some(col) = self.sort_by {
+            let descending = self.descending;
+            self.rows.sort_by(|a, b| {
+                let ord = a.cmp_by(b, col);
+                if descending {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+        }
+    }
+}
+
+// Lines up the cells of each column with the header, and spreads out the columns to fill the
+// width.
+fn layout(
+    ctx: &EventCtx,
+    headers: Vec<Widget>,
+    rows: Vec<(String, Vec<GeomBatch>)>,
+    total_width: f64,
+    selected: &Option<String>,
+) -> Vec<Widget> {
+    let total_width = total_width / ctx.get_scale_factor();
+    let mut width_per_col: Vec<f64> = headers
+        .iter()
+        .map(|w| w.get_width_for_forcing() / ctx.get_scale_factor())
+        .collect();
+    for (_, row) in &rows {
+        for (col, width) in row.iter().zip(width_per_col.iter_mut()) {
+            *width = width.max(col.get_dims().width / ctx.get_scale_factor());
+        }
+    }
+    let extra_margin = ((total_width - width_per_col.iter().sum::<f64>())
+        / (width_per_col.len().max(2) - 1) as f64)
+        .max(0.0);
+
+    let mut col = vec![Widget::row(
+        headers
+            .into_iter()
+            .enumerate()
+            .map(|(idx, w)| {
+                let margin = extra_margin + width_per_col[idx]
+                    - (w.get_width_for_forcing() / ctx.get_scale_factor());
+                if idx == width_per_col.len() - 1 {
+                    w.margin_right((margin - extra_margin) as usize)
+                } else {
+                    w.margin_right(margin as usize)
+                }
+            })
+            .collect(),
+    )
+    .bg(ctx.style().section_bg)];
+
+    for (label, row) in rows {
+        let mut batch = GeomBatch::new();
+        batch.autocrop_dims = false;
+        let mut x1 = 0.0;
+        for (cell, width) in row.into_iter().zip(width_per_col.iter()) {
+            batch.append(cell.scale(1.0 / ctx.get_scale_factor()).translate(x1, 0.0));
+            x1 += *width + extra_margin;
+        }
+
+        let rect = Polygon::rectangle(total_width, batch.get_dims().height);
+        let mut normal = GeomBatch::new();
+        if selected.as_ref() == Some(&label) {
+            normal.push(ctx.style().hovering_color.alpha(0.5), rect.clone());
+        }
+        normal.append(batch.clone());
+        let mut hovered = GeomBatch::new();
+        hovered.push(ctx.style().hovering_color, rect.clone());
+        hovered.append(batch);
+
+        col.push(
+            Btn::custom(normal, hovered, rect)
+                .tooltip(Text::new())
+                .build(ctx, label, None),
+        );
+    }
+
+    col
+}
@@ -0,0 +1,121 @@
+use crate::{
+    Btn, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Outcome, VerticalAlignment,
+    Widget,
+};
+
+// A few panels sharing one spot on the screen, with a strip of tabs above to switch between them.
+// Only the active panel handles events and gets drawn, but the others keep their state, like
+// scrolling and checkboxes, while they're hidden.
+pub struct TabContainer {
+    strip: Composite,
+    tabs: Vec<(String, Composite)>,
+    current: usize,
+    horiz: HorizontalAlignment,
+    vert: VerticalAlignment,
+}
+
+impl TabContainer {
+    // The first tab starts active. Tab names have to be unique.
+    pub fn new(
+        ctx: &mut EventCtx,
+        horiz: HorizontalAlignment,
+        vert: VerticalAlignment,
+        tabs: Vec<(&str, Widget)>,
+    ) -> TabContainer {
+        assert!(!tabs.is_empty());
+        let names: Vec<String> = tabs.iter().map(|(name, _)| name.to_string()).collect();
+        let strip = make_strip(ctx, horiz, vert, &names, 0);
+        let tabs = tabs
+            .into_iter()
+            .map(|(name, panel)| {
+                let mut c = Composite::new(panel)
+                    .aligned(horiz, VerticalAlignment::Top)
+                    .build(ctx);
+                c.align_below(ctx, &strip, 0.0);
+                (name.to_string(), c)
+            })
+            .collect();
+        TabContainer {
+            strip,
+            tabs,
+            current: 0,
+            horiz,
+            vert,
+        }
+    }
+
+    // Clicking a tab is handled here; anything else comes from the active panel.
+    pub fn event(&mut self, ctx: &mut EventCtx) -> Option<Outcome> {
+        match self.strip.event(ctx) {
+            Some(Outcome::Clicked(x)) => {
+                self.switch_to(ctx, &x);
+                None
+            }
+            None => self.tabs[self.current].1.event(ctx),
+        }
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        self.strip.draw(g);
+        self.tabs[self.current].1.draw(g);
+    }
+
+    pub fn current_tab(&self) -> &str {
+        &self.tabs[self.current].0
+    }
+
+    pub fn switch_to(&mut self, ctx: &mut EventCtx, name: &str) {
+        self.current = self.idx(name);
+        let names: Vec<String> = self.tabs.iter().map(|(name, _)| name.clone()).collect();
+        self.strip = make_strip(ctx, self.horiz, self.vert, &names, self.current);
+        self.tabs[self.current].1.align_below(ctx, &self.strip, 0.0);
+    }
+
+    pub fn panel(&self, name: &str) -> &Composite {
+        &self.tabs[self.idx(name)].1
+    }
+
+    pub fn panel_mut(&mut self, name: &str) -> &mut Composite {
+        let idx = self.idx(name);
+        &mut self.tabs[idx].1
+    }
+
+    // Swaps out the contents of one tab, whether or not it's active. Scrolling and other state is
+    // carried over from the old panel where possible.
+    pub fn set_panel(&mut self, ctx: &mut EventCtx, name: &str, panel: Widget) {
+        let idx = self.idx(name);
+        let mut c = Composite::new(panel)
+            .aligned(self.horiz, VerticalAlignment::Top)
+            .build(ctx);
+        c.restore(ctx, &self.tabs[idx].1);
+        c.align_below(ctx, &self.strip, 0.0);
+        self.tabs[idx].1 = c;
+    }
+
+    fn idx(&self, name: &str) -> usize {
+        self.tabs
+            .iter()
+            .position(|(n, _)| n == name)
+            .unwrap_or_else(|| panic!("No tab named {}", name))
+    }
+}
+
+fn make_strip(
+    ctx: &mut EventCtx,
+    horiz: HorizontalAlignment,
+    vert: VerticalAlignment,
+    names: &[String],
+    current: usize,
+) -> Composite {
+    let mut row = Vec::new();
+    for (idx, name) in names.iter().enumerate() {
+        if idx == current {
+            row.push(Btn::text_bg2(name).inactive(ctx));
+        } else {
+            row.push(Btn::text_bg2(name).build_def(ctx, None));
+        }
+    }
+    Composite::new(Widget::row(row).bg(Color::WHITE))
+        .aligned(horiz, vert)
+        .build(ctx)
+}
@@ -0,0 +1,313 @@
+use crate::{
+    text, Btn, Button, Color, EventCtx, GeomBatch, GfxCtx, Key, Line, ScreenDims, ScreenPt,
+    ScreenRectangle, Text, Widget, WidgetImpl, WidgetOutput,
+};
+use geom::{Distance, Duration, Polygon, Pt2D, Speed, UnitSystem};
+
+// TODO MAX_CHAR_WIDTH is a hardcoded nonsense value
+const TEXT_WIDTH: f64 = 5.0 * text::MAX_CHAR_WIDTH;
+
+// Something with units that a UnitSpinner can edit. The user sees and types numbers in the
+// display unit, which doesn't have to match how the value is stored.
+pub trait SpinnerValue: 'static + Copy + PartialOrd {
+    fn to_display(self) -> f64;
+    fn from_display(x: f64) -> Self;
+    fn unit() -> &'static str;
+}
+
+impl SpinnerValue for Duration {
+    fn to_display(self) -> f64 {
+        self.inner_seconds()
+    }
+    fn from_display(x: f64) -> Duration {
+        Duration::seconds(x)
+    }
+    fn unit() -> &'static str {
+        "s"
+    }
+}
+
+impl SpinnerValue for Distance {
+    fn to_display(self) -> f64 {
+        match UnitSystem::current() {
+            UnitSystem::Metric => self.inner_meters(),
+            UnitSystem::Imperial => self.inner_feet(),
+        }
+    }
+    fn from_display(x: f64) -> Distance {
+        match UnitSystem::current() {
+            UnitSystem::Metric => Distance::meters(x),
+            UnitSystem::Imperial => Distance::feet(x),
+        }
+    }
+    fn unit() -> &'static str {
+        match UnitSystem::current() {
+            UnitSystem::Metric => "m",
+            UnitSystem::Imperial => "ft",
+        }
+    }
+}
+
+impl SpinnerValue for Speed {
+    fn to_display(self) -> f64 {
+        match UnitSystem::current() {
+            UnitSystem::Metric => self.inner_meters_per_second() * 3.6,
+            UnitSystem::Imperial => self.inner_meters_per_second() * 2.23694,
+        }
+    }
+    fn from_display(x: f64) -> Speed {
+        match UnitSystem::current() {
+            UnitSystem::Metric => Speed::km_per_hour(x),
+            UnitSystem::Imperial => Speed::miles_per_hour(x),
+        }
+    }
+    fn unit() -> &'static str {
+        match UnitSystem::current() {
+            UnitSystem::Metric => "km/h",
+            UnitSystem::Imperial => "mph",
+        }
+    }
+}
+
+impl SpinnerValue for usize {
+    fn to_display(self) -> f64 {
+        self as f64
+    }
+    fn from_display(x: f64) -> usize {
+        x.max(0.0).round() as usize
+    }
+    fn unit() -> &'static str {
+        ""
+    }
+}
+
+// Like Spinner, but for typed values with units. Click the arrows, scroll, or click the value to
+// type a new one. Anything out of bounds is clamped; anything unparseable is rejected.
+pub struct UnitSpinner<T: SpinnerValue> {
+    low: T,
+    high: T,
+    step: T,
+    pub current: T,
+
+    // While typing, the partial input
+    editing: Option<String>,
+    // The last thing typed couldn't be parsed
+    invalid: bool,
+
+    up: Button,
+    down: Button,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+impl<T: SpinnerValue> UnitSpinner<T> {
+    pub fn new(ctx: &EventCtx, (low, high): (T, T), step: T, current: T) -> Widget {
+        assert!(low <= high);
+        let up = Btn::text_fg("▲")
+            .build(ctx, "increase value", None)
+            .take_btn();
+        let down = Btn::text_fg("▼")
+            .build(ctx, "decrease value", None)
+            .take_btn();
+
+        let dims = ScreenDims::new(
+            TEXT_WIDTH + up.get_dims().width,
+            up.get_dims().height + down.get_dims().height,
+        );
+
+        Widget::new(Box::new(UnitSpinner {
+            low,
+            high,
+            step,
+            current: clamp(current, low, high),
+
+            editing: None,
+            invalid: false,
+
+            up,
+            down,
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims,
+        }))
+    }
+
+    fn nudge(&mut self, steps: f64) {
+        let x = self.current.to_display() + steps * self.step.to_display();
+        self.current = clamp(T::from_display(x), self.low, self.high);
+        self.invalid = false;
+    }
+
+    fn finish_editing(&mut self) {
+        if let Some(line) = self.editing.take() {
+            match parse::<T>(&line) {
+                Some(x) => {
+                    self.current = clamp(x, self.low, self.high);
+                    self.invalid = false;
+                }
+                None => {
+                    self.invalid = true;
+                }
+            }
+        }
+    }
+
+    fn value_rect(&self) -> ScreenRectangle {
+        ScreenRectangle::top_left(self.top_left, ScreenDims::new(TEXT_WIDTH, self.dims.height))
+    }
+}
+
+impl<T: SpinnerValue> WidgetImpl for UnitSpinner<T> {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+        self.up
+            .set_pos(ScreenPt::new(top_left.x + TEXT_WIDTH, top_left.y));
+        self.down.set_pos(ScreenPt::new(
+            top_left.x + TEXT_WIDTH,
+            top_left.y + self.up.get_dims().height,
+        ));
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        if let Some(ref mut line) = self.editing {
+            if let Some(key) = ctx.input.any_key_pressed() {
+                match key {
+                    Key::Enter => {
+                        self.finish_editing();
+                    }
+                    Key::Escape => {
+                        self.editing = None;
+                    }
+                    Key::Backspace => {
+                        line.pop();
+                    }
+                    _ => {
+                        if let Some(c) = key.to_char(ctx.canvas.lshift_held) {
+                            line.push(c);
+                        } else {
+                            ctx.input.unconsume_event();
+                        }
+                    }
+                }
+                return;
+            }
+        }
+
+        if ctx.normal_left_click() {
+            let on_value = ctx
+                .canvas
+                .get_cursor_in_screen_space()
+                .map(|pt| self.value_rect().contains(pt))
+                .unwrap_or(false);
+            if on_value {
+                if self.editing.is_none() {
+                    self.editing = Some(String::new());
+                }
+                return;
+            }
+            // Clicking anywhere else commits what was typed. Let everything else see the click.
+            self.finish_editing();
+            ctx.input.unconsume_event();
+        }
+
+        self.up.event(ctx, output);
+        if output.outcome.take().is_some() {
+            self.editing = None;
+            self.nudge(1.0);
+            ctx.no_op_event(true, |ctx| self.up.event(ctx, output));
+            return;
+        }
+
+        self.down.event(ctx, output);
+        if output.outcome.take().is_some() {
+            self.editing = None;
+            self.nudge(-1.0);
+            ctx.no_op_event(true, |ctx| self.down.event(ctx, output));
+            return;
+        }
+
+        if let Some(pt) = ctx.canvas.get_cursor_in_screen_space() {
+            if ScreenRectangle::top_left(self.top_left, self.dims).contains(pt) {
+                if let Some((_, dy)) = ctx.input.get_mouse_scroll() {
+                    if dy > 0.0 {
+                        self.nudge(1.0);
+                    }
+                    if dy < 0.0 {
+                        self.nudge(-1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        // TODO Cache
+        let bg = if self.invalid {
+            Color::RED.alpha(0.5)
+        } else {
+            text::BG_COLOR
+        };
+        let mut batch = GeomBatch::from(vec![(
+            bg,
+            Polygon::rounded_rectangle(self.dims.width, self.dims.height, Some(5.0)),
+        )]);
+        let txt = if let Some(ref line) = self.editing {
+            Text::from_all(vec![
+                Line(line),
+                Line("|").fg(text::SELECTED_COLOR),
+                Line(T::unit()),
+            ])
+        } else {
+            Text::from(Line(describe(self.current)))
+        };
+        batch.append(
+            txt.render_to_batch(g.prerender)
+                .centered_on(Pt2D::new(TEXT_WIDTH / 2.0, self.dims.height / 2.0)),
+        );
+        let draw = g.upload(batch);
+        g.redraw_at(self.top_left, &draw);
+
+        self.up.draw(g);
+        self.down.draw(g);
+    }
+}
+
+fn clamp<T: SpinnerValue>(x: T, low: T, high: T) -> T {
+    if x < low {
+        low
+    } else if x > high {
+        high
+    } else {
+        x
+    }
+}
+
+// Whole numbers don't need a decimal point.
+fn describe<T: SpinnerValue>(x: T) -> String {
+    let value = x.to_display();
+    if (value - value.round()).abs() < 0.05 {
+        format!("{}{}", value.round(), T::unit())
+    } else {
+        format!("{:.1}{}", value, T::unit())
+    }
+}
+
+// The unit suffix is optional.
+fn parse<T: SpinnerValue>(line: &str) -> Option<T> {
+    let line = line.trim();
+    let number = if !T::unit().is_empty() && line.ends_with(T::unit()) {
+        line[..line.len() - T::unit().len()].trim()
+    } else {
+        line
+    };
+    let value = number.parse::<f64>().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    Some(T::from_display(value))
+}
@@ -1,9 +1,13 @@
 use crate::challenges::HighScore;
 use crate::colors::ColorScheme;
+use crate::devtools::MapWatcher;
 use crate::helpers::ID;
 use crate::layer::Layer;
 use crate::options::Options;
-use crate::render::{AgentCache, AgentColorScheme, DrawMap, DrawOptions, Renderable};
+use crate::render::{
+    AgentCache, AgentColorScheme, DrawMap, DrawOptions, MapLayer, Renderable,
+    MAX_ZOOM_FOR_GENERALIZED, OVERLAY_LAYER,
+};
 use crate::sandbox::{GameplayMode, TutorialState};
 use abstutil::{MeasureMemory, Timer};
 use ezgui::{EventCtx, GfxCtx, Prerender};
@@ -11,6 +15,7 @@ use geom::{Bounds, Circle, Distance, Duration, Pt2D, Time};
 use map_model::{IntersectionID, Map, Traversable};
 use rand::seq::SliceRandom;
 use sim::{Analytics, GetDrawAgents, Sim, SimCallback, SimFlags};
+use std::cell::Cell;
 use std::collections::BTreeMap;
 
 pub struct App {
@@ -28,12 +33,21 @@ pub struct App {
 
     pub per_obj: PerObjectActions,
     pub layer: Option<Box<dyn Layer>>,
+    // Stacked underneath layer, bottom first. They're drawn, but only layer has a panel.
+    pub layers_below: Vec<Box<dyn Layer>>,
 
     // Static data that lasts the entire session. Use sparingly.
     pub session: SessionState,
 
     // Only filled out in edit mode. Stored here once to avoid lots of clones. Used for preview.
     pub suspended_sim: Option<Sim>,
+
+    pub map_watcher: Option<MapWatcher>,
+
+    // Saved the next time everything is drawn
+    pub pending_screenshot: Cell<Option<String>>,
+    // Like pending_screenshot, but only the map and current layer get drawn
+    pub pending_layer_export: Cell<Option<String>>,
 }
 
 impl App {
@@ -71,7 +85,14 @@ impl App {
             }
         }
 
+        let map_watcher = if primary.current_flags.watch_map {
+            Some(MapWatcher::new(primary.map.get_name()))
+        } else {
+            None
+        };
+
         App {
+            map_watcher,
             primary,
             prebaked: None,
             agent_cs: AgentColorScheme::new(&cs),
@@ -79,8 +100,11 @@ impl App {
             opts,
             per_obj: PerObjectActions::new(),
             layer: None,
+            layers_below: Vec::new(),
             session: SessionState::empty(),
             suspended_sim: None,
+            pending_screenshot: Cell::new(None),
+            pending_layer_export: Cell::new(None),
         }
     }
 
@@ -159,33 +183,58 @@ impl App {
         let mut sample_intersection: Option<String> = None;
 
         g.clear(self.cs.void_background);
+        g.set_z(0);
         g.redraw(&self.primary.draw_map.boundary_polygon);
 
         if g.canvas.cam_zoom < self.opts.min_zoom_for_detail && !g.is_screencap() {
-            // Unzoomed mode
+            // Unzoomed mode. Each of these batches already covers every zorder, in order, so they
+            // all go in the ground band.
             let layers = show_objs.layers();
             if layers.show_areas {
+                g.set_z(MapLayer::Area.at(0));
                 g.redraw(&self.primary.draw_map.draw_all_areas);
             }
             if layers.show_parking_lots {
-                g.redraw(&self.primary.draw_map.draw_all_unzoomed_parking_lots);
+                g.set_z(MapLayer::ParkingLot.at(0));
+                g.redraw_cached(&self.primary.draw_map.draw_all_unzoomed_parking_lots);
             }
-            // Render bridges over intersections in the correct order
+            // At city scale, the full map is illegible, so draw a simplified version.
+            let generalized = g.canvas.cam_zoom < MAX_ZOOM_FOR_GENERALIZED;
+            let draw_map = &self.primary.draw_map;
+            // Bridges in the roads have to cover the intersections beneath them, so unlike zoomed
+            // in, intersections go under roads.
             if layers.show_intersections {
-                g.redraw(&self.primary.draw_map.draw_all_unzoomed_intersections);
+                g.set_z(MapLayer::Lane.at(0));
+                g.redraw_cached(if generalized {
+                    &draw_map.draw_generalized_intersections
+                } else {
+                    &draw_map.draw_all_unzoomed_intersections
+                });
             }
             if layers.show_lanes {
-                g.redraw(&self.primary.draw_map.draw_all_thick_roads);
+                g.set_z(MapLayer::LaneMarking.at(0));
+                g.redraw_cached(if generalized {
+                    &draw_map.draw_generalized_roads
+                } else {
+                    &draw_map.draw_all_thick_roads
+                });
             }
             if layers.show_buildings {
-                g.redraw(&self.primary.draw_map.draw_all_buildings);
+                g.set_z(MapLayer::Building.at(0));
+                if generalized {
+                    g.redraw_cached(&draw_map.draw_all_building_blocks);
+                } else {
+                    g.redraw(&draw_map.draw_all_buildings);
+                }
                 // Not the building paths
             }
 
             // Still show some shape selection when zoomed out.
             // TODO Refactor! Ideally use get_obj
+            g.set_z(MapLayer::Building.at(0));
             if let Some(ID::Area(id)) = self.primary.current_selection {
-                g.draw_polygon(
+                draw_map.draw_selection(
+                    g,
                     self.cs.selected,
                     &self
                         .primary
@@ -194,7 +243,8 @@ impl App {
                         .get_outline(&self.primary.map),
                 );
             } else if let Some(ID::Road(id)) = self.primary.current_selection {
-                g.draw_polygon(
+                draw_map.draw_selection(
+                    g,
                     self.cs.selected,
                     &self
                         .primary
@@ -204,16 +254,18 @@ impl App {
                 );
             } else if let Some(ID::Intersection(id)) = self.primary.current_selection {
                 // Actually, don't use get_outline here! Full polygon is easier to see.
-                g.draw_polygon(self.cs.selected, &self.primary.map.get_i(id).polygon);
+                draw_map.draw_selection(g, self.cs.selected, &self.primary.map.get_i(id).polygon);
             } else if let Some(ID::Building(id)) = self.primary.current_selection {
-                g.draw_polygon(self.cs.selected, &self.primary.map.get_b(id).polygon);
+                draw_map.draw_selection(g, self.cs.selected, &self.primary.map.get_b(id).polygon);
             }
 
+            g.set_z(MapLayer::Agent.at(0));
             let mut cache = self.primary.draw_map.agents.borrow_mut();
             cache.draw_unzoomed_agents(
                 source,
                 &self.primary.map,
                 &self.agent_cs,
+                generalized,
                 g,
                 if self.opts.large_unzoomed_agents {
                     Some(Distance::meters(10.0) / g.canvas.cam_zoom)
@@ -223,7 +275,7 @@ impl App {
             );
         } else {
             let mut cache = self.primary.draw_map.agents.borrow_mut();
-            let objects = self.get_renderables_back_to_front(
+            let objects = self.get_renderables(
                 g.get_screen_bounds(),
                 &g.prerender,
                 &mut cache,
@@ -233,21 +285,30 @@ impl App {
 
             let mut drawn_all_buildings = false;
             let mut drawn_all_areas = false;
+            let mut selected = None;
 
             for obj in objects {
+                let layer = MapLayer::of(&obj.get_id());
+                g.set_z(layer.at(obj.get_zorder()));
                 obj.draw(g, self, &opts);
 
                 match obj.get_id() {
                     ID::Building(_) => {
                         if !drawn_all_buildings {
-                            g.redraw(&self.primary.draw_map.draw_all_building_paths);
+                            g.set_z(layer.at(0));
+                            g.redraw_cached(&self.primary.draw_map.draw_all_building_paths);
                             g.redraw(&self.primary.draw_map.draw_all_buildings);
-                            g.redraw(&self.primary.draw_map.draw_all_building_outlines);
+                            g.redraw_cached(&self.primary.draw_map.draw_all_building_outlines);
+                            if opts.label_buildings {
+                                g.set_z(MapLayer::Label.at(0));
+                                self.primary.draw_map.draw_building_labels(g);
+                            }
                             drawn_all_buildings = true;
                         }
                     }
                     ID::Area(_) => {
                         if !drawn_all_areas {
+                            g.set_z(layer.at(0));
                             g.redraw(&self.primary.draw_map.draw_all_areas);
                             drawn_all_areas = true;
                         }
@@ -256,7 +317,7 @@ impl App {
                 };
 
                 if self.primary.current_selection == Some(obj.get_id()) {
-                    g.draw_polygon(self.cs.selected, &obj.get_outline(&self.primary.map));
+                    selected = Some((layer.at(obj.get_zorder()), obj));
                 }
 
                 if g.is_screencap() && sample_intersection.is_none() {
@@ -265,7 +326,18 @@ impl App {
                     }
                 }
             }
+
+            // The outline is translucent, so draw it after everything it might cover
+            if let Some((z, obj)) = selected {
+                g.set_z(z);
+                self.primary.draw_map.draw_selection(
+                    g,
+                    self.cs.selected,
+                    &obj.get_outline(&self.primary.map),
+                );
+            }
         }
+        g.set_z(OVERLAY_LAYER);
 
         if let Some(i) = sample_intersection {
             g.set_screencap_naming_hint(i);
@@ -306,13 +378,15 @@ impl App {
         let pt = ctx.canvas.get_cursor_in_map_space()?;
 
         let mut cache = self.primary.draw_map.agents.borrow_mut();
-        let mut objects = self.get_renderables_back_to_front(
+        let mut objects = self.get_renderables(
             Circle::new(pt, Distance::meters(3.0)).get_bounds(),
             ctx.prerender,
             &mut cache,
             source,
             show_objs,
         );
+        // Whatever's drawn on top gets picked first
+        objects.sort_by_key(|obj| MapLayer::of(&obj.get_id()).at(obj.get_zorder()));
         objects.reverse();
 
         for obj in objects {
@@ -356,7 +430,7 @@ impl App {
 
     // TODO This could probably belong to DrawMap again, but it's annoying to plumb things that
     // State does, like show_icons_for() and show().
-    fn get_renderables_back_to_front<'a>(
+    fn get_renderables<'a>(
         &'a self,
         bounds: Bounds,
         prerender: &Prerender,
@@ -411,7 +485,7 @@ impl App {
             }
         }
 
-        // From background to foreground Z-order
+        // Order doesn't matter for layering; draw() uses set_z. Grouping by type is just cheaper.
         let mut borrows: Vec<&dyn Renderable> = Vec::new();
         borrows.extend(areas);
         borrows.extend(parking_lots);
@@ -434,9 +508,6 @@ impl App {
             }
         }
 
-        // This is a stable sort.
-        borrows.sort_by_key(|x| x.get_zorder());
-
         borrows
     }
 }
@@ -497,6 +568,10 @@ pub struct Flags {
     // Number of agents to generate when requested. If unspecified, trips to/from borders will be
     // included.
     pub num_agents: Option<usize>,
+    // Reload the map whenever its file is rebuilt. Handy when working on the importer.
+    pub watch_map: bool,
+    // Draw buildings with walls and a roof, raised according to their height
+    pub extrude_bldgs: bool,
 }
 
 // All of the state that's bound to a specific map+edit has to live here.
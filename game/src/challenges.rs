@@ -391,6 +391,8 @@ fn prebake(map: &Map, scenario: Scenario, time_limit: Option<Duration>, timer: &
         abstutil::path_prebaked_results(&scenario.map_name, &scenario.scenario_name),
         sim.get_analytics(),
     );
+    // Lets later runs of this scenario warm-start with --warm_start
+    sim.get_prior_routes().save();
     timer.stop(format!(
         "prebake for {} / {}",
         scenario.map_name, scenario.scenario_name
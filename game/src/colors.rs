@@ -56,6 +56,7 @@ pub struct ColorScheme {
     // Roads
     pub driving_lane: Color,
     pub bus_lane: Color,
+    pub hov_lane: Color,
     pub parking_lane: Color,
     pub bike_lane: Color,
     pub under_construction: Color,
@@ -139,7 +140,7 @@ impl ColorScheme {
             // UI
             hovering: gui_style.hovering_color,
             panel_bg: gui_style.panel_bg,
-            section_bg: Color::grey(0.5),
+            section_bg: gui_style.section_bg,
             inner_panel: hex("#4C4C4C"),
             day_time_slider: hex("#F4DA22"),
             night_time_slider: hex("#12409D"),
@@ -154,6 +155,7 @@ impl ColorScheme {
             // Roads
             driving_lane: Color::BLACK,
             bus_lane: Color::rgb(190, 74, 76),
+            hov_lane: Color::rgb(140, 90, 180),
             parking_lane: Color::grey(0.2),
             bike_lane: Color::rgb(15, 125, 75),
             under_construction: Color::rgb(255, 109, 0),
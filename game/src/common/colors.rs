@@ -80,6 +80,12 @@ impl<'a> ColorDiscrete<'a> {
             .push(color, Circle::new(pt, Distance::meters(15.0)).to_polygon());
     }
 
+    pub fn add_polygon(&mut self, poly: Polygon, category: &'static str) {
+        let color = self.colors[category];
+        self.unzoomed.push(color, poly.clone());
+        self.zoomed.push(color.alpha(0.4), poly);
+    }
+
     pub fn build(self, ctx: &mut EventCtx) -> (Drawable, Drawable, Widget) {
         let legend = self
             .categories
@@ -1,5 +1,8 @@
 use crate::common::{ColorLegend, ColorScale};
-use ezgui::{Checkbox, Choice, Color, Composite, EventCtx, GeomBatch, Spinner, TextExt, Widget};
+use ezgui::{
+    BlendMode, Checkbox, Choice, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, Slider,
+    Spinner, TextExt, Widget,
+};
 use geom::{Bounds, Histogram, Polygon, Pt2D, Statistic};
 
 const NEIGHBORS: [[isize; 2]; 9] = [
@@ -14,6 +17,50 @@ const NEIGHBORS: [[isize; 2]; 9] = [
     [0, 1],
 ];
 
+// How a heatmap sits on top of the map and any other layers. The layer picker controls this, so
+// it isn't part of each layer's own panel.
+#[derive(Clone, Copy, PartialEq)]
+pub struct HeatmapStyle {
+    // So the map underneath still shows through
+    pub opacity: f64,
+    pub blend: BlendMode,
+}
+
+impl HeatmapStyle {
+    pub fn new() -> HeatmapStyle {
+        HeatmapStyle {
+            opacity: 0.6,
+            blend: BlendMode::Normal,
+        }
+    }
+
+    // Several of these can be in one panel, so the widgets are named after the layer.
+    pub fn to_controls(&self, ctx: &mut EventCtx, layer: &str) -> Widget {
+        Widget::row(vec![
+            Slider::horizontal(ctx, 100.0, 25.0, self.opacity)
+                .named(format!("{} opacity", layer))
+                .centered_vert()
+                .margin_right(10),
+            Widget::dropdown(
+                ctx,
+                &format!("{} blending", layer),
+                self.blend,
+                vec![
+                    Choice::new("normal", BlendMode::Normal),
+                    Choice::new("additive", BlendMode::Additive),
+                ],
+            ),
+        ])
+    }
+
+    pub fn from_controls(c: &Composite, layer: &str) -> HeatmapStyle {
+        HeatmapStyle {
+            opacity: c.slider(&format!("{} opacity", layer)).get_percent(),
+            blend: c.dropdown_value(&format!("{} blending", layer)),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct HeatmapOptions {
     // In meters
@@ -21,6 +68,7 @@ pub struct HeatmapOptions {
     radius: usize,
     smoothing: bool,
     color_scheme: String,
+    pub style: HeatmapStyle,
 }
 
 impl HeatmapOptions {
@@ -30,6 +78,7 @@ impl HeatmapOptions {
             radius: 3,
             smoothing: true,
             color_scheme: "Turbo".to_string(),
+            style: HeatmapStyle::new(),
         }
     }
 
@@ -77,7 +126,8 @@ impl HeatmapOptions {
         ]
     }
 
-    pub fn from_controls(c: &Composite) -> HeatmapOptions {
+    // The style isn't in these controls, so it's carried over.
+    pub fn from_controls(c: &Composite, style: HeatmapStyle) -> HeatmapOptions {
         // Did we just change?
         if c.has_widget("resolution") {
             HeatmapOptions {
@@ -85,11 +135,22 @@ impl HeatmapOptions {
                 radius: c.spinner("radius"),
                 smoothing: c.is_checked("smoothing"),
                 color_scheme: c.dropdown_value("Color scheme"),
+                style,
             }
         } else {
-            HeatmapOptions::new()
+            HeatmapOptions {
+                style,
+                ..HeatmapOptions::new()
+            }
         }
     }
+
+    // Draws something made by make_heatmap
+    pub fn redraw(&self, g: &mut GfxCtx, draw: &Drawable) {
+        g.set_blend_mode(self.style.blend);
+        g.redraw(draw);
+        g.set_blend_mode(BlendMode::Normal);
+    }
 }
 
 // Returns a legend
@@ -202,8 +263,8 @@ pub fn make_heatmap(
             if count > 0.0 {
                 let pct = (count as f64) / (distrib.select(Statistic::Max) as f64);
                 let c = gradient.eval_continuous(pct);
-                // Don't block the map underneath
-                let color = Color::rgb(c.r as usize, c.g as usize, c.b as usize).alpha(0.6);
+                let color = Color::rgb(c.r as usize, c.g as usize, c.b as usize)
+                    .alpha(opts.style.opacity as f32);
                 batch.push(
                     color,
                     square.translate((x * opts.resolution) as f64, (y * opts.resolution) as f64),
@@ -259,12 +259,12 @@ impl Minimap {
         g.enable_clipping(inner_rect);
         g.redraw(&app.primary.draw_map.boundary_polygon);
         g.redraw(&app.primary.draw_map.draw_all_areas);
-        g.redraw(&app.primary.draw_map.draw_all_unzoomed_parking_lots);
-        g.redraw(&app.primary.draw_map.draw_all_unzoomed_intersections);
-        g.redraw(&app.primary.draw_map.draw_all_thick_roads);
+        g.redraw_cached(&app.primary.draw_map.draw_all_unzoomed_parking_lots);
+        g.redraw_cached(&app.primary.draw_map.draw_all_unzoomed_intersections);
+        g.redraw_cached(&app.primary.draw_map.draw_all_thick_roads);
         g.redraw(&app.primary.draw_map.draw_all_buildings);
         // Not the building or parking lot paths
-        if let Some(ref l) = app.layer {
+        for l in app.layers_below.iter().chain(app.layer.iter()) {
             l.draw_minimap(g);
         }
 
@@ -273,6 +273,7 @@ impl Minimap {
             &app.primary.sim,
             &app.primary.map,
             &app.agent_cs,
+            false,
             g,
             if app.opts.large_unzoomed_agents {
                 Some(Distance::meters(2.0 + (self.zoom_lvl as f64)) / self.zoom)
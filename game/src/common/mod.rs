@@ -8,18 +8,19 @@ mod warp;
 
 pub use self::city_picker::CityPicker;
 pub use self::colors::{ColorDiscrete, ColorLegend, ColorNetwork, ColorScale, DivergingScale};
-pub use self::heatmap::{make_heatmap, HeatmapOptions};
+pub use self::heatmap::{make_heatmap, HeatmapOptions, HeatmapStyle};
 pub use self::minimap::Minimap;
 pub use self::panels::tool_panel;
-pub use self::warp::Warping;
+pub use self::warp::{Permalink, Warping};
 use crate::app::App;
 use crate::game::Transition;
 use crate::helpers::{list_names, ID};
 use crate::info::InfoPanel;
 pub use crate::info::{ContextualActions, Tab};
 use ezgui::{
-    hotkey, lctrl, Color, EventCtx, GeomBatch, GfxCtx, Key, Line, ScreenDims, ScreenPt,
-    ScreenRectangle, Text,
+    hotkey, key_sequence, lctrl, Color, Composite, EventCtx, GeomBatch, GfxCtx,
+    HorizontalAlignment, Key, Line, Outcome, RadialMenu, ScreenDims, ScreenPt, ScreenRectangle,
+    Text, VerticalAlignment,
 };
 use geom::Polygon;
 use std::collections::BTreeSet;
@@ -30,6 +31,9 @@ pub struct CommonState {
     info_panel: Option<InfoPanel>,
     // Just for drawing the OSD
     cached_actions: Vec<Key>,
+    // Opened by right-clicking an object, so every contextual action is reachable without
+    // knowing its hotkey
+    context_menu: Option<(ID, Composite)>,
 }
 
 impl CommonState {
@@ -37,6 +41,7 @@ impl CommonState {
         CommonState {
             info_panel: None,
             cached_actions: Vec::new(),
+            context_menu: None,
         }
     }
 
@@ -52,8 +57,60 @@ impl CommonState {
         if app.opts.dev && ctx.input.new_was_pressed(&lctrl(Key::J).unwrap()) {
             return Some(Transition::Push(warp::EnteringWarp::new()));
         }
+        // Comma then "i" to go to an intersection. The first key of a sequence can't be used
+        // alone anymore, and nothing else is bound to comma.
+        if app.opts.dev
+            && ctx
+                .input
+                .new_was_pressed(&key_sequence(Key::Comma, Key::I).unwrap())
+        {
+            return Some(Transition::Push(warp::EnteringWarp::intersection()));
+        }
+        // Paste the result into the warp prompt or pass it with --goto to come back here
+        if ctx.input.new_was_pressed(&lctrl(Key::K).unwrap()) {
+            let id = self
+                .info_panel_open(app)
+                .or_else(|| app.primary.current_selection.clone());
+            let link = Permalink::current(ctx, app, id).to_string();
+            println!("Copied link to clipboard: {}", link);
+            crate::info::copy_to_clipboard(link);
+        }
+        // Exactly what's on the screen, to attach to bug reports
+        if ctx.input.new_was_pressed(&hotkey(Key::F12).unwrap()) {
+            app.pending_screenshot
+                .set(Some(abstutil::path_player_screenshot(
+                    app.primary.map.get_name(),
+                    &app.primary.sim.time().as_filename(),
+                )));
+        }
+
+        if let Some((id, mut menu)) = self.context_menu.take() {
+            match menu.event(ctx) {
+                Some(Outcome::Clicked(x)) => match x.as_ref() {
+                    "close" => {}
+                    "show info" => {
+                        self.info_panel =
+                            Some(InfoPanel::new(ctx, app, Tab::from_id(app, id), ctx_actions));
+                    }
+                    _ => {
+                        return Some(ctx_actions.execute(ctx, app, id, x, &mut false));
+                    }
+                },
+                None => {
+                    // Clicking anywhere else dismisses the menu
+                    if !ctx.normal_left_click() {
+                        self.context_menu = Some((id, menu));
+                    }
+                }
+            }
+            return None;
+        }
 
         if let Some(ref id) = app.primary.current_selection {
+            if ctx.normal_right_click() {
+                self.context_menu = Some((id.clone(), context_menu(ctx, app, ctx_actions, id)));
+                return None;
+            }
             // TODO Also have a hotkey binding for this?
             if app.per_obj.left_click(ctx, "show info") {
                 self.info_panel = Some(InfoPanel::new(
@@ -93,6 +150,9 @@ impl CommonState {
     }
 
     pub fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if let Some((_, ref menu)) = self.context_menu {
+            menu.draw(g);
+        }
         let keys = if let Some(ref info) = self.info_panel {
             info.draw(g, app);
             info.active_keys()
@@ -287,3 +347,35 @@ impl CommonState {
         self.info_panel.as_ref().and_then(|i| i.active_id(app))
     }
 }
+
+fn context_menu(
+    ctx: &mut EventCtx,
+    app: &App,
+    ctx_actions: &mut dyn ContextualActions,
+    id: &ID,
+) -> Composite {
+    let mut choices = vec![(None, "show info".to_string())];
+    for (key, label) in ctx_actions.actions(app, id.clone()) {
+        choices.push((hotkey(key), label));
+    }
+    choices.push((hotkey(Key::Escape), "close".to_string()));
+    let radial = choices.len() <= RadialMenu::MAX_SLICES;
+    let menu = RadialMenu::new(ctx, choices);
+    let cursor = ctx.canvas.get_cursor();
+    // The pie surrounds the cursor; the list fallback hangs below it.
+    if radial {
+        Composite::new(menu)
+            .aligned(
+                HorizontalAlignment::Centered(cursor.x),
+                VerticalAlignment::Centered(cursor.y),
+            )
+            .build(ctx)
+    } else {
+        Composite::new(menu.bg(app.cs.panel_bg).padding(5))
+            .aligned(
+                HorizontalAlignment::Centered(cursor.x),
+                VerticalAlignment::Below(cursor.y),
+            )
+            .build(ctx)
+    }
+}
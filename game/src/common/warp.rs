@@ -9,6 +9,7 @@ use geom::Pt2D;
 use map_model::{AreaID, BuildingID, IntersectionID, LaneID, RoadID};
 use sim::{PedestrianID, PersonID, TripID};
 use std::collections::BTreeMap;
+use std::fmt;
 
 const WARP_TO_CAM_ZOOM: f64 = 10.0;
 
@@ -17,6 +18,11 @@ impl EnteringWarp {
     pub fn new() -> Box<dyn State> {
         WizardState::new(Box::new(warp_to))
     }
+
+    // Skips typing the "i"
+    pub fn intersection() -> Box<dyn State> {
+        WizardState::new(Box::new(warp_to_intersection))
+    }
 }
 
 pub struct Warping {
@@ -80,10 +86,206 @@ fn warp_to(wiz: &mut Wizard, ctx: &mut EventCtx, app: &mut App) -> Option<Transi
     }
 }
 
+fn warp_to_intersection(wiz: &mut Wizard, ctx: &mut EventCtx, app: &mut App) -> Option<Transition> {
+    let idx = wiz.wrap(ctx).input_usize("Warp to which intersection?")?;
+    if let Some(t) = inner_warp(ctx, app, &format!("i{}", idx)) {
+        Some(t)
+    } else {
+        Some(Transition::Replace(msg(
+            "Bad warp ID",
+            vec![format!("There's no intersection {}", idx)],
+        )))
+    }
+}
+
+// A textual pointer to an exact spot, for collaborators to send each other. It looks like
+// "montlake/osm:i123@4567.8,910.1,3.5": the map name, optionally an object using the same syntax
+// as warping, then the center of the camera and its zoom. Objects from OSM are named by their OSM
+// IDs, so links keep working after the map is imported again.
+pub struct Permalink {
+    pub map: String,
+    pub id: Option<String>,
+    pub pt: Pt2D,
+    pub zoom: f64,
+}
+
+impl Permalink {
+    pub fn current(ctx: &EventCtx, app: &App, id: Option<ID>) -> Permalink {
+        Permalink {
+            map: app.primary.map.get_name().to_string(),
+            id: id.and_then(|id| warp_id(app, id)),
+            pt: ctx.canvas.center_to_map_pt(),
+            zoom: ctx.canvas.cam_zoom,
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<Permalink> {
+        let (name, camera) = split_once(line, '@')?;
+        let (map, id) = match split_once(name, '/') {
+            Some((map, id)) => (map, Some(id.to_string())),
+            None => (name, None),
+        };
+        let nums: Vec<f64> = camera
+            .split(',')
+            .map(|x| x.parse::<f64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        if map.is_empty() || nums.len() != 3 {
+            return None;
+        }
+        Some(Permalink {
+            map: map.to_string(),
+            id,
+            pt: Pt2D::new(nums[0], nums[1]),
+            zoom: nums[2],
+        })
+    }
+
+    // Used at startup, once the right map is loaded. Jumps the camera to the saved spot, then
+    // returns a state to warp to the object and open its info panel, if there is one.
+    pub fn jump(&self, ctx: &mut EventCtx, app: &mut App) -> Option<Box<dyn State>> {
+        ctx.canvas.cam_zoom = self.zoom;
+        ctx.canvas.center_on_map_pt(self.pt);
+        let id = parse_id(app, self.id.as_ref()?)?;
+        let pt = id.canonical_point(&app.primary)?;
+        Some(Warping::new(
+            ctx,
+            pt,
+            Some(self.zoom),
+            Some(id),
+            &mut app.primary,
+        ))
+    }
+}
+
+impl fmt::Display for Permalink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.map)?;
+        if let Some(ref id) = self.id {
+            write!(f, "/{}", id)?;
+        }
+        write!(f, "@{:.1},{:.1},{:.2}", self.pt.x(), self.pt.y(), self.zoom)
+    }
+}
+
+fn split_once(x: &str, delim: char) -> Option<(&str, &str)> {
+    let idx = x.find(delim)?;
+    Some((&x[..idx], &x[idx + 1..]))
+}
+
+// Splits something like "i123" into the letter and the number
+fn split_id(line: &str) -> Option<(char, usize)> {
+    let prefix = line.chars().next()?;
+    let idx = line[prefix.len_utf8()..].parse::<usize>().ok()?;
+    Some((prefix, idx))
+}
+
+// The objects that inner_warp can find directly, without going through people or trips
+fn parse_id(app: &App, line: &str) -> Option<ID> {
+    if line.starts_with("osm:") {
+        return parse_osm_id(app, &line["osm:".len()..]);
+    }
+    let (prefix, idx) = split_id(line)?;
+    match prefix {
+        'r' => {
+            let r = app.primary.map.maybe_get_r(RoadID(idx))?;
+            Some(ID::Lane(r.children_forwards[0].0))
+        }
+        'l' => Some(ID::Lane(LaneID(idx))),
+        'i' => Some(ID::Intersection(IntersectionID(idx))),
+        'b' => Some(ID::Building(BuildingID(idx))),
+        'a' => Some(ID::Area(AreaID(idx))),
+        'p' => Some(ID::Pedestrian(PedestrianID(idx))),
+        // This one gets more complicated. :)
+        'c' => Some(ID::Car(app.primary.sim.lookup_car_id(idx)?)),
+        'T' => Some(ID::Turn(app.primary.map.lookup_turn_by_idx(idx)?)),
+        _ => None,
+    }
+}
+
+// Objects from OSM, named by OSM IDs instead of ours, so they survive importing the map again.
+// "i123" is a node, "b123" and "a123" are ways, "r123,4,5" is way 123 between nodes 4 and 5, and
+// "l123,4,5,2" is the third lane of that road, counting the way Road::all_lanes does.
+fn parse_osm_id(app: &App, line: &str) -> Option<ID> {
+    let map = &app.primary.map;
+    let prefix = line.chars().next()?;
+    let nums = line[prefix.len_utf8()..]
+        .split(',')
+        .map(|x| x.parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match (prefix, &nums[..]) {
+        ('i', [node]) => Some(ID::Intersection(map.find_i_by_osm_id(*node).ok()?)),
+        ('b', [way]) => Some(ID::Building(map.find_b_by_osm_id(*way)?)),
+        ('a', [way]) => map
+            .all_areas()
+            .iter()
+            .find(|a| a.osm_id == *way)
+            .map(|a| ID::Area(a.id)),
+        ('r', [way, node1, node2]) => {
+            let r = map.find_r_by_osm_id(*way, (*node1, *node2)).ok()?;
+            Some(ID::Lane(*map.get_r(r).all_lanes().get(0)?))
+        }
+        ('l', [way, node1, node2, idx]) => {
+            let r = map.find_r_by_osm_id(*way, (*node1, *node2)).ok()?;
+            Some(ID::Lane(*map.get_r(r).all_lanes().get(*idx as usize)?))
+        }
+        _ => None,
+    }
+}
+
+// The inverse of parse_id. Anything from OSM uses parse_osm_id's syntax.
+fn warp_id(app: &App, id: ID) -> Option<String> {
+    let map = &app.primary.map;
+    let osm_road = |r: RoadID| {
+        let orig = &map.get_r(r).orig_id;
+        format!(
+            "{},{},{}",
+            orig.osm_way_id, orig.i1.osm_node_id, orig.i2.osm_node_id
+        )
+    };
+    match id {
+        ID::Road(r) => Some(format!("osm:r{}", osm_road(r))),
+        ID::Lane(l) => {
+            let r = map.get_l(l).parent;
+            let idx = map.get_r(r).all_lanes().into_iter().position(|x| x == l)?;
+            Some(format!("osm:l{},{}", osm_road(r), idx))
+        }
+        ID::Intersection(i) => Some(format!("osm:i{}", map.get_i(i).orig_id.osm_node_id)),
+        ID::Building(b) => Some(format!("osm:b{}", map.get_b(b).osm_way_id)),
+        ID::Area(a) => Some(format!("osm:a{}", map.get_a(a).osm_id)),
+        // Agents don't come from OSM, but they're the same for anybody running the same scenario
+        ID::Pedestrian(p) => Some(format!("p{}", p.0)),
+        ID::Car(c) => Some(format!("c{}", c.0)),
+        // Turn numbers depend on how the map was imported, so just point the camera there
+        ID::Turn(_) | ID::ParkingLot(_) | ID::PedCrowd(_) | ID::BusStop(_) => None,
+    }
+}
+
 fn inner_warp(ctx: &mut EventCtx, app: &mut App, line: &str) -> Option<Transition> {
     if line.is_empty() {
         return None;
     }
+    if let Some(link) = Permalink::parse(line) {
+        // Switching maps from here is too disruptive; load the right map first.
+        if link.map != app.primary.map.get_name().as_str() {
+            return Some(Transition::Replace(msg(
+                "Wrong map",
+                vec![format!(
+                    "This link is for {}. Load that map and try again.",
+                    link.map
+                )],
+            )));
+        }
+        if let Some(ref id) = link.id {
+            return inner_warp(ctx, app, id);
+        }
+        return Some(Transition::Replace(Warping::new(
+            ctx,
+            link.pt,
+            Some(link.zoom),
+            None,
+            &mut app.primary,
+        )));
+    }
     // TODO Weird magic shortcut to go to last spot. What should this be?
     if line == "j" {
         if let Some((pt, zoom)) = app.primary.last_warped_from {
@@ -98,17 +300,8 @@ fn inner_warp(ctx: &mut EventCtx, app: &mut App, line: &str) -> Option<Transitio
         return None;
     }
 
-    let id = match usize::from_str_radix(&line[1..line.len()], 10) {
-        Ok(idx) => match line.chars().next().unwrap() {
-            'r' => {
-                let r = app.primary.map.maybe_get_r(RoadID(idx))?;
-                ID::Lane(r.children_forwards[0].0)
-            }
-            'l' => ID::Lane(LaneID(idx)),
-            'i' => ID::Intersection(IntersectionID(idx)),
-            'b' => ID::Building(BuildingID(idx)),
-            'a' => ID::Area(AreaID(idx)),
-            'p' => ID::Pedestrian(PedestrianID(idx)),
+    let id = match split_id(line) {
+        Some((prefix, idx)) => match prefix {
             'P' => {
                 let id = PersonID(idx);
                 app.primary.sim.lookup_person(id)?;
@@ -125,11 +318,6 @@ fn inner_warp(ctx: &mut EventCtx, app: &mut App, line: &str) -> Option<Transitio
                     }
                 })));
             }
-            'c' => {
-                // This one gets more complicated. :)
-                let c = app.primary.sim.lookup_car_id(idx)?;
-                ID::Car(c)
-            }
             't' => {
                 let trip = TripID(idx);
                 let person = app.primary.sim.trip_to_person(trip);
@@ -146,17 +334,9 @@ fn inner_warp(ctx: &mut EventCtx, app: &mut App, line: &str) -> Option<Transitio
                     }
                 })));
             }
-            'T' => {
-                let t = app.primary.map.lookup_turn_by_idx(idx)?;
-                ID::Turn(t)
-            }
-            _ => {
-                return None;
-            }
+            _ => parse_id(app, line)?,
         },
-        Err(_) => {
-            return None;
-        }
+        None => parse_id(app, line)?,
     };
     if let Some(pt) = id.canonical_point(&app.primary) {
         println!("Warping to {:?}", id);
@@ -171,3 +351,22 @@ fn inner_warp(ctx: &mut EventCtx, app: &mut App, line: &str) -> Option<Transitio
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permalink_with_osm_id() {
+        let line = "montlake/osm:l123,-4,5,2@4567.8,910.1,3.50";
+        let link = Permalink::parse(line).unwrap();
+        assert_eq!(link.map, "montlake");
+        assert_eq!(link.id, Some("osm:l123,-4,5,2".to_string()));
+        assert_eq!(link.to_string(), line);
+
+        assert_eq!(split_id("i123"), Some(('i', 123)));
+        assert_eq!(split_id("osm:i123"), None);
+        assert_eq!(split_id("é1"), Some(('é', 1)));
+        assert_eq!(split_id(""), None);
+    }
+}
@@ -315,6 +315,10 @@ impl State for DebugMode {
             self.tool_panel.draw(g);
         }
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Debug".to_string())
+    }
 }
 
 impl ShowObject for DebugMode {
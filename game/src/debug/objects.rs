@@ -56,6 +56,7 @@ impl ObjectDebugger {
                         PathConstraints::Car,
                         PathConstraints::Bike,
                         PathConstraints::Bus,
+                        PathConstraints::Truck,
                     ] {
                         if constraint.can_use(l, map) {
                             println!(
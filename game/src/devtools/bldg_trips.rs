@@ -0,0 +1,205 @@
+use crate::app::{App, ShowEverything};
+use crate::game::{State, Transition};
+use crate::helpers::{color_for_mode, ID};
+use abstutil::{prettyprint_usize, Counter};
+use ezgui::{
+    hotkey, Btn, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key,
+    Line, Outcome, Text, TextExt, VerticalAlignment, Widget,
+};
+use geom::{ArrowCap, Distance, PolyLine, Pt2D};
+use map_model::{BuildingID, Map};
+use sim::{DontDrawAgents, Scenario, TripEndpoint, TripMode};
+use std::collections::BTreeMap;
+
+// Click a building and see where all of the trips starting or ending there go.
+pub struct BuildingTrips {
+    scenario: Scenario,
+    bldg: Option<BuildingID>,
+    composite: Composite,
+    draw: Drawable,
+}
+
+impl BuildingTrips {
+    pub fn new(
+        ctx: &mut EventCtx,
+        app: &App,
+        scenario: Scenario,
+        bldg: Option<BuildingID>,
+    ) -> Box<dyn State> {
+        let map = &app.primary.map;
+        let mut batch = GeomBatch::new();
+        let mut col = vec![Widget::row(vec![
+            Line("Trips per building")
+                .small_heading()
+                .draw(ctx)
+                .margin_right(10),
+            Btn::text_fg("X")
+                .build(ctx, "close", hotkey(Key::Escape))
+                .align_right(),
+        ])];
+
+        if let Some(b) = bldg {
+            // Keyed by (the other end of the trip, is the trip leaving this building?)
+            let mut arrows: BTreeMap<(ID, bool), Counter<TripMode>> = BTreeMap::new();
+            let mut from_modes = Counter::new();
+            let mut to_modes = Counter::new();
+            let mut from_hours = Counter::new();
+            let mut to_hours = Counter::new();
+            for p in &scenario.people {
+                for trip in &p.trips {
+                    let mode = trip.trip.mode();
+                    let hour = trip.depart.get_parts().0;
+                    let start = trip.trip.start(map);
+                    let end = trip.trip.end(map);
+                    if start == TripEndpoint::Bldg(b) {
+                        from_modes.inc(mode);
+                        from_hours.inc(hour);
+                        arrows
+                            .entry((endpoint_id(&end), true))
+                            .or_insert_with(Counter::new)
+                            .inc(mode);
+                    }
+                    if end == TripEndpoint::Bldg(b) {
+                        to_modes.inc(mode);
+                        to_hours.inc(hour);
+                        arrows
+                            .entry((endpoint_id(&start), false))
+                            .or_insert_with(Counter::new)
+                            .inc(mode);
+                    }
+                }
+            }
+
+            let max = arrows.values().map(|cnt| cnt.sum()).max().unwrap_or(1);
+            let here = map.get_b(b).polygon.center();
+            for ((other, leaving), modes) in arrows {
+                let there = endpoint_center(&other, map);
+                let pts = if leaving {
+                    vec![here, there]
+                } else {
+                    vec![there, here]
+                };
+                // Color by the most common mode between the two places
+                let mode = modes
+                    .borrow()
+                    .iter()
+                    .max_by_key(|(_, cnt)| **cnt)
+                    .map(|(m, _)| *m)
+                    .unwrap();
+                let pct = (modes.sum() as f64) / (max as f64);
+                if let Some(pl) = PolyLine::maybe_new(pts) {
+                    batch.push(
+                        color_for_mode(app, mode).alpha(0.8),
+                        pl.make_arrow(Distance::meters(2.0 + 13.0 * pct), ArrowCap::Triangle)
+                            .unwrap(),
+                    );
+                }
+            }
+            batch.push(Color::BLUE.alpha(0.8), map.get_b(b).polygon.clone());
+
+            col.push(format!("{}", b).draw_text(ctx));
+            col.push(
+                summarize("Trips leaving here", from_modes, from_hours)
+                    .draw(ctx)
+                    .margin_below(10),
+            );
+            col.push(summarize("Trips arriving here", to_modes, to_hours).draw(ctx));
+        } else {
+            col.push("Click a building to explore its trips".draw_text(ctx));
+        }
+
+        Box::new(BuildingTrips {
+            scenario,
+            bldg,
+            draw: ctx.upload(batch),
+            composite: Composite::new(Widget::col(col).padding(10).bg(app.cs.panel_bg))
+                .aligned(HorizontalAlignment::Right, VerticalAlignment::Top)
+                .build(ctx),
+        })
+    }
+}
+
+impl State for BuildingTrips {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        if ctx.redo_mouseover() {
+            app.primary.current_selection = app.calculate_current_selection(
+                ctx,
+                &DontDrawAgents {},
+                &ShowEverything::new(),
+                false,
+                false,
+                true,
+            );
+            if let Some(ID::Building(_)) = app.primary.current_selection {
+            } else {
+                app.primary.current_selection = None;
+            }
+        }
+        if let Some(ID::Building(b)) = app.primary.current_selection {
+            if Some(b) != self.bldg && app.per_obj.left_click(ctx, "explore trips") {
+                return Transition::Replace(BuildingTrips::new(
+                    ctx,
+                    app,
+                    self.scenario.clone(),
+                    Some(b),
+                ));
+            }
+        }
+
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            },
+            None => {}
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        g.redraw(&self.draw);
+        self.composite.draw(g);
+    }
+}
+
+fn endpoint_id(endpt: &TripEndpoint) -> ID {
+    match endpt {
+        TripEndpoint::Bldg(b) => ID::Building(*b),
+        TripEndpoint::Border(i, _) => ID::Intersection(*i),
+    }
+}
+
+fn endpoint_center(id: &ID, map: &Map) -> Pt2D {
+    match id {
+        ID::Building(b) => map.get_b(*b).polygon.center(),
+        ID::Intersection(i) => map.get_i(*i).polygon.center(),
+        _ => unreachable!(),
+    }
+}
+
+fn summarize(title: &str, modes: Counter<TripMode>, hours: Counter<usize>) -> Text {
+    let mut txt = Text::from(Line(format!(
+        "{}: {}",
+        title,
+        prettyprint_usize(modes.sum())
+    )));
+    for (mode, cnt) in modes.consume() {
+        txt.add(Line(format!(
+            "  {}: {}",
+            mode.ongoing_verb(),
+            prettyprint_usize(cnt)
+        )));
+    }
+    for (hour, cnt) in hours.consume() {
+        txt.add(Line(format!(
+            "  departing {:02}:00: {}",
+            hour,
+            prettyprint_usize(cnt)
+        )));
+    }
+    txt
+}
@@ -1,5 +1,5 @@
 use crate::app::{App, ShowEverything};
-use crate::common::{make_heatmap, HeatmapOptions};
+use crate::common::{make_heatmap, HeatmapOptions, HeatmapStyle};
 use crate::game::{State, Transition};
 use crate::helpers::{amenity_type, ID};
 use abstutil::Counter;
@@ -145,7 +145,10 @@ impl State for PopularDestinations {
         }
 
         let opts = if self.composite.is_checked("Show heatmap") {
-            Some(HeatmapOptions::from_controls(&self.composite))
+            Some(HeatmapOptions::from_controls(
+                &self.composite,
+                HeatmapStyle::new(),
+            ))
         } else {
             None
         };
@@ -162,7 +165,11 @@ impl State for PopularDestinations {
     }
 
     fn draw(&self, g: &mut GfxCtx, app: &App) {
-        g.redraw(&self.draw);
+        if let Some(ref o) = self.opts {
+            o.redraw(g, &self.draw);
+        } else {
+            g.redraw(&self.draw);
+        }
         self.composite.draw(g);
 
         if let Some(ID::Building(b)) = app.primary.current_selection {
@@ -1,14 +1,17 @@
+mod bldg_trips;
 mod blocks;
 mod destinations;
 mod kml;
 pub mod mapping;
 mod polygon;
+mod render_bench;
 mod scenario;
 mod story;
+mod watch;
 
 use crate::app::App;
 use crate::common::CityPicker;
-use crate::game::{DrawBaselayer, State, Transition, WizardState};
+use crate::game::{msg, DrawBaselayer, State, Transition, WizardState};
 use crate::helpers::nice_map_name;
 use abstutil::Timer;
 use ezgui::{
@@ -16,6 +19,7 @@ use ezgui::{
     VerticalAlignment, Widget, Wizard,
 };
 use geom::LonLat;
+pub use watch::{reload_map, MapWatcher};
 
 pub struct DevToolsMode {
     composite: Composite,
@@ -48,6 +52,7 @@ impl DevToolsMode {
                         Btn::text_fg("load scenario").build_def(ctx, hotkey(Key::W)),
                         Btn::text_fg("view KML").build_def(ctx, hotkey(Key::K)),
                         Btn::text_fg("story maps").build_def(ctx, hotkey(Key::S)),
+                        Btn::text_fg("benchmark rendering").build_def(ctx, hotkey(Key::B)),
                     ])
                     .flex_wrap(ctx, 60),
                 ])
@@ -87,6 +92,9 @@ impl State for DevToolsMode {
                 "story maps" => {
                     return Transition::Push(story::StoryMapEditor::new(ctx, app));
                 }
+                "benchmark rendering" => {
+                    return Transition::Push(render_bench::RenderBenchmark::new(ctx, app));
+                }
                 "change map" => {
                     return Transition::Push(CityPicker::new(
                         ctx,
@@ -134,9 +142,17 @@ fn choose_polygon(wiz: &mut Wizard, ctx: &mut EventCtx, app: &mut App) -> Option
         abstutil::list_all_objects("../data/input/seattle/polygons/".to_string())
     })?;
     match LonLat::read_osmosis_polygon(format!("../data/input/seattle/polygons/{}.poly", name)) {
-        Ok(pts) => Some(Transition::Replace(polygon::PolygonEditor::new(
-            ctx, app, name, pts,
-        ))),
+        Ok(pts) => {
+            if app.primary.map.get_gps_bounds().try_convert(&pts).is_none() {
+                return Some(Transition::Replace(msg(
+                    "Can't edit polygon",
+                    vec![format!("{} extends past the edge of this map", name)],
+                )));
+            }
+            Some(Transition::Replace(polygon::PolygonEditor::new(
+                ctx, app, name, pts,
+            )))
+        }
         Err(err) => {
             println!("Bad polygon {}: {}", name, err);
             Some(Transition::Pop)
@@ -1,27 +1,47 @@
 use crate::app::App;
 use crate::common::CommonState;
-use crate::game::{State, Transition};
+use crate::game::{msg, State, Transition, WizardState};
+use abstutil::Timer;
 use ezgui::{
-    hotkey, Btn, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Text,
-    VerticalAlignment, Widget,
+    hotkey, Btn, Checkbox, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Key, Line,
+    Outcome, Text, VerticalAlignment, Widget,
 };
-use geom::{Circle, Distance, LonLat, Polygon, Pt2D};
+use geom::{Circle, Distance, FindClosest, GPSBounds, LonLat, Polygon, Pt2D};
+use map_model::RoadID;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::{Error, Write};
+use std::io::{Error, ErrorKind, Write};
 
 const POINT_RADIUS: Distance = Distance::const_meters(10.0);
+// Points this close to a road center-line snap to it
+const SNAP_DISTANCE: Distance = Distance::const_meters(20.0);
 // Localized and internal, so don't put in ColorScheme.
 const POINT_COLOR: Color = Color::RED;
 const POLYGON_COLOR: Color = Color::BLUE.alpha(0.6);
+const HOLE_COLOR: Color = Color::RED.alpha(0.4);
 const POINT_TO_MOVE: Color = Color::CYAN;
 const LAST_PLACED_POINT: Color = Color::GREEN;
+const EDGE_MIDPOINT: Color = Color::YELLOW.alpha(0.8);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hovering {
+    // (ring, index of the point)
+    Point(usize, usize),
+    // (ring, index of the first point of the edge)
+    Edge(usize, usize),
+}
 
 pub struct PolygonEditor {
     composite: Composite,
     name: String,
-    points: Vec<LonLat>,
-    mouseover_pt: Option<usize>,
+    // The first ring is the outer boundary; the rest are holes. Rings aren't closed; the first
+    // point isn't repeated.
+    rings: Vec<Vec<LonLat>>,
+    // New points get added to this ring
+    current_ring: usize,
+    hovering: Option<Hovering>,
     moving_pt: bool,
+    snap_to_roads: FindClosest<RoadID>,
 }
 
 impl PolygonEditor {
@@ -32,6 +52,11 @@ impl PolygonEditor {
         mut points: Vec<LonLat>,
     ) -> Box<dyn State> {
         points.pop();
+        let mut snap_to_roads = FindClosest::new(app.primary.map.get_bounds());
+        for r in app.primary.map.all_roads() {
+            snap_to_roads.add(r.id, r.center_pts.points());
+        }
+
         Box::new(PolygonEditor {
             composite: Composite::new(
                 Widget::col(vec![
@@ -41,8 +66,12 @@ impl PolygonEditor {
                             .build(ctx, "close", hotkey(Key::Escape))
                             .align_right(),
                     ]),
+                    Checkbox::text(ctx, "snap to roads", hotkey(Key::S), false),
+                    Btn::text_fg("start a hole").build_def(ctx, hotkey(Key::H)),
                     Btn::text_fg("export as an Osmosis polygon filter")
                         .build_def(ctx, hotkey(Key::X)),
+                    Btn::text_fg("export as GeoJSON").build_def(ctx, hotkey(Key::G)),
+                    Btn::text_fg("import GeoJSON").build_def(ctx, hotkey(Key::I)),
                 ])
                 .padding(16)
                 .bg(app.cs.panel_bg),
@@ -50,28 +79,63 @@ impl PolygonEditor {
             .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
             .build(ctx),
             name,
-            points,
-            mouseover_pt: None,
+            rings: vec![points],
+            current_ring: 0,
+            hovering: None,
             moving_pt: false,
+            snap_to_roads,
         })
     }
+
+    // Where should a point go, given the cursor?
+    fn cursor_to_gps(&self, ctx: &EventCtx, gps_bounds: &GPSBounds) -> Option<LonLat> {
+        let mut pt = ctx.canvas.get_cursor_in_map_space()?;
+        if self.composite.is_checked("snap to roads") {
+            if let Some((_, snapped)) = self.snap_to_roads.closest_pt(pt, SNAP_DISTANCE) {
+                pt = snapped;
+            }
+        }
+        pt.to_gps(gps_bounds)
+    }
+
+    fn calculate_hovering(&self, ctx: &EventCtx, gps_bounds: &GPSBounds) -> Option<Hovering> {
+        let cursor = ctx.canvas.get_cursor_in_map_space()?;
+        let radius = POINT_RADIUS / ctx.canvas.cam_zoom;
+        for (ring_idx, ring) in self.rings.iter().enumerate() {
+            let pts = match gps_bounds.try_convert(ring) {
+                Some(pts) => pts,
+                None => {
+                    continue;
+                }
+            };
+            if let Some(idx) = pts
+                .iter()
+                .position(|pt| Circle::new(*pt, radius).contains_pt(cursor))
+            {
+                return Some(Hovering::Point(ring_idx, idx));
+            }
+            if let Some(idx) = edge_midpoints(&pts)
+                .into_iter()
+                .position(|pt| Circle::new(pt, radius / 2.0).contains_pt(cursor))
+            {
+                return Some(Hovering::Edge(ring_idx, idx));
+            }
+        }
+        None
+    }
 }
 
 impl State for PolygonEditor {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         let gps_bounds = app.primary.map.get_gps_bounds();
 
-        ctx.canvas_movement();
-
         if self.moving_pt {
-            if let Some(pt) = ctx
-                .canvas
-                .get_cursor_in_map_space()
-                .and_then(|c| c.to_gps(gps_bounds))
-            {
-                self.points[self.mouseover_pt.unwrap()] = pt;
+            if let Some(pt) = self.cursor_to_gps(ctx, gps_bounds) {
+                if let Some(Hovering::Point(ring, idx)) = self.hovering {
+                    self.rings[ring][idx] = pt;
+                }
             }
-            if ctx.input.key_released(Key::LeftControl) {
+            if ctx.input.left_mouse_button_released() {
                 self.moving_pt = false;
             }
 
@@ -83,42 +147,85 @@ impl State for PolygonEditor {
                 "close" => {
                     return Transition::Pop;
                 }
+                "start a hole" => {
+                    if self.rings[0].len() >= 3 {
+                        self.rings.retain(|ring| !ring.is_empty());
+                        self.rings.push(Vec::new());
+                        self.current_ring = self.rings.len() - 1;
+                    }
+                }
                 "export as an Osmosis polygon filter" => {
-                    if self.points.len() >= 3 {
-                        save_as_osmosis(&self.name, &self.points).unwrap();
+                    if self.rings[0].len() >= 3 {
+                        save_as_osmosis(&self.name, &self.rings).unwrap();
                     }
                 }
+                "export as GeoJSON" => {
+                    if self.rings[0].len() >= 3 {
+                        save_as_geojson(&self.name, &self.rings);
+                    }
+                }
+                "import GeoJSON" => {
+                    return Transition::Push(WizardState::new(Box::new(|wiz, ctx, app| {
+                        let path = wiz
+                            .wrap(ctx)
+                            .input_string("Path to a GeoJSON file with one polygon")?;
+                        match read_geojson(path.clone(), app.primary.map.get_gps_bounds()) {
+                            Ok(rings) => {
+                                Some(Transition::PopWithData(Box::new(move |state, _, _| {
+                                    let editor = state.downcast_mut::<PolygonEditor>().unwrap();
+                                    editor.rings = rings;
+                                    editor.current_ring = 0;
+                                    editor.hovering = None;
+                                })))
+                            }
+                            Err(err) => Some(Transition::Replace(msg(
+                                "Couldn't import GeoJSON",
+                                vec![format!("{}: {}", path, err)],
+                            ))),
+                        }
+                    })));
+                }
                 _ => unreachable!(),
             },
             None => {}
         }
 
-        if let Some(cursor) = ctx.canvas.get_cursor_in_map_space() {
-            self.mouseover_pt = self.points.iter().position(|pt| {
-                Circle::new(
-                    Pt2D::from_gps(*pt, gps_bounds).unwrap(),
-                    POINT_RADIUS / ctx.canvas.cam_zoom,
-                )
-                .contains_pt(cursor)
-            });
-        } else {
-            self.mouseover_pt = None;
+        if ctx.redo_mouseover() {
+            self.hovering = self.calculate_hovering(ctx, gps_bounds);
         }
-        // TODO maybe click-and-drag is more intuitive
-        if self.mouseover_pt.is_some() {
-            if ctx
-                .input
-                .key_pressed(Key::LeftControl, "hold to move this point")
-            {
-                self.moving_pt = true;
+
+        match self.hovering {
+            Some(Hovering::Point(ring, idx)) => {
+                if ctx.input.left_mouse_button_pressed() {
+                    self.moving_pt = true;
+                } else if ctx.input.key_pressed(Key::Backspace, "delete this point") {
+                    self.rings[ring].remove(idx);
+                    // Deleting the last point of a hole deletes the hole
+                    if ring != 0 && self.rings[ring].is_empty() {
+                        self.rings.remove(ring);
+                        self.current_ring = 0;
+                    }
+                    self.hovering = None;
+                }
             }
-        } else if let Some(pt) = ctx
-            .canvas
-            .get_cursor_in_map_space()
-            .and_then(|c| c.to_gps(gps_bounds))
-        {
-            if app.per_obj.left_click(ctx, "add a new point") {
-                self.points.push(pt);
+            Some(Hovering::Edge(ring, idx)) => {
+                if app.per_obj.left_click(ctx, "insert a point here") {
+                    if let Some(pt) = gps_bounds
+                        .try_convert(&self.rings[ring])
+                        .and_then(|pts| edge_midpoints(&pts)[idx].to_gps(gps_bounds))
+                    {
+                        self.rings[ring].insert(idx + 1, pt);
+                        self.hovering = Some(Hovering::Point(ring, idx + 1));
+                    }
+                }
+            }
+            None => {
+                ctx.canvas_movement();
+                if let Some(pt) = self.cursor_to_gps(ctx, gps_bounds) {
+                    if app.per_obj.left_click(ctx, "add a new point") {
+                        self.rings[self.current_ring].push(pt);
+                    }
+                }
             }
         }
 
@@ -126,35 +233,55 @@ impl State for PolygonEditor {
     }
 
     fn draw(&self, g: &mut GfxCtx, app: &App) {
-        let pts: Vec<Pt2D> = app.primary.map.get_gps_bounds().must_convert(&self.points);
-
-        if pts.len() == 2 {
-            g.draw_line(
-                POINT_COLOR,
-                POINT_RADIUS / 2.0,
-                &geom::Line::new(pts[0], pts[1]),
-            );
-        }
-        if pts.len() >= 3 {
-            g.draw_polygon(POLYGON_COLOR, &Polygon::new(&pts));
-        }
-        for (idx, pt) in pts.iter().enumerate() {
-            let color = if Some(idx) == self.mouseover_pt {
-                POINT_TO_MOVE
-            } else if idx == pts.len() - 1 {
-                LAST_PLACED_POINT
+        let gps_bounds = app.primary.map.get_gps_bounds();
+        for (ring_idx, ring) in self.rings.iter().enumerate() {
+            // Imported points are checked, so this shouldn't happen
+            let pts: Vec<Pt2D> = match gps_bounds.try_convert(ring) {
+                Some(pts) => pts,
+                None => {
+                    continue;
+                }
+            };
+            let color = if ring_idx == 0 {
+                POLYGON_COLOR
             } else {
-                POINT_COLOR
+                HOLE_COLOR
             };
-            g.draw_circle(color, &Circle::new(*pt, POINT_RADIUS / g.canvas.cam_zoom));
+
+            if pts.len() == 2 {
+                g.draw_line(
+                    POINT_COLOR,
+                    POINT_RADIUS / 2.0,
+                    &geom::Line::new(pts[0], pts[1]),
+                );
+            }
+            if pts.len() >= 3 {
+                g.draw_polygon(color, &Polygon::new(&pts));
+                for pt in edge_midpoints(&pts) {
+                    g.draw_circle(
+                        EDGE_MIDPOINT,
+                        &Circle::new(pt, POINT_RADIUS / 2.0 / g.canvas.cam_zoom),
+                    );
+                }
+            }
+            for (idx, pt) in pts.iter().enumerate() {
+                let color = if Some(Hovering::Point(ring_idx, idx)) == self.hovering {
+                    POINT_TO_MOVE
+                } else if ring_idx == self.current_ring && idx == pts.len() - 1 {
+                    LAST_PLACED_POINT
+                } else {
+                    POINT_COLOR
+                };
+                g.draw_circle(color, &Circle::new(*pt, POINT_RADIUS / g.canvas.cam_zoom));
+            }
         }
 
         self.composite.draw(g);
-        if self.mouseover_pt.is_some() {
+        if let Some(Hovering::Point(_, _)) = self.hovering {
             CommonState::draw_custom_osd(
                 g,
                 app,
-                Text::from(Line("hold left Control to move point")),
+                Text::from(Line("drag to move point, backspace to delete it")),
             );
         } else {
             CommonState::draw_osd(g, app);
@@ -162,23 +289,138 @@ impl State for PolygonEditor {
     }
 }
 
+// Points in the middle of each edge of a closed ring, starting with the edge from the first point
+fn edge_midpoints(pts: &Vec<Pt2D>) -> Vec<Pt2D> {
+    if pts.len() < 3 {
+        return Vec::new();
+    }
+    (0..pts.len())
+        .map(|i| Pt2D::center(&vec![pts[i], pts[(i + 1) % pts.len()]]))
+        .collect()
+}
+
 // https://wiki.openstreetmap.org/wiki/Osmosis/Polygon_Filter_File_Format
-fn save_as_osmosis(name: &str, pts: &Vec<LonLat>) -> Result<(), Error> {
+fn save_as_osmosis(name: &str, rings: &Vec<Vec<LonLat>>) -> Result<(), Error> {
     let path = "bounding_boy.poly";
     let mut f = File::create(&path)?;
 
     writeln!(f, "{}", name)?;
-    writeln!(f, "1")?;
-    for gps in pts {
-        writeln!(f, "     {}    {}", gps.x(), gps.y())?;
-    }
-    // Have to repeat the first point
-    {
-        writeln!(f, "     {}    {}", pts[0].x(), pts[0].y())?;
+    for (idx, pts) in rings.iter().enumerate() {
+        if pts.len() < 3 {
+            continue;
+        }
+        // Holes are marked by a leading !
+        if idx == 0 {
+            writeln!(f, "{}", idx + 1)?;
+        } else {
+            writeln!(f, "!{}", idx + 1)?;
+        }
+        for gps in pts {
+            writeln!(f, "     {}    {}", gps.x(), gps.y())?;
+        }
+        // Have to repeat the first point
+        {
+            writeln!(f, "     {}    {}", pts[0].x(), pts[0].y())?;
+        }
+        writeln!(f, "END")?;
     }
     writeln!(f, "END")?;
-    writeln!(f, "END")?;
 
     println!("Exported {}", path);
     Ok(())
 }
+
+// Just enough of https://tools.ietf.org/html/rfc7946 to round-trip one polygon
+#[derive(Serialize, Deserialize)]
+struct FeatureCollection {
+    #[serde(rename = "type")]
+    collection_type: String,
+    features: Vec<Feature>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    feature_type: String,
+    #[serde(default)]
+    properties: std::collections::BTreeMap<String, String>,
+    geometry: Geometry,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Geometry {
+    #[serde(rename = "type")]
+    geometry_type: String,
+    // [ring][point][lon, lat]
+    coordinates: Vec<Vec<Vec<f64>>>,
+}
+
+fn save_as_geojson(name: &str, rings: &Vec<Vec<LonLat>>) {
+    let mut properties = std::collections::BTreeMap::new();
+    properties.insert("name".to_string(), name.to_string());
+    let coordinates = rings
+        .iter()
+        .filter(|pts| pts.len() >= 3)
+        .map(|pts| {
+            // GeoJSON rings are closed
+            let mut ring: Vec<Vec<f64>> = pts.iter().map(|gps| vec![gps.x(), gps.y()]).collect();
+            ring.push(ring[0].clone());
+            ring
+        })
+        .collect();
+    abstutil::write_json(
+        format!("{}.geojson", name),
+        &FeatureCollection {
+            collection_type: "FeatureCollection".to_string(),
+            features: vec![Feature {
+                feature_type: "Feature".to_string(),
+                properties,
+                geometry: Geometry {
+                    geometry_type: "Polygon".to_string(),
+                    coordinates,
+                },
+            }],
+        },
+    );
+}
+
+// Only the first feature is used, and it must be a Polygon inside the map.
+fn read_geojson(path: String, gps_bounds: &GPSBounds) -> Result<Vec<Vec<LonLat>>, Error> {
+    if !path.ends_with(".geojson") && !path.ends_with(".json") {
+        return Err(Error::new(ErrorKind::Other, "not a .geojson file"));
+    }
+    let collection: FeatureCollection = abstutil::maybe_read_json(path, &mut Timer::throwaway())?;
+    let feature = collection
+        .features
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::Other, "no features"))?;
+    if feature.geometry.geometry_type != "Polygon" {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("{} isn't a Polygon", feature.geometry.geometry_type),
+        ));
+    }
+    let mut rings = Vec::new();
+    for ring in feature.geometry.coordinates {
+        let mut pts = Vec::new();
+        for pair in ring {
+            if pair.len() < 2 {
+                return Err(Error::new(ErrorKind::Other, "bad coordinate"));
+            }
+            pts.push(LonLat::new(pair[0], pair[1]));
+        }
+        pts.pop();
+        if gps_bounds.try_convert(&pts).is_none() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "some points are outside the map",
+            ));
+        }
+        rings.push(pts);
+    }
+    if rings.is_empty() {
+        rings.push(Vec::new());
+    }
+    Ok(rings)
+}
@@ -0,0 +1,92 @@
+use crate::app::App;
+use crate::game::{DrawBaselayer, State, Transition};
+use ezgui::{
+    bench_tessellation, bench_upload, hotkey, BenchResult, Btn, Composite, DrawBench, EventCtx,
+    EventLoopMode, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Text, VerticalAlignment,
+    Widget,
+};
+use std::cell::RefCell;
+
+// Big enough that per-shape overhead dominates
+const NUM_SHAPES: usize = 10_000;
+
+// Runs the synthetic rendering workloads from ezgui's bench module. Tessellation and uploading
+// are measured once, when this starts. Issuing draw calls is measured every frame, so the numbers
+// settle as the window keeps redrawing.
+pub struct RenderBenchmark {
+    composite: Composite,
+    results: Vec<BenchResult>,
+    draw_bench: DrawBench,
+    draw_results: RefCell<Vec<BenchResult>>,
+}
+
+impl RenderBenchmark {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        let mut results = vec![bench_tessellation(NUM_SHAPES)];
+        results.extend(bench_upload(ctx.prerender, NUM_SHAPES));
+        for r in &results {
+            println!("{}", r.describe());
+        }
+        let mut bench = RenderBenchmark {
+            composite: Composite::new(Widget::nothing()).build(ctx),
+            results,
+            draw_bench: DrawBench::new(ctx.prerender, NUM_SHAPES),
+            draw_results: RefCell::new(Vec::new()),
+        };
+        bench.make_panel(ctx, app);
+        Box::new(bench)
+    }
+
+    fn make_panel(&mut self, ctx: &mut EventCtx, app: &App) {
+        let mut txt = Text::new();
+        for r in self.results.iter().chain(self.draw_results.borrow().iter()) {
+            txt.add(Line(r.describe()));
+        }
+        self.composite = Composite::new(
+            Widget::col(vec![
+                Widget::row(vec![
+                    Line("Rendering benchmark").small_heading().draw(ctx),
+                    Btn::text_fg("X")
+                        .build(ctx, "close", hotkey(Key::Escape))
+                        .align_right(),
+                ]),
+                txt.draw(ctx),
+            ])
+            .padding(10)
+            .bg(app.cs.panel_bg),
+        )
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+    }
+}
+
+impl State for RenderBenchmark {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            },
+            None => {}
+        }
+        // The draw timings change every frame, so keep redrawing.
+        if ctx.input.nonblocking_is_update_event().is_some() {
+            ctx.input.use_update_event();
+        }
+        self.make_panel(ctx, app);
+        Transition::KeepWithMode(EventLoopMode::Animation)
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.clear(app.cs.void_background);
+        *self.draw_results.borrow_mut() = self.draw_bench.draw(g);
+        self.composite.draw(g);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::app::App;
 use crate::common::{ColorDiscrete, CommonState};
+use crate::devtools::bldg_trips::BuildingTrips;
 use crate::devtools::blocks::BlockMap;
 use crate::devtools::destinations::PopularDestinations;
 use crate::game::{State, Transition};
@@ -59,6 +60,7 @@ impl ScenarioManager {
                     ]),
                     Btn::text_fg("block map").build_def(ctx, hotkey(Key::B)),
                     Btn::text_fg("popular destinations").build_def(ctx, hotkey(Key::D)),
+                    Btn::text_fg("trips per building").build_def(ctx, hotkey(Key::T)),
                     Text::from_multiline(vec![
                         Line(format!(
                             "{} people",
@@ -103,6 +105,14 @@ impl State for ScenarioManager {
                 "popular destinations" => {
                     return Transition::Push(PopularDestinations::new(ctx, app, &self.scenario));
                 }
+                "trips per building" => {
+                    return Transition::Push(BuildingTrips::new(
+                        ctx,
+                        app,
+                        self.scenario.clone(),
+                        None,
+                    ));
+                }
                 _ => unreachable!(),
             },
             None => {}
@@ -0,0 +1,111 @@
+use crate::app::App;
+use crate::game::State;
+use crate::sandbox::{GameplayMode, SandboxMode};
+use abstutil::Timer;
+use ezgui::EventCtx;
+use instant::Instant;
+use map_model::{Map, PermanentMapEdits};
+use std::io::Error;
+use std::time::{Duration, SystemTime};
+
+// Don't hit the filesystem on every single event
+const CHECK_FREQUENCY: Duration = Duration::from_secs(1);
+// The importer writes the map in pieces, so only reload once the file stops changing for this long.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+// For iterating on the importer: notice when the map file gets rebuilt, and reload it in place.
+pub struct MapWatcher {
+    path: String,
+    last_modified: Option<SystemTime>,
+    last_check: Instant,
+    // A newer modification time than the loaded map, and when it was first seen
+    pending: Option<(SystemTime, Instant)>,
+}
+
+impl MapWatcher {
+    pub fn new(map_name: &str) -> MapWatcher {
+        let path = abstutil::path_map(map_name);
+        MapWatcher {
+            last_modified: modified_time(&path),
+            path,
+            last_check: Instant::now(),
+            pending: None,
+        }
+    }
+
+    pub fn changed(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_FREQUENCY {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified = match modified_time(&self.path) {
+            Some(t) => t,
+            None => {
+                return false;
+            }
+        };
+        if Some(modified) == self.last_modified {
+            self.pending = None;
+            return false;
+        }
+        match self.pending {
+            Some((t, since)) if t == modified => {
+                if since.elapsed() < SETTLE_TIME {
+                    return false;
+                }
+                self.pending = None;
+                self.last_modified = Some(modified);
+                true
+            }
+            // Still being written
+            _ => {
+                self.pending = Some((modified, Instant::now()));
+                false
+            }
+        }
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Reloads the map, keeping the camera and whatever edits still apply. All states referring to
+// the old map are invalid afterwards, so this returns a fresh state stack. If the new file can't
+// be read, nothing changes.
+pub fn reload_map(ctx: &mut EventCtx, app: &mut App) -> Result<Vec<Box<dyn State>>, Error> {
+    let path = abstutil::path_map(app.primary.map.get_name());
+    println!("{} changed, reloading", path);
+    // Loading the map for real panics on a bad file, so check it first. This reads the file
+    // twice, but only when the importer rebuilds it.
+    abstutil::maybe_read_binary::<Map>(path.clone(), &mut Timer::new("check the rebuilt map"))?;
+    let perma = PermanentMapEdits::to_permanent(app.primary.map.get_edits(), &app.primary.map);
+    let num_cmds = perma.commands.len();
+
+    app.switch_map(ctx, path.clone());
+
+    if num_cmds > 0 {
+        match PermanentMapEdits::from_permanent(perma, &app.primary.map) {
+            Ok(edits) => {
+                let mut timer = Timer::new("reapply edits after reloading map");
+                crate::edit::apply_map_edits(ctx, app, edits);
+                app.primary
+                    .map
+                    .recalculate_pathfinding_after_edits(&mut timer);
+                app.primary.clear_sim();
+            }
+            Err(err) => {
+                println!(
+                    "Dropping {} edits that don't apply to the new map: {}",
+                    num_cmds, err
+                );
+            }
+        }
+    }
+
+    Ok(vec![Box::new(SandboxMode::new(
+        ctx,
+        app,
+        GameplayMode::Freeform(path),
+    ))])
+}
@@ -203,6 +203,7 @@ impl BulkEdit {
                                 Choice::new("parking", LaneType::Parking),
                                 Choice::new("bike", LaneType::Biking),
                                 Choice::new("bus", LaneType::Bus),
+                                Choice::new("HOV", LaneType::HOV),
                                 Choice::new("construction", LaneType::Construction),
                             ],
                         )
@@ -217,6 +218,7 @@ impl BulkEdit {
                                 Choice::new("parking", LaneType::Parking),
                                 Choice::new("bike", LaneType::Biking),
                                 Choice::new("bus", LaneType::Bus),
+                                Choice::new("HOV", LaneType::HOV),
                                 Choice::new("construction", LaneType::Construction),
                             ],
                         ),
@@ -284,6 +286,10 @@ impl State for BulkEdit {
         self.composite.draw(g);
         g.redraw(&self.preview);
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Bulk edit".to_string())
+    }
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -0,0 +1,263 @@
+use crate::app::App;
+use crate::common::CommonState;
+use crate::edit::apply_map_edits;
+use crate::game::{State, Transition};
+use abstutil::Timer;
+use ezgui::{
+    hotkey, Btn, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key,
+    Line, Outcome, Spinner, Text, TextExt, VerticalAlignment, Widget,
+};
+use geom::{Circle, Distance, Duration, Polygon, Pt2D};
+use map_model::{
+    can_change_bus_route, BusRouteID, BusStopID, EditBusRoute, EditCmd, PathConstraints,
+    PathRequest,
+};
+
+// Add and remove stops along a route and change how often buses run. Buses take the shortest path
+// between consecutive stops, so rerouting means adding a stop on a different street: either click
+// it, or drag part of the route's path onto it.
+pub struct BusRouteEditor {
+    composite: Composite,
+    id: BusRouteID,
+    stops: Vec<BusStopID>,
+    // In minutes; 0 means one bus for the whole route
+    headway: usize,
+    hovering: Option<BusStopID>,
+    draw_route: Drawable,
+    // The path from each stop to the next, for grabbing. None if there's no path.
+    legs: Vec<Option<Polygon>>,
+    hovering_leg: Option<usize>,
+    dragging_leg: Option<usize>,
+}
+
+impl BusRouteEditor {
+    pub fn new(ctx: &mut EventCtx, app: &mut App, id: BusRouteID) -> BusRouteEditor {
+        app.primary.current_selection = None;
+        // Checking the route needs up-to-date pathfinding
+        app.primary
+            .map
+            .recalculate_pathfinding_after_edits(&mut Timer::throwaway());
+
+        let orig = app.primary.map.get_br_edit(id);
+        let mut editor = BusRouteEditor {
+            composite: Composite::new(Widget::nothing()).build(ctx),
+            id,
+            stops: orig.stops,
+            headway: orig
+                .headway
+                .map(|dt| (dt.inner_seconds() / 60.0).round() as usize)
+                .unwrap_or(0),
+            hovering: None,
+            draw_route: ctx.upload(GeomBatch::new()),
+            legs: Vec::new(),
+            hovering_leg: None,
+            dragging_leg: None,
+        };
+        editor.rebuild(ctx, app);
+        editor
+    }
+
+    fn rebuild(&mut self, ctx: &mut EventCtx, app: &App) {
+        let map = &app.primary.map;
+        let err = can_change_bus_route(map, &self.stops);
+
+        let mut col = vec![
+            Line(format!("Editing {}", map.get_br(self.id).name))
+                .small_heading()
+                .draw(ctx),
+            format!(
+                "{} stops. Click a stop to add or remove it, or drag the route onto a new stop.",
+                self.stops.len()
+            )
+            .draw_text(ctx),
+            Widget::row(vec![
+                "Minutes between buses (0 for just one bus):".draw_text(ctx),
+                Spinner::new(ctx, (0, 120), self.headway).named("headway"),
+            ]),
+        ];
+        if let Some(ref err) = err {
+            col.push(Text::from(Line(err).fg(Color::RED)).draw(ctx));
+            col.push(Btn::text_fg("Apply").inactive(ctx));
+        } else {
+            col.push(Btn::text_fg("Apply").build_def(ctx, hotkey(Key::Enter)));
+        }
+        col.push(Btn::text_fg("Cancel").build_def(ctx, hotkey(Key::Escape)));
+        self.composite = Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+            .build(ctx);
+
+        let mut batch = GeomBatch::new();
+        self.legs.clear();
+        for (idx, stop1) in self.stops.iter().enumerate() {
+            let stop2 = self.stops[(idx + 1) % self.stops.len()];
+            let start = map.get_bs(*stop1).driving_pos;
+            let poly = map
+                .pathfind(PathRequest {
+                    start,
+                    end: map.get_bs(stop2).driving_pos,
+                    constraints: PathConstraints::Bus,
+                })
+                .and_then(|path| path.trace(map, start.dist_along(), None))
+                .map(|trace| trace.make_polygons(Distance::meters(2.0)));
+            if let Some(ref p) = poly {
+                batch.push(app.cs.unzoomed_bus, p.clone());
+            }
+            self.legs.push(poly);
+        }
+        self.draw_route = ctx.upload(batch);
+        self.hovering_leg = None;
+    }
+
+    fn stop_pt(&self, app: &App, stop: BusStopID) -> Pt2D {
+        let map = &app.primary.map;
+        map.get_bs(stop).sidewalk_pos.pt(map)
+    }
+
+    // Put a new stop wherever it makes the smallest detour.
+    fn add_stop(&mut self, app: &App, stop: BusStopID) {
+        let map = &app.primary.map;
+        let pt = |bs: BusStopID| map.get_bs(bs).sidewalk_pos.pt(map);
+        let new_pt = pt(stop);
+        let idx = (0..self.stops.len())
+            .min_by_key(|idx| {
+                let a = pt(self.stops[*idx]);
+                let b = pt(self.stops[(*idx + 1) % self.stops.len()]);
+                a.dist_to(new_pt) + new_pt.dist_to(b) - a.dist_to(b)
+            })
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        self.stops.insert(idx, stop);
+    }
+}
+
+impl State for BusRouteEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if ctx.redo_mouseover() {
+            self.hovering = None;
+            self.hovering_leg = None;
+            if let Some(pt) = ctx.canvas.get_cursor_in_map_space() {
+                let map = &app.primary.map;
+                self.hovering = map
+                    .all_bus_stops()
+                    .keys()
+                    .find(|bs| map.get_bs(**bs).sidewalk_pos.pt(map).dist_to(pt) < STOP_RADIUS)
+                    .cloned();
+                if self.hovering.is_none() {
+                    self.hovering_leg = self
+                        .legs
+                        .iter()
+                        .position(|poly| poly.as_ref().map_or(false, |p| p.contains_pt(pt)));
+                }
+            }
+        }
+
+        if let Some(idx) = self.dragging_leg {
+            if ctx.input.left_mouse_button_released() {
+                self.dragging_leg = None;
+                // Reroute this leg through the stop it was dropped on
+                if let Some(bs) = self.hovering {
+                    if !self.stops.contains(&bs) {
+                        self.stops.insert(idx + 1, bs);
+                    }
+                }
+                self.rebuild(ctx, app);
+            }
+            return Transition::Keep;
+        }
+        if self.hovering.is_none()
+            && self.hovering_leg.is_some()
+            && ctx.input.left_mouse_button_pressed()
+        {
+            self.dragging_leg = self.hovering_leg;
+            return Transition::Keep;
+        }
+        ctx.canvas_movement();
+
+        if let Some(bs) = self.hovering {
+            if let Some(idx) = self.stops.iter().position(|x| *x == bs) {
+                if app.per_obj.left_click(ctx, "remove stop") {
+                    self.stops.remove(idx);
+                    self.rebuild(ctx, app);
+                }
+            } else if app.per_obj.left_click(ctx, "add stop") {
+                self.add_stop(app, bs);
+                self.rebuild(ctx, app);
+            }
+        }
+
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "Apply" => {
+                    let new = EditBusRoute {
+                        stops: self.stops.clone(),
+                        headway: if self.headway == 0 {
+                            None
+                        } else {
+                            Some(Duration::minutes(self.headway))
+                        },
+                    };
+                    let old = app.primary.map.get_br_edit(self.id);
+                    if new != old {
+                        let mut edits = app.primary.map.get_edits().clone();
+                        edits.commands.push(EditCmd::ChangeBusRoute {
+                            id: self.id,
+                            new,
+                            old,
+                        });
+                        apply_map_edits(ctx, app, edits);
+                    }
+                    return Transition::Pop;
+                }
+                "Cancel" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            },
+            None => {
+                self.headway = self.composite.spinner("headway");
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        let map = &app.primary.map;
+        g.redraw(&self.draw_route);
+
+        let mut batch = GeomBatch::new();
+        if let Some(idx) = self.dragging_leg {
+            // Preview the detour through the cursor
+            if let Some(cursor) = g.canvas.get_cursor_in_map_space() {
+                let stop1 = self.stop_pt(app, self.stops[idx]);
+                let stop2 = self.stop_pt(app, self.stops[(idx + 1) % self.stops.len()]);
+                for (pt1, pt2) in vec![(stop1, cursor), (cursor, stop2)] {
+                    if let Some(l) = geom::Line::maybe_new(pt1, pt2) {
+                        batch.push(app.cs.hovering, l.make_polygons(Distance::meters(2.0)));
+                    }
+                }
+            }
+        } else if let Some(idx) = self.hovering_leg {
+            batch.push(app.cs.hovering, self.legs[idx].clone().unwrap());
+        }
+        for bs in map.all_bus_stops().keys() {
+            let color = if Some(*bs) == self.hovering {
+                app.cs.hovering
+            } else if self.stops.contains(bs) {
+                app.cs.unzoomed_bus
+            } else {
+                app.cs.unzoomed_bus.alpha(0.3)
+            };
+            batch.push(
+                color,
+                Circle::new(map.get_bs(*bs).sidewalk_pos.pt(map), STOP_RADIUS).to_polygon(),
+            );
+        }
+        batch.draw(g);
+
+        self.composite.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+const STOP_RADIUS: Distance = Distance::const_meters(5.0);
@@ -97,4 +97,8 @@ impl State for ClusterTrafficSignalEditor {
 
         self.composite.draw(g);
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Signal cluster".to_string())
+    }
 }
@@ -6,11 +6,14 @@ use crate::helpers::ID;
 use crate::render::Renderable;
 use crate::sandbox::GameplayMode;
 use ezgui::{
-    hotkey, Btn, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Key, Outcome,
+    hotkey, Btn, Choice, Color, Composite, EventCtx, GfxCtx, HorizontalAlignment, Key, Outcome,
     RewriteColor, TextExt, VerticalAlignment, Widget,
 };
-use map_model::{EditCmd, LaneID, LaneType, Map};
-use std::collections::BTreeSet;
+use map_model::{
+    can_change_lane_type, CurbRegulation, CurbRule, CurbUse, EditCmd, LaneID, LaneType, Map,
+    TruckAccess,
+};
+use std::collections::BTreeMap;
 
 pub struct LaneEditor {
     l: LaneID,
@@ -41,6 +44,12 @@ impl LaneEditor {
                 Key::T,
                 lt != LaneType::Bus,
             ),
+            (
+                "hov",
+                "convert to a carpool (HOV) lane",
+                Key::V,
+                lt != LaneType::HOV,
+            ),
             (
                 "parking",
                 "convert to an on-street parking lane",
@@ -80,6 +89,19 @@ impl LaneEditor {
                 .centered_horiz(),
             Widget::row(row).centered().margin_below(5),
             change_speed_limit(ctx, parent.speed_limit).margin_below(5),
+            if parent.access_restrictions.pedestrian_only {
+                Btn::text_fg("reopen to vehicles").build_def(ctx, None)
+            } else {
+                Btn::text_fg("pedestrianize this street").build_def(ctx, None)
+            }
+            .centered_horiz()
+            .margin_below(5),
+            change_truck_access(ctx, parent.access_restrictions.trucks).margin_below(5),
+            if lt == LaneType::Parking {
+                change_curb_regulation(ctx, &app.primary.map.get_curb_regulation(l)).margin_below(5)
+            } else {
+                Widget::nothing()
+            },
             Widget::row(vec![
                 Btn::text_fg("Finish").build_def(ctx, hotkey(Key::Escape)),
                 // TODO Handle reverting speed limit too...
@@ -140,6 +162,9 @@ impl State for LaneEditor {
                     "convert to a bus-only lane" => {
                         try_change_lane_type(self.l, LaneType::Bus, map)
                     }
+                    "convert to a carpool (HOV) lane" => {
+                        try_change_lane_type(self.l, LaneType::HOV, map)
+                    }
                     "convert to an on-street parking lane" => {
                         try_change_lane_type(self.l, LaneType::Parking, map)
                     }
@@ -147,6 +172,9 @@ impl State for LaneEditor {
                         try_change_lane_type(self.l, LaneType::Construction, map)
                     }
                     "reverse lane direction" => try_reverse(self.l, map),
+                    "pedestrianize this street" | "reopen to vehicles" => {
+                        Ok(toggle_pedestrian_only(self.l, map))
+                    }
                     "Finish" => {
                         return Transition::Pop;
                     }
@@ -196,6 +224,46 @@ impl State for LaneEditor {
                         self.mode.clone(),
                     )));
                 }
+
+                let new_trucks = self.composite.dropdown_value("trucks");
+                if new_trucks != parent.access_restrictions.trucks {
+                    let old = parent.access_restrictions.clone();
+                    let mut new = old.clone();
+                    new.trucks = new_trucks;
+                    let mut edits = app.primary.map.get_edits().clone();
+                    edits.commands.push(EditCmd::ChangeAccessRestrictions {
+                        id: parent.id,
+                        new,
+                        old,
+                    });
+                    apply_map_edits(ctx, app, edits);
+                    return Transition::Replace(Box::new(LaneEditor::new(
+                        ctx,
+                        app,
+                        self.l,
+                        self.mode.clone(),
+                    )));
+                }
+
+                if app.primary.map.get_l(self.l).lane_type == LaneType::Parking {
+                    let old = app.primary.map.get_curb_regulation(self.l);
+                    let new = read_curb_regulation(&self.composite, &old);
+                    if new != old {
+                        let mut edits = app.primary.map.get_edits().clone();
+                        edits.commands.push(EditCmd::ChangeCurbRegulation {
+                            l: self.l,
+                            new,
+                            old,
+                        });
+                        apply_map_edits(ctx, app, edits);
+                        return Transition::Replace(Box::new(LaneEditor::new(
+                            ctx,
+                            app,
+                            self.l,
+                            self.mode.clone(),
+                        )));
+                    }
+                }
             }
         }
 
@@ -213,63 +281,14 @@ impl State for LaneEditor {
         self.composite.draw(g);
         CommonState::draw_osd(g, app);
     }
-}
 
-fn can_change_lane_type(l: LaneID, new_lt: LaneType, map: &Map) -> Option<String> {
-    let r = map.get_parent(l);
-    let (fwds, idx) = r.dir_and_offset(l);
-    let (mut proposed_lts, other_side) = if fwds {
-        (r.get_lane_types().0, r.get_lane_types().1)
-    } else {
-        (r.get_lane_types().1, r.get_lane_types().0)
-    };
-    proposed_lts[idx] = new_lt;
-
-    // No-op change
-    if map.get_l(l).lane_type == new_lt {
-        return None;
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Lane".to_string())
     }
-
-    // Only one parking lane per side.
-    if proposed_lts
-        .iter()
-        .filter(|lt| **lt == LaneType::Parking)
-        .count()
-        > 1
-    {
-        // TODO Actually, we just don't want two adjacent parking lanes
-        // (What about dppd though?)
-        return Some(format!(
-            "You can only have one parking lane on the same side of the road"
-        ));
-    }
-
-    // Don't let players orphan a bus stop.
-    if !r.all_bus_stops(map).is_empty()
-        && !proposed_lts
-            .iter()
-            .any(|lt| *lt == LaneType::Driving || *lt == LaneType::Bus)
-    {
-        return Some(format!("You need a driving or bus lane for the bus stop!"));
-    }
-
-    let all_types: BTreeSet<LaneType> = other_side
-        .into_iter()
-        .chain(proposed_lts.iter().cloned())
-        .collect();
-
-    // A parking lane must have a driving lane somewhere on the road.
-    if all_types.contains(&LaneType::Parking) && !all_types.contains(&LaneType::Driving) {
-        return Some(format!(
-            "A parking lane needs a driving lane somewhere on the same road"
-        ));
-    }
-
-    None
 }
 
 pub fn try_change_lane_type(l: LaneID, new_lt: LaneType, map: &Map) -> Result<EditCmd, String> {
-    if let Some(err) = can_change_lane_type(l, new_lt, map) {
+    if let Some(err) = can_change_lane_type(map, l, new_lt, &BTreeMap::new()) {
         return Err(err);
     }
     Ok(EditCmd::ChangeLaneType {
@@ -294,3 +313,103 @@ fn try_reverse(l: LaneID, map: &Map) -> Result<EditCmd, String> {
         })
     }
 }
+
+fn toggle_pedestrian_only(l: LaneID, map: &Map) -> EditCmd {
+    let r = map.get_parent(l);
+    let old = r.access_restrictions.clone();
+    let mut new = old.clone();
+    new.pedestrian_only = !new.pedestrian_only;
+    EditCmd::ChangeAccessRestrictions { id: r.id, new, old }
+}
+
+fn change_truck_access(ctx: &mut EventCtx, current: TruckAccess) -> Widget {
+    Widget::row(vec![
+        "Trucks:".draw_text(ctx).centered_vert().margin_right(15),
+        Widget::dropdown(
+            ctx,
+            "trucks",
+            current,
+            vec![
+                Choice::new("allowed", TruckAccess::Allowed),
+                Choice::new("banned", TruckAccess::Banned),
+                Choice::new("designated truck route", TruckAccess::DesignatedRoute),
+                Choice::new("local deliveries only", TruckAccess::Destination),
+            ],
+        ),
+    ])
+}
+
+// Only the first time-of-day rule can be edited here.
+fn change_curb_regulation(ctx: &mut EventCtx, current: &CurbRegulation) -> Widget {
+    let rule = current.rules.get(0);
+    let hours: Vec<Choice<usize>> = (0..24)
+        .map(|hr| Choice::new(format!("{}:00", hr), hr))
+        .collect();
+    let mut rule_choices = vec![Choice::new("never", None)];
+    for usage in CurbUse::all() {
+        rule_choices.push(Choice::new(usage.to_string(), Some(usage)));
+    }
+
+    Widget::col(vec![
+        Widget::row(vec![
+            "Curb:".draw_text(ctx).centered_vert().margin_right(15),
+            Widget::dropdown(
+                ctx,
+                "curb use",
+                current.default,
+                CurbUse::all()
+                    .into_iter()
+                    .map(|usage| Choice::new(usage.to_string(), usage))
+                    .collect(),
+            ),
+        ])
+        .margin_below(5),
+        Widget::row(vec![
+            "Except:".draw_text(ctx).centered_vert().margin_right(15),
+            Widget::dropdown(ctx, "curb rule use", rule.map(|r| r.usage), rule_choices)
+                .margin_right(10),
+            "from".draw_text(ctx).centered_vert().margin_right(10),
+            Widget::dropdown(
+                ctx,
+                "curb rule start",
+                rule.map(|r| r.start_hour).unwrap_or(7),
+                hours.clone(),
+            )
+            .margin_right(10),
+            "until".draw_text(ctx).centered_vert().margin_right(10),
+            Widget::dropdown(
+                ctx,
+                "curb rule end",
+                rule.map(|r| r.end_hour).unwrap_or(9),
+                hours,
+            ),
+        ]),
+    ])
+}
+
+fn read_curb_regulation(composite: &Composite, current: &CurbRegulation) -> CurbRegulation {
+    let mut new = current.clone();
+    new.default = composite.dropdown_value("curb use");
+    let rule = composite
+        .dropdown_value::<Option<CurbUse>>("curb rule use")
+        .map(|usage| CurbRule {
+            usage,
+            start_hour: composite.dropdown_value("curb rule start"),
+            end_hour: composite.dropdown_value("curb rule end"),
+        });
+    match rule {
+        Some(rule) => {
+            if new.rules.is_empty() {
+                new.rules.push(rule);
+            } else {
+                new.rules[0] = rule;
+            }
+        }
+        None => {
+            if !new.rules.is_empty() {
+                new.rules.remove(0);
+            }
+        }
+    }
+    new
+}
@@ -1,9 +1,11 @@
 mod bulk;
+mod bus_routes;
 mod cluster_traffic_signals;
 mod lanes;
 mod stop_signs;
 mod traffic_signals;
 
+pub use self::bus_routes::BusRouteEditor;
 pub use self::cluster_traffic_signals::ClusterTrafficSignalEditor;
 pub use self::lanes::LaneEditor;
 pub use self::stop_signs::StopSignEditor;
@@ -22,10 +24,10 @@ use ezgui::{
     Key, Line, Outcome, PersistentSplit, RewriteColor, ScreenRectangle, Text, TextExt,
     VerticalAlignment, Widget, WrappedWizard,
 };
-use geom::Speed;
+use geom::{Speed, UnitSystem};
 use map_model::{
-    connectivity, EditCmd, EditIntersection, IntersectionID, LaneID, LaneType, MapEdits,
-    PathConstraints, PermanentMapEdits,
+    connectivity, BusRouteID, BusStopID, EditCmd, EditIntersection, IntersectionID, LaneID,
+    LaneType, MapEdits, PathConstraints, PermanentMapEdits,
 };
 use sim::DontDrawAgents;
 use std::collections::BTreeSet;
@@ -68,7 +70,7 @@ impl EditMode {
     }
 
     fn quit(&self, ctx: &mut EventCtx, app: &mut App) -> Transition {
-        let old_sim = app.suspended_sim.take().unwrap();
+        let mut old_sim = app.suspended_sim.take().unwrap();
 
         // If nothing changed, short-circuit
         if app.primary.map.get_edits() == &self.orig_edits {
@@ -94,16 +96,33 @@ impl EditMode {
                 app.primary.map.save_edits();
             }
             if app.opts.resume_after_edit {
-                if self.mode.reset_after_edits() {
-                    Transition::PopThenReplaceThenPush(
-                        Box::new(SandboxMode::new(ctx, app, self.mode.clone())),
-                        TimeWarpScreen::new(ctx, app, old_sim.time(), false),
-                    )
-                } else {
+                if !self.mode.reset_after_edits() {
                     app.primary.sim = old_sim;
                     app.primary.dirty_from_edits = true;
-                    Transition::Pop
+                    return Transition::Pop;
+                }
+                // Some edits can be applied without starting over.
+                if app
+                    .primary
+                    .map
+                    .get_edits()
+                    .can_apply_live_after(&self.orig_edits)
+                {
+                    timer.start("update the simulation");
+                    let result = old_sim.handle_live_edits(&app.primary.map);
+                    timer.stop("update the simulation");
+                    if let Err(err) = result {
+                        timer.warn(format!("Restarting the simulation: {}", err));
+                    } else {
+                        app.primary.sim = old_sim;
+                        app.primary.dirty_from_edits = true;
+                        return Transition::Pop;
+                    }
                 }
+                Transition::PopThenReplaceThenPush(
+                    Box::new(SandboxMode::new(ctx, app, self.mode.clone())),
+                    TimeWarpScreen::new(ctx, app, old_sim.time(), false),
+                )
             } else {
                 Transition::PopThenReplace(Box::new(SandboxMode::new(ctx, app, self.mode.clone())))
             }
@@ -147,6 +166,10 @@ impl State for EditMode {
                     app.primary.current_selection = None;
                 }
             } else if let Some(ID::Road(_)) = app.primary.current_selection {
+            } else if let Some(ID::BusStop(_)) = app.primary.current_selection {
+                if !self.mode.can_edit_lanes() {
+                    app.primary.current_selection = None;
+                }
             } else {
                 app.primary.current_selection = None;
             }
@@ -278,6 +301,11 @@ impl State for EditMode {
                     )));
                 }
             }
+            if let Some(ID::BusStop(bs)) = app.primary.current_selection {
+                if app.per_obj.left_click(ctx, "edit bus route") {
+                    return edit_bus_route(ctx, app, bs);
+                }
+            }
         }
 
         match self.tool_panel.event(ctx, app) {
@@ -301,6 +329,10 @@ impl State for EditMode {
         }
         CommonState::draw_osd(g, app);
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Edit map".to_string())
+    }
 }
 
 pub fn save_edits_as(wizard: &mut WrappedWizard, app: &mut App) -> Option<()> {
@@ -502,6 +534,40 @@ pub fn can_edit_lane(mode: &GameplayMode, l: LaneID, app: &App) -> bool {
         && app.primary.map.get_l(l).lane_type != LaneType::SharedLeftTurn
 }
 
+// If several routes serve the stop, asks which one first.
+fn edit_bus_route(ctx: &mut EventCtx, app: &mut App, bs: BusStopID) -> Transition {
+    let routes: Vec<BusRouteID> = app
+        .primary
+        .map
+        .get_routes_serving_stop(bs)
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    if routes.len() == 1 {
+        return Transition::Push(Box::new(BusRouteEditor::new(ctx, app, routes[0])));
+    }
+    Transition::Push(WizardState::new(Box::new(move |wiz, ctx, app| {
+        let (_, id) = wiz.wrap(ctx).choose("Edit which bus route?", || {
+            routes
+                .iter()
+                .map(|r| Choice::new(app.primary.map.get_br(*r).name.clone(), *r))
+                .collect()
+        })?;
+        Some(Transition::Replace(Box::new(BusRouteEditor::new(
+            ctx, app, id,
+        ))))
+    })))
+}
+
+pub fn toggle_block_the_box(ctx: &mut EventCtx, app: &mut App, i: IntersectionID) {
+    let old = app.primary.map.allow_block_the_box(i);
+    let mut edits = app.primary.map.get_edits().clone();
+    edits
+        .commands
+        .push(EditCmd::ChangeBlockTheBox { i, new: !old, old });
+    apply_map_edits(ctx, app, edits);
+}
+
 pub fn close_intersection(
     ctx: &mut EventCtx,
     app: &mut App,
@@ -593,32 +659,30 @@ pub fn check_parking_blackholes(
 }
 
 pub fn change_speed_limit(ctx: &mut EventCtx, default: Speed) -> Widget {
+    let mut choices: Vec<Choice<Speed>> = match UnitSystem::current() {
+        // Don't need anything higher. Though now I kind of miss 3am drives on TX-71...
+        UnitSystem::Imperial => (2..=14)
+            .map(|x| Speed::miles_per_hour(5.0 * (x as f64)))
+            .collect::<Vec<_>>(),
+        UnitSystem::Metric => (1..=11)
+            .map(|x| Speed::km_per_hour(10.0 * (x as f64)))
+            .collect::<Vec<_>>(),
+    }
+    .into_iter()
+    .map(|s| Choice::new(s.to_string(), s))
+    .collect();
+    // The current limit might not be a round number in these units.
+    if !choices.iter().any(|c| c.data == default) {
+        choices.push(Choice::new(default.to_string(), default));
+        choices.sort_by(|a, b| a.data.partial_cmp(&b.data).unwrap());
+    }
+
     Widget::row(vec![
         "Change speed limit:"
             .draw_text(ctx)
             .centered_vert()
             .margin_right(15),
-        Widget::dropdown(
-            ctx,
-            "speed limit",
-            default,
-            vec![
-                Choice::new("10 mph", Speed::miles_per_hour(10.0)),
-                Choice::new("15 mph", Speed::miles_per_hour(15.0)),
-                Choice::new("20 mph", Speed::miles_per_hour(20.0)),
-                Choice::new("25 mph", Speed::miles_per_hour(25.0)),
-                Choice::new("30 mph", Speed::miles_per_hour(30.0)),
-                Choice::new("35 mph", Speed::miles_per_hour(35.0)),
-                Choice::new("40 mph", Speed::miles_per_hour(40.0)),
-                Choice::new("45 mph", Speed::miles_per_hour(45.0)),
-                Choice::new("50 mph", Speed::miles_per_hour(50.0)),
-                Choice::new("55 mph", Speed::miles_per_hour(55.0)),
-                Choice::new("60 mph", Speed::miles_per_hour(60.0)),
-                Choice::new("65 mph", Speed::miles_per_hour(65.0)),
-                Choice::new("70 mph", Speed::miles_per_hour(70.0)),
-                // Don't need anything higher. Though now I kind of miss 3am drives on TX-71...
-            ],
-        ),
+        Widget::dropdown(ctx, "speed limit", default, choices),
     ])
 }
 
@@ -664,10 +728,26 @@ fn make_changelist(ctx: &mut EventCtx, app: &App) -> Composite {
                 "{} speed limits changed",
                 edits.changed_speed_limits.len()
             )),
+            Line(format!(
+                "{} access restrictions changed",
+                edits.changed_access_restrictions.len()
+            )),
+            Line(format!(
+                "{} curb regulations changed",
+                edits.changed_curb_regulations.len()
+            )),
             Line(format!(
                 "{} intersections changed",
                 edits.original_intersections.len()
             )),
+            Line(format!(
+                "{} intersections with box blocking changed",
+                edits.changed_block_the_box.len()
+            )),
+            Line(format!(
+                "{} bus routes changed",
+                edits.original_bus_routes.len()
+            )),
         ])
         .draw(ctx)
         .margin_below(10),
@@ -696,6 +776,10 @@ fn cmd_to_id(cmd: &EditCmd) -> ID {
         EditCmd::ChangeLaneType { id, .. } => ID::Lane(*id),
         EditCmd::ReverseLane { l, .. } => ID::Lane(*l),
         EditCmd::ChangeSpeedLimit { id, .. } => ID::Road(*id),
+        EditCmd::ChangeAccessRestrictions { id, .. } => ID::Road(*id),
+        EditCmd::ChangeCurbRegulation { l, .. } => ID::Lane(*l),
         EditCmd::ChangeIntersection { i, .. } => ID::Intersection(*i),
+        EditCmd::ChangeBlockTheBox { i, .. } => ID::Intersection(*i),
+        EditCmd::ChangeBusRoute { new, .. } => ID::BusStop(new.stops[0]),
     }
 }
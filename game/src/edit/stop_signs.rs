@@ -1,13 +1,13 @@
 use crate::app::App;
 use crate::common::CommonState;
-use crate::edit::{apply_map_edits, close_intersection, TrafficSignalEditor};
+use crate::edit::{apply_map_edits, close_intersection, toggle_block_the_box, TrafficSignalEditor};
 use crate::game::{State, Transition};
 use crate::render::DrawIntersection;
 use crate::sandbox::GameplayMode;
 use abstutil::Timer;
 use ezgui::{
-    hotkey, Btn, Composite, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome,
-    Text, TextExt, VerticalAlignment, Widget,
+    hotkey, Btn, Checkbox, Composite, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Outcome, Text, TextExt, VerticalAlignment, Widget,
 };
 use geom::Polygon;
 use map_model::{
@@ -59,6 +59,12 @@ impl StopSignEditor {
                 },
                 Btn::text_fg("close intersection for construction").build_def(ctx, None),
                 Btn::text_fg("convert to traffic signal").build_def(ctx, None),
+                Checkbox::text(
+                    ctx,
+                    "Vehicles may block the box",
+                    None,
+                    app.primary.map.allow_block_the_box(id),
+                ),
                 Btn::text_fg("Finish").build_def(ctx, hotkey(Key::Escape)),
             ])
             .bg(app.cs.panel_bg)
@@ -166,7 +172,13 @@ impl State for StopSignEditor {
                 }
                 _ => unreachable!(),
             },
-            None => {}
+            None => {
+                if self.composite.is_checked("Vehicles may block the box")
+                    != app.primary.map.allow_block_the_box(self.id)
+                {
+                    toggle_block_the_box(ctx, app, self.id);
+                }
+            }
         }
         Transition::Keep
     }
@@ -204,4 +216,8 @@ impl State for StopSignEditor {
             CommonState::draw_osd(g, app);
         }
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Stop sign".to_string())
+    }
 }
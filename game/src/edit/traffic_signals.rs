@@ -1,6 +1,6 @@
 use crate::app::{App, ShowEverything};
 use crate::common::CommonState;
-use crate::edit::{apply_map_edits, close_intersection, StopSignEditor};
+use crate::edit::{apply_map_edits, close_intersection, toggle_block_the_box, StopSignEditor};
 use crate::game::{msg, DrawBaselayer, State, Transition, WizardState};
 use crate::render::{
     draw_signal_phase, make_signal_diagram, DrawOptions, DrawTurnGroup, BIG_ARROW_THICKNESS,
@@ -9,8 +9,8 @@ use crate::sandbox::{spawn_agents_around, GameplayMode, SpeedControls, TimePanel
 use abstutil::Timer;
 use ezgui::{
     hotkey, lctrl, Btn, Choice, Color, Composite, EventCtx, EventLoopMode, GeomBatch, GfxCtx,
-    HorizontalAlignment, Key, Line, Outcome, RewriteColor, Text, TextExt, VerticalAlignment,
-    Widget,
+    HorizontalAlignment, Key, Line, Outcome, RewriteColor, Text, TextExt, UnitSpinner,
+    VerticalAlignment, Widget,
 };
 use geom::{ArrowCap, Distance, Duration};
 use map_model::{
@@ -25,6 +25,9 @@ pub struct TrafficSignalEditor {
     current_phase: usize,
     composite: Composite,
     pub top_panel: Composite,
+    presets_panel: Composite,
+    // Computed once, since they only depend on the intersection's geometry
+    presets: Vec<(String, ControlTrafficSignal)>,
     mode: GameplayMode,
 
     groups: Vec<DrawTurnGroup>,
@@ -44,11 +47,14 @@ impl TrafficSignalEditor {
         mode: GameplayMode,
     ) -> TrafficSignalEditor {
         app.primary.current_selection = None;
+        let presets = ControlTrafficSignal::get_presets(&app.primary.map, id);
         TrafficSignalEditor {
             i: id,
             current_phase: 0,
             composite: make_signal_diagram(ctx, app, id, 0, true),
             top_panel: make_top_panel(ctx, app, false, false),
+            presets_panel: make_presets_panel(ctx, app, &presets),
+            presets,
             mode,
             groups: DrawTurnGroup::for_i(id, &app.primary.map),
             group_selected: None,
@@ -78,28 +84,23 @@ impl State for TrafficSignalEditor {
 
         ctx.canvas_movement();
 
-        // TODO Buttons for these...
-        if self.current_phase != 0 && ctx.input.new_was_pressed(&hotkey(Key::UpArrow).unwrap()) {
-            self.change_phase(self.current_phase - 1, ctx, app);
-        }
-
-        if self.current_phase != app.primary.map.get_traffic_signal(self.i).phases.len() - 1
-            && ctx.input.new_was_pressed(&hotkey(Key::DownArrow).unwrap())
-        {
-            self.change_phase(self.current_phase + 1, ctx, app);
-        }
-
         match self.composite.event(ctx) {
             Some(Outcome::Clicked(x)) => match x {
                 x if x == "Edit entire signal" => {
                     return Transition::Push(edit_entire_signal(app, self.i, self.mode.clone()));
                 }
+                x if x == "previous phase" => {
+                    self.change_phase(self.current_phase - 1, ctx, app);
+                }
+                x if x == "next phase" => {
+                    self.change_phase(self.current_phase + 1, ctx, app);
+                }
                 x if x.starts_with("change duration of phase ") => {
                     let idx = x["change duration of phase ".len()..]
                         .parse::<usize>()
                         .unwrap()
                         - 1;
-                    return Transition::Push(change_duration(app, self.i, idx));
+                    return Transition::Push(ChangeDuration::new(ctx, app, self.i, idx));
                 }
                 x if x.starts_with("delete phase ") => {
                     let idx = x["delete phase ".len()..].parse::<usize>().unwrap() - 1;
@@ -263,6 +264,27 @@ impl State for TrafficSignalEditor {
             None => {}
         }
 
+        match self.presets_panel.event(ctx) {
+            Some(Outcome::Clicked(x)) => {
+                let new_signal = self
+                    .presets
+                    .iter()
+                    .find(|(name, _)| name == &x)
+                    .unwrap()
+                    .1
+                    .clone();
+                self.command_stack.push(orig_signal.clone());
+                self.redo_stack.clear();
+                self.top_panel = make_top_panel(ctx, app, true, false);
+                change_traffic_signal(new_signal, ctx, app);
+                // Don't use change_phase; it tries to preserve scroll
+                self.current_phase = 0;
+                self.composite = make_signal_diagram(ctx, app, self.i, self.current_phase, true);
+                return Transition::Keep;
+            }
+            None => {}
+        }
+
         Transition::Keep
     }
 
@@ -391,6 +413,7 @@ impl State for TrafficSignalEditor {
 
         self.composite.draw(g);
         self.top_panel.draw(g);
+        self.presets_panel.draw(g);
         if let Some((id, _)) = self.group_selected {
             let osd = if id.crosswalk {
                 Text::from(Line(format!(
@@ -409,6 +432,10 @@ impl State for TrafficSignalEditor {
             CommonState::draw_osd(g, app);
         }
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Traffic signal".to_string())
+    }
 }
 
 pub fn make_top_panel(ctx: &mut EventCtx, app: &App, can_undo: bool, can_redo: bool) -> Composite {
@@ -462,6 +489,33 @@ pub fn make_top_panel(ctx: &mut EventCtx, app: &App, can_undo: bool, can_redo: b
         .build(ctx)
 }
 
+// One click to start over from a standard pattern, then fine-tune from there.
+fn make_presets_panel(
+    ctx: &mut EventCtx,
+    app: &App,
+    presets: &Vec<(String, ControlTrafficSignal)>,
+) -> Composite {
+    let mut col = vec![Line("Presets").small_heading().draw(ctx).margin_below(5)];
+    if presets.is_empty() {
+        col.push("None fit this intersection".draw_text(ctx));
+    }
+    for (name, signal) in presets {
+        col.push(
+            Btn::text_fg(name)
+                .tooltip(Text::from(Line(format!(
+                    "{} phases, {} cycle",
+                    signal.phases.len(),
+                    signal.cycle_length()
+                ))))
+                .build_def(ctx, None)
+                .margin_below(5),
+        );
+    }
+    Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx)
+}
+
 pub fn change_traffic_signal(signal: ControlTrafficSignal, ctx: &mut EventCtx, app: &mut App) {
     let mut edits = app.primary.map.get_edits().clone();
     // TODO Only record one command for the entire session. Otherwise, we can exit this editor and
@@ -511,6 +565,11 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
         let stop_sign = "convert to stop signs";
         let close = "close intersection for construction";
         let offset = "edit signal offset";
+        let block_box = if app.primary.map.allow_block_the_box(i) {
+            "don't let vehicles block the box"
+        } else {
+            "let vehicles block the box"
+        };
         let reset = "reset to default";
 
         let mut choices = vec![use_template];
@@ -521,6 +580,7 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
         if mode.can_edit_stop_signs() {
             choices.push(stop_sign);
             choices.push(close);
+            choices.push(block_box);
         }
         choices.push(offset);
         choices.push(reset);
@@ -593,6 +653,10 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
                     editor.change_phase(editor.current_phase, ctx, app);
                 })))
             }
+            x if x == block_box => {
+                toggle_block_the_box(ctx, app, i);
+                Some(Transition::Pop)
+            }
             x if x == reset => {
                 Some(Transition::PopWithData(Box::new(move |state, ctx, app| {
                     let editor = state.downcast_mut::<TrafficSignalEditor>().unwrap();
@@ -619,32 +683,79 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
     }))
 }
 
-fn change_duration(app: &App, i: IntersectionID, idx: usize) -> Box<dyn State> {
-    let current_duration = app.primary.map.get_traffic_signal(i).phases[idx].duration;
-
-    WizardState::new(Box::new(move |wiz, ctx, _| {
-        let new_duration = wiz.wrap(ctx).input_something(
-            "How long should this phase be (seconds)?",
-            Some(format!("{}", current_duration.inner_seconds() as usize)),
-            Box::new(|line| {
-                line.parse::<usize>()
-                    .ok()
-                    .and_then(|n| if n != 0 { Some(n) } else { None })
-            }),
-        )?;
-        Some(Transition::PopWithData(Box::new(move |state, ctx, app| {
-            let editor = state.downcast_mut::<TrafficSignalEditor>().unwrap();
-            let orig_signal = app.primary.map.get_traffic_signal(editor.i);
-
-            let mut new_signal = orig_signal.clone();
-            new_signal.phases[idx].duration = Duration::seconds(new_duration as f64);
-            editor.command_stack.push(orig_signal.clone());
-            editor.redo_stack.clear();
-            editor.top_panel = make_top_panel(ctx, app, true, false);
-            change_traffic_signal(new_signal, ctx, app);
-            editor.change_phase(idx, ctx, app);
-        })))
-    }))
+struct ChangeDuration {
+    composite: Composite,
+    idx: usize,
+}
+
+impl ChangeDuration {
+    fn new(ctx: &mut EventCtx, app: &App, i: IntersectionID, idx: usize) -> Box<dyn State> {
+        let current = app.primary.map.get_traffic_signal(i).phases[idx].duration;
+        Box::new(ChangeDuration {
+            composite: Composite::new(
+                Widget::col(vec![
+                    Widget::row(vec![
+                        Line(format!("Phase {} duration", idx + 1))
+                            .small_heading()
+                            .draw(ctx),
+                        Btn::text_fg("X")
+                            .build(ctx, "close", hotkey(Key::Escape))
+                            .align_right(),
+                    ]),
+                    UnitSpinner::new(
+                        ctx,
+                        (Duration::seconds(1.0), Duration::minutes(5)),
+                        Duration::seconds(1.0),
+                        current,
+                    )
+                    .named("duration")
+                    .margin_below(10),
+                    Btn::text_bg2("Apply").build_def(ctx, hotkey(Key::Enter)),
+                ])
+                .bg(app.cs.panel_bg)
+                .padding(10),
+            )
+            .build(ctx),
+            idx,
+        })
+    }
+}
+
+impl State for ChangeDuration {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => Transition::Pop,
+                "Apply" => {
+                    let new_duration: Duration = self.composite.unit_spinner("duration");
+                    let idx = self.idx;
+                    Transition::PopWithData(Box::new(move |state, ctx, app| {
+                        let editor = state.downcast_mut::<TrafficSignalEditor>().unwrap();
+                        let orig_signal = app.primary.map.get_traffic_signal(editor.i);
+
+                        let mut new_signal = orig_signal.clone();
+                        new_signal.phases[idx].duration = new_duration;
+                        editor.command_stack.push(orig_signal.clone());
+                        editor.redo_stack.clear();
+                        editor.top_panel = make_top_panel(ctx, app, true, false);
+                        change_traffic_signal(new_signal, ctx, app);
+                        editor.change_phase(idx, ctx, app);
+                    }))
+                }
+                _ => unreachable!(),
+            },
+            None => Transition::Keep,
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        State::grey_out_map(g, app);
+        self.composite.draw(g);
+    }
 }
 
 fn check_for_missing_groups(
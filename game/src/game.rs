@@ -1,16 +1,20 @@
 use crate::app::{App, Flags, ShowEverything};
+use crate::common::Permalink;
+use crate::devtools::reload_map;
 use crate::options::Options;
 use crate::pregame::TitleScreen;
 use crate::render::DrawOptions;
 use crate::sandbox::{GameplayMode, SandboxMode};
-use ezgui::{Canvas, Drawable, EventCtx, EventLoopMode, GfxCtx, Wizard, GUI};
+use ezgui::{
+    Canvas, Drawable, EventCtx, EventLoopMode, GfxCtx, Modal, ModalDialog, ModalStack, Wizard, GUI,
+};
 use geom::Polygon;
 
 // This is the top-level of the GUI logic. This module should just manage interactions between the
 // top-level game states.
 pub struct Game {
     // A stack of states
-    states: Vec<Box<dyn State>>,
+    states: ModalStack<Box<dyn State>>,
     app: App,
 }
 
@@ -20,12 +24,15 @@ impl Game {
         opts: Options,
         start_with_edits: Option<String>,
         maybe_mode: Option<GameplayMode>,
+        goto: Option<Permalink>,
         ctx: &mut EventCtx,
     ) -> Game {
         let title = !opts.dev
             && !flags.sim_flags.load.contains("data/player/save")
             && !flags.sim_flags.load.contains("data/system/scenarios")
-            && maybe_mode.is_none();
+            && maybe_mode.is_none()
+            && goto.is_none();
+        opts.units.set();
         let mut app = App::new(flags, opts, ctx, title);
 
         // Handle savestates
@@ -56,7 +63,7 @@ impl Game {
             app.primary.clear_sim();
         }
 
-        let states: Vec<Box<dyn State>> = if title {
+        let mut states: Vec<Box<dyn State>> = if title {
             vec![Box::new(TitleScreen::new(ctx, &app))]
         } else {
             // TODO We're assuming we never wind up starting freeform mode with a synthetic map
@@ -70,7 +77,15 @@ impl Game {
             // PlayScenario without clobbering.
             app.primary.sim = ss;
         }
-        Game { states, app }
+        if let Some(link) = goto {
+            if let Some(state) = link.jump(ctx, &mut app) {
+                states.push(state);
+            }
+        }
+        Game {
+            states: ModalStack::new(states),
+            app,
+        }
     }
 }
 
@@ -78,7 +93,28 @@ impl GUI for Game {
     fn event(&mut self, ctx: &mut EventCtx) -> EventLoopMode {
         self.app.per_obj.reset();
 
-        let transition = self.states.last_mut().unwrap().event(ctx, &mut self.app);
+        if self
+            .app
+            .map_watcher
+            .as_mut()
+            .map(|w| w.changed())
+            .unwrap_or(false)
+        {
+            match reload_map(ctx, &mut self.app) {
+                Ok(states) => {
+                    self.states = ModalStack::new(states);
+                }
+                Err(err) => {
+                    self.states.push(msg(
+                        "Couldn't reload map",
+                        vec![format!("Keeping the old map: {}", err)],
+                    ));
+                }
+            }
+            return ctx.no_op_event(true, |ctx| self.event(ctx));
+        }
+
+        let transition = self.states.top_mut().event(ctx, &mut self.app);
         // If we fall through, there's a new state that we need to wakeup.
         match transition {
             Transition::Keep => {
@@ -89,59 +125,59 @@ impl GUI for Game {
             }
             Transition::KeepWithMouseover => {}
             Transition::Pop => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 if self.states.is_empty() {
                     self.before_quit(ctx.canvas);
                     std::process::exit(0);
                 }
             }
             Transition::PopWithData(cb) => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
-                cb(self.states.last_mut().unwrap(), ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
+                cb(self.states.top_mut(), ctx, &mut self.app);
             }
             Transition::ReplaceWithData(cb) => {
-                let mut last = self.states.pop().unwrap();
-                last.on_destroy(ctx, &mut self.app);
-                let new_states = cb(last, ctx, &mut self.app);
-                self.states.extend(new_states);
+                let last = self.states.pop(ctx, &mut self.app).unwrap();
+                for state in cb(last, ctx, &mut self.app) {
+                    self.states.push(state);
+                }
             }
             Transition::KeepWithData(cb) => {
-                cb(self.states.last_mut().unwrap(), ctx, &mut self.app);
+                cb(self.states.top_mut(), ctx, &mut self.app);
             }
             Transition::PopTwice => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
             }
             Transition::Push(state) => {
                 self.states.push(state);
             }
             Transition::Replace(state) => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 self.states.push(state);
             }
             Transition::ReplaceThenPush(state1, state2) => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 self.states.push(state1);
                 self.states.push(state2);
             }
             Transition::PopThenReplace(state) => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 assert!(!self.states.is_empty());
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 self.states.push(state);
             }
             Transition::PopThenReplaceThenPush(state1, state2) => {
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 assert!(!self.states.is_empty());
-                self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.pop(ctx, &mut self.app);
                 self.states.push(state1);
                 self.states.push(state2);
             }
             Transition::Clear(states) => {
-                while !self.states.is_empty() {
-                    self.states.pop().unwrap().on_destroy(ctx, &mut self.app);
+                self.states.clear(ctx, &mut self.app);
+                for state in states {
+                    self.states.push(state);
                 }
-                self.states.extend(states);
             }
             Transition::PushTwice(s1, s2) => {
                 self.states.push(s1);
@@ -155,7 +191,12 @@ impl GUI for Game {
     }
 
     fn draw(&self, g: &mut GfxCtx) {
-        let state = self.states.last().unwrap();
+        if let Some(path) = self.app.pending_layer_export.take() {
+            crate::layer::draw_for_export(g, &self.app, path);
+            return;
+        }
+
+        let state = self.states.top();
 
         match state.draw_baselayer() {
             DrawBaselayer::DefaultMap => {
@@ -168,7 +209,8 @@ impl GUI for Game {
             }
             DrawBaselayer::Custom => {}
             DrawBaselayer::PreviousState => {
-                match self.states[self.states.len() - 2].draw_baselayer() {
+                let previous = self.states.previous().unwrap();
+                match previous.draw_baselayer() {
                     DrawBaselayer::DefaultMap => {
                         self.app.draw(
                             g,
@@ -182,10 +224,16 @@ impl GUI for Game {
                     DrawBaselayer::PreviousState => {}
                 }
 
-                self.states[self.states.len() - 2].draw(g, &self.app);
+                previous.draw(g, &self.app);
             }
         }
         state.draw(g, &self.app);
+
+        self.states.draw_breadcrumbs::<App>(g);
+
+        if let Some(path) = self.app.pending_screenshot.take() {
+            g.screenshot_current_frame(path);
+        }
     }
 
     fn dump_before_abort(&self, canvas: &Canvas) {
@@ -220,6 +268,11 @@ pub trait State: downcast_rs::Downcast {
 
     // Before this state is popped or replaced, call this.
     fn on_destroy(&mut self, _: &mut EventCtx, _: &mut App) {}
+
+    // Major modes name themselves, so nested ones can show where you are.
+    fn breadcrumb(&self) -> Option<String> {
+        None
+    }
     // We don't need an on_enter -- the constructor for the state can just do it.
 }
 
@@ -238,6 +291,16 @@ impl dyn State {
 
 downcast_rs::impl_downcast!(State);
 
+impl Modal<App> for Box<dyn State> {
+    fn breadcrumb(&self) -> Option<String> {
+        State::breadcrumb(self.as_ref())
+    }
+
+    fn on_destroy(&mut self, ctx: &mut EventCtx, app: &mut App) {
+        State::on_destroy(self.as_mut(), ctx, app);
+    }
+}
+
 pub enum Transition {
     Keep,
     KeepWithMode(EventLoopMode),
@@ -319,9 +382,38 @@ impl State for WizardState {
 
 // TODO Word wrap
 pub fn msg<S: Into<String>>(title: &'static str, lines: Vec<S>) -> Box<dyn State> {
-    let str_lines: Vec<String> = lines.into_iter().map(|l| l.into()).collect();
-    WizardState::new(Box::new(move |wiz, ctx, _| {
-        wiz.wrap(ctx).acknowledge(title, || str_lines.clone())?;
-        Some(Transition::Pop)
-    }))
+    Box::new(PopupMsg {
+        title,
+        lines: lines.into_iter().map(|l| l.into()).collect(),
+        dialog: None,
+    })
+}
+
+struct PopupMsg {
+    title: &'static str,
+    lines: Vec<String>,
+    // Built on the first event, since msg() doesn't have an EventCtx
+    dialog: Option<ModalDialog>,
+}
+
+impl State for PopupMsg {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        if self.dialog.is_none() {
+            self.dialog = Some(ModalDialog::message(ctx, self.title, self.lines.clone()));
+        }
+        if self.dialog.as_mut().unwrap().event(ctx).is_some() {
+            return Transition::Pop;
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        if let Some(ref dialog) = self.dialog {
+            dialog.draw(g);
+        }
+    }
 }
@@ -1,8 +1,8 @@
 use crate::app::App;
-use crate::info::{header_btns, make_table, make_tabs, Details, Tab};
+use crate::info::{header_btns, make_table, make_tabs, osm_tags, Details, Tab};
 use crate::render::DrawPedestrian;
 use ezgui::{Btn, Color, EventCtx, Line, Text, TextExt, Widget};
-use geom::{Angle, Circle, Distance, Speed, Time};
+use geom::{describe_area, Angle, Circle, Distance, Speed, Time};
 use map_model::{BuildingID, LaneID, Traversable, SIDEWALK_THICKNESS};
 use sim::{DrawPedestrianInput, PedestrianID, PersonID, TripMode, TripResult};
 use std::collections::BTreeMap;
@@ -17,6 +17,7 @@ pub fn info(ctx: &mut EventCtx, app: &App, details: &mut Details, id: BuildingID
     if let Some(ref name) = b.name {
         kv.push(("Name", name.clone()));
     }
+    kv.push(("Area", describe_area(b.polygon.area())));
     if app.opts.dev {
         kv.push(("OSM ID", format!("{}", b.osm_way_id)));
     }
@@ -89,6 +90,8 @@ pub fn info(ctx: &mut EventCtx, app: &App, details: &mut Details, id: BuildingID
         rows.push(txt.draw(ctx))
     }
 
+    rows.extend(osm_tags(ctx, &b.osm_tags));
+
     rows
 }
 
@@ -20,6 +20,28 @@ pub fn stop(ctx: &mut EventCtx, app: &App, details: &mut Details, id: BusStopID)
     ]));
     rows.push(format!("On {}", app.primary.map.get_parent(id.sidewalk).get_name()).draw_text(ctx));
 
+    let amenities = &app.primary.map.get_bs(id).amenities;
+    let describe = |x: Option<bool>| match x {
+        Some(true) => "yes".to_string(),
+        Some(false) => "no".to_string(),
+        None => "unknown".to_string(),
+    };
+    rows.extend(make_table(
+        ctx,
+        vec![
+            ("Shelter".to_string(), describe(amenities.shelter)),
+            ("Bench".to_string(), describe(amenities.bench)),
+            (
+                "Wheelchair accessible".to_string(),
+                describe(amenities.wheelchair),
+            ),
+            (
+                "Level boarding".to_string(),
+                describe(amenities.level_boarding),
+            ),
+        ],
+    ));
+
     let all_arrivals = &sim.get_analytics().bus_arrivals;
     for r in app.primary.map.get_routes_serving_stop(id) {
         let buses = app.primary.sim.status_of_buses(r.id);
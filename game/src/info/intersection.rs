@@ -204,7 +204,7 @@ fn delay_plot(ctx: &EventCtx, app: &App, i: IntersectionID, opts: &DataOptions)
             by_mode.get_mut(mode).unwrap().push((*t, *dt));
         }
     }
-    let series: Vec<Series<Duration>> = by_mode
+    let series: Vec<Series<Time, Duration>> = by_mode
         .into_iter()
         .map(|(mode, pts)| Series {
             label: mode.noun().to_string(),
@@ -1,5 +1,8 @@
 use crate::app::App;
-use crate::info::{header_btns, make_table, make_tabs, throughput, DataOptions, Details, Tab};
+use crate::info::{
+    copy_to_clipboard, header_btns, make_table, make_tabs, osm_tags, throughput, DataOptions,
+    Details, Tab,
+};
 use abstutil::prettyprint_usize;
 use ezgui::{Btn, EventCtx, Line, LinePlot, PlotOptions, Series, Text, TextExt, Widget};
 use map_model::{LaneID, OriginalLane};
@@ -26,9 +29,23 @@ pub fn info(ctx: &EventCtx, app: &App, details: &mut Details, id: LaneID) -> Vec
                 l.number_parking_spots()
             ),
         ));
+        let curb = map.get_curb_regulation(l.id);
+        if !curb.is_default() {
+            kv.push((
+                "Curb",
+                format!(
+                    "{} (now {})",
+                    curb.describe(),
+                    curb.usage_at(app.primary.sim.time())
+                ),
+            ));
+        }
     } else {
         kv.push(("Speed limit", r.speed_limit.to_string()));
     }
+    if r.access_restrictions.pedestrian_only {
+        kv.push(("Access", "pedestrians only (and deliveries)".to_string()));
+    }
 
     kv.push(("Length", l.length().describe_rounded()));
 
@@ -134,27 +151,16 @@ pub fn debug(ctx: &EventCtx, app: &App, details: &mut Details, id: LaneID) -> Ve
         ),
     ]));
 
-    let mut txt = Text::from(Line(""));
-    txt.add(Line("Raw OpenStreetMap data"));
-    rows.push(txt.draw(ctx));
-
-    rows.extend(make_table(ctx, r.osm_tags.clone().into_iter().collect()));
+    rows.extend(osm_tags(ctx, &r.osm_tags));
 
     rows
 }
 
 pub fn copy_orig_lane(app: &App, id: LaneID) {
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        use clipboard::{ClipboardContext, ClipboardProvider};
-
-        let mut cb: ClipboardContext = ClipboardProvider::new().unwrap();
-        cb.set_contents(format!(
-            "{:?}",
-            OriginalLane::to_permanent(id, &app.primary.map)
-        ))
-        .unwrap();
-    }
+    copy_to_clipboard(format!(
+        "{:?}",
+        OriginalLane::to_permanent(id, &app.primary.map)
+    ));
 }
 
 pub fn traffic(
@@ -175,6 +181,18 @@ pub fn traffic(
         "Since midnight: {} agents crossed",
         prettyprint_usize(app.primary.sim.get_analytics().road_thruput.total_for(r.id))
     )));
+    // Vehicle counts undersell lanes carrying buses and carpools, so count people too.
+    txt.add(Line(format!(
+        "This lane: {} people in {} vehicles",
+        prettyprint_usize(
+            app.primary
+                .sim
+                .get_analytics()
+                .lane_person_thruput
+                .total_for(id)
+        ),
+        prettyprint_usize(app.primary.sim.get_analytics().lane_thruput.total_for(id))
+    )));
     rows.push(txt.draw(ctx));
 
     rows.push(opts.to_controls(ctx, app).margin_below(10));
@@ -14,7 +14,7 @@ use crate::helpers::{color_for_mode, hotkey_btn, ID};
 use crate::sandbox::{SandboxMode, TimeWarpScreen};
 use ezgui::{
     hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
-    HorizontalAlignment, Key, Line, LinePlot, Outcome, PlotOptions, Series, TextExt,
+    HorizontalAlignment, Key, Line, LinePlot, Outcome, PlotOptions, Series, Text, TextExt,
     VerticalAlignment, Widget,
 };
 use geom::{Circle, Distance, Time};
@@ -82,7 +82,16 @@ impl Tab {
     pub fn from_id(app: &App, id: ID) -> Tab {
         match id {
             ID::Road(_) => unreachable!(),
-            ID::Lane(l) => Tab::LaneInfo(l),
+            ID::Lane(l) => {
+                // Comparing runs, so jump straight to the numbers behind the colors
+                if app.layer.as_ref().and_then(|l| l.name()) == Some("compare runs") {
+                    let mut opts = DataOptions::new();
+                    opts.show_before = true;
+                    Tab::LaneTraffic(l, opts)
+                } else {
+                    Tab::LaneInfo(l)
+                }
+            }
             ID::Intersection(i) => Tab::IntersectionInfo(i),
             ID::Turn(_) => unreachable!(),
             ID::Building(b) => Tab::BldgInfo(b),
@@ -446,6 +455,18 @@ impl InfoPanel {
                         },
                     );
                     return (false, None);
+                } else if action == "copy OpenStreetMap editor link" {
+                    let map = &app.primary.map;
+                    let osm_way_id = match maybe_id {
+                        Some(ID::Lane(l)) => map.get_parent(l).orig_id.osm_way_id,
+                        Some(ID::Building(b)) => map.get_b(b).osm_way_id,
+                        _ => unreachable!(),
+                    };
+                    copy_to_clipboard(format!(
+                        "https://www.openstreetmap.org/edit?editor=id&way={}",
+                        osm_way_id
+                    ));
+                    return (false, None);
                 } else {
                     let mut close_panel = true;
                     let t =
@@ -509,6 +530,36 @@ fn make_table<I: Into<String>>(ctx: &EventCtx, rows: Vec<(I, String)>) -> Vec<Wi
     ])]*/
 }
 
+pub fn copy_to_clipboard(contents: String) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use clipboard::{ClipboardContext, ClipboardProvider};
+
+        let mut cb: ClipboardContext = ClipboardProvider::new().unwrap();
+        cb.set_contents(contents).unwrap();
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        println!("Can't copy to the clipboard on the web yet: {}", contents);
+    }
+}
+
+// The raw tags, so people can spot problems in the source data, plus a way to go fix them
+fn osm_tags(ctx: &EventCtx, tags: &BTreeMap<String, String>) -> Vec<Widget> {
+    let mut rows = vec![Widget::row(vec![
+        Line("Raw OpenStreetMap data").draw(ctx).margin_right(15),
+        Btn::svg_def("../data/system/assets/tools/clipboard.svg")
+            .tooltip(Text::from(Line(
+                "Copy a link to edit this in OpenStreetMap",
+            )))
+            .build(ctx, "copy OpenStreetMap editor link", None)
+            .align_right(),
+    ])
+    .margin_above(10)];
+    rows.extend(make_table(ctx, tags.clone().into_iter().collect()));
+    rows
+}
+
 fn throughput<F: Fn(&Analytics) -> Vec<(TripMode, Vec<(Time, usize)>)>>(
     ctx: &EventCtx,
     app: &App,
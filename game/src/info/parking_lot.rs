@@ -1,7 +1,7 @@
 use crate::app::App;
 use crate::info::{header_btns, make_tabs, Details, Tab};
 use abstutil::prettyprint_usize;
-use ezgui::{EventCtx, Line, LinePlot, PlotOptions, Series, TextExt, Widget};
+use ezgui::{Btn, EventCtx, Line, LinePlot, PlotOptions, Series, TextExt, Widget};
 use map_model::ParkingLotID;
 use std::collections::HashSet;
 
@@ -19,6 +19,12 @@ pub fn info(ctx: &mut EventCtx, app: &App, details: &mut Details, id: ParkingLot
         .draw_text(ctx),
     );
 
+    if let Some(bs) = app.primary.map.park_and_ride_stop(id) {
+        let label = format!("Park-and-ride to {}", bs);
+        details.hyperlinks.insert(label.clone(), Tab::BusStop(bs));
+        rows.push(Btn::text_bg2(label).build_def(ctx, None));
+    }
+
     let mut series = vec![Series {
         label: format!("After \"{}\"", app.primary.map.get_edits().edits_name),
         color: app.cs.after_changes,
@@ -10,8 +10,8 @@ use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use sim::{
-    AgentID, CarID, ParkingSpot, PedestrianID, Person, PersonID, PersonState, TripEndpoint, TripID,
-    TripMode, TripResult, VehicleType,
+    AgeGroup, AgentID, CarID, DemographicGroup, ParkingSpot, PedestrianID, Person, PersonID,
+    PersonState, TripEndpoint, TripID, TripMode, TripResult, VehicleType,
 };
 use std::collections::BTreeMap;
 
@@ -231,9 +231,18 @@ pub fn bio(
     );
 
     let nickname = petname::Petnames::default().generate(&mut rng, 2, " ");
-    let age = rng.gen_range(5, 100);
+    // Stay consistent with the scenario's demographics, if it has any
+    let age = match person.demographics.age {
+        Some(AgeGroup::Child) => rng.gen_range(5, 18),
+        Some(AgeGroup::Adult) => rng.gen_range(18, 65),
+        Some(AgeGroup::Senior) => rng.gen_range(65, 100),
+        None => rng.gen_range(5, 100),
+    };
 
     let mut table = vec![("Nickname", nickname), ("Age", age.to_string())];
+    if let Some(income) = person.demographics.income {
+        table.push(("Income", DemographicGroup::Income(income).to_string()));
+    }
     if app.opts.dev {
         table.push(("Debug ID", format!("{:?}", person.orig_id)));
     }
@@ -279,6 +279,25 @@ pub fn finished(
                 .force_width_pct(ctx, col_width),
             waiting.to_string().draw_text(ctx),
         ]));
+
+        // Parking isn't paid until the car leaves, so this can still grow
+        let cost = app
+            .primary
+            .sim
+            .get_trip_costs()
+            .trip(trip, app.primary.sim.time());
+        col.push(Widget::row(vec![
+            Widget::row(vec![Line("Cost").secondary().draw(ctx)]).force_width_pct(ctx, col_width),
+            Text::from_all(vec![
+                Line(format!("${:.2}", cost.total())),
+                Line(format!(
+                    " (${:.2} fuel, ${:.2} parking, ${:.2} tolls, ${:.2} fares)",
+                    cost.fuel, cost.parking, cost.tolls, cost.fares
+                ))
+                .secondary(),
+            ])
+            .draw(ctx),
+        ]));
     }
 
     col.push(make_timeline(
@@ -0,0 +1,219 @@
+use crate::app::App;
+use crate::common::{ColorLegend, ColorNetwork, DivergingScale};
+use crate::layer::{Layer, LayerOutcome};
+use abstutil::prettyprint_usize;
+use ezgui::{
+    hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
+    HorizontalAlignment, Key, Line, Outcome, Text, TextExt, VerticalAlignment, Widget,
+};
+use map_model::Map;
+use sim::{AccessibilityScores, TripMode, ACCESSIBILITY_TIME_LIMIT};
+
+pub struct Accessibility {
+    mode: TripMode,
+    compare: bool,
+    scores: AccessibilityScores,
+    // Without edits; only calculated if there are edits
+    baseline: Option<AccessibilityScores>,
+    // Where the last CSV export went
+    status: Option<String>,
+
+    unzoomed: Drawable,
+    zoomed: Drawable,
+    composite: Composite,
+}
+
+impl Layer for Accessibility {
+    fn name(&self) -> Option<&'static str> {
+        Some("accessibility")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        self.composite.align_above(ctx, minimap);
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                "export to CSV" => {
+                    self.status = Some(match self.export(app) {
+                        Ok(path) => format!("Wrote {}", path),
+                        Err(err) => format!("Export failed: {}", err),
+                    });
+                    self.rebuild(ctx, app);
+                    self.composite.align_above(ctx, minimap);
+                }
+                x => {
+                    self.mode = AccessibilityScores::modes()
+                        .into_iter()
+                        .find(|m| m.ongoing_verb() == x)
+                        .unwrap();
+                    self.rebuild(ctx, app);
+                    self.composite.align_above(ctx, minimap);
+                }
+            },
+            None => {
+                let new_compare = self.composite.has_widget("Compare before edits")
+                    && self.composite.is_checked("Compare before edits");
+                if new_compare != self.compare {
+                    self.compare = new_compare;
+                    self.rebuild(ctx, app);
+                    self.composite.align_above(ctx, minimap);
+                }
+            }
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl Accessibility {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Accessibility {
+        let (scores, baseline) = ctx.loading_screen("calculate accessibility", |_, timer| {
+            let map = &app.primary.map;
+            let scores = AccessibilityScores::new(map, timer);
+            let baseline = if map.get_edits().commands.is_empty() {
+                None
+            } else {
+                let unedited = Map::new(abstutil::path_map(map.get_name()), timer);
+                Some(AccessibilityScores::new(&unedited, timer))
+            };
+            (scores, baseline)
+        });
+
+        let mut layer = Accessibility {
+            mode: TripMode::Walk,
+            compare: false,
+            scores,
+            baseline,
+            status: None,
+
+            // Filled out below
+            unzoomed: ctx.upload(GeomBatch::new()),
+            zoomed: ctx.upload(GeomBatch::new()),
+            composite: Composite::new(Widget::nothing()).build(ctx),
+        };
+        layer.rebuild(ctx, app);
+        layer
+    }
+
+    fn rebuild(&mut self, ctx: &mut EventCtx, app: &App) {
+        let map = &app.primary.map;
+        let mut colorer = ColorNetwork::new(app);
+        let legend = if let (true, Some(ref baseline)) = (self.compare, &self.baseline) {
+            let scale =
+                DivergingScale::new(Color::hex("#A32015"), Color::WHITE, Color::hex("#5D9630"))
+                    .range(0.0, 2.0)
+                    .ignore(0.9, 1.1);
+            for b in map.all_buildings() {
+                let before = baseline.score(b.id, self.mode) as f64;
+                let after = self.scores.score(b.id, self.mode) as f64;
+                let ratio = if before == 0.0 {
+                    if after == 0.0 {
+                        continue;
+                    }
+                    2.0
+                } else {
+                    after / before
+                };
+                if let Some(c) = scale.eval(ratio) {
+                    colorer.add_b(b.id, c);
+                }
+            }
+            scale.make_legend(ctx, vec!["less reachable", "same", "more"])
+        } else {
+            let max = map
+                .all_buildings()
+                .iter()
+                .map(|b| self.scores.score(b.id, self.mode))
+                .max()
+                .unwrap_or(0)
+                .max(1) as f64;
+            for b in map.all_buildings() {
+                let pct = (self.scores.score(b.id, self.mode) as f64) / max;
+                colorer.add_b(b.id, app.cs.good_to_bad_green.eval(pct));
+            }
+            ColorLegend::gradient(
+                ctx,
+                &app.cs.good_to_bad_green,
+                vec!["0".to_string(), prettyprint_usize(max as usize)],
+            )
+        };
+        let (unzoomed, zoomed) = colorer.build(ctx);
+        self.unzoomed = unzoomed;
+        self.zoomed = zoomed;
+
+        let mut col = vec![
+            Widget::row(vec![
+                Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg").margin_right(10),
+                "Accessibility".draw_text(ctx),
+                Btn::plaintext("X")
+                    .build(ctx, "close", hotkey(Key::Escape))
+                    .align_right(),
+            ]),
+            Text::from(
+                Line(format!(
+                    "How many of the {} amenities are reachable within {}, ignoring traffic",
+                    self.scores.total_destinations, ACCESSIBILITY_TIME_LIMIT
+                ))
+                .secondary(),
+            )
+            .wrap_to_pct(ctx, 15)
+            .draw(ctx),
+            Widget::row(
+                AccessibilityScores::modes()
+                    .into_iter()
+                    .map(|m| {
+                        if m == self.mode {
+                            Btn::text_bg2(m.ongoing_verb()).inactive(ctx)
+                        } else {
+                            Btn::text_bg2(m.ongoing_verb()).build_def(ctx, None)
+                        }
+                        .margin_right(5)
+                    })
+                    .collect(),
+            )
+            .margin_below(5),
+        ];
+        if self.baseline.is_some() {
+            col.push(
+                Checkbox::text(ctx, "Compare before edits", None, self.compare).margin_below(5),
+            );
+        }
+        col.push(legend);
+        col.push(Btn::text_fg("export to CSV").build_def(ctx, None));
+        if let Some(ref status) = self.status {
+            col.push(
+                Text::from(Line(status).secondary())
+                    .wrap_to_pct(ctx, 15)
+                    .draw(ctx),
+            );
+        }
+        self.composite = Composite::new(Widget::col(col).padding(5).bg(app.cs.panel_bg))
+            .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+            .build(ctx);
+    }
+
+    fn export(&self, app: &App) -> Result<String, std::io::Error> {
+        let map = &app.primary.map;
+        let dir = abstutil::path_reports(map.get_name(), &map.get_edits().edits_name);
+        std::fs::create_dir_all(&dir)?;
+        let path = format!("{}/accessibility.csv", dir);
+        self.scores.write_csv(&path, map, self.baseline.as_ref())?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::app::App;
+use crate::common::{ColorNetwork, DivergingScale};
+use crate::layer::{Layer, LayerOutcome};
+use abstutil::Counter;
+use ezgui::{
+    hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GfxCtx, HorizontalAlignment, Key,
+    Outcome, TextExt, VerticalAlignment, Widget,
+};
+use geom::{Duration, Time};
+use map_model::RoadID;
+use std::collections::BTreeSet;
+
+// Shades every road by how much it changed from the baseline run. Clicking a road while this is
+// open shows its throughput over time, before and after.
+pub struct CompareRuns {
+    time: Time,
+    delay: bool,
+    unzoomed: Drawable,
+    zoomed: Drawable,
+    composite: Composite,
+}
+
+impl Layer for CompareRuns {
+    fn name(&self) -> Option<&'static str> {
+        Some("compare runs")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = CompareRuns::new(ctx, app, self.delay);
+        }
+
+        self.composite.align_above(ctx, minimap);
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                _ => unreachable!(),
+            },
+            None => {
+                let new_delay = self.composite.is_checked("Compare delay instead of volume");
+                if new_delay != self.delay {
+                    *self = CompareRuns::new(ctx, app, new_delay);
+                    self.composite.align_above(ctx, minimap);
+                }
+            }
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl CompareRuns {
+    pub fn new(ctx: &mut EventCtx, app: &App, delay: bool) -> CompareRuns {
+        let mut colorer = ColorNetwork::new(app);
+        let scale = DivergingScale::new(Color::hex("#5D9630"), Color::WHITE, Color::hex("#A32015"));
+
+        let legend = if delay {
+            // Relative to the road that changed the most, in either direction
+            let scale = scale.range(-1.0, 1.0).ignore(-0.1, 0.1);
+            let now = app.primary.sim.time();
+            let map = &app.primary.map;
+            let after = app.primary.sim.get_analytics().road_delays(now, map);
+            let before = app.prebaked().road_delays(now, map);
+
+            let mut deltas = Vec::new();
+            let roads: BTreeSet<RoadID> = after.keys().chain(before.keys()).cloned().collect();
+            for r in roads {
+                let dt = after.get(&r).cloned().unwrap_or(Duration::ZERO)
+                    - before.get(&r).cloned().unwrap_or(Duration::ZERO);
+                deltas.push((r, dt.inner_seconds()));
+            }
+            let max = deltas.iter().fold(0.0, |max, (_, dt)| dt.abs().max(max));
+            if max > 0.0 {
+                for (r, dt) in deltas {
+                    if let Some(c) = scale.eval(dt / max) {
+                        colorer.add_r(r, c);
+                    }
+                }
+            }
+            scale.make_legend(ctx, vec!["less delay", "same", "more"])
+        } else {
+            let scale = scale.range(0.0, 2.0).ignore(0.7, 1.3);
+            let hour = app.primary.sim.time().get_hours();
+            let mut after = Counter::new();
+            for ((r, _, _), count) in &app.primary.sim.get_analytics().road_thruput.counts {
+                after.add(*r, *count);
+            }
+            // TODO Same problem as Throughput; the current hour of the baseline is all counted.
+            let mut before = Counter::new();
+            for ((r, _, hr), count) in &app.prebaked().road_thruput.counts {
+                if *hr <= hour {
+                    before.add(*r, *count);
+                }
+            }
+            for (r, before, after) in before.compare(after) {
+                if let Some(c) = scale.eval((after as f64) / (before as f64)) {
+                    colorer.add_r(r, c);
+                }
+            }
+            scale.make_legend(ctx, vec!["less traffic", "same", "more"])
+        };
+
+        let composite = Composite::new(
+            Widget::col(vec![
+                Widget::row(vec![
+                    Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg")
+                        .margin_right(10),
+                    "Compare runs".draw_text(ctx),
+                    Btn::plaintext("X")
+                        .build(ctx, "close", hotkey(Key::Escape))
+                        .align_right(),
+                ]),
+                format!(
+                    "Versus before \"{}\"",
+                    app.primary.map.get_edits().edits_name
+                )
+                .draw_text(ctx),
+                Checkbox::text(ctx, "Compare delay instead of volume", None, delay).margin_below(5),
+                legend,
+                "Click a road to see it over time".draw_text(ctx),
+            ])
+            .padding(5)
+            .bg(app.cs.panel_bg),
+        )
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx);
+        let (unzoomed, zoomed) = colorer.build(ctx);
+
+        CompareRuns {
+            time: app.primary.sim.time(),
+            delay,
+            unzoomed,
+            zoomed,
+            composite,
+        }
+    }
+}
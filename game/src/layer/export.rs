@@ -0,0 +1,101 @@
+use crate::app::{App, ShowEverything};
+use crate::render::DrawOptions;
+use ezgui::{GfxCtx, ScreenPt};
+use geom::LonLat;
+use serde::Serialize;
+
+// Draws just the map and the current layer, without any panels, and saves the frame to path (a
+// .png). Next to it, writes a world file (.pgw) and the image's bounds as GeoJSON, so GIS tools
+// can place the image. Map-space converts linearly to longitude and latitude, so both are exact.
+pub fn draw_for_export(g: &mut GfxCtx, app: &App, path: String) {
+    app.draw(
+        g,
+        DrawOptions::new(),
+        &app.primary.sim,
+        &ShowEverything::new(),
+    );
+    if let Some(ref l) = app.layer {
+        l.draw_minimap(g);
+    }
+    g.screenshot_current_frame(path.clone());
+
+    let gps_bounds = app.primary.map.get_gps_bounds();
+    let (width, height) = (g.canvas.window_width, g.canvas.window_height);
+    let corner = |x, y| {
+        g.canvas
+            .screen_to_map(ScreenPt::new(x, y))
+            .forcibly_to_gps(gps_bounds)
+    };
+    let top_left = corner(0.0, 0.0);
+    let bottom_right = corner(width, height);
+    let base = path.trim_end_matches(".png");
+
+    // Degrees per pixel, then the center of the top-left pixel
+    let lon_per_px = (bottom_right.x() - top_left.x()) / width;
+    let lat_per_px = (bottom_right.y() - top_left.y()) / height;
+    let world_file = format!(
+        "{}\n0.0\n0.0\n{}\n{}\n{}\n",
+        lon_per_px,
+        lat_per_px,
+        top_left.x() + lon_per_px / 2.0,
+        top_left.y() + lat_per_px / 2.0
+    );
+    match std::fs::write(format!("{}.pgw", base), world_file) {
+        Ok(()) => println!("Wrote {}.pgw", base),
+        Err(err) => println!("Couldn't write {}.pgw: {}", base, err),
+    }
+
+    let ring: Vec<Vec<f64>> = vec![
+        top_left,
+        LonLat::new(bottom_right.x(), top_left.y()),
+        bottom_right,
+        LonLat::new(top_left.x(), bottom_right.y()),
+        top_left,
+    ]
+    .into_iter()
+    .map(|gps| vec![gps.x(), gps.y()])
+    .collect();
+    abstutil::write_json(
+        format!("{}.geojson", base),
+        &Feature {
+            feature_type: "Feature",
+            properties: Properties {
+                image: path.rsplit('/').next().unwrap().to_string(),
+                layer: app
+                    .layer
+                    .as_ref()
+                    .and_then(|l| l.name())
+                    .unwrap_or("none")
+                    .to_string(),
+                time: app.primary.sim.time().to_string(),
+            },
+            geometry: Geometry {
+                geometry_type: "Polygon",
+                coordinates: vec![ring],
+            },
+        },
+    );
+}
+
+#[derive(Serialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    feature_type: &'static str,
+    properties: Properties,
+    geometry: Geometry,
+}
+
+#[derive(Serialize)]
+struct Properties {
+    image: String,
+    layer: String,
+    time: String,
+}
+
+#[derive(Serialize)]
+struct Geometry {
+    #[serde(rename = "type")]
+    geometry_type: &'static str,
+    // [ring][point][lon, lat]
+    coordinates: Vec<Vec<Vec<f64>>>,
+}
@@ -0,0 +1,135 @@
+use crate::app::App;
+use crate::common::ColorLegend;
+use crate::layer::{Layer, LayerOutcome};
+use abstutil::{prettyprint_usize, Counter};
+use ezgui::{
+    hotkey, Btn, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Text, TextExt, VerticalAlignment, Widget,
+};
+use geom::{ArrowCap, Distance, Time};
+use map_model::{RoadID, Traversable};
+use sim::{CarStatus, GetDrawAgents};
+
+// Pulses travel this many meters along a road per simulated second
+const PULSE_SPEED: f64 = 5.0;
+const PULSE_LENGTH: Distance = Distance::const_meters(15.0);
+const PULSE_SPACING: Distance = Distance::const_meters(60.0);
+
+// When zoomed out, individual agents are too small to see. Instead, draw arrows moving along each
+// side of every road, thicker and redder the more vehicles are on it right now.
+pub struct Flow {
+    time: Time,
+    unzoomed: Drawable,
+    composite: Composite,
+}
+
+impl Layer for Flow {
+    fn name(&self) -> Option<&'static str> {
+        Some("traffic flow")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = Flow::new(ctx, app);
+        }
+
+        Layer::simple_event(ctx, minimap, &mut self.composite)
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        // Zoomed in, the agents themselves are visible
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl Flow {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Flow {
+        let map = &app.primary.map;
+        let now = app.primary.sim.time();
+
+        // Keyed by road and whether the vehicles are going forwards
+        let mut cnt: Counter<(RoadID, bool)> = Counter::new();
+        for car in app.primary.sim.get_all_draw_cars(map) {
+            if car.status != CarStatus::Moving {
+                continue;
+            }
+            if let Traversable::Lane(l) = car.on {
+                let r = map.get_l(l).parent;
+                cnt.inc((r, map.get_r(r).dir_and_offset(l).0));
+            }
+        }
+        let max = if cnt.borrow().is_empty() {
+            1
+        } else {
+            cnt.max()
+        };
+
+        let mut batch = GeomBatch::new();
+        // All pulses move in step, so the whole map looks like it's flowing
+        let phase =
+            Distance::meters((now.inner_seconds() * PULSE_SPEED) % PULSE_SPACING.inner_meters());
+        for ((r, fwds), count) in cnt.consume() {
+            let pct = (count as f64) / (max as f64);
+            let road = map.get_r(r);
+            let (center, total_width) = road.get_thick_polyline(map).unwrap();
+            let pl = if fwds { center } else { center.reversed() };
+            let pl = map.right_shift(pl, total_width / 4.0).unwrap();
+            let thickness = Distance::meters(1.0 + 4.0 * pct);
+            let color = app.cs.good_to_bad_red.eval(pct);
+
+            let mut start = phase;
+            while start + PULSE_LENGTH <= pl.length() {
+                batch.push(
+                    color,
+                    pl.exact_slice(start, start + PULSE_LENGTH)
+                        .make_arrow(thickness, ArrowCap::Triangle)
+                        .unwrap(),
+                );
+                start += PULSE_SPACING;
+            }
+        }
+
+        let composite = Composite::new(
+            Widget::col(vec![
+                Widget::row(vec![
+                    Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg")
+                        .margin_right(10),
+                    "Traffic flow".draw_text(ctx),
+                    Btn::plaintext("X")
+                        .build(ctx, "close", hotkey(Key::Escape))
+                        .align_right(),
+                ]),
+                Text::from(
+                    Line("Vehicles moving along each side of the road right now. Zoom out to see.")
+                        .secondary(),
+                )
+                .wrap_to_pct(ctx, 15)
+                .draw(ctx),
+                ColorLegend::gradient(
+                    ctx,
+                    &app.cs.good_to_bad_red,
+                    vec!["1".to_string(), prettyprint_usize(max)],
+                ),
+            ])
+            .padding(5)
+            .bg(app.cs.panel_bg),
+        )
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx);
+
+        Flow {
+            time: now,
+            unzoomed: ctx.upload(batch),
+            composite,
+        }
+    }
+}
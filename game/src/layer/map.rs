@@ -2,14 +2,14 @@ use crate::app::App;
 use crate::common::{ColorDiscrete, ColorLegend, ColorNetwork};
 use crate::helpers::amenity_type;
 use crate::layer::{Layer, LayerOutcome};
-use abstutil::Counter;
+use abstutil::{prettyprint_usize, Counter};
 use ezgui::{
     hotkey, Btn, Color, Composite, Drawable, EventCtx, GfxCtx, HorizontalAlignment, Key, Line,
     Text, TextExt, VerticalAlignment, Widget,
 };
-use geom::{Distance, Time};
-use map_model::LaneType;
-use sim::TripMode;
+use geom::{Distance, DistanceGrid, Time};
+use map_model::{LaneType, TruckAccess};
+use sim::{BikeShareKind, TripMode};
 
 pub struct BikeNetwork {
     composite: Composite,
@@ -150,6 +150,128 @@ impl BikeNetwork {
     }
 }
 
+pub struct BikeShare {
+    composite: Composite,
+    time: Time,
+    unzoomed: Drawable,
+    zoomed: Drawable,
+}
+
+impl Layer for BikeShare {
+    fn name(&self) -> Option<&'static str> {
+        Some("bike share")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = BikeShare::new(ctx, app);
+        }
+
+        Layer::simple_event(ctx, minimap, &mut self.composite)
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl BikeShare {
+    // Only call this if the sim has a bike share system.
+    pub fn new(ctx: &mut EventCtx, app: &App) -> BikeShare {
+        let system = app.primary.sim.get_bike_share().unwrap();
+
+        let mut colorer = ColorDiscrete::new(
+            app,
+            vec![
+                ("empty", Color::RED),
+                ("some bikes", Color::GREEN),
+                ("full", Color::BLUE),
+            ],
+        );
+        let mut checkouts = 0;
+        let mut empty_misses = 0;
+        let mut full_misses = 0;
+        for dock in system.get_docks() {
+            checkouts += dock.checkouts;
+            empty_misses += dock.empty_misses;
+            full_misses += dock.full_misses;
+            if dock.bikes == 0 {
+                if dock.capacity.is_some() {
+                    colorer.add_b(dock.bldg, "empty");
+                }
+            } else if dock.fullness() >= 1.0 {
+                colorer.add_b(dock.bldg, "full");
+            } else {
+                colorer.add_b(dock.bldg, "some bikes");
+            }
+        }
+        let (unzoomed, zoomed, legend) = colorer.build(ctx);
+
+        let mut txt = Text::from_multiline(vec![
+            Line(format!(
+                "{} bikes in use",
+                prettyprint_usize(system.num_bikes_in_use())
+            )),
+            Line(format!("{} trips so far", prettyprint_usize(checkouts))),
+            Line(format!(
+                "{} people found no bike",
+                prettyprint_usize(empty_misses)
+            )),
+        ]);
+        if system.kind() == BikeShareKind::Docked {
+            txt.add(Line(format!(
+                "{} people found no free dock",
+                prettyprint_usize(full_misses)
+            )));
+        }
+        txt.add(Line(format!(
+            "{} bikes rebalanced",
+            prettyprint_usize(system.bikes_rebalanced)
+        )));
+
+        let composite = Composite::new(
+            Widget::col(vec![
+                Widget::row(vec![
+                    Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg")
+                        .margin_right(10),
+                    match system.kind() {
+                        BikeShareKind::Docked => "Bike share docks",
+                        BikeShareKind::Dockless => "Dockless bike share",
+                    }
+                    .draw_text(ctx),
+                    Btn::plaintext("X")
+                        .build(ctx, "close", hotkey(Key::Escape))
+                        .align_right(),
+                ]),
+                txt.draw(ctx).margin_below(10),
+                legend,
+            ])
+            .padding(5)
+            .bg(app.cs.panel_bg),
+        )
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx);
+
+        BikeShare {
+            composite,
+            time: app.primary.sim.time(),
+            unzoomed,
+            zoomed,
+        }
+    }
+}
+
 pub struct Static {
     composite: Composite,
     pub unzoomed: Drawable,
@@ -254,13 +376,26 @@ impl Static {
         );
 
         let edits = app.primary.map.get_edits();
-        for l in edits.original_lts.keys().chain(&edits.reversed_lanes) {
+        for l in edits
+            .original_lts
+            .keys()
+            .chain(&edits.reversed_lanes)
+            .chain(&edits.changed_curb_regulations)
+        {
             colorer.add_l(*l, "modified lane/intersection");
         }
-        for i in edits.original_intersections.keys() {
+        for i in edits
+            .original_intersections
+            .keys()
+            .chain(&edits.changed_block_the_box)
+        {
             colorer.add_i(*i, "modified lane/intersection");
         }
-        for r in &edits.changed_speed_limits {
+        for r in edits
+            .changed_speed_limits
+            .iter()
+            .chain(&edits.changed_access_restrictions)
+        {
             colorer.add_r(*r, "modified lane/intersection");
         }
 
@@ -277,10 +412,26 @@ impl Static {
                     "{} speed limits changed",
                     edits.changed_speed_limits.len()
                 )),
+                Line(format!(
+                    "{} access restrictions changed",
+                    edits.changed_access_restrictions.len()
+                )),
+                Line(format!(
+                    "{} curb regulations changed",
+                    edits.changed_curb_regulations.len()
+                )),
                 Line(format!(
                     "{} intersections changed",
                     edits.original_intersections.len()
                 )),
+                Line(format!(
+                    "{} intersections with box blocking changed",
+                    edits.changed_block_the_box.len()
+                )),
+                Line(format!(
+                    "{} bus routes changed",
+                    edits.original_bus_routes.len()
+                )),
             ])
             .draw(ctx),
         )
@@ -327,4 +478,84 @@ impl Static {
             Widget::nothing(),
         )
     }
+
+    // Everywhere more than a short walk from a bus stop. Parks, water, and other places without
+    // roads aren't gaps, since nobody's there to use a bus.
+    pub fn transit_gaps(ctx: &mut EventCtx, app: &App) -> Static {
+        let map = &app.primary.map;
+        let threshold = Distance::meters(400.0);
+        let near_road = Distance::meters(50.0);
+        let resolution = Distance::meters(25.0);
+        let stops = DistanceGrid::from_pts(
+            map.get_bounds(),
+            resolution,
+            map.all_bus_stops()
+                .values()
+                .map(|bs| bs.sidewalk_pos.pt(map))
+                .collect(),
+        );
+        let roads = DistanceGrid::from_polylines(
+            map.get_bounds(),
+            resolution,
+            map.all_roads().iter().map(|r| &r.center_pts).collect(),
+        );
+
+        let mut colorer = ColorDiscrete::new(
+            app,
+            vec![
+                ("bus stops", app.cs.bus_layer),
+                ("far from a stop", Color::RED.alpha(0.5)),
+            ],
+        );
+        for poly in stops.cells_where(|pt, dist| {
+            dist.map(|d| d >= threshold).unwrap_or(true)
+                && roads.dist_at(pt).map(|d| d <= near_road).unwrap_or(false)
+        }) {
+            colorer.add_polygon(poly, "far from a stop");
+        }
+        for bs in map.all_bus_stops().keys() {
+            colorer.add_bs(*bs, "bus stops");
+        }
+
+        Static::new(
+            ctx,
+            app,
+            colorer,
+            "transit gaps",
+            "Transit coverage gaps".to_string(),
+            format!(
+                "Areas near roads, but more than {} from a bus stop",
+                threshold
+            )
+            .draw_text(ctx),
+        )
+    }
+
+    pub fn truck_routes(ctx: &mut EventCtx, app: &App) -> Static {
+        let mut colorer = ColorDiscrete::new(
+            app,
+            vec![
+                ("designated truck route", Color::GREEN),
+                ("trucks banned", Color::RED),
+                ("local deliveries only", Color::ORANGE),
+            ],
+        );
+        for r in app.primary.map.all_roads() {
+            match r.access_restrictions.trucks {
+                TruckAccess::Allowed => {}
+                TruckAccess::Banned => colorer.add_r(r.id, "trucks banned"),
+                TruckAccess::DesignatedRoute => colorer.add_r(r.id, "designated truck route"),
+                TruckAccess::Destination => colorer.add_r(r.id, "local deliveries only"),
+            }
+        }
+
+        Static::new(
+            ctx,
+            app,
+            colorer,
+            "truck routes",
+            "Truck routes".to_string(),
+            Widget::nothing(),
+        )
+    }
 }
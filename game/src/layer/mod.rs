@@ -1,16 +1,24 @@
+mod accessibility;
 pub mod bus;
+mod compare;
 mod elevation;
+mod export;
+mod flow;
 pub mod map;
 mod pandemic;
 mod parking;
 mod population;
+mod time_lapse;
 pub mod traffic;
 
 use crate::app::App;
-use crate::common::HeatmapOptions;
+use crate::common::{HeatmapOptions, HeatmapStyle};
 use crate::game::{DrawBaselayer, State, Transition};
 use crate::helpers::hotkey_btn;
-use ezgui::{hotkey, Btn, Composite, EventCtx, GfxCtx, Key, Line, Outcome, TextExt, Widget};
+pub use export::draw_for_export;
+use ezgui::{
+    hotkey, Btn, Checkbox, Composite, EventCtx, GfxCtx, Key, Line, Outcome, TextExt, Widget,
+};
 
 // TODO Good ideas in
 // https://towardsdatascience.com/top-10-map-types-in-data-visualization-b3a80898ea70
@@ -27,6 +35,11 @@ pub trait Layer {
     fn draw(&self, g: &mut GfxCtx, app: &App);
     // Just draw contents and do it always
     fn draw_minimap(&self, g: &mut GfxCtx);
+    // Heatmaps can be made translucent from the layer picker. Other layers return None.
+    fn heatmap_style(&self) -> Option<HeatmapStyle> {
+        None
+    }
+    fn set_heatmap_style(&mut self, _: &mut EventCtx, _: &App, _: HeatmapStyle) {}
 }
 
 impl dyn Layer {
@@ -66,16 +79,33 @@ impl PickLayer {
         let mut layer = app.layer.take().unwrap();
         match layer.event(ctx, app, minimap) {
             Some(LayerOutcome::Close) => {
-                app.layer = None;
+                // The next layer down takes over
+                app.layer = app.layers_below.pop();
                 return None;
             }
             None => {}
         }
         app.layer = Some(layer);
 
+        // Layers underneath have no panel to interact with, but still keep up with the sim.
+        let mut below = std::mem::take(&mut app.layers_below);
+        for l in &mut below {
+            ctx.no_op_event(false, |ctx| l.event(ctx, app, minimap));
+        }
+        app.layers_below = below;
+
         None
     }
 
+    // Bottom to top
+    fn active_layers(app: &App) -> Vec<&dyn Layer> {
+        app.layers_below
+            .iter()
+            .chain(app.layer.iter())
+            .map(|l| l.as_ref())
+            .collect()
+    }
+
     pub fn pick(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
         let mut col = vec![Widget::row(vec![
             Line("Layers").small_heading().draw(ctx),
@@ -106,6 +136,10 @@ impl PickLayer {
                     btn("delay", Key::D),
                     btn("throughput", Key::T),
                     btn("traffic jams", Key::J),
+                    btn("queue spillback", Key::Q),
+                    btn("safety hotspots", Key::V),
+                    btn("time-lapse", Key::I),
+                    btn("traffic flow", Key::F),
                 ]),
                 Widget::col(vec![
                     "Map".draw_text(ctx).margin_below(10),
@@ -113,6 +147,8 @@ impl PickLayer {
                     btn("parking occupancy", Key::P),
                     btn("bike network", Key::B),
                     btn("bus network", Key::U),
+                    btn("transit gaps", Key::G),
+                    btn("truck routes", Key::K),
                     btn("population map", Key::X),
                 ]),
             ])
@@ -122,12 +158,55 @@ impl PickLayer {
         col.extend(vec![
             "Experimental".draw_text(ctx).margin_below(10),
             btn("amenities", Key::A),
+            btn("accessibility", Key::C),
             btn("backpressure", Key::Z),
             btn("elevation", Key::S),
         ]);
+        if app.has_prebaked().is_some() {
+            col.push(btn("compare runs", Key::R));
+        }
         if app.primary.sim.get_pandemic_model().is_some() {
             col.push(btn("pandemic model", Key::Y));
         }
+        if app.primary.sim.get_bike_share().is_some() {
+            col.push(btn("bike share", Key::H));
+        }
+        if app.layer.is_some() {
+            col.push(
+                "Active layers"
+                    .draw_text(ctx)
+                    .margin_above(10)
+                    .margin_below(10),
+            );
+            for (idx, l) in PickLayer::active_layers(app).into_iter().enumerate() {
+                let mut row = vec![l
+                    .name()
+                    .unwrap_or("custom layer")
+                    .draw_text(ctx)
+                    .centered_vert()
+                    .margin_right(10)];
+                if let Some(style) = l.heatmap_style() {
+                    row.push(style.to_controls(ctx, &format!("layer {}", idx)));
+                }
+                row.push(
+                    Btn::plaintext("X")
+                        .build(ctx, format!("remove layer {}", idx), None)
+                        .align_right(),
+                );
+                col.push(Widget::row(row).margin_below(5));
+            }
+            col.push(Checkbox::text(
+                ctx,
+                "keep the current layers underneath",
+                None,
+                false,
+            ));
+            col.push(
+                Btn::text_fg("export current layer as an image")
+                    .build_def(ctx, None)
+                    .margin_above(10),
+            );
+        }
 
         Box::new(PickLayer {
             composite: Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(16))
@@ -144,61 +223,67 @@ impl State for PickLayer {
                 "close" => {}
                 "None" => {
                     app.layer = None;
+                    app.layers_below.clear();
                 }
-                "parking occupancy" => {
-                    app.layer = Some(Box::new(parking::Occupancy::new(
-                        ctx, app, true, true, true, false,
-                    )));
-                }
-                "delay" => {
-                    app.layer = Some(Box::new(traffic::Delay::new(ctx, app, false)));
-                }
-                "traffic jams" => {
-                    app.layer = Some(Box::new(traffic::TrafficJams::new(ctx, app)));
-                }
-                "throughput" => {
-                    app.layer = Some(Box::new(traffic::Throughput::new(ctx, app, false)));
+                "export current layer as an image" => {
+                    let name = app.layer.as_ref().unwrap().name().unwrap_or("layer");
+                    app.pending_layer_export
+                        .set(Some(abstutil::path_player_screenshot(
+                            app.primary.map.get_name(),
+                            &format!(
+                                "{}_{}",
+                                name.replace(" ", "_"),
+                                app.primary.sim.time().as_filename()
+                            ),
+                        )));
                 }
-                "backpressure" => {
-                    app.layer = Some(Box::new(traffic::Backpressure::new(ctx, app)));
+                "time-lapse" => {
+                    return Transition::Replace(time_lapse::TimeLapse::new(ctx, app));
                 }
-                "bike network" => {
-                    app.layer = Some(Box::new(map::BikeNetwork::new(ctx, app)));
+                x if x.starts_with("remove layer ") => {
+                    let idx = x["remove layer ".len()..].parse::<usize>().unwrap();
+                    if idx == app.layers_below.len() {
+                        app.layer = app.layers_below.pop();
+                    } else {
+                        app.layers_below.remove(idx);
+                    }
+                    return Transition::Replace(PickLayer::pick(ctx, app));
                 }
-                "bus network" => {
-                    app.layer = Some(Box::new(map::Static::bus_network(ctx, app)));
+                name => {
+                    let layer = make_layer(ctx, app, name);
+                    let stack = self
+                        .composite
+                        .has_widget("keep the current layers underneath")
+                        && self
+                            .composite
+                            .is_checked("keep the current layers underneath");
+                    if stack {
+                        app.layers_below.extend(app.layer.take());
+                        // Don't stack two copies of the same layer
+                        app.layers_below
+                            .retain(|l| l.name().is_none() || l.name() != layer.name());
+                    } else {
+                        app.layers_below.clear();
+                    }
+                    app.layer = Some(layer);
                 }
-                "elevation" => {
-                    app.layer = Some(Box::new(elevation::Elevation::new(ctx, app)));
-                }
-                "map edits" => {
-                    app.layer = Some(Box::new(map::Static::edits(ctx, app)));
-                }
-                "amenities" => {
-                    app.layer = Some(Box::new(map::Static::amenities(ctx, app)));
-                }
-                "population map" => {
-                    app.layer = Some(Box::new(population::PopulationMap::new(
-                        ctx,
-                        app,
-                        population::Options {
-                            heatmap: Some(HeatmapOptions::new()),
-                        },
-                    )));
-                }
-                "pandemic model" => {
-                    app.layer = Some(Box::new(pandemic::Pandemic::new(
-                        ctx,
-                        app,
-                        pandemic::Options {
-                            heatmap: Some(HeatmapOptions::new()),
-                            state: pandemic::SEIR::Infected,
-                        },
-                    )));
-                }
-                _ => unreachable!(),
             },
             None => {
+                // Restyle any heatmaps. Same trick as in update, since the layers need App.
+                let mut layers = std::mem::take(&mut app.layers_below);
+                layers.extend(app.layer.take());
+                for (idx, l) in layers.iter_mut().enumerate() {
+                    if let Some(style) = l.heatmap_style() {
+                        let new_style =
+                            HeatmapStyle::from_controls(&self.composite, &format!("layer {}", idx));
+                        if style != new_style {
+                            l.set_heatmap_style(ctx, app, new_style);
+                        }
+                    }
+                }
+                app.layer = layers.pop();
+                app.layers_below = layers;
+
                 if self.composite.clicked_outside(ctx) {
                     return Transition::Pop;
                 }
@@ -217,3 +302,42 @@ impl State for PickLayer {
         self.composite.draw(g);
     }
 }
+
+fn make_layer(ctx: &mut EventCtx, app: &App, name: &str) -> Box<dyn Layer> {
+    match name {
+        "parking occupancy" => Box::new(parking::Occupancy::new(ctx, app, true, true, true, false)),
+        "delay" => Box::new(traffic::Delay::new(ctx, app, false)),
+        "traffic jams" => Box::new(traffic::TrafficJams::new(ctx, app)),
+        "queue spillback" => Box::new(traffic::Spillback::new(ctx, app)),
+        "safety hotspots" => Box::new(traffic::SafetyHotspots::new(ctx, app, false)),
+        "traffic flow" => Box::new(flow::Flow::new(ctx, app)),
+        "throughput" => Box::new(traffic::Throughput::new(ctx, app, false)),
+        "compare runs" => Box::new(compare::CompareRuns::new(ctx, app, false)),
+        "backpressure" => Box::new(traffic::Backpressure::new(ctx, app)),
+        "bike network" => Box::new(map::BikeNetwork::new(ctx, app)),
+        "bus network" => Box::new(map::Static::bus_network(ctx, app)),
+        "truck routes" => Box::new(map::Static::truck_routes(ctx, app)),
+        "bike share" => Box::new(map::BikeShare::new(ctx, app)),
+        "transit gaps" => Box::new(map::Static::transit_gaps(ctx, app)),
+        "elevation" => Box::new(elevation::Elevation::new(ctx, app)),
+        "map edits" => Box::new(map::Static::edits(ctx, app)),
+        "accessibility" => Box::new(accessibility::Accessibility::new(ctx, app)),
+        "amenities" => Box::new(map::Static::amenities(ctx, app)),
+        "population map" => Box::new(population::PopulationMap::new(
+            ctx,
+            app,
+            population::Options {
+                heatmap: Some(HeatmapOptions::new()),
+            },
+        )),
+        "pandemic model" => Box::new(pandemic::Pandemic::new(
+            ctx,
+            app,
+            pandemic::Options {
+                heatmap: Some(HeatmapOptions::new()),
+                state: pandemic::SEIR::Infected,
+            },
+        )),
+        _ => unreachable!(),
+    }
+}
@@ -7,7 +7,7 @@ use ezgui::{
     Outcome, Text, TextExt, VerticalAlignment, Widget,
 };
 use geom::Time;
-use map_model::{BuildingID, Map, ParkingLotID, RoadID};
+use map_model::{BuildingID, CurbUse, Map, ParkingLotID, RoadID};
 use sim::{ParkingSpot, VehicleType};
 use std::collections::HashSet;
 
@@ -155,6 +155,28 @@ impl Occupancy {
             }
         }
 
+        let curb = app.primary.sim.curb_report(&app.primary.map);
+        let mut curb_lines = Vec::new();
+        if onstreet
+            && curb
+                .occupancy
+                .keys()
+                .any(|usage| *usage != CurbUse::Parking)
+        {
+            for (usage, (filled, total)) in &curb.occupancy {
+                curb_lines.push(Line(format!(
+                    "{}: {} / {} spots filled",
+                    usage,
+                    prettyprint_usize(*filled),
+                    prettyprint_usize(*total)
+                )));
+            }
+            curb_lines.push(Line(format!(
+                "{} cars parked in violation of curb regulations",
+                prettyprint_usize(curb.violations.len())
+            )));
+        }
+
         let composite = Composite::new(
             Widget::col(vec![
                 Widget::row(vec![
@@ -187,6 +209,11 @@ impl Occupancy {
                 ])
                 .draw(ctx)
                 .margin_below(10),
+                if curb_lines.is_empty() {
+                    Widget::nothing()
+                } else {
+                    Text::from_multiline(curb_lines).draw(ctx).margin_below(10)
+                },
                 Widget::row(vec![
                     Checkbox::text(ctx, "On-street spots", None, onstreet).margin_right(15),
                     Checkbox::text(ctx, "Parking lots", None, lots),
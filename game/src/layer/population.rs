@@ -1,5 +1,5 @@
 use crate::app::App;
-use crate::common::{make_heatmap, HeatmapOptions};
+use crate::common::{make_heatmap, HeatmapOptions, HeatmapStyle};
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::prettyprint_usize;
 use ezgui::{
@@ -56,11 +56,23 @@ impl Layer for PopulationMap {
     fn draw(&self, g: &mut GfxCtx, app: &App) {
         self.composite.draw(g);
         if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
-            g.redraw(&self.draw);
+            self.redraw(g);
         }
     }
     fn draw_minimap(&self, g: &mut GfxCtx) {
-        g.redraw(&self.draw);
+        self.redraw(g);
+    }
+    fn heatmap_style(&self) -> Option<HeatmapStyle> {
+        self.opts.heatmap.as_ref().map(|o| o.style)
+    }
+    fn set_heatmap_style(&mut self, ctx: &mut EventCtx, app: &App, style: HeatmapStyle) {
+        let mut opts = self.opts.clone();
+        if let Some(ref mut o) = opts.heatmap {
+            o.style = style;
+        }
+        let mut new = PopulationMap::new(ctx, app, opts);
+        new.composite.restore(ctx, &self.composite);
+        *self = new;
     }
 }
 
@@ -123,9 +135,21 @@ impl PopulationMap {
         }
     }
 
+    // The dot map is always drawn normally
+    fn redraw(&self, g: &mut GfxCtx) {
+        if let Some(ref o) = self.opts.heatmap {
+            o.redraw(g, &self.draw);
+        } else {
+            g.redraw(&self.draw);
+        }
+    }
+
     fn options(&self) -> Options {
         let heatmap = if self.composite.is_checked("Show heatmap") {
-            Some(HeatmapOptions::from_controls(&self.composite))
+            Some(HeatmapOptions::from_controls(
+                &self.composite,
+                self.heatmap_style().unwrap_or_else(HeatmapStyle::new),
+            ))
         } else {
             None
         };
@@ -0,0 +1,238 @@
+use crate::app::App;
+use crate::common::{ColorLegend, ColorNetwork};
+use crate::game::{DrawBaselayer, State, Transition};
+use abstutil::{prettyprint_usize, Counter};
+use ezgui::{
+    hotkey, Btn, Composite, Drawable, EventCtx, EventLoopMode, GeomBatch, GfxCtx,
+    HorizontalAlignment, Key, Line, Outcome, Slider, Text, TextExt, VerticalAlignment, Widget,
+};
+use geom::{Duration, Time};
+use map_model::{IntersectionID, RoadID};
+
+// How much of the simulated day plays back per second of real time
+const SPEED: f64 = 3600.0;
+
+// Replays how many people crossed every road and intersection, one hour at a time, to see where
+// congestion builds up first. Uses the hourly throughput already recorded, so it works partway
+// through a run too.
+pub struct TimeLapse {
+    composite: Composite,
+    playing: bool,
+    time: Time,
+    end: Time,
+    // Indexed by hour
+    roads: Vec<Counter<RoadID>>,
+    intersections: Vec<Counter<IntersectionID>>,
+    // Across all hours, so colors are comparable over time
+    max_count: usize,
+
+    // What's currently drawn
+    hour: usize,
+    unzoomed: Drawable,
+    zoomed: Drawable,
+}
+
+impl TimeLapse {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        let end = app.primary.sim.time();
+        let num_hours = end.get_hours() + 1;
+        let stats = app.primary.sim.get_analytics();
+
+        let mut roads: Vec<Counter<RoadID>> = (0..num_hours).map(|_| Counter::new()).collect();
+        for ((r, _, hour), count) in &stats.road_thruput.counts {
+            if *hour < num_hours {
+                roads[*hour].add(*r, *count);
+            }
+        }
+        let mut intersections: Vec<Counter<IntersectionID>> =
+            (0..num_hours).map(|_| Counter::new()).collect();
+        for ((i, _, hour), count) in &stats.intersection_thruput.counts {
+            if *hour < num_hours {
+                intersections[*hour].add(*i, *count);
+            }
+        }
+        let max_count = roads
+            .iter()
+            .map(|cnt| cnt.max())
+            .chain(intersections.iter().map(|cnt| cnt.max()))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut time_lapse = TimeLapse {
+            composite: make_panel(ctx, app, false, max_count),
+            playing: false,
+            time: Time::START_OF_DAY,
+            end,
+            roads,
+            intersections,
+            max_count,
+            hour: 0,
+            unzoomed: ctx.upload(GeomBatch::new()),
+            zoomed: ctx.upload(GeomBatch::new()),
+        };
+        time_lapse.redraw(ctx, app);
+        Box::new(time_lapse)
+    }
+
+    fn redraw(&mut self, ctx: &mut EventCtx, app: &App) {
+        self.hour = self.time.get_hours().min(self.roads.len() - 1);
+
+        let mut colorer = ColorNetwork::new(app);
+        let max = self.max_count as f64;
+        for (r, count) in self.roads[self.hour].borrow() {
+            colorer.add_r(*r, app.cs.good_to_bad_red.eval((*count as f64) / max));
+        }
+        for (i, count) in self.intersections[self.hour].borrow() {
+            colorer.add_i(*i, app.cs.good_to_bad_red.eval((*count as f64) / max));
+        }
+        let (unzoomed, zoomed) = colorer.build(ctx);
+        self.unzoomed = unzoomed;
+        self.zoomed = zoomed;
+
+        self.composite.replace(
+            ctx,
+            "time",
+            format!(
+                "{} - {}",
+                Time::START_OF_DAY + Duration::hours(self.hour),
+                Time::START_OF_DAY + Duration::hours(self.hour + 1)
+            )
+            .draw_text(ctx)
+            .named("time"),
+        );
+    }
+
+    fn set_time(&mut self, ctx: &mut EventCtx, app: &App, time: Time) {
+        self.time = time;
+        let pct = if self.end == Time::START_OF_DAY {
+            0.0
+        } else {
+            (time - Time::START_OF_DAY) / (self.end - Time::START_OF_DAY)
+        };
+        self.composite.slider_mut("scrub").set_percent(ctx, pct);
+        if time.get_hours() != self.hour {
+            self.redraw(ctx, app);
+        }
+    }
+}
+
+impl State for TimeLapse {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "play" | "pause" => {
+                    self.playing = !self.playing;
+                    // Start over if we're at the end
+                    if self.playing && self.time >= self.end {
+                        self.set_time(ctx, app, Time::START_OF_DAY);
+                    }
+                    self.composite
+                        .replace(ctx, "play", play_btn(ctx, self.playing).named("play"));
+                }
+                _ => unreachable!(),
+            },
+            None => {}
+        }
+
+        // Did the user drag the slider?
+        let pct = self.composite.slider("scrub").get_percent();
+        let scrubbed = Time::START_OF_DAY + (self.end - Time::START_OF_DAY) * pct;
+        let diff = if scrubbed > self.time {
+            scrubbed - self.time
+        } else {
+            self.time - scrubbed
+        };
+        if diff > Duration::seconds(1.0) {
+            self.time = scrubbed;
+            if self.time.get_hours() != self.hour {
+                self.redraw(ctx, app);
+            }
+        }
+
+        if self.playing {
+            if let Some(real_dt) = ctx.input.nonblocking_is_update_event() {
+                ctx.input.use_update_event();
+                let time = self.time + real_dt * SPEED;
+                if time >= self.end {
+                    self.playing = false;
+                    self.composite
+                        .replace(ctx, "play", play_btn(ctx, false).named("play"));
+                    self.set_time(ctx, app, self.end);
+                } else {
+                    self.set_time(ctx, app, time);
+                }
+            }
+            return Transition::KeepWithMode(EventLoopMode::Animation);
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::DefaultMap
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+        self.composite.draw(g);
+    }
+}
+
+fn play_btn(ctx: &EventCtx, playing: bool) -> Widget {
+    if playing {
+        Btn::svg_def("../data/system/assets/speed/pause.svg").build(
+            ctx,
+            "pause",
+            hotkey(Key::Space),
+        )
+    } else {
+        Btn::svg_def("../data/system/assets/speed/triangle.svg").build(
+            ctx,
+            "play",
+            hotkey(Key::Space),
+        )
+    }
+}
+
+fn make_panel(ctx: &mut EventCtx, app: &App, playing: bool, max_count: usize) -> Composite {
+    Composite::new(
+        Widget::col(vec![
+            Widget::row(vec![
+                Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg").margin_right(10),
+                "Time-lapse".draw_text(ctx),
+                Btn::plaintext("X")
+                    .build(ctx, "close", hotkey(Key::Escape))
+                    .align_right(),
+            ]),
+            Text::from(Line("People crossing each road and intersection per hour").secondary())
+                .wrap_to_pct(ctx, 15)
+                .draw(ctx),
+            Widget::row(vec![
+                play_btn(ctx, playing).named("play").margin_right(10),
+                "".draw_text(ctx).named("time").centered_vert(),
+            ])
+            .margin_below(5),
+            Slider::horizontal(ctx, 0.15 * ctx.canvas.window_width, 25.0, 0.0)
+                .named("scrub")
+                .margin_below(5),
+            ColorLegend::gradient(
+                ctx,
+                &app.cs.good_to_bad_red,
+                vec!["0".to_string(), prettyprint_usize(max_count)],
+            ),
+        ])
+        .padding(5)
+        .bg(app.cs.panel_bg),
+    )
+    .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+    .build(ctx)
+}
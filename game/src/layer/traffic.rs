@@ -1,7 +1,7 @@
 use crate::app::App;
 use crate::common::{ColorLegend, ColorNetwork, ColorScale, DivergingScale};
 use crate::layer::{Layer, LayerOutcome};
-use abstutil::Counter;
+use abstutil::{prettyprint_usize, Counter};
 use ezgui::{
     hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
     HorizontalAlignment, Key, Line, Outcome, Text, TextExt, VerticalAlignment, Widget,
@@ -9,6 +9,7 @@ use ezgui::{
 use geom::{Distance, Duration, Polygon, Time};
 use map_model::{IntersectionID, Map, Traversable};
 use maplit::btreeset;
+use sim::SafetyEvent;
 use std::collections::BTreeSet;
 
 pub struct Backpressure {
@@ -106,6 +107,255 @@ impl Backpressure {
     }
 }
 
+pub struct Spillback {
+    time: Time,
+    unzoomed: Drawable,
+    zoomed: Drawable,
+    composite: Composite,
+}
+
+impl Layer for Spillback {
+    fn name(&self) -> Option<&'static str> {
+        Some("queue spillback")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = Spillback::new(ctx, app);
+        }
+
+        Layer::simple_event(ctx, minimap, &mut self.composite)
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl Spillback {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Spillback {
+        let map = &app.primary.map;
+        let mut cnt_per_r = Counter::new();
+        let mut cnt_per_i = Counter::new();
+        let mut last = None;
+        for (t, i, l) in &app.primary.sim.get_analytics().spillbacks {
+            cnt_per_r.inc(map.get_l(*l).parent);
+            cnt_per_i.inc(*i);
+            last = Some((*t, *i));
+        }
+
+        let mut col = vec![
+            Widget::row(vec![
+                Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg").margin_right(10),
+                "Queue spillback".draw_text(ctx),
+                Btn::plaintext("X")
+                    .build(ctx, "close", hotkey(Key::Escape))
+                    .align_right(),
+            ]),
+            Text::from(
+                Line(
+                    "Roads whose queue backed up into the intersection before them, and the \
+                     intersections that got blocked",
+                )
+                .secondary(),
+            )
+            .wrap_to_pct(ctx, 15)
+            .draw(ctx),
+            format!(
+                "{} spillbacks at {} intersections",
+                prettyprint_usize(cnt_per_i.sum()),
+                prettyprint_usize(cnt_per_i.borrow().len())
+            )
+            .draw_text(ctx),
+        ];
+        if let Some((t, i)) = last {
+            col.push(format!("Most recently at {}, {}", t.ampm_tostring(), i).draw_text(ctx));
+        }
+        col.push(ColorLegend::gradient(
+            ctx,
+            &app.cs.good_to_bad_red,
+            vec!["fewest", "most"],
+        ));
+        let composite = Composite::new(Widget::col(col).padding(5).bg(app.cs.panel_bg))
+            .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+            .build(ctx);
+
+        let mut colorer = ColorNetwork::new(app);
+        colorer.ranked_roads(cnt_per_r, &app.cs.good_to_bad_red);
+        colorer.ranked_intersections(cnt_per_i, &app.cs.good_to_bad_red);
+        let (unzoomed, zoomed) = colorer.build(ctx);
+
+        Spillback {
+            time: app.primary.sim.time(),
+            unzoomed,
+            zoomed,
+            composite,
+        }
+    }
+}
+
+// Where hard braking and tight gaps between conflicting turns happen, as a stand-in for where
+// crashes might
+pub struct SafetyHotspots {
+    time: Time,
+    compare: bool,
+    unzoomed: Drawable,
+    zoomed: Drawable,
+    composite: Composite,
+}
+
+impl Layer for SafetyHotspots {
+    fn name(&self) -> Option<&'static str> {
+        Some("safety hotspots")
+    }
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        minimap: &Composite,
+    ) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = SafetyHotspots::new(ctx, app, self.compare);
+        }
+
+        self.composite.align_above(ctx, minimap);
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                _ => unreachable!(),
+            },
+            None => {
+                let new_compare = self.composite.has_widget("Compare before edits")
+                    && self.composite.is_checked("Compare before edits");
+                if new_compare != self.compare {
+                    *self = SafetyHotspots::new(ctx, app, new_compare);
+                    self.composite.align_above(ctx, minimap);
+                }
+            }
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.composite.draw(g);
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            g.redraw(&self.unzoomed);
+        } else {
+            g.redraw(&self.zoomed);
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.unzoomed);
+    }
+}
+
+impl SafetyHotspots {
+    pub fn new(ctx: &mut EventCtx, app: &App, compare: bool) -> SafetyHotspots {
+        let now = app.primary.sim.time();
+        let after = app.primary.sim.get_analytics();
+        let mut hard_braking = 0;
+        let mut tight_gaps = 0;
+        for (t, _, ev) in &after.safety_events {
+            if *t > now {
+                break;
+            }
+            match ev {
+                SafetyEvent::HardBraking => {
+                    hard_braking += 1;
+                }
+                SafetyEvent::TightGap => {
+                    tight_gaps += 1;
+                }
+            }
+        }
+
+        let mut colorer = ColorNetwork::new(app);
+        let legend = if compare {
+            let before = app.prebaked().safety_hotspots(now);
+            let after = after.safety_hotspots(now);
+            // Relative to the intersection that changed the most, in either direction
+            let diffs: Vec<(IntersectionID, isize)> = before
+                .compare(after)
+                .into_iter()
+                .map(|(i, before, after)| (i, (after as isize) - (before as isize)))
+                .collect();
+            let max = diffs.iter().map(|(_, d)| d.abs()).max().unwrap_or(0).max(1) as f64;
+            let scale =
+                DivergingScale::new(Color::hex("#5D9630"), Color::WHITE, Color::hex("#A32015"))
+                    .range(-1.0, 1.0)
+                    .ignore(-0.1, 0.1);
+            for (i, diff) in diffs {
+                if let Some(c) = scale.eval((diff as f64) / max) {
+                    colorer.add_i(i, c);
+                }
+            }
+            scale.make_legend(ctx, vec!["fewer", "same", "more"])
+        } else {
+            colorer.ranked_intersections(after.safety_hotspots(now), &app.cs.good_to_bad_red);
+            ColorLegend::gradient(ctx, &app.cs.good_to_bad_red, vec!["fewest", "most"])
+        };
+        let (unzoomed, zoomed) = colorer.build(ctx);
+
+        let composite = Composite::new(
+            Widget::col(vec![
+                Widget::row(vec![
+                    Widget::draw_svg(ctx, "../data/system/assets/tools/layers.svg")
+                        .margin_right(10),
+                    "Potential safety hotspots".draw_text(ctx),
+                    Btn::plaintext("X")
+                        .build(ctx, "close", hotkey(Key::Escape))
+                        .align_right(),
+                ]),
+                Text::from(
+                    Line(
+                        "Intersections where vehicles had to brake hard for a signal, or turns \
+                         started right after a conflicting one",
+                    )
+                    .secondary(),
+                )
+                .wrap_to_pct(ctx, 15)
+                .draw(ctx),
+                format!(
+                    "{} hard braking, {} tight gaps",
+                    prettyprint_usize(hard_braking),
+                    prettyprint_usize(tight_gaps)
+                )
+                .draw_text(ctx),
+                if app.has_prebaked().is_some() {
+                    Checkbox::text(ctx, "Compare before edits", None, compare).margin_below(5)
+                } else {
+                    Widget::nothing()
+                },
+                legend,
+            ])
+            .padding(5)
+            .bg(app.cs.panel_bg),
+        )
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx);
+
+        SafetyHotspots {
+            time: now,
+            compare,
+            unzoomed,
+            zoomed,
+            composite,
+        }
+    }
+}
+
 // TODO Filter by mode
 pub struct Throughput {
     time: Time,
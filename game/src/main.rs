@@ -32,9 +32,14 @@ fn main() {
         sim_flags: SimFlags::from_args(&mut args),
         draw_lane_markings: !args.enabled("--dont_draw_lane_markings"),
         num_agents: args.optional_parse("--num_agents", |s| s.parse()),
+        watch_map: args.enabled("--watch"),
+        extrude_bldgs: args.enabled("--3d_bldgs"),
     };
     let mut opts = options::Options::default();
     opts.dev = args.enabled("--dev");
+    if args.enabled("--imperial") {
+        opts.units = geom::UnitSystem::Imperial;
+    }
     if args.enabled("--lowzoom") {
         opts.min_zoom_for_detail = 1.0;
     }
@@ -60,6 +65,7 @@ fn main() {
     }
     let mut settings = ezgui::Settings::new("A/B Street", "../data/system/fonts");
     settings.window_icon("../data/system/assets/pregame/icon.png");
+    settings.icon_sheet("../data/system/assets/icons.json");
     if args.enabled("--enable_profiler") {
         settings.enable_profiling();
     }
@@ -72,6 +78,12 @@ fn main() {
     if let Some(s) = args.optional_parse("--scale_factor", |s| s.parse::<f64>()) {
         settings.scale_factor(s);
     }
+    if let Some(lang) = args.optional("--lang") {
+        settings.language(lang);
+    }
+    if let Some(mb) = args.optional_parse("--gpu_memory_mb", |s| s.parse::<usize>()) {
+        settings.gpu_memory_budget_mb(mb);
+    }
 
     let mut mode = None;
     if let Some(x) = args.optional("--challenge") {
@@ -105,10 +117,17 @@ fn main() {
         mode = Some(sandbox::GameplayMode::PlayScenario(map_path, scenario));
     }
     let start_with_edits = args.optional("--edits");
+    // Something copied with ctrl+K, like "montlake/i123@4567.8,910.1,3.5"
+    let goto = args.optional("--goto").map(|x| {
+        let link = common::Permalink::parse(&x)
+            .unwrap_or_else(|| panic!("Invalid --goto={}. Try map/object@x,y,zoom", x));
+        flags.sim_flags.load = abstutil::path_map(&link.map);
+        link
+    });
 
     args.done();
 
     ezgui::run(settings, |ctx| {
-        game::Game::new(flags, opts, start_with_edits, mode, ctx)
+        game::Game::new(flags, opts, start_with_edits, mode, goto, ctx)
     });
 }
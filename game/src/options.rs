@@ -1,10 +1,11 @@
 use crate::app::App;
 use crate::colors::ColorSchemeChoice;
-use crate::game::{State, Transition};
+use crate::game::{msg, State, Transition};
 use ezgui::{
-    hotkey, Btn, Checkbox, Choice, Composite, EventCtx, GfxCtx, Key, Line, Outcome, TextExt, Widget,
+    hotkey, Btn, Checkbox, Choice, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, Outcome,
+    TabContainer, TextExt, VerticalAlignment, Widget,
 };
-use geom::Duration;
+use geom::{Duration, UnitSystem};
 
 // TODO SimOptions stuff too
 #[derive(Clone)]
@@ -19,6 +20,7 @@ pub struct Options {
 
     pub time_increment: Duration,
     pub resume_after_edit: bool,
+    pub units: UnitSystem,
 }
 
 impl Options {
@@ -34,6 +36,7 @@ impl Options {
 
             time_increment: Duration::minutes(10),
             resume_after_edit: true,
+            units: UnitSystem::Metric,
         }
     }
 }
@@ -47,189 +50,294 @@ pub enum TrafficSignalStyle {
     IndividualTurnArrows,
 }
 
+// The settings are split into a few tabs. Hidden tabs keep whatever was changed in them, and
+// "Apply" takes everything from all of them.
 pub struct OptionsPanel {
-    composite: Composite,
+    tabs: TabContainer,
 }
 
 impl OptionsPanel {
     pub fn new(ctx: &mut EventCtx, app: &App) -> OptionsPanel {
-        OptionsPanel {
-            composite: Composite::new(
-                Widget::col(vec![
-                    Widget::row(vec![
-                        Line("Settings").small_heading().draw(ctx),
-                        Btn::plaintext("X")
-                            .build(ctx, "close", hotkey(Key::Escape))
-                            .align_right(),
-                    ])
-                    .margin_below(10),
-                    Checkbox::text(ctx, "Enable developer mode", None, app.opts.dev)
-                        .margin_below(10),
-                    "Camera controls".draw_text(ctx).margin_below(10),
-                    Widget::col(vec![
-                        Checkbox::text(
-                            ctx,
-                            "Invert direction of vertical scrolling",
-                            None,
-                            ctx.canvas.invert_scroll,
-                        )
-                        .margin_below(10),
-                        Checkbox::text(
-                            ctx,
-                            "Pan map when cursor is at edge of screen",
-                            None,
-                            ctx.canvas.edge_auto_panning,
-                        )
-                        .named("autopan")
-                        .margin_below(10),
-                        Checkbox::text(
-                            ctx,
-                            "Use touchpad to pan and hold Control to zoom",
-                            None,
-                            ctx.canvas.touchpad_to_move,
-                        )
-                        .margin_below(10),
-                        Checkbox::text(
-                            ctx,
-                            "Use arrow keys to pan and Q/W to zoom",
-                            None,
-                            ctx.canvas.keys_to_pan,
-                        ),
-                    ])
-                    .bg(app.cs.section_bg)
-                    .padding(8)
-                    .margin_below(10),
-                    "Appearance".draw_text(ctx).margin_below(10),
-                    Widget::col(vec![
-                        Checkbox::text(ctx, "Draw road names", None, app.opts.label_roads)
-                            .margin_below(10),
-                        Widget::row(vec![
-                            "Traffic signal rendering:".draw_text(ctx).margin_right(15),
-                            Widget::dropdown(
-                                ctx,
-                                "Traffic signal rendering",
-                                app.opts.traffic_signal_style.clone(),
-                                vec![
-                                    Choice::new(
-                                        "Brian's variation of arrows showing the protected and \
+        let general = Widget::col(vec![
+            Checkbox::text(ctx, "Enable developer mode", None, app.opts.dev).margin_below(10),
+            Widget::row(vec![
+                "Language:".draw_text(ctx).margin_right(15),
+                Widget::dropdown(ctx, "Language", ctx.get_language(), {
+                    let mut choices = vec![Choice::new("English", None)];
+                    for lang in ezgui::available_languages() {
+                        choices.push(Choice::new(lang.clone(), Some(lang)));
+                    }
+                    choices
+                }),
+                if app.opts.dev && ctx.get_language().is_some() {
+                    Btn::text_fg("save missing translations")
+                        .build_def(ctx, None)
+                        .margin_left(15)
+                } else {
+                    Widget::nothing()
+                },
+            ])
+            .margin_below(10),
+            Widget::row(vec![
+                "Units:".draw_text(ctx).margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "Units",
+                    app.opts.units,
+                    vec![
+                        Choice::new("metric", UnitSystem::Metric),
+                        Choice::new("imperial", UnitSystem::Imperial),
+                    ],
+                ),
+            ]),
+        ])
+        .bg(app.cs.section_bg)
+        .padding(8);
+        let camera = Widget::col(vec![
+            Checkbox::text(
+                ctx,
+                "Invert direction of vertical scrolling",
+                None,
+                ctx.canvas.invert_scroll,
+            )
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Invert direction of horizontal scrolling",
+                None,
+                ctx.canvas.invert_horiz_scroll,
+            )
+            .margin_below(10),
+            Widget::row(vec![
+                "Zoom sensitivity:".draw_text(ctx).margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "Zoom sensitivity",
+                    ctx.canvas.zoom_sensitivity,
+                    vec![
+                        Choice::new("0.1", 0.1),
+                        Choice::new("0.25", 0.25),
+                        Choice::new("0.5", 0.5),
+                        Choice::new("1.0", 1.0),
+                        Choice::new("2.0", 2.0),
+                    ],
+                ),
+            ])
+            .margin_below(10),
+            Widget::row(vec![
+                "Show tooltips after:".draw_text(ctx).margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "Tooltip delay",
+                    ctx.canvas.tooltip_delay,
+                    vec![
+                        Choice::new("no delay", Duration::ZERO),
+                        Choice::new("0.25s", Duration::seconds(0.25)),
+                        Choice::new("0.5s", Duration::seconds(0.5)),
+                        Choice::new("1s", Duration::seconds(1.0)),
+                    ],
+                ),
+            ])
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Pan map when cursor is at edge of screen",
+                None,
+                ctx.canvas.edge_auto_panning,
+            )
+            .named("autopan")
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Use touchpad to pan and hold Control to zoom",
+                None,
+                ctx.canvas.touchpad_to_move,
+            )
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Use arrow keys to pan and Q/W to zoom",
+                None,
+                ctx.canvas.keys_to_pan,
+            ),
+        ])
+        .bg(app.cs.section_bg)
+        .padding(8);
+        let appearance = Widget::col(vec![
+            Checkbox::text(ctx, "Draw road names", None, app.opts.label_roads).margin_below(10),
+            Widget::row(vec![
+                "Traffic signal rendering:".draw_text(ctx).margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "Traffic signal rendering",
+                    app.opts.traffic_signal_style.clone(),
+                    vec![
+                        Choice::new(
+                            "Brian's variation of arrows showing the protected and \
                                          permitted movements",
-                                        TrafficSignalStyle::BAP,
-                                    ),
-                                    Choice::new(
-                                        "arrows showing the protected and permitted movements",
-                                        TrafficSignalStyle::GroupArrows,
-                                    ),
-                                    Choice::new(
-                                        "arrows showing the protected and permitted movements, \
+                            TrafficSignalStyle::BAP,
+                        ),
+                        Choice::new(
+                            "arrows showing the protected and permitted movements",
+                            TrafficSignalStyle::GroupArrows,
+                        ),
+                        Choice::new(
+                            "arrows showing the protected and permitted movements, \
                                          with sidewalks",
-                                        TrafficSignalStyle::Sidewalks,
-                                    ),
-                                    Choice::new(
-                                        "icons for movements (like the editor UI)",
-                                        TrafficSignalStyle::Icons,
-                                    ),
-                                    Choice::new(
-                                        "arrows showing individual turns (to debug)",
-                                        TrafficSignalStyle::IndividualTurnArrows,
-                                    ),
-                                ],
-                            ),
-                        ])
-                        .margin_below(10),
-                        Widget::row(vec![
-                            "Color scheme:".draw_text(ctx).margin_right(5),
-                            Widget::dropdown(
-                                ctx,
-                                "Color scheme",
-                                app.opts.color_scheme,
-                                ColorSchemeChoice::choices(),
-                            ),
-                        ])
-                        .margin_below(10),
-                        Widget::row(vec![
-                            format!(
-                                "Scale factor for text / UI elements (your monitor is {}):",
-                                ctx.monitor_scale_factor()
-                            )
-                            .draw_text(ctx)
-                            .margin_right(15),
-                            Widget::dropdown(
-                                ctx,
-                                "Scale factor",
-                                ctx.get_scale_factor(),
-                                vec![
-                                    Choice::new("0.5", 0.5),
-                                    Choice::new("1.0", 1.0),
-                                    Choice::new("1.5", 1.5),
-                                    Choice::new("2.0", 2.0),
-                                ],
-                            ),
-                        ])
-                        .margin_below(10),
-                        Widget::row(vec![
-                            "Camera zoom to switch to unzoomed view"
-                                .draw_text(ctx)
-                                .margin_right(15),
-                            Widget::dropdown(
-                                ctx,
-                                "min zoom",
-                                app.opts.min_zoom_for_detail,
-                                vec![
-                                    Choice::new("1.0", 1.0),
-                                    Choice::new("2.0", 2.0),
-                                    Choice::new("3.0", 3.0),
-                                    Choice::new("4.0", 4.0),
-                                    Choice::new("5.0", 5.0),
-                                    Choice::new("6.0", 6.0),
-                                ],
-                            ),
-                        ])
-                        .margin_below(10),
-                        Checkbox::text(
-                            ctx,
-                            "Draw enlarged unzoomed agents",
-                            None,
-                            app.opts.large_unzoomed_agents,
+                            TrafficSignalStyle::Sidewalks,
                         ),
-                    ])
-                    .bg(app.cs.section_bg)
-                    .padding(8)
-                    .margin_below(10),
-                    Btn::text_bg2("Apply")
-                        .build_def(ctx, hotkey(Key::Enter))
-                        .centered_horiz(),
-                ])
-                .padding(16)
-                .bg(app.cs.panel_bg),
+                        Choice::new(
+                            "icons for movements (like the editor UI)",
+                            TrafficSignalStyle::Icons,
+                        ),
+                        Choice::new(
+                            "arrows showing individual turns (to debug)",
+                            TrafficSignalStyle::IndividualTurnArrows,
+                        ),
+                    ],
+                ),
+            ])
+            .margin_below(10),
+            Widget::row(vec![
+                "Color scheme:".draw_text(ctx).margin_right(5),
+                Widget::dropdown(
+                    ctx,
+                    "Color scheme",
+                    app.opts.color_scheme,
+                    ColorSchemeChoice::choices(),
+                ),
+            ])
+            .margin_below(10),
+            Widget::row(vec![
+                format!(
+                    "Scale factor for text / UI elements (your monitor is {}):",
+                    ctx.monitor_scale_factor()
+                )
+                .draw_text(ctx)
+                .margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "Scale factor",
+                    ctx.get_scale_factor(),
+                    vec![
+                        Choice::new("0.5", 0.5),
+                        Choice::new("1.0", 1.0),
+                        Choice::new("1.5", 1.5),
+                        Choice::new("2.0", 2.0),
+                    ],
+                ),
+            ])
+            .margin_below(10),
+            Widget::row(vec![
+                "Camera zoom to switch to unzoomed view"
+                    .draw_text(ctx)
+                    .margin_right(15),
+                Widget::dropdown(
+                    ctx,
+                    "min zoom",
+                    app.opts.min_zoom_for_detail,
+                    vec![
+                        Choice::new("1.0", 1.0),
+                        Choice::new("2.0", 2.0),
+                        Choice::new("3.0", 3.0),
+                        Choice::new("4.0", 4.0),
+                        Choice::new("5.0", 5.0),
+                        Choice::new("6.0", 6.0),
+                    ],
+                ),
+            ])
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Draw enlarged unzoomed agents",
+                None,
+                app.opts.large_unzoomed_agents,
             )
-            .build(ctx),
+            .margin_below(10),
+            Checkbox::text(
+                ctx,
+                "Draw buildings in 3D",
+                None,
+                app.primary.current_flags.extrude_bldgs,
+            ),
+        ])
+        .bg(app.cs.section_bg)
+        .padding(8);
+        OptionsPanel {
+            tabs: TabContainer::new(
+                ctx,
+                HorizontalAlignment::Center,
+                VerticalAlignment::Percent(0.1),
+                vec![
+                    ("General", tab(ctx, app, general)),
+                    ("Camera controls", tab(ctx, app, camera)),
+                    ("Appearance", tab(ctx, app, appearance)),
+                ],
+            ),
         }
     }
 }
 
+// Every tab has the same title and buttons around its settings.
+fn tab(ctx: &mut EventCtx, app: &App, settings: Widget) -> Widget {
+    Widget::col(vec![
+        Widget::row(vec![
+            Line("Settings").small_heading().draw(ctx),
+            Btn::plaintext("X")
+                .build(ctx, "close", hotkey(Key::Escape))
+                .align_right(),
+        ])
+        .margin_below(10),
+        settings.margin_below(10),
+        Btn::text_bg2("Apply")
+            .build_def(ctx, hotkey(Key::Enter))
+            .centered_horiz(),
+    ])
+    .padding(16)
+    .bg(app.cs.panel_bg)
+}
+
 impl State for OptionsPanel {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
-        match self.composite.event(ctx) {
+        match self.tabs.event(ctx) {
             Some(Outcome::Clicked(x)) => match x.as_ref() {
                 "close" => {
                     return Transition::Pop;
                 }
+                "save missing translations" => {
+                    if let Some(path) = ctx.save_missing_translations() {
+                        return Transition::Push(msg(
+                            "Saved",
+                            vec![format!("Untranslated text written to {}", path)],
+                        ));
+                    }
+                }
                 "Apply" => {
-                    app.opts.dev = self.composite.is_checked("Enable developer mode");
+                    let general = self.tabs.panel("General");
+                    let camera = self.tabs.panel("Camera controls");
+                    let appearance = self.tabs.panel("Appearance");
+                    app.opts.dev = general.is_checked("Enable developer mode");
 
-                    ctx.canvas.invert_scroll = self
-                        .composite
-                        .is_checked("Invert direction of vertical scrolling");
-                    ctx.canvas.touchpad_to_move = self
-                        .composite
-                        .is_checked("Use touchpad to pan and hold Control to zoom");
-                    ctx.canvas.keys_to_pan = self
-                        .composite
-                        .is_checked("Use arrow keys to pan and Q/W to zoom");
-                    ctx.canvas.edge_auto_panning = self.composite.is_checked("autopan");
+                    ctx.canvas.invert_scroll =
+                        camera.is_checked("Invert direction of vertical scrolling");
+                    ctx.canvas.invert_horiz_scroll =
+                        camera.is_checked("Invert direction of horizontal scrolling");
+                    ctx.canvas.zoom_sensitivity = camera.dropdown_value("Zoom sensitivity");
+                    ctx.canvas.tooltip_delay = camera.dropdown_value("Tooltip delay");
+                    ctx.canvas.touchpad_to_move =
+                        camera.is_checked("Use touchpad to pan and hold Control to zoom");
+                    ctx.canvas.keys_to_pan =
+                        camera.is_checked("Use arrow keys to pan and Q/W to zoom");
+                    ctx.canvas.edge_auto_panning = camera.is_checked("autopan");
 
-                    app.opts.label_roads = self.composite.is_checked("Draw road names");
-                    let style = self.composite.dropdown_value("Traffic signal rendering");
+                    app.opts.label_roads = appearance.is_checked("Draw road names");
+                    app.opts.units = general.dropdown_value("Units");
+                    app.opts.units.set();
+                    let lang = general.dropdown_value("Language");
+                    if ctx.get_language() != lang {
+                        ctx.set_language(lang);
+                    }
+                    let style = appearance.dropdown_value("Traffic signal rendering");
                     if app.opts.traffic_signal_style != style {
                         app.opts.traffic_signal_style = style;
                         println!("Rerendering traffic signals...");
@@ -238,20 +346,25 @@ impl State for OptionsPanel {
                         }
                     }
 
-                    let scheme = self.composite.dropdown_value("Color scheme");
-                    if app.opts.color_scheme != scheme {
+                    // Both of these need everything to be rendered again
+                    let scheme = appearance.dropdown_value("Color scheme");
+                    let extrude_bldgs = appearance.is_checked("Draw buildings in 3D");
+                    if app.opts.color_scheme != scheme
+                        || app.primary.current_flags.extrude_bldgs != extrude_bldgs
+                    {
                         app.opts.color_scheme = scheme;
+                        app.primary.current_flags.extrude_bldgs = extrude_bldgs;
                         app.switch_map(ctx, app.primary.current_flags.sim_flags.load.clone());
                     }
 
-                    let factor = self.composite.dropdown_value("Scale factor");
+                    let factor = appearance.dropdown_value("Scale factor");
                     if ctx.get_scale_factor() != factor {
                         ctx.set_scale_factor(factor);
                     }
 
-                    app.opts.min_zoom_for_detail = self.composite.dropdown_value("min zoom");
+                    app.opts.min_zoom_for_detail = appearance.dropdown_value("min zoom");
                     app.opts.large_unzoomed_agents =
-                        self.composite.is_checked("Draw enlarged unzoomed agents");
+                        appearance.is_checked("Draw enlarged unzoomed agents");
 
                     return Transition::Pop;
                 }
@@ -264,6 +377,6 @@ impl State for OptionsPanel {
     }
 
     fn draw(&self, g: &mut GfxCtx, _: &App) {
-        self.composite.draw(g);
+        self.tabs.draw(g);
     }
 }
@@ -2,20 +2,19 @@ use crate::app::App;
 use crate::colors::ColorScheme;
 use crate::helpers::ID;
 use crate::render::{DrawOptions, Renderable, OUTLINE_THICKNESS};
-use ezgui::{Color, Drawable, GeomBatch, GfxCtx, Line, Prerender, Text};
-use geom::{Distance, Line, Polygon, Pt2D};
+use ezgui::{Color, GeomBatch, GfxCtx, Line, Prerender, Text, TextBatch};
+use geom::{Distance, Line, Polygon, Pt2D, Ring};
 use map_model::{Building, BuildingID, Map, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS};
-use std::cell::RefCell;
 
 pub struct DrawBuilding {
     pub id: BuildingID,
-    label: RefCell<Option<Drawable>>,
 }
 
 impl DrawBuilding {
     pub fn new(
         bldg: &Building,
         cs: &ColorScheme,
+        extrude: bool,
         bldg_batch: &mut GeomBatch,
         paths_batch: &mut GeomBatch,
         outlines_batch: &mut GeomBatch,
@@ -38,8 +37,11 @@ impl DrawBuilding {
             cs.sidewalk,
             front_path_line.make_polygons(NORMAL_LANE_THICKNESS),
         );
-        if let Some(p) = bldg.polygon.maybe_to_outline(Distance::meters(0.1)) {
-            outlines_batch.push(cs.building_outline, p);
+        // Extruded buildings get an outline around the roof instead.
+        if !extrude {
+            if let Some(p) = bldg.polygon.maybe_to_outline(Distance::meters(0.1)) {
+                outlines_batch.push(cs.building_outline, p);
+            }
         }
 
         if bldg
@@ -50,16 +52,63 @@ impl DrawBuilding {
         {
             // Might need to scale down more for some buildings, but so far, this works everywhere.
             bldg_batch.append(
-                GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/parking.svg")
+                GeomBatch::mapspace_icon(prerender, "parking")
                     .scale(0.1)
                     .centered_on(bldg.label_center),
             );
         }
 
-        DrawBuilding {
-            id: bldg.id,
-            label: RefCell::new(None),
+        DrawBuilding { id: bldg.id }
+    }
+}
+
+// Buildings without height info are drawn about two levels tall.
+const DEFAULT_HEIGHT: Distance = Distance::const_meters(6.0);
+// How far up the screen the roof is shifted, per meter of height. This isn't a real projection,
+// just enough to make density stand out.
+const EXTRUDE_SCALE: f64 = 0.3;
+
+// Draws walls rising from the building's footprint and a roof on top. The footprint has already
+// been drawn by DrawBuilding::new; callers have to handle buildings from back to front.
+pub fn draw_extruded(bldg: &Building, cs: &ColorScheme, batch: &mut GeomBatch) {
+    let height = bldg.height.unwrap_or(DEFAULT_HEIGHT).inner_meters() * EXTRUDE_SCALE;
+    let (dx, dy) = (0.3 * height, -height);
+
+    // Polygons with holes don't form a single ring, so skip the walls for them.
+    if let Some(ring) = Ring::maybe_new(bldg.polygon.points().clone()) {
+        for pair in ring.points().windows(2) {
+            let (pt1, pt2) = (pair[0], pair[1]);
+            // Shade walls by which way they face, with light coming from the upper left.
+            let facing = pt1.angle_to(pt2).rotate_degs(90.0).normalized_radians();
+            let light = (facing - (-135.0_f64).to_radians()).cos();
+            let shade = 0.4 - 0.25 * light;
+            batch.push(
+                cs.building.lerp(Color::BLACK, shade),
+                Polygon::precomputed(
+                    vec![pt1, pt2, pt2.offset(dx, dy), pt1.offset(dx, dy)],
+                    vec![0, 1, 2, 0, 2, 3],
+                ),
+            );
+        }
+    }
+
+    let roof = bldg.polygon.translate(dx, dy);
+    let outline = roof.maybe_to_outline(Distance::meters(0.1));
+    batch.push(cs.building, roof);
+    if let Some(p) = outline {
+        batch.push(cs.building_outline, p);
+    }
+}
+
+// Names the first amenity of a building, if it has any. All of these labels get batched together
+// in DrawMap, since drawing each one separately is slow when many are on screen.
+pub fn add_label(bldg: &Building, labels: &mut TextBatch) {
+    if let Some((name, _)) = bldg.amenities.iter().next() {
+        let mut txt = Text::from(Line(name).fg(Color::BLACK));
+        if bldg.amenities.len() > 1 {
+            txt.append(Line(format!(" (+{})", bldg.amenities.len() - 1)).fg(Color::BLACK));
         }
+        labels.add(txt.auto_halo(), 0.1, bldg.label_center);
     }
 }
 
@@ -68,30 +117,8 @@ impl Renderable for DrawBuilding {
         ID::Building(self.id)
     }
 
-    fn draw(&self, g: &mut GfxCtx, app: &App, opts: &DrawOptions) {
-        if opts.label_buildings {
-            // Labels are expensive to compute up-front, so do it lazily, since we don't really
-            // zoom in on all buildings in a single session anyway
-            let mut label = self.label.borrow_mut();
-            if label.is_none() {
-                let mut batch = GeomBatch::new();
-                let b = app.primary.map.get_b(self.id);
-                if let Some((name, _)) = b.amenities.iter().next() {
-                    let mut txt = Text::from(Line(name).fg(Color::BLACK));
-                    if b.amenities.len() > 1 {
-                        txt.append(Line(format!(" (+{})", b.amenities.len() - 1)).fg(Color::BLACK));
-                    }
-                    batch.append(
-                        txt.render_to_batch(g.prerender)
-                            .scale(0.1)
-                            .centered_on(b.label_center),
-                    );
-                }
-                *label = Some(g.prerender.upload(batch));
-            }
-            g.redraw(label.as_ref().unwrap());
-        }
-    }
+    // Everything, including the label, is drawn in batches by DrawMap.
+    fn draw(&self, _: &mut GfxCtx, _: &App, _: &DrawOptions) {}
 
     // Some buildings cover up tunnels
     fn get_zorder(&self) -> isize {
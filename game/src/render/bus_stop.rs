@@ -26,7 +26,7 @@ impl DrawBusStop {
 
         let mut icon = GeomBatch::new();
         icon.append(
-            GeomBatch::mapspace_svg(prerender, "../data/system/assets/meters/bus.svg")
+            GeomBatch::mapspace_icon(prerender, "bus_stop")
                 .scale(0.05)
                 .centered_on(center),
         );
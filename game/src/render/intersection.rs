@@ -160,7 +160,13 @@ impl Renderable for DrawIntersection {
                             .centered_on(app.primary.map.get_i(self.id).polygon.center()),
                     );
                 }
-                *maybe_redraw = Some((app.primary.sim.time(), g.prerender.upload(batch)));
+                // Reuse the buffers already on the GPU; this changes every time the sim steps.
+                if let Some((t, draw)) = maybe_redraw.as_mut() {
+                    *t = app.primary.sim.time();
+                    draw.update(g.prerender, batch);
+                } else {
+                    *maybe_redraw = Some((app.primary.sim.time(), g.prerender.upload(batch)));
+                }
             }
             let (_, batch) = maybe_redraw.as_ref().unwrap();
             g.redraw(batch);
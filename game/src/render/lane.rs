@@ -28,7 +28,7 @@ impl AlmostDrawLane {
             while dist + buffer <= len {
                 let (pt, angle) = lane.lane_center_pts.dist_along(dist);
                 self.draw_default.append(
-                    GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/bus_only.svg")
+                    GeomBatch::mapspace_icon(prerender, "bus_lane")
                         .scale(0.06)
                         .centered_on(pt)
                         .rotate(angle.shortest_rotation_towards(Angle::new_degs(-90.0))),
@@ -44,7 +44,7 @@ impl AlmostDrawLane {
             while dist + buffer <= len {
                 let (pt, angle) = lane.lane_center_pts.dist_along(dist);
                 self.draw_default.append(
-                    GeomBatch::mapspace_svg(prerender, "../data/system/assets/meters/bike.svg")
+                    GeomBatch::mapspace_icon(prerender, "bike_lane")
                         .scale(0.06)
                         .centered_on(pt)
                         .rotate(angle.shortest_rotation_towards(Angle::new_degs(-90.0))),
@@ -85,11 +85,27 @@ impl DrawLane {
         let road = map.get_r(lane.parent);
         let polygon = lane.lane_center_pts.make_polygons(lane.width);
 
+        // Pedestrian streets are paved over like a plaza, without any road markings.
+        if road.access_restrictions.pedestrian_only && lane.lane_type != LaneType::Parking {
+            let mut draw = GeomBatch::new();
+            draw.push(cs.sidewalk, polygon.clone());
+            if draw_lane_markings && lane.is_sidewalk() {
+                draw.extend(cs.sidewalk_lines, calculate_sidewalk_lines(lane));
+            }
+            return AlmostDrawLane {
+                id: lane.id,
+                polygon,
+                zorder: road.zorder,
+                draw_default: draw,
+            };
+        }
+
         let mut draw = GeomBatch::new();
         draw.push(
             match lane.lane_type {
                 LaneType::Driving => cs.driving_lane,
                 LaneType::Bus => cs.bus_lane,
+                LaneType::HOV => cs.hov_lane,
                 LaneType::Parking => cs.parking_lane,
                 LaneType::Sidewalk => cs.sidewalk,
                 LaneType::Biking => cs.bike_lane,
@@ -106,7 +122,7 @@ impl DrawLane {
                 LaneType::Parking => {
                     draw.extend(cs.general_road_marking, calculate_parking_lines(map, lane));
                 }
-                LaneType::Driving | LaneType::Bus => {
+                LaneType::Driving | LaneType::Bus | LaneType::HOV => {
                     draw.extend(
                         cs.general_road_marking,
                         calculate_driving_lines(map, lane, road, timer),
@@ -1,7 +1,7 @@
 use crate::app::{App, Flags};
 use crate::colors::ColorScheme;
 use crate::helpers::ID;
-use crate::render::building::DrawBuilding;
+use crate::render::building::{self, DrawBuilding};
 use crate::render::bus_stop::DrawBusStop;
 use crate::render::intersection::DrawIntersection;
 use crate::render::lane::DrawLane;
@@ -10,17 +10,33 @@ use crate::render::road::DrawRoad;
 use crate::render::{draw_vehicle, DrawArea, DrawPedCrowd, DrawPedestrian, Renderable};
 use aabb_quadtree::QuadTree;
 use abstutil::Timer;
-use ezgui::{Color, Drawable, EventCtx, GeomBatch, GfxCtx, Prerender};
-use geom::{Bounds, Circle, Distance, Pt2D, Time};
+use ezgui::{
+    CachedDrawable, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Instance, Prerender, TextBatch,
+};
+use geom::{Angle, Bounds, Circle, Distance, Polygon, Pt2D, Time};
 use map_model::{
-    AreaID, BuildingID, BusStopID, Intersection, IntersectionID, LaneID, Map, ParkingLotID, Road,
-    RoadID, Traversable, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
+    AreaID, Building, BuildingID, BusStopID, Intersection, IntersectionID, LaneID, Map,
+    ParkingLotID, Road, RoadID, RoadImportance, Traversable, NORMAL_LANE_THICKNESS,
+    SIDEWALK_THICKNESS,
 };
 use sim::{GetDrawAgents, UnzoomedAgent, VehicleType};
 use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+// Smaller roads disappear from the generalized view
+const MIN_GENERALIZED_IMPORTANCE: RoadImportance = RoadImportance::Arterial;
+
+// Is this lane or turn still drawn when the map is generalized?
+fn shown_when_generalized(on: Traversable, map: &Map) -> bool {
+    match on {
+        Traversable::Lane(l) => map.get_parent(l).importance >= MIN_GENERALIZED_IMPORTANCE,
+        Traversable::Turn(t) => {
+            map.get_i(t.parent).get_importance(map) >= MIN_GENERALIZED_IMPORTANCE
+        }
+    }
+}
+
 pub struct DrawMap {
     pub roads: Vec<DrawRoad>,
     pub lanes: Vec<DrawLane>,
@@ -32,15 +48,27 @@ pub struct DrawMap {
 
     // TODO Move?
     pub agents: RefCell<AgentCache>,
+    // The selection changes whenever the mouse moves onto something else, so its buffers are
+    // overwritten instead of uploading something new every time.
+    selection: RefCell<Option<(Color, Polygon, Drawable)>>,
 
     pub boundary_polygon: Drawable,
-    pub draw_all_thick_roads: Drawable,
-    pub draw_all_unzoomed_intersections: Drawable,
     pub draw_all_buildings: Drawable,
-    pub draw_all_building_paths: Drawable,
-    pub draw_all_building_outlines: Drawable,
-    pub draw_all_unzoomed_parking_lots: Drawable,
     pub draw_all_areas: Drawable,
+    // Only drawn when unzoomed, so these can be evicted from the GPU
+    pub draw_all_thick_roads: CachedDrawable,
+    pub draw_all_unzoomed_intersections: CachedDrawable,
+    pub draw_all_unzoomed_parking_lots: CachedDrawable,
+    // A simplified view for city scale: only important roads, and buildings merged into blocks
+    pub draw_generalized_roads: CachedDrawable,
+    pub draw_generalized_intersections: CachedDrawable,
+    pub draw_all_building_blocks: CachedDrawable,
+    // Only drawn when zoomed in, so these can be evicted from the GPU
+    pub draw_all_building_paths: CachedDrawable,
+    pub draw_all_building_outlines: CachedDrawable,
+    // Only laid out and uploaded the first time they're drawn zoomed in, since we don't really
+    // zoom in on buildings in every session anyway
+    building_labels: TextBatch,
 
     quadtree: QuadTree<ID>,
 }
@@ -64,11 +92,14 @@ impl DrawMap {
         let mut road_refs: Vec<&Road> = map.all_roads().iter().collect();
         road_refs.sort_by_key(|r| r.zorder);
         let mut all_roads = GeomBatch::new();
+        let mut generalized_roads = GeomBatch::new();
         for r in road_refs {
-            all_roads.push(
-                osm_rank_to_color(cs, r.get_rank()),
-                r.get_thick_polygon(map).get(timer),
-            );
+            let color = osm_rank_to_color(cs, r.get_rank());
+            let poly = r.get_thick_polygon(map).get(timer);
+            if r.importance >= MIN_GENERALIZED_IMPORTANCE {
+                generalized_roads.push(color, poly.clone());
+            }
+            all_roads.push(color, poly);
             /*if false {
                 all_roads.push(
                     color,
@@ -76,7 +107,8 @@ impl DrawMap {
                 );
             }*/
         }
-        let draw_all_thick_roads = all_roads.upload(ctx);
+        let draw_all_thick_roads = CachedDrawable::new(ctx.prerender, all_roads);
+        let draw_generalized_roads = CachedDrawable::new(ctx.prerender, generalized_roads);
         timer.stop("generate thick roads");
 
         let almost_lanes =
@@ -110,6 +142,7 @@ impl DrawMap {
         let mut intersection_refs: Vec<&Intersection> = map.all_intersections().iter().collect();
         intersection_refs.sort_by_key(|i| i.get_zorder(map));
         let mut all_intersections = GeomBatch::new();
+        let mut generalized_intersections = GeomBatch::new();
         for i in intersection_refs {
             // TODO Would be neat to show closed intersections here, but then edits need to
             // regenerate this
@@ -124,30 +157,58 @@ impl DrawMap {
             } else {
                 all_intersections.push(cs.unzoomed_interesting_intersection, i.polygon.clone());
             }
+            // Signals don't stand out at this scale
+            if i.get_importance(map) >= MIN_GENERALIZED_IMPORTANCE {
+                generalized_intersections
+                    .push(osm_rank_to_color(cs, i.get_rank(map)), i.polygon.clone());
+            }
         }
-        let draw_all_unzoomed_intersections = all_intersections.upload(ctx);
+        let draw_all_unzoomed_intersections = CachedDrawable::new(ctx.prerender, all_intersections);
+        let draw_generalized_intersections =
+            CachedDrawable::new(ctx.prerender, generalized_intersections);
         timer.stop("generate unzoomed intersections");
 
         let mut buildings: Vec<DrawBuilding> = Vec::new();
         let mut all_buildings = GeomBatch::new();
         let mut all_building_paths = GeomBatch::new();
         let mut all_building_outlines = GeomBatch::new();
+        let mut building_labels = TextBatch::new();
         timer.start_iter("make DrawBuildings", map.all_buildings().len());
         for b in map.all_buildings() {
             timer.next();
+            building::add_label(b, &mut building_labels);
             buildings.push(DrawBuilding::new(
                 b,
                 cs,
+                flags.extrude_bldgs,
                 &mut all_buildings,
                 &mut all_building_paths,
                 &mut all_building_outlines,
                 ctx.prerender,
             ));
         }
+        if flags.extrude_bldgs {
+            // Roofs are shifted up, so anything further down the screen has to be drawn later to
+            // cover up whatever's behind it.
+            let mut bldgs: Vec<&Building> = map.all_buildings().iter().collect();
+            bldgs.sort_by(|a, b| a.label_center.y().partial_cmp(&b.label_center.y()).unwrap());
+            for b in bldgs {
+                building::draw_extruded(b, cs, &mut all_buildings);
+            }
+        }
         timer.start("upload all buildings");
         let draw_all_buildings = all_buildings.upload(ctx);
-        let draw_all_building_paths = all_building_paths.upload(ctx);
-        let draw_all_building_outlines = all_building_outlines.upload(ctx);
+        let draw_all_building_blocks = CachedDrawable::new(
+            ctx.prerender,
+            GeomBatch::from(
+                map.all_building_blocks()
+                    .iter()
+                    .map(|p| (cs.building, p.clone()))
+                    .collect(),
+            ),
+        );
+        let draw_all_building_paths = CachedDrawable::new(ctx.prerender, all_building_paths);
+        let draw_all_building_outlines = CachedDrawable::new(ctx.prerender, all_building_outlines);
         timer.stop("upload all buildings");
 
         timer.start("make DrawParkingLot");
@@ -161,7 +222,8 @@ impl DrawMap {
                 ctx.prerender,
             ));
         }
-        let draw_all_unzoomed_parking_lots = all_unzoomed_parking_lots.upload(ctx);
+        let draw_all_unzoomed_parking_lots =
+            CachedDrawable::new(ctx.prerender, all_unzoomed_parking_lots);
         timer.stop("make DrawParkingLot");
 
         timer.start_iter("make DrawBusStop", map.all_bus_stops().len());
@@ -228,21 +290,53 @@ impl DrawMap {
             draw_all_thick_roads,
             draw_all_unzoomed_intersections,
             draw_all_buildings,
+            draw_generalized_roads,
+            draw_generalized_intersections,
+            draw_all_building_blocks,
             draw_all_building_paths,
             draw_all_building_outlines,
             draw_all_unzoomed_parking_lots,
             draw_all_areas,
+            building_labels,
 
             agents: RefCell::new(AgentCache {
                 time: None,
                 agents_per_on: HashMap::new(),
                 unzoomed: None,
+                circles: None,
             }),
+            selection: RefCell::new(None),
 
             quadtree,
         }
     }
 
+    pub fn draw_selection(&self, g: &mut GfxCtx, color: Color, poly: &Polygon) {
+        let mut cache = self.selection.borrow_mut();
+        let stale = match *cache {
+            Some((c, ref p, _)) => c != color || p.points() != poly.points(),
+            None => true,
+        };
+        if stale {
+            let batch = GeomBatch::from(vec![(color, poly.clone())]);
+            match cache.as_mut() {
+                Some((c, p, draw)) => {
+                    draw.update(g.prerender, batch);
+                    *c = color;
+                    *p = poly.clone();
+                }
+                None => {
+                    *cache = Some((color, poly.clone(), g.upload(batch)));
+                }
+            }
+        }
+        g.redraw(&cache.as_ref().unwrap().2);
+    }
+
+    pub fn draw_building_labels(&self, g: &mut GfxCtx) {
+        self.building_labels.draw(g);
+    }
+
     // The alt to these is implementing std::ops::Index, but that's way more verbose!
     pub fn get_r(&self, id: RoadID) -> &DrawRoad {
         &self.roads[id.0]
@@ -343,8 +437,10 @@ pub struct AgentCache {
     // This time applies to agents_per_on. unzoomed has its own possibly separate Time!
     time: Option<Time>,
     agents_per_on: HashMap<Traversable, Vec<Box<dyn Renderable>>>,
-    // agent radius also matters
-    unzoomed: Option<(Time, Option<Distance>, AgentColorScheme, Drawable)>,
+    // Where to draw each car and pedestrian circle, and whether the map was generalized
+    unzoomed: Option<(Time, AgentColorScheme, bool, Vec<Instance>, Vec<Instance>)>,
+    // One white circle for cars and one for pedestrians, uploaded once per radius
+    circles: Option<(Option<Distance>, Drawable, Drawable)>,
 }
 
 impl AgentCache {
@@ -393,52 +489,69 @@ impl AgentCache {
 
     // TODO GetDrawAgents indirection added for time traveling, but that's been removed. Maybe
     // simplify this.
+    // When the map is generalized, agents on hidden roads aren't drawn either.
     pub fn draw_unzoomed_agents(
         &mut self,
         source: &dyn GetDrawAgents,
         map: &Map,
         acs: &AgentColorScheme,
+        generalized: bool,
         g: &mut GfxCtx,
         maybe_radius: Option<Distance>,
     ) {
         let now = source.time();
-        if let Some((time, r, ref orig_acs, ref draw)) = self.unzoomed {
-            if now == time && maybe_radius == r && acs == orig_acs {
-                g.redraw(draw);
-                return;
+        let stale = match self.unzoomed {
+            Some((time, ref orig_acs, orig_generalized, _, _)) => {
+                now != time || acs != orig_acs || generalized != orig_generalized
             }
-        }
-
-        let mut batch = GeomBatch::new();
-        // It's quite silly to produce triangles for the same circle over and over again. ;)
-        if let Some(r) = maybe_radius {
-            let circle = Circle::new(Pt2D::new(0.0, 0.0), r).to_polygon();
+            None => true,
+        };
+        if stale {
+            let mut cars = Vec::new();
+            let mut peds = Vec::new();
             for agent in source.get_unzoomed_agents(map) {
-                if let Some(color) = acs.color(&agent) {
-                    batch.push(color, circle.translate(agent.pos.x(), agent.pos.y()));
+                if generalized && !shown_when_generalized(agent.on, map) {
+                    continue;
                 }
-            }
-        } else {
-            // Lane thickness is a little hard to see, so double it. Most of the time, the circles
-            // don't leak out of the road too much.
-            let car_circle =
-                Circle::new(Pt2D::new(0.0, 0.0), 4.0 * NORMAL_LANE_THICKNESS).to_polygon();
-            let ped_circle =
-                Circle::new(Pt2D::new(0.0, 0.0), 4.0 * SIDEWALK_THICKNESS).to_polygon();
-            for agent in source.get_unzoomed_agents(map) {
                 if let Some(color) = acs.color(&agent) {
+                    let instance = Instance {
+                        pos: agent.pos,
+                        angle: Angle::ZERO,
+                        color,
+                    };
                     if agent.vehicle_type.is_some() {
-                        batch.push(color, car_circle.translate(agent.pos.x(), agent.pos.y()));
+                        cars.push(instance);
                     } else {
-                        batch.push(color, ped_circle.translate(agent.pos.x(), agent.pos.y()));
+                        peds.push(instance);
                     }
                 }
             }
+            self.unzoomed = Some((now, acs.clone(), generalized, cars, peds));
+        }
+
+        if self.circles.as_ref().map(|(r, _, _)| *r) != Some(maybe_radius) {
+            let (car_radius, ped_radius) = if let Some(r) = maybe_radius {
+                (r, r)
+            } else {
+                // Lane thickness is a little hard to see, so double it. Most of the time, the
+                // circles don't leak out of the road too much.
+                (4.0 * NORMAL_LANE_THICKNESS, 4.0 * SIDEWALK_THICKNESS)
+            };
+            let car_circle = g.upload(GeomBatch::from(vec![(
+                Color::WHITE,
+                Circle::new(Pt2D::new(0.0, 0.0), car_radius).to_polygon(),
+            )]));
+            let ped_circle = g.upload(GeomBatch::from(vec![(
+                Color::WHITE,
+                Circle::new(Pt2D::new(0.0, 0.0), ped_radius).to_polygon(),
+            )]));
+            self.circles = Some((maybe_radius, car_circle, ped_circle));
         }
 
-        let draw = g.upload(batch);
-        g.redraw(&draw);
-        self.unzoomed = Some((now, maybe_radius, acs.clone(), draw));
+        let (_, _, _, cars, peds) = self.unzoomed.as_ref().unwrap();
+        let (_, car_circle, ped_circle) = self.circles.as_ref().unwrap();
+        g.redraw_instances(car_circle, cars);
+        g.redraw_instances(ped_circle, peds);
     }
 }
 
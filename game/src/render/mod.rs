@@ -25,7 +25,7 @@ pub use crate::render::pedestrian::{DrawPedCrowd, DrawPedestrian};
 pub use crate::render::road::DrawRoad;
 pub use crate::render::traffic_signal::{draw_signal_phase, make_signal_diagram};
 pub use crate::render::turn::{DrawTurnGroup, DrawUberTurnGroup};
-use ezgui::{GfxCtx, Prerender};
+use ezgui::{GfxCtx, Prerender, NUM_MAP_LAYERS};
 use geom::{Distance, Polygon, Pt2D};
 use map_model::{IntersectionID, Map};
 use sim::{DrawCarInput, VehicleType};
@@ -36,6 +36,53 @@ pub const CROSSWALK_LINE_THICKNESS: Distance = Distance::const_meters(0.15);
 
 pub const OUTLINE_THICKNESS: Distance = Distance::const_meters(0.5);
 
+// Below this zoom, the unzoomed map only shows important roads and buildings merged into blocks.
+pub const MAX_ZOOM_FOR_GENERALIZED: f64 = 0.3;
+
+// Bridges and tunnels beyond these share the top or bottom band of map layers
+const MIN_ZORDER: isize = -5;
+const MAX_ZORDER: isize = 5;
+
+// Map-space layers for GfxCtx::set_z. Each zorder gets its own band, so bridges cover everything
+// beneath them, and within a band, these go from bottom to top.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MapLayer {
+    Area,
+    ParkingLot,
+    Lane,
+    // Center lines
+    LaneMarking,
+    Intersection,
+    Building,
+    BusStop,
+    Agent,
+    Label,
+}
+
+impl MapLayer {
+    pub fn of(id: &ID) -> MapLayer {
+        match id {
+            ID::Area(_) => MapLayer::Area,
+            ID::ParkingLot(_) => MapLayer::ParkingLot,
+            ID::Lane(_) | ID::Turn(_) => MapLayer::Lane,
+            ID::Road(_) => MapLayer::LaneMarking,
+            ID::Intersection(_) => MapLayer::Intersection,
+            ID::Building(_) => MapLayer::Building,
+            ID::BusStop(_) => MapLayer::BusStop,
+            ID::Car(_) | ID::Pedestrian(_) | ID::PedCrowd(_) => MapLayer::Agent,
+        }
+    }
+
+    // Pass this to GfxCtx::set_z.
+    pub fn at(self, zorder: isize) -> usize {
+        let band = (zorder.max(MIN_ZORDER).min(MAX_ZORDER) - MIN_ZORDER) as usize;
+        band * (MapLayer::Label as usize + 1) + (self as usize)
+    }
+}
+
+// Everything drawn over the map after App::draw, like layers and route previews. Above every band.
+pub const OVERLAY_LAYER: usize = NUM_MAP_LAYERS - 1;
+
 // Does something belong here or as a method on ID? If it ONLY applies to renderable things, then
 // here. For example, trips aren't drawn, so it's meaningless to ask what their bounding box is.
 pub trait Renderable {
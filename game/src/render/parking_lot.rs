@@ -30,7 +30,7 @@ impl DrawParkingLot {
             );
         }
         unzoomed_batch.append(
-            GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/parking.svg")
+            GeomBatch::mapspace_icon(prerender, "parking")
                 .scale(0.05)
                 .centered_on(lot.polygon.polylabel()),
         );
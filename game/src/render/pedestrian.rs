@@ -3,7 +3,7 @@ use crate::colors::ColorScheme;
 use crate::helpers::ID;
 use crate::render::{DrawOptions, Renderable, OUTLINE_THICKNESS};
 use ezgui::{Color, Drawable, GeomBatch, GfxCtx, Line, Prerender, Text};
-use geom::{ArrowCap, Circle, Distance, PolyLine, Polygon};
+use geom::{Angle, ArrowCap, Circle, Distance, PolyLine, Polygon, Pt2D};
 use map_model::{Map, SIDEWALK_THICKNESS};
 use sim::{DrawPedCrowdInput, DrawPedestrianInput, PedCrowdLocation, PedestrianID};
 
@@ -12,7 +12,10 @@ pub struct DrawPedestrian {
     body_circle: Circle,
     zorder: isize,
 
+    // Drawn around the origin, facing Angle::ZERO. The shader moves it into place.
     draw_default: Drawable,
+    pos: Pt2D,
+    facing: Angle,
 }
 
 impl DrawPedestrian {
@@ -23,20 +26,34 @@ impl DrawPedestrian {
         prerender: &Prerender,
         cs: &ColorScheme,
     ) -> DrawPedestrian {
+        let (id, pos, facing) = (input.id, input.pos, input.facing);
+        let zorder = input.on.get_zorder(map);
+        let waiting_for_turn = input.waiting_for_turn;
+
+        let origin = Pt2D::new(0.0, 0.0);
         let mut draw_default = GeomBatch::new();
-        DrawPedestrian::geometry(&mut draw_default, cs, &input, step_count);
+        DrawPedestrian::geometry(
+            &mut draw_default,
+            cs,
+            &DrawPedestrianInput {
+                pos: origin,
+                facing: Angle::ZERO,
+                ..input
+            },
+            step_count,
+        );
 
         let radius = SIDEWALK_THICKNESS / 4.0; // TODO make const after const fn is better
-        let body_circle = Circle::new(input.pos, radius);
+        let body_circle = Circle::new(pos, radius);
 
-        if let Some(t) = input.waiting_for_turn {
+        if let Some(t) = waiting_for_turn {
             // A silly idea for peds... use hands to point at their turn?
-            let angle = map.get_t(t).angle();
+            let angle = map.get_t(t).angle() + -facing;
             draw_default.push(
                 cs.turn_arrow,
                 PolyLine::new(vec![
-                    input.pos.project_away(radius / 2.0, angle.opposite()),
-                    input.pos.project_away(radius / 2.0, angle),
+                    origin.project_away(radius / 2.0, angle.opposite()),
+                    origin.project_away(radius / 2.0, angle),
                 ])
                 .make_arrow(Distance::meters(0.15), ArrowCap::Triangle)
                 .unwrap(),
@@ -44,10 +61,12 @@ impl DrawPedestrian {
         }
 
         DrawPedestrian {
-            id: input.id,
+            id,
             body_circle,
-            zorder: input.on.get_zorder(map),
+            zorder,
             draw_default: prerender.upload(draw_default),
+            pos,
+            facing,
         }
     }
 
@@ -174,7 +193,7 @@ impl Renderable for DrawPedestrian {
     }
 
     fn draw(&self, g: &mut GfxCtx, _: &App, _: &DrawOptions) {
-        g.redraw(&self.draw_default);
+        g.redraw_transformed(&self.draw_default, self.pos, self.facing, 1.0);
     }
 
     fn get_outline(&self, _: &Map) -> Polygon {
@@ -1,7 +1,7 @@
 use crate::app::App;
 use crate::colors::ColorScheme;
 use crate::helpers::ID;
-use crate::render::{DrawOptions, Renderable};
+use crate::render::{DrawOptions, MapLayer, Renderable};
 use ezgui::{Drawable, GeomBatch, GfxCtx, Line, Prerender, Text};
 use geom::{Distance, Polygon, Pt2D};
 use map_model::{LaneType, Map, Road, RoadID};
@@ -95,7 +95,8 @@ impl Renderable for DrawRoad {
                 }
                 *label = Some(g.prerender.upload(batch));
             }
-            // TODO Covered up sometimes. We could fork and force a different z value...
+            // Agents and intersections would cover it otherwise
+            g.set_z(MapLayer::Label.at(self.zorder));
             g.redraw(label.as_ref().unwrap());
         }
     }
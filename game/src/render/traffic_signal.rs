@@ -69,7 +69,7 @@ pub fn draw_signal_phase(
                 } else {
                     let (center, angle) = crosswalk_icon(&signal.turn_groups[g].geom);
                     batch.append(
-                        GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/walk.svg")
+                        GeomBatch::mapspace_icon(prerender, "walk")
                             .scale(0.07)
                             .centered_on(center)
                             .rotate(angle)
@@ -81,7 +81,7 @@ pub fn draw_signal_phase(
             for g in dont_walk {
                 let (center, angle) = crosswalk_icon(&signal.turn_groups[g].geom);
                 batch.append(
-                    GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/dont_walk.svg")
+                    GeomBatch::mapspace_icon(prerender, "dont_walk")
                         .scale(0.07)
                         .centered_on(center)
                         .rotate(angle),
@@ -158,7 +158,7 @@ pub fn draw_signal_phase(
                 } else {
                     let (center, angle) = crosswalk_icon(&signal.turn_groups[g].geom);
                     batch.append(
-                        GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/walk.svg")
+                        GeomBatch::mapspace_icon(prerender, "walk")
                             .scale(0.07)
                             .centered_on(center)
                             .rotate(angle),
@@ -169,7 +169,7 @@ pub fn draw_signal_phase(
             for g in dont_walk {
                 let (center, angle) = crosswalk_icon(&signal.turn_groups[g].geom);
                 batch.append(
-                    GeomBatch::mapspace_svg(prerender, "../data/system/assets/map/dont_walk.svg")
+                    GeomBatch::mapspace_icon(prerender, "dont_walk")
                         .scale(0.07)
                         .centered_on(center)
                         .rotate(angle),
@@ -323,6 +323,20 @@ pub fn make_signal_diagram(
         vec![
             txt_widget.margin_below(10),
             Btn::text_bg2("Edit entire signal").build_def(ctx, hotkey(Key::E)),
+            Widget::row(vec![
+                if selected == 0 {
+                    Btn::text_fg("previous phase").inactive(ctx)
+                } else {
+                    Btn::text_fg("previous phase").build_def(ctx, hotkey(Key::UpArrow))
+                }
+                .margin_right(10),
+                if selected == signal.phases.len() - 1 {
+                    Btn::text_fg("next phase").inactive(ctx)
+                } else {
+                    Btn::text_fg("next phase").build_def(ctx, hotkey(Key::DownArrow))
+                },
+            ])
+            .margin_above(10),
         ]
     } else {
         vec![Widget::row(vec![
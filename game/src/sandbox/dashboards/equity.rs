@@ -0,0 +1,198 @@
+use crate::app::App;
+use crate::game::{DrawBaselayer, State, Transition};
+use crate::sandbox::dashboards::DashTab;
+use abstutil::prettyprint_usize;
+use ezgui::{
+    Color, Composite, EventCtx, GeomBatch, GfxCtx, Line, Outcome, Table, TableRow, Text, TextExt,
+    Widget,
+};
+use geom::Duration;
+use sim::{EquityScoreboard, GroupScore};
+use std::cmp::Ordering;
+
+const ROWS: usize = 20;
+
+// Who wins and loses from the current edits?
+pub struct Equity {
+    composite: Composite,
+    summary: Text,
+    table: Table<Row>,
+}
+
+impl Equity {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        let scoreboard = EquityScoreboard::new(&app.primary.sim, app.prebaked());
+
+        let mut summary = Text::new();
+        for attribute in vec!["income", "age", "car ownership"] {
+            if let Some(pct) = scoreboard.equity_weighted_change(attribute) {
+                summary.add(Line(format!(
+                    "Weighting every {} group equally: {:+.1}% trip time",
+                    attribute,
+                    pct * 100.0
+                )));
+            }
+        }
+        if scoreboard
+            .scores
+            .iter()
+            .all(|s| s.group.attribute() == "car ownership")
+        {
+            summary.add(
+                Line("This scenario doesn't have income or age groups, only car ownership.")
+                    .secondary(),
+            );
+        }
+
+        let rows = scoreboard
+            .scores
+            .into_iter()
+            .map(|score| Row {
+                color: color(app, &score),
+                score,
+            })
+            .collect();
+        let table = Table::new(
+            vec![
+                ("Group", true),
+                ("People", true),
+                ("Trips", true),
+                ("Faster / slower", false),
+                ("Average before", true),
+                ("Average after", true),
+                ("Total trip time", true),
+            ],
+            rows,
+            ROWS,
+        );
+
+        Box::new(Equity {
+            composite: make(ctx, app, &summary, &table),
+            summary,
+            table,
+        })
+    }
+}
+
+impl State for Equity {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
+                    let mut new = make(ctx, app, &self.summary, &self.table);
+                    new.restore(ctx, &self.composite);
+                    self.composite = new;
+                    return Transition::Keep;
+                }
+                // Rows of the table are clickable, but don't do anything yet
+                if x.starts_with("group ") {
+                    return Transition::Keep;
+                }
+                DashTab::Equity.transition(ctx, app, &x)
+            }
+            None => Transition::Keep,
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.clear(app.cs.grass);
+        self.composite.draw(g);
+    }
+}
+
+fn make(ctx: &mut EventCtx, app: &App, summary: &Text, table: &Table<Row>) -> Composite {
+    let scores = table.rows();
+    let col = vec![
+        DashTab::Equity.picker(ctx, app),
+        Text::from_multiline(vec![
+            Line("Trips are grouped by who takes them. Everybody is in several groups."),
+            Line("Only trips that finished both before and after the changes are counted.")
+                .secondary(),
+        ])
+        .draw(ctx)
+        .margin_below(10),
+        summary.clone().draw(ctx).margin_below(10),
+        format!(
+            "{} groups got slower, {} got faster",
+            scores
+                .iter()
+                .filter(|r| r.score.pct_change().map(|pct| pct > 0.0).unwrap_or(false))
+                .count(),
+            scores
+                .iter()
+                .filter(|r| r.score.pct_change().map(|pct| pct < 0.0).unwrap_or(false))
+                .count()
+        )
+        .draw_text(ctx)
+        .margin_below(5),
+        table.render(ctx, 0.8 * ctx.canvas.window_width),
+    ];
+    Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
+        .exact_size_percent(90, 90)
+        .build(ctx)
+}
+
+fn color(app: &App, score: &GroupScore) -> Color {
+    match score.pct_change() {
+        Some(pct) if pct < 0.0 => app.cs.good_to_bad_green.eval((-pct / 0.2).min(1.0)),
+        Some(pct) if pct > 0.0 => app.cs.good_to_bad_red.eval((pct / 0.2).min(1.0)),
+        _ => Color::grey(0.6),
+    }
+}
+
+struct Row {
+    score: GroupScore,
+    color: Color,
+}
+
+impl TableRow for Row {
+    fn label(&self) -> String {
+        format!("group {}", self.score.group)
+    }
+
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch> {
+        let fmt_avg = |dt: Option<Duration>| {
+            dt.map(|dt| dt.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let score = &self.score;
+        vec![
+            Text::from(Line(score.group.to_string())).render_ctx(ctx),
+            Text::from(Line(prettyprint_usize(score.num_people))).render_ctx(ctx),
+            Text::from(Line(prettyprint_usize(score.num_trips))).render_ctx(ctx),
+            Text::from(Line(format!(
+                "{} / {}",
+                prettyprint_usize(score.num_faster),
+                prettyprint_usize(score.num_slower)
+            )))
+            .render_ctx(ctx),
+            Text::from(Line(fmt_avg(score.avg_before()))).render_ctx(ctx),
+            Text::from(Line(fmt_avg(score.avg_after()))).render_ctx(ctx),
+            Text::from(match score.pct_change() {
+                Some(pct) => Line(format!("{:+.1}%", pct * 100.0)).fg(self.color),
+                None => Line("-"),
+            })
+            .render_ctx(ctx),
+        ]
+    }
+
+    fn cmp_by(&self, other: &Row, col: usize) -> Ordering {
+        let (a, b) = (&self.score, &other.score);
+        match col {
+            0 => a.group.cmp(&b.group),
+            1 => a.num_people.cmp(&b.num_people),
+            2 => a.num_trips.cmp(&b.num_trips),
+            4 => a.avg_before().cmp(&b.avg_before()),
+            5 => a.avg_after().cmp(&b.avg_after()),
+            6 => a
+                .pct_change()
+                .partial_cmp(&b.pct_change())
+                .unwrap_or(Ordering::Equal),
+            _ => unreachable!(),
+        }
+    }
+}
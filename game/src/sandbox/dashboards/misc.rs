@@ -4,8 +4,10 @@ use crate::game::{msg, DrawBaselayer, State, Transition};
 use crate::sandbox::dashboards::DashTab;
 use crate::sandbox::SandboxMode;
 use ezgui::{
-    Btn, Composite, EventCtx, GfxCtx, Line, LinePlot, Outcome, PlotOptions, Series, Widget,
+    Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, Line, LinePlot, Outcome, PlotOptions,
+    ScreenDims, ScrollingPanel, Series, Text, Widget,
 };
+use geom::Polygon;
 
 pub struct ActiveTraffic {
     composite: Composite,
@@ -65,8 +67,17 @@ impl State for ActiveTraffic {
     }
 }
 
+// There can be hundreds of routes, so they're drawn in a ScrollingPanel instead of as buttons.
 pub struct BusRoutes {
     composite: Composite,
+    panel: ScrollingPanel,
+    // Route names and where each row starts, top to bottom
+    rows: Vec<(String, f64)>,
+    row_dims: ScreenDims,
+    draw_rows: Drawable,
+    hovering: Option<usize>,
+    // The scroll offset when the mouse was pressed, to tell clicks from drags
+    pressed_at: Option<(f64, f64)>,
 }
 
 impl BusRoutes {
@@ -81,18 +92,53 @@ impl BusRoutes {
         // TODO Sort first by length, then lexicographically
         routes.sort();
 
-        let mut col = vec![
-            DashTab::BusRoutes.picker(ctx, app),
-            Line("Bus routes").small_heading().draw(ctx),
-        ];
-        for r in routes {
-            col.push(Btn::text_fg(r).build_def(ctx, None).margin(5));
+        let row_height = ctx.default_line_height() + 10.0;
+        let mut batch = GeomBatch::new();
+        let mut rows = Vec::new();
+        for (idx, r) in routes.into_iter().enumerate() {
+            let y = (idx as f64) * row_height;
+            batch.append(Text::from(Line(&r)).render_ctx(ctx).translate(5.0, y + 5.0));
+            rows.push((r, y));
         }
+        let contents = ScreenDims::new(
+            batch.get_dims().width + 10.0,
+            (rows.len() as f64) * row_height,
+        );
+
+        let composite = Composite::new(
+            Widget::col(vec![
+                DashTab::BusRoutes.picker(ctx, app),
+                Line("Bus routes").small_heading().draw(ctx),
+                // Just reserves space for the panel
+                Widget::draw_batch(
+                    ctx,
+                    GeomBatch::from(vec![(
+                        Color::INVISIBLE,
+                        Polygon::rectangle(
+                            0.8 * ctx.canvas.window_width,
+                            0.7 * ctx.canvas.window_height,
+                        ),
+                    )]),
+                )
+                .named("routes"),
+            ])
+            .bg(app.cs.panel_bg)
+            .padding(10),
+        )
+        .exact_size_percent(90, 90)
+        .build(ctx);
+        let rect = composite.rect_of("routes").clone();
+        let row_dims = ScreenDims::new(contents.width.max(rect.width()), row_height);
+        let panel = ScrollingPanel::new(rect, contents);
 
         Box::new(BusRoutes {
-            composite: Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
-                .exact_size_percent(90, 90)
-                .build(ctx),
+            composite,
+            panel,
+            rows,
+            row_dims,
+            draw_rows: batch.upload(ctx),
+            hovering: None,
+            pressed_at: None,
         })
     }
 }
@@ -101,32 +147,59 @@ impl State for BusRoutes {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
             Some(Outcome::Clicked(x)) => {
-                if let Some(r) = app.primary.map.get_bus_route(&x) {
-                    let buses = app.primary.sim.status_of_buses(r.id);
-                    if buses.is_empty() {
-                        Transition::Push(msg(
-                            "No buses running",
-                            vec![format!("Sorry, no buses for route {} running", r.name)],
-                        ))
-                    } else {
-                        Transition::PopWithData(Box::new(move |state, ctx, app| {
-                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
-                            let mut actions = sandbox.contextual_actions();
-                            sandbox.controls.common.as_mut().unwrap().launch_info_panel(
-                                ctx,
-                                app,
-                                // Arbitrarily use the first one
-                                Tab::BusStatus(buses[0].0),
-                                &mut actions,
-                            );
-                        }))
-                    }
+                return DashTab::BusRoutes.transition(ctx, app, &x);
+            }
+            None => {}
+        }
+        if ctx.input.is_window_resized() {
+            self.panel
+                .set_rect(self.composite.rect_of("routes").clone());
+        }
+
+        if ctx.input.left_mouse_button_pressed() {
+            self.pressed_at = Some(self.panel.get_offset());
+        }
+        let clicked = ctx.input.left_mouse_button_released()
+            && self.pressed_at.take() == Some(self.panel.get_offset());
+        self.panel.event(ctx);
+
+        if ctx.redo_mouseover() {
+            let row_height = self.row_dims.height;
+            self.hovering = self.panel.get_cursor_in_contents(ctx).and_then(|pt| {
+                let idx = (pt.y / row_height) as usize;
+                if idx < self.rows.len() {
+                    Some(idx)
                 } else {
-                    DashTab::BusRoutes.transition(ctx, app, &x)
+                    None
                 }
+            });
+        }
+
+        if let Some(idx) = self.hovering {
+            if clicked {
+                let r = app.primary.map.get_bus_route(&self.rows[idx].0).unwrap();
+                let buses = app.primary.sim.status_of_buses(r.id);
+                if buses.is_empty() {
+                    return Transition::Push(msg(
+                        "No buses running",
+                        vec![format!("Sorry, no buses for route {} running", r.name)],
+                    ));
+                }
+                return Transition::PopWithData(Box::new(move |state, ctx, app| {
+                    let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
+                    let mut actions = sandbox.contextual_actions();
+                    sandbox.controls.common.as_mut().unwrap().launch_info_panel(
+                        ctx,
+                        app,
+                        // Arbitrarily use the first one
+                        Tab::BusStatus(buses[0].0),
+                        &mut actions,
+                    );
+                }));
             }
-            None => Transition::Keep,
         }
+
+        Transition::Keep
     }
 
     fn draw_baselayer(&self) -> DrawBaselayer {
@@ -136,5 +209,15 @@ impl State for BusRoutes {
     fn draw(&self, g: &mut GfxCtx, app: &App) {
         g.clear(app.cs.grass);
         self.composite.draw(g);
+        self.panel.draw(g, |g| {
+            if let Some(idx) = self.hovering {
+                g.draw_polygon(
+                    app.cs.hovering,
+                    &Polygon::rectangle(self.row_dims.width, self.row_dims.height)
+                        .translate(0.0, self.rows[idx].1),
+                );
+            }
+            g.redraw(&self.draw_rows);
+        });
     }
 }
@@ -1,11 +1,16 @@
+mod equity;
 mod misc;
+mod neighborhoods;
 mod parking_overhead;
+mod reliability;
+mod report;
 mod summaries;
 mod trip_table;
 
 use crate::app::App;
 use crate::game::Transition;
-use ezgui::{hotkey, Btn, Color, EventCtx, Key, Widget};
+use ezgui::{hotkey, Btn, Choice, Color, EventCtx, Key, Widget};
+use sim::{DemographicGroup, TripID};
 pub use trip_table::TripTable;
 
 // Oh the dashboards melted, but we still had the radio
@@ -13,7 +18,10 @@ pub use trip_table::TripTable;
 pub enum DashTab {
     TripTable,
     TripSummaries,
+    Neighborhoods,
+    Equity,
     ParkingOverhead,
+    Reliability,
     ActiveTraffic,
     BusRoutes,
 }
@@ -24,11 +32,18 @@ impl DashTab {
         for (name, tab) in vec![
             ("trip table", DashTab::TripTable),
             ("trip summaries", DashTab::TripSummaries),
+            ("neighborhoods", DashTab::Neighborhoods),
+            ("equity", DashTab::Equity),
             ("parking overhead", DashTab::ParkingOverhead),
+            ("reliability", DashTab::Reliability),
             ("active traffic", DashTab::ActiveTraffic),
             ("bus routes", DashTab::BusRoutes),
         ] {
-            if tab == DashTab::TripSummaries && app.has_prebaked().is_none() {
+            if (tab == DashTab::TripSummaries
+                || tab == DashTab::Neighborhoods
+                || tab == DashTab::Equity)
+                && app.has_prebaked().is_none()
+            {
                 continue;
             }
             if self == tab {
@@ -56,12 +71,34 @@ impl DashTab {
                 app,
                 summaries::Filter::new(),
             )),
+            "neighborhoods" => Transition::Replace(neighborhoods::Neighborhoods::new(ctx, app)),
+            "equity" => Transition::Replace(equity::Equity::new(ctx, app)),
             "parking overhead" => {
                 Transition::Replace(parking_overhead::ParkingOverhead::new(ctx, app))
             }
+            "reliability" => Transition::Replace(reliability::TravelTimeReliability::new(ctx, app)),
             "active traffic" => Transition::Replace(misc::ActiveTraffic::new(ctx, app)),
             "bus routes" => Transition::Replace(misc::BusRoutes::new(ctx, app)),
             _ => unreachable!(),
         }
     }
 }
+
+// Lets the trip dashboards only look at trips taken by one demographic group
+fn group_dropdown(ctx: &EventCtx, current: Option<DemographicGroup>) -> Widget {
+    let mut choices = vec![Choice::new("everyone", None)];
+    for g in DemographicGroup::all() {
+        choices.push(Choice::new(g.to_string(), Some(g)));
+    }
+    Widget::dropdown(ctx, "group", current, choices)
+}
+
+fn in_group(app: &App, trip: TripID, group: Option<DemographicGroup>) -> bool {
+    match group {
+        Some(g) => {
+            let sim = &app.primary.sim;
+            g.contains(&sim.get_person(sim.trip_to_person(trip)).demographics)
+        }
+        None => true,
+    }
+}
@@ -0,0 +1,292 @@
+use crate::app::App;
+use crate::common::ColorLegend;
+use crate::game::{DrawBaselayer, State, Transition};
+use crate::sandbox::dashboards::DashTab;
+use abstutil::prettyprint_usize;
+use ezgui::{
+    Color, Composite, DrawWithTooltips, EventCtx, GeomBatch, GfxCtx, Line, Outcome, Table,
+    TableRow, Text, TextExt, Widget,
+};
+use geom::{Distance, Duration, Polygon};
+use sim::{NeighborhoodScore, NeighborhoodScoreboard, TripCost};
+use std::cmp::Ordering;
+
+// Past this much change, colors stop getting more intense
+const MAX_PCT_CHANGE: f64 = 0.2;
+const ROWS: usize = 20;
+
+// Which parts of the map win and lose from the current edits?
+pub struct Neighborhoods {
+    composite: Composite,
+    table: Table<Row>,
+    // How many people have spent something on their trips so far, and the total
+    people_spending: (usize, f64),
+}
+
+impl Neighborhoods {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        let scoreboard =
+            NeighborhoodScoreboard::new(&app.primary.map, &app.primary.sim, app.prebaked());
+        let costs = app.primary.sim.get_trip_costs();
+        let mut spent = costs.by_neighborhood(&app.primary.map, &app.primary.sim);
+        let by_person = costs.by_person(&app.primary.sim);
+        let people_spending = (
+            by_person.values().filter(|c| c.total() > 0.0).count(),
+            by_person.values().map(|c| c.total()).sum(),
+        );
+        let rows = scoreboard
+            .scores
+            .into_iter()
+            .map(|score| Row {
+                spent: spent.remove(&score.name).unwrap_or_default(),
+                color: color(app, &score),
+                delay_color: if score.delay_after > score.delay_before {
+                    app.cs.good_to_bad_red.eval(1.0)
+                } else {
+                    app.cs.good_to_bad_green.eval(1.0)
+                },
+                score,
+            })
+            .collect();
+        let mut table = Table::new(
+            vec![
+                ("Neighborhood", true),
+                ("Trips", true),
+                ("Average before", true),
+                ("Average after", true),
+                ("Total trip time", true),
+                ("Intersection delay", true),
+                ("Spent", true),
+            ],
+            rows,
+            ROWS,
+        );
+        // Biggest losers first, then neighborhoods without any trips
+        table.sort_by(4, true);
+
+        Box::new(Neighborhoods {
+            composite: make(ctx, app, &table, people_spending),
+            table,
+            people_spending,
+        })
+    }
+}
+
+impl State for Neighborhoods {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
+                    let mut new = make(ctx, app, &self.table, self.people_spending);
+                    new.restore(ctx, &self.composite);
+                    self.composite = new;
+                    return Transition::Keep;
+                }
+                // Rows of the table are clickable, but don't do anything yet
+                if x.starts_with("neighborhood ") {
+                    return Transition::Keep;
+                }
+                DashTab::Neighborhoods.transition(ctx, app, &x)
+            }
+            None => Transition::Keep,
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.clear(app.cs.grass);
+        self.composite.draw(g);
+    }
+}
+
+fn make(
+    ctx: &mut EventCtx,
+    app: &App,
+    table: &Table<Row>,
+    (num_people, total_spent): (usize, f64),
+) -> Composite {
+    let rows = table.rows();
+    Composite::new(
+        Widget::col(vec![
+            DashTab::Neighborhoods.picker(ctx, app),
+            Text::from_multiline(vec![
+                Line("Trips are grouped by where they start."),
+                Line("Only trips that finished both before and after the changes are counted.")
+                    .secondary(),
+                Line(format!(
+                    "So far, {} people have spent ${:.2} on fuel, parking, tolls, and fares, \
+                     counting every trip.",
+                    prettyprint_usize(num_people),
+                    total_spent
+                ))
+                .secondary(),
+            ])
+            .draw(ctx)
+            .margin_below(10),
+            Widget::row(vec![
+                Widget::col(vec![
+                    choropleth(ctx, app, rows).margin_below(10),
+                    ColorLegend::gradient(
+                        ctx,
+                        &app.cs.good_to_bad_green,
+                        vec![
+                            "0%".to_string(),
+                            format!("{}% faster", MAX_PCT_CHANGE * 100.0),
+                        ],
+                    ),
+                    ColorLegend::gradient(
+                        ctx,
+                        &app.cs.good_to_bad_red,
+                        vec![
+                            "0%".to_string(),
+                            format!("{}% slower", MAX_PCT_CHANGE * 100.0),
+                        ],
+                    ),
+                ])
+                .margin_right(20),
+                Widget::col(vec![
+                    format!(
+                        "{} neighborhoods got slower, {} got faster",
+                        rows.iter()
+                            .filter(|r| r.score.pct_change().map(|pct| pct > 0.0).unwrap_or(false))
+                            .count(),
+                        rows.iter()
+                            .filter(|r| r.score.pct_change().map(|pct| pct < 0.0).unwrap_or(false))
+                            .count()
+                    )
+                    .draw_text(ctx)
+                    .margin_below(5),
+                    table.render(ctx, 0.45 * ctx.canvas.window_width),
+                ]),
+            ]),
+        ])
+        .bg(app.cs.panel_bg)
+        .padding(10),
+    )
+    .exact_size_percent(90, 90)
+    .build(ctx)
+}
+
+fn color(app: &App, score: &NeighborhoodScore) -> Color {
+    match score.pct_change() {
+        Some(pct) if pct < 0.0 => app
+            .cs
+            .good_to_bad_green
+            .eval((-pct / MAX_PCT_CHANGE).min(1.0)),
+        Some(pct) if pct > 0.0 => app.cs.good_to_bad_red.eval((pct / MAX_PCT_CHANGE).min(1.0)),
+        Some(_) => Color::grey(0.6),
+        None => Color::grey(0.3),
+    }
+}
+
+fn choropleth(ctx: &mut EventCtx, app: &App, rows: &Vec<Row>) -> Widget {
+    let map = &app.primary.map;
+    let bounds = map.get_bounds();
+    let zoom = 0.35 * ctx.canvas.window_width / bounds.width().max(bounds.height());
+    let transform = |p: &Polygon| p.translate(-bounds.min_x, -bounds.min_y).scale(zoom);
+
+    let mut batch = GeomBatch::new();
+    batch.autocrop_dims = false;
+    batch.push(app.cs.map_background, transform(map.get_boundary_polygon()));
+    let mut tooltips = Vec::new();
+    for row in rows {
+        let score = &row.score;
+        let poly = transform(&score.polygon);
+        batch.push(row.color.alpha(0.8), poly.clone());
+        batch.push(Color::BLACK, poly.to_outline(Distance::meters(1.0)));
+
+        let mut txt = Text::from(Line(&score.name).small_heading());
+        txt.add(Line(format!(
+            "{} trips starting here",
+            prettyprint_usize(score.num_trips)
+        )));
+        if let Some(pct) = score.pct_change() {
+            txt.add(Line(format!(
+                "{} faster, {} slower, {:.1}% change overall",
+                prettyprint_usize(score.num_faster),
+                prettyprint_usize(score.num_slower),
+                pct * 100.0
+            )));
+        }
+        txt.add(Line(format!(
+            "${:.2} spent by trips starting here",
+            row.spent.total()
+        )));
+        tooltips.push((poly, txt));
+    }
+    // Roads on top, for orientation
+    for r in map.all_roads() {
+        batch.push(
+            Color::grey(0.2).alpha(0.5),
+            transform(&r.center_pts.make_polygons(Distance::meters(5.0))),
+        );
+    }
+
+    DrawWithTooltips::new(ctx, batch, tooltips)
+}
+
+struct Row {
+    score: NeighborhoodScore,
+    // Every trip starting here, not just the finished ones
+    spent: TripCost,
+    color: Color,
+    // Only used when the delay changed
+    delay_color: Color,
+}
+
+impl TableRow for Row {
+    fn label(&self) -> String {
+        format!("neighborhood {}", self.score.name)
+    }
+
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch> {
+        let fmt_avg = |dt: Option<Duration>| {
+            dt.map(|dt| dt.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let score = &self.score;
+        vec![
+            Text::from(Line(&score.name)).render_ctx(ctx),
+            Text::from(Line(prettyprint_usize(score.num_trips))).render_ctx(ctx),
+            Text::from(Line(fmt_avg(score.avg_before()))).render_ctx(ctx),
+            Text::from(Line(fmt_avg(score.avg_after()))).render_ctx(ctx),
+            Text::from(match score.pct_change() {
+                Some(pct) => Line(format!("{:+.1}%", pct * 100.0)).fg(self.color),
+                None => Line("-"),
+            })
+            .render_ctx(ctx),
+            Text::from(if score.delay_after > score.delay_before {
+                Line(format!("+{}", score.delay_after - score.delay_before)).fg(self.delay_color)
+            } else if score.delay_after < score.delay_before {
+                Line(format!("-{}", score.delay_before - score.delay_after)).fg(self.delay_color)
+            } else {
+                Line("same")
+            })
+            .render_ctx(ctx),
+            Text::from(Line(format!("${:.2}", self.spent.total()))).render_ctx(ctx),
+        ]
+    }
+
+    fn cmp_by(&self, other: &Row, col: usize) -> Ordering {
+        let (a, b) = (&self.score, &other.score);
+        // Neighborhoods without any trips go last
+        let pct = |s: &NeighborhoodScore| s.pct_change().unwrap_or(std::f64::NEG_INFINITY);
+        match col {
+            0 => a.name.cmp(&b.name),
+            1 => a.num_trips.cmp(&b.num_trips),
+            2 => a.avg_before().cmp(&b.avg_before()),
+            3 => a.avg_after().cmp(&b.avg_after()),
+            4 => pct(a).partial_cmp(&pct(b)).unwrap_or(Ordering::Equal),
+            5 => (a.delay_after - a.delay_before).cmp(&(b.delay_after - b.delay_before)),
+            6 => self
+                .spent
+                .total()
+                .partial_cmp(&other.spent.total())
+                .unwrap_or(Ordering::Equal),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,74 +1,59 @@
 use crate::app::App;
 use crate::game::{DrawBaselayer, State, Transition};
-use crate::info::{OpenTrip, Tab};
-use crate::sandbox::dashboards::trip_table::{make_table, preview_trip};
+use crate::sandbox::dashboards::trip_table::{open_trip, preview_trip};
 use crate::sandbox::dashboards::DashTab;
-use crate::sandbox::SandboxMode;
-use abstutil::prettyprint_usize;
 use ezgui::{
-    Btn, Checkbox, Composite, EventCtx, Filler, GfxCtx, Line, Outcome, ScreenDims, Text, TextExt,
-    Widget,
+    Checkbox, Composite, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome, ScreenDims, Table,
+    TableRow, Text, Widget,
 };
 use geom::Duration;
 use sim::{TripEndpoint, TripID, TripPhaseType};
+use std::cmp::Ordering;
 
 const ROWS: usize = 20;
 
-// TODO Mostly dupliclated code with trip_table. Find the right generalization.
 // TODO Compare all of these things before/after
 // TODO Filter out border trips
 
 pub struct ParkingOverhead {
     composite: Composite,
-    opts: Options,
+    table: Table<Entry>,
+    filters: Filters,
 }
 
-struct Options {
-    sort_by: SortBy,
-    descending: bool,
+struct Filters {
     off_map_starts: bool,
     off_map_ends: bool,
-    skip: usize,
-}
-
-impl Options {
-    fn change(&mut self, value: SortBy) {
-        self.skip = 0;
-        if self.sort_by == value {
-            self.descending = !self.descending;
-        } else {
-            self.sort_by = value;
-            self.descending = true;
-        }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum SortBy {
-    TotalDuration,
-    DrivingDuration,
-    ParkingDuration,
-    WalkingDuration,
-    PercentOverhead,
 }
 
 impl ParkingOverhead {
     pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
-        let opts = Options {
-            sort_by: SortBy::PercentOverhead,
-            descending: true,
+        let filters = Filters {
             off_map_starts: true,
             off_map_ends: true,
-            skip: 0,
         };
+        let mut table = Table::new(
+            vec![
+                ("Trip ID", false),
+                ("Total duration", true),
+                ("Driving duration", true),
+                ("Parking duration", true),
+                ("Walking duration", true),
+                ("Percent overhead", true),
+            ],
+            gather(app, &filters),
+            ROWS,
+        );
+        table.sort_by(5, true);
         Box::new(ParkingOverhead {
-            composite: make(ctx, app, &opts),
-            opts,
+            composite: make(ctx, app, &filters, &table),
+            table,
+            filters,
         })
     }
 
     fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
-        let mut new = make(ctx, app, &self.opts);
+        let mut new = make(ctx, app, &self.filters, &self.table);
         new.restore(ctx, &self.composite);
         self.composite = new;
     }
@@ -77,62 +62,24 @@ impl ParkingOverhead {
 impl State for ParkingOverhead {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
-            Some(Outcome::Clicked(x)) => match x.as_ref() {
-                "Total duration" => {
-                    self.opts.change(SortBy::TotalDuration);
-                    self.recalc(ctx, app);
-                }
-                "Driving duration" => {
-                    self.opts.change(SortBy::DrivingDuration);
-                    self.recalc(ctx, app);
-                }
-                "Parking duration" => {
-                    self.opts.change(SortBy::ParkingDuration);
-                    self.recalc(ctx, app);
-                }
-                "Walking duration" => {
-                    self.opts.change(SortBy::WalkingDuration);
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
                     self.recalc(ctx, app);
+                } else if let Ok(idx) = x.parse::<usize>() {
+                    return open_trip(app, TripID(idx));
+                } else {
+                    return DashTab::ParkingOverhead.transition(ctx, app, &x);
                 }
-                "Percent overhead" => {
-                    self.opts.change(SortBy::PercentOverhead);
-                    self.recalc(ctx, app);
-                }
-                "previous trips" => {
-                    self.opts.skip -= ROWS;
-                    self.recalc(ctx, app);
-                }
-                "next trips" => {
-                    self.opts.skip += ROWS;
-                    self.recalc(ctx, app);
-                }
-                x => {
-                    if let Ok(idx) = x.parse::<usize>() {
-                        let trip = TripID(idx);
-                        let person = app.primary.sim.trip_to_person(trip);
-                        return Transition::PopWithData(Box::new(move |state, ctx, app| {
-                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
-                            let mut actions = sandbox.contextual_actions();
-                            sandbox.controls.common.as_mut().unwrap().launch_info_panel(
-                                ctx,
-                                app,
-                                Tab::PersonTrips(person, OpenTrip::single(trip)),
-                                &mut actions,
-                            );
-                        }));
-                    }
-                    return DashTab::ParkingOverhead.transition(ctx, app, x);
-                }
-            },
+            }
             None => {
                 let off_map_starts = self.composite.is_checked("starting off-map");
                 let off_map_ends = self.composite.is_checked("ending off-map");
-                if self.opts.off_map_starts != off_map_starts
-                    || self.opts.off_map_ends != off_map_ends
+                if self.filters.off_map_starts != off_map_starts
+                    || self.filters.off_map_ends != off_map_ends
                 {
-                    self.opts.off_map_starts = off_map_starts;
-                    self.opts.off_map_ends = off_map_ends;
-                    self.opts.skip = 0;
+                    self.filters.off_map_starts = off_map_starts;
+                    self.filters.off_map_ends = off_map_ends;
+                    self.table.set_rows(gather(app, &self.filters));
                     self.recalc(ctx, app);
                 }
             }
@@ -161,17 +108,44 @@ struct Entry {
     percent_overhead: usize,
 }
 
-fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
-    // Gather raw data
+impl TableRow for Entry {
+    fn label(&self) -> String {
+        self.trip.0.to_string()
+    }
+
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch> {
+        vec![
+            Text::from(Line(self.trip.0.to_string())).render_ctx(ctx),
+            Text::from(Line(self.total_duration.to_string())).render_ctx(ctx),
+            Text::from(Line(self.driving_duration.to_string())).render_ctx(ctx),
+            Text::from(Line(self.parking_duration.to_string())).render_ctx(ctx),
+            Text::from(Line(self.walking_duration.to_string())).render_ctx(ctx),
+            Text::from(Line(format!("{}%", self.percent_overhead))).render_ctx(ctx),
+        ]
+    }
+
+    fn cmp_by(&self, other: &Entry, col: usize) -> Ordering {
+        match col {
+            1 => self.total_duration.cmp(&other.total_duration),
+            2 => self.driving_duration.cmp(&other.driving_duration),
+            3 => self.parking_duration.cmp(&other.parking_duration),
+            4 => self.walking_duration.cmp(&other.walking_duration),
+            5 => self.percent_overhead.cmp(&other.percent_overhead),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn gather(app: &App, filters: &Filters) -> Vec<Entry> {
     let mut data = Vec::new();
     for (id, phases) in app.primary.sim.get_analytics().get_all_trip_phases() {
         let (_, start, end, _) = app.primary.sim.trip_info(id);
-        if !opts.off_map_starts {
+        if !filters.off_map_starts {
             if let TripEndpoint::Border(_, _) = start {
                 continue;
             }
         }
-        if !opts.off_map_ends {
+        if !filters.off_map_ends {
             if let TripEndpoint::Border(_, _) = end {
                 continue;
             }
@@ -216,57 +190,10 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
             percent_overhead: (100.0 * (1.0 - (driving_duration / total_duration))) as usize,
         });
     }
+    data
+}
 
-    // Sort
-    match opts.sort_by {
-        SortBy::TotalDuration => data.sort_by_key(|x| x.total_duration),
-        SortBy::DrivingDuration => data.sort_by_key(|x| x.driving_duration),
-        SortBy::ParkingDuration => data.sort_by_key(|x| x.parking_duration),
-        SortBy::WalkingDuration => data.sort_by_key(|x| x.walking_duration),
-        SortBy::PercentOverhead => data.sort_by_key(|x| x.percent_overhead),
-    }
-    if opts.descending {
-        data.reverse();
-    }
-    let total_rows = data.len();
-
-    // Render data
-    let mut rows = Vec::new();
-    for x in data.into_iter().skip(opts.skip).take(ROWS) {
-        rows.push((
-            x.trip.0.to_string(),
-            vec![
-                Text::from(Line(x.trip.0.to_string())).render_ctx(ctx),
-                Text::from(Line(x.total_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.driving_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.parking_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.walking_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(format!("{}%", x.percent_overhead))).render_ctx(ctx),
-            ],
-        ));
-    }
-
-    let btn = |value, name| {
-        if opts.sort_by == value {
-            Btn::text_bg2(format!(
-                "{} {}",
-                name,
-                if opts.descending { "↓" } else { "↑" }
-            ))
-            .build(ctx, name, None)
-        } else {
-            Btn::text_bg2(name).build_def(ctx, None)
-        }
-    };
-    let headers = vec![
-        Line("Trip ID").draw(ctx),
-        btn(SortBy::TotalDuration, "Total duration"),
-        btn(SortBy::DrivingDuration, "Driving duration"),
-        btn(SortBy::ParkingDuration, "Parking duration"),
-        btn(SortBy::WalkingDuration, "Walking duration"),
-        btn(SortBy::PercentOverhead, "Percent overhead"),
-    ];
-
+fn make(ctx: &mut EventCtx, app: &App, filters: &Filters, table: &Table<Entry>) -> Composite {
     let mut col = vec![DashTab::ParkingOverhead.picker(ctx, app)];
     col.push(
         Widget::row(vec![
@@ -301,47 +228,12 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
     );
     col.push(
         Widget::row(vec![
-            Checkbox::text(ctx, "starting off-map", None, opts.off_map_starts).margin_right(10),
-            Checkbox::text(ctx, "ending off-map", None, opts.off_map_ends),
+            Checkbox::text(ctx, "starting off-map", None, filters.off_map_starts).margin_right(10),
+            Checkbox::text(ctx, "ending off-map", None, filters.off_map_ends),
         ])
         .margin_below(5),
     );
-    col.push(
-        Widget::row(vec![
-            if opts.skip > 0 {
-                Btn::text_fg("<").build(ctx, "previous trips", None)
-            } else {
-                Btn::text_fg("<").inactive(ctx)
-            }
-            .margin_right(10),
-            format!(
-                "{}-{} of {}",
-                if total_rows > 0 {
-                    prettyprint_usize(opts.skip + 1)
-                } else {
-                    "0".to_string()
-                },
-                prettyprint_usize((opts.skip + 1 + ROWS).min(total_rows)),
-                prettyprint_usize(total_rows)
-            )
-            .draw_text(ctx)
-            .margin_right(10),
-            if opts.skip + 1 + ROWS < total_rows {
-                Btn::text_fg(">").build(ctx, "next trips", None)
-            } else {
-                Btn::text_fg(">").inactive(ctx)
-            },
-        ])
-        .margin_below(5),
-    );
-
-    col.extend(make_table(
-        ctx,
-        app,
-        headers,
-        rows,
-        0.88 * ctx.canvas.window_width,
-    ));
+    col.push(table.render(ctx, 0.88 * ctx.canvas.window_width));
 
     Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
         .exact_size_percent(90, 90)
@@ -0,0 +1,179 @@
+use crate::app::App;
+use crate::game::{DrawBaselayer, State, Transition};
+use crate::sandbox::dashboards::DashTab;
+use abstutil::prettyprint_usize;
+use ezgui::{
+    Checkbox, Composite, EventCtx, GeomBatch, GfxCtx, Line, Outcome, Table, TableRow, Text, Widget,
+};
+use sim::{Reliability, TravelTimeSamples};
+use std::cmp::Ordering;
+
+const ROWS: usize = 20;
+// The tail of a handful of trips is just noise
+const MIN_SAMPLES: usize = 5;
+
+// How unpredictable are trips between neighborhoods, or drives down one road?
+pub struct TravelTimeReliability {
+    composite: Composite,
+    table: Table<Row>,
+    samples: TravelTimeSamples,
+    by_road: bool,
+}
+
+impl TravelTimeReliability {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        let samples = TravelTimeSamples::new(&app.primary.map, &app.primary.sim);
+        let by_road = false;
+        let mut table = Table::new(
+            vec![
+                ("Where", false),
+                ("Samples", true),
+                ("Mean", true),
+                ("Median", true),
+                ("95th percentile", true),
+                ("Buffer index", true),
+            ],
+            gather(app, &samples, by_road),
+            ROWS,
+        );
+        // Least reliable first
+        table.sort_by(5, true);
+        Box::new(TravelTimeReliability {
+            composite: make(ctx, app, &table, by_road),
+            table,
+            samples,
+            by_road,
+        })
+    }
+
+    fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
+        let mut new = make(ctx, app, &self.table, self.by_road);
+        new.restore(ctx, &self.composite);
+        self.composite = new;
+    }
+}
+
+impl State for TravelTimeReliability {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
+                    self.recalc(ctx, app);
+                } else if x.starts_with("reliability of ") {
+                    // Rows of the table are clickable, but don't do anything yet
+                } else {
+                    return DashTab::Reliability.transition(ctx, app, &x);
+                }
+            }
+            None => {
+                let by_road = self.composite.is_checked("group by road");
+                if by_road != self.by_road {
+                    self.by_road = by_road;
+                    self.table
+                        .set_rows(gather(app, &self.samples, self.by_road));
+                    self.recalc(ctx, app);
+                }
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.clear(app.cs.grass);
+        self.composite.draw(g);
+    }
+}
+
+struct Row {
+    name: String,
+    reliability: Reliability,
+}
+
+impl TableRow for Row {
+    fn label(&self) -> String {
+        format!("reliability of {}", self.name)
+    }
+
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch> {
+        let r = &self.reliability;
+        vec![
+            Text::from(Line(&self.name)).render_ctx(ctx),
+            Text::from(Line(prettyprint_usize(r.count))).render_ctx(ctx),
+            Text::from(Line(r.mean.to_string())).render_ctx(ctx),
+            Text::from(Line(r.p50.to_string())).render_ctx(ctx),
+            Text::from(Line(r.p95.to_string())).render_ctx(ctx),
+            Text::from(Line(format!("{:.0}%", r.buffer_index * 100.0))).render_ctx(ctx),
+        ]
+    }
+
+    fn cmp_by(&self, other: &Row, col: usize) -> Ordering {
+        let (a, b) = (&self.reliability, &other.reliability);
+        match col {
+            1 => a.count.cmp(&b.count),
+            2 => a.mean.cmp(&b.mean),
+            3 => a.p50.cmp(&b.p50),
+            4 => a.p95.cmp(&b.p95),
+            5 => a
+                .buffer_index
+                .partial_cmp(&b.buffer_index)
+                .unwrap_or(Ordering::Equal),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn gather(app: &App, samples: &TravelTimeSamples, by_road: bool) -> Vec<Row> {
+    if by_road {
+        let map = &app.primary.map;
+        samples
+            .corridor_reliability(MIN_SAMPLES)
+            .into_iter()
+            .map(|(r, reliability)| Row {
+                name: format!("{} ({})", map.get_r(r).get_name(), r),
+                reliability,
+            })
+            .collect()
+    } else {
+        samples
+            .od_reliability(MIN_SAMPLES)
+            .into_iter()
+            .map(|((from, to, mode), reliability)| Row {
+                name: format!("{} from {} to {}", mode.ongoing_verb(), from, to),
+                reliability,
+            })
+            .collect()
+    }
+}
+
+fn make(ctx: &mut EventCtx, app: &App, table: &Table<Row>, by_road: bool) -> Composite {
+    Composite::new(
+        Widget::col(vec![
+            DashTab::Reliability.picker(ctx, app),
+            Text::from_multiline(vec![
+                Line("Averages hide how unpredictable a trip is."),
+                Line(
+                    "The buffer index is how much extra time, relative to the mean, somebody has \
+                     to budget to arrive on time 95% of the time.",
+                ),
+                Line(format!(
+                    "Only finished trips count, and groups with fewer than {} samples are skipped.",
+                    MIN_SAMPLES
+                ))
+                .secondary(),
+            ])
+            .draw(ctx)
+            .margin_below(10),
+            Checkbox::text(ctx, "group by road", None, by_road).margin_below(5),
+            table.render(ctx, 0.88 * ctx.canvas.window_width),
+        ])
+        .bg(app.cs.panel_bg)
+        .padding(10),
+    )
+    .exact_size_percent(90, 90)
+    .build(ctx)
+}
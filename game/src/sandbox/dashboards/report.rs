@@ -0,0 +1,269 @@
+use crate::app::{App, ShowEverything};
+use crate::edit::apply_map_edits;
+use crate::game::{msg, DrawBaselayer, State, Transition};
+use crate::render::DrawOptions;
+use abstutil::{prettyprint_usize, Timer};
+use ezgui::{EventCtx, EventLoopMode, GfxCtx};
+use geom::Duration;
+use map_model::MapEdits;
+use sim::{DontDrawAgents, TripMode};
+use std::fs::File;
+use std::io::{Error, Write};
+
+const BEFORE: &str = "before.png";
+const AFTER: &str = "after.png";
+
+// Screenshots the current view of the map without the proposal's edits, then with them, and then
+// writes the report. The map is briefly reverted in between, but the simulation isn't stepped
+// while that happens.
+pub struct ExportReport {
+    edits: MapEdits,
+    dir: String,
+    step: Step,
+}
+
+enum Step {
+    Start,
+    ShowingBefore,
+    ShowingAfter,
+}
+
+impl ExportReport {
+    pub fn new(app: &App) -> Box<dyn State> {
+        let map = &app.primary.map;
+        Box::new(ExportReport {
+            edits: map.get_edits().clone(),
+            dir: abstutil::path_reports(map.get_name(), &map.get_edits().edits_name),
+            step: Step::Start,
+        })
+    }
+}
+
+impl State for ExportReport {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.step {
+            Step::Start => {
+                // Nothing to compare
+                if self.edits.commands.is_empty() {
+                    return finish(app);
+                }
+                if let Err(err) = std::fs::create_dir_all(&self.dir) {
+                    return Transition::Replace(msg(
+                        "Export failed",
+                        vec![format!("Couldn't create {}: {}", self.dir, err)],
+                    ));
+                }
+                apply_map_edits(ctx, app, MapEdits::new());
+                self.step = Step::ShowingBefore;
+            }
+            Step::ShowingBefore => {
+                apply_map_edits(ctx, app, self.edits.clone());
+                // The edits are the same as before, but the map doesn't know that
+                app.primary
+                    .map
+                    .recalculate_pathfinding_after_edits(&mut Timer::throwaway());
+                self.step = Step::ShowingAfter;
+            }
+            Step::ShowingAfter => {
+                return finish(app);
+            }
+        }
+        // Keep events coming, so each screenshot gets drawn before moving on
+        Transition::KeepWithMode(EventLoopMode::Animation)
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        let file = match self.step {
+            Step::Start => {
+                return;
+            }
+            Step::ShowingBefore => BEFORE,
+            Step::ShowingAfter => AFTER,
+        };
+        app.draw(
+            g,
+            DrawOptions::new(),
+            &DontDrawAgents {},
+            &ShowEverything::new(),
+        );
+        g.screenshot_current_frame(format!("{}/{}", self.dir, file));
+    }
+}
+
+fn finish(app: &App) -> Transition {
+    Transition::Replace(match export_report(app) {
+        Ok(path) => msg(
+            "Report exported",
+            vec![
+                format!("Wrote {}", path),
+                "It shows the current view before and after the edits. Other screenshots saved \
+                 in that directory are included too."
+                    .to_string(),
+            ],
+        ),
+        Err(err) => msg(
+            "Export failed",
+            vec![format!("Couldn't write the report: {}", err)],
+        ),
+    })
+}
+
+// Bundle the current proposal into a standalone HTML page, suitable for attaching to a public
+// comment. Before and after screenshots from ExportReport go first, then any other .png or .gif
+// screenshots already in the report's directory. Returns the path written.
+fn export_report(app: &App) -> Result<String, Error> {
+    let map = &app.primary.map;
+    let edits = map.get_edits();
+    let dir = abstutil::path_reports(map.get_name(), &edits.edits_name);
+    std::fs::create_dir_all(&dir)?;
+    let path = format!("{}/report.html", dir);
+    let mut f = File::create(&path)?;
+
+    writeln!(f, "<!DOCTYPE html>")?;
+    writeln!(f, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(
+        f,
+        "<title>{}</title>",
+        escape(&format!("{} on {}", edits.edits_name, map.get_name()))
+    )?;
+    writeln!(
+        f,
+        "<style>body {{ font-family: sans-serif; max-width: 60em; margin: auto; }} table {{ \
+         border-collapse: collapse; }} td, th {{ border: 1px solid #999; padding: 4px 8px; \
+         }}</style>"
+    )?;
+    writeln!(f, "</head><body>")?;
+    writeln!(f, "<h1>{}</h1>", escape(&edits.edits_name))?;
+    writeln!(
+        f,
+        "<p>Map: {}. Simulated until {}.</p>",
+        escape(map.get_name()),
+        app.primary.sim.time()
+    )?;
+    for line in &edits.proposal_description {
+        writeln!(f, "<p>{}</p>", escape(line))?;
+    }
+    if let Some(ref link) = edits.proposal_link {
+        writeln!(f, "<p><a href=\"{0}\">{0}</a></p>", escape(link))?;
+    }
+
+    writeln!(f, "<h2>Changes ({})</h2>", edits.commands.len())?;
+    writeln!(f, "<ul>")?;
+    for cmd in &edits.commands {
+        writeln!(f, "<li>{}</li>", escape(&cmd.short_name()))?;
+    }
+    writeln!(f, "</ul>")?;
+
+    if app.has_prebaked().is_some() {
+        write_kpis(&mut f, app)?;
+    } else {
+        writeln!(
+            f,
+            "<p>No baseline results to compare against, so there are no before/after \
+             numbers.</p>"
+        )?;
+    }
+
+    let mut screenshots: Vec<String> = abstutil::list_dir(std::path::Path::new(&dir))
+        .into_iter()
+        .filter(|x| x.ends_with(".png") || x.ends_with(".gif"))
+        .map(|file| {
+            std::path::Path::new(&file)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    screenshots.sort();
+    if screenshots.iter().any(|x| x == BEFORE) && screenshots.iter().any(|x| x == AFTER) {
+        screenshots.retain(|x| x != BEFORE && x != AFTER);
+        writeln!(f, "<h2>Before and after</h2>")?;
+        writeln!(f, "<table><tr><th>Before</th><th>After</th></tr><tr>")?;
+        for name in &[BEFORE, AFTER] {
+            writeln!(
+                f,
+                "<td><img src=\"{0}\" alt=\"{0}\" style=\"max-width: 100%\"></td>",
+                name
+            )?;
+        }
+        writeln!(f, "</tr></table>")?;
+    }
+    if !screenshots.is_empty() {
+        writeln!(f, "<h2>Screenshots</h2>")?;
+        for name in screenshots {
+            writeln!(
+                f,
+                "<p><img src=\"{0}\" alt=\"{0}\" style=\"max-width: 100%\"></p>",
+                escape(&name)
+            )?;
+        }
+    }
+
+    writeln!(f, "</body></html>")?;
+    println!("Exported {}", path);
+    Ok(path)
+}
+
+fn write_kpis(f: &mut File, app: &App) -> Result<(), Error> {
+    let (_, scenario) = app.has_prebaked().unwrap();
+    writeln!(
+        f,
+        "<h2>Trip times, compared to the baseline {}</h2>",
+        escape(scenario)
+    )?;
+    writeln!(
+        f,
+        "<table><tr><th>Mode</th><th>Trips</th><th>Faster</th><th>Slower</th><th>Total time \
+         before</th><th>Total time after</th></tr>"
+    )?;
+
+    let results = app
+        .primary
+        .sim
+        .get_analytics()
+        .both_finished_trips(app.primary.sim.time(), app.prebaked());
+    for mode in TripMode::all() {
+        let mut num = 0;
+        let mut faster = 0;
+        let mut slower = 0;
+        let mut sum_before = Duration::ZERO;
+        let mut sum_after = Duration::ZERO;
+        for (b, a, m) in &results {
+            if *m != mode {
+                continue;
+            }
+            num += 1;
+            sum_before += *b;
+            sum_after += *a;
+            if a < b {
+                faster += 1;
+            } else if a > b {
+                slower += 1;
+            }
+        }
+        writeln!(
+            f,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            mode.ongoing_verb(),
+            prettyprint_usize(num),
+            prettyprint_usize(faster),
+            prettyprint_usize(slower),
+            sum_before,
+            sum_after
+        )?;
+    }
+    writeln!(f, "</table>")?;
+    Ok(())
+}
+
+fn escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,14 +1,15 @@
 use crate::app::App;
 use crate::game::{DrawBaselayer, State, Transition};
 use crate::helpers::color_for_mode;
-use crate::sandbox::dashboards::DashTab;
+use crate::sandbox::dashboards::report::ExportReport;
+use crate::sandbox::dashboards::{group_dropdown, in_group, DashTab};
 use abstutil::prettyprint_usize;
 use ezgui::{
-    Checkbox, Choice, Color, CompareTimes, Composite, DrawWithTooltips, EventCtx, GeomBatch,
-    GfxCtx, Line, Outcome, Text, TextExt, Widget,
+    Btn, Checkbox, Choice, Color, CompareTimes, Composite, DrawWithTooltips, EventCtx, GeomBatch,
+    GfxCtx, Histogram, Line, Outcome, Text, TextExt, Widget,
 };
 use geom::{Distance, Duration, Polygon, Pt2D};
-use sim::TripMode;
+use sim::{DemographicGroup, TripMode};
 use std::collections::BTreeSet;
 
 pub struct TripSummaries {
@@ -30,6 +31,7 @@ impl TripSummaries {
             ],
         )
         .margin_right(10)];
+        filters.push(group_dropdown(ctx, filter.group).margin_right(10));
         for m in TripMode::all() {
             filters.push(
                 Checkbox::colored(
@@ -47,6 +49,9 @@ impl TripSummaries {
             composite: Composite::new(
                 Widget::col(vec![
                     DashTab::TripSummaries.picker(ctx, app),
+                    Btn::text_fg("export report")
+                        .build_def(ctx, None)
+                        .align_right(),
                     Widget::row(filters).centered_horiz().margin_below(10),
                     summary(ctx, app, &filter).margin_below(10),
                     Widget::row(vec![
@@ -55,7 +60,9 @@ impl TripSummaries {
                             .margin_right(20),
                         scatter_plot(ctx, app, &filter),
                     ])
-                    .evenly_spaced(),
+                    .evenly_spaced()
+                    .margin_below(10),
+                    distribution(ctx, app, &filter),
                 ])
                 .bg(app.cs.panel_bg)
                 .padding(10),
@@ -70,11 +77,17 @@ impl TripSummaries {
 impl State for TripSummaries {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
-            Some(Outcome::Clicked(x)) => DashTab::TripSummaries.transition(ctx, app, &x),
+            Some(Outcome::Clicked(x)) => {
+                if x == "export report" {
+                    return Transition::Push(ExportReport::new(app));
+                }
+                DashTab::TripSummaries.transition(ctx, app, &x)
+            }
             None => {
                 let mut filter = Filter {
                     changes_pct: self.composite.dropdown_value("filter"),
                     modes: BTreeSet::new(),
+                    group: self.composite.dropdown_value("group"),
                 };
                 for m in TripMode::all() {
                     if self.composite.is_checked(m.ongoing_verb()) {
@@ -110,13 +123,13 @@ fn summary(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
     let mut num_slower = 0;
     let mut sum_faster = Duration::ZERO;
     let mut sum_slower = Duration::ZERO;
-    for (b, a, mode) in app
+    for (id, b, a, mode) in app
         .primary
         .sim
         .get_analytics()
-        .both_finished_trips(app.primary.sim.time(), app.prebaked())
+        .both_finished_trips_by_id(app.primary.sim.time(), app.prebaked())
     {
-        if !filter.modes.contains(&mode) {
+        if !filter.modes.contains(&mode) || !in_group(app, id, filter.group) {
             continue;
         }
         let same = if let Some(pct) = filter.changes_pct {
@@ -203,6 +216,32 @@ fn scatter_plot(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
     .padding(10)
 }
 
+fn distribution(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
+    if app.has_prebaked().is_none() {
+        return Widget::nothing();
+    }
+
+    let points = filter.get_trips(app);
+    if points.is_empty() {
+        return Widget::nothing();
+    }
+
+    let (before, after): (Vec<Duration>, Vec<Duration>) = points.into_iter().unzip();
+    Widget::col(vec![
+        Line("Trip time distribution").small_heading().draw(ctx),
+        Histogram::new(
+            ctx,
+            vec![
+                ("before changes", Color::grey(0.6), before),
+                ("after changes", Color::CYAN, after),
+            ],
+            20,
+        ),
+    ])
+    .outline(2.0, Color::WHITE)
+    .padding(10)
+}
+
 fn contingency_table(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
     if app.has_prebaked().is_none() {
         return Widget::nothing();
@@ -335,6 +374,7 @@ fn contingency_table(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
 pub struct Filter {
     changes_pct: Option<f64>,
     modes: BTreeSet<TripMode>,
+    group: Option<DemographicGroup>,
 }
 
 impl Filter {
@@ -342,18 +382,20 @@ impl Filter {
         Filter {
             changes_pct: None,
             modes: TripMode::all().into_iter().collect(),
+            group: None,
         }
     }
 
     fn get_trips(&self, app: &App) -> Vec<(Duration, Duration)> {
         let mut points = Vec::new();
-        for (b, a, mode) in app
+        for (id, b, a, mode) in app
             .primary
             .sim
             .get_analytics()
-            .both_finished_trips(app.primary.sim.time(), app.prebaked())
+            .both_finished_trips_by_id(app.primary.sim.time(), app.prebaked())
         {
             if self.modes.contains(&mode)
+                && in_group(app, id, self.group)
                 && self
                     .changes_pct
                     .map(|pct| pct_diff(a, b) > pct)
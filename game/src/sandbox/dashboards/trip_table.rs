@@ -2,74 +2,70 @@ use crate::app::App;
 use crate::game::{DrawBaselayer, State, Transition};
 use crate::helpers::{cmp_duration_shorter, color_for_mode, color_for_trip_phase};
 use crate::info::{OpenTrip, Tab};
-use crate::sandbox::dashboards::DashTab;
+use crate::sandbox::dashboards::{group_dropdown, in_group, DashTab};
 use crate::sandbox::SandboxMode;
 use abstutil::prettyprint_usize;
 use ezgui::{
-    Btn, Checkbox, Color, Composite, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome,
-    RewriteColor, ScreenDims, ScreenPt, Text, TextExt, Widget,
+    Checkbox, Color, Composite, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome, RewriteColor,
+    ScreenDims, ScreenPt, Table, TableRow, Text, TextExt, Widget,
 };
-use geom::{Distance, Duration, Polygon, Pt2D, Time};
-use sim::{TripEndpoint, TripID, TripMode};
+use geom::{Distance, Duration, Pt2D, Time};
+use sim::{DemographicGroup, TripEndpoint, TripID, TripMode};
+use std::cmp::Ordering;
 use std::collections::BTreeSet;
 
 const ROWS: usize = 10;
 
 pub struct TripTable {
     composite: Composite,
-    opts: Options,
+    table: Table<Entry>,
+    filters: Filters,
+    aborted: usize,
 }
 
-struct Options {
-    sort_by: SortBy,
-    descending: bool,
+struct Filters {
     modes: BTreeSet<TripMode>,
     off_map_starts: bool,
     off_map_ends: bool,
-    skip: usize,
-}
-
-impl Options {
-    fn change(&mut self, value: SortBy) {
-        self.skip = 0;
-        if self.sort_by == value {
-            self.descending = !self.descending;
-        } else {
-            self.sort_by = value;
-            self.descending = true;
-        }
-    }
-}
-
-// TODO Is there a heterogenously typed table crate somewhere?
-#[derive(Clone, Copy, PartialEq)]
-enum SortBy {
-    Departure,
-    Duration,
-    RelativeDuration,
-    PercentChangeDuration,
-    Waiting,
-    PercentWaiting,
+    group: Option<DemographicGroup>,
 }
 
 impl TripTable {
     pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
-        let opts = Options {
-            sort_by: SortBy::PercentWaiting,
-            descending: true,
+        let filters = Filters {
             modes: TripMode::all().into_iter().collect(),
             off_map_starts: true,
             off_map_ends: true,
-            skip: 0,
+            group: None,
         };
+        let (rows, aborted) = gather(app, &filters);
+
+        let mut headers = vec![
+            ("Trip ID", false),
+            ("Type", false),
+            ("Departure", true),
+            ("Duration", true),
+        ];
+        if app.has_prebaked().is_some() {
+            headers.push(("Comparison", true));
+            headers.push(("Normalized", true));
+        }
+        headers.push(("Time spent waiting", true));
+        headers.push(("Percent waiting", true));
+        let percent_waiting = headers.len() - 1;
+        let mut table = Table::new(headers, rows, ROWS);
+        table.sort_by(percent_waiting, true);
+
         Box::new(TripTable {
-            composite: make(ctx, app, &opts),
-            opts,
+            composite: make(ctx, app, &filters, aborted, &table),
+            table,
+            filters,
+            aborted,
         })
     }
 
     fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
-        let mut new = make(ctx, app, &self.opts);
+        let mut new = make(ctx, app, &self.filters, self.aborted, &self.table);
         new.restore(ctx, &self.composite);
         self.composite = new;
     }
@@ -78,57 +74,15 @@ impl TripTable {
 impl State for TripTable {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
-            Some(Outcome::Clicked(x)) => match x.as_ref() {
-                "Departure" => {
-                    self.opts.change(SortBy::Departure);
-                    self.recalc(ctx, app);
-                }
-                "Duration" => {
-                    self.opts.change(SortBy::Duration);
-                    self.recalc(ctx, app);
-                }
-                "Comparison" => {
-                    self.opts.change(SortBy::RelativeDuration);
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
                     self.recalc(ctx, app);
+                } else if let Ok(idx) = x.parse::<usize>() {
+                    return open_trip(app, TripID(idx));
+                } else {
+                    return DashTab::TripTable.transition(ctx, app, &x);
                 }
-                "Normalized" => {
-                    self.opts.change(SortBy::PercentChangeDuration);
-                    self.recalc(ctx, app);
-                }
-                "Time spent waiting" => {
-                    self.opts.change(SortBy::Waiting);
-                    self.recalc(ctx, app);
-                }
-                "Percent waiting" => {
-                    self.opts.change(SortBy::PercentWaiting);
-                    self.recalc(ctx, app);
-                }
-                "previous trips" => {
-                    self.opts.skip -= ROWS;
-                    self.recalc(ctx, app);
-                }
-                "next trips" => {
-                    self.opts.skip += ROWS;
-                    self.recalc(ctx, app);
-                }
-                x => {
-                    if let Ok(idx) = x.parse::<usize>() {
-                        let trip = TripID(idx);
-                        let person = app.primary.sim.trip_to_person(trip);
-                        return Transition::PopWithData(Box::new(move |state, ctx, app| {
-                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
-                            let mut actions = sandbox.contextual_actions();
-                            sandbox.controls.common.as_mut().unwrap().launch_info_panel(
-                                ctx,
-                                app,
-                                Tab::PersonTrips(person, OpenTrip::single(trip)),
-                                &mut actions,
-                            );
-                        }));
-                    }
-                    return DashTab::TripTable.transition(ctx, app, x);
-                }
-            },
+            }
             None => {
                 let mut modes = BTreeSet::new();
                 for m in TripMode::all() {
@@ -136,19 +90,21 @@ impl State for TripTable {
                         modes.insert(m);
                     }
                 }
-                if modes != self.opts.modes {
-                    self.opts.skip = 0;
-                    self.opts.modes = modes;
-                    self.recalc(ctx, app);
-                }
                 let off_map_starts = self.composite.is_checked("starting off-map");
                 let off_map_ends = self.composite.is_checked("ending off-map");
-                if self.opts.off_map_starts != off_map_starts
-                    || self.opts.off_map_ends != off_map_ends
+                let group = self.composite.dropdown_value("group");
+                if modes != self.filters.modes
+                    || self.filters.off_map_starts != off_map_starts
+                    || self.filters.off_map_ends != off_map_ends
+                    || self.filters.group != group
                 {
-                    self.opts.off_map_starts = off_map_starts;
-                    self.opts.off_map_ends = off_map_ends;
-                    self.opts.skip = 0;
+                    self.filters.modes = modes;
+                    self.filters.off_map_starts = off_map_starts;
+                    self.filters.off_map_ends = off_map_ends;
+                    self.filters.group = group;
+                    let (rows, aborted) = gather(app, &self.filters);
+                    self.table.set_rows(rows);
+                    self.aborted = aborted;
                     self.recalc(ctx, app);
                 }
             }
@@ -171,21 +127,90 @@ impl State for TripTable {
 struct Entry {
     trip: TripID,
     mode: TripMode,
+    mode_color: Color,
     departure: Time,
     duration_after: Duration,
     duration_before: Duration,
     waiting: Duration,
     percent_waiting: usize,
+    // Only true when there's a baseline to compare against. Otherwise, the two comparison
+    // columns are missing.
+    compare: bool,
+}
+
+impl TableRow for Entry {
+    fn label(&self) -> String {
+        self.trip.0.to_string()
+    }
+
+    fn render(&self, ctx: &EventCtx) -> Vec<GeomBatch> {
+        let mut row = vec![
+            Text::from(Line(self.trip.0.to_string())).render_ctx(ctx),
+            Text::from(Line(self.mode.ongoing_verb()).fg(self.mode_color)).render_ctx(ctx),
+            Text::from(Line(self.departure.ampm_tostring())).render_ctx(ctx),
+            Text::from(Line(self.duration_after.to_string())).render_ctx(ctx),
+        ];
+        if self.compare {
+            row.push(
+                Text::from_all(cmp_duration_shorter(
+                    self.duration_after,
+                    self.duration_before,
+                ))
+                .render_ctx(ctx),
+            );
+            if self.duration_after == self.duration_before {
+                row.push(Text::from(Line("same")).render_ctx(ctx));
+            } else if self.duration_after < self.duration_before {
+                row.push(
+                    Text::from(Line(format!(
+                        "{}% faster",
+                        (100.0 * (1.0 - (self.duration_after / self.duration_before))) as usize
+                    )))
+                    .render_ctx(ctx),
+                );
+            } else {
+                row.push(
+                    Text::from(Line(format!(
+                        "{}% slower ",
+                        (100.0 * ((self.duration_after / self.duration_before) - 1.0)) as usize
+                    )))
+                    .render_ctx(ctx),
+                );
+            }
+        }
+        row.push(Text::from(Line(self.waiting.to_string())).render_ctx(ctx));
+        row.push(Text::from(Line(format!("{}%", self.percent_waiting))).render_ctx(ctx));
+        row
+    }
+
+    fn cmp_by(&self, other: &Entry, col: usize) -> Ordering {
+        let col = if !self.compare && col >= 4 {
+            col + 2
+        } else {
+            col
+        };
+        let normalized = |x: &Entry| (100.0 * (x.duration_after / x.duration_before)) as isize;
+        match col {
+            2 => self.departure.cmp(&other.departure),
+            3 => self.duration_after.cmp(&other.duration_after),
+            4 => (self.duration_after - self.duration_before)
+                .cmp(&(other.duration_after - other.duration_before)),
+            5 => normalized(self).cmp(&normalized(other)),
+            6 => self.waiting.cmp(&other.waiting),
+            7 => self.percent_waiting.cmp(&other.percent_waiting),
+            _ => unreachable!(),
+        }
+    }
 }
 
-fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
-    // Gather raw data
+// Also returns the number of aborted trips
+fn gather(app: &App, filters: &Filters) -> (Vec<Entry>, usize) {
     let mut data = Vec::new();
     let sim = &app.primary.sim;
     let mut aborted = 0;
     for (_, id, maybe_mode, duration_after) in &sim.get_analytics().finished_trips {
         let mode = if let Some(m) = maybe_mode {
-            if !opts.modes.contains(m) {
+            if !filters.modes.contains(m) {
                 continue;
             }
             *m
@@ -193,13 +218,16 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
             aborted += 1;
             continue;
         };
+        if !in_group(app, *id, filters.group) {
+            continue;
+        }
         let (_, start, end, _) = sim.trip_info(*id);
-        if !opts.off_map_starts {
+        if !filters.off_map_starts {
             if let TripEndpoint::Border(_, _) = start {
                 continue;
             }
         }
-        if !opts.off_map_ends {
+        if !filters.off_map_ends {
             if let TripEndpoint::Border(_, _) = end {
                 continue;
             }
@@ -222,114 +250,45 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         data.push(Entry {
             trip: *id,
             mode,
+            mode_color: color_for_mode(app, mode),
             departure,
             duration_after: *duration_after,
             duration_before,
             waiting,
             percent_waiting: (100.0 * waiting / *duration_after) as usize,
+            compare: app.has_prebaked().is_some(),
         });
     }
+    (data, aborted)
+}
 
-    // Sort
-    match opts.sort_by {
-        SortBy::Departure => data.sort_by_key(|x| x.departure),
-        SortBy::Duration => data.sort_by_key(|x| x.duration_after),
-        SortBy::RelativeDuration => data.sort_by_key(|x| x.duration_after - x.duration_before),
-        SortBy::PercentChangeDuration => {
-            data.sort_by_key(|x| (100.0 * (x.duration_after / x.duration_before)) as isize)
-        }
-        SortBy::Waiting => data.sort_by_key(|x| x.waiting),
-        SortBy::PercentWaiting => data.sort_by_key(|x| x.percent_waiting),
-    }
-    if opts.descending {
-        data.reverse();
-    }
-    let total_rows = data.len();
-
-    // Render data
-    let mut rows = Vec::new();
-    for x in data.into_iter().skip(opts.skip).take(ROWS) {
-        let mut row = vec![
-            Text::from(Line(x.trip.0.to_string())).render_ctx(ctx),
-            Text::from(Line(x.mode.ongoing_verb()).fg(color_for_mode(app, x.mode))).render_ctx(ctx),
-            Text::from(Line(x.departure.ampm_tostring())).render_ctx(ctx),
-            Text::from(Line(x.duration_after.to_string())).render_ctx(ctx),
-        ];
-        if app.has_prebaked().is_some() {
-            row.push(
-                Text::from_all(cmp_duration_shorter(x.duration_after, x.duration_before))
-                    .render_ctx(ctx),
-            );
-            if x.duration_after == x.duration_before {
-                row.push(Text::from(Line("same")).render_ctx(ctx));
-            } else if x.duration_after < x.duration_before {
-                row.push(
-                    Text::from(Line(format!(
-                        "{}% faster",
-                        (100.0 * (1.0 - (x.duration_after / x.duration_before))) as usize
-                    )))
-                    .render_ctx(ctx),
-                );
-            } else {
-                row.push(
-                    Text::from(Line(format!(
-                        "{}% slower ",
-                        (100.0 * ((x.duration_after / x.duration_before) - 1.0)) as usize
-                    )))
-                    .render_ctx(ctx),
-                );
-            }
-        }
-        row.push(Text::from(Line(x.waiting.to_string())).render_ctx(ctx));
-        row.push(Text::from(Line(format!("{}%", x.percent_waiting))).render_ctx(ctx));
-
-        rows.push((x.trip.0.to_string(), row));
-    }
-
-    let btn = |value, name| {
-        if opts.sort_by == value {
-            Btn::text_bg2(format!(
-                "{} {}",
-                name,
-                if opts.descending { "↓" } else { "↑" }
-            ))
-            .build(ctx, name, None)
-        } else {
-            Btn::text_bg2(name).build_def(ctx, None)
-        }
-    };
-    let mut headers = vec![
-        Line("Trip ID").draw(ctx),
-        Line("Type").draw(ctx),
-        btn(SortBy::Departure, "Departure"),
-        btn(SortBy::Duration, "Duration"),
-    ];
-    if app.has_prebaked().is_some() {
-        headers.push(btn(SortBy::RelativeDuration, "Comparison"));
-        headers.push(btn(SortBy::PercentChangeDuration, "Normalized"));
-    }
-    headers.push(btn(SortBy::Waiting, "Time spent waiting"));
-    headers.push(btn(SortBy::PercentWaiting, "Percent waiting"));
-
+fn make(
+    ctx: &mut EventCtx,
+    app: &App,
+    filters: &Filters,
+    aborted: usize,
+    table: &Table<Entry>,
+) -> Composite {
     let mut col = vec![DashTab::TripTable.picker(ctx, app)];
-    let mut filters = Vec::new();
+    let mut checkboxes = Vec::new();
     for m in TripMode::all() {
-        filters.push(
+        checkboxes.push(
             Checkbox::colored(
                 ctx,
                 m.ongoing_verb(),
                 color_for_mode(app, m),
-                opts.modes.contains(&m),
+                filters.modes.contains(&m),
             )
             .margin_right(5),
         );
-        filters.push(m.ongoing_verb().draw_text(ctx).margin_right(10));
+        checkboxes.push(m.ongoing_verb().draw_text(ctx).margin_right(10));
     }
-    col.push(Widget::row(filters).margin_below(5));
+    col.push(Widget::row(checkboxes).margin_below(5));
     col.push(
         Widget::row(vec![
-            Checkbox::text(ctx, "starting off-map", None, opts.off_map_starts).margin_right(10),
-            Checkbox::text(ctx, "ending off-map", None, opts.off_map_ends),
+            Checkbox::text(ctx, "starting off-map", None, filters.off_map_starts).margin_right(10),
+            Checkbox::text(ctx, "ending off-map", None, filters.off_map_ends).margin_right(10),
+            group_dropdown(ctx, filters.group),
         ])
         .margin_below(5),
     );
@@ -341,42 +300,8 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         .draw_text(ctx)
         .margin_below(5),
     );
-    col.push(
-        Widget::row(vec![
-            if opts.skip > 0 {
-                Btn::text_fg("<").build(ctx, "previous trips", None)
-            } else {
-                Btn::text_fg("<").inactive(ctx)
-            }
-            .margin_right(10),
-            format!(
-                "{}-{} of {}",
-                if total_rows > 0 {
-                    prettyprint_usize(opts.skip + 1)
-                } else {
-                    "0".to_string()
-                },
-                prettyprint_usize((opts.skip + 1 + ROWS).min(total_rows)),
-                prettyprint_usize(total_rows)
-            )
-            .draw_text(ctx)
-            .margin_right(10),
-            if opts.skip + 1 + ROWS < total_rows {
-                Btn::text_fg(">").build(ctx, "next trips", None)
-            } else {
-                Btn::text_fg(">").inactive(ctx)
-            },
-        ])
-        .margin_below(5),
-    );
 
-    col.extend(make_table(
-        ctx,
-        app,
-        headers,
-        rows,
-        0.88 * ctx.canvas.window_width,
-    ));
+    col.push(table.render(ctx, 0.88 * ctx.canvas.window_width));
     col.push(
         Filler::new(ScreenDims::new(
             0.15 * ctx.canvas.window_width,
@@ -392,67 +317,19 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         .build(ctx)
 }
 
-// TODO Figure out a nicer API to construct generic sortable tables.
-pub fn make_table(
-    ctx: &mut EventCtx,
-    app: &App,
-    headers: Vec<Widget>,
-    rows: Vec<(String, Vec<GeomBatch>)>,
-    total_width: f64,
-) -> Vec<Widget> {
-    let total_width = total_width / ctx.get_scale_factor();
-    let mut width_per_col: Vec<f64> = headers
-        .iter()
-        .map(|w| w.get_width_for_forcing() / ctx.get_scale_factor())
-        .collect();
-    for (_, row) in &rows {
-        for (col, width) in row.iter().zip(width_per_col.iter_mut()) {
-            *width = width.max(col.get_dims().width / ctx.get_scale_factor());
-        }
-    }
-    let extra_margin = ((total_width - width_per_col.clone().into_iter().sum::<f64>())
-        / (width_per_col.len() - 1) as f64)
-        .max(0.0);
-
-    let mut col = vec![Widget::row(
-        headers
-            .into_iter()
-            .enumerate()
-            .map(|(idx, w)| {
-                let margin = extra_margin + width_per_col[idx]
-                    - (w.get_width_for_forcing() / ctx.get_scale_factor());
-                if idx == width_per_col.len() - 1 {
-                    w.margin_right((margin - extra_margin) as usize)
-                } else {
-                    w.margin_right(margin as usize)
-                }
-            })
-            .collect(),
-    )
-    .bg(app.cs.section_bg)];
-
-    for (label, row) in rows {
-        let mut batch = GeomBatch::new();
-        batch.autocrop_dims = false;
-        let mut x1 = 0.0;
-        for (col, width) in row.into_iter().zip(width_per_col.iter()) {
-            batch.append(col.scale(1.0 / ctx.get_scale_factor()).translate(x1, 0.0));
-            x1 += *width + extra_margin;
-        }
-
-        let rect = Polygon::rectangle(total_width, batch.get_dims().height);
-        let mut hovered = GeomBatch::new();
-        hovered.push(app.cs.hovering, rect.clone());
-        hovered.append(batch.clone());
-
-        col.push(
-            Btn::custom(batch, hovered, rect)
-                .tooltip(Text::new())
-                .build(ctx, label, None),
+// Closes the dashboard and opens the info panel for a trip.
+pub fn open_trip(app: &App, trip: TripID) -> Transition {
+    let person = app.primary.sim.trip_to_person(trip);
+    Transition::PopWithData(Box::new(move |state, ctx, app| {
+        let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
+        let mut actions = sandbox.contextual_actions();
+        sandbox.controls.common.as_mut().unwrap().launch_info_panel(
+            ctx,
+            app,
+            Tab::PersonTrips(person, OpenTrip::single(trip)),
+            &mut actions,
         );
-    }
-
-    col
+    }))
 }
 
 pub fn preview_trip(g: &mut GfxCtx, app: &App, composite: &Composite) {
@@ -469,8 +346,8 @@ pub fn preview_trip(g: &mut GfxCtx, app: &App, composite: &Composite) {
 
     g.redraw(&app.primary.draw_map.boundary_polygon);
     g.redraw(&app.primary.draw_map.draw_all_areas);
-    g.redraw(&app.primary.draw_map.draw_all_unzoomed_intersections);
-    g.redraw(&app.primary.draw_map.draw_all_thick_roads);
+    g.redraw_cached(&app.primary.draw_map.draw_all_unzoomed_intersections);
+    g.redraw_cached(&app.primary.draw_map.draw_all_thick_roads);
 
     if let Some(x) = composite.currently_hovering() {
         if let Ok(idx) = x.parse::<usize>() {
@@ -503,7 +380,7 @@ fn preview_route(g: &mut GfxCtx, app: &App, trip: TripID) -> GeomBatch {
 
     let (_, start, end, _) = app.primary.sim.trip_info(trip);
     batch.append(
-        GeomBatch::mapspace_svg(g.prerender, "../data/system/assets/timeline/start_pos.svg")
+        GeomBatch::mapspace_icon(g.prerender, "trip_start")
             .scale(10.0)
             .color(RewriteColor::Change(Color::WHITE, Color::BLACK))
             .color(RewriteColor::Change(
@@ -516,7 +393,7 @@ fn preview_route(g: &mut GfxCtx, app: &App, trip: TripID) -> GeomBatch {
             }),
     );
     batch.append(
-        GeomBatch::mapspace_svg(g.prerender, "../data/system/assets/timeline/goal_pos.svg")
+        GeomBatch::mapspace_icon(g.prerender, "trip_goal")
             .scale(10.0)
             .color(RewriteColor::Change(Color::WHITE, Color::BLACK))
             .color(RewriteColor::Change(
@@ -8,19 +8,22 @@ use crate::sandbox::SandboxControls;
 use crate::sandbox::SandboxMode;
 use abstutil::Timer;
 use ezgui::{
-    hotkey, lctrl, Btn, Choice, Color, Composite, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment,
-    Key, Line, Outcome, ScreenRectangle, Spinner, Text, TextExt, VerticalAlignment, Widget,
+    hotkey, lctrl, Btn, Choice, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
+    HorizontalAlignment, Key, Line, Outcome, ScreenRectangle, Spinner, Text, TextExt, UnitSpinner,
+    VerticalAlignment, Widget,
 };
-use geom::{Distance, Polygon};
+use geom::{Distance, Duration, Polygon};
 use map_model::{
-    BuildingID, IntersectionID, Map, PathConstraints, PathRequest, Position, NORMAL_LANE_THICKNESS,
+    IntersectionID, Itinerary, LegMode, Map, PathConstraints, PathRequest, Position, RoadID,
+    NORMAL_LANE_THICKNESS,
 };
 use rand::seq::SliceRandom;
 use rand::Rng;
 use sim::{
-    DontDrawAgents, DrivingGoal, IndividTrip, PersonID, PersonSpec, Scenario, SidewalkSpot,
-    SpawnTrip, TripEndpoint, TripMode, TripSpec,
+    DontDrawAgents, DrivingGoal, IndividTrip, MessageSign, PersonID, PersonSpec, Scenario,
+    SidewalkSpot, SpawnTrip, TripEndpoint, TripMode, TripSpec,
 };
+use std::collections::BTreeSet;
 
 // TODO Maybe remember what things were spawned, offer to replay this later
 pub struct Freeform {
@@ -159,6 +162,13 @@ pub fn make_change_traffic(btn: ScreenRectangle, current: String) -> Box<dyn Sta
                         list.push(Choice::new(name.clone(), name));
                     }
                 }
+                list.push(
+                    Choice::new("a day of activities", "activities".to_string()).tooltip(
+                        "Everybody leaves home, maybe goes to work or school and runs errands, \
+                         then goes home. Trips depend on how long activities take and when \
+                         places are open.",
+                    ),
+                );
                 list.push(
                     Choice::new("random unrealistic trips", "random".to_string()).tooltip(
                         "Lots of trips will start at midnight, but not constantly appear through \
@@ -196,12 +206,12 @@ pub fn make_change_traffic(btn: ScreenRectangle, current: String) -> Box<dyn Sta
 struct AgentSpawner {
     composite: Composite,
     source: Option<TripEndpoint>,
-    goal: Option<(TripEndpoint, Option<Polygon>)>,
+    goal: Option<(TripEndpoint, Vec<Polygon>)>,
     confirmed: bool,
 }
 
 impl AgentSpawner {
-    fn new(ctx: &mut EventCtx, app: &App, start: Option<BuildingID>) -> Box<dyn State> {
+    fn new(ctx: &mut EventCtx, app: &App, start: Option<TripEndpoint>) -> Box<dyn State> {
         let mut spawner = AgentSpawner {
             source: None,
             goal: None,
@@ -233,6 +243,17 @@ impl AgentSpawner {
                         "Number of trips:".draw_text(ctx).margin_right(10),
                         Spinner::new(ctx, (1, 1000), 1).named("number"),
                     ]),
+                    Widget::row(vec![
+                        "Depart in:".draw_text(ctx).margin_right(10),
+                        UnitSpinner::new(
+                            ctx,
+                            (Duration::ZERO, Duration::hours(1)),
+                            Duration::minutes(1),
+                            Duration::ZERO,
+                        )
+                        .named("delay"),
+                    ]),
+                    "".draw_text(ctx).named("route"),
                     Btn::text_fg("Confirm").inactive(ctx).named("Confirm"),
                 ])
                 .bg(app.cs.panel_bg)
@@ -241,8 +262,8 @@ impl AgentSpawner {
             .aligned(HorizontalAlignment::Right, VerticalAlignment::Top)
             .build(ctx),
         };
-        if let Some(b) = start {
-            spawner.source = Some(TripEndpoint::Bldg(b));
+        if let Some(endpt) = start {
+            spawner.source = Some(endpt);
             spawner.composite.replace(
                 ctx,
                 "instructions",
@@ -253,6 +274,52 @@ impl AgentSpawner {
         }
         Box::new(spawner)
     }
+
+    // Calculate the route to this goal and describe each leg of it, so the user can sanity-check
+    // before confirming.
+    fn preview_route(&mut self, ctx: &mut EventCtx, app: &App, to: TripEndpoint) {
+        let map = &app.primary.map;
+        let mode: TripMode = self.composite.dropdown_value("mode");
+        if let Some(legs) = itinerary(self.source.clone().unwrap(), to.clone(), mode, map)
+            .and_then(|itinerary| itinerary.legs(map))
+        {
+            let mut txt = Text::from(Line(format!(
+                "Route is {} long, about {}",
+                legs.iter()
+                    .fold(Distance::ZERO, |sum, leg| sum + leg.path.total_length()),
+                legs.iter().fold(Duration::ZERO, |sum, leg| sum + leg.time)
+            )));
+            for leg in &legs {
+                txt.add(Line(format!(
+                    "- {} for {}, about {}",
+                    describe_leg(leg.mode, map),
+                    leg.path.total_length(),
+                    leg.time
+                )));
+            }
+            self.composite
+                .replace(ctx, "route", txt.draw(ctx).named("route"));
+            self.goal = Some((
+                to,
+                legs.iter()
+                    .filter_map(|leg| {
+                        leg.path
+                            .trace(map, Distance::ZERO, None)
+                            .map(|pl| pl.make_polygons(NORMAL_LANE_THICKNESS))
+                    })
+                    .collect(),
+            ));
+        } else {
+            self.composite.replace(
+                ctx,
+                "route",
+                format!("No route for {} there", mode.ongoing_verb())
+                    .draw_text(ctx)
+                    .named("route"),
+            );
+            self.goal = None;
+        }
+    }
 }
 
 impl State for AgentSpawner {
@@ -268,12 +335,13 @@ impl State for AgentSpawner {
                     let mut scenario = Scenario::empty(map, "one-shot");
                     let from = self.source.take().unwrap();
                     let to = self.goal.take().unwrap().0;
+                    let delay: Duration = self.composite.unit_spinner("delay");
                     for i in 0..self.composite.spinner("number") {
                         scenario.people.push(PersonSpec {
                             id: PersonID(app.primary.sim.get_all_people().len() + i),
                             orig_id: None,
                             trips: vec![IndividTrip {
-                                depart: app.primary.sim.time(),
+                                depart: app.primary.sim.time() + delay,
                                 trip: SpawnTrip::new(
                                     from.clone(),
                                     to.clone(),
@@ -302,21 +370,8 @@ impl State for AgentSpawner {
         // pedestrian into wandering on/off a highway border.
         if old_mode != self.composite.dropdown_value("mode") && self.goal.is_some() {
             let to = self.goal.as_ref().unwrap().0.clone();
-            if let Some(path) = path_request(
-                self.source.clone().unwrap(),
-                to.clone(),
-                self.composite.dropdown_value("mode"),
-                &app.primary.map,
-            )
-            .and_then(|req| app.primary.map.pathfind(req))
-            {
-                self.goal = Some((
-                    to,
-                    path.trace(&app.primary.map, Distance::ZERO, None)
-                        .map(|pl| pl.make_polygons(NORMAL_LANE_THICKNESS)),
-                ));
-            } else {
-                self.goal = None;
+            self.preview_route(ctx, app, to);
+            if self.goal.is_none() {
                 self.confirmed = false;
                 self.composite.replace(
                     ctx,
@@ -374,22 +429,7 @@ impl State for AgentSpawner {
                     .map(|(to, _)| to != &hovering)
                     .unwrap_or(true)
                 {
-                    if let Some(path) = path_request(
-                        self.source.clone().unwrap(),
-                        hovering.clone(),
-                        self.composite.dropdown_value("mode"),
-                        &app.primary.map,
-                    )
-                    .and_then(|req| app.primary.map.pathfind(req))
-                    {
-                        self.goal = Some((
-                            hovering,
-                            path.trace(&app.primary.map, Distance::ZERO, None)
-                                .map(|pl| pl.make_polygons(NORMAL_LANE_THICKNESS)),
-                        ));
-                    } else {
-                        self.goal = None;
-                    }
+                    self.preview_route(ctx, app, hovering);
                 }
 
                 if self.goal.is_some() && app.per_obj.left_click(ctx, "end here") {
@@ -409,8 +449,10 @@ impl State for AgentSpawner {
                     );
                 }
             }
-        } else {
+        } else if self.goal.is_some() {
             self.goal = None;
+            self.composite
+                .replace(ctx, "route", "".draw_text(ctx).named("route"));
         }
 
         Transition::Keep
@@ -429,7 +471,7 @@ impl State for AgentSpawner {
                 },
             );
         }
-        if let Some((ref endpt, ref poly)) = self.goal {
+        if let Some((ref endpt, ref polys)) = self.goal {
             g.draw_polygon(
                 Color::GREEN.alpha(0.8),
                 match endpt {
@@ -437,13 +479,157 @@ impl State for AgentSpawner {
                     TripEndpoint::Bldg(b) => &app.primary.map.get_b(*b).polygon,
                 },
             );
-            if let Some(p) = poly {
+            for p in polys {
                 g.draw_polygon(Color::PURPLE, p);
             }
         }
     }
 }
 
+struct MessageSignPlacer {
+    composite: Composite,
+    road: RoadID,
+    warns_about: BTreeSet<RoadID>,
+    draw: Drawable,
+}
+
+impl MessageSignPlacer {
+    fn new(ctx: &mut EventCtx, app: &App, road: RoadID) -> Box<dyn State> {
+        let mut placer = MessageSignPlacer {
+            composite: Composite::new(
+                Widget::col(vec![
+                    Widget::row(vec![
+                        Line("New message sign").small_heading().draw(ctx),
+                        Btn::plaintext("X")
+                            .build(ctx, "close", hotkey(Key::Escape))
+                            .align_right(),
+                    ]),
+                    format!(
+                        "Drivers on {} will be warned about trouble ahead",
+                        app.primary.map.get_r(road).get_name()
+                    )
+                    .draw_text(ctx),
+                    "Click roads to add or remove them from the warning".draw_text(ctx),
+                    Widget::row(vec![
+                        "Drivers who follow the advice:"
+                            .draw_text(ctx)
+                            .margin_right(10),
+                        Widget::dropdown(
+                            ctx,
+                            "compliance",
+                            0.25,
+                            vec![
+                                Choice::new("10%", 0.1),
+                                Choice::new("25%", 0.25),
+                                Choice::new("50%", 0.5),
+                                Choice::new("everyone", 1.0),
+                            ],
+                        ),
+                    ]),
+                    Btn::text_fg("Place sign").inactive(ctx).named("Place sign"),
+                ])
+                .bg(app.cs.panel_bg)
+                .padding(10),
+            )
+            .aligned(HorizontalAlignment::Right, VerticalAlignment::Top)
+            .build(ctx),
+            road,
+            warns_about: BTreeSet::new(),
+            draw: ctx.upload(GeomBatch::new()),
+        };
+        placer.recalc(ctx, app);
+        Box::new(placer)
+    }
+
+    fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
+        let map = &app.primary.map;
+        let mut batch = GeomBatch::new();
+        batch.push(
+            Color::BLUE.alpha(0.8),
+            map.get_r(self.road).get_thick_polygon(map).unwrap(),
+        );
+        for r in &self.warns_about {
+            batch.push(
+                Color::RED.alpha(0.8),
+                map.get_r(*r).get_thick_polygon(map).unwrap(),
+            );
+        }
+        self.draw = ctx.upload(batch);
+
+        self.composite.replace(
+            ctx,
+            "Place sign",
+            if self.warns_about.is_empty() {
+                Btn::text_fg("Place sign").inactive(ctx).named("Place sign")
+            } else {
+                Btn::text_fg("Place sign").build_def(ctx, hotkey(Key::Enter))
+            },
+        );
+    }
+}
+
+impl State for MessageSignPlacer {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Place sign" => {
+                    app.primary.sim.place_message_sign(MessageSign::new(
+                        self.road,
+                        std::mem::replace(&mut self.warns_about, BTreeSet::new()),
+                        self.composite.dropdown_value("compliance"),
+                    ));
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            },
+            None => {}
+        }
+
+        ctx.canvas_movement();
+
+        if ctx.redo_mouseover() {
+            app.primary.current_selection = app.calculate_current_selection(
+                ctx,
+                &DontDrawAgents {},
+                &ShowEverything::new(),
+                false,
+                true,
+                false,
+            );
+            if let Some(ID::Lane(l)) = app.primary.current_selection {
+                if app.primary.map.get_l(l).parent == self.road {
+                    app.primary.current_selection = None;
+                }
+            } else {
+                app.primary.current_selection = None;
+            }
+        }
+        if let Some(ID::Lane(l)) = app.primary.current_selection {
+            let r = app.primary.map.get_l(l).parent;
+            if self.warns_about.contains(&r) {
+                if app.per_obj.left_click(ctx, "stop warning about this road") {
+                    self.warns_about.remove(&r);
+                    self.recalc(ctx, app);
+                }
+            } else if app.per_obj.left_click(ctx, "warn about this road") {
+                self.warns_about.insert(r);
+                self.recalc(ctx, app);
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.composite.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
+
 // TODO This exists in a few other places, in less clear forms...
 fn path_request(
     from: TripEndpoint,
@@ -497,6 +683,28 @@ fn pos(endpt: TripEndpoint, mode: TripMode, from: bool, map: &Map) -> Option<Pos
     }
 }
 
+// Mirrors how the trip will play out: transit riders walk to and from the stops, and drivers or
+// cyclists heading to a building walk the rest of the way from where they park.
+fn itinerary(from: TripEndpoint, to: TripEndpoint, mode: TripMode, map: &Map) -> Option<Itinerary> {
+    let req = path_request(from, to.clone(), mode, map)?;
+    match (mode, to) {
+        (TripMode::Transit, _) => Some(Itinerary::walk_transit_walk(req.start, req.end, map)),
+        (TripMode::Drive, TripEndpoint::Bldg(b)) | (TripMode::Bike, TripEndpoint::Bldg(b)) => {
+            Itinerary::drive_park_walk(req, map.get_b(b).front_path.sidewalk, map)
+        }
+        _ => Some(Itinerary::single(req)),
+    }
+}
+
+fn describe_leg(mode: LegMode, map: &Map) -> String {
+    match mode {
+        LegMode::Walk => "Walk".to_string(),
+        LegMode::Drive => "Drive".to_string(),
+        LegMode::Bike => "Bike".to_string(),
+        LegMode::RideBus { route, .. } => format!("Ride {}", map.get_br(route).name),
+    }
+}
+
 pub fn spawn_agents_around(i: IntersectionID, app: &mut App) {
     let map = &app.primary.map;
     let sim = &mut app.primary.sim;
@@ -576,10 +784,31 @@ pub fn spawn_agents_around(i: IntersectionID, app: &mut App) {
     sim.tiny_step(map, &mut app.primary.sim_cb);
 }
 
-pub fn actions(_: &App, id: ID) -> Vec<(Key, String)> {
+pub fn actions(app: &App, id: ID) -> Vec<(Key, String)> {
     match id {
         ID::Building(_) => vec![(Key::Z, "start a trip here".to_string())],
-        ID::Intersection(_) => vec![(Key::Z, "spawn agents here".to_string())],
+        ID::Intersection(i) => {
+            let mut actions = vec![(Key::Z, "spawn agents here".to_string())];
+            if app.primary.map.get_i(i).is_border() {
+                actions.push((Key::T, "start a trip here".to_string()));
+            }
+            actions
+        }
+        ID::Lane(l) => {
+            let r = app.primary.map.get_l(l).parent;
+            if let Some(sign) = app.primary.sim.get_message_sign(r) {
+                vec![
+                    (Key::G, "remove message sign".to_string()),
+                    if sign.active {
+                        (Key::O, "turn off message sign".to_string())
+                    } else {
+                        (Key::O, "turn on message sign".to_string())
+                    },
+                ]
+            } else {
+                vec![(Key::G, "place a message sign here".to_string())]
+            }
+        }
         _ => Vec::new(),
     }
 }
@@ -587,12 +816,36 @@ pub fn actions(_: &App, id: ID) -> Vec<(Key, String)> {
 pub fn execute(ctx: &mut EventCtx, app: &mut App, id: ID, action: String) -> Transition {
     match (id, action.as_ref()) {
         (ID::Building(b), "start a trip here") => {
-            Transition::Push(AgentSpawner::new(ctx, app, Some(b)))
+            Transition::Push(AgentSpawner::new(ctx, app, Some(TripEndpoint::Bldg(b))))
         }
+        (ID::Intersection(i), "start a trip here") => Transition::Push(AgentSpawner::new(
+            ctx,
+            app,
+            Some(TripEndpoint::Border(i, None)),
+        )),
         (ID::Intersection(id), "spawn agents here") => {
             spawn_agents_around(id, app);
             Transition::Keep
         }
+        (ID::Lane(l), "place a message sign here") => {
+            let r = app.primary.map.get_l(l).parent;
+            Transition::Push(MessageSignPlacer::new(ctx, app, r))
+        }
+        (ID::Lane(l), "remove message sign") => {
+            let r = app.primary.map.get_l(l).parent;
+            app.primary.sim.remove_message_sign(r);
+            Transition::Keep
+        }
+        (ID::Lane(l), "turn off message sign") => {
+            let r = app.primary.map.get_l(l).parent;
+            app.primary.sim.set_message_sign_active(r, false);
+            Transition::Keep
+        }
+        (ID::Lane(l), "turn on message sign") => {
+            let r = app.primary.map.get_l(l).parent;
+            app.primary.sim.set_message_sign_active(r, true);
+            Transition::Keep
+        }
         _ => unreachable!(),
     }
 }
@@ -23,7 +23,7 @@ use ezgui::{
 use geom::{Duration, Polygon};
 use map_model::{EditCmd, EditIntersection, Map, MapEdits};
 use rand_xorshift::XorShiftRng;
-use sim::{Analytics, OrigPersonID, Scenario, ScenarioGenerator};
+use sim::{ActivityModel, Analytics, OrigPersonID, Scenario, ScenarioGenerator};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum GameplayMode {
@@ -114,6 +114,12 @@ impl GameplayMode {
                 ScenarioGenerator::small_run(map)
             })
             .generate(map, &mut rng, &mut Timer::new("generate scenario"))
+        } else if name == "activities" {
+            ActivityModel::new(num_agents.unwrap_or_else(|| map.all_buildings().len())).generate(
+                map,
+                &mut rng,
+                &mut Timer::new("generate scenario"),
+            )
         } else if name == "5 weekdays repeated" {
             let s: Scenario =
                 abstutil::read_binary(abstutil::path_scenario(map.get_name(), "weekday"), timer);
@@ -157,11 +163,19 @@ impl GameplayMode {
             match cmd {
                 EditCmd::ChangeLaneType { .. }
                 | EditCmd::ReverseLane { .. }
-                | EditCmd::ChangeSpeedLimit { .. } => {
+                | EditCmd::ChangeSpeedLimit { .. }
+                | EditCmd::ChangeAccessRestrictions { .. }
+                | EditCmd::ChangeCurbRegulation { .. }
+                | EditCmd::ChangeBusRoute { .. } => {
                     if !self.can_edit_lanes() {
                         return false;
                     }
                 }
+                EditCmd::ChangeBlockTheBox { .. } => {
+                    if !self.can_edit_stop_signs() {
+                        return false;
+                    }
+                }
                 EditCmd::ChangeIntersection { ref new, .. } => match new {
                     // TODO Conflating construction
                     EditIntersection::StopSign(_) | EditIntersection::Closed => {
@@ -1185,6 +1185,7 @@ impl TutorialState {
                             percent_driving: 1.0,
                             percent_biking: 0.0,
                             percent_use_transit: 0.0,
+                            percent_park_and_ride: 0.0,
                         }],
                         border_spawn_over_time: Vec::new(),
                     }
@@ -18,13 +18,14 @@ use crate::layer::PickLayer;
 use crate::managed::{WrappedComposite, WrappedOutcome};
 use crate::pregame::MainMenu;
 use crate::render::AgentColorScheme;
+use abstutil::prettyprint_usize;
 use ezgui::{
     hotkey, lctrl, Btn, Choice, Color, Composite, EventCtx, EventLoopMode, GeomBatch, GfxCtx,
     HorizontalAlignment, Key, Line, Outcome, Text, TextExt, VerticalAlignment, Widget, Wizard,
 };
 pub use gameplay::{spawn_agents_around, GameplayMode, TutorialPointer, TutorialState};
-use geom::{Polygon, Time};
-use map_model::MapEdits;
+use geom::{Polygon, Speed, Time};
+use map_model::{MapEdits, RoadID};
 use sim::{TripMode, VehicleType};
 pub use speed::TimeWarpScreen;
 pub use speed::{SpeedControls, TimePanel};
@@ -197,6 +198,12 @@ impl State for SandboxMode {
     }
 
     fn draw(&self, g: &mut GfxCtx, app: &App) {
+        // Layers underneath only have their contents drawn, and only when unzoomed
+        if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+            for l in &app.layers_below {
+                l.draw_minimap(g);
+            }
+        }
         if let Some(ref l) = app.layer {
             l.draw(g, app);
         }
@@ -226,13 +233,33 @@ impl State for SandboxMode {
         }
 
         self.gameplay.draw(g, app);
+
+        // Only while the sim is running, and not while some other panel has the mouse
+        if app.primary.sim.time() > Time::START_OF_DAY
+            && self.controls.common.is_some()
+            && g.canvas.get_cursor_in_map_space().is_some()
+        {
+            let road = match app.primary.current_selection {
+                Some(ID::Road(r)) => Some(r),
+                Some(ID::Lane(l)) => Some(app.primary.map.get_l(l).parent),
+                _ => None,
+            };
+            if let Some(r) = road {
+                g.draw_mouse_tooltip(road_tooltip(app, r));
+            }
+        }
     }
 
     fn on_destroy(&mut self, _: &mut EventCtx, app: &mut App) {
         app.layer = None;
+        app.layers_below.clear();
         app.agent_cs = AgentColorScheme::new(&app.cs);
         self.gameplay.on_destroy(app);
     }
+
+    fn breadcrumb(&self) -> Option<String> {
+        Some("Sandbox".to_string())
+    }
 }
 
 pub fn maybe_exit_sandbox() -> Transition {
@@ -368,6 +395,35 @@ impl AgentMeter {
     }
 }
 
+// Summarize traffic on a road without opening the info panel
+fn road_tooltip(app: &App, r: RoadID) -> Text {
+    let road = app.primary.map.get_r(r);
+    let speeds = app.primary.sim.current_speeds_on_road(r, &app.primary.map);
+    let mut txt = Text::from(Line(road.get_name()).small_heading());
+    txt.add(Line(format!(
+        "{} vehicles here now",
+        prettyprint_usize(speeds.len())
+    )));
+    if !speeds.is_empty() {
+        let avg = Speed::meters_per_second(
+            speeds
+                .iter()
+                .map(|x| x.inner_meters_per_second())
+                .sum::<f64>()
+                / (speeds.len() as f64),
+        );
+        txt.add(Line(format!(
+            "Average speed {} (free-flow {})",
+            avg, road.speed_limit
+        )));
+    }
+    txt.add(Line(format!(
+        "{} crossed today",
+        prettyprint_usize(app.primary.sim.get_analytics().road_thruput.total_for(r))
+    )));
+    txt
+}
+
 // pub for Warping
 pub struct Actions {
     is_paused: bool,
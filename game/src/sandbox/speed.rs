@@ -5,8 +5,8 @@ use crate::helpers::ID;
 use crate::sandbox::{GameplayMode, SandboxMode};
 use ezgui::{
     hotkey, AreaSlider, Btn, Choice, Color, Composite, EventCtx, EventLoopMode, GeomBatch, GfxCtx,
-    HorizontalAlignment, Key, Line, Outcome, PersistentSplit, RewriteColor, Text,
-    VerticalAlignment, Widget,
+    HorizontalAlignment, Key, Line, Outcome, PersistentSplit, Slider, Text, VerticalAlignment,
+    Widget,
 };
 use geom::{Duration, Polygon, Pt2D, Time};
 use instant::Instant;
@@ -31,8 +31,14 @@ enum SpeedSetting {
     Fastest,
 }
 
+const SPEED_SETTINGS: [SpeedSetting; 4] = [
+    SpeedSetting::Realtime,
+    SpeedSetting::Fast,
+    SpeedSetting::Faster,
+    SpeedSetting::Fastest,
+];
+
 impl SpeedControls {
-    // TODO Could use checkbox here, but not sure it'll make things that much simpler.
     fn make_panel(ctx: &mut EventCtx, app: &App, paused: bool, setting: SpeedSetting) -> Composite {
         let mut row = Vec::new();
         row.push(
@@ -50,31 +56,19 @@ impl SpeedControls {
         );
 
         row.push(
-            Widget::row(
-                vec![
-                    (SpeedSetting::Realtime, "real-time speed"),
-                    (SpeedSetting::Fast, "5x speed"),
-                    (SpeedSetting::Faster, "30x speed"),
-                    (SpeedSetting::Fastest, "3600x speed"),
-                ]
-                .into_iter()
-                .map(|(s, label)| {
-                    let mut txt = Text::from(Line(label).small());
-                    txt.extend(Text::tooltip(ctx, hotkey(Key::LeftArrow), "slow down"));
-                    txt.extend(Text::tooltip(ctx, hotkey(Key::RightArrow), "speed up"));
-
-                    Btn::svg_def("../data/system/assets/speed/triangle.svg")
-                        .normal_color(if setting >= s {
-                            RewriteColor::NoOp
-                        } else {
-                            RewriteColor::ChangeAll(Color::WHITE.alpha(0.2))
-                        })
-                        .pad(3)
-                        .tooltip(txt)
-                        .build(ctx, label, None)
-                })
-                .collect(),
-            )
+            Widget::row(vec![
+                Slider::horizontal_steps(
+                    ctx,
+                    120.0,
+                    30.0,
+                    SPEED_SETTINGS.len(),
+                    SPEED_SETTINGS.iter().position(|s| *s == setting).unwrap(),
+                )
+                .named("speed")
+                .centered_vert()
+                .margin_right(8),
+                SpeedControls::speed_label(ctx, setting),
+            ])
             .bg(app.cs.section_bg)
             .centered()
             .padding(6)
@@ -118,6 +112,18 @@ impl SpeedControls {
             .build(ctx)
     }
 
+    fn speed_label(ctx: &EventCtx, setting: SpeedSetting) -> Widget {
+        let label = match setting {
+            SpeedSetting::Realtime => "real-time speed",
+            SpeedSetting::Fast => "5x speed",
+            SpeedSetting::Faster => "30x speed",
+            SpeedSetting::Fastest => "3600x speed",
+        };
+        Text::from(Line(label).small())
+            .draw(ctx)
+            .named("speed label")
+    }
+
     pub fn new(ctx: &mut EventCtx, app: &App) -> SpeedControls {
         let composite = SpeedControls::make_panel(ctx, app, false, SpeedSetting::Realtime);
         SpeedControls {
@@ -135,26 +141,6 @@ impl SpeedControls {
     ) -> Option<Transition> {
         match self.composite.event(ctx) {
             Some(Outcome::Clicked(x)) => match x.as_ref() {
-                "real-time speed" => {
-                    self.setting = SpeedSetting::Realtime;
-                    self.composite = SpeedControls::make_panel(ctx, app, self.paused, self.setting);
-                    return None;
-                }
-                "5x speed" => {
-                    self.setting = SpeedSetting::Fast;
-                    self.composite = SpeedControls::make_panel(ctx, app, self.paused, self.setting);
-                    return None;
-                }
-                "30x speed" => {
-                    self.setting = SpeedSetting::Faster;
-                    self.composite = SpeedControls::make_panel(ctx, app, self.paused, self.setting);
-                    return None;
-                }
-                "3600x speed" => {
-                    self.setting = SpeedSetting::Fastest;
-                    self.composite = SpeedControls::make_panel(ctx, app, self.paused, self.setting);
-                    return None;
-                }
                 "play" => {
                     self.paused = false;
                     self.composite = SpeedControls::make_panel(ctx, app, self.paused, self.setting);
@@ -202,7 +188,21 @@ impl SpeedControls {
                 }
                 _ => unreachable!(),
             },
-            None => {}
+            None => {
+                // Don't rebuild the whole panel, or dragging the slider would stop
+                let setting = SPEED_SETTINGS[self
+                    .composite
+                    .slider("speed")
+                    .get_value(SPEED_SETTINGS.len())];
+                if setting != self.setting {
+                    self.setting = setting;
+                    self.composite.replace(
+                        ctx,
+                        "speed label",
+                        SpeedControls::speed_label(ctx, setting),
+                    );
+                }
+            }
         }
         // Just kind of constantly scrape this
         app.opts.time_increment = self.composite.persistent_split_value("step forwards");
@@ -635,21 +635,19 @@ impl TimePanel {
                             .to_percent(app.primary.sim.get_end_of_day())
                             .min(1.0);
 
-                        // TODO Why is the rounding so hard? The white background is always rounded
-                        // at both ends. The moving bar should always be rounded on the left, flat
-                        // on the right, except at the very end (for the last 'radius' pixels). And
-                        // when the width is too small for the radius, this messes up.
-
-                        batch.push(Color::WHITE, Polygon::rectangle(width, height));
-
+                        // The moving bar is rounded on the left and flat on the right, except at
+                        // the very end. Clip it to the background to get that.
+                        let background = Polygon::pill(width, height);
+                        batch.push(Color::WHITE, background.clone());
                         if percent != 0.0 {
-                            batch.push(
+                            batch.extend(
                                 if percent < 0.25 || percent > 0.75 {
                                     app.cs.night_time_slider
                                 } else {
                                     app.cs.day_time_slider
                                 },
-                                Polygon::rectangle(percent * width, height),
+                                background
+                                    .intersection(&Polygon::rectangle(percent * width, height)),
                             );
                         }
 
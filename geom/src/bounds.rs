@@ -1,6 +1,7 @@
-use crate::{LonLat, Polygon, Pt2D};
+use crate::{Distance, LonLat, Polygon, Pt2D};
 use aabb_quadtree::geom::{Point, Rect};
 use serde::{Deserialize, Serialize};
+use std::iter::FromIterator;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bounds {
@@ -44,6 +45,41 @@ impl Bounds {
         pt.x() >= self.min_x && pt.x() <= self.max_x && pt.y() >= self.min_y && pt.y() <= self.max_y
     }
 
+    // Touching edges count
+    pub fn contains_bounds(&self, other: &Bounds) -> bool {
+        other.min_x >= self.min_x
+            && other.max_x <= self.max_x
+            && other.min_y >= self.min_y
+            && other.max_y <= self.max_y
+    }
+
+    // None if they don't overlap at all. Bounds that only share an edge have a zero-width
+    // intersection.
+    pub fn intersection(&self, other: &Bounds) -> Option<Bounds> {
+        let b = Bounds {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+        if b.min_x > b.max_x || b.min_y > b.max_y {
+            None
+        } else {
+            Some(b)
+        }
+    }
+
+    // Grows every side outwards
+    pub fn inflate(&self, dist: Distance) -> Bounds {
+        let d = dist.inner_meters();
+        Bounds {
+            min_x: self.min_x - d,
+            min_y: self.min_y - d,
+            max_x: self.max_x + d,
+            max_y: self.max_y + d,
+        }
+    }
+
     pub fn as_bbox(&self) -> Rect {
         Rect {
             top_left: Point {
@@ -82,6 +118,26 @@ impl Bounds {
     }
 }
 
+impl FromIterator<Pt2D> for Bounds {
+    fn from_iter<I: IntoIterator<Item = Pt2D>>(pts: I) -> Bounds {
+        let mut b = Bounds::new();
+        for pt in pts {
+            b.update(pt);
+        }
+        b
+    }
+}
+
+impl<'a> FromIterator<&'a Polygon> for Bounds {
+    fn from_iter<I: IntoIterator<Item = &'a Polygon>>(polygons: I) -> Bounds {
+        let mut b = Bounds::new();
+        for p in polygons {
+            b.union(p.get_bounds());
+        }
+        b
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GPSBounds {
     pub(crate) min_lon: f64,
@@ -167,3 +223,34 @@ impl GPSBounds {
                 .approx_eq(LonLat::new(other.max_lon, other.max_lat))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x1: f64, y1: f64, x2: f64, y2: f64) -> Bounds {
+        vec![Pt2D::new(x1, y1), Pt2D::new(x2, y2)]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn intersection() {
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let i = a.intersection(&rect(5.0, -5.0, 20.0, 5.0)).unwrap();
+        assert_eq!((i.min_x, i.min_y, i.max_x, i.max_y), (5.0, 0.0, 10.0, 5.0));
+        assert!(a.intersection(&rect(11.0, 0.0, 12.0, 1.0)).is_none());
+        assert!(a.contains_bounds(&i));
+        assert!(!i.contains_bounds(&a));
+    }
+
+    #[test]
+    fn inflate() {
+        let b = rect(0.0, 0.0, 10.0, 10.0).inflate(Distance::meters(2.0));
+        assert_eq!(
+            (b.min_x, b.min_y, b.max_x, b.max_y),
+            (-2.0, -2.0, 12.0, 12.0)
+        );
+        assert!(b.contains_bounds(&rect(0.0, 0.0, 10.0, 10.0)));
+    }
+}
@@ -0,0 +1,157 @@
+// Most of a serialized map is lists of points. Binary formats store each one as two f64s, but
+// Pt2D::new already trims everything to 0.1mm, and neighboring points are close together. So store
+// each point as the difference from the previous one, in units of 0.1mm, as variable-length
+// integers. Usually that's a few bytes per coordinate instead of 8.
+//
+// Human-readable formats like JSON keep the plain representation.
+
+use crate::Pt2D;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const PRECISION: f64 = 10_000.0;
+
+// Use with #[serde(with = "crate::compact::pts")]
+pub mod pts {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pts: &[Pt2D], s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            pts.serialize(s)
+        } else {
+            encode_pts(pts).serialize(s)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Pt2D>, D::Error> {
+        if d.is_human_readable() {
+            Vec::<Pt2D>::deserialize(d)
+        } else {
+            let bytes = Vec::<u8>::deserialize(d)?;
+            decode_pts(&bytes).ok_or_else(|| serde::de::Error::custom("corrupt compact points"))
+        }
+    }
+}
+
+// Use with #[serde(with = "crate::compact::indices")]
+pub mod indices {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(indices: &[usize], s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            indices.serialize(s)
+        } else {
+            let mut bytes = Vec::new();
+            for idx in indices {
+                write_varint(&mut bytes, *idx as u64);
+            }
+            bytes.serialize(s)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<usize>, D::Error> {
+        if d.is_human_readable() {
+            Vec::<usize>::deserialize(d)
+        } else {
+            let bytes = Vec::<u8>::deserialize(d)?;
+            let mut indices = Vec::new();
+            let mut pos = 0;
+            while pos < bytes.len() {
+                let idx = read_varint(&bytes, &mut pos)
+                    .ok_or_else(|| serde::de::Error::custom("corrupt compact indices"))?;
+                indices.push(idx as usize);
+            }
+            Ok(indices)
+        }
+    }
+}
+
+fn encode_pts(pts: &[Pt2D]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut last = (0, 0);
+    for pt in pts {
+        let x = (pt.x() * PRECISION).round() as i64;
+        let y = (pt.y() * PRECISION).round() as i64;
+        write_varint(&mut bytes, zigzag(x - last.0));
+        write_varint(&mut bytes, zigzag(y - last.1));
+        last = (x, y);
+    }
+    bytes
+}
+
+fn decode_pts(bytes: &[u8]) -> Option<Vec<Pt2D>> {
+    let mut pts = Vec::new();
+    let mut last = (0, 0);
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let x = last.0 + unzigzag(read_varint(bytes, &mut pos)?);
+        let y = last.1 + unzigzag(read_varint(bytes, &mut pos)?);
+        pts.push(Pt2D::new(x as f64 / PRECISION, y as f64 / PRECISION));
+        last = (x, y);
+    }
+    Some(pts)
+}
+
+// Small negative numbers become small positive numbers.
+fn zigzag(x: i64) -> u64 {
+    ((x << 1) ^ (x >> 63)) as u64
+}
+
+fn unzigzag(x: u64) -> i64 {
+    ((x >> 1) as i64) ^ -((x & 1) as i64)
+}
+
+// 7 bits at a time, with the high bit set when there's more to come
+fn write_varint(bytes: &mut Vec<u8>, mut x: u64) {
+    while x >= 0x80 {
+        bytes.push((x as u8) | 0x80);
+        x >>= 7;
+    }
+    bytes.push(x as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift >= 64 {
+            return None;
+        }
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_pts() {
+        let pts = vec![
+            Pt2D::new(0.0, 0.0),
+            Pt2D::new(1234.5678, 9.1),
+            Pt2D::new(1234.5677, 12000.0),
+            Pt2D::new(0.0001, 3.3333),
+        ];
+        let bytes = encode_pts(&pts);
+        assert!(bytes.len() < pts.len() * 16);
+        let decoded = decode_pts(&bytes).unwrap();
+        assert_eq!(pts.len(), decoded.len());
+        for (pt1, pt2) in pts.iter().zip(decoded.iter()) {
+            // Exact, not just within EPSILON_DIST
+            assert_eq!(pt1.x().to_bits(), pt2.x().to_bits());
+            assert_eq!(pt1.y().to_bits(), pt2.y().to_bits());
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = encode_pts(&[Pt2D::new(5000.0, 5000.0)]);
+        assert!(decode_pts(&bytes[0..bytes.len() - 1]).is_none());
+    }
+}
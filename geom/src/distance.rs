@@ -1,4 +1,4 @@
-use crate::{trim_f64, Duration, Speed};
+use crate::{trim_f64, Duration, Speed, UnitSystem};
 use serde::{Deserialize, Serialize};
 use std::{cmp, f64, fmt, ops};
 
@@ -59,22 +59,44 @@ impl Distance {
         self.0
     }
 
-    // TODO Store a bit in Maps to mark if they're in the US or not, plumb here to use meters
+    pub fn feet(value: f64) -> Distance {
+        Distance::meters(0.3048 * value)
+    }
+
+    pub fn inner_feet(self) -> f64 {
+        self.0 * 3.28084
+    }
+
+    // Uses the current UnitSystem.
     pub fn describe_rounded(self) -> String {
-        let feet = self.0 * 3.28084;
-        let miles = feet / 5280.0;
-        if miles >= 0.1 {
-            format!("{} miles", (miles * 10.0).round() / 10.0)
-        } else {
-            format!("{} ft", feet.round())
+        match UnitSystem::current() {
+            UnitSystem::Metric => {
+                let km = self.0 / 1000.0;
+                if km >= 0.1 {
+                    format!("{} km", (km * 10.0).round() / 10.0)
+                } else {
+                    format!("{} m", self.0.round())
+                }
+            }
+            UnitSystem::Imperial => {
+                let feet = self.inner_feet();
+                let miles = feet / 5280.0;
+                if miles >= 0.1 {
+                    format!("{} miles", (miles * 10.0).round() / 10.0)
+                } else {
+                    format!("{} ft", feet.round())
+                }
+            }
         }
     }
 }
 
 impl fmt::Display for Distance {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // TODO This is harder to localize
-        write!(f, "{}m", self.0)
+        match UnitSystem::current() {
+            UnitSystem::Metric => write!(f, "{}m", self.0),
+            UnitSystem::Imperial => write!(f, "{}ft", trim_f64(self.inner_feet())),
+        }
     }
 }
 
@@ -0,0 +1,248 @@
+use crate::{Bounds, Distance, PolyLine, Polygon, Pt2D};
+
+// Rasterizes some sources (points or lines) over an area, and stores the distance from every cell
+// to the nearest source. Useful for coverage overlays, like "everywhere more than 400m from a bus
+// stop."
+pub struct DistanceGrid {
+    bounds: Bounds,
+    resolution: Distance,
+    width: usize,
+    height: usize,
+    // Indexed by y * width + x. The distance from the cell's center to the nearest source, or
+    // None if there are no sources at all.
+    dist: Vec<Option<Distance>>,
+}
+
+impl DistanceGrid {
+    // Each cell is a square with sides of the given resolution.
+    pub fn from_pts(bounds: &Bounds, resolution: Distance, sources: Vec<Pt2D>) -> DistanceGrid {
+        assert!(resolution > Distance::ZERO);
+        let res = resolution.inner_meters();
+        let width = ((bounds.width() / res).ceil() as usize).max(1);
+        let height = ((bounds.height() / res).ceil() as usize).max(1);
+        let mut grid = DistanceGrid {
+            bounds: bounds.clone(),
+            resolution,
+            width,
+            height,
+            dist: Vec::new(),
+        };
+
+        // Track the nearest source for each cell, and propagate it to neighbors. Two passes (down
+        // and to the right, then back up) are almost always exact; the tiny error in the rare
+        // case is well below the resolution.
+        let mut nearest: Vec<Option<Pt2D>> = vec![None; width * height];
+        for pt in sources {
+            if let Some(idx) = grid.idx(pt) {
+                let center = grid.center(idx % width, idx / width);
+                let better = match nearest[idx] {
+                    Some(old) => center.raw_dist_to(pt) < center.raw_dist_to(old),
+                    None => true,
+                };
+                if better {
+                    nearest[idx] = Some(pt);
+                }
+            }
+        }
+
+        let forwards = [(-1, 0), (-1, -1), (0, -1), (1, -1)];
+        let backwards = [(1, 0), (1, 1), (0, 1), (-1, 1)];
+        for y in 0..height {
+            for x in 0..width {
+                grid.relax(&mut nearest, x, y, &forwards);
+            }
+        }
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                grid.relax(&mut nearest, x, y, &backwards);
+            }
+        }
+
+        grid.dist = nearest
+            .into_iter()
+            .enumerate()
+            .map(|(idx, pt)| pt.map(|pt| grid.center(idx % width, idx / width).dist_to(pt)))
+            .collect();
+        grid
+    }
+
+    // Distance to the nearest point along any of the lines.
+    pub fn from_polylines(
+        bounds: &Bounds,
+        resolution: Distance,
+        sources: Vec<&PolyLine>,
+    ) -> DistanceGrid {
+        // Sampling twice per cell keeps the error under the resolution.
+        let step = resolution / 2.0;
+        let mut pts = Vec::new();
+        for pl in sources {
+            let mut dist = Distance::ZERO;
+            while dist < pl.length() {
+                pts.push(pl.dist_along(dist).0);
+                dist += step;
+            }
+            pts.push(pl.last_pt());
+        }
+        DistanceGrid::from_pts(bounds, resolution, pts)
+    }
+
+    // None if the point is out of bounds or there are no sources.
+    pub fn dist_at(&self, pt: Pt2D) -> Option<Distance> {
+        self.dist[self.idx(pt)?]
+    }
+
+    // Squares covering every cell at least this far from any source.
+    pub fn cells_farther_than(&self, threshold: Distance) -> Vec<Polygon> {
+        self.cells_where(|_, dist| dist.map(|d| d >= threshold).unwrap_or(true))
+    }
+
+    // Squares covering every cell that passes the filter, given the cell's center and distance to
+    // the nearest source. Adjacent cells in a row are merged, to keep the number of polygons down.
+    pub fn cells_where<F: Fn(Pt2D, Option<Distance>) -> bool>(&self, filter: F) -> Vec<Polygon> {
+        let res = self.resolution.inner_meters();
+        let mut polygons = Vec::new();
+        for y in 0..self.height {
+            let mut start: Option<usize> = None;
+            for x in 0..=self.width {
+                let far =
+                    x < self.width && filter(self.center(x, y), self.dist[y * self.width + x]);
+                match (far, start) {
+                    (true, None) => {
+                        start = Some(x);
+                    }
+                    (false, Some(x1)) => {
+                        polygons.push(Polygon::rectangle(((x - x1) as f64) * res, res).translate(
+                            self.bounds.min_x + (x1 as f64) * res,
+                            self.bounds.min_y + (y as f64) * res,
+                        ));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        polygons
+    }
+
+    fn idx(&self, pt: Pt2D) -> Option<usize> {
+        if !self.bounds.contains(pt) {
+            return None;
+        }
+        let res = self.resolution.inner_meters();
+        let x = (((pt.x() - self.bounds.min_x) / res) as usize).min(self.width - 1);
+        let y = (((pt.y() - self.bounds.min_y) / res) as usize).min(self.height - 1);
+        Some(y * self.width + x)
+    }
+
+    fn center(&self, x: usize, y: usize) -> Pt2D {
+        let res = self.resolution.inner_meters();
+        Pt2D::new(
+            self.bounds.min_x + ((x as f64) + 0.5) * res,
+            self.bounds.min_y + ((y as f64) + 0.5) * res,
+        )
+    }
+
+    fn relax(&self, nearest: &mut [Option<Pt2D>], x: usize, y: usize, offsets: &[(isize, isize)]) {
+        let center = self.center(x, y);
+        let idx = y * self.width + x;
+        for (dx, dy) in offsets {
+            let nx = (x as isize) + dx;
+            let ny = (y as isize) + dy;
+            if nx < 0 || ny < 0 || nx >= self.width as isize || ny >= self.height as isize {
+                continue;
+            }
+            if let Some(candidate) = nearest[(ny as usize) * self.width + (nx as usize)] {
+                let better = match nearest[idx] {
+                    Some(old) => center.raw_dist_to(candidate) < center.raw_dist_to(old),
+                    None => true,
+                };
+                if better {
+                    nearest[idx] = Some(candidate);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> Bounds {
+        Bounds::from(&vec![Pt2D::new(0.0, 0.0), Pt2D::new(size, size)])
+    }
+
+    // Cells are 1m, so their centers are at 0.5, 1.5, ...
+    #[test]
+    fn distance_to_points() {
+        let sources = vec![Pt2D::new(0.5, 0.5), Pt2D::new(7.5, 3.5)];
+        let grid = DistanceGrid::from_pts(&square(10.0), Distance::meters(1.0), sources.clone());
+        for y in 0..10 {
+            for x in 0..10 {
+                let center = Pt2D::new((x as f64) + 0.5, (y as f64) + 0.5);
+                let expected = sources
+                    .iter()
+                    .map(|pt| center.raw_dist_to(*pt))
+                    .fold(std::f64::MAX, f64::min);
+                let actual = grid.dist_at(center).unwrap().inner_meters();
+                // Propagating the nearest source between neighbors can rarely pick the wrong one,
+                // but never by much
+                assert!(actual >= expected - 1e-6, "at {}", center);
+                assert!(actual - expected < 0.5, "at {}", center);
+            }
+        }
+
+        // With just one source, every cell is exact
+        let grid = DistanceGrid::from_pts(&square(10.0), Distance::meters(1.0), vec![sources[1]]);
+        for y in 0..10 {
+            for x in 0..10 {
+                let center = Pt2D::new((x as f64) + 0.5, (y as f64) + 0.5);
+                let actual = grid.dist_at(center).unwrap().inner_meters();
+                assert!((actual - center.raw_dist_to(sources[1])).abs() < 1e-6);
+            }
+        }
+
+        assert_eq!(grid.dist_at(Pt2D::new(11.0, 5.0)), None);
+        assert_eq!(
+            DistanceGrid::from_pts(&square(10.0), Distance::meters(1.0), Vec::new())
+                .dist_at(Pt2D::new(5.0, 5.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn distance_to_polylines() {
+        // A horizontal line across the middle, and a short vertical one in a corner
+        let horiz = PolyLine::new(vec![Pt2D::new(0.0, 5.0), Pt2D::new(10.0, 5.0)]);
+        let vert = PolyLine::new(vec![Pt2D::new(9.0, 0.0), Pt2D::new(9.0, 2.0)]);
+        let res = Distance::meters(1.0);
+        let grid = DistanceGrid::from_polylines(&square(10.0), res, vec![&horiz, &vert]);
+        for y in 0..10 {
+            for x in 0..10 {
+                let center = Pt2D::new((x as f64) + 0.5, (y as f64) + 0.5);
+                let to_horiz = (center.y() - 5.0).abs();
+                let to_vert = center.raw_dist_to(Pt2D::new(9.0, center.y().min(2.0)));
+                let expected = to_horiz.min(to_vert);
+                let actual = grid.dist_at(center).unwrap().inner_meters();
+                // Sampling the lines can only overestimate, and not by more than the resolution
+                assert!(actual >= expected - 1e-6, "at {}", center);
+                assert!(actual - expected < res.inner_meters(), "at {}", center);
+            }
+        }
+    }
+
+    #[test]
+    fn far_cells() {
+        // Only the bottom row's centers are at least 9m from the top edge
+        let top = PolyLine::new(vec![Pt2D::new(0.0, 0.0), Pt2D::new(10.0, 0.0)]);
+        let grid = DistanceGrid::from_polylines(&square(10.0), Distance::meters(1.0), vec![&top]);
+        let far = grid.cells_farther_than(Distance::meters(9.0));
+        assert_eq!(far.len(), 1);
+        assert!((far[0].area() - 10.0).abs() < 1e-6);
+
+        // Nothing passes a filter that checks the distance, when there are no sources
+        let empty = DistanceGrid::from_pts(&square(10.0), Distance::meters(1.0), Vec::new());
+        assert!(empty.cells_where(|_, dist| dist.is_some()).is_empty());
+        assert_eq!(empty.cells_farther_than(Distance::ZERO).len(), 10);
+    }
+}
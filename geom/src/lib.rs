@@ -1,7 +1,9 @@
 mod angle;
 mod bounds;
 mod circle;
+mod compact;
 mod distance;
+mod distance_grid;
 mod duration;
 mod find_closest;
 mod gps;
@@ -12,23 +14,29 @@ mod pt;
 mod ring;
 mod speed;
 mod stats;
+mod sweep;
 mod time;
+mod triangulate;
+mod units;
 
 pub use crate::angle::Angle;
 pub use crate::bounds::{Bounds, GPSBounds};
 pub use crate::circle::Circle;
 pub use crate::distance::Distance;
+pub use crate::distance_grid::DistanceGrid;
 pub use crate::duration::Duration;
 pub use crate::find_closest::FindClosest;
 pub use crate::gps::LonLat;
 pub use crate::line::{InfiniteLine, Line};
 pub use crate::polygon::{Polygon, Triangle};
-pub use crate::polyline::{ArrowCap, PolyLine};
-pub use crate::pt::{HashablePt2D, Pt2D};
+pub use crate::polyline::{ArrowCap, LineCap, LineJoin, LineStyle, PolyLine};
+pub use crate::pt::{HashablePt2D, Pt2D, QuantizedPt2D};
 pub use crate::ring::Ring;
 pub use crate::speed::Speed;
 pub use crate::stats::{HgramValue, Histogram, Statistic};
+pub use crate::sweep::find_intersections;
 pub use crate::time::Time;
+pub use crate::units::{describe_area, UnitSystem};
 
 // About 0.4 inches... which is quite tiny on the scale of things. :)
 pub const EPSILON_DIST: Distance = Distance::const_meters(0.01);
@@ -1,4 +1,5 @@
 use crate::{Angle, Distance, PolyLine, Polygon, Pt2D, EPSILON_DIST};
+use abstutil::Error;
 use geo::prelude::ClosestPoint;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -9,18 +10,20 @@ pub struct Line(Pt2D, Pt2D);
 
 impl Line {
     pub fn new(pt1: Pt2D, pt2: Pt2D) -> Line {
+        Line::try_new(pt1, pt2).unwrap()
+    }
+
+    // Like new, but for input that might be degenerate, like geometry straight from OSM.
+    pub fn try_new(pt1: Pt2D, pt2: Pt2D) -> Result<Line, Error> {
         let len = pt1.dist_to(pt2);
         if len < EPSILON_DIST {
-            panic!("Tiny line with length {}", len);
+            return Err(Error::new(format!("Tiny line with length {}", len)));
         }
-        Line(pt1, pt2)
+        Ok(Line(pt1, pt2))
     }
 
     pub fn maybe_new(pt1: Pt2D, pt2: Pt2D) -> Option<Line> {
-        if pt1 == pt2 {
-            return None;
-        }
-        Some(Line::new(pt1, pt2))
+        Line::try_new(pt1, pt2).ok()
     }
 
     pub fn infinite(&self) -> InfiniteLine {
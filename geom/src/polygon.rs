@@ -1,4 +1,6 @@
+use crate::triangulate::triangulate;
 use crate::{Angle, Bounds, Distance, HashablePt2D, PolyLine, Pt2D, Ring};
+use abstutil::Error;
 use geo::algorithm::area::Area;
 use geo::algorithm::convexhull::ConvexHull;
 use geo_booleanop::boolean::BooleanOp;
@@ -7,19 +9,16 @@ use std::fmt;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Polygon {
+    #[serde(with = "crate::compact::pts")]
     points: Vec<Pt2D>,
     // Groups of three indices make up the triangles
     // TODO u32 better for later, but then we can't index stuff!
+    #[serde(with = "crate::compact::indices")]
     indices: Vec<usize>,
 }
 
-// TODO The triangulation is a bit of a mess. Everything except for Polygon::new comes from
-// https://github.com/lionfish0/earclip/blob/master/earclip/__init__.py.
-
 impl Polygon {
     // TODO Should the first and last points match or not?
-    // Adapted from https://crates.io/crates/polygon2; couldn't use the crate directly because it
-    // depends on nightly.
     pub fn new(orig_pts: &Vec<Pt2D>) -> Polygon {
         assert!(orig_pts.len() >= 3);
 
@@ -30,55 +29,37 @@ impl Polygon {
         } else {
             orig_pts.clone()
         };
+        let indices = triangulate(&pts, &[]);
 
-        let mut indices: Vec<usize> = Vec::new();
-        let mut avl = Vec::with_capacity(pts.len());
-        for i in 0..pts.len() {
-            avl.push(i);
+        Polygon {
+            points: pts,
+            indices,
         }
+    }
 
-        let mut i = 0;
-        let mut al = pts.len();
-        while al > 3 {
-            let i0 = avl[i % al];
-            let i1 = avl[(i + 1) % al];
-            let i2 = avl[(i + 2) % al];
-
-            let tri = Triangle::new(pts[i0], pts[i1], pts[i2]);
-            let mut ear_found = false;
-            if tri.is_convex() {
-                ear_found = true;
-
-                for vi in avl.iter().take(al) {
-                    if *vi != i0 && *vi != i1 && *vi != i2 && tri.contains_pt(pts[*vi]) {
-                        ear_found = false;
-                        break;
-                    }
-                }
-            }
-
-            if ear_found {
-                indices.push(i0);
-                indices.push(i1);
-                indices.push(i2);
-                avl.remove((i + 1) % al);
-                al -= 1;
-                i = 0;
-            } else if i > 3 * al {
-                break;
-            } else {
-                i += 1;
-            }
+    // Unlike new, fails when there are too few points or they enclose no area, instead of
+    // producing a polygon with nothing to draw.
+    pub fn try_new(pts: &Vec<Pt2D>) -> Result<Polygon, Error> {
+        if pts.len() < 3 {
+            return Err(Error::new(format!("Polygon with only {} pts", pts.len())));
         }
+        let result = Polygon::new(pts);
+        if result.indices.is_empty() {
+            return Err(Error::new(format!("Polygon has no area: {:?}", pts)));
+        }
+        Ok(result)
+    }
 
-        indices.push(avl[0]);
-        indices.push(avl[1]);
-        indices.push(avl[2]);
-
-        Polygon {
-            points: pts,
-            indices,
+    // The points of the outer ring come first, then each hole. Like anything not built from a
+    // single ring, to_outline won't work on the result.
+    pub fn with_holes(outer: Ring, holes: Vec<Ring>) -> Polygon {
+        let holes: Vec<Vec<Pt2D>> = holes.into_iter().map(|r| r.into_points()).collect();
+        let mut points = outer.into_points();
+        let indices = triangulate(&points, &holes);
+        for hole in holes {
+            points.extend(hole);
         }
+        Polygon { points, indices }
     }
 
     pub fn precomputed(points: Vec<Pt2D>, indices: Vec<usize>) -> Polygon {
@@ -211,11 +192,11 @@ impl Polygon {
     }
 
     // Top-left at the origin. Doesn't take Distance, because this is usually pixels, actually.
-    // If radius is None, be as round as possible
+    // If radius is None, be as round as possible. A radius too big for the rectangle is shrunk to
+    // fit, so callers with changing sizes don't have to check.
     pub fn rounded_rectangle(w: f64, h: f64, r: Option<f64>) -> Polygon {
-        let r = r.unwrap_or_else(|| w.min(h) / 2.0);
-        assert!(2.0 * r <= w);
-        assert!(2.0 * r <= h);
+        let max_r = w.min(h) / 2.0;
+        let r = r.unwrap_or(max_r).min(max_r);
 
         let mut pts = vec![];
 
@@ -246,6 +227,11 @@ impl Polygon {
         Polygon::new(&pts)
     }
 
+    // A rectangle with completely round ends, along the longer side. Top-left at the origin.
+    pub fn pill(w: f64, h: f64) -> Polygon {
+        Polygon::rounded_rectangle(w, h, None)
+    }
+
     pub fn union(self, other: Polygon) -> Polygon {
         let mut points = self.points;
         let mut indices = self.indices;
@@ -370,18 +356,6 @@ impl Triangle {
         Triangle { pt1, pt2, pt3 }
     }
 
-    fn is_convex(&self) -> bool {
-        let x1 = self.pt1.x();
-        let y1 = self.pt1.y();
-        let x2 = self.pt2.x();
-        let y2 = self.pt2.y();
-        let x3 = self.pt3.x();
-        let y3 = self.pt3.y();
-
-        let cross_product = (x2 - x1) * (y3 - y1) - (y2 - y1) * (x3 - x1);
-        cross_product >= 0.0
-    }
-
     fn contains_pt(&self, pt: Pt2D) -> bool {
         let x1 = self.pt1.x();
         let y1 = self.pt1.y();
@@ -444,3 +418,49 @@ fn from_geo(p: geo::Polygon<f64>) -> Polygon {
 fn from_multi(multi: geo::MultiPolygon<f64>) -> Vec<Polygon> {
     multi.into_iter().map(from_geo).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::triangulate::tests::{random_star, Rng};
+
+    #[test]
+    fn rounded_rectangle_radius_too_big() {
+        let b = Polygon::rounded_rectangle(40.0, 10.0, Some(20.0)).get_bounds();
+        assert!(b.min_x >= -0.001 && b.max_x <= 40.001);
+        assert!(b.min_y >= -0.001 && b.max_y <= 10.001);
+        assert!(b.max_y - b.min_y > 9.999);
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        let mut rng = Rng(0xA076_1D64_78BD_642F);
+        assert!(Polygon::try_new(&vec![Pt2D::new(0.0, 0.0), Pt2D::new(1.0, 1.0)]).is_err());
+
+        for _ in 0..500 {
+            let n = 3 + (rng.next() * 20.0) as usize;
+
+            // Everything on one line
+            let line: Vec<Pt2D> = (0..n)
+                .map(|i| Pt2D::new(i as f64, 3.0 * i as f64))
+                .collect();
+            assert!(Polygon::try_new(&line).is_err());
+
+            // All the same point
+            assert!(Polygon::try_new(&vec![Pt2D::new(5.0, 5.0); n]).is_err());
+
+            // Never panics, and anything accepted has something to draw
+            let tiny: Vec<Pt2D> = (0..n)
+                .map(|_| Pt2D::new(rng.next() * 0.01, rng.next() * 0.01))
+                .collect();
+            if let Ok(p) = Polygon::try_new(&tiny) {
+                assert!(!p.triangles().is_empty());
+            }
+
+            if n >= 5 {
+                let star = random_star(&mut rng, n);
+                assert!(Polygon::try_new(&star).is_ok());
+            }
+        }
+    }
+}
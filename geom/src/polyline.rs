@@ -1,7 +1,8 @@
 use crate::{
-    Angle, Bounds, Distance, HashablePt2D, InfiniteLine, Line, Polygon, Pt2D, Ring, EPSILON_DIST,
+    Angle, Bounds, Circle, Distance, HashablePt2D, InfiniteLine, Line, Polygon, Pt2D, Ring,
+    EPSILON_DIST,
 };
-use abstutil::Warn;
+use abstutil::{Error, Warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fmt;
@@ -14,79 +15,133 @@ pub enum ArrowCap {
     Lines,
 }
 
+// How the ends and corners of a thick line look
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineStyle {
+    pub cap: LineCap,
+    pub join: LineJoin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineCap {
+    // Stop exactly at the ends
+    Butt,
+    // A half-circle past each end
+    Round,
+    // Extend past each end by half the width
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineJoin {
+    // Extend the edges until they meet. Very sharp corners get cut off, but might leave a notch.
+    Miter,
+    Round,
+    // Cut the corner off
+    Bevel,
+}
+
+impl LineStyle {
+    // What make_polygons does
+    pub const BUTT: LineStyle = LineStyle {
+        cap: LineCap::Butt,
+        join: LineJoin::Miter,
+    };
+    pub const ROUND: LineStyle = LineStyle {
+        cap: LineCap::Round,
+        join: LineJoin::Round,
+    };
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SerializedPolyLine")]
 pub struct PolyLine {
+    #[serde(with = "crate::compact::pts")]
     pts: Vec<Pt2D>,
     // TODO Note that caching length doesn't improve profiling results (by running
     // small_spawn_completes test in release mode). May not be worth doing this.
     length: Distance,
+    // How far along each point is. Lets dist_along find the right line without measuring every
+    // one before it. Always derived from pts, so it isn't serialized.
+    #[serde(skip_serializing)]
+    cumulative: Vec<Distance>,
+}
+
+// What PolyLines used to look like serialized, and still do.
+#[derive(Deserialize)]
+struct SerializedPolyLine {
+    #[serde(with = "crate::compact::pts")]
+    pts: Vec<Pt2D>,
+    length: Distance,
+}
+
+impl From<SerializedPolyLine> for PolyLine {
+    fn from(pl: SerializedPolyLine) -> PolyLine {
+        PolyLine {
+            cumulative: cumulative_lengths(&pl.pts),
+            pts: pl.pts,
+            length: pl.length,
+        }
+    }
 }
 
 impl PolyLine {
     pub fn new(pts: Vec<Pt2D>) -> PolyLine {
-        assert!(pts.len() >= 2);
-        let length = pts.windows(2).fold(Distance::ZERO, |so_far, pair| {
-            so_far + pair[0].dist_to(pair[1])
-        });
+        PolyLine::try_new(pts).unwrap()
+    }
+
+    pub fn try_new(pts: Vec<Pt2D>) -> Result<PolyLine, Error> {
+        if pts.len() < 2 {
+            return Err(Error::new(format!("PL with only {} pts", pts.len())));
+        }
+        let cumulative = cumulative_lengths(&pts);
+        let length = *cumulative.last().unwrap();
 
         // This checks no lines are too small. Could take the other approach and automatically
         // squish down points here and make sure the final result is at least EPSILON_DIST.
         // But probably better for the callers to do this -- they have better understanding of what
         // needs to be squished down, why, and how.
         if pts.windows(2).any(|pair| pair[0] == pair[1]) {
-            panic!(
+            return Err(Error::new(format!(
                 "PL with total length {} and {} pts has ~dupe adjacent pts: {:?}",
                 length,
                 pts.len(),
                 pts
-            );
+            )));
         }
 
-        let result = PolyLine { pts, length };
+        let result = PolyLine {
+            pts,
+            length,
+            cumulative,
+        };
 
         // Can't have duplicates! If the polyline ever crosses back on itself, all sorts of things
         // are broken.
         let (_, dupes) = to_set(result.points());
         if !dupes.is_empty() {
-            panic!(
+            return Err(Error::new(format!(
                 "PolyLine has non-adjacent repeat points: {}\nRepeated points: {:?}",
                 result, dupes
-            );
+            )));
         }
 
-        result
+        Ok(result)
     }
 
     pub fn maybe_new(pts: Vec<Pt2D>) -> Option<PolyLine> {
-        if pts.len() < 2 {
-            return None;
-        }
-        let length = pts.windows(2).fold(Distance::ZERO, |so_far, pair| {
-            so_far + pair[0].dist_to(pair[1])
-        });
-
-        if pts.windows(2).any(|pair| pair[0] == pair[1]) {
-            return None;
-        }
-
-        let result = PolyLine { pts, length };
-
-        let (_, dupes) = to_set(result.points());
-        if !dupes.is_empty() {
-            return None;
-        }
-
-        Some(result)
+        PolyLine::try_new(pts).ok()
     }
 
     // Doesn't check for duplicates. Use at your own risk.
     pub fn unchecked_new(pts: Vec<Pt2D>) -> PolyLine {
         assert!(pts.len() >= 2);
-        let length = pts.windows(2).fold(Distance::ZERO, |so_far, pair| {
-            so_far + pair[0].dist_to(pair[1])
-        });
-
-        PolyLine { pts, length }
+        let cumulative = cumulative_lengths(&pts);
+        PolyLine {
+            length: *cumulative.last().unwrap(),
+            pts,
+            cumulative,
+        }
     }
 
     pub fn to_thick_boundary(
@@ -290,28 +345,14 @@ impl PolyLine {
             return None;
         }
 
-        let mut dist_left = dist_along;
-        let mut length_remeasured = Distance::ZERO;
-        for (idx, l) in self.lines().iter().enumerate() {
-            let length = l.length();
-            length_remeasured += length;
-            let epsilon = if idx == self.pts.len() - 2 {
-                EPSILON_DIST
-            } else {
-                Distance::ZERO
-            };
-            if dist_left <= length + epsilon {
-                return Some((l.dist_along(dist_left), l.angle()));
-            }
-            dist_left -= length;
-        }
-        panic!(
-            "PolyLine dist_along of {} broke on length {} (recalculated length {}): {}",
-            dist_along,
-            self.length(),
-            length_remeasured,
-            self
-        );
+        // The first line ending at or after dist_along. Past the end of the last line (but within
+        // the length, so just because of rounding), use the last line.
+        let idx = match self.cumulative[1..].binary_search(&dist_along) {
+            Ok(idx) => idx,
+            Err(idx) => idx.min(self.pts.len() - 2),
+        };
+        let l = Line::new(self.pts[idx], self.pts[idx + 1]);
+        Some((l.dist_along(dist_along - self.cumulative[idx]), l.angle()))
     }
 
     pub fn middle(&self) -> Pt2D {
@@ -446,6 +487,41 @@ impl PolyLine {
         Polygon::precomputed(points, indices)
     }
 
+    pub fn make_polygons_with_style(&self, width: Distance, style: LineStyle) -> Polygon {
+        let pl = if style.cap == LineCap::Square {
+            let mut pts = self.pts.clone();
+            let n = pts.len();
+            pts[0] = pts[0].project_away(width / 2.0, pts[1].angle_to(pts[0]));
+            pts[n - 1] = pts[n - 1].project_away(width / 2.0, pts[n - 2].angle_to(pts[n - 1]));
+            PolyLine::unchecked_new(pts)
+        } else {
+            self.clone()
+        };
+
+        let mut polygons = Vec::new();
+        if style.join == LineJoin::Miter {
+            polygons.push(pl.make_polygons(width));
+        } else {
+            // Draw each segment separately, and fill in the gaps at the corners
+            let lines = pl.lines();
+            for l in &lines {
+                polygons.push(l.make_polygons(width));
+            }
+            for pair in lines.windows(2) {
+                if style.join == LineJoin::Round {
+                    polygons.push(Circle::new(pair[0].pt2(), width / 2.0).to_polygon());
+                } else {
+                    polygons.extend(bevel(&pair[0], &pair[1], width));
+                }
+            }
+        }
+        if style.cap == LineCap::Round {
+            polygons.push(Circle::new(pl.first_pt(), width / 2.0).to_polygon());
+            polygons.push(Circle::new(pl.last_pt(), width / 2.0).to_polygon());
+        }
+        Polygon::union_all(polygons)
+    }
+
     pub fn exact_dashed_polygons(
         &self,
         width: Distance,
@@ -513,9 +589,13 @@ impl PolyLine {
                     corner2,
                 ])))
             }
-            ArrowCap::Lines => Warn::ok(self.make_polygons(thickness).union(
-                PolyLine::new(vec![corner1, self.last_pt(), corner2]).make_polygons(thickness),
-            )),
+            // The head is a sharp corner, so round it off
+            ArrowCap::Lines => Warn::ok(
+                self.make_polygons(thickness).union(
+                    PolyLine::new(vec![corner1, self.last_pt(), corner2])
+                        .make_polygons_with_style(thickness, LineStyle::ROUND),
+                ),
+            ),
         }
     }
 
@@ -714,6 +794,23 @@ impl fmt::Display for PolyLine {
     }
 }
 
+// Fills in the outside of the corner where l1 ends and l2 begins. Both sides are filled, since the
+// inside is covered by the segments anyway.
+pub(crate) fn bevel(l1: &Line, l2: &Line, width: Distance) -> Vec<Polygon> {
+    let mut result = Vec::new();
+    for side in vec![width / 2.0, -width / 2.0] {
+        let pt1 = l1.shift_either_direction(side).pt2();
+        let pt2 = l2.shift_either_direction(side).pt1();
+        if pt1 != pt2 {
+            result.push(Polygon::precomputed(
+                vec![l1.pt2(), pt1, pt2],
+                vec![0, 1, 2],
+            ));
+        }
+    }
+    result
+}
+
 fn fix_angles(orig: &PolyLine, result: PolyLine) -> PolyLine {
     let mut pts = result.pts.clone();
 
@@ -769,3 +866,101 @@ fn to_set(pts: &[Pt2D]) -> (HashSet<HashablePt2D>, HashSet<HashablePt2D>) {
     }
     (deduped, dupes)
 }
+
+fn cumulative_lengths(pts: &[Pt2D]) -> Vec<Distance> {
+    let mut so_far = Distance::ZERO;
+    let mut result = vec![so_far];
+    for pair in pts.windows(2) {
+        so_far += pair[0].dist_to(pair[1]);
+        result.push(so_far);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::triangulate::tests::{random_star, Rng};
+
+    // Points in a small box, so some land within EPSILON_DIST of each other once rounded.
+    fn random_pts(rng: &mut Rng, n: usize) -> Vec<Pt2D> {
+        (0..n)
+            .map(|_| Pt2D::new(rng.next() * 0.05, rng.next() * 0.05))
+            .collect()
+    }
+
+    #[test]
+    fn degenerate_lines() {
+        let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+        for _ in 0..1000 {
+            let pt1 = Pt2D::new(rng.next() * 100.0, rng.next() * 100.0);
+            // Sometimes far away, sometimes barely moved
+            let scale = if rng.next() < 0.5 { 100.0 } else { 0.02 };
+            let pt2 = Pt2D::new(pt1.x() + rng.next() * scale, pt1.y() + rng.next() * scale);
+
+            match Line::try_new(pt1, pt2) {
+                Ok(l) => assert!(l.length() >= EPSILON_DIST),
+                Err(_) => assert!(pt1.dist_to(pt2) < EPSILON_DIST),
+            }
+            assert_eq!(
+                Line::try_new(pt1, pt2).is_ok(),
+                Line::maybe_new(pt1, pt2).is_some()
+            );
+            assert!(Line::try_new(pt1, pt1).is_err());
+        }
+    }
+
+    #[test]
+    fn degenerate_polylines() {
+        let mut rng = Rng(0x8CB9_2BA7_2F3D_8DD7);
+        assert!(PolyLine::try_new(Vec::new()).is_err());
+        assert!(PolyLine::try_new(vec![Pt2D::new(1.0, 1.0)]).is_err());
+
+        for _ in 0..1000 {
+            let n = 2 + (rng.next() * 10.0) as usize;
+            let mut pts = random_pts(&mut rng, n);
+            if rng.next() < 0.3 {
+                let idx = (rng.next() * (n as f64)) as usize;
+                pts.insert(idx, pts[idx]);
+            }
+
+            match PolyLine::try_new(pts.clone()) {
+                Ok(pl) => {
+                    assert!(!pts.windows(2).any(|pair| pair[0] == pair[1]));
+                    let expected: f64 = pts
+                        .windows(2)
+                        .map(|pair| pair[0].dist_to(pair[1]).inner_meters())
+                        .sum();
+                    assert!((pl.length().inner_meters() - expected).abs() < 1e-6);
+                    assert_eq!(pl.points().len(), pts.len());
+                }
+                Err(_) => assert!(PolyLine::maybe_new(pts).is_none()),
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_rings() {
+        let mut rng = Rng(0x4F1B_BCDC_BFA5_3E0B);
+        assert!(Ring::try_new(vec![Pt2D::new(1.0, 1.0), Pt2D::new(1.0, 1.0)]).is_err());
+
+        for _ in 0..200 {
+            let n = 5 + (rng.next() * 30.0) as usize;
+            let mut pts = random_star(&mut rng, n);
+            // Not closed yet
+            assert!(Ring::try_new(pts.clone()).is_err());
+
+            pts.push(pts[0]);
+            assert!(Ring::try_new(pts.clone()).is_ok());
+
+            // Crossing back over an earlier point
+            let mut repeat = pts.clone();
+            repeat.insert(n / 2, pts[1]);
+            assert!(Ring::try_new(repeat).is_err());
+
+            let mut dupe = pts.clone();
+            dupe.insert(n / 2, pts[n / 2]);
+            assert!(Ring::try_new(dupe).is_err());
+        }
+    }
+}
@@ -191,6 +191,17 @@ impl Pt2D {
             y_nan: NotNan::new(self.y()).unwrap(),
         }
     }
+
+    // Snaps to a grid with cells of the given size.
+    pub fn quantize(self, resolution: Distance) -> QuantizedPt2D {
+        let res = resolution.inner_meters();
+        assert!(res > 0.0);
+        QuantizedPt2D {
+            x: (self.x() / res).round() as i64,
+            y: (self.y() / res).round() as i64,
+            resolution: res.to_bits(),
+        }
+    }
 }
 
 impl fmt::Display for Pt2D {
@@ -212,3 +223,46 @@ impl HashablePt2D {
         Pt2D::new(self.x_nan.into_inner(), self.y_nan.into_inner())
     }
 }
+
+// A point snapped to a grid, for use as a key when welding together points that should be the
+// same, like road endpoints. Unlike HashablePt2D, points a tiny distance apart usually (but not
+// always -- they might straddle a cell boundary) wind up with the same key. Keys made with
+// different resolutions are never equal.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct QuantizedPt2D {
+    x: i64,
+    y: i64,
+    // The exact f64, since floats can't be hashed directly. Rounding it (to millimeters, say)
+    // would make keys from slightly different resolutions collide.
+    resolution: u64,
+}
+
+impl QuantizedPt2D {
+    // The center of the grid cell, not the original point.
+    pub fn to_pt2d(self) -> Pt2D {
+        let res = f64::from_bits(self.resolution);
+        Pt2D::new((self.x as f64) * res, (self.y as f64) * res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantize_keeps_resolution() {
+        let pt = Pt2D::new(0.0, 0.0);
+        assert_ne!(
+            pt.quantize(Distance::meters(0.0001)),
+            pt.quantize(Distance::meters(0.0004))
+        );
+        assert_eq!(
+            pt.quantize(Distance::meters(0.0004)),
+            pt.quantize(Distance::meters(0.0004))
+        );
+
+        let pt = Pt2D::new(12.0, 3.0);
+        let res = Distance::meters(0.0004);
+        assert!(pt.quantize(res).to_pt2d().raw_dist_to(pt) < res.inner_meters());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{Distance, Line, PolyLine, Polygon, Pt2D};
+use crate::polyline::bevel;
+use crate::{find_intersections, Distance, Line, PolyLine, Polygon, Pt2D};
+use abstutil::Error;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fmt;
@@ -7,20 +9,32 @@ use std::fmt;
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Ring {
     // first equals last
+    #[serde(with = "crate::compact::pts")]
     pts: Vec<Pt2D>,
 }
 
 impl Ring {
     pub fn new(pts: Vec<Pt2D>) -> Ring {
-        assert!(pts.len() >= 3);
-        assert_eq!(pts[0], *pts.last().unwrap());
+        Ring::try_new(pts).unwrap()
+    }
+
+    pub fn try_new(pts: Vec<Pt2D>) -> Result<Ring, Error> {
+        if pts.len() < 3 {
+            return Err(Error::new(format!("Ring with only {} pts", pts.len())));
+        }
+        if pts[0] != *pts.last().unwrap() {
+            return Err(Error::new(format!("Ring doesn't close: {:?}", pts)));
+        }
 
         // This checks no lines are too small. Could take the other approach and automatically
         // squish down points here and make sure the final result is at least EPSILON_DIST.
         // But probably better for the callers to do this -- they have better understanding of what
         // needs to be squished down, why, and how.
         if pts.windows(2).any(|pair| pair[0] == pair[1]) {
-            panic!("Ring has ~dupe adjacent pts: {:?}", pts);
+            return Err(Error::new(format!(
+                "Ring has ~dupe adjacent pts: {:?}",
+                pts
+            )));
         }
 
         let result = Ring { pts };
@@ -30,38 +44,27 @@ impl Ring {
             seen_pts.insert(pt.to_hashable());
         }
         if seen_pts.len() != result.pts.len() - 1 {
-            panic!("Ring has repeat points: {}", result);
+            return Err(Error::new(format!("Ring has repeat points: {}", result)));
         }
 
-        result
+        Ok(result)
     }
 
     pub fn maybe_new(pts: Vec<Pt2D>) -> Option<Ring> {
-        assert!(pts.len() >= 3);
-        if pts[0] != *pts.last().unwrap() {
-            return None;
-        }
-
-        if pts.windows(2).any(|pair| pair[0] == pair[1]) {
-            return None;
-        }
-
-        let result = Ring { pts };
-
-        let mut seen_pts = HashSet::new();
-        for pt in result.pts.iter().skip(1) {
-            seen_pts.insert(pt.to_hashable());
-        }
-        if seen_pts.len() != result.pts.len() - 1 {
-            return None;
-        }
-
-        Some(result)
+        Ring::try_new(pts).ok()
     }
 
     pub fn make_polygons(&self, thickness: Distance) -> Polygon {
-        // TODO Has a weird corner. Use the polygon offset thing instead?
-        PolyLine::unchecked_new(self.pts.clone()).make_polygons(thickness)
+        // The polyline has ends where the ring closes, so fill in that corner.
+        // TODO Use the polygon offset thing instead?
+        let n = self.pts.len();
+        let mut polygons = vec![PolyLine::unchecked_new(self.pts.clone()).make_polygons(thickness)];
+        polygons.extend(bevel(
+            &Line::new(self.pts[n - 2], self.pts[n - 1]),
+            &Line::new(self.pts[0], self.pts[1]),
+            thickness,
+        ));
+        Polygon::union_all(polygons)
     }
 
     pub fn points(&self) -> &Vec<Pt2D> {
@@ -73,16 +76,25 @@ impl Ring {
 
     // The order of results isn't meaningful. Dedupes.
     pub fn all_intersections(&self, other: &PolyLine) -> Vec<Pt2D> {
+        // Rings are often huge (like a map boundary), so don't check every pair of lines.
+        let mut lines: Vec<Line> = self
+            .pts
+            .windows(2)
+            .map(|pair| Line::new(pair[0], pair[1]))
+            .collect();
+        let num_ours = lines.len();
+        lines.extend(other.lines());
+
         let mut hits = Vec::new();
         let mut seen = HashSet::new();
-        for l1 in other.lines() {
-            for l2 in self.pts.windows(2).map(|pair| Line::new(pair[0], pair[1])) {
-                if let Some(pt) = l1.intersection(&l2) {
-                    if !seen.contains(&pt.to_hashable()) {
-                        hits.push(pt);
-                        seen.insert(pt.to_hashable());
-                    }
-                }
+        for (idx1, idx2, pt) in find_intersections(&lines) {
+            // Only hits between the ring and the polyline
+            if (idx1 < num_ours) == (idx2 < num_ours) {
+                continue;
+            }
+            if !seen.contains(&pt.to_hashable()) {
+                hits.push(pt);
+                seen.insert(pt.to_hashable());
             }
         }
         hits
@@ -1,4 +1,4 @@
-use crate::{trim_f64, Distance, Duration, EPSILON_DIST};
+use crate::{trim_f64, Distance, Duration, UnitSystem, EPSILON_DIST};
 use serde::{Deserialize, Serialize};
 use std::{fmt, ops};
 
@@ -31,6 +31,10 @@ impl Speed {
         Speed::meters_per_second(0.44704 * value)
     }
 
+    pub fn km_per_hour(value: f64) -> Speed {
+        Speed::meters_per_second(value / 3.6)
+    }
+
     pub fn from_dist_time(d: Distance, t: Duration) -> Speed {
         Speed::meters_per_second(d.inner_meters() / t.inner_seconds())
     }
@@ -107,6 +111,9 @@ impl ops::Mul<Duration> for Speed {
 
 impl fmt::Display for Speed {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} mph", (self.0 * 2.23694).round())
+        match UnitSystem::current() {
+            UnitSystem::Metric => write!(f, "{} km/h", (self.0 * 3.6).round()),
+            UnitSystem::Imperial => write!(f, "{} mph", (self.0 * 2.23694).round()),
+        }
     }
 }
@@ -0,0 +1,107 @@
+use crate::{Line, Pt2D};
+
+// Finds every pair of segments that intersect, returning (index of the first, index of the
+// second, the hit). The first index is always smaller. Segments that only touch at a shared
+// endpoint count as intersecting, matching Line::intersection.
+//
+// This sweeps a vertical line from left to right, only keeping segments whose x-range overlaps
+// the sweep line active, and only testing active pairs whose y-ranges also overlap. That isn't a
+// full Bentley-Ottmann (which needs an ordered status structure and is notoriously fragile with
+// floating point), but for map geometry, where most segments are short and spread out, it avoids
+// nearly all of the pairwise checks.
+pub fn find_intersections(lines: &[Line]) -> Vec<(usize, usize, Pt2D)> {
+    struct Extent {
+        idx: usize,
+        min_x: f64,
+        max_x: f64,
+        min_y: f64,
+        max_y: f64,
+    }
+
+    let mut extents: Vec<Extent> = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| Extent {
+            idx,
+            min_x: l.pt1().x().min(l.pt2().x()),
+            max_x: l.pt1().x().max(l.pt2().x()),
+            min_y: l.pt1().y().min(l.pt2().y()),
+            max_y: l.pt1().y().max(l.pt2().y()),
+        })
+        .collect();
+    extents.sort_by(|a, b| a.min_x.partial_cmp(&b.min_x).unwrap());
+
+    let mut hits = Vec::new();
+    let mut active: Vec<&Extent> = Vec::new();
+    for e in &extents {
+        // Anything ending before this segment starts can't hit it or anything after it.
+        active.retain(|other| other.max_x >= e.min_x);
+        for other in &active {
+            if other.max_y < e.min_y || other.min_y > e.max_y {
+                continue;
+            }
+            if let Some(pt) = lines[e.idx].intersection(&lines[other.idx]) {
+                hits.push((e.idx.min(other.idx), e.idx.max(other.idx), pt));
+            }
+        }
+        active.push(e);
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::triangulate::tests::Rng;
+    use std::collections::BTreeSet;
+
+    // Short segments scattered over a box, some of them starting where the last one ended and some
+    // perfectly horizontal or vertical, to hit the ties in the sweep.
+    fn random_lines(rng: &mut Rng, n: usize) -> Vec<Line> {
+        let mut lines: Vec<Line> = Vec::new();
+        while lines.len() < n {
+            let pt1 = match lines.last() {
+                Some(l) if rng.next() < 0.3 => l.pt2(),
+                _ => Pt2D::new(rng.next() * 100.0, rng.next() * 100.0),
+            };
+            let (dx, dy) = match (rng.next() * 4.0) as usize {
+                0 => (rng.next() * 20.0 - 10.0, 0.0),
+                1 => (0.0, rng.next() * 20.0 - 10.0),
+                _ => (rng.next() * 20.0 - 10.0, rng.next() * 20.0 - 10.0),
+            };
+            if let Some(l) = Line::maybe_new(pt1, pt1.offset(dx, dy)) {
+                lines.push(l);
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn matches_pairwise() {
+        let mut rng = Rng(0x5851_F42D_4C95_7F2D);
+        for n in vec![0, 1, 2, 10, 50, 200] {
+            for _ in 0..20 {
+                let lines = random_lines(&mut rng, n);
+
+                let mut expected = BTreeSet::new();
+                for i in 0..lines.len() {
+                    for j in (i + 1)..lines.len() {
+                        if lines[i].intersection(&lines[j]).is_some() {
+                            expected.insert((i, j));
+                        }
+                    }
+                }
+
+                let hits = find_intersections(&lines);
+                let actual: BTreeSet<(usize, usize)> =
+                    hits.iter().map(|(i, j, _)| (*i, *j)).collect();
+                assert_eq!(hits.len(), actual.len(), "a pair was reported twice");
+                assert_eq!(expected, actual);
+                for (i, j, pt) in hits {
+                    assert!(lines[i].contains_pt(pt));
+                    assert!(lines[j].contains_pt(pt));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::Pt2D;
+
+// Twice the area of a triangle, below which it's considered degenerate. Coordinates are usually
+// meters or pixels, so this is far below anything visible.
+const DEGENERATE_AREA: f64 = 1e-6;
+
+// Ear-clipping triangulation that's tolerant of the messy input OSM produces: repeated points, the
+// last point duplicating the first, runs of collinear points, zero-area spikes, slight
+// self-intersections, and holes. Indices refer to the outer points followed by the points of each
+// hole, in order. The winding order of the input doesn't matter.
+//
+// Never panics and always terminates. If the input is hopelessly broken, some of its area may be
+// missing from the result, but no sliver triangles are produced.
+pub fn triangulate(outer: &[Pt2D], holes: &[Vec<Pt2D>]) -> Vec<usize> {
+    let mut all_pts: Vec<Pt2D> = outer.to_vec();
+    let mut ring = clean_ring(outer, 0, true);
+    let mut hole_rings = Vec::new();
+    for hole in holes {
+        let hole_ring = clean_ring(hole, all_pts.len(), false);
+        all_pts.extend(hole.iter().cloned());
+        if hole_ring.len() >= 3 {
+            hole_rings.push(hole_ring);
+        }
+    }
+    if ring.len() < 3 {
+        return Vec::new();
+    }
+
+    // Bridge holes in from right to left, so earlier bridges don't block later ones.
+    hole_rings.sort_by(|a, b| max_x(&all_pts, b).partial_cmp(&max_x(&all_pts, a)).unwrap());
+    for hole in hole_rings {
+        if let Some(merged) = bridge_hole(&all_pts, &ring, &hole) {
+            ring = merged;
+        }
+    }
+
+    clip_ears(&all_pts, ring)
+}
+
+// Twice the signed area of the triangle; positive when counter-clockwise (with y pointing up).
+fn cross(a: Pt2D, b: Pt2D, c: Pt2D) -> f64 {
+    (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+}
+
+fn signed_area(pts: &[Pt2D]) -> f64 {
+    let mut sum = 0.0;
+    for i in 0..pts.len() {
+        let a = pts[i];
+        let b = pts[(i + 1) % pts.len()];
+        sum += a.x() * b.y() - b.x() * a.y();
+    }
+    sum / 2.0
+}
+
+fn max_x(pts: &[Pt2D], ring: &[usize]) -> f64 {
+    ring.iter()
+        .map(|i| pts[*i].x())
+        .fold(f64::NEG_INFINITY, f64::max)
+}
+
+// Drops repeated points and collinear points, then winds the ring counter-clockwise for the outer
+// boundary, or clockwise for holes.
+fn clean_ring(pts: &[Pt2D], offset: usize, ccw: bool) -> Vec<usize> {
+    let mut ring: Vec<usize> = Vec::new();
+    for (idx, pt) in pts.iter().enumerate() {
+        if let Some(last) = ring.last() {
+            if pts[*last - offset] == *pt {
+                continue;
+            }
+        }
+        ring.push(offset + idx);
+    }
+    while ring.len() > 1 && pts[ring[0] - offset] == pts[*ring.last().unwrap() - offset] {
+        ring.pop();
+    }
+
+    // Removing one collinear point can make its neighbor collinear (think of a zero-area spike),
+    // so repeat until nothing changes.
+    loop {
+        let n = ring.len();
+        if n < 3 {
+            return Vec::new();
+        }
+        let before = n;
+        let mut keep = Vec::with_capacity(n);
+        for i in 0..n {
+            let prev = pts[ring[(i + n - 1) % n] - offset];
+            let cur = pts[ring[i] - offset];
+            let next = pts[ring[(i + 1) % n] - offset];
+            if cross(prev, cur, next).abs() > DEGENERATE_AREA {
+                keep.push(ring[i]);
+            }
+        }
+        ring = keep;
+        if ring.len() == before {
+            break;
+        }
+    }
+    if ring.len() < 3 {
+        return Vec::new();
+    }
+
+    let area = signed_area(&ring.iter().map(|i| pts[*i - offset]).collect::<Vec<_>>());
+    if (area > 0.0) != ccw {
+        ring.reverse();
+    }
+    ring
+}
+
+// Connect the hole to the outer ring with a pair of coincident edges, turning both into one ring.
+// Returns None if no connection can be found, in which case the hole is just dropped.
+fn bridge_hole(pts: &[Pt2D], ring: &[usize], hole: &[usize]) -> Option<Vec<usize>> {
+    // The rightmost point of the hole can always see something on the outer ring to its right.
+    let (hole_start, _) = hole
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| pts[**a].x().partial_cmp(&pts[**b].x()).unwrap())?;
+    let m = pts[hole[hole_start]];
+
+    // Try the closest candidates first
+    let mut candidates: Vec<usize> = (0..ring.len())
+        .filter(|i| pts[ring[*i]].x() >= m.x())
+        .collect();
+    candidates.sort_by(|a, b| {
+        pts[ring[*a]]
+            .raw_dist_to(m)
+            .partial_cmp(&pts[ring[*b]].raw_dist_to(m))
+            .unwrap()
+    });
+    let visible = candidates.into_iter().find(|i| {
+        let v = pts[ring[*i]];
+        !crosses_any(pts, ring, m, v) && !crosses_any(pts, hole, m, v)
+    })?;
+
+    let mut merged = Vec::with_capacity(ring.len() + hole.len() + 2);
+    merged.extend_from_slice(&ring[..=visible]);
+    for i in 0..=hole.len() {
+        merged.push(hole[(hole_start + i) % hole.len()]);
+    }
+    merged.extend_from_slice(&ring[visible..]);
+    Some(merged)
+}
+
+// Does the segment from a to b properly cross any edge of the ring? Touching at endpoints doesn't
+// count.
+fn crosses_any(pts: &[Pt2D], ring: &[usize], a: Pt2D, b: Pt2D) -> bool {
+    (0..ring.len()).any(|i| {
+        let c = pts[ring[i]];
+        let d = pts[ring[(i + 1) % ring.len()]];
+        if c == a || c == b || d == a || d == b {
+            return false;
+        }
+        let d1 = cross(a, b, c);
+        let d2 = cross(a, b, d);
+        let d3 = cross(c, d, a);
+        let d4 = cross(c, d, b);
+        ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+            && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    })
+}
+
+// Inclusive of the edges, so collinear points touching an ear disqualify it.
+fn in_triangle(a: Pt2D, b: Pt2D, c: Pt2D, pt: Pt2D) -> bool {
+    cross(a, b, pt) >= -DEGENERATE_AREA
+        && cross(b, c, pt) >= -DEGENERATE_AREA
+        && cross(c, a, pt) >= -DEGENERATE_AREA
+}
+
+fn clip_ears(pts: &[Pt2D], mut ring: Vec<usize>) -> Vec<usize> {
+    let mut indices = Vec::new();
+    let mut i = 0;
+    let mut misses = 0;
+    while ring.len() > 3 {
+        let n = ring.len();
+        i %= n;
+        let (prev, cur, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let (a, b, c) = (pts[prev], pts[cur], pts[next]);
+        let area = cross(a, b, c);
+
+        // Collinear points and spikes contribute nothing; just drop them.
+        if area.abs() <= DEGENERATE_AREA {
+            ring.remove(i);
+            misses = 0;
+            continue;
+        }
+
+        if area > 0.0 {
+            let is_ear = ring.iter().all(|v| {
+                let pt = pts[*v];
+                // Bridges to holes duplicate points, so compare positions, not just indices.
+                pt == a || pt == b || pt == c || !in_triangle(a, b, c, pt)
+            });
+            if is_ear {
+                indices.extend_from_slice(&[prev, cur, next]);
+                ring.remove(i);
+                misses = 0;
+                continue;
+            }
+        }
+
+        i += 1;
+        misses += 1;
+        if misses >= n {
+            // Stuck, probably because the ring intersects itself. Cut off the most convex corner
+            // anyway, so we make progress.
+            let best = (0..n)
+                .max_by(|x, y| {
+                    corner_area(pts, &ring, *x)
+                        .partial_cmp(&corner_area(pts, &ring, *y))
+                        .unwrap()
+                })
+                .unwrap();
+            if corner_area(pts, &ring, best) > DEGENERATE_AREA {
+                indices.extend_from_slice(&[
+                    ring[(best + n - 1) % n],
+                    ring[best],
+                    ring[(best + 1) % n],
+                ]);
+            }
+            ring.remove(best);
+            misses = 0;
+        }
+    }
+    if ring.len() == 3 && cross(pts[ring[0]], pts[ring[1]], pts[ring[2]]) > DEGENERATE_AREA {
+        indices.extend_from_slice(&ring);
+    }
+    indices
+}
+
+fn corner_area(pts: &[Pt2D], ring: &[usize], i: usize) -> f64 {
+    let n = ring.len();
+    cross(
+        pts[ring[(i + n - 1) % n]],
+        pts[ring[i]],
+        pts[ring[(i + 1) % n]],
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // A tiny xorshift generator, so the fuzzing is reproducible without any dependencies. Other
+    // modules' tests use this too.
+    pub(crate) struct Rng(pub u64);
+
+    impl Rng {
+        pub fn next(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % 1_000_000) as f64 / 1_000_000.0
+        }
+    }
+
+    fn triangulated_area(pts: &[Pt2D], indices: &[usize]) -> f64 {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let area = cross(pts[t[0]], pts[t[1]], pts[t[2]]);
+                assert!(area.abs() > DEGENERATE_AREA, "sliver triangle");
+                area.abs() / 2.0
+            })
+            .sum()
+    }
+
+    fn true_area(pts: &[Pt2D]) -> f64 {
+        signed_area(pts).abs()
+    }
+
+    // Points around a center at increasing angles, never more than half a turn apart, don't
+    // self-intersect, but can be very concave.
+    pub(crate) fn random_star(rng: &mut Rng, n: usize) -> Vec<Pt2D> {
+        assert!(n >= 5);
+        let slice = 2.0 * std::f64::consts::PI / (n as f64);
+        (0..n)
+            .map(|i| {
+                let theta = ((i as f64) + 0.8 * rng.next()) * slice;
+                let r = 10.0 + 90.0 * rng.next();
+                Pt2D::new(500.0 + r * theta.cos(), 500.0 + r * theta.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fuzz_star_polygons() {
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+        for _ in 0..500 {
+            let n = 5 + (rng.next() * 60.0) as usize;
+            let pts = random_star(&mut rng, n);
+            let expected = true_area(&pts);
+            let got = triangulated_area(&pts, &triangulate(&pts, &[]));
+            assert!(
+                (got - expected).abs() < 0.01 * expected,
+                "{} vs {}",
+                got,
+                expected
+            );
+
+            // The same shape, clockwise, with a closing point, repeated points, and extra
+            // collinear points shouldn't change anything.
+            let mut messy = Vec::new();
+            for (idx, pt) in pts.iter().enumerate().rev() {
+                messy.push(*pt);
+                if idx % 3 == 0 {
+                    messy.push(*pt);
+                }
+                if idx % 4 == 0 {
+                    let next = pts[(idx + pts.len() - 1) % pts.len()];
+                    messy.push(Pt2D::new(
+                        (pt.x() + next.x()) / 2.0,
+                        (pt.y() + next.y()) / 2.0,
+                    ));
+                }
+            }
+            messy.push(messy[0]);
+            let got = triangulated_area(&messy, &triangulate(&messy, &[]));
+            assert!(
+                (got - expected).abs() < 0.01 * expected,
+                "{} vs {}",
+                got,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn fuzz_degenerate_input() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..500 {
+            let n = 3 + (rng.next() * 20.0) as usize;
+            // Random points in a tiny box self-intersect all over the place, and some will
+            // coincide once rounded.
+            let pts: Vec<Pt2D> = (0..n)
+                .map(|_| Pt2D::new(rng.next() * 0.01, rng.next() * 0.01))
+                .collect();
+            triangulate(&pts, &[]);
+
+            // Everything on one line
+            let line: Vec<Pt2D> = (0..n)
+                .map(|i| Pt2D::new(i as f64, 2.0 * i as f64))
+                .collect();
+            assert!(triangulate(&line, &[]).is_empty());
+        }
+    }
+
+    #[test]
+    fn holes() {
+        let square = |x: f64, y: f64, size: f64| {
+            vec![
+                Pt2D::new(x, y),
+                Pt2D::new(x + size, y),
+                Pt2D::new(x + size, y + size),
+                Pt2D::new(x, y + size),
+            ]
+        };
+        let outer = square(0.0, 0.0, 100.0);
+        let holes = vec![square(10.0, 10.0, 20.0), square(60.0, 50.0, 30.0)];
+        let mut all_pts = outer.clone();
+        for h in &holes {
+            all_pts.extend(h.clone());
+        }
+        let got = triangulated_area(&all_pts, &triangulate(&outer, &holes));
+        let expected = 100.0 * 100.0 - 20.0 * 20.0 - 30.0 * 30.0;
+        assert!((got - expected).abs() < 0.01, "{} vs {}", got, expected);
+
+        // A hole that's nothing but a line is ignored.
+        let got = triangulated_area(
+            &outer,
+            &triangulate(
+                &outer,
+                &[vec![
+                    Pt2D::new(5.0, 5.0),
+                    Pt2D::new(6.0, 6.0),
+                    Pt2D::new(7.0, 7.0),
+                ]],
+            ),
+        );
+        assert!((got - 100.0 * 100.0).abs() < 0.01);
+    }
+}
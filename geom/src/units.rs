@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// How distances, speeds, and areas are described to people. Everything is stored in metric no
+// matter what; this only changes formatting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+// One setting for the whole process, so that Display and friends don't need it plumbed in. Only
+// the game's options change it; tools and headless runs always print metric.
+static IMPERIAL: AtomicBool = AtomicBool::new(false);
+
+impl UnitSystem {
+    pub fn current() -> UnitSystem {
+        if IMPERIAL.load(Ordering::Relaxed) {
+            UnitSystem::Imperial
+        } else {
+            UnitSystem::Metric
+        }
+    }
+
+    pub fn set(self) {
+        IMPERIAL.store(self == UnitSystem::Imperial, Ordering::Relaxed);
+    }
+}
+
+// Input in square meters. There's no Area type yet; Polygon::area just returns an f64.
+pub fn describe_area(sq_meters: f64) -> String {
+    match UnitSystem::current() {
+        UnitSystem::Metric => {
+            let hectares = sq_meters / 10_000.0;
+            if hectares >= 0.1 {
+                format!("{} ha", (hectares * 10.0).round() / 10.0)
+            } else {
+                format!("{} m²", sq_meters.round())
+            }
+        }
+        UnitSystem::Imperial => {
+            let acres = sq_meters / 4046.86;
+            if acres >= 0.1 {
+                format!("{} acres", (acres * 10.0).round() / 10.0)
+            } else {
+                format!("{} sq ft", (sq_meters * 10.7639).round())
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::utils::{download, osmconvert};
+use geom::Distance;
 
 fn input() {
     download(
@@ -30,6 +31,7 @@ pub fn osm_to_raw(name: &str) {
             elevation: None,
             clip: Some(format!("../data/input/austin/polygons/{}.poly", name)),
             drive_on_right: true,
+            snap_radius: Some(Distance::meters(0.5)),
         },
         &mut abstutil::Timer::throwaway(),
     );
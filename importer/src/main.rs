@@ -14,6 +14,7 @@ struct Job {
     scenario_everyone: bool,
 
     skip_ch: bool,
+    repair_lanes: bool,
 
     only_map: Option<String>,
 
@@ -36,6 +37,9 @@ fn main() {
         // Skip the most expensive step of --map, building contraction hierarchies. The resulting
         // map won't be usable for simulation; as soon as you try to pathfind, it'll crash.
         skip_ch: args.enabled("--skip_ch"),
+        // Add turns to --map wherever a vehicle lane would otherwise be a dead end or unreachable.
+        // These turns don't exist in OSM.
+        repair_lanes: args.enabled("--repair_lanes"),
 
         // Only process one map. If not specified, process all maps defined by clipping polygons in
         // data/input/$city/polygons/.
@@ -106,7 +110,14 @@ fn main() {
         }
 
         let mut maybe_map = if job.raw_to_map {
-            Some(utils::raw_to_map(&name, !job.skip_ch, &mut timer))
+            Some(utils::raw_to_map(
+                &name,
+                &map_model::RawToMapOptions {
+                    build_ch: !job.skip_ch,
+                    repair_lanes: job.repair_lanes,
+                },
+                &mut timer,
+            ))
         } else if job.scenario || job.scenario_everyone {
             Some(map_model::Map::new(abstutil::path_map(&name), &mut timer))
         } else {
@@ -165,10 +176,12 @@ fn oneshot(osm_path: String, clip: Option<String>) {
             elevation: None,
             clip,
             drive_on_right: true,
+            snap_radius: Some(geom::Distance::meters(0.5)),
         },
         &mut timer,
     );
-    let map = map_model::Map::create_from_raw(raw, true, &mut timer);
+    let map =
+        map_model::Map::create_from_raw(raw, &map_model::RawToMapOptions::default(), &mut timer);
     timer.start("save map");
     map.save();
     timer.stop("save map");
@@ -1,4 +1,5 @@
 use crate::utils::{download, osmconvert};
+use geom::Distance;
 use map_model::Map;
 use sim::Scenario;
 
@@ -69,6 +70,7 @@ pub fn osm_to_raw(name: &str) {
             elevation: Some("../data/input/seattle/N47W122.hgt".to_string()),
             clip: Some(format!("../data/input/seattle/polygons/{}.poly", name)),
             drive_on_right: true,
+            snap_radius: Some(Distance::meters(0.5)),
         },
         &mut abstutil::Timer::throwaway(),
     );
@@ -96,7 +98,11 @@ pub fn ensure_popdat_exists(
     let huge_map = if abstutil::file_exists(abstutil::path_map("huge_seattle")) {
         map_model::Map::new(abstutil::path_map("huge_seattle"), timer)
     } else {
-        crate::utils::raw_to_map("huge_seattle", true, timer)
+        crate::utils::raw_to_map(
+            "huge_seattle",
+            &map_model::RawToMapOptions::default(),
+            timer,
+        )
     };
 
     (crate::soundcast::import_data(&huge_map), huge_map)
@@ -296,6 +296,11 @@ pub fn make_weekday_scenario(
         map_name: map.get_name().to_string(),
         people,
         only_seed_buses: None,
+        // TODO Soundcast has household income and age; plumb those through
+        demographics: None,
+        driver_behavior: None,
+        pricing: None,
+        car_occupancy: None,
     }
     .remove_weird_schedules(map)
 }
@@ -366,6 +371,11 @@ pub fn make_weekday_scenario_with_everyone(
         map_name: map.get_name().to_string(),
         people,
         only_seed_buses: None,
+        // TODO Soundcast has household income and age; plumb those through
+        demographics: None,
+        driver_behavior: None,
+        pricing: None,
+        car_occupancy: None,
     }
     .remove_weird_schedules(map)
 }
@@ -102,13 +102,19 @@ fn run(cmd: &mut Command) {
 }
 
 // Converts a RawMap to a Map.
-pub fn raw_to_map(name: &str, build_ch: bool, timer: &mut Timer) -> map_model::Map {
+pub fn raw_to_map(
+    name: &str,
+    opts: &map_model::RawToMapOptions,
+    timer: &mut Timer,
+) -> map_model::Map {
     timer.start(format!("Raw->Map for {}", name));
     let raw: map_model::raw::RawMap = abstutil::read_binary(abstutil::path_raw_map(name), timer);
-    let map = map_model::Map::create_from_raw(raw, build_ch, timer);
+    let map = map_model::Map::create_from_raw(raw, opts, timer);
     timer.start("save map");
     map.save();
     timer.stop("save map");
+    map_model::WalkingDistances::new(&map, timer).save();
+    map_model::QaReport::new(&map, timer).save();
     timer.stop(format!("Raw->Map for {}", name));
 
     // TODO Just sticking this here for now
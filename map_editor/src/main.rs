@@ -3,13 +3,15 @@ mod world;
 
 use abstutil::{CmdArgs, Timer};
 use ezgui::{
-    hotkey, Btn, Canvas, Choice, Color, Composite, Drawable, EventCtx, EventLoopMode, GeomBatch,
-    GfxCtx, HorizontalAlignment, Key, Line, Outcome, ScreenPt, Text, VerticalAlignment, Widget,
-    Wizard, GUI,
+    hotkey, Btn, Canvas, Checkbox, Choice, Color, Composite, Drawable, EventCtx, EventLoopMode,
+    GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome, ScreenPt, Text, VerticalAlignment,
+    Widget, Wizard, GUI,
 };
-use geom::{Distance, Line, Polygon, Pt2D};
-use map_model::raw::{OriginalBuilding, OriginalIntersection, OriginalRoad, RestrictionType};
-use map_model::{osm, NORMAL_LANE_THICKNESS};
+use geom::{Circle, Distance, Line, Polygon, Pt2D};
+use map_model::raw::{
+    GeometryOverride, OriginalBuilding, OriginalIntersection, OriginalRoad, RestrictionType,
+};
+use map_model::{osm, QaReport, NORMAL_LANE_THICKNESS};
 use model::{Model, ID};
 use std::collections::HashSet;
 
@@ -40,8 +42,37 @@ enum State {
     PreviewIntersection(Drawable, bool),
     EnteringWarp(Wizard),
     StampingRoads(String, String, String, String),
+    FixingIntersectionGeometry(IntersectionFix),
+    ReviewingQa(QaQueue),
+}
+
+// Manually overriding the generated geometry of one intersection
+struct IntersectionFix {
+    id: OriginalIntersection,
+    fix: GeometryOverride,
+    // Index into pts
+    dragging: Option<usize>,
+
+    // Derived from fix. The polygon, without repeating the first point at the end
+    pts: Vec<Pt2D>,
+    roads: Vec<(OriginalRoad, Polygon)>,
+    draw: Drawable,
 }
 
+// Stepping through the issues the importer flagged
+struct QaQueue {
+    report: QaReport,
+    current: usize,
+    composite: Composite,
+}
+
+// Must match ObjectID::layer
+const LAYERS: [&str; 4] = ["roads", "intersections", "buildings", "turn restrictions"];
+
+const HANDLE_RADIUS: Distance = Distance::const_meters(1.0);
+const TRIM_STEP: Distance = Distance::const_meters(1.0);
+const QA_ISSUE_RADIUS: Distance = Distance::const_meters(10.0);
+
 impl State {
     fn viewing() -> State {
         State::Viewing {
@@ -83,11 +114,19 @@ impl UI {
                             (hotkey(Key::G), "preview all intersections"),
                             (None, "find overlapping intersections"),
                             (hotkey(Key::Z), "find/clear short roads"),
+                            (hotkey(Key::Q), "review QA issues"),
                         ]
                         .into_iter()
                         .map(|(key, action)| Btn::text_fg(action).build_def(ctx, key))
                         .collect(),
                     ),
+                    Line("Layers").draw(ctx),
+                    Widget::col(
+                        LAYERS
+                            .iter()
+                            .map(|layer| Checkbox::toggle(ctx, *layer, None, true).margin_below(5))
+                            .collect(),
+                    ),
                 ])
                 .padding(10)
                 .bg(Color::grey(0.4)),
@@ -169,6 +208,15 @@ impl GUI for UI {
                         {
                             let draw = preview_intersection(i, &self.model, ctx);
                             self.state = State::PreviewIntersection(draw, false);
+                        } else if !self.model.map.roads_per_intersection(i).is_empty()
+                            && ctx.input.key_pressed(Key::O, "fix intersection geometry")
+                        {
+                            self.state = State::FixingIntersectionGeometry(IntersectionFix::new(
+                                i,
+                                self.model.get_i_override(i),
+                                &self.model,
+                                ctx,
+                            ));
                         }
                     }
                     Some(ID::Building(b)) => {
@@ -287,9 +335,32 @@ impl GUI for UI {
                                         short_roads.clear();
                                     }
                                 }
+                                "review QA issues" => {
+                                    if let Some(report) = QaReport::load(
+                                        &self.model.map.name,
+                                        &mut Timer::throwaway(),
+                                    ) {
+                                        self.state = State::ReviewingQa(QaQueue::new(
+                                            report,
+                                            &self.model,
+                                            ctx,
+                                        ));
+                                    } else {
+                                        println!(
+                                            "No QA report for {}; run the importer first",
+                                            self.model.map.name
+                                        );
+                                    }
+                                }
                                 _ => unreachable!(),
                             },
                             None => {
+                                for layer in &LAYERS {
+                                    if let Some(visible) = self.composite.checkbox_toggled(layer) {
+                                        self.model.world.set_layer_visible(*layer, visible);
+                                    }
+                                }
+
                                 if ctx.input.key_pressed(Key::I, "create intersection") {
                                     if let Some(pt) = cursor {
                                         self.model.create_i(pt, ctx.prerender);
@@ -534,6 +605,118 @@ impl GUI for UI {
                     }
                 }
             }
+            State::FixingIntersectionGeometry(ref mut f) => {
+                if let Some(idx) = f.dragging {
+                    if let Some(pt) = cursor {
+                        f.pts[idx] = pt;
+                        f.pin_polygon(&self.model);
+                        f.recalculate(&self.model, ctx);
+                    }
+                    if ctx.input.key_released(Key::LeftControl) {
+                        f.dragging = None;
+                    }
+                } else if ctx
+                    .input
+                    .key_pressed(Key::Escape, "finish fixing intersection")
+                {
+                    self.model
+                        .set_i_override(f.id, f.fix.clone(), ctx.prerender);
+                    self.state = State::viewing();
+                    self.model.world.handle_mouseover(ctx);
+                } else if ctx.input.key_pressed(Key::X, "reset to generated geometry") {
+                    f.fix = GeometryOverride::new();
+                    f.recalculate(&self.model, ctx);
+                } else if let Some(pt) = cursor {
+                    if let Some(idx) = f.pts.iter().position(|p| p.dist_to(pt) < HANDLE_RADIUS) {
+                        if ctx.input.key_pressed(Key::LeftControl, "move point") {
+                            f.dragging = Some(idx);
+                        } else if f.pts.len() > 3
+                            && ctx.input.key_pressed(Key::Backspace, "delete point")
+                        {
+                            f.pts.remove(idx);
+                            f.pin_polygon(&self.model);
+                            f.recalculate(&self.model, ctx);
+                        }
+                    } else if let Some(r) = f
+                        .roads
+                        .iter()
+                        .find(|(_, poly)| poly.contains_pt(pt))
+                        .map(|(r, _)| *r)
+                    {
+                        let trim = f.fix.trim_roads.get(&r).cloned().unwrap_or(Distance::ZERO);
+                        let new_trim = if ctx.input.key_pressed(Key::LeftBracket, "trim road back")
+                        {
+                            Some(trim + TRIM_STEP)
+                        } else if ctx.input.key_pressed(Key::RightBracket, "extend road") {
+                            Some(trim - TRIM_STEP)
+                        } else {
+                            None
+                        };
+                        if let Some(new_trim) = new_trim {
+                            if new_trim == Distance::ZERO {
+                                f.fix.trim_roads.remove(&r);
+                            } else {
+                                f.fix.trim_roads.insert(r, new_trim);
+                            }
+                            f.recalculate(&self.model, ctx);
+                        }
+                    } else if ctx.input.key_pressed(Key::P, "add point") {
+                        // Put it on whichever edge makes the smallest detour
+                        let n = f.pts.len();
+                        let idx = (0..n)
+                            .min_by_key(|idx| {
+                                let a = f.pts[*idx];
+                                let b = f.pts[(*idx + 1) % n];
+                                a.dist_to(pt) + pt.dist_to(b) - a.dist_to(b)
+                            })
+                            .unwrap();
+                        f.pts.insert(idx + 1, pt);
+                        f.pin_polygon(&self.model);
+                        f.recalculate(&self.model, ctx);
+                    }
+                }
+            }
+            State::ReviewingQa(ref mut q) => {
+                if let Some(Outcome::Clicked(x)) = q.composite.event(ctx) {
+                    let n = q.report.issues.len();
+                    match x.as_ref() {
+                        "previous" => {
+                            q.select((q.current + n - 1) % n, &self.model, ctx);
+                        }
+                        "next" => {
+                            q.select((q.current + 1) % n, &self.model, ctx);
+                        }
+                        "apply fix" => {
+                            let issue = q.report.issues.remove(q.current);
+                            if !self
+                                .model
+                                .apply_qa_fix(issue.fix.as_ref().unwrap(), ctx.prerender)
+                            {
+                                println!("Can't {} anymore", issue.fix.unwrap().describe());
+                            }
+                            self.model.world.handle_mouseover(ctx);
+                            q.select(q.current, &self.model, ctx);
+                        }
+                        "dismiss" => {
+                            q.report.issues.remove(q.current);
+                            q.select(q.current, &self.model, ctx);
+                        }
+                        "export remaining" => {
+                            q.report.save();
+                            println!(
+                                "Saved {} remaining issues to {}",
+                                q.report.issues.len(),
+                                abstutil::path_qa_report(&q.report.map_name)
+                            );
+                        }
+                        "stop reviewing" => {
+                            self.state = State::viewing();
+                            self.model.world.handle_mouseover(ctx);
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            }
         }
 
         self.popup = None;
@@ -560,10 +743,12 @@ impl GUI for UI {
 
         g.draw_polygon(Color::rgb(242, 239, 233), &self.model.map.boundary_polygon);
         match self.state {
-            State::PreviewIntersection(_, _) => self.model.world.draw(g, |id| match id {
-                ID::Intersection(_) => false,
-                _ => true,
-            }),
+            State::PreviewIntersection(_, _) | State::FixingIntersectionGeometry(_) => {
+                self.model.world.draw(g, |id| match id {
+                    ID::Intersection(_) => false,
+                    _ => true,
+                })
+            }
             _ => self.model.world.draw(g, |_| true),
         }
 
@@ -624,6 +809,22 @@ impl GUI for UI {
                     }
                 }
             }
+            State::FixingIntersectionGeometry(ref f) => {
+                g.redraw(&f.draw);
+            }
+            State::ReviewingQa(ref q) => {
+                if let Some(issue) = q.report.issues.get(q.current) {
+                    g.draw_polygon(
+                        Color::RED.alpha(0.5),
+                        &Circle::new(
+                            Pt2D::forcibly_from_gps(issue.location, &self.model.map.gps_bounds),
+                            QA_ISSUE_RADIUS,
+                        )
+                        .to_polygon(),
+                    );
+                }
+                q.composite.draw(g);
+            }
         };
 
         self.composite.draw(g);
@@ -646,12 +847,14 @@ impl GUI for UI {
 }
 
 fn preview_intersection(i: OriginalIntersection, model: &Model, ctx: &EventCtx) -> Drawable {
-    let (intersection, roads, debug) = model
-        .map
-        .preview_intersection(i, &mut Timer::new("calculate intersection_polygon"));
+    let (intersection, roads, debug) = model.map.preview_intersection(
+        i,
+        Some(&model.get_i_override(i)),
+        &mut Timer::new("calculate intersection_polygon"),
+    );
     let mut batch = GeomBatch::new();
     batch.push(Color::ORANGE.alpha(0.5), intersection);
-    for r in roads {
+    for (_, r) in roads {
         batch.push(Color::GREEN.alpha(0.5), r);
     }
     for (label, poly) in debug {
@@ -677,7 +880,10 @@ fn preview_all_intersections(model: &Model, ctx: &EventCtx) -> Drawable {
         if model.map.roads_per_intersection(*i).is_empty() {
             continue;
         }
-        let (intersection, _, _) = model.map.preview_intersection(*i, &mut timer);
+        let (intersection, _, _) =
+            model
+                .map
+                .preview_intersection(*i, Some(&model.get_i_override(*i)), &mut timer);
         batch.push(Color::ORANGE.alpha(0.5), intersection);
     }
     batch.upload(ctx)
@@ -690,7 +896,10 @@ fn find_overlapping_intersections(model: &Model, ctx: &EventCtx) -> Drawable {
         if model.map.roads_per_intersection(*i).is_empty() {
             continue;
         }
-        let (intersection, _, _) = model.map.preview_intersection(*i, &mut timer);
+        let (intersection, _, _) =
+            model
+                .map
+                .preview_intersection(*i, Some(&model.get_i_override(*i)), &mut timer);
         polygons.push((*i, intersection));
     }
 
@@ -718,6 +927,120 @@ fn find_overlapping_intersections(model: &Model, ctx: &EventCtx) -> Drawable {
     batch.upload(ctx)
 }
 
+impl IntersectionFix {
+    fn new(
+        id: OriginalIntersection,
+        fix: GeometryOverride,
+        model: &Model,
+        ctx: &EventCtx,
+    ) -> IntersectionFix {
+        let mut f = IntersectionFix {
+            id,
+            fix,
+            dragging: None,
+            pts: Vec::new(),
+            roads: Vec::new(),
+            draw: GeomBatch::new().upload(ctx),
+        };
+        f.recalculate(model, ctx);
+        f
+    }
+
+    // Once any point is touched, the polygon no longer comes from the roads.
+    fn pin_polygon(&mut self, model: &Model) {
+        self.fix.polygon = Some(
+            self.pts
+                .iter()
+                .map(|pt| pt.forcibly_to_gps(&model.map.gps_bounds))
+                .collect(),
+        );
+    }
+
+    fn recalculate(&mut self, model: &Model, ctx: &EventCtx) {
+        let (polygon, roads, _) =
+            model
+                .map
+                .preview_intersection(self.id, Some(&self.fix), &mut Timer::throwaway());
+        // Polygon might reorder the points, so keep the pinned ones in their original order.
+        self.pts = if let Some(ref pts) = self.fix.polygon {
+            pts.iter()
+                .map(|gps| Pt2D::forcibly_from_gps(*gps, &model.map.gps_bounds))
+                .collect()
+        } else {
+            let mut pts = polygon.points().clone();
+            if pts.len() > 1 && pts[0] == *pts.last().unwrap() {
+                pts.pop();
+            }
+            pts
+        };
+
+        let mut batch = GeomBatch::new();
+        for (r, poly) in &roads {
+            let color = if self.fix.trim_roads.contains_key(r) {
+                Color::CYAN
+            } else {
+                Color::GREEN
+            };
+            batch.push(color.alpha(0.5), poly.clone());
+        }
+        batch.push(Color::ORANGE.alpha(0.5), polygon);
+        for pt in &self.pts {
+            batch.push(Color::RED, Circle::new(*pt, HANDLE_RADIUS).to_polygon());
+        }
+        self.roads = roads;
+        self.draw = batch.upload(ctx);
+    }
+}
+
+impl QaQueue {
+    fn new(report: QaReport, model: &Model, ctx: &mut EventCtx) -> QaQueue {
+        let mut q = QaQueue {
+            report,
+            current: 0,
+            composite: Composite::new(Widget::nothing()).build(ctx),
+        };
+        q.select(0, model, ctx);
+        q
+    }
+
+    // Warps to the issue. Clamps the index, since issues get removed as they're handled.
+    fn select(&mut self, idx: usize, model: &Model, ctx: &mut EventCtx) {
+        let n = self.report.issues.len();
+        self.current = idx.min(n.saturating_sub(1));
+
+        let mut col = Vec::new();
+        if let Some(issue) = self.report.issues.get(self.current) {
+            ctx.canvas.center_on_map_pt(Pt2D::forcibly_from_gps(
+                issue.location,
+                &model.map.gps_bounds,
+            ));
+
+            col.push(
+                Line(format!("QA issue {}/{}", self.current + 1, n))
+                    .small_heading()
+                    .draw(ctx),
+            );
+            col.push(Text::from(Line(&issue.description)).draw(ctx));
+            col.push(Widget::row(vec![
+                Btn::text_fg("previous").build_def(ctx, hotkey(Key::LeftArrow)),
+                Btn::text_fg("next").build_def(ctx, hotkey(Key::RightArrow)),
+            ]));
+            if let Some(ref fix) = issue.fix {
+                col.push(Btn::text_fg(fix.describe()).build(ctx, "apply fix", hotkey(Key::F)));
+            }
+            col.push(Btn::text_fg("dismiss").build_def(ctx, hotkey(Key::Backspace)));
+        } else {
+            col.push(Line("No QA issues left").small_heading().draw(ctx));
+        }
+        col.push(Btn::text_fg("export remaining").build_def(ctx, None));
+        col.push(Btn::text_fg("stop reviewing").build_def(ctx, hotkey(Key::Escape)));
+
+        self.composite = Composite::new(Widget::col(col).padding(10).bg(Color::grey(0.4)))
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+            .build(ctx);
+    }
+}
+
 // TODO OriginalRoad is dangerous, as this map changes. :\
 fn find_short_roads(model: &Model) -> HashSet<OriginalRoad> {
     // Assume the full map has been built. We really care about short lanes there.
@@ -3,11 +3,11 @@ use abstutil::Timer;
 use ezgui::{Color, Line, Prerender, Text};
 use geom::{ArrowCap, Bounds, Circle, Distance, FindClosest, GPSBounds, PolyLine, Polygon, Pt2D};
 use map_model::raw::{
-    OriginalBuilding, OriginalIntersection, OriginalRoad, RawBuilding, RawIntersection, RawMap,
-    RawRoad, RestrictionType, TurnRestriction,
+    GeometryOverride, IntersectionGeometryOverrides, OriginalBuilding, OriginalIntersection,
+    OriginalRoad, RawBuilding, RawIntersection, RawMap, RawRoad, RestrictionType, TurnRestriction,
 };
 use map_model::{
-    osm, IntersectionType, LaneType, RoadSpec, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
+    osm, IntersectionType, LaneType, QaFix, RoadSpec, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
 };
 use std::collections::{BTreeMap, BTreeSet};
 use std::mem;
@@ -22,6 +22,7 @@ pub struct Model {
     pub map: RawMap,
     showing_pts: Option<OriginalRoad>,
     pub world: World<ID>,
+    geometry_overrides: IntersectionGeometryOverrides,
 
     include_bldgs: bool,
     pub intersection_geom: bool,
@@ -36,6 +37,7 @@ impl Model {
 
             include_bldgs: false,
             world: World::new(),
+            geometry_overrides: IntersectionGeometryOverrides::new(""),
             intersection_geom: false,
         }
     }
@@ -56,6 +58,7 @@ impl Model {
             model.map = abstutil::read_json(path, &mut timer);
         }
         model.intersection_geom = intersection_geom;
+        model.geometry_overrides = IntersectionGeometryOverrides::load(&model.map.name, &mut timer);
 
         if model.include_bldgs {
             for id in model.map.buildings.keys().cloned().collect::<Vec<_>>() {
@@ -123,21 +126,18 @@ impl Model {
     }
 
     fn compute_bounds(&self) -> Bounds {
-        let mut bounds = Bounds::new();
-        for b in self.map.buildings.values() {
-            for pt in b.polygon.points() {
-                bounds.update(*pt);
-            }
-        }
-        for i in self.map.intersections.values() {
-            bounds.update(i.point);
-        }
-        for r in self.map.roads.values() {
-            for pt in &r.center_points {
-                bounds.update(*pt);
-            }
-        }
-        bounds
+        let bldgs = self
+            .map
+            .buildings
+            .values()
+            .flat_map(|b| b.polygon.points().clone());
+        let intersections = self.map.intersections.values().map(|i| i.point);
+        let roads = self
+            .map
+            .roads
+            .values()
+            .flat_map(|r| r.center_points.clone());
+        bldgs.chain(intersections).chain(roads).collect()
     }
 
     pub fn delete_everything_inside(&mut self, area: Polygon) {
@@ -247,7 +247,11 @@ impl Model {
         };
 
         let poly = if self.intersection_geom && !self.map.roads_per_intersection(id).is_empty() {
-            let (poly, _, _) = self.map.preview_intersection(id, &mut Timer::throwaway());
+            let (poly, _, _) = self.map.preview_intersection(
+                id,
+                self.geometry_overrides.intersections.get(&id),
+                &mut Timer::throwaway(),
+            );
             poly
         } else {
             Circle::new(i.point, INTERSECTION_RADIUS).to_polygon()
@@ -306,6 +310,35 @@ impl Model {
         self.intersection_added(id, prerender);
     }
 
+    pub fn get_i_override(&self, id: OriginalIntersection) -> GeometryOverride {
+        self.geometry_overrides
+            .intersections
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(GeometryOverride::new)
+    }
+
+    pub fn set_i_override(
+        &mut self,
+        id: OriginalIntersection,
+        fix: GeometryOverride,
+        prerender: &Prerender,
+    ) {
+        if fix.is_empty() {
+            self.geometry_overrides.intersections.remove(&id);
+        } else {
+            self.geometry_overrides.intersections.insert(id, fix);
+        }
+        self.world.delete(ID::Intersection(id));
+        self.intersection_added(id, prerender);
+
+        // The map might've been named since loading
+        if !self.map.name.is_empty() {
+            self.geometry_overrides.map_name = self.map.name.clone();
+            self.geometry_overrides.save();
+        }
+    }
+
     pub fn delete_i(&mut self, id: OriginalIntersection) {
         if !self.map.can_delete_intersection(id) {
             println!("Can't delete intersection used by roads");
@@ -482,6 +515,30 @@ impl Model {
         }
     }
 
+    // Unlike toggle_r_sidewalks, only touches this one road.
+    pub fn set_r_sidewalks(&mut self, id: OriginalRoad, value: String, prerender: &Prerender) {
+        self.road_deleted(id);
+
+        let osm_tags = &mut self.map.roads.get_mut(&id).unwrap().osm_tags;
+        osm_tags.remove(osm::INFERRED_SIDEWALKS);
+        osm_tags.insert(osm::SIDEWALK.to_string(), value);
+
+        self.road_added(id, prerender);
+    }
+
+    // Returns false if the fix refers to something that doesn't exist anymore.
+    pub fn apply_qa_fix(&mut self, fix: &QaFix, prerender: &Prerender) -> bool {
+        match fix {
+            QaFix::SetSidewalks(r, value) => {
+                if !self.map.roads.contains_key(r) {
+                    return false;
+                }
+                self.set_r_sidewalks(*r, value.clone(), prerender);
+                true
+            }
+        }
+    }
+
     pub fn delete_r(&mut self, id: OriginalRoad) {
         self.stop_showing_pts(id);
         self.road_deleted(id);
@@ -584,6 +641,7 @@ impl Model {
             LaneType::Biking => Color::rgb(15, 125, 75),
             LaneType::SharedLeftTurn => Color::YELLOW,
             LaneType::Construction => Color::rgb(255, 109, 0),
+            LaneType::HOV => Color::rgb(140, 90, 180),
         };
         if unset {
             Color::rgba_f(0.9, color.g, color.b, 0.5)
@@ -766,6 +824,7 @@ impl Model {
                 public_garage_name: None,
                 num_parking_spots: 0,
                 amenities: BTreeSet::new(),
+                height: None,
             },
         );
         self.bldg_added(id, prerender);
@@ -810,6 +869,15 @@ impl ObjectID for ID {
             ID::TurnRestriction(_) => 4,
         }
     }
+
+    fn layer(&self) -> &'static str {
+        match self {
+            ID::Road(_) | ID::RoadPoint(_, _) => "roads",
+            ID::Intersection(_) => "intersections",
+            ID::Building(_) => "buildings",
+            ID::TurnRestriction(_) => "turn restrictions",
+        }
+    }
 }
 
 // Don't conflict with the synthetic IDs generated by map clipping.
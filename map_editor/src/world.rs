@@ -1,13 +1,15 @@
 use aabb_quadtree::{ItemId, QuadTree};
 use ezgui::{Color, Drawable, EventCtx, GeomBatch, GfxCtx, Prerender};
 use geom::{Bounds, Circle, Distance, Polygon, Pt2D};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
 
 pub trait ObjectID: Clone + Copy + Debug + Eq + Hash {
     // Higher shows up in the front.
     fn zorder(&self) -> usize;
+    // Objects in a hidden layer aren't drawn and can't be selected.
+    fn layer(&self) -> &'static str;
 }
 
 pub struct Object<ID: ObjectID> {
@@ -49,6 +51,7 @@ pub struct World<ID: ObjectID> {
     objects: HashMap<ID, WorldObject>,
     quadtree: QuadTree<ID>,
     current_selection: Option<ID>,
+    hidden_layers: HashSet<&'static str>,
 }
 
 impl<ID: ObjectID> World<ID> {
@@ -66,13 +69,22 @@ impl<ID: ObjectID> World<ID> {
                 .as_bbox(),
             ),
             current_selection: None,
+            hidden_layers: HashSet::new(),
+        }
+    }
+
+    pub fn set_layer_visible(&mut self, layer: &'static str, visible: bool) {
+        if visible {
+            self.hidden_layers.remove(layer);
+        } else {
+            self.hidden_layers.insert(layer);
         }
     }
 
     pub fn draw<F: Fn(ID) -> bool>(&self, g: &mut GfxCtx, show: F) {
         let mut objects: Vec<ID> = Vec::new();
         for &(id, _, _) in &self.quadtree.query(g.get_screen_bounds().as_bbox()) {
-            if show(*id) {
+            if show(*id) && !self.hidden_layers.contains(id.layer()) {
                 objects.push(*id);
             }
         }
@@ -104,7 +116,9 @@ impl<ID: ObjectID> World<ID> {
                 .get_bounds()
                 .as_bbox(),
         ) {
-            objects.push(*id);
+            if !self.hidden_layers.contains(id.layer()) {
+                objects.push(*id);
+            }
         }
         objects.sort_by_key(|id| id.zorder());
         objects.reverse();
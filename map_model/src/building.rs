@@ -1,7 +1,7 @@
 use crate::{LaneID, Position};
-use geom::{Line, PolyLine, Polygon, Pt2D};
+use geom::{Distance, Line, PolyLine, Polygon, Pt2D};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 // TODO reconsider pub usize. maybe outside world shouldnt know.
@@ -41,11 +41,14 @@ pub struct Building {
     pub address: String,
     pub name: Option<String>,
     pub osm_way_id: i64,
+    pub osm_tags: BTreeMap<String, String>,
     // Where a text label should be centered to have the best chances of being contained within the
     // polygon.
     pub label_center: Pt2D,
     // (Name, amenity)
     pub amenities: BTreeSet<(String, String)>,
+    // Only known if OSM has it
+    pub height: Option<Distance>,
 
     pub front_path: FrontPath,
     // Every building can't have OffstreetParking, because the nearest usable driving lane (not in
@@ -1,5 +1,7 @@
 use crate::{LaneID, Position};
+use geom::Duration;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -32,6 +34,38 @@ pub struct BusStop {
     // anywhere.
     pub driving_pos: Position,
     pub sidewalk_pos: Position,
+    pub amenities: BusStopAmenities,
+}
+
+// From OSM, when the stop is tagged. None means unknown.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BusStopAmenities {
+    pub shelter: Option<bool>,
+    pub bench: Option<bool>,
+    pub wheelchair: Option<bool>,
+    // Can people board without stepping up? Without this, boarding takes longer, especially for
+    // wheelchairs, strollers, and bikes.
+    pub level_boarding: Option<bool>,
+}
+
+impl BusStopAmenities {
+    pub fn from_osm(tags: &BTreeMap<String, String>) -> BusStopAmenities {
+        let flag = |key: &str| match tags.get(key).map(|x| x.as_str()) {
+            Some("yes") | Some("designated") => Some(true),
+            Some("no") => Some(false),
+            _ => None,
+        };
+        BusStopAmenities {
+            shelter: flag("shelter"),
+            bench: flag("bench"),
+            wheelchair: flag("wheelchair"),
+            level_boarding: match tags.get("kerb").map(|x| x.as_str()) {
+                Some("raised") => Some(true),
+                Some("lowered") | Some("flush") | Some("no") => Some(false),
+                _ => None,
+            },
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,4 +73,6 @@ pub struct BusRoute {
     pub id: BusRouteID,
     pub name: String,
     pub stops: Vec<BusStopID>,
+    // How often a bus should arrive at each stop. None means a single bus serves the whole route.
+    pub headway: Option<Duration>,
 }
@@ -1,7 +1,8 @@
-use crate::{LaneID, Map, PathConstraints};
+use crate::make::turns::make_vehicle_turn;
+use crate::{Lane, LaneID, Map, PathConstraints, Turn, TurnID, TurnType};
 use abstutil::Timer;
 use petgraph::graphmap::DiGraphMap;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 
 // SCC = strongly connected component
 
@@ -93,3 +94,215 @@ fn bidi_flood(map: &Map, start: LaneID, largest_group: &HashSet<LaneID>) -> Opti
     }
     None
 }
+
+const ALL_MODES: [PathConstraints; 5] = [
+    PathConstraints::Pedestrian,
+    PathConstraints::Car,
+    PathConstraints::Bike,
+    PathConstraints::Bus,
+    PathConstraints::Truck,
+];
+
+// Something that would strand an agent using one mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LaneProblem {
+    // No turn usable by this mode leads out of the lane.
+    DeadEnd(LaneID, PathConstraints),
+    // No turn usable by this mode leads into the lane.
+    Unreachable(LaneID, PathConstraints),
+}
+
+impl LaneProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            LaneProblem::DeadEnd(l, mode) => format!("{:?} can't leave {}", mode, l),
+            LaneProblem::Unreachable(l, mode) => format!("{:?} can't reach {}", mode, l),
+        }
+    }
+}
+
+// Checks that every lane can be entered and left by every mode allowed to use it. Lanes touching
+// a border are fine, since agents appear and vanish there. Unlike find_scc, this catches the local
+// problems that route agents into dead ends, not just islands.
+pub fn validate_lanes(map: &Map) -> Vec<LaneProblem> {
+    let mut problems = Vec::new();
+    for l in map.all_lanes() {
+        for mode in ALL_MODES.iter().cloned() {
+            if !mode.can_use(l, map) {
+                continue;
+            }
+            let mut src_border = map.get_i(l.src_i).is_border();
+            let mut dst_border = map.get_i(l.dst_i).is_border();
+            // Sidewalks are bidirectional, so either end counts.
+            if mode == PathConstraints::Pedestrian {
+                src_border |= dst_border;
+                dst_border = src_border;
+            }
+
+            if !dst_border && map.get_turns_for(l.id, mode).is_empty() {
+                problems.push(LaneProblem::DeadEnd(l.id, mode));
+            }
+            if !src_border
+                && !map
+                    .get_turns_to_lane(l.id)
+                    .into_iter()
+                    .any(|t| mode.can_use(map.get_l(t.id.src), map))
+            {
+                problems.push(LaneProblem::Unreachable(l.id, mode));
+            }
+        }
+    }
+    problems
+}
+
+// Adds the most natural missing turn for every vehicle lane that's a dead end or unreachable. The
+// map has to have turns, but doesn't need traffic control yet. Sidewalk problems are left for the
+// QA report, since crosswalk geometry can't be guessed like this.
+pub(crate) fn repair_lanes(map: &Map, timer: &mut Timer) -> Vec<Turn> {
+    let mut new_turns: BTreeMap<TurnID, Turn> = BTreeMap::new();
+    for problem in validate_lanes(map) {
+        let (src, dst) = match problem {
+            LaneProblem::DeadEnd(_, PathConstraints::Pedestrian)
+            | LaneProblem::Unreachable(_, PathConstraints::Pedestrian) => {
+                continue;
+            }
+            LaneProblem::DeadEnd(l, mode) => {
+                let src = map.get_l(l);
+                let candidates = map
+                    .get_i(src.dst_i)
+                    .outgoing_lanes
+                    .iter()
+                    .map(|l| map.get_l(*l))
+                    .filter(|dst| mode.can_use(dst, map))
+                    .collect();
+                if let Some(dst) = best_match(src, candidates, true) {
+                    (src, dst)
+                } else {
+                    timer.warn(format!("Can't repair: {}", problem.describe()));
+                    continue;
+                }
+            }
+            LaneProblem::Unreachable(l, mode) => {
+                let dst = map.get_l(l);
+                let candidates = map
+                    .get_i(dst.src_i)
+                    .incoming_lanes
+                    .iter()
+                    .map(|l| map.get_l(*l))
+                    .filter(|src| mode.can_use(src, map))
+                    .collect();
+                if let Some(src) = best_match(dst, candidates, false) {
+                    (src, dst)
+                } else {
+                    timer.warn(format!("Can't repair: {}", problem.describe()));
+                    continue;
+                }
+            }
+        };
+        if let Some(turn) = make_vehicle_turn(
+            map.all_lanes(),
+            src.dst_i,
+            src.id,
+            dst.id,
+            TurnType::from_angles(src.last_line().angle(), dst.first_line().angle()),
+        ) {
+            if map.maybe_get_t(turn.id).is_none() {
+                new_turns.insert(turn.id, turn);
+            }
+        }
+    }
+    if !new_turns.is_empty() {
+        timer.note(format!(
+            "Added {} turns to repair lane connectivity",
+            new_turns.len()
+        ));
+    }
+    new_turns.into_iter().map(|(_, t)| t).collect()
+}
+
+// The candidate that keeps the closest heading, avoiding U-turns back onto the same road unless
+// there's no other choice. `from_src` means `lane` is where the turn starts.
+fn best_match<'a>(lane: &Lane, candidates: Vec<&'a Lane>, from_src: bool) -> Option<&'a Lane> {
+    let deviation = |other: &Lane| {
+        let rotation = if from_src {
+            lane.last_line()
+                .angle()
+                .shortest_rotation_towards(other.first_line().angle())
+        } else {
+            other
+                .last_line()
+                .angle()
+                .shortest_rotation_towards(lane.first_line().angle())
+        }
+        .normalized_degrees();
+        let deviation = rotation.min(360.0 - rotation);
+        let u_turn = other.parent == lane.parent;
+        (u_turn, (deviation * 100.0) as usize)
+    };
+    candidates
+        .into_iter()
+        .filter(|other| other.id != lane.id)
+        .min_by_key(|other| deviation(other))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::{RawMap, RestrictionType};
+    use crate::RawToMapOptions;
+
+    // Two intersections in a row. Every turn out of the road between them is banned at its east
+    // end, so its eastbound lanes lead nowhere.
+    fn dead_end_grid() -> RawMap {
+        let mut raw = RawMap::synthetic_grid("dead_end", 2, 1);
+        let from = *raw.roads.keys().find(|r| r.osm_way_id == 1).unwrap();
+        let banned: Vec<_> = raw
+            .roads
+            .keys()
+            .filter(|r| r.i1 == from.i2 || r.i2 == from.i2)
+            .cloned()
+            .collect();
+        for to in banned {
+            raw.roads
+                .get_mut(&from)
+                .unwrap()
+                .turn_restrictions
+                .push((RestrictionType::BanTurns, to));
+        }
+        raw
+    }
+
+    fn vehicle_problems(map: &Map) -> Vec<LaneProblem> {
+        validate_lanes(map)
+            .into_iter()
+            .filter(|p| match p {
+                LaneProblem::DeadEnd(_, mode) | LaneProblem::Unreachable(_, mode) => {
+                    *mode != PathConstraints::Pedestrian
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repair_only_when_asked() {
+        let opts = |repair_lanes| RawToMapOptions {
+            build_ch: false,
+            repair_lanes,
+        };
+        let mut timer = Timer::throwaway();
+
+        let broken = Map::create_from_raw(dead_end_grid(), &opts(false), &mut timer);
+        let road = broken
+            .all_roads()
+            .iter()
+            .find(|r| r.orig_id.osm_way_id == 1)
+            .unwrap();
+        let eastbound = road.children_forwards[0].0;
+        assert!(vehicle_problems(&broken)
+            .contains(&LaneProblem::DeadEnd(eastbound, PathConstraints::Car)));
+
+        let repaired = Map::create_from_raw(dead_end_grid(), &opts(true), &mut timer);
+        assert_eq!(vehicle_problems(&repaired), Vec::new());
+        assert!(repaired.all_turns().len() > broken.all_turns().len());
+    }
+}
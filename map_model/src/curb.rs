@@ -0,0 +1,102 @@
+use geom::Time;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// How one blockface of curb (a parking lane) may be used. Without any regulation, it's just
+// parking.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CurbRegulation {
+    pub default: CurbUse,
+    // The first rule covering the time of day wins.
+    pub rules: Vec<CurbRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CurbUse {
+    Parking,
+    NoParking,
+    // Only buses may stop
+    BusZone,
+    // Only deliveries to the buildings along this blockface may stop
+    LoadingZone,
+}
+
+// Applies every day, from start_hour up to end_hour. Wraps around midnight if end_hour is smaller.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CurbRule {
+    pub usage: CurbUse,
+    pub start_hour: usize,
+    pub end_hour: usize,
+}
+
+impl CurbRegulation {
+    pub fn parking() -> CurbRegulation {
+        CurbRegulation {
+            default: CurbUse::Parking,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == CurbRegulation::parking()
+    }
+
+    pub fn usage_at(&self, time: Time) -> CurbUse {
+        let hour = time.get_parts().0 % 24;
+        for rule in &self.rules {
+            if rule.covers(hour) {
+                return rule.usage;
+            }
+        }
+        self.default
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.default.to_string()];
+        for rule in &self.rules {
+            parts.push(format!(
+                "{} {}:00-{}:00",
+                rule.usage, rule.start_hour, rule.end_hour
+            ));
+        }
+        parts.join(", ")
+    }
+}
+
+impl std::default::Default for CurbRegulation {
+    fn default() -> CurbRegulation {
+        CurbRegulation::parking()
+    }
+}
+
+impl CurbRule {
+    fn covers(&self, hour: usize) -> bool {
+        if self.start_hour <= self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+impl CurbUse {
+    pub fn all() -> Vec<CurbUse> {
+        vec![
+            CurbUse::Parking,
+            CurbUse::NoParking,
+            CurbUse::BusZone,
+            CurbUse::LoadingZone,
+        ]
+    }
+}
+
+impl fmt::Display for CurbUse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CurbUse::Parking => write!(f, "parking"),
+            CurbUse::NoParking => write!(f, "no parking"),
+            CurbUse::BusZone => write!(f, "bus zone"),
+            CurbUse::LoadingZone => write!(f, "loading zone"),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    BusStopID, ControlTrafficSignal, EditCmd, EditEffects, EditIntersection, IntersectionID,
+    IntersectionType, LaneID, LaneType, Map, MapEdits, PathConstraints, PathRequest, RoadID,
+    TurnGroup, TurnGroupID,
+};
+use abstutil::Timer;
+use std::collections::{BTreeMap, BTreeSet};
+
+// For constructing edits from code, instead of through the UI. Each operation is checked against
+// the map with everything before it in the builder already taken into account, so one bad
+// operation doesn't silently produce broken edits.
+pub struct MapEditsBuilder<'a> {
+    map: &'a Map,
+    edits: MapEdits,
+    // Lane types changed by earlier operations
+    pending_lts: BTreeMap<LaneID, LaneType>,
+}
+
+impl<'a> MapEditsBuilder<'a> {
+    // Starts from the edits currently applied to the map.
+    pub fn new(map: &'a Map) -> MapEditsBuilder<'a> {
+        MapEditsBuilder {
+            map,
+            edits: map.get_edits().clone(),
+            pending_lts: BTreeMap::new(),
+        }
+    }
+
+    pub fn name(&mut self, name: &str) -> &mut MapEditsBuilder<'a> {
+        self.edits.edits_name = name.to_string();
+        self
+    }
+
+    pub fn change_lane_type(
+        &mut self,
+        l: LaneID,
+        lt: LaneType,
+    ) -> Result<&mut MapEditsBuilder<'a>, String> {
+        let orig_lt = self.lane_type(l);
+        if orig_lt == lt {
+            return Ok(self);
+        }
+        if let Some(err) = can_change_lane_type(self.map, l, lt, &self.pending_lts) {
+            return Err(err);
+        }
+        self.edits
+            .commands
+            .push(EditCmd::ChangeLaneType { id: l, lt, orig_lt });
+        self.pending_lts.insert(l, lt);
+        Ok(self)
+    }
+
+    pub fn set_signal(
+        &mut self,
+        signal: ControlTrafficSignal,
+    ) -> Result<&mut MapEditsBuilder<'a>, String> {
+        let i = signal.id;
+        self.check_editable(i)?;
+        // Against the turns there now, not counting lanes changed earlier in this builder
+        let expected: BTreeSet<TurnGroupID> =
+            TurnGroup::for_i(i, self.map).keys().cloned().collect();
+        if signal.turn_groups.keys().cloned().collect::<BTreeSet<_>>() != expected {
+            return Err(format!(
+                "Traffic signal for {} doesn't match the turns there",
+                i
+            ));
+        }
+        let signal = signal.validate()?;
+        self.push_intersection(i, EditIntersection::TrafficSignal(signal));
+        Ok(self)
+    }
+
+    pub fn close_intersection(
+        &mut self,
+        i: IntersectionID,
+    ) -> Result<&mut MapEditsBuilder<'a>, String> {
+        self.check_editable(i)?;
+        self.push_intersection(i, EditIntersection::Closed);
+        Ok(self)
+    }
+
+    // Closes every lane except for sidewalks, so people can still walk along the road.
+    pub fn close_road(&mut self, r: RoadID) -> Result<&mut MapEditsBuilder<'a>, String> {
+        let road = self.map.get_r(r);
+        if !road.all_bus_stops(self.map).is_empty() {
+            return Err(format!("Can't close {}; it has a bus stop", r));
+        }
+        let close: Vec<LaneID> = road
+            .all_lanes()
+            .into_iter()
+            .filter(|l| {
+                !self.map.get_l(*l).is_sidewalk() && self.lane_type(*l) != LaneType::Construction
+            })
+            .collect();
+        // Check each lane as if the rest of the road is already closed, so the order doesn't
+        // matter. Nothing changes unless every lane is fine.
+        let mut pending = self.pending_lts.clone();
+        for l in &close {
+            pending.insert(*l, LaneType::Construction);
+        }
+        for l in &close {
+            let mut others = pending.clone();
+            others.remove(l);
+            if let Some(err) = can_change_lane_type(self.map, *l, LaneType::Construction, &others) {
+                return Err(format!("Can't close {}: {}", r, err));
+            }
+        }
+        for l in close {
+            let orig_lt = self.lane_type(l);
+            self.edits.commands.push(EditCmd::ChangeLaneType {
+                id: l,
+                lt: LaneType::Construction,
+                orig_lt,
+            });
+        }
+        self.pending_lts = pending;
+        Ok(self)
+    }
+
+    pub fn build(self) -> MapEdits {
+        self.edits
+    }
+
+    // Applies edits from build() and recalculates pathfinding. The builder borrows the map, so
+    // this can't take self. Returns everything that changed since the edits already applied to
+    // the map, so callers know what else needs updating.
+    pub fn apply(edits: MapEdits, map: &mut Map, timer: &mut Timer) -> EditEffects {
+        let (changed_roads, deleted_turns, added_turns, changed_intersections) =
+            map.apply_edits(edits, timer);
+        map.recalculate_pathfinding_after_edits(timer);
+        EditEffects {
+            changed_roads,
+            changed_intersections,
+            added_turns,
+            deleted_turns,
+        }
+    }
+
+    fn lane_type(&self, l: LaneID) -> LaneType {
+        self.pending_lts
+            .get(&l)
+            .cloned()
+            .unwrap_or_else(|| self.map.get_l(l).lane_type)
+    }
+
+    fn check_editable(&self, i: IntersectionID) -> Result<(), String> {
+        if self.map.get_i(i).intersection_type == IntersectionType::Border {
+            return Err(format!("{} is a border; it can't be edited", i));
+        }
+        Ok(())
+    }
+
+    fn push_intersection(&mut self, i: IntersectionID, new: EditIntersection) {
+        // If an earlier operation already changed this intersection, the map doesn't know yet.
+        let old = self
+            .edits
+            .commands
+            .iter()
+            .rev()
+            .find_map(|cmd| match cmd {
+                EditCmd::ChangeIntersection { i: id, new, .. } if *id == i => Some(new.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.map.get_i_edit(i));
+        self.edits
+            .commands
+            .push(EditCmd::ChangeIntersection { i, new, old });
+    }
+}
+
+// None if the change is fine. Lanes in pending have already been changed to a new type.
+pub fn can_change_lane_type(
+    map: &Map,
+    l: LaneID,
+    new_lt: LaneType,
+    pending: &BTreeMap<LaneID, LaneType>,
+) -> Option<String> {
+    let r = map.get_parent(l);
+    let current = |(id, lt): &(LaneID, LaneType)| pending.get(id).cloned().unwrap_or(*lt);
+    let fwds_lts: Vec<LaneType> = r.children_forwards.iter().map(current).collect();
+    let back_lts: Vec<LaneType> = r.children_backwards.iter().map(current).collect();
+    let (fwds, idx) = r.dir_and_offset(l);
+    let (mut proposed_lts, other_side) = if fwds {
+        (fwds_lts, back_lts)
+    } else {
+        (back_lts, fwds_lts)
+    };
+
+    // No-op change
+    if proposed_lts[idx] == new_lt {
+        return None;
+    }
+    proposed_lts[idx] = new_lt;
+
+    // Only one parking lane per side.
+    if proposed_lts
+        .iter()
+        .filter(|lt| **lt == LaneType::Parking)
+        .count()
+        > 1
+    {
+        // TODO Actually, we just don't want two adjacent parking lanes
+        // (What about dppd though?)
+        return Some(format!(
+            "You can only have one parking lane on the same side of the road"
+        ));
+    }
+
+    // Don't let players orphan a bus stop.
+    if !r.all_bus_stops(map).is_empty()
+        && !proposed_lts
+            .iter()
+            .any(|lt| *lt == LaneType::Driving || *lt == LaneType::Bus)
+    {
+        return Some(format!("You need a driving or bus lane for the bus stop!"));
+    }
+
+    let all_types: BTreeSet<LaneType> = other_side
+        .into_iter()
+        .chain(proposed_lts.iter().cloned())
+        .collect();
+
+    // A parking lane must have a driving lane somewhere on the same road.
+    if all_types.contains(&LaneType::Parking) && !all_types.contains(&LaneType::Driving) {
+        return Some(format!(
+            "A parking lane needs a driving lane somewhere on the same road"
+        ));
+    }
+
+    None
+}
+
+// None if a bus can serve these stops in order, looping back to the first. Pathfinding must be up
+// to date.
+pub fn can_change_bus_route(map: &Map, stops: &[BusStopID]) -> Option<String> {
+    if stops.len() < 2 {
+        return Some("A route needs at least two stops".to_string());
+    }
+    for (idx, stop1) in stops.iter().enumerate() {
+        let stop2 = stops[(idx + 1) % stops.len()];
+        let bs1 = map.get_bs(*stop1);
+        let bs2 = map.get_bs(stop2);
+        // Same as when routes are first imported
+        if bs1.driving_pos.lane() == bs2.driving_pos.lane() {
+            return Some(format!(
+                "Stops {} and {} are too close together",
+                idx + 1,
+                (idx + 1) % stops.len() + 1
+            ));
+        }
+        if map
+            .pathfind(PathRequest {
+                start: bs1.driving_pos,
+                end: bs2.driving_pos,
+                constraints: PathConstraints::Bus,
+            })
+            .is_none()
+        {
+            return Some(format!(
+                "A bus can't get from stop {} to {}",
+                idx + 1,
+                (idx + 1) % stops.len() + 1
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::RawMap;
+
+    fn road(map: &Map, osm_way_id: i64) -> RoadID {
+        map.all_roads()
+            .iter()
+            .find(|r| r.orig_id.osm_way_id == osm_way_id)
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn effects_only_cover_new_operations() {
+        let mut timer = Timer::throwaway();
+        let mut map = Map::synthetic(RawMap::synthetic_grid("builder", 2, 1), true);
+        let (r1, r2) = (road(&map, 1), road(&map, 2));
+
+        let mut builder = MapEditsBuilder::new(&map);
+        builder.close_road(r1).unwrap();
+        let edits = builder.build();
+        let effects = MapEditsBuilder::apply(edits, &mut map, &mut timer);
+        assert_eq!(effects.changed_roads, vec![r1].into_iter().collect());
+
+        // Builds on the edits already applied
+        let mut builder = MapEditsBuilder::new(&map);
+        builder.close_road(r2).unwrap();
+        let edits = builder.build();
+        let effects = MapEditsBuilder::apply(edits, &mut map, &mut timer);
+        assert_eq!(effects.changed_roads, vec![r2].into_iter().collect());
+        // Driving and parking in both directions on each road
+        assert_eq!(map.get_edits().commands.len(), 8);
+        for l in map.get_r(r1).all_lanes() {
+            assert!(map.get_l(l).is_sidewalk() || map.get_l(l).lane_type == LaneType::Construction);
+        }
+    }
+}
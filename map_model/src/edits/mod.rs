@@ -1,9 +1,13 @@
+mod builder;
+
+pub use self::builder::{can_change_bus_route, can_change_lane_type, MapEditsBuilder};
 use crate::raw::{OriginalIntersection, OriginalRoad};
 use crate::{
-    ControlStopSign, ControlTrafficSignal, IntersectionID, LaneID, LaneType, Map, RoadID, TurnID,
+    AccessRestrictions, BusRouteID, BusStopID, ControlStopSign, ControlTrafficSignal,
+    CurbRegulation, IntersectionID, LaneID, LaneType, Map, RoadID, TruckAccess, TurnID,
 };
 use abstutil::{deserialize_btreemap, retain_btreemap, retain_btreeset, serialize_btreemap, Timer};
-use geom::Speed;
+use geom::{Duration, Speed};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -17,6 +21,10 @@ pub struct MapEdits {
     pub reversed_lanes: BTreeSet<LaneID>,
     pub original_intersections: BTreeMap<IntersectionID, EditIntersection>,
     pub changed_speed_limits: BTreeSet<RoadID>,
+    pub changed_access_restrictions: BTreeSet<RoadID>,
+    pub changed_curb_regulations: BTreeSet<LaneID>,
+    pub changed_block_the_box: BTreeSet<IntersectionID>,
+    pub original_bus_routes: BTreeMap<BusRouteID, EditBusRoute>,
 
     // Edits without these are player generated.
     pub proposal_description: Vec<String>,
@@ -31,6 +39,12 @@ pub enum EditIntersection {
     Closed,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditBusRoute {
+    pub stops: Vec<BusStopID>,
+    pub headway: Option<Duration>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EditCmd {
     ChangeLaneType {
@@ -53,6 +67,27 @@ pub enum EditCmd {
         new: EditIntersection,
         old: EditIntersection,
     },
+    ChangeAccessRestrictions {
+        id: RoadID,
+        new: AccessRestrictions,
+        old: AccessRestrictions,
+    },
+    ChangeCurbRegulation {
+        l: LaneID,
+        new: CurbRegulation,
+        old: CurbRegulation,
+    },
+    // Whether vehicles may enter the intersection without room to exit it
+    ChangeBlockTheBox {
+        i: IntersectionID,
+        new: bool,
+        old: bool,
+    },
+    ChangeBusRoute {
+        id: BusRouteID,
+        new: EditBusRoute,
+        old: EditBusRoute,
+    },
 }
 
 impl EditCmd {
@@ -66,6 +101,46 @@ impl EditCmd {
                 EditIntersection::TrafficSignal(_) => format!("traffic signal #{}", i.0),
                 EditIntersection::Closed => format!("close {}", i),
             },
+            EditCmd::ChangeAccessRestrictions { id, new, .. } => {
+                if new.pedestrian_only {
+                    format!("pedestrianize {}", id)
+                } else {
+                    match new.trucks {
+                        TruckAccess::Banned => format!("ban trucks on {}", id),
+                        TruckAccess::DesignatedRoute => format!("truck route on {}", id),
+                        TruckAccess::Destination => format!("local trucks only on {}", id),
+                        TruckAccess::Allowed => format!("access restrictions for {}", id),
+                    }
+                }
+            }
+            EditCmd::ChangeCurbRegulation { l, new, .. } => {
+                format!("{} on #{}", new.default, l.0)
+            }
+            EditCmd::ChangeBlockTheBox { i, new, .. } => {
+                if *new {
+                    format!("allow blocking #{}", i.0)
+                } else {
+                    format!("don't block #{}", i.0)
+                }
+            }
+            EditCmd::ChangeBusRoute { id, .. } => format!("reroute bus #{}", id.0),
+        }
+    }
+
+    // Can this be applied in the middle of a simulation, without restarting it? Only retiming
+    // traffic signals, changing speed limits, and closing lanes for vehicles.
+    pub fn can_apply_live(&self) -> bool {
+        match self {
+            EditCmd::ChangeSpeedLimit { .. } => true,
+            EditCmd::ChangeLaneType { lt, orig_lt, .. } => {
+                *lt == LaneType::Construction && orig_lt.is_for_moving_vehicles()
+            }
+            EditCmd::ChangeIntersection {
+                new: EditIntersection::TrafficSignal(_),
+                old: EditIntersection::TrafficSignal(_),
+                ..
+            } => true,
+            _ => false,
         }
     }
 }
@@ -78,6 +153,15 @@ pub struct EditEffects {
 }
 
 impl MapEdits {
+    // True if these edits just add commands to some earlier edits, and all of them can be applied
+    // live.
+    pub fn can_apply_live_after(&self, earlier: &MapEdits) -> bool {
+        self.commands.starts_with(&earlier.commands)
+            && self.commands[earlier.commands.len()..]
+                .iter()
+                .all(|cmd| cmd.can_apply_live())
+    }
+
     pub fn new() -> MapEdits {
         MapEdits {
             // Something has to fill this out later
@@ -90,6 +174,10 @@ impl MapEdits {
             reversed_lanes: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_speed_limits: BTreeSet::new(),
+            changed_access_restrictions: BTreeSet::new(),
+            changed_curb_regulations: BTreeSet::new(),
+            changed_block_the_box: BTreeSet::new(),
+            original_bus_routes: BTreeMap::new(),
         }
     }
 
@@ -118,6 +206,10 @@ impl MapEdits {
         let mut reversed_lanes = BTreeSet::new();
         let mut orig_intersections: BTreeMap<IntersectionID, EditIntersection> = BTreeMap::new();
         let mut changed_speed_limits = BTreeSet::new();
+        let mut changed_access_restrictions = BTreeSet::new();
+        let mut changed_curb_regulations = BTreeSet::new();
+        let mut changed_block_the_box = BTreeSet::new();
+        let mut orig_bus_routes: BTreeMap<BusRouteID, EditBusRoute> = BTreeMap::new();
 
         for cmd in &self.commands {
             match cmd {
@@ -141,6 +233,20 @@ impl MapEdits {
                         orig_intersections.insert(*i, old.clone());
                     }
                 }
+                EditCmd::ChangeAccessRestrictions { id, .. } => {
+                    changed_access_restrictions.insert(*id);
+                }
+                EditCmd::ChangeCurbRegulation { l, .. } => {
+                    changed_curb_regulations.insert(*l);
+                }
+                EditCmd::ChangeBlockTheBox { i, .. } => {
+                    changed_block_the_box.insert(*i);
+                }
+                EditCmd::ChangeBusRoute { id, ref old, .. } => {
+                    if !orig_bus_routes.contains_key(id) {
+                        orig_bus_routes.insert(*id, old.clone());
+                    }
+                }
             }
         }
 
@@ -151,11 +257,25 @@ impl MapEdits {
         retain_btreeset(&mut changed_speed_limits, |r| {
             map.get_r(*r).speed_limit != map.get_r(*r).speed_limit_from_osm()
         });
+        retain_btreeset(&mut changed_access_restrictions, |r| {
+            map.get_r(*r).access_restrictions != map.get_r(*r).access_restrictions_from_osm()
+        });
+        retain_btreeset(&mut changed_curb_regulations, |l| {
+            !map.get_curb_regulation(*l).is_default()
+        });
+        retain_btreeset(&mut changed_block_the_box, |i| {
+            map.allow_block_the_box(*i) != map.get_i(*i).allow_block_the_box_by_default()
+        });
+        retain_btreemap(&mut orig_bus_routes, |r, orig| map.get_br_edit(*r) != *orig);
 
         self.original_lts = orig_lts;
         self.reversed_lanes = reversed_lanes;
         self.original_intersections = orig_intersections;
         self.changed_speed_limits = changed_speed_limits;
+        self.changed_access_restrictions = changed_access_restrictions;
+        self.changed_curb_regulations = changed_curb_regulations;
+        self.changed_block_the_box = changed_block_the_box;
+        self.original_bus_routes = orig_bus_routes;
     }
 
     // Assumes update_derived has been called.
@@ -187,6 +307,34 @@ impl MapEdits {
                 old: map.get_r(*r).speed_limit_from_osm(),
             });
         }
+        for r in &self.changed_access_restrictions {
+            self.commands.push(EditCmd::ChangeAccessRestrictions {
+                id: *r,
+                new: map.get_r(*r).access_restrictions.clone(),
+                old: map.get_r(*r).access_restrictions_from_osm(),
+            });
+        }
+        for l in &self.changed_curb_regulations {
+            self.commands.push(EditCmd::ChangeCurbRegulation {
+                l: *l,
+                new: map.get_curb_regulation(*l),
+                old: CurbRegulation::parking(),
+            });
+        }
+        for i in &self.changed_block_the_box {
+            self.commands.push(EditCmd::ChangeBlockTheBox {
+                i: *i,
+                new: map.allow_block_the_box(*i),
+                old: map.get_i(*i).allow_block_the_box_by_default(),
+            });
+        }
+        for (r, old) in &self.original_bus_routes {
+            self.commands.push(EditCmd::ChangeBusRoute {
+                id: *r,
+                old: old.clone(),
+                new: map.get_br_edit(*r),
+            });
+        }
     }
 }
 
@@ -235,6 +383,13 @@ enum PermanentEditIntersection {
     Closed,
 }
 
+// Routes are identified by name, and stops by their sidewalk and index along it.
+#[derive(Serialize, Deserialize, Clone)]
+struct PermanentEditBusRoute {
+    stops: Vec<(OriginalLane, usize)>,
+    headway: Option<Duration>,
+}
+
 // Enough data to notice when lanes along a road have changed
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct OriginalLane {
@@ -267,10 +422,30 @@ enum PermanentEditCmd {
         new: PermanentEditIntersection,
         old: PermanentEditIntersection,
     },
+    ChangeAccessRestrictions {
+        id: OriginalRoad,
+        new: AccessRestrictions,
+        old: AccessRestrictions,
+    },
+    ChangeCurbRegulation {
+        l: OriginalLane,
+        new: CurbRegulation,
+        old: CurbRegulation,
+    },
+    ChangeBlockTheBox {
+        i: OriginalIntersection,
+        new: bool,
+        old: bool,
+    },
+    ChangeBusRoute {
+        name: String,
+        new: PermanentEditBusRoute,
+        old: PermanentEditBusRoute,
+    },
 }
 
 impl PermanentMapEdits {
-    fn to_permanent(edits: &MapEdits, map: &Map) -> PermanentMapEdits {
+    pub fn to_permanent(edits: &MapEdits, map: &Map) -> PermanentMapEdits {
         PermanentMapEdits {
             map_name: map.get_name().to_string(),
             edits_name: edits.edits_name.clone(),
@@ -305,6 +480,32 @@ impl PermanentMapEdits {
                             old: old.to_permanent(map),
                         }
                     }
+                    EditCmd::ChangeAccessRestrictions { id, new, old } => {
+                        PermanentEditCmd::ChangeAccessRestrictions {
+                            id: map.get_r(*id).orig_id,
+                            new: new.clone(),
+                            old: old.clone(),
+                        }
+                    }
+                    EditCmd::ChangeCurbRegulation { l, new, old } => {
+                        PermanentEditCmd::ChangeCurbRegulation {
+                            l: OriginalLane::to_permanent(*l, map),
+                            new: new.clone(),
+                            old: old.clone(),
+                        }
+                    }
+                    EditCmd::ChangeBlockTheBox { i, new, old } => {
+                        PermanentEditCmd::ChangeBlockTheBox {
+                            i: map.get_i(*i).orig_id,
+                            new: *new,
+                            old: *old,
+                        }
+                    }
+                    EditCmd::ChangeBusRoute { id, new, old } => PermanentEditCmd::ChangeBusRoute {
+                        name: map.get_br(*id).name.clone(),
+                        new: new.to_permanent(map),
+                        old: old.to_permanent(map),
+                    },
                 })
                 .collect(),
         }
@@ -350,6 +551,32 @@ impl PermanentMapEdits {
                                 .ok_or(format!("old ChangeIntersection of {} invalid", i))?,
                         })
                     }
+                    PermanentEditCmd::ChangeAccessRestrictions { id, new, old } => {
+                        let id = map.find_r_by_osm_id(
+                            id.osm_way_id,
+                            (id.i1.osm_node_id, id.i2.osm_node_id),
+                        )?;
+                        Ok(EditCmd::ChangeAccessRestrictions { id, new, old })
+                    }
+                    PermanentEditCmd::ChangeCurbRegulation { l, new, old } => {
+                        let l = l.from_permanent(map)?;
+                        Ok(EditCmd::ChangeCurbRegulation { l, new, old })
+                    }
+                    PermanentEditCmd::ChangeBlockTheBox { i, new, old } => {
+                        let i = map.find_i_by_osm_id(i.osm_node_id)?;
+                        Ok(EditCmd::ChangeBlockTheBox { i, new, old })
+                    }
+                    PermanentEditCmd::ChangeBusRoute { name, new, old } => {
+                        let id = map
+                            .get_bus_route(&name)
+                            .ok_or(format!("can't find bus route {}", name))?
+                            .id;
+                        Ok(EditCmd::ChangeBusRoute {
+                            id,
+                            new: new.from_permanent(map)?,
+                            old: old.from_permanent(map)?,
+                        })
+                    }
                 })
                 .collect::<Result<Vec<EditCmd>, String>>()?,
 
@@ -357,6 +584,10 @@ impl PermanentMapEdits {
             reversed_lanes: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_speed_limits: BTreeSet::new(),
+            changed_access_restrictions: BTreeSet::new(),
+            changed_curb_regulations: BTreeSet::new(),
+            changed_block_the_box: BTreeSet::new(),
+            original_bus_routes: BTreeMap::new(),
         };
         edits.update_derived(map);
         Ok(edits)
@@ -413,6 +644,39 @@ impl PermanentEditIntersection {
     }
 }
 
+impl EditBusRoute {
+    fn to_permanent(&self, map: &Map) -> PermanentEditBusRoute {
+        PermanentEditBusRoute {
+            stops: self
+                .stops
+                .iter()
+                .map(|bs| (OriginalLane::to_permanent(bs.sidewalk, map), bs.idx))
+                .collect(),
+            headway: self.headway,
+        }
+    }
+}
+
+impl PermanentEditBusRoute {
+    fn from_permanent(self, map: &Map) -> Result<EditBusRoute, String> {
+        let mut stops = Vec::new();
+        for (sidewalk, idx) in self.stops {
+            let id = BusStopID {
+                sidewalk: sidewalk.from_permanent(map)?,
+                idx,
+            };
+            if !map.all_bus_stops().contains_key(&id) {
+                return Err(format!("bus stop {} no longer exists", id));
+            }
+            stops.push(id);
+        }
+        Ok(EditBusRoute {
+            stops,
+            headway: self.headway,
+        })
+    }
+}
+
 impl OriginalLane {
     pub fn to_permanent(l: LaneID, map: &Map) -> OriginalLane {
         let r = map.get_parent(l);
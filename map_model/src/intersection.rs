@@ -1,10 +1,41 @@
 use crate::raw::OriginalIntersection;
-use crate::{DirectedRoadID, LaneID, Map, PathConstraints, Road, RoadID, TurnID};
+use crate::{DirectedRoadID, LaneID, Map, PathConstraints, Road, RoadID, RoadImportance, TurnID};
 use geom::{Distance, Polygon};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::fmt;
 
+// OSM node IDs of intersections where vehicles may block the box by default.
+// TODO Sometimes a traffic signal is surrounded by tiny lanes with almost no capacity.
+// Workaround for now.
+#[rustfmt::skip]
+const ALLOW_BLOCK_THE_BOX: &[i64] = &[
+    // 23rd and Madison
+    53211694, 53211693,
+    // 31st and S Jackson
+    53045512,
+    // 23rd and Rainier
+    53212741, 3496255252,
+    // Rainier and Dearborn
+    4607162574,
+    // WA 509 and WA 99
+    31327525,
+    // 45th and Union Bay
+    31192107, 4272330879, 53120147,
+    // 45th and Montlake
+    31430639, 29977895, 3391705317, 29977897,
+    // Rainier and Brandon
+    53089293,
+    // Rainier and Letitia
+    1729797719, 4272388873, 53194882,
+    // Rainier and MLK
+    53131428,
+    // Montlake and 520
+    53128053,
+    // 12th and Boren
+    53165712,
+];
+
 // TODO reconsider pub usize. maybe outside world shouldnt know.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct IntersectionID(pub usize);
@@ -60,6 +91,12 @@ impl Intersection {
         self.intersection_type == IntersectionType::TrafficSignal
     }
 
+    // When the simulation is set to not block the box, vehicles can still block these, unless an
+    // edit says otherwise.
+    pub fn allow_block_the_box_by_default(&self) -> bool {
+        ALLOW_BLOCK_THE_BOX.contains(&self.orig_id.osm_node_id)
+    }
+
     pub fn get_incoming_lanes(&self, map: &Map, constraints: PathConstraints) -> Vec<LaneID> {
         self.incoming_lanes
             .iter()
@@ -91,6 +128,14 @@ impl Intersection {
             .unwrap()
     }
 
+    pub fn get_importance(&self, map: &Map) -> RoadImportance {
+        self.roads
+            .iter()
+            .map(|r| map.get_r(*r).importance)
+            .max()
+            .unwrap()
+    }
+
     pub(crate) fn get_roads_sorted_by_incoming_angle(&self, all_roads: &Vec<Road>) -> Vec<RoadID> {
         let center = self.polygon.center();
         let mut roads: Vec<RoadID> = self.roads.iter().cloned().collect();
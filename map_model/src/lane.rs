@@ -33,6 +33,9 @@ pub enum LaneType {
     Bus,
     SharedLeftTurn,
     Construction,
+    // High-occupancy vehicles only. Buses and bikes can always use these; cars need a minimum
+    // number of people inside.
+    HOV,
 }
 
 impl LaneType {
@@ -41,6 +44,7 @@ impl LaneType {
             LaneType::Driving => true,
             LaneType::Biking => true,
             LaneType::Bus => true,
+            LaneType::HOV => true,
             LaneType::Parking => false,
             LaneType::Sidewalk => false,
             LaneType::SharedLeftTurn => false,
@@ -53,6 +57,7 @@ impl LaneType {
             LaneType::Driving => true,
             LaneType::Biking => true,
             LaneType::Bus => true,
+            LaneType::HOV => true,
             LaneType::Parking => false,
             LaneType::Sidewalk => true,
             LaneType::SharedLeftTurn => false,
@@ -65,6 +70,7 @@ impl LaneType {
             LaneType::Driving => "a general-purpose driving lane",
             LaneType::Biking => "a protected bike lane",
             LaneType::Bus => "a bus-only lane",
+            LaneType::HOV => "a carpool (HOV) lane",
             LaneType::Parking => "an on-street parking lane",
             LaneType::Sidewalk => "a sidewalk",
             LaneType::SharedLeftTurn => "a shared left-turn lane",
@@ -77,6 +83,7 @@ impl LaneType {
             LaneType::Driving => "driving lane",
             LaneType::Biking => "bike lane",
             LaneType::Bus => "bus lane",
+            LaneType::HOV => "HOV lane",
             LaneType::Parking => "parking lane",
             LaneType::Sidewalk => "sidewalk",
             LaneType::SharedLeftTurn => "left-turn lane",
@@ -189,6 +196,10 @@ impl Lane {
         self.lane_type == LaneType::Bus
     }
 
+    pub fn is_hov(&self) -> bool {
+        self.lane_type == LaneType::HOV
+    }
+
     pub fn is_sidewalk(&self) -> bool {
         self.lane_type == LaneType::Sidewalk
     }
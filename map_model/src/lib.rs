@@ -3,6 +3,7 @@ mod building;
 mod bus_stop;
 mod city;
 pub mod connectivity;
+mod curb;
 mod edits;
 mod intersection;
 mod lane;
@@ -11,32 +12,43 @@ mod map;
 pub mod osm;
 mod parking_lot;
 mod pathfind;
+mod qa;
 pub mod raw;
 mod road;
+mod spatial_index;
 mod stop_signs;
 mod traffic_signals;
 mod traversable;
 mod turn;
+mod walking_distances;
 
 pub use crate::area::{Area, AreaID, AreaType};
 pub use crate::building::{Building, BuildingID, FrontPath, OffstreetParking};
-pub use crate::bus_stop::{BusRoute, BusRouteID, BusStop, BusStopID};
+pub use crate::bus_stop::{BusRoute, BusRouteID, BusStop, BusStopAmenities, BusStopID};
 pub use crate::city::City;
+pub use crate::curb::{CurbRegulation, CurbRule, CurbUse};
 pub use crate::edits::{
-    EditCmd, EditEffects, EditIntersection, MapEdits, OriginalLane, PermanentMapEdits,
+    can_change_bus_route, can_change_lane_type, EditBusRoute, EditCmd, EditEffects,
+    EditIntersection, MapEdits, MapEditsBuilder, OriginalLane, PermanentMapEdits,
 };
 pub use crate::intersection::{Intersection, IntersectionID, IntersectionType};
 pub use crate::lane::{Lane, LaneID, LaneType, PARKING_LOT_SPOT_LENGTH, PARKING_SPOT_LENGTH};
 pub use crate::make::initial::lane_specs::RoadSpec;
-pub use crate::map::Map;
+pub use crate::make::intersection_control::ControlChoice;
+pub use crate::map::{Map, RawToMapOptions};
 pub use crate::parking_lot::{ParkingLot, ParkingLotID};
 pub use crate::pathfind::uber_turns::{IntersectionCluster, UberTurn, UberTurnGroup};
-pub use crate::pathfind::{Path, PathConstraints, PathRequest, PathStep};
-pub use crate::road::{DirectedRoadID, Road, RoadID};
+pub use crate::pathfind::{Itinerary, Leg, LegMode, Path, PathConstraints, PathRequest, PathStep};
+pub use crate::qa::{QaFix, QaIssue, QaProblem, QaReport};
+pub use crate::road::{
+    AccessRestrictions, DirectedRoadID, Road, RoadID, RoadImportance, TruckAccess,
+};
+pub use crate::spatial_index::ObjectID;
 pub use crate::stop_signs::{ControlStopSign, RoadWithStopSign};
 pub use crate::traffic_signals::{ControlTrafficSignal, Phase};
 pub use crate::traversable::{Position, Traversable};
 pub use crate::turn::{Turn, TurnGroup, TurnGroupID, TurnID, TurnPriority, TurnType};
+pub use crate::walking_distances::WalkingDistances;
 use abstutil::Cloneable;
 use geom::Distance;
 
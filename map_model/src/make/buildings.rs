@@ -5,7 +5,7 @@ use crate::{
     ParkingLotID, Position, NORMAL_LANE_THICKNESS, PARKING_LOT_SPOT_LENGTH,
 };
 use abstutil::Timer;
-use geom::{Angle, Distance, HashablePt2D, Line, PolyLine, Polygon, Pt2D, Ring};
+use geom::{Angle, Distance, Line, PolyLine, Polygon, Pt2D, QuantizedPt2D, Ring, EPSILON_DIST};
 use std::collections::{BTreeMap, HashSet};
 
 pub fn make_all_buildings(
@@ -14,12 +14,12 @@ pub fn make_all_buildings(
     timer: &mut Timer,
 ) -> Vec<Building> {
     timer.start("convert buildings");
-    let mut center_per_bldg: BTreeMap<OriginalBuilding, HashablePt2D> = BTreeMap::new();
-    let mut query: HashSet<HashablePt2D> = HashSet::new();
+    let mut center_per_bldg: BTreeMap<OriginalBuilding, QuantizedPt2D> = BTreeMap::new();
+    let mut query: HashSet<QuantizedPt2D> = HashSet::new();
     timer.start_iter("get building center points", input.len());
     for (id, b) in input {
         timer.next();
-        let center = b.polygon.center().to_hashable();
+        let center = b.polygon.center().quantize(EPSILON_DIST);
         center_per_bldg.insert(*id, center);
         query.insert(center);
     }
@@ -57,11 +57,13 @@ pub fn make_all_buildings(
                 address: get_address(&b.osm_tags, sidewalk_pos.lane(), map),
                 name: b.osm_tags.get(osm::NAME).cloned(),
                 osm_way_id: orig_id.osm_way_id,
+                osm_tags: b.osm_tags.clone(),
                 front_path: FrontPath {
                     sidewalk: *sidewalk_pos,
                     line: sidewalk_line.clone(),
                 },
                 amenities: b.amenities.clone(),
+                height: b.height,
                 parking: None,
                 label_center: b.polygon.polylabel(),
             };
@@ -118,10 +120,10 @@ pub fn make_all_parking_lots(
     timer: &mut Timer,
 ) -> Vec<ParkingLot> {
     timer.start("convert parking lots");
-    let mut center_per_lot: Vec<HashablePt2D> = Vec::new();
-    let mut query: HashSet<HashablePt2D> = HashSet::new();
+    let mut center_per_lot: Vec<QuantizedPt2D> = Vec::new();
+    let mut query: HashSet<QuantizedPt2D> = HashSet::new();
     for lot in input {
-        let center = lot.polygon.center().to_hashable();
+        let center = lot.polygon.center().quantize(EPSILON_DIST);
         center_per_lot.push(center);
         query.insert(center);
     }
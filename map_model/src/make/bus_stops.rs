@@ -1,27 +1,29 @@
 use crate::make::sidewalk_finder::find_sidewalk_points;
+use crate::raw::RawBusStop;
 use crate::{
     BusRoute, BusRouteID, BusStop, BusStopID, LaneID, LaneType, Map, PathConstraints, PathRequest,
     Position,
 };
 use abstutil::{MultiMap, Timer};
-use geom::{Bounds, Distance, GPSBounds, HashablePt2D, Pt2D};
+use geom::{Bounds, Distance, FindClosest, GPSBounds, Pt2D, QuantizedPt2D, EPSILON_DIST};
 use gtfs;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub fn make_bus_stops(
     map: &Map,
     bus_routes: &Vec<gtfs::Route>,
+    osm_stops: &Vec<RawBusStop>,
     gps_bounds: &GPSBounds,
     bounds: &Bounds,
     timer: &mut Timer,
 ) -> (BTreeMap<BusStopID, BusStop>, Vec<BusRoute>) {
     timer.start("make bus stops");
-    let mut bus_stop_pts: HashSet<HashablePt2D> = HashSet::new();
-    let mut route_lookups: HashMap<String, Vec<HashablePt2D>> = HashMap::new();
+    let mut bus_stop_pts: HashSet<QuantizedPt2D> = HashSet::new();
+    let mut route_lookups: HashMap<String, Vec<QuantizedPt2D>> = HashMap::new();
     for route in bus_routes {
         for gps in &route.stops {
             if let Some(pt) = Pt2D::from_gps(*gps, gps_bounds) {
-                let hash_pt = pt.to_hashable();
+                let hash_pt = pt.quantize(EPSILON_DIST);
                 bus_stop_pts.insert(hash_pt);
                 route_lookups
                     .entry(route.name.clone())
@@ -31,7 +33,7 @@ pub fn make_bus_stops(
         }
     }
 
-    let mut stops_per_sidewalk: MultiMap<LaneID, (Distance, HashablePt2D)> = MultiMap::new();
+    let mut stops_per_sidewalk: MultiMap<LaneID, (Distance, QuantizedPt2D)> = MultiMap::new();
     for (pt, pos) in find_sidewalk_points(
         bounds,
         bus_stop_pts,
@@ -43,7 +45,13 @@ pub fn make_bus_stops(
     {
         stops_per_sidewalk.insert(pos.lane(), (pos.dist_along(), pt));
     }
-    let mut point_to_stop_id: HashMap<HashablePt2D, BusStopID> = HashMap::new();
+    // GTFS and OSM don't agree exactly on stop positions
+    let mut closest_osm_stop: FindClosest<usize> = FindClosest::new(bounds);
+    for (idx, stop) in osm_stops.iter().enumerate() {
+        closest_osm_stop.add(idx, &vec![stop.pt]);
+    }
+
+    let mut point_to_stop_id: HashMap<QuantizedPt2D, BusStopID> = HashMap::new();
     let mut bus_stops: BTreeMap<BusStopID, BusStop> = BTreeMap::new();
 
     for (sidewalk_id, dists_set) in stops_per_sidewalk.consume().into_iter() {
@@ -51,7 +59,7 @@ pub fn make_bus_stops(
         if let Ok(driving_lane) =
             road.find_closest_lane(sidewalk_id, vec![LaneType::Driving, LaneType::Bus])
         {
-            let mut dists: Vec<(Distance, HashablePt2D)> = dists_set.into_iter().collect();
+            let mut dists: Vec<(Distance, QuantizedPt2D)> = dists_set.into_iter().collect();
             dists.sort_by_key(|(dist, _)| *dist);
             for (idx, (dist_along, orig_pt)) in dists.into_iter().enumerate() {
                 let stop_id = BusStopID {
@@ -67,6 +75,10 @@ pub fn make_bus_stops(
                         id: stop_id,
                         sidewalk_pos,
                         driving_pos,
+                        amenities: closest_osm_stop
+                            .closest_pt(orig_pt.to_pt2d(), Distance::meters(30.0))
+                            .map(|(idx, _)| osm_stops[idx].amenities.clone())
+                            .unwrap_or_default(),
                     },
                 );
             }
@@ -93,6 +105,7 @@ pub fn make_bus_stops(
             id,
             name: route_name.to_string(),
             stops,
+            headway: None,
         });
     }
     timer.stop("make bus stops");
@@ -0,0 +1,239 @@
+use crate::{Intersection, Map, ObjectID, Road, RoadImportance};
+use abstutil::Timer;
+use geom::{Distance, Polygon};
+
+// Roads shorter than this take on the importance of what they connect, so the major network
+// doesn't have gaps in it when the minor roads are hidden.
+const SHORT_CONNECTOR: Distance = Distance::const_meters(30.0);
+// Buildings further apart than this along the sidewalk start a new block.
+const MAX_GAP_IN_BLOCK: Distance = Distance::const_meters(30.0);
+// Long blocks are split up, so their convex hull doesn't cut across a curving road.
+const MAX_BLOCK_LENGTH: Distance = Distance::const_meters(150.0);
+// Slivers left over from clipping blocks against roads aren't worth drawing
+const MIN_BLOCK_AREA: f64 = 1.0;
+
+pub fn classify_roads(roads: &mut Vec<Road>, intersections: &Vec<Intersection>, timer: &mut Timer) {
+    timer.start("classify road importance");
+    for r in roads.iter_mut() {
+        let rank = r.get_rank();
+        r.importance = if rank >= 16 {
+            RoadImportance::Highway
+        } else if rank >= 12 {
+            RoadImportance::Arterial
+        } else if rank >= 6 {
+            RoadImportance::Collector
+        } else {
+            RoadImportance::Local
+        };
+    }
+
+    // Look at the original classification, so promotions don't cascade down a chain of short
+    // roads.
+    let orig: Vec<RoadImportance> = roads.iter().map(|r| r.importance).collect();
+    for r in roads.iter_mut() {
+        if r.center_pts.length() >= SHORT_CONNECTOR {
+            continue;
+        }
+        let id = r.id;
+        let best_at = |i: &Intersection| {
+            i.roads
+                .iter()
+                .filter(|other| **other != id)
+                .map(|other| orig[other.0])
+                .max()
+        };
+        if let (Some(src), Some(dst)) = (
+            best_at(&intersections[r.src_i.0]),
+            best_at(&intersections[r.dst_i.0]),
+        ) {
+            r.importance = r.importance.max(src.min(dst));
+        }
+    }
+    timer.stop("classify road importance");
+}
+
+// Groups buildings next to each other along the same side of a road into one polygon. The hull of
+// a block can reach over a road at corners and curves, so roads and intersections are cut out of
+// it; that might split one block into a few.
+pub fn merge_buildings_into_blocks(map: &Map, timer: &mut Timer) -> Vec<Polygon> {
+    let mut hulls = Vec::new();
+    timer.start_iter("merge buildings into blocks", map.all_lanes().len());
+    for l in map.all_lanes() {
+        timer.next();
+        // Already sorted by distance along the sidewalk
+        let mut current: Vec<Polygon> = Vec::new();
+        let mut start = Distance::ZERO;
+        let mut last = Distance::ZERO;
+        for b in &l.building_paths {
+            let bldg = map.get_b(*b);
+            let dist = bldg.front_path.sidewalk.dist_along();
+            if !current.is_empty()
+                && (dist - last > MAX_GAP_IN_BLOCK || dist - start > MAX_BLOCK_LENGTH)
+            {
+                hulls.push(Polygon::convex_hull(std::mem::take(&mut current)));
+            }
+            if current.is_empty() {
+                start = dist;
+            }
+            current.push(bldg.polygon.clone());
+            last = dist;
+        }
+        if !current.is_empty() {
+            hulls.push(Polygon::convex_hull(current));
+        }
+    }
+
+    let mut blocks = Vec::new();
+    timer.start_iter("clip blocks against roads", hulls.len());
+    for hull in hulls {
+        timer.next();
+        blocks.extend(clip_block(hull, map, timer));
+    }
+    blocks
+}
+
+fn clip_block(hull: Polygon, map: &Map, timer: &mut Timer) -> Vec<Polygon> {
+    let bounds = hull.get_bounds();
+    let mut pieces = vec![hull];
+    for id in map.objects_in_bounds(bounds) {
+        let cut = match id {
+            ObjectID::Road(r) => road_outline(map.get_r(r), map, timer),
+            ObjectID::Intersection(i) => Some(map.get_i(i).polygon.clone()),
+            _ => None,
+        };
+        if let Some(cut) = cut {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|p| p.difference(&cut))
+                .collect();
+        }
+    }
+    pieces.retain(|p| p.area() >= MIN_BLOCK_AREA);
+    pieces
+}
+
+// get_thick_polygon is built from triangles, but boolean operations need the outer ring.
+fn road_outline(r: &Road, map: &Map, timer: &mut Timer) -> Option<Polygon> {
+    let (pl, width) = r.get_thick_polyline(map).get(timer);
+    let mut pts = pl.shift_right(width / 2.0).get(timer).into_points();
+    pts.extend(
+        pl.shift_left(width / 2.0)
+            .get(timer)
+            .reversed()
+            .into_points(),
+    );
+    Polygon::try_new(&pts).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::{OriginalIntersection, RawMap};
+    use crate::{osm, IntersectionType, RoadID};
+
+    // Two stop signs joined by one road, with roads out to the borders
+    fn grid<F: Fn(&mut RawMap)>(edit: F) -> Map {
+        let mut raw = RawMap::synthetic_grid("generalize", 2, 1);
+        edit(&mut raw);
+        Map::synthetic(raw, false)
+    }
+
+    fn inner_road(map: &Map) -> RoadID {
+        map.all_roads()
+            .iter()
+            .find(|r| !map.get_i(r.src_i).is_border() && !map.get_i(r.dst_i).is_border())
+            .unwrap()
+            .id
+    }
+
+    // Everything but the inner road becomes a primary road.
+    fn promote_outer_roads(raw: &mut RawMap) {
+        let is_border = |i| raw.intersections[i].intersection_type == IntersectionType::Border;
+        let inner = raw
+            .roads
+            .keys()
+            .find(|id| !is_border(&id.i1) && !is_border(&id.i2))
+            .cloned()
+            .unwrap();
+        for (id, r) in raw.roads.iter_mut() {
+            if *id != inner {
+                r.osm_tags
+                    .insert(osm::HIGHWAY.to_string(), "primary".to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn classify_by_rank() {
+        let map = grid(|_| {});
+        assert!(map
+            .all_roads()
+            .iter()
+            .all(|r| r.importance == RoadImportance::Local));
+
+        // The inner road is long enough to stay a gap in the major network.
+        let map = grid(promote_outer_roads);
+        let inner = inner_road(&map);
+        for r in map.all_roads() {
+            if r.id == inner {
+                assert_eq!(r.importance, RoadImportance::Local);
+            } else {
+                assert_eq!(r.importance, RoadImportance::Arterial);
+            }
+        }
+    }
+
+    #[test]
+    fn short_connectors_are_promoted() {
+        let map = grid(|raw| {
+            promote_outer_roads(raw);
+            // Pull the second stop sign much closer to the first
+            let moved = OriginalIntersection { osm_node_id: 2 };
+            let pt = raw.intersections[&OriginalIntersection { osm_node_id: 1 }]
+                .point
+                .offset(40.0, 0.0);
+            raw.intersections.get_mut(&moved).unwrap().point = pt;
+            for (id, r) in raw.roads.iter_mut() {
+                if id.i1 == moved {
+                    r.center_points[0] = pt;
+                }
+                if id.i2 == moved {
+                    *r.center_points.last_mut().unwrap() = pt;
+                }
+            }
+        });
+        let inner = map.get_r(inner_road(&map));
+        assert!(inner.center_pts.length() < SHORT_CONNECTOR);
+        assert_eq!(inner.get_rank(), 5);
+        assert_eq!(inner.importance, RoadImportance::Arterial);
+    }
+
+    #[test]
+    fn blocks_dont_cover_roads() {
+        let map = grid(|_| {});
+        assert!(!map.all_building_blocks().is_empty());
+        let overlap = |block: &Polygon, r: &Road| -> f64 {
+            let outline = road_outline(r, &map, &mut Timer::throwaway()).unwrap();
+            block.intersection(&outline).iter().map(|p| p.area()).sum()
+        };
+        for block in map.all_building_blocks() {
+            for r in map.all_roads() {
+                assert!(overlap(block, r) < MIN_BLOCK_AREA);
+            }
+        }
+
+        // A hull reaching straight across the road gets cut into the two sides
+        let r = map.get_r(inner_road(&map));
+        let hull = Polygon::rectangle_centered(
+            r.center_pts.middle(),
+            Distance::meters(40.0),
+            Distance::meters(40.0),
+        );
+        let pieces = clip_block(hull.clone(), &map, &mut Timer::throwaway());
+        assert_eq!(pieces.len(), 2);
+        for p in &pieces {
+            assert!(overlap(p, r) < MIN_BLOCK_AREA);
+            assert!(p.area() < hull.area() / 2.0);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::make::initial::{Intersection, Road};
-use crate::raw::{DrivingSide, OriginalIntersection, OriginalRoad};
+use crate::raw::{DrivingSide, GeometryOverride, OriginalIntersection, OriginalRoad};
 use abstutil::{wraparound_get, Timer};
-use geom::{Distance, Line, PolyLine, Polygon, Pt2D};
+use geom::{Distance, GPSBounds, Line, PolyLine, Polygon, Pt2D};
 use std::collections::BTreeMap;
 
 const DEGENERATE_INTERSECTION_HALF_LENGTH: Distance = Distance::const_meters(2.5);
@@ -53,7 +53,7 @@ pub fn intersection_polygon(
 
     // Find the average of all road endpoints at the intersection. This is usually just a single
     // point, except for merged intersections.
-    road_endpts.sort_by_key(|pt| pt.to_hashable());
+    road_endpts.sort_by_key(|pt| pt.quantize(geom::EPSILON_DIST));
     road_endpts.dedup();
     let intersection_center = Pt2D::center(&road_endpts);
 
@@ -70,6 +70,61 @@ pub fn intersection_polygon(
     }
 }
 
+// Has to happen right after intersection_polygon for the same intersection, since the other end of
+// each road might get trimmed later.
+pub fn apply_geometry_override(
+    i: &mut Intersection,
+    fix: &GeometryOverride,
+    roads: &mut BTreeMap<OriginalRoad, Road>,
+    gps_bounds: &GPSBounds,
+    timer: &mut Timer,
+) {
+    for (id, trim) in &fix.trim_roads {
+        let r = match roads.get_mut(id) {
+            Some(r) if r.src_i == i.id || r.dst_i == i.id => r,
+            _ => {
+                timer.warn(format!(
+                    "Geometry override for {} mentions {}, which doesn't touch it",
+                    i.id, id
+                ));
+                continue;
+            }
+        };
+
+        // pl ends at the intersection.
+        let pl = if r.dst_i == i.id {
+            r.trimmed_center_pts.clone()
+        } else {
+            r.trimmed_center_pts.reversed()
+        };
+        let pl = if *trim > Distance::ZERO {
+            if pl.length() < *trim + 3.0 * geom::EPSILON_DIST {
+                timer.warn(format!("Can't trim {} back {} from {}", id, trim, i.id));
+                continue;
+            }
+            pl.exact_slice(Distance::ZERO, pl.length() - *trim)
+        } else if *trim < Distance::ZERO {
+            let last_line = pl.last_line();
+            let mut pts = pl.points().clone();
+            *pts.last_mut().unwrap() = last_line.pt2().project_away(-*trim, last_line.angle());
+            PolyLine::new(pts)
+        } else {
+            pl
+        };
+        r.trimmed_center_pts = if r.dst_i == i.id { pl } else { pl.reversed() };
+    }
+
+    if let Some(ref pts) = fix.polygon {
+        if pts.len() >= 3 {
+            i.polygon = close_off_polygon(
+                pts.iter()
+                    .map(|gps| Pt2D::forcibly_from_gps(*gps, gps_bounds))
+                    .collect(),
+            );
+        }
+    }
+}
+
 fn generalized_trim_back(
     driving_side: DrivingSide,
     roads: &mut BTreeMap<OriginalRoad, Road>,
@@ -323,7 +378,7 @@ fn generalized_trim_back(
     // around its center.
     let mut deduped = main_result.clone();
     deduped.pop();
-    deduped.sort_by_key(|pt| pt.to_hashable());
+    deduped.sort_by_key(|pt| pt.quantize(geom::EPSILON_DIST));
     deduped = Pt2D::approx_dedupe(deduped, Distance::meters(0.1));
     let center = Pt2D::center(&deduped);
     deduped.sort_by_key(|pt| pt.angle_to(center).normalized_degrees() as i64);
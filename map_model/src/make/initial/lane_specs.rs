@@ -209,6 +209,7 @@ impl RoadSpec {
             LaneType::Bus => 'u',
             LaneType::SharedLeftTurn => 'l',
             LaneType::Construction => 'c',
+            LaneType::HOV => 'h',
         }
     }
 
@@ -221,6 +222,7 @@ impl RoadSpec {
             'u' => Some(LaneType::Bus),
             'l' => Some(LaneType::SharedLeftTurn),
             'c' => Some(LaneType::Construction),
+            'h' => Some(LaneType::HOV),
             _ => None,
         }
     }
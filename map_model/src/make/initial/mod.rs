@@ -1,8 +1,10 @@
 mod geometry;
 pub mod lane_specs;
 
-pub use self::geometry::intersection_polygon;
-use crate::raw::{OriginalIntersection, OriginalRoad, RawMap, RawRoad};
+pub use self::geometry::{apply_geometry_override, intersection_polygon};
+use crate::raw::{
+    IntersectionGeometryOverrides, OriginalIntersection, OriginalRoad, RawMap, RawRoad,
+};
 use crate::{IntersectionType, LaneType, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS};
 use abstutil::Timer;
 use geom::{Bounds, Distance, PolyLine, Pt2D};
@@ -69,7 +71,12 @@ pub struct Intersection {
 }
 
 impl InitialMap {
-    pub fn new(raw: &RawMap, bounds: &Bounds, timer: &mut Timer) -> InitialMap {
+    pub fn new(
+        raw: &RawMap,
+        bounds: &Bounds,
+        overrides: &IntersectionGeometryOverrides,
+        timer: &mut Timer,
+    ) -> InitialMap {
         let mut m = InitialMap {
             roads: BTreeMap::new(),
             intersections: BTreeMap::new(),
@@ -105,6 +112,9 @@ impl InitialMap {
             timer.next();
 
             i.polygon = intersection_polygon(raw.driving_side, i, &mut m.roads, timer).0;
+            if let Some(fix) = overrides.intersections.get(&i.id) {
+                apply_geometry_override(i, fix, &mut m.roads, &raw.gps_bounds, timer);
+            }
         }
 
         m
@@ -0,0 +1,132 @@
+use crate::{osm, IntersectionID, IntersectionType, Map, RoadID};
+use abstutil::{Counter, Timer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// OSM usually tags traffic signals, but almost never says which intersections have stop signs or
+// no control at all. Guess based on what kinds of roads meet.
+
+// The ranks from Road::get_rank
+const SECONDARY: usize = 12;
+const RESIDENTIAL: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ControlChoice {
+    TrafficSignal,
+    // Everything except the most important roads stops
+    MinorRoadsStop,
+    AllWayStop,
+    // Nobody stops; everyone yields
+    Uncontrolled,
+}
+
+pub struct ControlDecision {
+    pub choice: ControlChoice,
+    pub reason: String,
+}
+
+impl fmt::Display for ControlChoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlChoice::TrafficSignal => write!(f, "traffic signal"),
+            ControlChoice::MinorRoadsStop => write!(f, "stop signs on minor roads"),
+            ControlChoice::AllWayStop => write!(f, "all-way stop"),
+            ControlChoice::Uncontrolled => write!(f, "uncontrolled"),
+        }
+    }
+}
+
+// Doesn't look at the current intersection type at all.
+pub fn decide(map: &Map, i: IntersectionID) -> ControlDecision {
+    let mut ranked: Vec<(usize, RoadID)> = map
+        .get_i(i)
+        .roads
+        .iter()
+        .filter(|r| {
+            map.get_r(**r)
+                .incoming_lanes(i)
+                .iter()
+                .any(|(_, lt)| lt.is_for_moving_vehicles())
+        })
+        .map(|r| (map.get_r(*r).get_rank(), *r))
+        .collect();
+    // Highest rank first
+    ranked.sort_by(|a, b| b.cmp(a));
+    let hwy = |r: RoadID| {
+        map.get_r(r)
+            .osm_tags
+            .get(osm::HIGHWAY)
+            .cloned()
+            .unwrap_or_else(|| "unknown road".to_string())
+    };
+
+    if ranked.len() <= 2 {
+        return ControlDecision {
+            choice: ControlChoice::Uncontrolled,
+            reason: format!("only {} approaches", ranked.len()),
+        };
+    }
+    let (top, second) = (ranked[0], ranked[1]);
+    if top.0 >= SECONDARY && second.0 >= SECONDARY {
+        return ControlDecision {
+            choice: ControlChoice::TrafficSignal,
+            reason: format!("{} meets {}", hwy(top.1), hwy(second.1)),
+        };
+    }
+    if top.0 <= RESIDENTIAL && ranked.len() == 3 {
+        return ControlDecision {
+            choice: ControlChoice::Uncontrolled,
+            reason: format!("a T between {} streets", hwy(top.1)),
+        };
+    }
+    if ranked.iter().all(|(rank, _)| *rank == top.0) {
+        return ControlDecision {
+            choice: ControlChoice::AllWayStop,
+            reason: format!("{} approaches, all {}", ranked.len(), hwy(top.1)),
+        };
+    }
+    ControlDecision {
+        choice: ControlChoice::MinorRoadsStop,
+        reason: format!(
+            "{} has priority over {}",
+            hwy(top.1),
+            hwy(ranked.last().unwrap().1)
+        ),
+    }
+}
+
+// Returns stop signs that should be traffic signals instead. Signals tagged in OSM are never
+// removed. Summarizes every decision for the import report.
+pub fn find_new_signals(map: &Map, timer: &mut Timer) -> Vec<IntersectionID> {
+    let mut new_signals = Vec::new();
+    let mut tagged_signals = 0;
+    let mut choices = Counter::new();
+    for i in map.all_intersections() {
+        match i.intersection_type {
+            IntersectionType::TrafficSignal => {
+                tagged_signals += 1;
+            }
+            IntersectionType::StopSign => {
+                let decision = decide(map, i.id);
+                if decision.choice == ControlChoice::TrafficSignal {
+                    new_signals.push(i.id);
+                    timer.note(format!(
+                        "{} wasn't tagged as a signal, but making it one: {}",
+                        i.id, decision.reason
+                    ));
+                }
+                choices.inc(decision.choice);
+            }
+            IntersectionType::Border | IntersectionType::Construction => {}
+        }
+    }
+
+    timer.note(format!(
+        "Intersection control: {} signals from OSM",
+        tagged_signals
+    ));
+    for (choice, cnt) in choices.consume() {
+        timer.note(format!("Intersection control: {} guessed {}", cnt, choice));
+    }
+    new_signals
+}
@@ -1,7 +1,9 @@
 pub mod bridges;
 pub mod buildings;
 pub mod bus_stops;
+pub mod generalize;
 pub mod initial;
+pub mod intersection_control;
 pub mod remove_disconnected;
 pub mod sidewalk_finder;
 pub mod traffic_signals;
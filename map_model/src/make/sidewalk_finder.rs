@@ -1,17 +1,17 @@
 use crate::{Lane, LaneID, Position};
 use abstutil::Timer;
-use geom::{Bounds, Distance, FindClosest, HashablePt2D};
+use geom::{Bounds, Distance, FindClosest, QuantizedPt2D};
 use std::collections::{HashMap, HashSet};
 
 // If the result doesn't contain a requested point, then there was no matching sidewalk close
 // enough.
 pub fn find_sidewalk_points(
     bounds: &Bounds,
-    pts: HashSet<HashablePt2D>,
+    pts: HashSet<QuantizedPt2D>,
     lanes: &Vec<Lane>,
     max_dist_away: Distance,
     timer: &mut Timer,
-) -> HashMap<HashablePt2D, Position> {
+) -> HashMap<QuantizedPt2D, Position> {
     if pts.is_empty() {
         return HashMap::new();
     }
@@ -27,7 +27,7 @@ pub fn find_sidewalk_points(
 
     // For each point, find the closest point to any sidewalk, using the quadtree to prune the
     // search.
-    let mut results: HashMap<HashablePt2D, Position> = HashMap::new();
+    let mut results: HashMap<QuantizedPt2D, Position> = HashMap::new();
     timer.start_iter("find closest sidewalk point", pts.len());
     for query_pt in pts {
         timer.next();
@@ -4,7 +4,7 @@ use crate::{
 };
 use abstutil::Timer;
 use geom::Duration;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub fn get_possible_policies(
     map: &Map,
@@ -60,6 +60,35 @@ pub fn get_possible_policies(
     results
 }
 
+// Standard patterns that someone editing a signal might want to start from. Only the ones that
+// make sense for this intersection's shape are returned.
+pub fn get_presets(map: &Map, id: IntersectionID) -> Vec<(String, ControlTrafficSignal)> {
+    let mut results = Vec::new();
+    if let Some(ts) = four_way_two_phase(map, id)
+        .or_else(|| three_way(map, id))
+        .or_else(|| degenerate(map, id))
+    {
+        results.push(("two-phase".to_string(), ts));
+    }
+    if let Some(ts) = four_way_four_phase(map, id) {
+        results.push(("protected lefts".to_string(), ts));
+    }
+    if let Some(ts) = split_phasing(map, id) {
+        results.push(("split phasing".to_string(), ts));
+    }
+    // Layer the scramble on top of the simplest pattern
+    if let Some(mut ts) = results.first().map(|(_, ts)| ts.clone()) {
+        let has_crosswalks = ts
+            .turn_groups
+            .values()
+            .any(|g| g.turn_type == TurnType::Crosswalk);
+        if has_crosswalks && ts.convert_to_ped_scramble() {
+            results.push(("all-walk scramble".to_string(), ts));
+        }
+    }
+    results
+}
+
 fn greedy_assignment(map: &Map, intersection: IntersectionID) -> ControlTrafficSignal {
     let turn_groups = TurnGroup::for_i(intersection, map);
 
@@ -376,6 +405,69 @@ fn phase_per_road(map: &Map, i: IntersectionID) -> Option<ControlTrafficSignal>
     ts.validate().ok()
 }
 
+// Each road gets a phase to itself, with all of its movements protected. Crosswalks go along with
+// whichever phases they don't conflict with.
+fn split_phasing(map: &Map, i: IntersectionID) -> Option<ControlTrafficSignal> {
+    let turn_groups = TurnGroup::for_i(i, map);
+
+    let mut phases = Vec::new();
+    for r in map
+        .get_i(i)
+        .get_roads_sorted_by_incoming_angle(map.all_roads())
+    {
+        let mut phase = Phase::new();
+        for group in turn_groups.values() {
+            if group.turn_type == TurnType::Crosswalk || group.id.from.id != r {
+                continue;
+            }
+            if phase.could_be_protected(group.id, &turn_groups) {
+                phase.protected_groups.insert(group.id);
+            } else {
+                phase.yield_groups.insert(group.id);
+            }
+        }
+        // Might have a one-way outgoing road. Skip it.
+        if !phase.protected_groups.is_empty() || !phase.yield_groups.is_empty() {
+            phases.push(phase);
+        }
+    }
+    if phases.len() < 2 {
+        return None;
+    }
+
+    let mut missing_crosswalks = false;
+    for group in turn_groups.values() {
+        if group.turn_type != TurnType::Crosswalk {
+            continue;
+        }
+        let mut placed = false;
+        for phase in phases.iter_mut() {
+            if phase.could_be_protected(group.id, &turn_groups) {
+                phase.protected_groups.insert(group.id);
+                placed = true;
+            }
+        }
+        missing_crosswalks |= !placed;
+    }
+    if missing_crosswalks {
+        let mut all_walk = Phase::new();
+        for group in turn_groups.values() {
+            if group.turn_type == TurnType::Crosswalk {
+                all_walk.protected_groups.insert(group.id);
+            }
+        }
+        phases.push(all_walk);
+    }
+
+    let ts = ControlTrafficSignal {
+        id: i,
+        phases,
+        offset: Duration::ZERO,
+        turn_groups,
+    };
+    ts.validate().ok()
+}
+
 // Add all possible protected groups to existing phases.
 fn expand_all_phases(phases: &mut Vec<Phase>, turn_groups: &BTreeMap<TurnGroupID, TurnGroup>) {
     for phase in phases.iter_mut() {
@@ -435,6 +527,8 @@ pub fn brute_force(map: &Map, i: IntersectionID) {
         .into_iter()
         .filter_map(|(id, tg)| if id.crosswalk { None } else { Some(tg) })
         .collect();
+    // Every partition checks the same pairs over and over, so find the conflicts once.
+    let conflicts = TurnGroup::all_conflicts(&turn_groups.iter().collect::<Vec<_>>());
     let indices: Vec<usize> = (0..turn_groups.len()).collect();
     for num_phases in 1..=turn_groups.len() {
         println!(
@@ -443,7 +537,7 @@ pub fn brute_force(map: &Map, i: IntersectionID) {
             num_phases
         );
         for partition in helper(&indices, num_phases) {
-            if okay_partition(turn_groups.iter().collect(), partition) {
+            if okay_partition(&conflicts, partition) {
                 return;
             }
         }
@@ -451,15 +545,17 @@ pub fn brute_force(map: &Map, i: IntersectionID) {
     unreachable!()
 }
 
-fn okay_partition(turn_groups: Vec<&TurnGroup>, partition: Partition) -> bool {
+fn okay_partition(conflicts: &BTreeSet<(usize, usize)>, partition: Partition) -> bool {
     for phase in partition.0 {
-        let mut protected: Vec<&TurnGroup> = Vec::new();
+        let mut protected: Vec<usize> = Vec::new();
         for idx in phase {
-            let tg = turn_groups[idx];
-            if protected.iter().any(|other| tg.conflicts_with(other)) {
+            if protected
+                .iter()
+                .any(|other| conflicts.contains(&(idx.min(*other), idx.max(*other))))
+            {
                 return false;
             }
-            protected.push(tg);
+            protected.push(idx);
         }
     }
     println!("found one that works! :O");
@@ -3,7 +3,7 @@ use crate::{
     Intersection, IntersectionID, Lane, LaneID, LaneType, Road, RoadID, Turn, TurnID, TurnType,
 };
 use abstutil::{wraparound_get, Timer, Warn};
-use geom::{Distance, Line, PolyLine, Pt2D, Ring};
+use geom::{Distance, Line, PolyLine, Pt2D, Ring, EPSILON_DIST};
 use nbez::{Bez3o, BezCurve, Point2d};
 use std::collections::{BTreeSet, HashMap, HashSet};
 
@@ -572,7 +572,9 @@ fn make_shared_sidewalk_corner(
         let mut deduped = pts.clone();
         deduped.dedup();
         if deduped.len() >= 2 {
-            if abstutil::contains_duplicates(&deduped.iter().map(|pt| pt.to_hashable()).collect()) {
+            if abstutil::contains_duplicates(
+                &deduped.iter().map(|pt| pt.quantize(EPSILON_DIST)).collect(),
+            ) {
                 timer.warn(format!(
                     "SharedSidewalkCorner between {} and {} has weird duplicate geometry, so just \
                      doing straight line",
@@ -610,7 +612,12 @@ fn make_shared_sidewalk_corner(
         final_pts.pop();
         final_pts.push(l2.first_pt());
     }
-    if abstutil::contains_duplicates(&final_pts.iter().map(|pt| pt.to_hashable()).collect()) {
+    if abstutil::contains_duplicates(
+        &final_pts
+            .iter()
+            .map(|pt| pt.quantize(EPSILON_DIST))
+            .collect(),
+    ) {
         timer.warn(format!(
             "SharedSidewalkCorner between {} and {} has weird duplicate geometry, so just doing \
              straight line",
@@ -660,7 +667,7 @@ fn filter_lanes(lanes: &Vec<(LaneID, LaneType)>, filter: LaneType) -> Vec<LaneID
         .collect()
 }
 
-fn make_vehicle_turn(
+pub(crate) fn make_vehicle_turn(
     lanes: &Vec<Lane>,
     i: IntersectionID,
     l1: LaneID,
@@ -1,17 +1,45 @@
 use crate::pathfind::Pathfinder;
-use crate::raw::{DrivingSide, OriginalIntersection, OriginalRoad, RawMap};
+use crate::raw::{
+    DrivingSide, GeometryOverride, IntersectionGeometryOverrides, OriginalIntersection,
+    OriginalRoad, RawMap,
+};
+use crate::spatial_index;
 use crate::{
-    connectivity, make, osm, Area, AreaID, Building, BuildingID, BusRoute, BusRouteID, BusStop,
-    BusStopID, ControlStopSign, ControlTrafficSignal, EditCmd, EditEffects, EditIntersection,
-    Intersection, IntersectionID, IntersectionType, Lane, LaneID, LaneType, MapEdits, ParkingLot,
-    ParkingLotID, Path, PathConstraints, PathRequest, Position, Road, RoadID, Turn, TurnGroupID,
+    connectivity, make, osm, AccessRestrictions, Area, AreaID, Building, BuildingID, BusRoute,
+    BusRouteID, BusStop, BusStopID, ControlStopSign, ControlTrafficSignal, CurbRegulation,
+    EditBusRoute, EditCmd, EditEffects, EditIntersection, Intersection, IntersectionID,
+    IntersectionType, Lane, LaneID, LaneType, MapEdits, ObjectID, ParkingLot, ParkingLotID, Path,
+    PathConstraints, PathRequest, Position, Road, RoadID, RoadImportance, Turn, TurnGroupID,
     TurnID, TurnType, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
 };
+use aabb_quadtree::QuadTree;
 use abstutil::{deserialize_btreemap, serialize_btreemap, Error, Timer, Warn};
-use geom::{Angle, Bounds, Distance, GPSBounds, Line, PolyLine, Polygon, Pt2D, Speed};
+use geom::{
+    Angle, Bounds, Distance, Duration, GPSBounds, Line, PolyLine, Polygon, Pt2D, Speed, Time,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 
+// Choices made when turning a RawMap into a Map.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RawToMapOptions {
+    // Skipping the contraction hierarchies saves lots of time, but the map can't be used for
+    // simulation; as soon as anything pathfinds, it'll crash.
+    pub build_ch: bool,
+    // Add the most natural missing turn for every vehicle lane that can't be entered or left.
+    // These turns don't exist in OSM, so this is off by default.
+    pub repair_lanes: bool,
+}
+
+impl Default for RawToMapOptions {
+    fn default() -> RawToMapOptions {
+        RawToMapOptions {
+            build_ch: true,
+            repair_lanes: false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Map {
     roads: Vec<Road>,
@@ -32,14 +60,28 @@ pub struct Map {
     areas: Vec<Area>,
     parking_lots: Vec<ParkingLot>,
     boundary_polygon: Polygon,
+    // Building footprints merged together, for drawing the map zoomed far out
+    building_blocks: Vec<Polygon>,
 
     // Note that border nodes belong in neither!
     stop_signs: BTreeMap<IntersectionID, ControlStopSign>,
     traffic_signals: BTreeMap<IntersectionID, ControlTrafficSignal>,
+    // Only parking lanes with something besides plain parking are here
+    curb_regulations: BTreeMap<LaneID, CurbRegulation>,
+    // Only intersections where edits changed allow_block_the_box_by_default are here
+    block_the_box_overrides: BTreeMap<IntersectionID, bool>,
 
     gps_bounds: GPSBounds,
     bounds: Bounds,
     driving_side: DrivingSide,
+    // The map editor's fixes to intersection geometry that went into this map, and how to build
+    // it again once they change
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    geometry_overrides: BTreeMap<OriginalIntersection, GeometryOverride>,
+    build_opts: RawToMapOptions,
 
     turn_lookup: Vec<TurnID>,
     // TODO Argh, hack, initialization order is hard!
@@ -50,6 +92,9 @@ pub struct Map {
     name: String,
     #[serde(skip_serializing, skip_deserializing)]
     edits: MapEdits,
+    // Not worth storing; it's fast to rebuild after loading.
+    #[serde(skip_serializing, skip_deserializing)]
+    quadtree: Option<QuadTree<ObjectID>>,
 }
 
 impl Map {
@@ -57,7 +102,11 @@ impl Map {
         if path.starts_with(&abstutil::path_all_maps()) {
             match abstutil::maybe_read_binary(path.clone(), timer) {
                 Ok(map) => {
-                    let map: Map = map;
+                    let mut map: Map = map;
+                    if let Some(rebuilt) = map.rebuild_for_new_overrides(timer) {
+                        return rebuilt;
+                    }
+                    map.quadtree = Some(spatial_index::build(&map));
 
                     if false {
                         use abstutil::{prettyprint_usize, serialized_size_bytes};
@@ -111,7 +160,38 @@ impl Map {
             // Synthetic
             abstutil::read_json(path, timer)
         };
-        Map::create_from_raw(raw, true, timer)
+        Map::create_from_raw(raw, &RawToMapOptions::default(), timer)
+    }
+
+    // Intersection geometry overrides are applied while building the map from the RawMap. If
+    // they've been changed in the map editor since this map was built, build it again and save it.
+    fn rebuild_for_new_overrides(&self, timer: &mut Timer) -> Option<Map> {
+        // Only people working on the map have the input files
+        if !abstutil::file_exists(abstutil::path_intersection_geometry(&self.name)) {
+            return None;
+        }
+        let overrides = IntersectionGeometryOverrides::load(&self.name, timer);
+        if overrides.intersections == self.geometry_overrides {
+            return None;
+        }
+        let raw_path = abstutil::path_raw_map(&self.name);
+        if !abstutil::file_exists(raw_path.clone()) {
+            timer.warn(format!(
+                "Intersection geometry overrides for {} changed, but {} is missing, so they won't \
+                 apply until the map is imported again",
+                self.name, raw_path
+            ));
+            return None;
+        }
+
+        timer.note(format!(
+            "Intersection geometry overrides for {} changed, so building the map again",
+            self.name
+        ));
+        let raw: RawMap = abstutil::read_binary(raw_path, timer);
+        let map = Map::create_from_raw(raw, &self.build_opts, timer);
+        map.save();
+        Some(map)
     }
 
     // Just for temporary std::mem::replace tricks.
@@ -131,36 +211,61 @@ impl Map {
                 Pt2D::new(1.0, 0.0),
                 Pt2D::new(1.0, 1.0),
             ]),
+            building_blocks: Vec::new(),
             stop_signs: BTreeMap::new(),
             traffic_signals: BTreeMap::new(),
+            curb_regulations: BTreeMap::new(),
+            block_the_box_overrides: BTreeMap::new(),
             gps_bounds: GPSBounds::new(),
             bounds: Bounds::new(),
             driving_side: DrivingSide::Right,
+            geometry_overrides: BTreeMap::new(),
+            build_opts: RawToMapOptions::default(),
             turn_lookup: Vec::new(),
             pathfinder: None,
             pathfinder_dirty: false,
             city_name: "blank city".to_string(),
             name: "blank".to_string(),
             edits: MapEdits::new(),
+            quadtree: None,
         }
     }
 
-    pub fn create_from_raw(mut raw: RawMap, build_ch: bool, timer: &mut Timer) -> Map {
+    // For tests, which usually start from RawMap::synthetic_grid. Building the contraction
+    // hierarchy is slow, so only ask for it when the test pathfinds.
+    pub fn synthetic(raw: RawMap, build_ch: bool) -> Map {
+        Map::create_from_raw(
+            raw,
+            &RawToMapOptions {
+                build_ch,
+                ..Default::default()
+            },
+            &mut Timer::throwaway(),
+        )
+    }
+
+    pub fn create_from_raw(mut raw: RawMap, opts: &RawToMapOptions, timer: &mut Timer) -> Map {
         // Better to defer this and see RawMaps with more debug info in map_editor
         make::remove_disconnected::remove_disconnected_roads(&mut raw, timer);
 
         timer.start("raw_map to InitialMap");
         let gps_bounds = raw.gps_bounds.clone();
         let bounds = gps_bounds.to_bounds();
-        let initial_map = make::initial::InitialMap::new(&raw, &bounds, timer);
+        let overrides = IntersectionGeometryOverrides::load(&raw.name, timer);
+        let initial_map = make::initial::InitialMap::new(&raw, &bounds, &overrides, timer);
         timer.stop("raw_map to InitialMap");
 
         timer.start("InitialMap to half of Map");
-        let mut m = make_half_map(&raw, initial_map, gps_bounds, bounds, timer);
+        let mut m = make_half_map(&raw, initial_map, gps_bounds, bounds, opts, timer);
+        m.geometry_overrides = overrides.intersections;
         timer.stop("InitialMap to half of Map");
 
         timer.start("finalize Map");
 
+        for i in make::intersection_control::find_new_signals(&m, timer) {
+            m.intersections[i.0].intersection_type = IntersectionType::TrafficSignal;
+        }
+
         // TODO Can probably move this into make_half_map.
         {
             let mut stop_signs: BTreeMap<IntersectionID, ControlStopSign> = BTreeMap::new();
@@ -184,7 +289,7 @@ impl Map {
         // Here's a fun one: we can't set up walking_using_transit yet, because we haven't
         // finalized bus stops and routes. We need the bus graph in place for that. So setup
         // pathfinding in two stages.
-        if build_ch {
+        if opts.build_ch {
             timer.start("setup (most of) Pathfinder");
             m.pathfinder = Some(Pathfinder::new_without_transit(&m, timer));
             timer.stop("setup (most of) Pathfinder");
@@ -193,6 +298,7 @@ impl Map {
                 let (stops, routes) = make::bus_stops::make_bus_stops(
                     &m,
                     &raw.bus_routes,
+                    &raw.bus_stops,
                     &m.gps_bounds,
                     &m.bounds,
                     timer,
@@ -251,6 +357,13 @@ impl Map {
                 timer.warn(format!("- Sidewalk {} is disconnected", l));
             }
         }
+        for problem in connectivity::validate_lanes(&m) {
+            timer.warn(problem.describe());
+        }
+
+        m.quadtree = Some(spatial_index::build(&m));
+        // Needs the quadtree to find the roads each block covers
+        m.building_blocks = make::generalize::merge_buildings_into_blocks(&m, timer);
 
         timer.stop("finalize Map");
         m
@@ -276,6 +389,10 @@ impl Map {
         &self.buildings
     }
 
+    pub fn all_building_blocks(&self) -> &Vec<Polygon> {
+        &self.building_blocks
+    }
+
     pub fn all_areas(&self) -> &Vec<Area> {
         &self.areas
     }
@@ -320,6 +437,18 @@ impl Map {
         self.stop_signs.get(&id)
     }
 
+    pub fn get_curb_regulation(&self, l: LaneID) -> CurbRegulation {
+        self.curb_regulations.get(&l).cloned().unwrap_or_default()
+    }
+
+    // Only matters when the simulation is told not to block the box.
+    pub fn allow_block_the_box(&self, i: IntersectionID) -> bool {
+        self.block_the_box_overrides
+            .get(&i)
+            .cloned()
+            .unwrap_or_else(|| self.get_i(i).allow_block_the_box_by_default())
+    }
+
     pub fn maybe_get_traffic_signal(&self, id: IntersectionID) -> Option<&ControlTrafficSignal> {
         self.traffic_signals.get(&id)
     }
@@ -486,6 +615,20 @@ impl Map {
         &self.gps_bounds
     }
 
+    // Everything whose bounding box overlaps this one, in no particular order. Callers that need
+    // exact hits should check the object's real geometry.
+    pub fn objects_in_bounds(&self, bounds: Bounds) -> impl Iterator<Item = ObjectID> {
+        let results: Vec<ObjectID> = match self.quadtree {
+            Some(ref quadtree) => quadtree
+                .query(bounds.as_bbox())
+                .into_iter()
+                .map(|(id, _, _)| *id)
+                .collect(),
+            None => Vec::new(),
+        };
+        results.into_iter()
+    }
+
     pub fn get_bounds(&self) -> &Bounds {
         &self.bounds
     }
@@ -518,6 +661,14 @@ impl Map {
         self.bus_routes.iter().find(|r| r.name == name)
     }
 
+    pub fn get_br_edit(&self, route: BusRouteID) -> EditBusRoute {
+        let r = self.get_br(route);
+        EditBusRoute {
+            stops: r.stops.clone(),
+            headway: r.headway,
+        }
+    }
+
     pub fn get_routes_serving_stop(&self, stop: BusStopID) -> Vec<&BusRoute> {
         let mut routes = Vec::new();
         for r in &self.bus_routes {
@@ -675,6 +826,27 @@ impl Map {
         self.pathfinder.as_ref().unwrap().pathfind(req, self)
     }
 
+    pub fn pathfind_avoiding_roads(
+        &self,
+        req: PathRequest,
+        avoid: &BTreeSet<RoadID>,
+    ) -> Option<Path> {
+        assert!(!self.pathfinder_dirty);
+        self.pathfinder
+            .as_ref()
+            .unwrap()
+            .pathfind_avoiding_roads(req, avoid, self)
+    }
+
+    pub fn pathfind_time_dependent<F: Fn(LaneID, Time) -> Duration>(
+        &self,
+        req: PathRequest,
+        departure: Time,
+        lane_cost: F,
+    ) -> Option<Path> {
+        crate::pathfind::pathfind_time_dependent(req, departure, lane_cost, self)
+    }
+
     pub fn should_use_transit(
         &self,
         start: Position,
@@ -686,6 +858,32 @@ impl Map {
             .should_use_transit(self, start, end)
     }
 
+    // Parking lots close enough to a bus stop to leave a car there and continue by transit. Each
+    // lot comes with its closest stop.
+    pub fn park_and_ride_lots(&self) -> Vec<(ParkingLotID, BusStopID)> {
+        self.parking_lots
+            .iter()
+            .filter_map(|pl| self.park_and_ride_stop(pl.id).map(|bs| (pl.id, bs)))
+            .collect()
+    }
+
+    // The closest bus stop, if it's within a short walk of the lot.
+    pub fn park_and_ride_stop(&self, pl: ParkingLotID) -> Option<BusStopID> {
+        // Roughly a 5 minute walk
+        let max_dist = Distance::meters(400.0);
+        let lot_pt = self.get_pl(pl).sidewalk_pos.pt(self);
+        let (dist, stop) = self
+            .bus_stops
+            .values()
+            .map(|bs| (bs.sidewalk_pos.pt(self).dist_to(lot_pt), bs.id))
+            .min()?;
+        if dist <= max_dist {
+            Some(stop)
+        } else {
+            None
+        }
+    }
+
     // None for SharedSidewalkCorners
     pub fn get_turn_group(&self, t: TurnID) -> Option<TurnGroupID> {
         if let Some(ref ts) = self.maybe_get_traffic_signal(t.parent) {
@@ -818,11 +1016,20 @@ impl Map {
         BTreeSet<IntersectionID>,
     ) {
         // TODO More efficient ways to do this: given two sets of edits, produce a smaller diff.
-        // Simplest strategy: Remove common prefix.
         let mut effects = EditEffects::new();
 
-        // First undo all existing edits.
-        let mut undo = std::mem::replace(&mut self.edits.commands, Vec::new());
+        // Commands both sets of edits start with are already applied, so leave them alone. The
+        // effects only describe what actually changes.
+        let common = self
+            .edits
+            .commands
+            .iter()
+            .zip(new_edits.commands.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        // First undo the existing edits that differ.
+        let mut undo = self.edits.commands.split_off(common);
         undo.reverse();
         let mut undid = 0;
         for cmd in &undo {
@@ -834,7 +1041,7 @@ impl Map {
 
         // Apply new edits.
         let mut applied = 0;
-        for cmd in &new_edits.commands {
+        for cmd in &new_edits.commands[common..] {
             if cmd.apply(&mut effects, self, timer) {
                 applied += 1;
             }
@@ -842,7 +1049,7 @@ impl Map {
         timer.note(format!(
             "Applied {} / {} new edits",
             applied,
-            new_edits.commands.len()
+            new_edits.commands.len() - common
         ));
 
         // Might need to update bus stops.
@@ -905,6 +1112,7 @@ fn make_half_map(
     initial_map: make::initial::InitialMap,
     gps_bounds: GPSBounds,
     bounds: Bounds,
+    opts: &RawToMapOptions,
     timer: &mut Timer,
 ) -> Map {
     let mut map = Map {
@@ -918,17 +1126,23 @@ fn make_half_map(
         areas: Vec::new(),
         parking_lots: Vec::new(),
         boundary_polygon: raw.boundary_polygon.clone(),
+        building_blocks: Vec::new(),
         stop_signs: BTreeMap::new(),
         traffic_signals: BTreeMap::new(),
+        curb_regulations: BTreeMap::new(),
+        block_the_box_overrides: BTreeMap::new(),
         gps_bounds,
         bounds,
         driving_side: raw.driving_side,
+        geometry_overrides: BTreeMap::new(),
+        build_opts: opts.clone(),
         turn_lookup: Vec::new(),
         pathfinder: None,
         pathfinder_dirty: false,
         city_name: raw.city_name.clone(),
         name: raw.name.clone(),
         edits: MapEdits::new(),
+        quadtree: None,
     };
 
     let road_id_mapping: BTreeMap<OriginalRoad, RoadID> = initial_map
@@ -1008,13 +1222,16 @@ fn make_half_map(
             src_i: i1,
             dst_i: i2,
             speed_limit: Speed::ZERO,
+            access_restrictions: AccessRestrictions::default(),
             zorder: if let Some(layer) = raw.roads[&r.id].osm_tags.get("layer") {
                 layer.parse::<isize>().unwrap()
             } else {
                 0
             },
+            importance: RoadImportance::Local,
         };
         road.speed_limit = road.speed_limit_from_osm();
+        road.access_restrictions = road.access_restrictions_from_osm();
 
         for lane in &r.lane_specs {
             let id = LaneID(map.lanes.len());
@@ -1106,6 +1323,15 @@ fn make_half_map(
         }
     }
 
+    if opts.repair_lanes {
+        timer.start("repair lane connectivity");
+        for t in connectivity::repair_lanes(&map, timer) {
+            map.intersections[t.id.parent.0].turns.insert(t.id);
+            map.turns.insert(t.id, t);
+        }
+        timer.stop("repair lane connectivity");
+    }
+
     for t in map.turns.values_mut() {
         t.lookup_idx = map.turn_lookup.len();
         map.turn_lookup.push(t.id);
@@ -1146,6 +1372,8 @@ fn make_half_map(
 
     make::bridges::find_bridges(&mut map.roads, &map.bounds, timer);
 
+    make::generalize::classify_roads(&mut map.roads, &map.intersections, timer);
+
     map
 }
 
@@ -1257,6 +1485,52 @@ impl EditCmd {
                     false
                 }
             }
+            EditCmd::ChangeAccessRestrictions { id, ref new, .. } => {
+                let r = &mut map.roads[id.0];
+                if r.access_restrictions == *new {
+                    return false;
+                }
+                r.access_restrictions = new.clone();
+                effects.changed_roads.insert(*id);
+                // Rendering of the intersections depends on this
+                effects.changed_intersections.insert(r.src_i);
+                effects.changed_intersections.insert(r.dst_i);
+                true
+            }
+            EditCmd::ChangeCurbRegulation { l, ref new, .. } => {
+                if map.get_curb_regulation(*l) == *new {
+                    return false;
+                }
+                if new.is_default() {
+                    map.curb_regulations.remove(l);
+                } else {
+                    map.curb_regulations.insert(*l, new.clone());
+                }
+                effects.changed_roads.insert(map.lanes[l.0].parent);
+                true
+            }
+            EditCmd::ChangeBlockTheBox { i, new, .. } => {
+                if map.allow_block_the_box(*i) == *new {
+                    return false;
+                }
+                if *new == map.get_i(*i).allow_block_the_box_by_default() {
+                    map.block_the_box_overrides.remove(i);
+                } else {
+                    map.block_the_box_overrides.insert(*i, *new);
+                }
+                effects.changed_intersections.insert(*i);
+                true
+            }
+            EditCmd::ChangeBusRoute { id, ref new, .. } => {
+                if map.get_br_edit(*id) == *new {
+                    return false;
+                }
+                let r = &mut map.bus_routes[id.0];
+                r.stops = new.stops.clone();
+                r.headway = new.headway;
+                // Nothing needs to be redrawn, but pathfinding using transit changes
+                true
+            }
             EditCmd::ChangeIntersection {
                 i,
                 ref new,
@@ -1322,6 +1596,42 @@ impl EditCmd {
                     false
                 }
             }
+            EditCmd::ChangeAccessRestrictions {
+                id,
+                ref old,
+                ref new,
+            } => EditCmd::ChangeAccessRestrictions {
+                id: *id,
+                old: new.clone(),
+                new: old.clone(),
+            }
+            .apply(effects, map, timer),
+            EditCmd::ChangeCurbRegulation {
+                l,
+                ref old,
+                ref new,
+            } => EditCmd::ChangeCurbRegulation {
+                l: *l,
+                old: new.clone(),
+                new: old.clone(),
+            }
+            .apply(effects, map, timer),
+            EditCmd::ChangeBlockTheBox { i, old, new } => EditCmd::ChangeBlockTheBox {
+                i: *i,
+                old: *new,
+                new: *old,
+            }
+            .apply(effects, map, timer),
+            EditCmd::ChangeBusRoute {
+                id,
+                ref old,
+                ref new,
+            } => EditCmd::ChangeBusRoute {
+                id: *id,
+                old: new.clone(),
+                new: old.clone(),
+            }
+            .apply(effects, map, timer),
             EditCmd::ChangeIntersection {
                 i,
                 ref old,
@@ -1,10 +1,14 @@
 use crate::pathfind::node_map::{deserialize_nodemap, NodeMap};
 use crate::pathfind::uber_turns::{IntersectionCluster, UberTurn};
-use crate::{Lane, LaneID, Map, Path, PathConstraints, PathRequest, PathStep, Turn, TurnID};
+use crate::{
+    Lane, LaneID, Map, Path, PathConstraints, PathRequest, PathStep, RoadID, TruckAccess, Turn,
+    TurnID,
+};
 use abstutil::MultiMap;
 use fast_paths::{deserialize_32, serialize_32, FastGraph, InputGraph, PathCalculator};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::collections::BTreeSet;
 use thread_local::ThreadLocal;
 
 #[derive(Serialize, Deserialize)]
@@ -31,6 +35,18 @@ impl VehiclePathfinder {
         map: &Map,
         constraints: PathConstraints,
         seed: Option<&VehiclePathfinder>,
+    ) -> VehiclePathfinder {
+        VehiclePathfinder::new_avoiding(map, constraints, &BTreeSet::new(), seed)
+    }
+
+    // Lanes on the avoided roads cost much more, so paths only use them when there's no other way.
+    // Edits don't update the extra costs, so throw these away after edits instead of calling
+    // apply_edits.
+    pub fn new_avoiding(
+        map: &Map,
+        constraints: PathConstraints,
+        avoid: &BTreeSet<RoadID>,
+        seed: Option<&VehiclePathfinder>,
     ) -> VehiclePathfinder {
         // Insert every lane as a node. Even if the lane type is wrong now, it might change later,
         // and we want the node in the graph. Do this first, so the IDs of all the nodes doesn't
@@ -49,7 +65,7 @@ impl VehiclePathfinder {
             }
         }
 
-        let input_graph = make_input_graph(map, &nodes, &uber_turns, constraints);
+        let input_graph = make_input_graph(map, &nodes, &uber_turns, constraints, avoid);
 
         // All VehiclePathfinders have the same nodes (lanes), so if we're not the first being
         // built, seed from the node ordering.
@@ -118,7 +134,13 @@ impl VehiclePathfinder {
         // the node ordering.
         // TODO Make sure the result of this is deterministic and equivalent to computing from
         // scratch.
-        let input_graph = make_input_graph(map, &self.nodes, &self.uber_turns, self.constraints);
+        let input_graph = make_input_graph(
+            map,
+            &self.nodes,
+            &self.uber_turns,
+            self.constraints,
+            &BTreeSet::new(),
+        );
         let node_ordering = self.graph.get_node_ordering();
         self.graph = fast_paths::prepare_with_order(&input_graph, &node_ordering).unwrap();
     }
@@ -129,8 +151,17 @@ fn make_input_graph(
     nodes: &NodeMap<Node>,
     uber_turns: &Vec<UberTurn>,
     constraints: PathConstraints,
+    avoid: &BTreeSet<RoadID>,
 ) -> InputGraph {
     let mut input_graph = InputGraph::new();
+    let cost = |lane: &Lane, turn: &Turn| {
+        let c = cost(lane, turn, constraints, map);
+        if avoid.contains(&lane.parent) {
+            c * AVOID_ROAD_PENALTY
+        } else {
+            c
+        }
+    };
 
     // From some lanes, instead of adding edges to turns, add edges to these (indexed) uber-turns.
     let mut uber_turn_entrances: MultiMap<LaneID, usize> = MultiMap::new();
@@ -170,7 +201,7 @@ fn make_input_graph(
                         from,
                         nodes.get(Node::Lane(turn.id.dst)),
                         // Round up! 0 cost edges are ignored
-                        cost(l, turn, constraints, map).max(1),
+                        cost(l, turn).max(1),
                     );
                 }
             } else {
@@ -180,7 +211,7 @@ fn make_input_graph(
 
                     let mut sum_cost = 0;
                     for t in &ut.path {
-                        sum_cost += cost(map.get_l(t.src), map.get_t(*t), constraints, map);
+                        sum_cost += cost(map.get_l(t.src), map.get_t(*t));
                     }
                     input_graph.add_edge(from, nodes.get(Node::UberTurn(*idx)), sum_cost.max(1));
                     input_graph.add_edge(
@@ -205,7 +236,22 @@ fn make_input_graph(
     input_graph
 }
 
+// Vehicles can still use pedestrian streets to reach somewhere along them, but should never cut
+// through one when there's any reasonable alternative.
+const PEDESTRIAN_STREET_PENALTY: usize = 100;
+// Same idea for roads a driver was warned about
+const AVOID_ROAD_PENALTY: usize = 100;
+
 pub fn cost(lane: &Lane, turn: &Turn, constraints: PathConstraints, map: &Map) -> usize {
+    let base = base_cost(lane, turn, constraints, map);
+    if map.get_r(lane.parent).access_restrictions.pedestrian_only {
+        base * PEDESTRIAN_STREET_PENALTY
+    } else {
+        base
+    }
+}
+
+fn base_cost(lane: &Lane, turn: &Turn, constraints: PathConstraints, map: &Map) -> usize {
     // TODO Could cost turns differently.
 
     match constraints {
@@ -248,6 +294,21 @@ pub fn cost(lane: &Lane, turn: &Turn, constraints: PathConstraints, map: &Map) -
             };
             (lt_penalty * (t1 + t2)).inner_seconds().round() as usize
         }
+        PathConstraints::Truck => {
+            // Like Car, but stick to truck routes when possible.
+            let t1 = lane.length() / map.get_r(lane.parent).speed_limit;
+            let t2 = turn.geom.length() / map.get_parent(turn.id.dst).speed_limit;
+            // The contraction hierarchy can't know where each truck is going, so roads only for
+            // local deliveries are just very expensive to pass through. Trucks starting or ending
+            // there still use them, but nobody else takes them as a shortcut.
+            let route_penalty = match map.get_r(lane.parent).access_restrictions.trucks {
+                TruckAccess::DesignatedRoute => 1.0,
+                TruckAccess::Allowed => 1.5,
+                // Banned lanes only get here from the middle of an uber-turn.
+                TruckAccess::Destination | TruckAccess::Banned => 20.0,
+            };
+            (route_penalty * (t1 + t2)).inner_seconds().round() as usize
+        }
         PathConstraints::Pedestrian => unreachable!(),
     }
 }
@@ -0,0 +1,288 @@
+use crate::{
+    BusRouteID, BusStopID, LaneType, Map, Path, PathConstraints, PathRequest, PathStep, Position,
+};
+use geom::{Distance, Duration, Speed};
+
+// Rough speeds for estimating how long each leg takes. Estimates ignore congestion, waiting at
+// intersections, and waiting for the bus.
+const WALKING_SPEED: Speed = Speed::const_meters_per_second(1.34);
+// About 10mph
+const BIKING_SPEED: Speed = Speed::const_meters_per_second(4.47);
+
+// A whole trip from start to end, maybe switching modes along the way, like walking to a bus stop,
+// riding, and walking the rest of the way. Deciding on the legs is cheap; nothing is pathfound
+// until something asks for legs().
+#[derive(Clone, Debug)]
+pub struct Itinerary {
+    // For a bus ride, just the stops to board and alight at
+    plan: Vec<(LegMode, PathRequest)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Leg {
+    pub mode: LegMode,
+    pub path: Path,
+    // Estimated; see WALKING_SPEED
+    pub time: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LegMode {
+    Walk,
+    Drive,
+    Bike,
+    RideBus {
+        route: BusRouteID,
+        board: BusStopID,
+        alight: BusStopID,
+    },
+}
+
+impl Itinerary {
+    // Just one mode the whole way, determined by the request's constraints.
+    pub fn single(req: PathRequest) -> Itinerary {
+        let mode = match req.constraints {
+            PathConstraints::Pedestrian => LegMode::Walk,
+            PathConstraints::Bike => LegMode::Bike,
+            PathConstraints::Car | PathConstraints::Bus | PathConstraints::Truck => LegMode::Drive,
+        };
+        Itinerary {
+            plan: vec![(mode, req)],
+        }
+    }
+
+    // Walk to a bus stop, ride, then walk the rest of the way. If no bus helps, just walks. Both
+    // positions are on sidewalks.
+    pub fn walk_transit_walk(start: Position, end: Position, map: &Map) -> Itinerary {
+        let (board, alight, route) = match map.should_use_transit(start, end) {
+            Some(x) => x,
+            None => {
+                return Itinerary::single(walk(start, end));
+            }
+        };
+        let board_stop = map.get_bs(board);
+        let alight_stop = map.get_bs(alight);
+        Itinerary {
+            plan: vec![
+                (LegMode::Walk, walk(start, board_stop.sidewalk_pos)),
+                (
+                    LegMode::RideBus {
+                        route,
+                        board,
+                        alight,
+                    },
+                    PathRequest {
+                        start: board_stop.driving_pos,
+                        end: alight_stop.driving_pos,
+                        constraints: PathConstraints::Bus,
+                    },
+                ),
+                (LegMode::Walk, walk(alight_stop.sidewalk_pos, end)),
+            ],
+        }
+    }
+
+    // Drive (or bike) to the end of the request, park there, and walk to the end, which is on a
+    // sidewalk. None if there's no sidewalk near where the vehicle parks.
+    pub fn drive_park_walk(req: PathRequest, end: Position, map: &Map) -> Option<Itinerary> {
+        let mode = if req.constraints == PathConstraints::Bike {
+            LegMode::Bike
+        } else {
+            LegMode::Drive
+        };
+        let park = req.end;
+        let sidewalk = map
+            .find_closest_lane(park.lane(), vec![LaneType::Sidewalk])
+            .ok()?;
+        let walk_start = park.equiv_pos(sidewalk, Distance::ZERO, map);
+        Some(Itinerary {
+            plan: vec![(mode, req), (LegMode::Walk, walk(walk_start, end))],
+        })
+    }
+
+    // Pathfinds every leg, every time this is called. None if any leg can't be routed.
+    pub fn legs(&self, map: &Map) -> Option<Vec<Leg>> {
+        self.plan
+            .iter()
+            .map(|(mode, req)| {
+                let path = match mode {
+                    LegMode::RideBus {
+                        route,
+                        board,
+                        alight,
+                    } => ride_bus(*route, *board, *alight, map)?,
+                    _ => map.pathfind(req.clone())?,
+                };
+                Some(Leg::new(*mode, path, map))
+            })
+            .collect()
+    }
+
+    // The stops and route, if this itinerary rides a bus. Doesn't pathfind.
+    pub fn bus_ride(&self) -> Option<(BusStopID, BusStopID, BusRouteID)> {
+        self.plan.iter().find_map(|(mode, _)| match mode {
+            LegMode::RideBus {
+                route,
+                board,
+                alight,
+            } => Some((*board, *alight, *route)),
+            _ => None,
+        })
+    }
+}
+
+impl Leg {
+    fn new(mode: LegMode, path: Path, map: &Map) -> Leg {
+        let mut time = Duration::ZERO;
+        for step in path.get_steps() {
+            let t = step.as_traversable();
+            let speed = match mode {
+                LegMode::Walk => WALKING_SPEED,
+                LegMode::Bike => t.speed_limit(map).min(BIKING_SPEED),
+                LegMode::Drive | LegMode::RideBus { .. } => t.speed_limit(map),
+            };
+            time += t.length(map) / speed;
+        }
+        Leg { mode, path, time }
+    }
+}
+
+fn walk(start: Position, end: Position) -> PathRequest {
+    PathRequest {
+        start,
+        end,
+        constraints: PathConstraints::Pedestrian,
+    }
+}
+
+// Follows the route stop by stop, the same way the bus will.
+fn ride_bus(route: BusRouteID, board: BusStopID, alight: BusStopID, map: &Map) -> Option<Leg> {
+    let stops = &map.get_br(route).stops;
+    let start_idx = stops.iter().position(|s| *s == board)?;
+    let mut steps: Vec<PathStep> = Vec::new();
+    let mut idx = start_idx;
+    loop {
+        let next_idx = (idx + 1) % stops.len();
+        let hop = map.pathfind(PathRequest {
+            start: map.get_bs(stops[idx]).driving_pos,
+            end: map.get_bs(stops[next_idx]).driving_pos,
+            constraints: PathConstraints::Bus,
+        })?;
+        // Each hop starts on the lane where the previous one ended
+        let skip = if steps.is_empty() { 0 } else { 1 };
+        steps.extend(hop.get_steps().iter().skip(skip).cloned());
+        if stops[next_idx] == alight {
+            break;
+        }
+        idx = next_idx;
+        if idx == start_idx {
+            return None;
+        }
+    }
+    let path = Path::new(map, steps, map.get_bs(alight).driving_pos.dist_along());
+    Some(Leg::new(
+        LegMode::RideBus {
+            route,
+            board,
+            alight,
+        },
+        path,
+        map,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::RawMap;
+    use crate::{LaneID, Traversable};
+
+    fn grid(build_ch: bool) -> Map {
+        Map::synthetic(RawMap::synthetic_grid("itinerary", 2, 1), build_ch)
+    }
+
+    // Drive from the start of some driving lane to the middle of a different one, then walk to the
+    // first sidewalk of that other road.
+    fn drive_then_walk(map: &Map) -> (PathRequest, Position) {
+        let driving: Vec<LaneID> = map
+            .all_lanes()
+            .iter()
+            .filter(|l| l.is_driving())
+            .map(|l| l.id)
+            .collect();
+        let from = driving[0];
+        let to = *driving
+            .iter()
+            .find(|l| map.get_l(**l).parent != map.get_l(from).parent)
+            .unwrap();
+        let sidewalk = map
+            .get_parent(to)
+            .all_lanes()
+            .into_iter()
+            .find(|l| map.get_l(*l).is_sidewalk())
+            .unwrap();
+        (
+            PathRequest {
+                start: Position::new(from, Distance::ZERO),
+                end: Position::new(to, map.get_l(to).length() / 2.0),
+                constraints: PathConstraints::Car,
+            },
+            Position::new(sidewalk, map.get_l(sidewalk).length()),
+        )
+    }
+
+    #[test]
+    fn planning_doesnt_pathfind() {
+        // Without contraction hierarchies, anything that pathfinds would crash.
+        let map = grid(false);
+        let (req, end) = drive_then_walk(&map);
+        assert_eq!(Itinerary::single(req.clone()).bus_ride(), None);
+        assert_eq!(
+            Itinerary::drive_park_walk(req, end, &map)
+                .unwrap()
+                .bus_ride(),
+            None
+        );
+    }
+
+    #[test]
+    fn drive_park_walk() {
+        let map = grid(true);
+        let (req, end) = drive_then_walk(&map);
+        let legs = Itinerary::drive_park_walk(req.clone(), end, &map)
+            .unwrap()
+            .legs(&map)
+            .unwrap();
+        assert_eq!(
+            legs.iter().map(|leg| leg.mode).collect::<Vec<_>>(),
+            vec![LegMode::Drive, LegMode::Walk]
+        );
+        assert_eq!(
+            legs[0].path.last_step().as_traversable(),
+            Traversable::Lane(req.end.lane())
+        );
+        assert_eq!(
+            legs[1].path.last_step().as_traversable(),
+            Traversable::Lane(end.lane())
+        );
+        for leg in &legs {
+            assert!(leg.time > Duration::ZERO);
+        }
+        // Walking is slower than driving
+        let speed = |leg: &Leg| leg.path.total_length().inner_meters() / leg.time.inner_seconds();
+        let (drive_speed, walk_speed) = (speed(&legs[0]), speed(&legs[1]));
+        assert!(walk_speed < drive_speed);
+    }
+
+    #[test]
+    fn no_bus_means_walking() {
+        let map = grid(true);
+        let (_, end) = drive_then_walk(&map);
+        let start = map.all_buildings()[0].front_path.sidewalk;
+        let itinerary = Itinerary::walk_transit_walk(start, end, &map);
+        assert_eq!(itinerary.bus_ride(), None);
+        let legs = itinerary.legs(&map).unwrap();
+        assert_eq!(legs.len(), 1);
+        assert_eq!(legs[0].mode, LegMode::Walk);
+    }
+}
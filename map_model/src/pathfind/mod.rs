@@ -1,4 +1,5 @@
 mod driving;
+mod itinerary;
 mod node_map;
 // TODO tmp
 pub mod uber_turns;
@@ -6,15 +7,19 @@ mod walking;
 
 pub use self::driving::cost;
 use self::driving::VehiclePathfinder;
+pub use self::itinerary::{Itinerary, Leg, LegMode};
 use self::walking::SidewalkPathfinder;
 use crate::{
-    osm, BusRouteID, BusStopID, Lane, LaneID, LaneType, Map, Position, Traversable, TurnID,
+    osm, BusRouteID, BusStopID, Lane, LaneID, LaneType, Map, Position, RoadID, Traversable,
+    TruckAccess, TurnID,
 };
 use abstutil::Timer;
-use geom::{Distance, PolyLine, EPSILON_DIST};
+use geom::{Distance, Duration, PolyLine, Time, EPSILON_DIST};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PathStep {
@@ -102,7 +107,8 @@ pub struct Path {
 }
 
 impl Path {
-    pub(crate) fn new(map: &Map, steps: Vec<PathStep>, end_dist: Distance) -> Path {
+    // Trusts that the steps are contiguous.
+    pub fn new(map: &Map, steps: Vec<PathStep>, end_dist: Distance) -> Path {
         // Haven't seen problems here in a very long time. Noticeably saves some time to skip.
         if false {
             validate_continuity(map, &steps);
@@ -162,6 +168,10 @@ impl Path {
         self.total_length
     }
 
+    pub fn end_dist(&self) -> Distance {
+        self.end_dist
+    }
+
     pub fn percent_dist_crossed(&self) -> f64 {
         // Sometimes this happens
         if self.total_length == Distance::ZERO {
@@ -321,12 +331,14 @@ impl Path {
 
 // Who's asking for a path?
 // TODO This is an awful name.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PathConstraints {
     Pedestrian,
     Car,
     Bike,
     Bus,
+    // Freight. Like cars, but respecting truck bans and preferring truck routes.
+    Truck,
 }
 
 impl PathConstraints {
@@ -337,6 +349,8 @@ impl PathConstraints {
             LaneType::Driving => PathConstraints::Car,
             LaneType::Biking => PathConstraints::Bike,
             LaneType::Bus => PathConstraints::Bus,
+            // Cars can't route through these, but buses can
+            LaneType::HOV => PathConstraints::Bus,
             _ => panic!("PathConstraints::from_lt({:?}) doesn't make sense", lt),
         }
     }
@@ -348,7 +362,7 @@ impl PathConstraints {
             PathConstraints::Bike => {
                 if l.is_biking() {
                     true
-                } else if l.is_driving() || l.is_bus() {
+                } else if l.is_driving() || l.is_bus() || l.is_hov() {
                     // Note bikes can use bus lanes -- this is generally true in Seattle.
                     let road = map.get_r(l.parent);
                     road.osm_tags.get("bicycle") != Some(&"no".to_string())
@@ -358,7 +372,11 @@ impl PathConstraints {
                     false
                 }
             }
-            PathConstraints::Bus => l.is_driving() || l.is_bus(),
+            PathConstraints::Bus => l.is_driving() || l.is_bus() || l.is_hov(),
+            PathConstraints::Truck => {
+                l.is_driving()
+                    && map.get_r(l.parent).access_restrictions.trucks != TruckAccess::Banned
+            }
         }
     }
 
@@ -383,6 +401,60 @@ impl PathConstraints {
     }
 }
 
+// Built on the fly, and the time to cross each lane depends on when the vehicle gets there,
+// starting from the departure time. This lets callers use congested travel times that change
+// through the day, like from a previous run of a simulation. lane_cost(l, t) should return the
+// time to cross l and get onto the next lane, if the vehicle enters it at t. Assumes that leaving
+// later never means arriving earlier. Doesn't handle uber-turns. Only for vehicles.
+pub fn pathfind_time_dependent<F: Fn(LaneID, Time) -> Duration>(
+    req: PathRequest,
+    departure: Time,
+    lane_cost: F,
+    map: &Map,
+) -> Option<Path> {
+    assert!(!map.get_l(req.start.lane()).is_sidewalk());
+    let start = req.start.lane();
+    let end = req.end.lane();
+    if start == end {
+        return None;
+    }
+
+    // When the vehicle can first enter each lane, and the turn it took to get there
+    let mut arrivals: BTreeMap<LaneID, Time> = BTreeMap::new();
+    let mut backrefs: BTreeMap<LaneID, TurnID> = BTreeMap::new();
+    let mut queue: BinaryHeap<(Reverse<Time>, LaneID)> = BinaryHeap::new();
+    arrivals.insert(start, departure);
+    queue.push((Reverse(departure), start));
+
+    while let Some((Reverse(time), current)) = queue.pop() {
+        if current == end {
+            let mut steps = vec![PathStep::Lane(end)];
+            let mut at = end;
+            while at != start {
+                let t = backrefs[&at];
+                steps.push(PathStep::Turn(t));
+                steps.push(PathStep::Lane(t.src));
+                at = t.src;
+            }
+            steps.reverse();
+            return Some(Path::new(map, steps, req.end.dist_along()));
+        }
+        if time > arrivals[&current] {
+            continue;
+        }
+        let next_time = time + lane_cost(current, time);
+        for turn in map.get_turns_for(current, req.constraints) {
+            let next = turn.id.dst;
+            if arrivals.get(&next).map(|t| next_time < *t).unwrap_or(true) {
+                arrivals.insert(next, next_time);
+                backrefs.insert(next, turn.id);
+                queue.push((Reverse(next_time), next));
+            }
+        }
+    }
+    None
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct PathRequest {
     pub start: Position,
@@ -473,9 +545,14 @@ pub struct Pathfinder {
     car_graph: VehiclePathfinder,
     bike_graph: VehiclePathfinder,
     bus_graph: VehiclePathfinder,
+    // Only built once some road restricts trucks. Until then, trucks route just like cars.
+    truck_graph: Option<VehiclePathfinder>,
     walking_graph: SidewalkPathfinder,
     // TODO Option just during initialization! Ewww.
     walking_with_transit_graph: Option<SidewalkPathfinder>,
+    // Made on demand by pathfind_avoiding_roads, and thrown away after edits
+    #[serde(skip_serializing, skip_deserializing)]
+    avoiding: Mutex<BTreeMap<(PathConstraints, BTreeSet<RoadID>), Arc<VehiclePathfinder>>>,
 }
 
 impl Pathfinder {
@@ -494,6 +571,15 @@ impl Pathfinder {
         let bus_graph = VehiclePathfinder::new(map, PathConstraints::Bus, Some(&car_graph));
         timer.stop("prepare pathfinding for buses");
 
+        let truck_graph = if restricts_trucks(map) {
+            timer.start("prepare pathfinding for trucks");
+            let g = VehiclePathfinder::new(map, PathConstraints::Truck, Some(&car_graph));
+            timer.stop("prepare pathfinding for trucks");
+            Some(g)
+        } else {
+            None
+        };
+
         timer.start("prepare pathfinding for pedestrians");
         let walking_graph = SidewalkPathfinder::new(map, false, &bus_graph);
         timer.stop("prepare pathfinding for pedestrians");
@@ -502,8 +588,10 @@ impl Pathfinder {
             car_graph,
             bike_graph,
             bus_graph,
+            truck_graph,
             walking_graph,
             walking_with_transit_graph: None,
+            avoiding: Mutex::new(BTreeMap::new()),
         }
     }
 
@@ -517,9 +605,48 @@ impl Pathfinder {
             PathConstraints::Car => self.car_graph.pathfind(&req, map).map(|(p, _)| p),
             PathConstraints::Bike => self.bike_graph.pathfind(&req, map).map(|(p, _)| p),
             PathConstraints::Bus => self.bus_graph.pathfind(&req, map).map(|(p, _)| p),
+            PathConstraints::Truck => self
+                .truck_graph
+                .as_ref()
+                .unwrap_or(&self.car_graph)
+                .pathfind(&req, map)
+                .map(|(p, _)| p),
         }
     }
 
+    // Like pathfind, but only uses the avoided roads when there's no other way. The first request
+    // for each set of roads prepares another contraction hierarchy, which is quick since it reuses
+    // the node ordering from the normal one. Only for vehicles.
+    pub fn pathfind_avoiding_roads(
+        &self,
+        req: PathRequest,
+        avoid: &BTreeSet<RoadID>,
+        map: &Map,
+    ) -> Option<Path> {
+        let seed = match req.constraints {
+            PathConstraints::Car => &self.car_graph,
+            PathConstraints::Bike => &self.bike_graph,
+            PathConstraints::Bus => &self.bus_graph,
+            PathConstraints::Truck => self.truck_graph.as_ref().unwrap_or(&self.car_graph),
+            PathConstraints::Pedestrian => unreachable!(),
+        };
+        let graph = self
+            .avoiding
+            .lock()
+            .unwrap()
+            .entry((req.constraints, avoid.clone()))
+            .or_insert_with(|| {
+                Arc::new(VehiclePathfinder::new_avoiding(
+                    map,
+                    req.constraints,
+                    avoid,
+                    Some(seed),
+                ))
+            })
+            .clone();
+        graph.pathfind(&req, map).map(|(p, _)| p)
+    }
+
     pub fn should_use_transit(
         &self,
         map: &Map,
@@ -533,6 +660,8 @@ impl Pathfinder {
     }
 
     pub fn apply_edits(&mut self, map: &Map, timer: &mut Timer) {
+        self.avoiding.get_mut().unwrap().clear();
+
         timer.start("apply edits to car pathfinding");
         self.car_graph.apply_edits(map);
         timer.stop("apply edits to car pathfinding");
@@ -545,6 +674,20 @@ impl Pathfinder {
         self.bus_graph.apply_edits(map);
         timer.stop("apply edits to bus pathfinding");
 
+        if let Some(ref mut g) = self.truck_graph {
+            timer.start("apply edits to truck pathfinding");
+            g.apply_edits(map);
+            timer.stop("apply edits to truck pathfinding");
+        } else if restricts_trucks(map) {
+            timer.start("prepare pathfinding for trucks");
+            self.truck_graph = Some(VehiclePathfinder::new(
+                map,
+                PathConstraints::Truck,
+                Some(&self.car_graph),
+            ));
+            timer.stop("prepare pathfinding for trucks");
+        }
+
         timer.start("apply edits to pedestrian pathfinding");
         self.walking_graph.apply_edits(map, &self.bus_graph);
         timer.stop("apply edits to pedestrian pathfinding");
@@ -557,3 +700,11 @@ impl Pathfinder {
         timer.stop("apply edits to pedestrian using transit pathfinding");
     }
 }
+
+// Without any truck restrictions, trucks can share the car graph. The truck costs only differ by a
+// constant factor then, which doesn't change any routes.
+fn restricts_trucks(map: &Map) -> bool {
+    map.all_roads()
+        .iter()
+        .any(|r| r.access_restrictions.trucks != TruckAccess::Allowed)
+}
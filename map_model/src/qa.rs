@@ -0,0 +1,213 @@
+use crate::make::intersection_control;
+use crate::raw::{OriginalIntersection, OriginalRoad};
+use crate::{
+    connectivity, ControlChoice, IntersectionType, Map, PathConstraints, RoadID, TurnType,
+};
+use abstutil::Timer;
+use geom::LonLat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Problems with the map that the importer notices, but that need a human to look at. Everything is
+// keyed by OSM IDs, so the report is still meaningful after the map is rebuilt.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QaReport {
+    pub map_name: String,
+    pub issues: Vec<QaIssue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QaIssue {
+    pub problem: QaProblem,
+    pub description: String,
+    // In GPS, so the editor can find it in the RawMap
+    pub location: LonLat,
+    pub fix: Option<QaFix>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum QaProblem {
+    // Pedestrians can't get between this road's sidewalks and the rest of the map
+    DisconnectedSidewalk(OriginalRoad),
+    // A sidewalk ends at this intersection without any way across
+    MissingCrosswalk(OriginalIntersection, OriginalRoad),
+    // How an intersection is controlled, and why. Besides signals, OSM rarely says, so this is
+    // usually the importer's guess and worth checking against the ground.
+    IntersectionControl(OriginalIntersection, ControlChoice),
+}
+
+// Something the map editor knows how to apply to the RawMap
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum QaFix {
+    // Overwrite the OSM sidewalk tag for just this road
+    SetSidewalks(OriginalRoad, String),
+}
+
+impl QaReport {
+    pub fn new(map: &Map, timer: &mut Timer) -> QaReport {
+        let mut issues = Vec::new();
+        let gps_bounds = map.get_gps_bounds();
+
+        timer.start("find disconnected sidewalks");
+        let (_, disconnected) = connectivity::find_scc(map, PathConstraints::Pedestrian);
+        let roads: BTreeSet<RoadID> = disconnected
+            .into_iter()
+            .map(|l| map.get_l(l).parent)
+            .collect();
+        for r in roads {
+            let road = map.get_r(r);
+            issues.push(QaIssue {
+                problem: QaProblem::DisconnectedSidewalk(road.orig_id),
+                description: format!(
+                    "Sidewalks along {} can't reach the rest of the map",
+                    road.get_name()
+                ),
+                location: road.center_pts.middle().forcibly_to_gps(gps_bounds),
+                // Usually these are leftovers from service roads and parking aisles
+                fix: Some(QaFix::SetSidewalks(road.orig_id, "none".to_string())),
+            });
+        }
+        timer.stop("find disconnected sidewalks");
+
+        timer.start_iter("find missing crosswalks", map.all_intersections().len());
+        for i in map.all_intersections() {
+            timer.next();
+            // Dead-ends and degenerate intersections don't need crosswalks
+            if i.is_border() || i.roads.len() < 3 {
+                continue;
+            }
+            let crosswalks: Vec<_> = map
+                .get_turns_in_intersection(i.id)
+                .into_iter()
+                .filter(|t| t.turn_type == TurnType::Crosswalk)
+                .collect();
+            let mut seen = BTreeSet::new();
+            for l in i.incoming_lanes.iter().chain(i.outgoing_lanes.iter()) {
+                let lane = map.get_l(*l);
+                if !lane.is_sidewalk()
+                    || crosswalks.iter().any(|t| t.id.src == *l || t.id.dst == *l)
+                    || !seen.insert(lane.parent)
+                {
+                    continue;
+                }
+                let road = map.get_r(lane.parent);
+                let one_sided = road
+                    .all_lanes()
+                    .into_iter()
+                    .filter(|l| map.get_l(*l).is_sidewalk())
+                    .count()
+                    == 1;
+                issues.push(QaIssue {
+                    problem: QaProblem::MissingCrosswalk(i.orig_id, road.orig_id),
+                    description: format!(
+                        "The sidewalk along {} has no crosswalk at {}",
+                        road.get_name(),
+                        i.orig_id
+                    ),
+                    location: lane.endpoint(i.id).forcibly_to_gps(gps_bounds),
+                    // With sidewalks on both sides, there's at least a crosswalk over the road
+                    // itself.
+                    fix: if one_sided {
+                        Some(QaFix::SetSidewalks(road.orig_id, "both".to_string()))
+                    } else {
+                        None
+                    },
+                });
+            }
+        }
+
+        for i in map.all_intersections() {
+            let decision = intersection_control::decide(map, i.id);
+            let (choice, reason) = match i.intersection_type {
+                IntersectionType::StopSign => (decision.choice, decision.reason),
+                // Either tagged in OSM or guessed by find_new_signals. Tagged signals stay, no
+                // matter what the heuristic says.
+                IntersectionType::TrafficSignal => (
+                    ControlChoice::TrafficSignal,
+                    if decision.choice == ControlChoice::TrafficSignal {
+                        decision.reason
+                    } else {
+                        "tagged in OSM".to_string()
+                    },
+                ),
+                IntersectionType::Border | IntersectionType::Construction => {
+                    continue;
+                }
+            };
+            issues.push(QaIssue {
+                problem: QaProblem::IntersectionControl(i.orig_id, choice),
+                description: format!("{} at {}: {}", choice, i.orig_id, reason),
+                location: i.polygon.center().forcibly_to_gps(gps_bounds),
+                fix: None,
+            });
+        }
+
+        QaReport {
+            map_name: map.get_name().to_string(),
+            issues,
+        }
+    }
+
+    // None if the importer hasn't run since this was added
+    pub fn load(map_name: &str, timer: &mut Timer) -> Option<QaReport> {
+        abstutil::maybe_read_json(abstutil::path_qa_report(map_name), timer).ok()
+    }
+
+    pub fn save(&self) {
+        abstutil::write_json(abstutil::path_qa_report(&self.map_name), self);
+    }
+}
+
+impl QaFix {
+    pub fn describe(&self) -> String {
+        match self {
+            QaFix::SetSidewalks(r, value) => format!("set sidewalk={} on {}", value, r),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm;
+    use crate::raw::RawMap;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn records_intersection_control() {
+        // Two intersections joined by a secondary road, each with residential roads out to the
+        // borders. OSM tags the second as a signal.
+        let mut raw = RawMap::synthetic_grid("qa", 2, 1);
+        for (id, road) in raw.roads.iter_mut() {
+            if id.osm_way_id == 1 {
+                road.osm_tags
+                    .insert(osm::HIGHWAY.to_string(), "secondary".to_string());
+            }
+        }
+        raw.intersections
+            .get_mut(&OriginalIntersection { osm_node_id: 2 })
+            .unwrap()
+            .intersection_type = IntersectionType::TrafficSignal;
+        let map = Map::synthetic(raw, false);
+
+        let controls: BTreeMap<i64, (ControlChoice, String)> =
+            QaReport::new(&map, &mut Timer::throwaway())
+                .issues
+                .into_iter()
+                .filter_map(|issue| match issue.problem {
+                    QaProblem::IntersectionControl(i, choice) => {
+                        Some((i.osm_node_id, (choice, issue.description)))
+                    }
+                    _ => None,
+                })
+                .collect();
+        // Borders aren't controlled
+        assert_eq!(controls.len(), 2);
+        assert_eq!(controls[&1].0, ControlChoice::MinorRoadsStop);
+        assert!(controls[&1]
+            .1
+            .ends_with(": secondary has priority over residential"));
+        assert_eq!(controls[&2].0, ControlChoice::TrafficSignal);
+        assert!(controls[&2].1.ends_with(": tagged in OSM"));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::make::initial::lane_specs::get_lane_types;
-use crate::{osm, AreaType, IntersectionType, RoadSpec};
+use crate::{osm, AreaType, BusStopAmenities, IntersectionType, LaneType, RoadSpec};
 use abstutil::{deserialize_btreemap, serialize_btreemap, Timer, Warn};
-use geom::{Angle, Distance, GPSBounds, Line, PolyLine, Polygon, Pt2D};
+use geom::{Angle, Bounds, Distance, GPSBounds, Line, LonLat, PolyLine, Polygon, Pt2D};
 use gtfs::Route;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
@@ -27,6 +27,7 @@ pub struct RawMap {
     )]
     pub buildings: BTreeMap<OriginalBuilding, RawBuilding>,
     pub bus_routes: Vec<Route>,
+    pub bus_stops: Vec<RawBusStop>,
     pub areas: Vec<RawArea>,
     pub parking_lots: Vec<RawParkingLot>,
     pub parking_aisles: Vec<Vec<Pt2D>>,
@@ -94,6 +95,7 @@ impl RawMap {
             intersections: BTreeMap::new(),
             buildings: BTreeMap::new(),
             bus_routes: Vec::new(),
+            bus_stops: Vec::new(),
             areas: Vec::new(),
             parking_lots: Vec::new(),
             parking_aisles: Vec::new(),
@@ -104,6 +106,150 @@ impl RawMap {
         }
     }
 
+    // A tiny synthetic city for tests, which can't rely on any imported map being around. There's a
+    // grid of cols x rows stop signs, 100m apart, connected by two-way residential streets with
+    // parking and sidewalks on both sides. Every edge of the grid has a short road out to a border.
+    // Each east-west block has three houses on either side. Tweak the result before building a Map
+    // for anything fancier.
+    pub fn synthetic_grid(name: &str, cols: usize, rows: usize) -> RawMap {
+        const BLOCK: f64 = 100.0;
+        let mut map = RawMap::blank("synthetic", name);
+
+        let mut next_id = 1;
+        let mut add_i = |map: &mut RawMap, x: f64, y: f64, intersection_type| {
+            let id = OriginalIntersection {
+                osm_node_id: next_id,
+            };
+            next_id += 1;
+            map.intersections.insert(
+                id,
+                RawIntersection {
+                    point: Pt2D::new(x, y),
+                    intersection_type,
+                    elevation: Distance::ZERO,
+                },
+            );
+            id
+        };
+        // Leave room for the roads out to the borders
+        let pos = |idx: usize| BLOCK / 2.0 + BLOCK * (idx as f64);
+        let mut grid = Vec::new();
+        for r in 0..rows {
+            let mut row = Vec::new();
+            for c in 0..cols {
+                row.push(add_i(&mut map, pos(c), pos(r), IntersectionType::StopSign));
+            }
+            grid.push(row);
+        }
+        let (max_x, max_y) = (BLOCK * (cols as f64), BLOCK * (rows as f64));
+        let mut borders = Vec::new();
+        for r in 0..rows {
+            borders.push((
+                grid[r][0],
+                add_i(&mut map, 0.0, pos(r), IntersectionType::Border),
+            ));
+            borders.push((
+                grid[r][cols - 1],
+                add_i(&mut map, max_x, pos(r), IntersectionType::Border),
+            ));
+        }
+        for c in 0..cols {
+            borders.push((
+                grid[0][c],
+                add_i(&mut map, pos(c), 0.0, IntersectionType::Border),
+            ));
+            borders.push((
+                grid[rows - 1][c],
+                add_i(&mut map, pos(c), max_y, IntersectionType::Border),
+            ));
+        }
+
+        let mut roads = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                if c + 1 < cols {
+                    roads.push((grid[r][c], grid[r][c + 1]));
+                }
+                if r + 1 < rows {
+                    roads.push((grid[r][c], grid[r + 1][c]));
+                }
+            }
+        }
+        roads.extend(borders);
+        for (idx, (i1, i2)) in roads.into_iter().enumerate() {
+            let id = OriginalRoad {
+                osm_way_id: (idx + 1) as i64,
+                i1,
+                i2,
+            };
+            let mut osm_tags = BTreeMap::new();
+            osm_tags.insert(osm::SYNTHETIC.to_string(), "true".to_string());
+            osm_tags.insert(
+                osm::SYNTHETIC_LANES.to_string(),
+                RoadSpec {
+                    fwd: vec![LaneType::Driving, LaneType::Parking, LaneType::Sidewalk],
+                    back: vec![LaneType::Driving, LaneType::Parking, LaneType::Sidewalk],
+                }
+                .to_string(),
+            );
+            osm_tags.insert(osm::ENDPT_FWD.to_string(), "true".to_string());
+            osm_tags.insert(osm::ENDPT_BACK.to_string(), "true".to_string());
+            osm_tags.insert(osm::OSM_WAY_ID.to_string(), id.osm_way_id.to_string());
+            osm_tags.insert(osm::HIGHWAY.to_string(), "residential".to_string());
+            osm_tags.insert(osm::NAME.to_string(), format!("Street {}", idx + 1));
+            osm_tags.insert(osm::MAXSPEED.to_string(), "25 mph".to_string());
+            map.roads.insert(
+                id,
+                RawRoad {
+                    center_points: vec![map.intersections[&i1].point, map.intersections[&i2].point],
+                    osm_tags,
+                    turn_restrictions: Vec::new(),
+                    complicated_turn_restrictions: Vec::new(),
+                },
+            );
+        }
+
+        let mut next_bldg = 1;
+        for r in 0..rows {
+            for c in 0..cols - 1 {
+                for along in &[0.3, 0.5, 0.7] {
+                    for side in &[-15.0, 15.0] {
+                        let center = Pt2D::new(pos(c) + along * BLOCK, pos(r) + side);
+                        let mut osm_tags = BTreeMap::new();
+                        osm_tags.insert("building".to_string(), "house".to_string());
+                        map.buildings.insert(
+                            OriginalBuilding {
+                                osm_way_id: next_bldg,
+                            },
+                            RawBuilding {
+                                polygon: Polygon::rectangle_centered(
+                                    center,
+                                    Distance::meters(8.0),
+                                    Distance::meters(8.0),
+                                ),
+                                osm_tags,
+                                public_garage_name: None,
+                                num_parking_spots: 0,
+                                amenities: BTreeSet::new(),
+                                height: None,
+                            },
+                        );
+                        next_bldg += 1;
+                    }
+                }
+            }
+        }
+
+        let bounds = Bounds::from(&vec![Pt2D::new(0.0, 0.0), Pt2D::new(max_x, max_y)]);
+        map.boundary_polygon = bounds.get_rectangle();
+        map.gps_bounds = GPSBounds::new();
+        map.gps_bounds
+            .update(Pt2D::new(0.0, 0.0).forcibly_to_gps(&GPSBounds::seattle_bounds()));
+        map.gps_bounds
+            .update(Pt2D::new(max_x, max_y).forcibly_to_gps(&GPSBounds::seattle_bounds()));
+        map
+    }
+
     // TODO Might be better to maintain this instead of doing a search everytime.
     pub fn roads_per_intersection(&self, i: OriginalIntersection) -> Vec<OriginalRoad> {
         let mut results = Vec::new();
@@ -153,11 +299,16 @@ impl RawMap {
     pub fn preview_intersection(
         &self,
         id: OriginalIntersection,
+        fix: Option<&GeometryOverride>,
         timer: &mut Timer,
-    ) -> (Polygon, Vec<Polygon>, Vec<(String, Polygon)>) {
+    ) -> (
+        Polygon,
+        Vec<(OriginalRoad, Polygon)>,
+        Vec<(String, Polygon)>,
+    ) {
         use crate::make::initial;
 
-        let i = initial::Intersection {
+        let mut i = initial::Intersection {
             id,
             polygon: Vec::new(),
             roads: self.roads_per_intersection(id).into_iter().collect(),
@@ -171,11 +322,15 @@ impl RawMap {
 
         let (i_pts, debug) =
             initial::intersection_polygon(self.driving_side, &i, &mut roads, timer);
+        i.polygon = i_pts;
+        if let Some(fix) = fix {
+            initial::apply_geometry_override(&mut i, fix, &mut roads, &self.gps_bounds, timer);
+        }
         (
-            Polygon::new(&i_pts),
+            Polygon::new(&i.polygon),
             roads
-                .values()
-                .map(|r| {
+                .iter()
+                .map(|(id, r)| {
                     // A little of get_thick_polyline
                     let pl = if r.fwd_width >= r.back_width {
                         self.driving_side
@@ -192,7 +347,7 @@ impl RawMap {
                             )
                             .unwrap()
                     };
-                    pl.make_polygons(r.fwd_width + r.back_width)
+                    (*id, pl.make_polygons(r.fwd_width + r.back_width))
                 })
                 .collect(),
             debug,
@@ -315,6 +470,63 @@ pub struct RawIntersection {
     pub elevation: Distance,
 }
 
+// Manual fixes for intersections whose generated geometry is just wrong. These live outside the
+// RawMap, so they survive re-importing from OSM, and get applied when the RawMap becomes a Map.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntersectionGeometryOverrides {
+    pub map_name: String,
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    pub intersections: BTreeMap<OriginalIntersection, GeometryOverride>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeometryOverride {
+    // Replaces the generated polygon. In GPS, since the map's bounds might change.
+    pub polygon: Option<Vec<LonLat>>,
+    // Trim each road back this much farther from the intersection. Negative extends the road into
+    // the intersection instead.
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    pub trim_roads: BTreeMap<OriginalRoad, Distance>,
+}
+
+impl IntersectionGeometryOverrides {
+    pub fn new(map_name: &str) -> IntersectionGeometryOverrides {
+        IntersectionGeometryOverrides {
+            map_name: map_name.to_string(),
+            intersections: BTreeMap::new(),
+        }
+    }
+
+    // Most maps don't have any
+    pub fn load(map_name: &str, timer: &mut Timer) -> IntersectionGeometryOverrides {
+        abstutil::maybe_read_json(abstutil::path_intersection_geometry(map_name), timer)
+            .unwrap_or_else(|_| IntersectionGeometryOverrides::new(map_name))
+    }
+
+    pub fn save(&self) {
+        abstutil::write_json(abstutil::path_intersection_geometry(&self.map_name), self);
+    }
+}
+
+impl GeometryOverride {
+    pub fn new() -> GeometryOverride {
+        GeometryOverride {
+            polygon: None,
+            trim_roads: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygon.is_none() && self.trim_roads.is_empty()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RawBuilding {
     pub polygon: Polygon,
@@ -323,6 +535,8 @@ pub struct RawBuilding {
     pub num_parking_spots: usize,
     // (Name, amenity type)
     pub amenities: BTreeSet<(String, String)>,
+    // From the height or building:levels tags, if either is present
+    pub height: Option<Distance>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -339,6 +553,14 @@ pub struct RawParkingLot {
     pub osm_id: i64,
 }
 
+// GTFS decides where bus stops are. OSM just has extra details about them, matched up by location
+// later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawBusStop {
+    pub pt: Pt2D,
+    pub amenities: BusStopAmenities,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RestrictionType {
     BanTurns,
@@ -98,7 +98,11 @@ pub struct Road {
     pub complicated_turn_restrictions: Vec<(RoadID, RoadID)>,
     pub orig_id: OriginalRoad,
     pub speed_limit: Speed,
+    pub access_restrictions: AccessRestrictions,
     pub zorder: isize,
+    // How prominent the road is when the map is drawn zoomed far out. Calculated once when the
+    // map is built.
+    pub importance: RoadImportance,
 
     // Invariant: A road must contain at least one child
     // These are ordered from closest to center lane (left-most when driving on the right) to
@@ -113,6 +117,43 @@ pub struct Road {
     pub dst_i: IntersectionID,
 }
 
+// Who can use a road, beyond what the lane types say.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AccessRestrictions {
+    // Closed to vehicles, except to reach somewhere along the road itself -- deliveries, mostly.
+    // Those vehicles move at walking pace.
+    pub pedestrian_only: bool,
+    pub trucks: TruckAccess,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruckAccess {
+    Allowed,
+    Banned,
+    // Part of a designated truck route; freight should prefer these.
+    DesignatedRoute,
+    // Trucks can go here to deliver something, but shouldn't cut through.
+    Destination,
+}
+
+impl Default for TruckAccess {
+    fn default() -> TruckAccess {
+        TruckAccess::Allowed
+    }
+}
+
+// Ordered from least to most important, for drawing a simplified map at city scale.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RoadImportance {
+    Local,
+    Collector,
+    Arterial,
+    Highway,
+}
+
+// Vehicles allowed on a pedestrian street
+const PEDESTRIAN_STREET_SPEED: Speed = Speed::const_meters_per_second(2.5);
+
 impl Road {
     pub fn get_lane_types(&self) -> (Vec<LaneType>, Vec<LaneType>) {
         (
@@ -207,6 +248,29 @@ impl Road {
         }
     }
 
+    pub(crate) fn access_restrictions_from_osm(&self) -> AccessRestrictions {
+        AccessRestrictions {
+            // convert_osm skips highway=pedestrian ways entirely, so streets only become
+            // pedestrian-only through edits.
+            pedestrian_only: false,
+            trucks: match self.osm_tags.get("hgv").map(|x| x.as_str()) {
+                Some("no") => TruckAccess::Banned,
+                Some("designated") => TruckAccess::DesignatedRoute,
+                Some("destination") | Some("delivery") => TruckAccess::Destination,
+                _ => TruckAccess::Allowed,
+            },
+        }
+    }
+
+    // What vehicles actually drive at
+    pub fn vehicle_speed_limit(&self) -> Speed {
+        if self.access_restrictions.pedestrian_only {
+            self.speed_limit.min(PEDESTRIAN_STREET_SPEED)
+        } else {
+            self.speed_limit
+        }
+    }
+
     pub(crate) fn speed_limit_from_osm(&self) -> Speed {
         if let Some(limit) = self.osm_tags.get(osm::MAXSPEED) {
             // TODO handle other units
@@ -0,0 +1,57 @@
+use crate::{AreaID, BuildingID, IntersectionID, LaneID, Map, RoadID};
+use aabb_quadtree::QuadTree;
+use geom::{Bounds, Distance, PolyLine};
+
+// Anything on the map with a footprint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectID {
+    Road(RoadID),
+    Lane(LaneID),
+    Intersection(IntersectionID),
+    Building(BuildingID),
+    Area(AreaID),
+}
+
+// Map edits only change lane types, never geometry, so this only needs to be built once per map.
+pub(crate) fn build(map: &Map) -> QuadTree<ObjectID> {
+    let mut quadtree = QuadTree::default(map.get_bounds().as_bbox());
+    for r in map.all_roads() {
+        let mut bounds = Bounds::new();
+        let lanes = r.all_lanes();
+        for l in &lanes {
+            let l = *l;
+            let lane = map.get_l(l);
+            let lane_bounds = thick_bounds(&lane.lane_center_pts, lane.width);
+            quadtree.insert_with_box(ObjectID::Lane(l), lane_bounds.as_bbox());
+            bounds.union(lane_bounds);
+        }
+        if lanes.is_empty() {
+            bounds = r.center_pts.get_bounds();
+        }
+        quadtree.insert_with_box(ObjectID::Road(r.id), bounds.as_bbox());
+    }
+    for i in map.all_intersections() {
+        quadtree.insert_with_box(
+            ObjectID::Intersection(i.id),
+            i.polygon.get_bounds().as_bbox(),
+        );
+    }
+    for b in map.all_buildings() {
+        quadtree.insert_with_box(ObjectID::Building(b.id), b.polygon.get_bounds().as_bbox());
+    }
+    for a in map.all_areas() {
+        quadtree.insert_with_box(ObjectID::Area(a.id), a.polygon.get_bounds().as_bbox());
+    }
+    quadtree
+}
+
+// The polyline is the center of something this wide.
+fn thick_bounds(pl: &PolyLine, width: Distance) -> Bounds {
+    let mut b = pl.get_bounds();
+    let pad = width.inner_meters() / 2.0;
+    b.min_x -= pad;
+    b.min_y -= pad;
+    b.max_x += pad;
+    b.max_y += pad;
+    b
+}
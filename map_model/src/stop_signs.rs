@@ -1,3 +1,4 @@
+use crate::make::intersection_control::{decide, ControlChoice};
 use crate::{IntersectionID, LaneID, Map, RoadID, TurnID, TurnPriority, TurnType};
 use abstutil::{deserialize_btreemap, serialize_btreemap};
 use serde::{Deserialize, Serialize};
@@ -78,6 +79,20 @@ impl ControlStopSign {
             return ss;
         }
 
+        match decide(map, id).choice {
+            ControlChoice::Uncontrolled => {
+                return ss;
+            }
+            ControlChoice::AllWayStop => {
+                for cfg in ss.roads.values_mut() {
+                    cfg.must_stop = true;
+                }
+                return ss;
+            }
+            // Even if a signal would make more sense, somebody asked for a stop sign here.
+            ControlChoice::MinorRoadsStop | ControlChoice::TrafficSignal => {}
+        }
+
         // What's the rank of each road?
         let mut rank: HashMap<RoadID, usize> = HashMap::new();
         for r in ss.roads.keys() {
@@ -1,4 +1,4 @@
-use crate::make::traffic_signals::{brute_force, get_possible_policies};
+use crate::make::traffic_signals::{brute_force, get_possible_policies, get_presets};
 use crate::{
     DirectedRoadID, IntersectionID, Map, TurnGroup, TurnGroupID, TurnID, TurnPriority, TurnType,
 };
@@ -43,6 +43,9 @@ impl ControlTrafficSignal {
     ) -> Vec<(String, ControlTrafficSignal)> {
         get_possible_policies(map, id, timer)
     }
+    pub fn get_presets(map: &Map, id: IntersectionID) -> Vec<(String, ControlTrafficSignal)> {
+        get_presets(map, id)
+    }
     // TODO tmp
     pub fn brute_force(map: &Map, id: IntersectionID) {
         brute_force(map, id)
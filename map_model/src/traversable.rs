@@ -138,8 +138,8 @@ impl Traversable {
 
     pub fn speed_limit(&self, map: &Map) -> Speed {
         match *self {
-            Traversable::Lane(id) => map.get_parent(id).speed_limit,
-            Traversable::Turn(id) => map.get_parent(id.dst).speed_limit,
+            Traversable::Lane(id) => map.get_parent(id).vehicle_speed_limit(),
+            Traversable::Turn(id) => map.get_parent(id.dst).vehicle_speed_limit(),
         }
     }
 
@@ -1,6 +1,6 @@
 use crate::{DirectedRoadID, IntersectionID, LaneID, Map};
 use abstutil::MultiMap;
-use geom::{Angle, Distance, PolyLine, Pt2D};
+use geom::{find_intersections, Angle, Distance, PolyLine, Pt2D};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
@@ -256,26 +256,62 @@ impl TurnGroup {
     }
 
     pub fn conflicts_with(&self, other: &TurnGroup) -> bool {
+        self.conflicts_without_geom(other)
+            .unwrap_or_else(|| self.geom.intersection(&other.geom).is_some())
+    }
+
+    // Every pair of groups that conflict, as indices into the input, smaller first. The same as
+    // calling conflicts_with on every pair, but the geometry is checked all at once.
+    pub fn all_conflicts(groups: &[&TurnGroup]) -> BTreeSet<(usize, usize)> {
+        let mut lines = Vec::new();
+        let mut owner = Vec::new();
+        for (idx, g) in groups.iter().enumerate() {
+            for l in g.geom.lines() {
+                lines.push(l);
+                owner.push(idx);
+            }
+        }
+        let crossing: BTreeSet<(usize, usize)> = find_intersections(&lines)
+            .into_iter()
+            .map(|(l1, l2, _)| (owner[l1].min(owner[l2]), owner[l1].max(owner[l2])))
+            .collect();
+
+        let mut conflicts = BTreeSet::new();
+        for idx1 in 0..groups.len() {
+            for idx2 in (idx1 + 1)..groups.len() {
+                if groups[idx1]
+                    .conflicts_without_geom(groups[idx2])
+                    .unwrap_or_else(|| crossing.contains(&(idx1, idx2)))
+                {
+                    conflicts.insert((idx1, idx2));
+                }
+            }
+        }
+        conflicts
+    }
+
+    // None if it depends on whether the two groups cross
+    fn conflicts_without_geom(&self, other: &TurnGroup) -> Option<bool> {
         if self.id == other.id {
-            return false;
+            return Some(false);
         }
         if self.turn_type == TurnType::Crosswalk && other.turn_type == TurnType::Crosswalk {
-            return false;
+            return Some(false);
         }
 
         if self.id.from == other.id.from
             && self.turn_type != TurnType::Crosswalk
             && other.turn_type != TurnType::Crosswalk
         {
-            return false;
+            return Some(false);
         }
         if self.id.to == other.id.to
             && self.turn_type != TurnType::Crosswalk
             && other.turn_type != TurnType::Crosswalk
         {
-            return true;
+            return Some(true);
         }
-        self.geom.intersection(&other.geom).is_some()
+        None
     }
 }
 
@@ -303,3 +339,28 @@ fn turn_group_geom(
     }
     PolyLine::new(pts)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::RawMap;
+
+    #[test]
+    fn all_conflicts_matches_pairwise() {
+        let map = Map::synthetic(RawMap::synthetic_grid("conflicts", 3, 3), false);
+        for i in map.all_intersections() {
+            let groups = TurnGroup::for_i(i.id, &map);
+            let groups: Vec<&TurnGroup> = groups.values().collect();
+
+            let mut expected = BTreeSet::new();
+            for idx1 in 0..groups.len() {
+                for idx2 in (idx1 + 1)..groups.len() {
+                    if groups[idx1].conflicts_with(groups[idx2]) {
+                        expected.insert((idx1, idx2));
+                    }
+                }
+            }
+            assert_eq!(TurnGroup::all_conflicts(&groups), expected, "at {}", i.id);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    BuildingID, BusStopID, LaneID, LaneType, Map, ParkingLotID, PermanentMapEdits, Position,
+};
+use abstutil::Timer;
+use geom::Distance;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+
+// Nobody's going to walk farther than this to a bus stop or their car.
+const MAX_WALK: Distance = Distance::const_meters(800.0);
+
+// For every building, the walking distance to nearby bus stops and parking, sorted by distance.
+// Spawning lots of trips asks the same questions over and over, so compute the answers once per map
+// and edits, and save them.
+#[derive(Serialize, Deserialize)]
+pub struct WalkingDistances {
+    map_name: String,
+    edits_name: String,
+    // The full edits, so a cache for an edits file that's since been changed isn't used
+    edits: String,
+    bus_stops: BTreeMap<BuildingID, Vec<(BusStopID, Distance)>>,
+    parking_lanes: BTreeMap<BuildingID, Vec<(LaneID, Distance)>>,
+    parking_lots: BTreeMap<BuildingID, Vec<(ParkingLotID, Distance)>>,
+}
+
+// Something to walk to, somewhere along a sidewalk
+#[derive(Clone, Copy)]
+enum Target {
+    BusStop(BusStopID),
+    ParkingLane(LaneID),
+    ParkingLot(ParkingLotID),
+}
+
+impl WalkingDistances {
+    pub fn new(map: &Map, timer: &mut Timer) -> WalkingDistances {
+        // Group everything by the sidewalk it's on
+        let mut targets: HashMap<LaneID, Vec<(Distance, Target)>> = HashMap::new();
+        for l in map.all_lanes() {
+            if !l.is_sidewalk() {
+                continue;
+            }
+            // Assume people reach parking lanes by crossing over from the sidewalk at the middle.
+            if let Ok(parking) = map.find_closest_lane(l.id, vec![LaneType::Parking]) {
+                add_target(
+                    &mut targets,
+                    Position::new(l.id, l.length() / 2.0),
+                    Target::ParkingLane(parking),
+                );
+            }
+        }
+        for stop in map.all_bus_stops().values() {
+            add_target(&mut targets, stop.sidewalk_pos, Target::BusStop(stop.id));
+        }
+        for pl in map.all_parking_lots() {
+            add_target(&mut targets, pl.sidewalk_pos, Target::ParkingLot(pl.id));
+        }
+
+        let mut result = WalkingDistances {
+            map_name: map.get_name().to_string(),
+            edits_name: map.get_edits().edits_name.clone(),
+            edits: edits_key(map),
+            bus_stops: BTreeMap::new(),
+            parking_lanes: BTreeMap::new(),
+            parking_lots: BTreeMap::new(),
+        };
+        timer.start_iter("calculate walking distances", map.all_buildings().len());
+        for b in map.all_buildings() {
+            timer.next();
+            let mut stops = Vec::new();
+            let mut lanes = Vec::new();
+            let mut lots = Vec::new();
+            for (target, dist) in walk_from(map, b.front_path.sidewalk, &targets) {
+                match target {
+                    Target::BusStop(id) => stops.push((id, dist)),
+                    Target::ParkingLane(id) => lanes.push((id, dist)),
+                    Target::ParkingLot(id) => lots.push((id, dist)),
+                }
+            }
+            result.bus_stops.insert(b.id, stops);
+            result.parking_lanes.insert(b.id, lanes);
+            result.parking_lots.insert(b.id, lots);
+        }
+        result
+    }
+
+    // Uses the cached copy if it's there and was calculated with the same edits, since they
+    // might change sidewalks and parking. Otherwise calculates and saves a fresh copy.
+    pub fn load_or_compute(map: &Map, timer: &mut Timer) -> WalkingDistances {
+        let edits_name = &map.get_edits().edits_name;
+        if let Ok(wd) = abstutil::maybe_read_binary::<WalkingDistances>(
+            abstutil::path_walking_distances(map.get_name(), edits_name),
+            timer,
+        ) {
+            if &wd.map_name == map.get_name() && wd.edits == edits_key(map) {
+                return wd;
+            }
+        }
+        let wd = WalkingDistances::new(map, timer);
+        wd.save();
+        wd
+    }
+
+    pub fn save(&self) {
+        abstutil::write_binary(
+            abstutil::path_walking_distances(&self.map_name, &self.edits_name),
+            self,
+        );
+    }
+
+    // None if the building doesn't exist in the map this was calculated for.
+    pub fn bus_stops_near(&self, b: BuildingID) -> Option<&Vec<(BusStopID, Distance)>> {
+        self.bus_stops.get(&b)
+    }
+
+    // None if the building doesn't exist in the map this was calculated for.
+    pub fn parking_lanes_near(&self, b: BuildingID) -> Option<&Vec<(LaneID, Distance)>> {
+        self.parking_lanes.get(&b)
+    }
+
+    // None if the building doesn't exist in the map this was calculated for.
+    pub fn parking_lots_near(&self, b: BuildingID) -> Option<&Vec<(ParkingLotID, Distance)>> {
+        self.parking_lots.get(&b)
+    }
+}
+
+fn edits_key(map: &Map) -> String {
+    abstutil::to_json(&PermanentMapEdits::to_permanent(map.get_edits(), map))
+}
+
+fn add_target(targets: &mut HashMap<LaneID, Vec<(Distance, Target)>>, pos: Position, t: Target) {
+    targets
+        .entry(pos.lane())
+        .or_insert_with(Vec::new)
+        .push((pos.dist_along(), t));
+}
+
+// Dijkstra's over sidewalk endpoints, stopping at MAX_WALK. Results are sorted by distance, and
+// each target appears at most once.
+fn walk_from(
+    map: &Map,
+    start: Position,
+    targets: &HashMap<LaneID, Vec<(Distance, Target)>>,
+) -> Vec<(Target, Distance)> {
+    let mut best: BTreeMap<(LaneID, bool), Distance> = BTreeMap::new();
+    // Nodes are (sidewalk, at the end). Reverse for a min-heap.
+    let mut queue: BinaryHeap<(Reverse<Distance>, LaneID, bool)> = BinaryHeap::new();
+    let start_len = map.get_l(start.lane()).length();
+    queue.push((Reverse(start.dist_along()), start.lane(), false));
+    queue.push((Reverse(start_len - start.dist_along()), start.lane(), true));
+
+    let mut found_dist: HashMap<(LaneID, usize), Distance> = HashMap::new();
+    // The start sidewalk is special; targets on it are reached directly.
+    if let Some(list) = targets.get(&start.lane()) {
+        for (idx, (dist, _)) in list.iter().enumerate() {
+            let d = if *dist > start.dist_along() {
+                *dist - start.dist_along()
+            } else {
+                start.dist_along() - *dist
+            };
+            found_dist.insert((start.lane(), idx), d);
+        }
+    }
+
+    while let Some((Reverse(dist), l, at_end)) = queue.pop() {
+        if dist > MAX_WALK || best.contains_key(&(l, at_end)) {
+            continue;
+        }
+        best.insert((l, at_end), dist);
+        let lane = map.get_l(l);
+
+        // Walk the full length of this sidewalk, picking up targets along the way.
+        if l != start.lane() {
+            if let Some(list) = targets.get(&l) {
+                for (idx, (dist_along, _)) in list.iter().enumerate() {
+                    let d = dist
+                        + if at_end {
+                            lane.length() - *dist_along
+                        } else {
+                            *dist_along
+                        };
+                    let entry = found_dist.entry((l, idx)).or_insert(d);
+                    if d < *entry {
+                        *entry = d;
+                    }
+                }
+            }
+            queue.push((Reverse(dist + lane.length()), l, !at_end));
+        }
+
+        let i = if at_end { lane.dst_i } else { lane.src_i };
+        for turn in map.get_turns_from_lane(l) {
+            if turn.id.parent != i || !turn.between_sidewalks() {
+                continue;
+            }
+            let next_at_end = map.get_l(turn.id.dst).dst_i == i;
+            queue.push((Reverse(dist + turn.geom.length()), turn.id.dst, next_at_end));
+        }
+    }
+
+    // Several sidewalks might share one parking lane, so only keep the closest.
+    let mut seen_lanes = HashSet::new();
+    let mut found = Vec::new();
+    let mut sorted: Vec<((LaneID, usize), Distance)> = found_dist
+        .into_iter()
+        .filter(|(_, d)| *d <= MAX_WALK)
+        .collect();
+    sorted.sort_by_key(|(key, d)| (*d, *key));
+    for ((l, idx), d) in sorted {
+        let target = targets[&l][idx].1;
+        if let Target::ParkingLane(parking) = target {
+            if !seen_lanes.insert(parking) {
+                continue;
+            }
+        }
+        found.push((target, d));
+    }
+    found
+}
@@ -0,0 +1,165 @@
+use crate::TripMode;
+use abstutil::Timer;
+use geom::{Duration, Speed};
+use map_model::{BuildingID, LaneID, Map, PathConstraints, RoadID};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::fs::File;
+use std::io::{Error, Write};
+
+// The standard cutoff for "how much can I reach from home"
+pub const ACCESSIBILITY_TIME_LIMIT: Duration = Duration::const_seconds(30.0 * 60.0);
+
+// For each building, how many destinations are reachable within ACCESSIBILITY_TIME_LIMIT by each
+// mode. There's no jobs data, so every amenity tagged in OSM counts as one destination.
+//
+// This measures the network, not the simulation: travel times assume free-flow speeds, with no
+// waiting at intersections. Buildings along the same road get the same score.
+pub struct AccessibilityScores {
+    scores: BTreeMap<BuildingID, BTreeMap<TripMode, usize>>,
+    pub total_destinations: usize,
+}
+
+impl AccessibilityScores {
+    pub fn new(map: &Map, timer: &mut Timer) -> AccessibilityScores {
+        let mut dests_per_road: BTreeMap<RoadID, usize> = BTreeMap::new();
+        let mut bldgs_per_road: BTreeMap<RoadID, Vec<BuildingID>> = BTreeMap::new();
+        for b in map.all_buildings() {
+            let r = map.building_to_road(b.id).id;
+            *dests_per_road.entry(r).or_insert(0) += b.amenities.len();
+            bldgs_per_road.entry(r).or_insert_with(Vec::new).push(b.id);
+        }
+        let total_destinations = dests_per_road.values().sum();
+
+        let mut requests = Vec::new();
+        for r in bldgs_per_road.keys() {
+            for mode in AccessibilityScores::modes() {
+                requests.push((*r, mode));
+            }
+        }
+        let dests_per_road = &dests_per_road;
+        let results = timer.parallelize("calculate accessibility", requests, |(r, mode)| {
+            (
+                r,
+                mode,
+                reachable_destinations(map, r, mode, dests_per_road),
+            )
+        });
+
+        let mut scores: BTreeMap<BuildingID, BTreeMap<TripMode, usize>> = BTreeMap::new();
+        for (r, mode, cnt) in results {
+            for b in &bldgs_per_road[&r] {
+                scores
+                    .entry(*b)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(mode, cnt);
+            }
+        }
+        AccessibilityScores {
+            scores,
+            total_destinations,
+        }
+    }
+
+    // Transit isn't included; it'd need schedules, not just the network.
+    pub fn modes() -> Vec<TripMode> {
+        vec![TripMode::Walk, TripMode::Bike, TripMode::Drive]
+    }
+
+    pub fn score(&self, b: BuildingID, mode: TripMode) -> usize {
+        self.scores
+            .get(&b)
+            .and_then(|per_mode| per_mode.get(&mode))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    // One row per building. If there's a baseline (usually the map without edits), include the
+    // change from it too.
+    pub fn write_csv(
+        &self,
+        path: &str,
+        map: &Map,
+        baseline: Option<&AccessibilityScores>,
+    ) -> Result<(), Error> {
+        let mut f = File::create(path)?;
+        let mut header = vec!["building".to_string(), "osm_way_id".to_string()];
+        for mode in AccessibilityScores::modes() {
+            header.push(mode.verb().to_string());
+            if baseline.is_some() {
+                header.push(format!("{}_change", mode.verb()));
+            }
+        }
+        writeln!(f, "{}", header.join(","))?;
+
+        for b in map.all_buildings() {
+            let mut row = vec![b.id.0.to_string(), b.osm_way_id.to_string()];
+            for mode in AccessibilityScores::modes() {
+                let score = self.score(b.id, mode);
+                row.push(score.to_string());
+                if let Some(base) = baseline {
+                    row.push((score as isize - base.score(b.id, mode) as isize).to_string());
+                }
+            }
+            writeln!(f, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+// Dijkstra over lanes, starting from every lane of the road this mode can use.
+fn reachable_destinations(
+    map: &Map,
+    start: RoadID,
+    mode: TripMode,
+    dests_per_road: &BTreeMap<RoadID, usize>,
+) -> usize {
+    let constraints = match mode {
+        TripMode::Walk => PathConstraints::Pedestrian,
+        TripMode::Bike => PathConstraints::Bike,
+        _ => PathConstraints::Car,
+    };
+    let cost = |l: LaneID| {
+        let lane = map.get_l(l);
+        let limit = map.get_r(lane.parent).speed_limit;
+        let speed = match mode {
+            TripMode::Walk => Speed::meters_per_second(1.34),
+            TripMode::Bike => limit.min(Speed::miles_per_hour(10.0)),
+            _ => limit,
+        };
+        lane.length() / speed
+    };
+
+    let mut best: BTreeMap<LaneID, Duration> = BTreeMap::new();
+    let mut queue: BinaryHeap<(Reverse<Duration>, LaneID)> = BinaryHeap::new();
+    for l in map.get_r(start).all_lanes() {
+        if constraints.can_use(map.get_l(l), map) {
+            best.insert(l, Duration::ZERO);
+            queue.push((Reverse(Duration::ZERO), l));
+        }
+    }
+
+    let mut reached: BTreeSet<RoadID> = BTreeSet::new();
+    while let Some((Reverse(time), current)) = queue.pop() {
+        if time > best[&current] {
+            continue;
+        }
+        reached.insert(map.get_l(current).parent);
+        for turn in map.get_turns_for(current, constraints) {
+            let next = turn.id.dst;
+            let next_time = time + cost(next);
+            if next_time > ACCESSIBILITY_TIME_LIMIT {
+                continue;
+            }
+            if best.get(&next).map(|t| next_time < *t).unwrap_or(true) {
+                best.insert(next, next_time);
+                queue.push((Reverse(next_time), next));
+            }
+        }
+    }
+
+    reached
+        .into_iter()
+        .map(|r| dests_per_road.get(&r).cloned().unwrap_or(0))
+        .sum()
+}
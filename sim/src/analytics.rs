@@ -1,4 +1,4 @@
-use crate::{AlertLocation, CarID, Event, ParkingSpot, TripID, TripMode, TripPhaseType};
+use crate::{AgentID, AlertLocation, CarID, Event, ParkingSpot, TripID, TripMode, TripPhaseType};
 use abstutil::Counter;
 use geom::{Distance, Duration, Histogram, Time};
 use map_model::{
@@ -6,12 +6,15 @@ use map_model::{
     Traversable, TurnGroupID,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Analytics {
     pub road_thruput: TimeSeriesCount<RoadID>,
     pub intersection_thruput: TimeSeriesCount<IntersectionID>,
+    // Vehicles and people crossing each lane. A bus lane might carry few vehicles but many people.
+    pub lane_thruput: TimeSeriesCount<LaneID>,
+    pub lane_person_thruput: TimeSeriesCount<LaneID>,
 
     // Unlike everything else in Analytics, this is just for a moment in time.
     pub demand: BTreeMap<TurnGroupID, usize>,
@@ -27,12 +30,32 @@ pub struct Analytics {
     // Per parking lane or lot, when does a spot become filled (true) or free (false)
     pub parking_lane_changes: BTreeMap<LaneID, Vec<(Time, bool)>>,
     pub parking_lot_changes: BTreeMap<ParkingLotID, Vec<(Time, bool)>>,
+    // When a queue on the lane backed up into the upstream intersection
+    pub spillbacks: Vec<(Time, IntersectionID, LaneID)>,
+    // How many vehicles were waiting offscreen at each border, whenever that changed
+    #[serde(default)]
+    pub border_queues: BTreeMap<IntersectionID, Vec<(Time, usize)>>,
+    // Surrogates for crashes, since the simulation never has any
+    pub safety_events: Vec<(Time, IntersectionID, SafetyEvent)>,
+    // How long vehicles took to drive the full length of each road, including waiting to leave it,
+    // and when they left
+    pub road_traversals: BTreeMap<RoadID, Vec<(Time, Duration)>>,
+    // Vehicles that entered a road from an intersection, and when. Cars that appear in the middle
+    // of a road (from parking, a building, a border) aren't tracked until they reach the next one.
+    on_road: BTreeMap<CarID, (RoadID, Time)>,
+    in_turn: BTreeSet<CarID>,
     pub(crate) alerts: Vec<(Time, AlertLocation, String)>,
 
     // After we restore from a savestate, don't record anything. This is only going to make sense
     // if savestates are only used for quickly previewing against prebaked results, where we have
     // the full Analytics anyway.
     record_anything: bool,
+    // Metrics are only recorded between these times, so a warm-up or cool-down period doesn't
+    // pollute comparisons. Bookkeeping like demand and trip phases happens regardless.
+    #[serde(default)]
+    record_from: Option<Time>,
+    #[serde(default)]
+    pub(crate) record_until: Option<Time>,
 }
 
 impl Analytics {
@@ -40,6 +63,8 @@ impl Analytics {
         Analytics {
             road_thruput: TimeSeriesCount::new(),
             intersection_thruput: TimeSeriesCount::new(),
+            lane_thruput: TimeSeriesCount::new(),
+            lane_person_thruput: TimeSeriesCount::new(),
             demand: BTreeMap::new(),
             bus_arrivals: Vec::new(),
             bus_passengers_waiting: Vec::new(),
@@ -49,25 +74,50 @@ impl Analytics {
             intersection_delays: BTreeMap::new(),
             parking_lane_changes: BTreeMap::new(),
             parking_lot_changes: BTreeMap::new(),
+            spillbacks: Vec::new(),
+            border_queues: BTreeMap::new(),
+            safety_events: Vec::new(),
+            road_traversals: BTreeMap::new(),
+            on_road: BTreeMap::new(),
+            in_turn: BTreeSet::new(),
             alerts: Vec::new(),
             record_anything: true,
+            record_from: None,
+            record_until: None,
         }
     }
 
+    pub(crate) fn set_recording_window(&mut self, from: Option<Time>, until: Option<Time>) {
+        self.record_from = from;
+        self.record_until = until;
+    }
+
+    pub fn is_recording(&self, time: Time) -> bool {
+        self.record_from.map(|t| time >= t).unwrap_or(true)
+            && self.record_until.map(|t| time < t).unwrap_or(true)
+    }
+
     pub fn event(&mut self, ev: Event, time: Time, map: &Map) {
         if !self.record_anything {
             return;
         }
+        let record = self.is_recording(time);
 
         // Throughput
-        if let Event::AgentEntersTraversable(a, to) = ev {
+        if let Event::AgentEntersTraversable(a, to, people) = ev {
             let mode = TripMode::from_agent(a);
             match to {
                 Traversable::Lane(l) => {
-                    self.road_thruput.record(time, map.get_l(l).parent, mode);
+                    if record {
+                        self.road_thruput.record(time, map.get_l(l).parent, mode);
+                        self.lane_thruput.record(time, l, mode);
+                        self.lane_person_thruput.record_many(time, l, mode, people);
+                    }
                 }
                 Traversable::Turn(t) => {
-                    self.intersection_thruput.record(time, t.parent, mode);
+                    if record {
+                        self.intersection_thruput.record(time, t.parent, mode);
+                    }
 
                     if let Some(id) = map.get_turn_group(t) {
                         *self.demand.entry(id).or_insert(0) -= 1;
@@ -77,11 +127,52 @@ impl Analytics {
         }
         match ev {
             Event::PersonLeavesMap(_, mode, i, _) | Event::PersonEntersMap(_, mode, i, _) => {
-                self.intersection_thruput.record(time, i, mode);
+                if record {
+                    self.intersection_thruput.record(time, i, mode);
+                }
             }
             _ => {}
         }
 
+        // Road traversal times
+        if let Event::AgentEntersTraversable(AgentID::Car(car), to, _) = ev {
+            match to {
+                Traversable::Lane(l) => {
+                    if self.in_turn.remove(&car) {
+                        self.on_road.insert(car, (map.get_l(l).parent, time));
+                    }
+                }
+                Traversable::Turn(_) => {
+                    if let Some((r, t)) = self.on_road.remove(&car).filter(|_| record) {
+                        self.road_traversals
+                            .entry(r)
+                            .or_insert_with(Vec::new)
+                            .push((time, time - t));
+                    }
+                    self.in_turn.insert(car);
+                }
+            }
+        }
+        match ev {
+            Event::CarReachedParkingSpot(car, _) | Event::BikeStoppedAtSidewalk(car, _) => {
+                self.on_road.remove(&car);
+            }
+            _ => {}
+        }
+
+        // Started trips
+        if let Event::TripPhaseStarting(id, _, _, _) = ev {
+            self.started_trips.entry(id).or_insert(time);
+        }
+        if let Event::TripAborted(id) = ev {
+            self.started_trips.entry(id).or_insert(time);
+        }
+
+        if !record {
+            self.consume(ev, time, map);
+            return;
+        }
+
         // Bus arrivals
         if let Event::BusArrivedAtStop(bus, route, stop) = ev {
             self.bus_arrivals.push((time, bus, route, stop));
@@ -94,12 +185,18 @@ impl Analytics {
             }
         }
 
-        // Started trips
-        if let Event::TripPhaseStarting(id, _, _, _) = ev {
-            self.started_trips.entry(id).or_insert(time);
-        }
-
-        // Finished trips
+        // Finished trips. Trips that started before recording did are left out too, so every
+        // finished trip ran entirely after the warm-up.
+        let started_in_window = |trip: TripID| {
+            self.record_from
+                .map(|t| {
+                    self.started_trips
+                        .get(&trip)
+                        .map(|s| *s >= t)
+                        .unwrap_or(true)
+                })
+                .unwrap_or(true)
+        };
         if let Event::TripFinished {
             trip,
             mode,
@@ -107,11 +204,14 @@ impl Analytics {
             ..
         } = ev
         {
-            self.finished_trips
-                .push((time, trip, Some(mode), total_time));
+            if started_in_window(trip) {
+                self.finished_trips
+                    .push((time, trip, Some(mode), total_time));
+            }
         } else if let Event::TripAborted(id) = ev {
-            self.started_trips.entry(id).or_insert(time);
-            self.finished_trips.push((time, id, None, Duration::ZERO));
+            if started_in_window(id) {
+                self.finished_trips.push((time, id, None, Duration::ZERO));
+            }
         }
 
         // Intersection delays
@@ -122,6 +222,27 @@ impl Analytics {
                 .push((time, delay, mode));
         }
 
+        // Spillback
+        if let Event::QueueSpillback(i, l) = ev {
+            self.spillbacks.push((time, i, l));
+        }
+
+        // Border metering
+        if let Event::BorderQueueChanged(i, waiting) = ev {
+            self.border_queues
+                .entry(i)
+                .or_insert_with(Vec::new)
+                .push((time, waiting));
+        }
+
+        // Safety
+        if let Event::HardBraking(_, i) = ev {
+            self.safety_events.push((time, i, SafetyEvent::HardBraking));
+        }
+        if let Event::TightGap(_, i, _) = ev {
+            self.safety_events.push((time, i, SafetyEvent::TightGap));
+        }
+
         // Parking spot changes
         if let Event::CarReachedParkingSpot(_, spot) = ev {
             if let ParkingSpot::Onstreet(l, _) = spot {
@@ -150,7 +271,12 @@ impl Analytics {
             }
         }
 
-        // TODO Kinda hacky, but these all consume the event, so kinda bundle em.
+        self.consume(ev, time, map);
+    }
+
+    // TODO Kinda hacky, but these all consume the event, so kinda bundle em. These happen even
+    // outside the recording window; the UI needs them.
+    fn consume(&mut self, ev: Event, time: Time, map: &Map) {
         match ev {
             Event::TripPhaseStarting(id, _, maybe_req, phase_type) => {
                 self.trip_log.push((time, id, maybe_req, phase_type));
@@ -185,6 +311,18 @@ impl Analytics {
     // TODO If these ever need to be speeded up, just cache the histogram and index in the events
     // list.
 
+    // How many safety events of any kind happened at each intersection, up to some time
+    pub fn safety_hotspots(&self, now: Time) -> Counter<IntersectionID> {
+        let mut cnt = Counter::new();
+        for (t, i, _) in &self.safety_events {
+            if *t > now {
+                break;
+            }
+            cnt.inc(*i);
+        }
+        cnt
+    }
+
     // Ignores the current time. Returns None for aborted trips.
     pub fn finished_trip_time(&self, trip: TripID) -> Option<Duration> {
         // TODO This is so inefficient!
@@ -206,6 +344,18 @@ impl Analytics {
         now: Time,
         before: &Analytics,
     ) -> Vec<(Duration, Duration, TripMode)> {
+        self.both_finished_trips_by_id(now, before)
+            .into_iter()
+            .map(|(_, b, a, mode)| (b, a, mode))
+            .collect()
+    }
+
+    // Same as above, but also returns the trip.
+    pub fn both_finished_trips_by_id(
+        &self,
+        now: Time,
+        before: &Analytics,
+    ) -> Vec<(TripID, Duration, Duration, TripMode)> {
         let mut a = BTreeMap::new();
         for (t, id, maybe_mode, dt) in &self.finished_trips {
             if *t > now {
@@ -223,13 +373,35 @@ impl Analytics {
             }
             if let Some(mode) = maybe_mode {
                 if let Some(dt1) = a.remove(id) {
-                    results.push((*dt, dt1, *mode));
+                    results.push((*id, *dt, dt1, *mode));
                 }
             }
         }
         results
     }
 
+    // Per road, the total time vehicles spent on it beyond what driving at the speed limit would
+    // take. Only counts vehicles that left the road before now.
+    pub fn road_delays(&self, now: Time, map: &Map) -> BTreeMap<RoadID, Duration> {
+        let mut results = BTreeMap::new();
+        for (r, traversals) in &self.road_traversals {
+            let road = map.get_r(*r);
+            let free_flow = road.center_pts.length() / road.speed_limit;
+            let total = traversals.iter().filter(|(t, _)| *t <= now).fold(
+                Duration::ZERO,
+                |sum, (_, dt)| {
+                    if *dt > free_flow {
+                        sum + *dt - free_flow
+                    } else {
+                        sum
+                    }
+                },
+            );
+            results.insert(*r, total);
+        }
+        results
+    }
+
     // Find intersections where the cumulative sum of delay has changed. Negative means faster.
     pub fn compare_delay(&self, now: Time, before: &Analytics) -> Vec<(IntersectionID, Duration)> {
         let mut results = Vec::new();
@@ -531,6 +703,12 @@ impl Default for Analytics {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SafetyEvent {
+    HardBraking,
+    TightGap,
+}
+
 #[derive(Debug)]
 pub struct TripPhase {
     pub start_time: Time,
@@ -561,13 +739,19 @@ impl<X: Ord + Clone> TimeSeriesCount<X> {
     }
 
     fn record(&mut self, time: Time, id: X, mode: TripMode) {
+        self.record_many(time, id, mode, 1);
+    }
+
+    fn record_many(&mut self, time: Time, id: X, mode: TripMode, count: usize) {
         // TODO Manually change flag
         if false {
-            self.raw.push((time, mode, id.clone()));
+            for _ in 0..count {
+                self.raw.push((time, mode, id.clone()));
+            }
         }
 
         let hour = time.get_parts().0;
-        *self.counts.entry((id, mode, hour)).or_insert(0) += 1;
+        *self.counts.entry((id, mode, hour)).or_insert(0) += count;
     }
 
     pub fn total_for(&self, id: X) -> usize {
@@ -0,0 +1,270 @@
+use crate::{CarID, DriverBehavior, SidewalkSpot, Vehicle, VehicleSpec, BIKE_LENGTH};
+use geom::{Distance, Duration, Pt2D, Speed, Time};
+use map_model::{BuildingID, Map};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Shorter walking trips aren't worth the hassle of finding a bike.
+const MIN_TRIP_DIST: Distance = Distance::const_meters(1500.0);
+// How far people will walk to pick up or drop off a bike
+const MAX_WALK_TO_DOCK: Distance = Distance::const_meters(400.0);
+// When the map doesn't have any docks tagged, place one roughly this often.
+const DOCK_SPACING: f64 = 400.0;
+const DOCK_CAPACITY: usize = 15;
+const INITIAL_BIKES_PER_DOCK: usize = 10;
+// A crew with a van periodically evens out the fleet.
+const REBALANCE_FREQUENCY: Duration = Duration::const_seconds(3600.0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BikeShareKind {
+    // Bikes are picked up and dropped off at fixed docks with limited capacity.
+    Docked,
+    // Bikes are left at the rider's destination, and rebalancing gathers them back to the spots
+    // where the fleet started.
+    Dockless,
+}
+
+// Shared bikes are only picked up and dropped off at buildings, so riders can use the normal bike
+// rack logic. For a dockless fleet, each building where bikes have been left acts like a dock with
+// unlimited capacity.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BikeShareSystem {
+    kind: BikeShareKind,
+    docks: BTreeMap<BuildingID, Dock>,
+    // Bikes currently being ridden, and where they'll be dropped off
+    checked_out: BTreeMap<CarID, (Vehicle, BuildingID)>,
+    last_rebalance: Time,
+    pub bikes_rebalanced: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Dock {
+    pub bldg: BuildingID,
+    pub pt: Pt2D,
+    // None for dockless locations
+    pub capacity: Option<usize>,
+    pub bikes: usize,
+    // Slots promised to bikes currently being ridden here
+    reserved: usize,
+    // What rebalancing tries to restore
+    target: usize,
+
+    pub checkouts: usize,
+    pub returns: usize,
+    // Somebody wanted a bike here, but there weren't any
+    pub empty_misses: usize,
+    // Somebody wanted to drop off a bike here, but it was full
+    pub full_misses: usize,
+}
+
+impl Dock {
+    fn new(bldg: BuildingID, capacity: Option<usize>, bikes: usize, map: &Map) -> Dock {
+        Dock {
+            bldg,
+            pt: map.get_b(bldg).label_center,
+            capacity,
+            bikes,
+            reserved: 0,
+            target: bikes,
+            checkouts: 0,
+            returns: 0,
+            empty_misses: 0,
+            full_misses: 0,
+        }
+    }
+
+    fn has_room(&self) -> bool {
+        match self.capacity {
+            Some(cap) => self.bikes + self.reserved < cap,
+            None => true,
+        }
+    }
+
+    // 0 is empty, 1 is full. Dockless locations are measured against their starting stock.
+    pub fn fullness(&self) -> f64 {
+        let cap = self.capacity.unwrap_or(INITIAL_BIKES_PER_DOCK).max(1);
+        ((self.bikes as f64) / (cap as f64)).min(1.0)
+    }
+}
+
+impl BikeShareSystem {
+    pub fn new(map: &Map, kind: BikeShareKind) -> BikeShareSystem {
+        // Only buildings where somebody could actually start biking work as docks.
+        let usable = |b: BuildingID| {
+            SidewalkSpot::bike_from_bike_rack(map.get_b(b).sidewalk(), map).is_some()
+        };
+
+        let mut bldgs: Vec<BuildingID> = map
+            .all_buildings()
+            .iter()
+            .filter(|b| b.amenities.iter().any(|(_, a)| a == "bicycle_rental"))
+            .map(|b| b.id)
+            .filter(|b| usable(*b))
+            .collect();
+        if bldgs.is_empty() {
+            // Fake it with one dock per grid cell
+            let mut per_cell: BTreeMap<(i64, i64), BuildingID> = BTreeMap::new();
+            for b in map.all_buildings() {
+                let pt = b.label_center;
+                let cell = (
+                    (pt.x() / DOCK_SPACING) as i64,
+                    (pt.y() / DOCK_SPACING) as i64,
+                );
+                if !per_cell.contains_key(&cell) && usable(b.id) {
+                    per_cell.insert(cell, b.id);
+                }
+            }
+            bldgs = per_cell.into_iter().map(|(_, b)| b).collect();
+        }
+
+        let capacity = match kind {
+            BikeShareKind::Docked => Some(DOCK_CAPACITY),
+            BikeShareKind::Dockless => None,
+        };
+        BikeShareSystem {
+            kind,
+            docks: bldgs
+                .into_iter()
+                .map(|b| (b, Dock::new(b, capacity, INITIAL_BIKES_PER_DOCK, map)))
+                .collect(),
+            checked_out: BTreeMap::new(),
+            last_rebalance: Time::START_OF_DAY,
+            bikes_rebalanced: 0,
+        }
+    }
+
+    pub fn kind(&self) -> BikeShareKind {
+        self.kind
+    }
+
+    pub fn get_docks(&self) -> Vec<&Dock> {
+        self.docks.values().collect()
+    }
+
+    pub fn num_bikes_in_use(&self) -> usize {
+        self.checked_out.len()
+    }
+
+    // If using a shared bike makes sense for this walking trip, reserves the bike and the drop-off
+    // spot, and returns where to pick up and drop off. The bike gets the given ID.
+    pub(crate) fn maybe_checkout(
+        &mut self,
+        now: Time,
+        start: Pt2D,
+        goal: BuildingID,
+        bike: CarID,
+        map: &Map,
+    ) -> Option<(BuildingID, BuildingID)> {
+        let goal_pt = map.get_b(goal).label_center;
+        if start.dist_to(goal_pt) < MIN_TRIP_DIST {
+            return None;
+        }
+        if now - self.last_rebalance >= REBALANCE_FREQUENCY {
+            self.rebalance();
+            self.last_rebalance = now;
+        }
+
+        let pickup = self.closest(start, |d| d.bikes > 0);
+        let dropoff = match self.kind {
+            BikeShareKind::Docked => self.closest(goal_pt, |d| d.has_room()),
+            BikeShareKind::Dockless => Some(goal),
+        };
+        let (pickup, dropoff) = match (pickup, dropoff) {
+            (Some(p), Some(d)) if p != d => (p, d),
+            _ => {
+                // Only count a miss if there was a dock nearby that would've worked.
+                if pickup.is_none() {
+                    if let Some(b) = self.closest(start, |_| true) {
+                        self.docks.get_mut(&b).unwrap().empty_misses += 1;
+                    }
+                }
+                if dropoff.is_none() {
+                    if let Some(b) = self.closest(goal_pt, |_| true) {
+                        self.docks.get_mut(&b).unwrap().full_misses += 1;
+                    }
+                }
+                return None;
+            }
+        };
+
+        {
+            let dock = self.docks.get_mut(&pickup).unwrap();
+            dock.bikes -= 1;
+            dock.checkouts += 1;
+        }
+        self.docks
+            .entry(dropoff)
+            .or_insert_with(|| Dock::new(dropoff, None, 0, map))
+            .reserved += 1;
+
+        let vehicle = VehicleSpec {
+            vehicle_type: bike.1,
+            length: BIKE_LENGTH,
+            max_speed: Some(Speed::miles_per_hour(9.0)),
+            occupancy: 1,
+            behavior: DriverBehavior::TYPICAL,
+        }
+        .make(bike, None);
+        self.checked_out.insert(bike, (vehicle, dropoff));
+        Some((pickup, dropoff))
+    }
+
+    pub(crate) fn lookup_vehicle(&self, bike: CarID) -> Option<Vehicle> {
+        self.checked_out.get(&bike).map(|(v, _)| v.clone())
+    }
+
+    // Returns false if this isn't a shared bike. Aborted trips also return the bike, as if it were
+    // dropped off at the intended spot.
+    pub(crate) fn return_bike(&mut self, bike: CarID) -> bool {
+        if let Some((_, dropoff)) = self.checked_out.remove(&bike) {
+            let dock = self.docks.get_mut(&dropoff).unwrap();
+            dock.reserved -= 1;
+            dock.bikes += 1;
+            dock.returns += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn closest<F: Fn(&Dock) -> bool>(&self, pt: Pt2D, ok: F) -> Option<BuildingID> {
+        self.docks
+            .values()
+            .filter(|d| d.capacity.is_some() || d.target > 0 || d.bikes > 0)
+            .filter(|d| ok(d))
+            .map(|d| (d.pt.dist_to(pt), d.bldg))
+            .filter(|(dist, _)| *dist <= MAX_WALK_TO_DOCK)
+            .min_by_key(|(dist, _)| *dist)
+            .map(|(_, b)| b)
+    }
+
+    // Move bikes from places with a surplus to places with a deficit, in one go.
+    fn rebalance(&mut self) {
+        let mut surplus = 0;
+        for dock in self.docks.values_mut() {
+            if dock.bikes > dock.target {
+                surplus += dock.bikes - dock.target;
+                dock.bikes = dock.target;
+            }
+        }
+        self.bikes_rebalanced += surplus;
+        for dock in self.docks.values_mut() {
+            while surplus > 0 && dock.bikes < dock.target && dock.has_room() {
+                dock.bikes += 1;
+                surplus -= 1;
+            }
+        }
+        // If everywhere is already at its target, put the leftovers back wherever there's room,
+        // starting with the original locations.
+        for dock in self.docks.values_mut() {
+            if dock.target == 0 {
+                continue;
+            }
+            while surplus > 0 && dock.has_room() {
+                dock.bikes += 1;
+                surplus -= 1;
+            }
+        }
+        assert_eq!(surplus, 0);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::neighborhoods::{cell, cell_name, endpoint_pt};
+use crate::{
+    AgentID, CarID, Event, ParkingSimState, ParkingSpot, PersonID, PersonState, Sim, TripID,
+    TripManager, TripPhaseType, VehicleType,
+};
+use abstutil::{deserialize_btreemap, serialize_btreemap};
+use geom::{Duration, Time};
+use map_model::{LaneID, Map, ParkingLotID, RoadID, Traversable};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// What everything costs, in dollars. Per-lane and per-lot prices override the defaults, so
+// pricing changes can target individual blockfaces.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pricing {
+    // Fuel or energy, by distance driven
+    pub driving_per_km: f64,
+    pub onstreet_parking_per_hour: f64,
+    pub lot_parking_per_hour: f64,
+    // Every time somebody boards a bus
+    pub transit_fare: f64,
+
+    pub onstreet_overrides: BTreeMap<LaneID, f64>,
+    pub lot_overrides: BTreeMap<ParkingLotID, f64>,
+    // Charged every time a vehicle enters the road
+    pub tolls: BTreeMap<RoadID, f64>,
+}
+
+impl Pricing {
+    pub fn new() -> Pricing {
+        Pricing {
+            driving_per_km: 0.15,
+            onstreet_parking_per_hour: 2.0,
+            lot_parking_per_hour: 4.0,
+            transit_fare: 2.75,
+            onstreet_overrides: BTreeMap::new(),
+            lot_overrides: BTreeMap::new(),
+            tolls: BTreeMap::new(),
+        }
+    }
+
+    // Per hour. Private parking inside buildings is free.
+    pub fn parking_rate(&self, spot: ParkingSpot) -> f64 {
+        match spot {
+            ParkingSpot::Onstreet(l, _) => *self
+                .onstreet_overrides
+                .get(&l)
+                .unwrap_or(&self.onstreet_parking_per_hour),
+            ParkingSpot::Offstreet(_, _) => 0.0,
+            ParkingSpot::Lot(pl, _) => *self
+                .lot_overrides
+                .get(&pl)
+                .unwrap_or(&self.lot_parking_per_hour),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TripCost {
+    pub fuel: f64,
+    pub parking: f64,
+    pub tolls: f64,
+    pub fares: f64,
+}
+
+impl TripCost {
+    pub fn total(&self) -> f64 {
+        self.fuel + self.parking + self.tolls + self.fares
+    }
+
+    pub fn add(&mut self, other: &TripCost) {
+        self.fuel += other.fuel;
+        self.parking += other.parking;
+        self.tolls += other.tolls;
+        self.fares += other.fares;
+    }
+}
+
+// Adds up what each trip spends as the simulation runs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TripCosts {
+    pricing: Pricing,
+    trips: BTreeMap<TripID, TripCost>,
+    // Parking is paid when the car leaves, by the trip that parked it there.
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    parked: BTreeMap<CarID, (ParkingSpot, Time, TripID)>,
+}
+
+impl TripCosts {
+    pub(crate) fn new(pricing: Pricing) -> TripCosts {
+        TripCosts {
+            pricing,
+            trips: BTreeMap::new(),
+            parked: BTreeMap::new(),
+        }
+    }
+
+    // Whatever's already been charged keeps the old prices.
+    pub(crate) fn set_pricing(&mut self, pricing: Pricing) {
+        self.pricing = pricing;
+    }
+
+    pub(crate) fn event(
+        &mut self,
+        ev: &Event,
+        time: Time,
+        map: &Map,
+        trips: &TripManager,
+        parking: &ParkingSimState,
+    ) {
+        match ev {
+            Event::AgentEntersTraversable(AgentID::Car(car), Traversable::Lane(l), _) => {
+                if car.1 != VehicleType::Car {
+                    return;
+                }
+                if let Some(trip) = trips.agent_to_trip(AgentID::Car(*car)) {
+                    let lane = map.get_l(*l);
+                    let cost = self.trips.entry(trip).or_insert_with(TripCost::default);
+                    cost.fuel +=
+                        lane.length().inner_meters() / 1000.0 * self.pricing.driving_per_km;
+                    if let Some(toll) = self.pricing.tolls.get(&lane.parent) {
+                        cost.tolls += *toll;
+                    }
+                }
+            }
+            Event::CarReachedParkingSpot(car, spot) => {
+                // The car isn't an agent anymore, so find the trip through its owner.
+                if let Some(person) = parking.get_owner_of_car(*car) {
+                    if let PersonState::Trip(trip) = trips.get_person(person).unwrap().state {
+                        self.parked.insert(*car, (*spot, time, trip));
+                    }
+                }
+            }
+            Event::CarLeftParkingSpot(car, _) => {
+                if let Some((spot, since, trip)) = self.parked.remove(car) {
+                    self.trips
+                        .entry(trip)
+                        .or_insert_with(TripCost::default)
+                        .parking += parking_fee(&self.pricing, spot, time - since);
+                }
+            }
+            Event::TripPhaseStarting(trip, _, _, TripPhaseType::RidingBus(_, _, _)) => {
+                self.trips
+                    .entry(*trip)
+                    .or_insert_with(TripCost::default)
+                    .fares += self.pricing.transit_fare;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn get_pricing(&self) -> &Pricing {
+        &self.pricing
+    }
+
+    // Includes parking for cars that haven't left their spot yet.
+    pub fn trip(&self, id: TripID, now: Time) -> TripCost {
+        let mut cost = self.trips.get(&id).cloned().unwrap_or_default();
+        for (spot, since, trip) in self.parked.values() {
+            if *trip == id {
+                cost.parking += parking_fee(&self.pricing, *spot, now - *since);
+            }
+        }
+        cost
+    }
+
+    pub fn all_trips(&self, now: Time) -> BTreeMap<TripID, TripCost> {
+        let mut all = self.trips.clone();
+        for (spot, since, trip) in self.parked.values() {
+            all.entry(*trip).or_insert_with(TripCost::default).parking +=
+                parking_fee(&self.pricing, *spot, now - *since);
+        }
+        all
+    }
+
+    pub fn by_person(&self, sim: &Sim) -> BTreeMap<PersonID, TripCost> {
+        let mut people: BTreeMap<PersonID, TripCost> = BTreeMap::new();
+        for (trip, cost) in self.all_trips(sim.time()) {
+            people
+                .entry(sim.trip_to_person(trip))
+                .or_insert_with(TripCost::default)
+                .add(&cost);
+        }
+        people
+    }
+
+    // Keyed by the same names as NeighborhoodScoreboard. Trips are grouped by where they start.
+    pub fn by_neighborhood(&self, map: &Map, sim: &Sim) -> BTreeMap<String, TripCost> {
+        let mut cells: BTreeMap<String, TripCost> = BTreeMap::new();
+        for (trip, cost) in self.all_trips(sim.time()) {
+            let (x, y) = cell(map, endpoint_pt(map, &sim.trip_info(trip).1));
+            cells
+                .entry(cell_name(x, y))
+                .or_insert_with(TripCost::default)
+                .add(&cost);
+        }
+        cells
+    }
+}
+
+impl Default for TripCosts {
+    fn default() -> TripCosts {
+        TripCosts::new(Pricing::new())
+    }
+}
+
+fn parking_fee(pricing: &Pricing, spot: ParkingSpot, dt: Duration) -> f64 {
+    pricing.parking_rate(spot) * dt.inner_seconds() / 3600.0
+}
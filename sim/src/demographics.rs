@@ -0,0 +1,243 @@
+use crate::{Analytics, PersonID, Sim};
+use geom::Duration;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Demographics {
+    // None if the scenario didn't say
+    pub income: Option<IncomeBracket>,
+    pub age: Option<AgeGroup>,
+    // Derived from the person's vehicles, not configured
+    pub owns_car: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IncomeBracket {
+    Low,
+    Middle,
+    High,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AgeGroup {
+    Child,
+    Adult,
+    Senior,
+}
+
+// Scenarios don't come with real demographics, so just describe the mix of people. Whoever's left
+// over is middle income or an adult.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DemographicsConfig {
+    pub pct_low_income: f64,
+    pub pct_high_income: f64,
+    pub pct_children: f64,
+    pub pct_seniors: f64,
+}
+
+impl DemographicsConfig {
+    // Seeded by the person, so the assignment is stable no matter what else the scenario or map
+    // edits change.
+    pub(crate) fn assign(&self, person: PersonID, owns_car: bool) -> Demographics {
+        let mut rng = XorShiftRng::seed_from_u64(person.0 as u64);
+        let x = rng.gen_range(0.0, 1.0);
+        let income = if x < self.pct_low_income {
+            IncomeBracket::Low
+        } else if x < self.pct_low_income + self.pct_high_income {
+            IncomeBracket::High
+        } else {
+            IncomeBracket::Middle
+        };
+        let x = rng.gen_range(0.0, 1.0);
+        let age = if x < self.pct_children {
+            AgeGroup::Child
+        } else if x < self.pct_children + self.pct_seniors {
+            AgeGroup::Senior
+        } else {
+            AgeGroup::Adult
+        };
+        Demographics {
+            income: Some(income),
+            age: Some(age),
+            owns_car,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DemographicGroup {
+    Income(IncomeBracket),
+    Age(AgeGroup),
+    CarOwnership(bool),
+}
+
+impl DemographicGroup {
+    // What kind of group this is
+    pub fn attribute(self) -> &'static str {
+        match self {
+            DemographicGroup::Income(_) => "income",
+            DemographicGroup::Age(_) => "age",
+            DemographicGroup::CarOwnership(_) => "car ownership",
+        }
+    }
+
+    // Every possible group, for letting people pick one
+    pub fn all() -> Vec<DemographicGroup> {
+        vec![
+            DemographicGroup::Income(IncomeBracket::Low),
+            DemographicGroup::Income(IncomeBracket::Middle),
+            DemographicGroup::Income(IncomeBracket::High),
+            DemographicGroup::Age(AgeGroup::Child),
+            DemographicGroup::Age(AgeGroup::Adult),
+            DemographicGroup::Age(AgeGroup::Senior),
+            DemographicGroup::CarOwnership(true),
+            DemographicGroup::CarOwnership(false),
+        ]
+    }
+
+    pub fn contains(self, d: &Demographics) -> bool {
+        DemographicGroup::all_for(d).contains(&self)
+    }
+
+    fn all_for(d: &Demographics) -> Vec<DemographicGroup> {
+        let mut groups = vec![DemographicGroup::CarOwnership(d.owns_car)];
+        if let Some(income) = d.income {
+            groups.push(DemographicGroup::Income(income));
+        }
+        if let Some(age) = d.age {
+            groups.push(DemographicGroup::Age(age));
+        }
+        groups
+    }
+}
+
+impl fmt::Display for DemographicGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DemographicGroup::Income(IncomeBracket::Low) => write!(f, "low income"),
+            DemographicGroup::Income(IncomeBracket::Middle) => write!(f, "middle income"),
+            DemographicGroup::Income(IncomeBracket::High) => write!(f, "high income"),
+            DemographicGroup::Age(AgeGroup::Child) => write!(f, "children"),
+            DemographicGroup::Age(AgeGroup::Adult) => write!(f, "adults"),
+            DemographicGroup::Age(AgeGroup::Senior) => write!(f, "seniors"),
+            DemographicGroup::CarOwnership(true) => write!(f, "car owners"),
+            DemographicGroup::CarOwnership(false) => write!(f, "without a car"),
+        }
+    }
+}
+
+// Like the NeighborhoodScoreboard, but splitting trips by who takes them. Somebody belongs to one
+// group per attribute, so the groups overlap.
+pub struct EquityScoreboard {
+    pub scores: Vec<GroupScore>,
+}
+
+#[derive(Clone)]
+pub struct GroupScore {
+    pub group: DemographicGroup,
+    pub num_people: usize,
+
+    // Only trips that finished both before and after the edits count.
+    pub num_trips: usize,
+    pub num_faster: usize,
+    pub num_slower: usize,
+    pub total_before: Duration,
+    pub total_after: Duration,
+}
+
+impl GroupScore {
+    pub fn avg_before(&self) -> Option<Duration> {
+        if self.num_trips == 0 {
+            None
+        } else {
+            Some(self.total_before / (self.num_trips as f64))
+        }
+    }
+
+    pub fn avg_after(&self) -> Option<Duration> {
+        if self.num_trips == 0 {
+            None
+        } else {
+            Some(self.total_after / (self.num_trips as f64))
+        }
+    }
+
+    // Negative means the group got faster overall.
+    pub fn pct_change(&self) -> Option<f64> {
+        if self.total_before == Duration::ZERO {
+            None
+        } else {
+            Some((self.total_after - self.total_before) / self.total_before)
+        }
+    }
+}
+
+impl EquityScoreboard {
+    pub fn new(sim: &Sim, before: &Analytics) -> EquityScoreboard {
+        let mut scores: BTreeMap<DemographicGroup, GroupScore> = BTreeMap::new();
+
+        for person in sim.get_all_people() {
+            for group in DemographicGroup::all_for(&person.demographics) {
+                score(&mut scores, group).num_people += 1;
+            }
+        }
+
+        for (id, dt_before, dt_after, _) in sim
+            .get_analytics()
+            .both_finished_trips_by_id(sim.time(), before)
+        {
+            let person = sim.get_person(sim.trip_to_person(id));
+            for group in DemographicGroup::all_for(&person.demographics) {
+                let s = score(&mut scores, group);
+                s.num_trips += 1;
+                if dt_after < dt_before {
+                    s.num_faster += 1;
+                } else if dt_after > dt_before {
+                    s.num_slower += 1;
+                }
+                s.total_before += dt_before;
+                s.total_after += dt_after;
+            }
+        }
+
+        EquityScoreboard {
+            scores: scores.into_iter().map(|(_, s)| s).collect(),
+        }
+    }
+
+    // Average the change of every group under one attribute, so a small group counts just as much
+    // as a big one. Edits that speed up the majority at a minority's expense look worse here than
+    // in the overall totals.
+    pub fn equity_weighted_change(&self, attribute: &str) -> Option<f64> {
+        let changes: Vec<f64> = self
+            .scores
+            .iter()
+            .filter(|s| s.group.attribute() == attribute)
+            .filter_map(|s| s.pct_change())
+            .collect();
+        if changes.is_empty() {
+            None
+        } else {
+            Some(changes.iter().sum::<f64>() / (changes.len() as f64))
+        }
+    }
+}
+
+fn score(
+    scores: &mut BTreeMap<DemographicGroup, GroupScore>,
+    group: DemographicGroup,
+) -> &mut GroupScore {
+    scores.entry(group).or_insert_with(|| GroupScore {
+        group,
+        num_people: 0,
+        num_trips: 0,
+        num_faster: 0,
+        num_slower: 0,
+        total_before: Duration::ZERO,
+        total_after: Duration::ZERO,
+    })
+}
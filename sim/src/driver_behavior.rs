@@ -0,0 +1,181 @@
+use crate::{PersonID, VehicleSpec, VehicleType, FOLLOWING_DISTANCE};
+use geom::{Distance, Duration, Speed};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+
+// How one driver behaves. Unless the scenario says otherwise, everybody drives exactly the same.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DriverBehavior {
+    // Relative to the speed limit. The vehicle's max_speed still applies.
+    pub speed_factor: f64,
+    // The gap kept to whoever's in front, from our front to their back
+    pub following_distance: Distance,
+    // Multiplies how long to wait before starting a turn that has to yield. Under 1 means
+    // accepting tighter gaps.
+    pub yield_wait_factor: f64,
+}
+
+impl DriverBehavior {
+    pub const TYPICAL: DriverBehavior = DriverBehavior {
+        speed_factor: 1.0,
+        following_distance: FOLLOWING_DISTANCE,
+        yield_wait_factor: 1.0,
+    };
+
+    pub fn desired_speed(&self, speed_limit: Speed) -> Speed {
+        speed_limit * self.speed_factor
+    }
+
+    pub fn yield_wait(&self, base: Duration) -> Duration {
+        base * self.yield_wait_factor
+    }
+}
+
+// Every driver gets their own parameters, spread uniformly between the low and high end of each
+// range. Only cars are affected; bikes already vary by max_speed, and buses follow a schedule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DriverBehaviorConfig {
+    pub speed_factor: (f64, f64),
+    pub following_distance: (Distance, Distance),
+    pub yield_wait_factor: (f64, f64),
+}
+
+impl DriverBehaviorConfig {
+    // Made-up, but a plausible mix of timid and aggressive drivers
+    pub fn mixed() -> DriverBehaviorConfig {
+        DriverBehaviorConfig {
+            speed_factor: (0.85, 1.15),
+            following_distance: (Distance::meters(0.5), Distance::meters(2.5)),
+            yield_wait_factor: (0.5, 2.0),
+        }
+    }
+
+    // Seeded by the person, so the assignment is stable no matter what else the scenario or map
+    // edits change.
+    pub(crate) fn assign(&self, person: PersonID, vehicles: &mut Vec<VehicleSpec>) {
+        let mut rng = XorShiftRng::seed_from_u64(person.0 as u64);
+        for v in vehicles {
+            if v.vehicle_type != VehicleType::Car {
+                continue;
+            }
+            v.behavior = DriverBehavior {
+                speed_factor: rand_between(&mut rng, self.speed_factor),
+                following_distance: Distance::meters(rand_between(
+                    &mut rng,
+                    (
+                        self.following_distance.0.inner_meters(),
+                        self.following_distance.1.inner_meters(),
+                    ),
+                )),
+                yield_wait_factor: rand_between(&mut rng, self.yield_wait_factor),
+            };
+        }
+    }
+}
+
+// Tolerates an empty range, meaning everybody gets the same value
+fn rand_between(rng: &mut XorShiftRng, (low, high): (f64, f64)) -> f64 {
+    if high > low {
+        rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scenario;
+
+    // Everybody owns a car and a bike
+    fn assign_all(config: &DriverBehaviorConfig, num_people: usize) -> Vec<Vec<VehicleSpec>> {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        (0..num_people)
+            .map(|p| {
+                let mut vehicles =
+                    vec![Scenario::rand_car(&mut rng), Scenario::rand_bike(&mut rng)];
+                config.assign(PersonID(p), &mut vehicles);
+                vehicles
+            })
+            .collect()
+    }
+
+    fn mean(values: &[f64]) -> f64 {
+        values.iter().sum::<f64>() / (values.len() as f64)
+    }
+
+    #[test]
+    fn samples_cover_the_ranges() {
+        let config = DriverBehaviorConfig::mixed();
+        let cars: Vec<DriverBehavior> = assign_all(&config, 1000)
+            .into_iter()
+            .map(|vehicles| vehicles[0].behavior)
+            .collect();
+
+        let speed: Vec<f64> = cars.iter().map(|b| b.speed_factor).collect();
+        let follow: Vec<f64> = cars
+            .iter()
+            .map(|b| b.following_distance.inner_meters())
+            .collect();
+        let yield_wait: Vec<f64> = cars.iter().map(|b| b.yield_wait_factor).collect();
+        for (values, (low, high)) in vec![
+            (speed, config.speed_factor),
+            (
+                follow,
+                (
+                    config.following_distance.0.inner_meters(),
+                    config.following_distance.1.inner_meters(),
+                ),
+            ),
+            (yield_wait, config.yield_wait_factor),
+        ] {
+            assert!(values.iter().all(|x| *x >= low && *x < high));
+            // Uniform, so the mean is near the middle and both ends get used
+            let width = high - low;
+            assert!((mean(&values) - (low + high) / 2.0).abs() < 0.05 * width);
+            assert!(values.iter().any(|x| *x < low + 0.1 * width));
+            assert!(values.iter().any(|x| *x > high - 0.1 * width));
+        }
+    }
+
+    #[test]
+    fn only_cars_vary() {
+        for vehicles in assign_all(&DriverBehaviorConfig::mixed(), 100) {
+            assert_eq!(vehicles[1].vehicle_type, VehicleType::Bike);
+            assert_eq!(vehicles[1].behavior, DriverBehavior::TYPICAL);
+        }
+    }
+
+    #[test]
+    fn stable_per_person() {
+        let config = DriverBehaviorConfig::mixed();
+        assert_eq!(assign_all(&config, 10), assign_all(&config, 10));
+
+        // Somebody's draws don't depend on who else is in the scenario, but differ from others'
+        assert_eq!(assign_all(&config, 1)[0], assign_all(&config, 5)[0]);
+        assert_ne!(
+            assign_all(&config, 2)[1][0].behavior,
+            assign_all(&config, 2)[0][0].behavior
+        );
+    }
+
+    #[test]
+    fn empty_range_is_exact() {
+        let config = DriverBehaviorConfig {
+            speed_factor: (1.1, 1.1),
+            following_distance: (Distance::meters(1.0), Distance::meters(1.0)),
+            yield_wait_factor: (0.5, 0.5),
+        };
+        for vehicles in assign_all(&config, 10) {
+            assert_eq!(
+                vehicles[0].behavior,
+                DriverBehavior {
+                    speed_factor: 1.1,
+                    following_distance: Distance::meters(1.0),
+                    yield_wait_factor: 0.5,
+                }
+            );
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{AgentID, CarID, Event, TripID};
+use abstutil::Timer;
+use geom::{Duration, Time};
+use map_model::{LaneID, Map, Path, PathConstraints, PathRequest, PathStep, Traversable};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Congestion changes through the day, so lane times are averaged separately for each window of
+// this long.
+const TIME_BIN: Duration = Duration::const_seconds(15.0 * 60.0);
+
+// Route choices made by vehicles in a previous run of the same scenario. Seeding a new run with
+// these is a form of iterative assignment: most trips stick with what they did last time, but a
+// shrinking fraction of them (method of successive averages) reroute using the congested lane
+// times observed last time, at the time of day they'd be driving. Over enough iterations, this
+// converges towards user equilibrium, instead of everybody naively taking the free-flow shortest
+// path.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PriorRoutes {
+    pub map_name: String,
+    pub scenario_name: String,
+    // How many runs have been folded into these routes
+    pub iteration: usize,
+    // The request is stored to detect when map edits or a different parking spot changed the trip
+    routes: BTreeMap<TripID, (PathRequest, Vec<PathStep>)>,
+    // Average time vehicles spent on each lane, including waiting at the end of it, grouped by
+    // the TIME_BIN when they entered the lane
+    lane_times: BTreeMap<(LaneID, usize), Duration>,
+}
+
+impl PriorRoutes {
+    pub fn load(map_name: &str, scenario_name: &str, timer: &mut Timer) -> Option<PriorRoutes> {
+        abstutil::maybe_read_binary(abstutil::path_prior_routes(map_name, scenario_name), timer)
+            .ok()
+    }
+
+    pub fn save(&self) {
+        abstutil::write_binary(
+            abstutil::path_prior_routes(&self.map_name, &self.scenario_name),
+            self,
+        );
+    }
+
+    pub fn num_routes(&self) -> usize {
+        self.routes.len()
+    }
+
+    // Returns None if there's no prior route for this trip, or if it doesn't apply anymore. The
+    // caller should fall back to normal pathfinding.
+    pub(crate) fn pathfind(
+        &self,
+        now: Time,
+        trip: TripID,
+        req: &PathRequest,
+        map: &Map,
+    ) -> Option<Path> {
+        let (prev_req, steps) = self.routes.get(&trip)?;
+        if prev_req != req {
+            return None;
+        }
+
+        if self.should_reroute(trip) {
+            return self.congested_pathfind(req, now, map);
+        }
+
+        // Edits may have made the old route impossible.
+        for step in steps {
+            let ok = match step {
+                PathStep::Lane(l) | PathStep::ContraflowLane(l) => map
+                    .maybe_get_l(*l)
+                    .map(|lane| req.constraints.can_use(lane, map))
+                    .unwrap_or(false),
+                PathStep::Turn(t) => map.maybe_get_t(*t).is_some(),
+            };
+            if !ok {
+                return None;
+            }
+        }
+        Some(Path::new(map, steps.clone(), req.end.dist_along()))
+    }
+
+    // After n iterations, 1/(n+1) of trips switch. The choice is deterministic per trip, so
+    // re-running the same iteration gives the same results.
+    fn should_reroute(&self, trip: TripID) -> bool {
+        let n = self.iteration + 1;
+        (trip.0 + self.iteration) % n == 0
+    }
+
+    // Falls back to free-flow if nobody entered the lane around that time last run.
+    fn lane_time(&self, l: LaneID, entered: Time, map: &Map) -> Duration {
+        if let Some(dt) = self.lane_times.get(&(l, time_bin(entered))) {
+            return *dt;
+        }
+        let lane = map.get_l(l);
+        lane.length() / map.get_r(lane.parent).speed_limit
+    }
+
+    // Weighted by the lane times observed around when the vehicle would reach each lane. This
+    // ignores uber-turns; the routes are only used as a starting point anyway.
+    fn congested_pathfind(&self, req: &PathRequest, departure: Time, map: &Map) -> Option<Path> {
+        map.pathfind_time_dependent(req.clone(), departure, |l, t| self.lane_time(l, t, map))
+    }
+}
+
+// Watches a running simulation to produce PriorRoutes for the next run.
+#[derive(Clone, Default)]
+pub(crate) struct RouteRecorder {
+    routes: BTreeMap<TripID, (PathRequest, Vec<PathStep>)>,
+    // When did each vehicle enter its current lane?
+    entered_lane: BTreeMap<CarID, (LaneID, Time)>,
+    // Sum of time spent and number of vehicles per lane and TIME_BIN
+    lane_times: BTreeMap<(LaneID, usize), (Duration, usize)>,
+}
+
+impl RouteRecorder {
+    pub fn record_route(&mut self, trip: TripID, req: &PathRequest, path: &Path) {
+        match req.constraints {
+            PathConstraints::Car | PathConstraints::Bike | PathConstraints::Truck => {}
+            PathConstraints::Bus | PathConstraints::Pedestrian => {
+                return;
+            }
+        }
+        self.routes.insert(
+            trip,
+            (req.clone(), path.get_steps().iter().cloned().collect()),
+        );
+    }
+
+    pub fn event(&mut self, ev: &Event, time: Time) {
+        if let Event::AgentEntersTraversable(AgentID::Car(car), on, _) = ev {
+            if let Some((l, entered)) = self.entered_lane.remove(car) {
+                let entry = self
+                    .lane_times
+                    .entry((l, time_bin(entered)))
+                    .or_insert((Duration::ZERO, 0));
+                entry.0 += time - entered;
+                entry.1 += 1;
+            }
+            if let Traversable::Lane(l) = on {
+                self.entered_lane.insert(*car, (*l, time));
+            }
+        }
+    }
+
+    pub fn finish(
+        &self,
+        map_name: String,
+        scenario_name: String,
+        prev: Option<&PriorRoutes>,
+    ) -> PriorRoutes {
+        PriorRoutes {
+            map_name,
+            scenario_name,
+            iteration: prev.map(|p| p.iteration + 1).unwrap_or(1),
+            routes: self.routes.clone(),
+            lane_times: self
+                .lane_times
+                .iter()
+                .map(|(key, (sum, cnt))| (*key, *sum / (*cnt as f64)))
+                .collect(),
+        }
+    }
+}
+
+fn time_bin(t: Time) -> usize {
+    (t.inner_seconds() / TIME_BIN.inner_seconds()) as usize
+}
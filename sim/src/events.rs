@@ -29,8 +29,20 @@ pub enum Event {
 
     BikeStoppedAtSidewalk(CarID, LaneID),
 
-    AgentEntersTraversable(AgentID, Traversable),
+    // Also the number of people in the vehicle, or 1 for pedestrians
+    AgentEntersTraversable(AgentID, Traversable, usize),
     IntersectionDelayMeasured(IntersectionID, Duration, TripMode),
+    // A car with permission to turn at the intersection couldn't, because the queue on the lane
+    // it's turning into is full. That queue is backing up from the lane's downstream end.
+    QueueSpillback(IntersectionID, LaneID),
+    // How many vehicles are now waiting offscreen to enter the map at this border
+    BorderQueueChanged(IntersectionID, usize),
+    // A vehicle reached a traffic signal that turned against it too recently to stop comfortably.
+    // The simulation doesn't model deceleration, so this is estimated from the approach speed.
+    HardBraking(CarID, IntersectionID),
+    // An agent started a turn soon after a conflicting one finished (or while it's still going,
+    // when conflicts are allowed). Also how long the gap was.
+    TightGap(AgentID, IntersectionID, Duration),
 
     TripFinished {
         trip: TripID,
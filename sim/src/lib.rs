@@ -1,8 +1,17 @@
+mod accessibility;
 mod analytics;
+mod bike_share;
+mod costs;
+mod demographics;
+mod driver_behavior;
+mod equilibrium;
 mod events;
 mod make;
 mod mechanics;
+mod message_signs;
+mod neighborhoods;
 mod pandemic;
+mod reliability;
 mod render;
 mod router;
 mod scheduler;
@@ -10,20 +19,35 @@ mod sim;
 mod transit;
 mod trips;
 
-pub use self::analytics::{Analytics, TripPhase};
+pub use self::accessibility::{AccessibilityScores, ACCESSIBILITY_TIME_LIMIT};
+pub use self::analytics::{Analytics, SafetyEvent, TripPhase};
+pub use self::bike_share::{BikeShareKind, BikeShareSystem, Dock};
+pub use self::costs::{Pricing, TripCost, TripCosts};
+pub use self::demographics::{
+    AgeGroup, DemographicGroup, Demographics, DemographicsConfig, EquityScoreboard, GroupScore,
+    IncomeBracket,
+};
+pub use self::driver_behavior::{DriverBehavior, DriverBehaviorConfig};
+pub use self::equilibrium::PriorRoutes;
+pub(crate) use self::equilibrium::RouteRecorder;
 pub(crate) use self::events::Event;
 pub use self::events::{AlertLocation, TripPhaseType};
 pub use self::make::{
-    BorderSpawnOverTime, IndividTrip, OffMapLocation, OriginDestination, PersonSpec, Scenario,
-    ScenarioGenerator, SimFlags, SpawnOverTime, SpawnTrip, TripSpawner, TripSpec,
+    Activity, ActivityModel, BorderSpawnOverTime, CarOccupancy, IndividTrip, OffMapLocation,
+    OpeningHours, OriginDestination, PersonSpec, Scenario, ScenarioGenerator, SimFlags,
+    SpawnOverTime, SpawnTrip, TripSpawner, TripSpec,
 };
+pub use self::mechanics::CurbReport;
 pub(crate) use self::mechanics::{
     DrivingSimState, IntersectionSimState, ParkingSimState, WalkingSimState,
 };
+pub use self::message_signs::MessageSign;
+pub use self::neighborhoods::{NeighborhoodScore, NeighborhoodScoreboard};
 pub(crate) use self::pandemic::PandemicModel;
-pub(crate) use self::router::{ActionAtEnd, Router};
+pub use self::reliability::{Reliability, TravelTimeSamples};
+pub(crate) use self::router::{path_broken_by_edits, repair_precomputed_path, ActionAtEnd, Router};
 pub(crate) use self::scheduler::{Command, Scheduler};
-pub use self::sim::{AgentProperties, AlertHandler, Sim, SimCallback, SimOptions};
+pub use self::sim::{AgentProperties, AlertHandler, Sim, SimCallback, SimGranularity, SimOptions};
 pub(crate) use self::transit::TransitSimState;
 pub use self::trips::{Person, PersonState, TripResult};
 pub use self::trips::{TripEndpoint, TripMode};
@@ -49,9 +73,12 @@ pub const MIN_CAR_LENGTH: Distance = Distance::const_meters(4.5);
 pub const MAX_CAR_LENGTH: Distance = Distance::const_meters(6.5);
 // Note this is more than MAX_CAR_LENGTH
 pub const BUS_LENGTH: Distance = Distance::const_meters(12.5);
+// Cars with at least this many people inside may use HOV lanes.
+pub const MIN_HOV_OCCUPANCY: usize = 2;
 
 // At all speeds (including at rest), cars must be at least this far apart, measured from front of
-// one car to the back of the other.
+// one car to the back of the other. Individual drivers may keep a different gap; see
+// DriverBehavior.
 pub const FOLLOWING_DISTANCE: Distance = Distance::const_meters(1.0);
 
 // The numeric ID must be globally unique, without considering VehicleType. VehicleType is bundled
@@ -160,6 +187,21 @@ pub struct Vehicle {
     pub vehicle_type: VehicleType,
     pub length: Distance,
     pub max_speed: Option<Speed>,
+    // How many people are inside, including the driver
+    pub occupancy: usize,
+    pub behavior: DriverBehavior,
+}
+
+impl Vehicle {
+    // How fast this vehicle goes on something with this speed limit
+    pub fn speed(&self, speed_limit: Speed) -> Speed {
+        let speed = self.behavior.desired_speed(speed_limit);
+        if let Some(s) = self.max_speed {
+            speed.min(s)
+        } else {
+            speed
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -167,6 +209,8 @@ pub struct VehicleSpec {
     pub vehicle_type: VehicleType,
     pub length: Distance,
     pub max_speed: Option<Speed>,
+    pub occupancy: usize,
+    pub behavior: DriverBehavior,
 }
 
 impl VehicleSpec {
@@ -178,6 +222,8 @@ impl VehicleSpec {
             vehicle_type: self.vehicle_type,
             length: self.length,
             max_speed: self.max_speed,
+            occupancy: self.occupancy,
+            behavior: self.behavior,
         }
     }
 }
@@ -203,6 +249,8 @@ pub struct ParkedCar {
 pub enum DrivingGoal {
     ParkNear(BuildingID),
     Border(IntersectionID, LaneID, Option<OffMapLocation>),
+    // Only for cars. Park in this lot, or give up if it's full.
+    ParkAtLot(ParkingLotID),
 }
 
 impl DrivingGoal {
@@ -224,7 +272,7 @@ impl DrivingGoal {
     pub fn goal_pos(&self, constraints: PathConstraints, map: &Map) -> Position {
         match self {
             DrivingGoal::ParkNear(b) => match constraints {
-                PathConstraints::Car => {
+                PathConstraints::Car | PathConstraints::Truck => {
                     Position::new(map.find_driving_lane_near_building(*b), Distance::ZERO)
                 }
                 PathConstraints::Bike => {
@@ -234,6 +282,7 @@ impl DrivingGoal {
                 PathConstraints::Bus | PathConstraints::Pedestrian => unreachable!(),
             },
             DrivingGoal::Border(_, l, _) => Position::new(*l, map.get_l(*l).length()),
+            DrivingGoal::ParkAtLot(pl) => map.get_pl(*pl).driving_pos,
         }
     }
 
@@ -254,6 +303,14 @@ impl DrivingGoal {
                 map.get_l(*last_lane).length(),
                 *i,
             )),
+            DrivingGoal::ParkAtLot(pl) => {
+                assert_ne!(vt, VehicleType::Bike);
+                Some(Router::park_at_lot(
+                    path,
+                    *pl,
+                    map.get_pl(*pl).driving_pos.dist_along(),
+                ))
+            }
         }
     }
 
@@ -261,6 +318,7 @@ impl DrivingGoal {
         match self {
             DrivingGoal::ParkNear(b) => map.get_b(*b).polygon.center(),
             DrivingGoal::Border(i, _, _) => map.get_i(*i).polygon.center(),
+            DrivingGoal::ParkAtLot(pl) => map.get_pl(*pl).polygon.center(),
         }
     }
 }
@@ -0,0 +1,413 @@
+use crate::{
+    CarOccupancy, DemographicsConfig, DriverBehaviorConfig, IndividTrip, PersonID, PersonSpec,
+    Scenario, SpawnTrip, TripEndpoint, TripMode,
+};
+use abstutil::Timer;
+use geom::{Distance, Duration, Speed, Time};
+use map_model::{Building, BuildingID, Map};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Something people go somewhere to do. Where they can do it comes from the amenities at a
+// building.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Activity {
+    Work,
+    School,
+    Groceries,
+    Shopping,
+    Eating,
+    Leisure,
+}
+
+impl Activity {
+    pub fn all() -> Vec<Activity> {
+        vec![
+            Activity::Work,
+            Activity::School,
+            Activity::Groceries,
+            Activity::Shopping,
+            Activity::Eating,
+            Activity::Leisure,
+        ]
+    }
+
+    // What can be done at a building, besides living there. Any business counts as a workplace.
+    pub fn at(b: &Building) -> Vec<Activity> {
+        let mut results = Vec::new();
+        for (_, amenity) in &b.amenities {
+            let activity = match amenity.as_str() {
+                "school" | "college" | "university" | "kindergarten" => Activity::School,
+                "supermarket" | "convenience" | "greengrocer" | "butcher" | "bakery" => {
+                    Activity::Groceries
+                }
+                "restaurant" | "cafe" | "fast_food" | "bar" | "pub" | "food_court" => {
+                    Activity::Eating
+                }
+                "cinema" | "theatre" | "library" | "community_centre" | "gym"
+                | "fitness_centre" | "arts_centre" => Activity::Leisure,
+                _ => Activity::Shopping,
+            };
+            if !results.contains(&activity) {
+                results.push(activity);
+            }
+        }
+        if !results.is_empty() {
+            results.push(Activity::Work);
+        }
+        results
+    }
+
+    // How long people typically stay. The actual time is picked uniformly from this range.
+    fn dwell_range(self) -> (Duration, Duration) {
+        match self {
+            Activity::Work => (Duration::hours(7), Duration::hours(9)),
+            Activity::School => (Duration::minutes(330), Duration::minutes(420)),
+            Activity::Groceries => (Duration::minutes(15), Duration::minutes(35)),
+            Activity::Shopping => (Duration::minutes(20), Duration::minutes(60)),
+            Activity::Eating => (Duration::minutes(30), Duration::minutes(90)),
+            Activity::Leisure => (Duration::minutes(60), Duration::minutes(150)),
+        }
+    }
+
+    pub fn dwell_time(self, rng: &mut XorShiftRng) -> Duration {
+        let (low, high) = self.dwell_range();
+        Duration::seconds(rng.gen_range(low.inner_seconds(), high.inner_seconds()))
+    }
+
+    // When OSM doesn't say
+    fn default_hours(self) -> OpeningHours {
+        let (open, close) = match self {
+            Activity::Work => (6, 20),
+            Activity::School => (7, 17),
+            Activity::Groceries => (7, 22),
+            Activity::Shopping => (10, 19),
+            Activity::Eating => (7, 23),
+            Activity::Leisure => (9, 22),
+        };
+        OpeningHours {
+            open: Time::START_OF_DAY + Duration::hours(open),
+            close: Time::START_OF_DAY + Duration::hours(close),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OpeningHours {
+    pub open: Time,
+    // Might be past midnight
+    pub close: Time,
+}
+
+impl OpeningHours {
+    // Uses the opening_hours tag if it's simple enough to understand, otherwise guesses from the
+    // activity.
+    pub fn lookup(b: &Building, activity: Activity) -> OpeningHours {
+        b.osm_tags
+            .get("opening_hours")
+            .and_then(|x| OpeningHours::parse(x))
+            .unwrap_or_else(|| activity.default_hours())
+    }
+
+    // Handles "24/7" and the first time range in things like "Mo-Fr 08:00-18:00; Sa 10:00-14:00".
+    // Days of the week are ignored; every day is the same in the simulation.
+    pub fn parse(raw: &str) -> Option<OpeningHours> {
+        if raw.trim() == "24/7" {
+            return Some(OpeningHours {
+                open: Time::START_OF_DAY,
+                close: Time::START_OF_DAY + Duration::hours(24),
+            });
+        }
+        for word in raw.split(|c: char| c.is_whitespace() || c == ';' || c == ',') {
+            let mut parts = word.split('-');
+            if let (Some(a), Some(b), None) = (parts.next(), parts.next(), parts.next()) {
+                if let (Some(open), Some(mut close)) = (parse_hhmm(a), parse_hhmm(b)) {
+                    if close <= open {
+                        close = close + Duration::hours(24);
+                    }
+                    return Some(OpeningHours { open, close });
+                }
+            }
+        }
+        None
+    }
+
+    // Can somebody arrive at this time and stay this long?
+    pub fn allows(&self, arrive: Time, dwell: Duration) -> bool {
+        arrive >= self.open && arrive + dwell <= self.close
+    }
+}
+
+fn parse_hhmm(x: &str) -> Option<Time> {
+    let mut parts = x.split(':');
+    let hours = parts.next()?.parse::<usize>().ok()?;
+    let mins = parts.next()?.parse::<usize>().ok()?;
+    if parts.next().is_some() || hours > 24 || mins >= 60 {
+        return None;
+    }
+    Some(Time::START_OF_DAY + Duration::hours(hours) + Duration::minutes(mins))
+}
+
+// Generates a day of activities for everybody, instead of independent trips. Each person leaves
+// home, maybe goes to work or school, maybe runs some errands, then goes home. Each trip departs
+// after the previous activity is over, so trip chains and how long cars stay parked fall out of
+// dwell times and opening hours.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActivityModel {
+    pub scenario_name: String,
+    pub num_people: usize,
+    pub percent_workers: f64,
+    pub percent_students: f64,
+    // The chance of running each errand, up to a few per day
+    pub percent_errand: f64,
+    pub demographics: Option<DemographicsConfig>,
+    pub driver_behavior: Option<DriverBehaviorConfig>,
+    pub car_occupancy: Option<CarOccupancy>,
+}
+
+// Trips longer than this won't be walked
+const MAX_WALK: Distance = Distance::const_meters(1500.0);
+// Trips longer than this won't be biked
+const MAX_BIKE: Distance = Distance::const_meters(5000.0);
+const MAX_ERRANDS: usize = 3;
+
+impl ActivityModel {
+    pub fn new(num_people: usize) -> ActivityModel {
+        ActivityModel {
+            scenario_name: "activities".to_string(),
+            num_people,
+            percent_workers: 0.6,
+            percent_students: 0.15,
+            percent_errand: 0.4,
+            // Made-up, but a plausible mix
+            demographics: Some(DemographicsConfig {
+                pct_low_income: 0.25,
+                pct_high_income: 0.25,
+                pct_children: 0.2,
+                pct_seniors: 0.15,
+            }),
+            driver_behavior: Some(DriverBehaviorConfig::mixed()),
+            car_occupancy: Some(CarOccupancy::commuters()),
+        }
+    }
+
+    pub fn generate(&self, map: &Map, rng: &mut XorShiftRng, timer: &mut Timer) -> Scenario {
+        let mut scenario = Scenario::empty(map, &self.scenario_name);
+        scenario.only_seed_buses = None;
+        scenario.demographics = self.demographics.clone();
+        scenario.driver_behavior = self.driver_behavior.clone();
+        scenario.car_occupancy = self.car_occupancy.clone();
+
+        let mut places: BTreeMap<Activity, Vec<BuildingID>> = BTreeMap::new();
+        let mut homes = Vec::new();
+        for b in map.all_buildings() {
+            let activities = Activity::at(b);
+            if activities.is_empty() {
+                homes.push(b.id);
+            }
+            for a in activities {
+                places.entry(a).or_insert_with(Vec::new).push(b.id);
+            }
+        }
+        if homes.is_empty() {
+            homes = map.all_buildings().iter().map(|b| b.id).collect();
+        }
+        if homes.is_empty() {
+            timer.warn("No buildings, so no activities".to_string());
+            return scenario;
+        }
+
+        timer.start_iter("plan each person's day", self.num_people);
+        for _ in 0..self.num_people {
+            timer.next();
+            let home = *homes.choose(rng).unwrap();
+            let plan = self.plan_day(home, &places, map, rng);
+            if plan.len() < 2 {
+                continue;
+            }
+            let trips = schedule(plan, map, rng);
+            if trips.is_empty() {
+                continue;
+            }
+            scenario.people.push(PersonSpec {
+                id: PersonID(scenario.people.len()),
+                orig_id: None,
+                trips,
+            });
+        }
+        timer.note(format!(
+            "{} of {} people have something to do today",
+            scenario.people.len(),
+            self.num_people
+        ));
+        scenario
+    }
+
+    // The sequence of places to go, starting and ending at home, along with the earliest time to
+    // leave home.
+    fn plan_day(
+        &self,
+        home: BuildingID,
+        places: &BTreeMap<Activity, Vec<BuildingID>>,
+        map: &Map,
+        rng: &mut XorShiftRng,
+    ) -> Vec<Stop> {
+        let mut plan = vec![Stop {
+            bldg: home,
+            activity: None,
+            // Leave home somewhere in here
+            not_before: rand_time(rng, 6, 10),
+        }];
+
+        let roll: f64 = rng.gen();
+        let main = if roll < self.percent_workers {
+            Some(Activity::Work)
+        } else if roll < self.percent_workers + self.percent_students {
+            Some(Activity::School)
+        } else {
+            // People without a job or school start the day later.
+            plan[0].not_before = rand_time(rng, 9, 14);
+            None
+        };
+        if let Some(a) = main {
+            if let Some(b) = places.get(&a).and_then(|list| list.choose(rng)) {
+                plan.push(Stop {
+                    bldg: *b,
+                    activity: Some(a),
+                    not_before: Time::START_OF_DAY,
+                });
+            }
+        }
+
+        for _ in 0..MAX_ERRANDS {
+            if !rng.gen_bool(self.percent_errand) {
+                break;
+            }
+            let a = *[
+                Activity::Groceries,
+                Activity::Shopping,
+                Activity::Eating,
+                Activity::Leisure,
+            ]
+            .choose(rng)
+            .unwrap();
+            // Prefer something close to wherever they are
+            let here = map.get_b(plan.last().unwrap().bldg).label_center;
+            if let Some(list) = places.get(&a) {
+                let candidates: Vec<&BuildingID> = list.choose_multiple(rng, 5).collect();
+                if let Some(b) = candidates
+                    .into_iter()
+                    .min_by_key(|b| map.get_b(**b).label_center.dist_to(here))
+                {
+                    plan.push(Stop {
+                        bldg: *b,
+                        activity: Some(a),
+                        not_before: Time::START_OF_DAY,
+                    });
+                }
+            }
+        }
+
+        plan.push(Stop {
+            bldg: home,
+            activity: None,
+            not_before: Time::START_OF_DAY,
+        });
+        plan.dedup_by_key(|s| s.bldg);
+        plan
+    }
+}
+
+struct Stop {
+    bldg: BuildingID,
+    // None for home
+    activity: Option<Activity>,
+    not_before: Time,
+}
+
+// Turns the plan into trips. Everybody uses one mode all day, so a car driven to work is there to
+// drive home. Stops that'd be closed on arrival are skipped.
+fn schedule(plan: Vec<Stop>, map: &Map, rng: &mut XorShiftRng) -> Vec<IndividTrip> {
+    let longest = plan
+        .windows(2)
+        .map(|pair| dist(pair[0].bldg, pair[1].bldg, map))
+        .max()
+        .unwrap();
+    let mode = if longest <= MAX_WALK {
+        TripMode::Walk
+    } else if longest <= MAX_BIKE && rng.gen_bool(0.2) {
+        TripMode::Bike
+    } else {
+        TripMode::Drive
+    };
+
+    let mut trips = Vec::new();
+    let mut at = plan[0].bldg;
+    let mut now = plan[0].not_before;
+    for stop in plan.into_iter().skip(1) {
+        let arrive = now + estimate_trip_time(dist(at, stop.bldg, map), mode);
+        if let Some(a) = stop.activity {
+            let dwell = a.dwell_time(rng);
+            let hours = OpeningHours::lookup(map.get_b(stop.bldg), a);
+            // Show up when it opens, if that's not too long of a wait
+            let arrive = if arrive < hours.open && hours.open - arrive < Duration::hours(1) {
+                hours.open
+            } else {
+                arrive
+            };
+            if !hours.allows(arrive, dwell) {
+                continue;
+            }
+            trips.push(IndividTrip {
+                depart: arrive - estimate_trip_time(dist(at, stop.bldg, map), mode),
+                trip: SpawnTrip::new(
+                    TripEndpoint::Bldg(at),
+                    TripEndpoint::Bldg(stop.bldg),
+                    mode,
+                    map,
+                ),
+            });
+            at = stop.bldg;
+            now = arrive + dwell;
+        } else {
+            if at == stop.bldg {
+                break;
+            }
+            trips.push(IndividTrip {
+                depart: now,
+                trip: SpawnTrip::new(
+                    TripEndpoint::Bldg(at),
+                    TripEndpoint::Bldg(stop.bldg),
+                    mode,
+                    map,
+                ),
+            });
+        }
+    }
+    trips
+}
+
+fn dist(b1: BuildingID, b2: BuildingID, map: &Map) -> Distance {
+    map.get_b(b1)
+        .label_center
+        .dist_to(map.get_b(b2).label_center)
+}
+
+// Generous, since streets aren't straight lines. If somebody's late, the simulation holds their
+// next trip until they arrive anyway.
+fn estimate_trip_time(dist: Distance, mode: TripMode) -> Duration {
+    let speed = match mode {
+        TripMode::Walk | TripMode::Transit => Speed::meters_per_second(1.3),
+        TripMode::Bike => Speed::miles_per_hour(10.0),
+        TripMode::Drive => Speed::miles_per_hour(20.0),
+    };
+    1.5 * dist / speed + Duration::minutes(2)
+}
+
+fn rand_time(rng: &mut XorShiftRng, low_hour: usize, high_hour: usize) -> Time {
+    Time::START_OF_DAY
+        + Duration::seconds(rng.gen_range((low_hour * 3600) as f64, (high_hour * 3600) as f64))
+}
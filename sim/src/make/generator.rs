@@ -1,7 +1,11 @@
-use crate::{DrivingGoal, IndividTrip, PersonID, PersonSpec, Scenario, SidewalkSpot, SpawnTrip};
+use crate::{
+    DrivingGoal, IndividTrip, PersonID, PersonSpec, Scenario, SidewalkPOI, SidewalkSpot, SpawnTrip,
+};
 use abstutil::Timer;
 use geom::{Duration, Time};
-use map_model::{BuildingID, DirectedRoadID, Map, PathConstraints};
+use map_model::{
+    BuildingID, BusStopID, DirectedRoadID, Map, ParkingLotID, PathConstraints, WalkingDistances,
+};
 use rand::seq::SliceRandom;
 use rand::Rng;
 use rand_xorshift::XorShiftRng;
@@ -31,6 +35,10 @@ pub struct SpawnOverTime {
     pub percent_driving: f64,
     pub percent_biking: f64,
     pub percent_use_transit: f64,
+    // Of the people who'd otherwise drive, how many drive to a park-and-ride lot and take the bus
+    // from there instead
+    #[serde(default)]
+    pub percent_park_and_ride: f64,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -54,11 +62,34 @@ impl ScenarioGenerator {
 
         timer.start(format!("Generating scenario {}", self.scenario_name));
 
+        // Only needed to cheaply rule out transit
+        let walking = if self
+            .spawn_over_time
+            .iter()
+            .any(|s| s.percent_use_transit > 0.0)
+        {
+            Some(WalkingDistances::load_or_compute(map, timer))
+        } else {
+            None
+        };
+
         for s in &self.spawn_over_time {
+            let park_and_ride_lots = if s.percent_park_and_ride > 0.0 {
+                map.park_and_ride_lots()
+            } else {
+                Vec::new()
+            };
             timer.start_iter("SpawnOverTime each agent", s.num_agents);
             for _ in 0..s.num_agents {
                 timer.next();
-                s.spawn_agent(rng, &mut scenario, map, timer);
+                s.spawn_agent(
+                    rng,
+                    &mut scenario,
+                    &park_and_ride_lots,
+                    walking.as_ref(),
+                    map,
+                    timer,
+                );
             }
         }
 
@@ -100,6 +131,7 @@ impl ScenarioGenerator {
                 percent_driving: 0.5,
                 percent_biking: 0.5,
                 percent_use_transit: 0.5,
+                percent_park_and_ride: 0.0,
             }],
             // If there are no sidewalks/driving lanes at a border, scenario instantiation will
             // just warn and skip them.
@@ -127,6 +159,7 @@ impl ScenarioGenerator {
                 percent_driving: 0.5,
                 percent_biking: 0.5,
                 percent_use_transit: 0.5,
+                percent_park_and_ride: 0.0,
             });
         }
         s
@@ -154,6 +187,7 @@ impl ScenarioGenerator {
                 percent_driving: 0.5,
                 percent_biking: 0.5,
                 percent_use_transit: 0.5,
+                percent_park_and_ride: 0.0,
             }],
             border_spawn_over_time: Vec::new(),
         }
@@ -165,6 +199,8 @@ impl SpawnOverTime {
         &self,
         rng: &mut XorShiftRng,
         scenario: &mut Scenario,
+        park_and_ride_lots: &Vec<(ParkingLotID, BusStopID)>,
+        walking: Option<&WalkingDistances>,
         map: &Map,
         timer: &mut Timer,
     ) {
@@ -175,6 +211,24 @@ impl SpawnOverTime {
         let id = PersonID(scenario.people.len());
 
         if rng.gen_bool(self.percent_driving) {
+            // Only roll the dice when needed, so older scenarios generate the same way
+            if self.percent_park_and_ride > 0.0 && rng.gen_bool(self.percent_park_and_ride) {
+                if let Some(trip) = self
+                    .goal
+                    .pick_walking_goal(map, rng, timer)
+                    .and_then(|goal| {
+                        SpawnTrip::park_and_ride(from_bldg, goal, park_and_ride_lots, map)
+                    })
+                {
+                    scenario.people.push(PersonSpec {
+                        id,
+                        orig_id: None,
+                        trips: vec![IndividTrip { depart, trip }],
+                    });
+                    return;
+                }
+            }
+
             if let Some(goal) = self
                 .goal
                 .pick_driving_goal(PathConstraints::Car, map, rng, timer)
@@ -216,7 +270,9 @@ impl SpawnOverTime {
                 return;
             }
 
-            if rng.gen_bool(self.percent_use_transit) {
+            if rng.gen_bool(self.percent_use_transit)
+                && walking.map_or(true, |w| near_transit(w, from_bldg, &goal))
+            {
                 // TODO This throws away some work. It also sequentially does expensive
                 // work right here.
                 if let Some((stop1, stop2, route)) =
@@ -404,3 +460,18 @@ fn rand_time(rng: &mut XorShiftRng, low: Time, high: Time) -> Time {
     assert!(high > low);
     Time::START_OF_DAY + Duration::seconds(rng.gen_range(low.inner_seconds(), high.inner_seconds()))
 }
+
+// Deciding on transit pathfinds, so first make sure both ends have a bus stop within walking
+// distance. WalkingDistances only covers buildings, so other endpoints get through.
+fn near_transit(walking: &WalkingDistances, from: BuildingID, goal: &SidewalkSpot) -> bool {
+    let has_stop = |b| {
+        walking
+            .bus_stops_near(b)
+            .map_or(true, |stops| !stops.is_empty())
+    };
+    has_stop(from)
+        && match goal.connection {
+            SidewalkPOI::Building(b) => has_stop(b),
+            _ => true,
+        }
+}
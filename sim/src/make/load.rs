@@ -1,5 +1,6 @@
-use crate::{AlertHandler, Scenario, Sim, SimOptions};
+use crate::{AlertHandler, BikeShareKind, Scenario, Sim, SimGranularity, SimOptions};
 use abstutil::CmdArgs;
+use geom::{Duration, Time};
 use map_model::{Map, MapEdits};
 use rand::SeedableRng;
 use rand_xorshift::XorShiftRng;
@@ -47,6 +48,22 @@ impl SimFlags {
                     })
                     .unwrap_or(AlertHandler::Print),
                 pathfinding_upfront: args.enabled("--pathfinding_upfront"),
+                warm_start: args.enabled("--warm_start"),
+                bike_share: args.optional("--bike_share").map(|x| match x.as_ref() {
+                    "docked" => BikeShareKind::Docked,
+                    "dockless" => BikeShareKind::Dockless,
+                    _ => panic!("Bad --bike_share={}. Must be docked|dockless", x),
+                }),
+                granularity: match args.optional("--granularity") {
+                    Some(x) => match x.as_ref() {
+                        "micro" => SimGranularity::Microscopic,
+                        "meso" => SimGranularity::Mesoscopic,
+                        _ => panic!("Bad --granularity={}. Must be micro|meso", x),
+                    },
+                    None => SimGranularity::Microscopic,
+                },
+                warm_up: args.optional_parse("--warm_up", Duration::parse),
+                cool_down: args.optional_parse("--cool_down", Time::parse),
             },
         }
     }
@@ -1,11 +1,15 @@
+mod activity_model;
 mod generator;
 mod load;
 mod scenario;
 mod spawner;
 
+pub use self::activity_model::{Activity, ActivityModel, OpeningHours};
 pub use self::generator::{
     BorderSpawnOverTime, OriginDestination, ScenarioGenerator, SpawnOverTime,
 };
 pub use self::load::SimFlags;
-pub use self::scenario::{IndividTrip, OffMapLocation, PersonSpec, Scenario, SpawnTrip};
+pub use self::scenario::{
+    CarOccupancy, IndividTrip, OffMapLocation, PersonSpec, Scenario, SpawnTrip,
+};
 pub use self::spawner::{TripSpawner, TripSpec};
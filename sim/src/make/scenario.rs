@@ -1,12 +1,13 @@
 use crate::{
-    CarID, DrivingGoal, OrigPersonID, ParkingSpot, PersonID, SidewalkPOI, SidewalkSpot, Sim,
-    TripEndpoint, TripMode, TripSpec, Vehicle, VehicleSpec, VehicleType, BIKE_LENGTH,
-    MAX_CAR_LENGTH, MIN_CAR_LENGTH,
+    CarID, DemographicsConfig, DriverBehavior, DriverBehaviorConfig, DrivingGoal, OrigPersonID,
+    ParkingSpot, PersonID, Pricing, SidewalkPOI, SidewalkSpot, Sim, TripEndpoint, TripMode,
+    TripSpec, Vehicle, VehicleSpec, VehicleType, BIKE_LENGTH, MAX_CAR_LENGTH, MIN_CAR_LENGTH,
 };
 use abstutil::{prettyprint_usize, Counter, Timer};
 use geom::{Distance, Duration, LonLat, Speed, Time};
 use map_model::{
-    BuildingID, BusRouteID, BusStopID, DirectedRoadID, Map, PathConstraints, Position, RoadID,
+    BuildingID, BusRouteID, BusStopID, CurbUse, DirectedRoadID, Itinerary, Map, ParkingLotID,
+    PathConstraints, Position, RoadID, WalkingDistances,
 };
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
@@ -23,6 +24,48 @@ pub struct Scenario {
     pub people: Vec<PersonSpec>,
     // None means seed all buses. Otherwise the route name must be present here.
     pub only_seed_buses: Option<BTreeSet<String>>,
+    // If set, everybody gets an income bracket and age group, for equity metrics.
+    pub demographics: Option<DemographicsConfig>,
+    // If set, each driver goes their own speed, keeps their own following distance, and accepts
+    // their own gaps when yielding.
+    pub driver_behavior: Option<DriverBehaviorConfig>,
+    // If set, replaces the default prices for fuel, parking, tolls, and transit fares.
+    pub pricing: Option<Pricing>,
+    // If set, some cars carry more than just the driver. Otherwise, everybody drives alone.
+    pub car_occupancy: Option<CarOccupancy>,
+}
+
+// The chance of a car carrying 1, 2, 3, ... people, including the driver. The last entry covers
+// anything left over if the chances don't add up to 1.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CarOccupancy {
+    pub pct_by_occupancy: Vec<f64>,
+}
+
+impl CarOccupancy {
+    // Roughly matches regional commute surveys
+    pub fn commuters() -> CarOccupancy {
+        CarOccupancy {
+            pct_by_occupancy: vec![0.88, 0.09, 0.03],
+        }
+    }
+
+    fn assign(&self, rng: &mut XorShiftRng, vehicles: &mut Vec<VehicleSpec>) {
+        for v in vehicles {
+            if v.vehicle_type != VehicleType::Car {
+                continue;
+            }
+            let mut roll: f64 = rng.gen();
+            v.occupancy = self.pct_by_occupancy.len();
+            for (idx, pct) in self.pct_by_occupancy.iter().enumerate() {
+                if roll < *pct {
+                    v.occupancy = idx + 1;
+                    break;
+                }
+                roll -= pct;
+            }
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -65,6 +108,15 @@ pub enum SpawnTrip {
         trip_time: Duration,
         mode: TripMode,
     },
+    // Drive from home to a park-and-ride lot, then take the bus from a stop near the lot.
+    ParkAndRide {
+        start: BuildingID,
+        lot: ParkingLotID,
+        goal: SidewalkSpot,
+        route: BusRouteID,
+        stop1: BusStopID,
+        stop2: BusStopID,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -77,8 +129,12 @@ impl Scenario {
     // Any case where map edits could change the calls to the RNG, we have to fork.
     pub fn instantiate(&self, sim: &mut Sim, map: &Map, rng: &mut XorShiftRng, timer: &mut Timer) {
         sim.set_name(self.scenario_name.clone());
+        if let Some(ref pricing) = self.pricing {
+            sim.set_pricing(pricing.clone());
+        }
 
         timer.start(format!("Instantiating {}", self.scenario_name));
+        sim.load_prior_routes(&self.scenario_name, timer);
 
         if let Some(ref routes) = self.only_seed_buses {
             for route in map.get_all_bus_routes() {
@@ -93,6 +149,26 @@ impl Scenario {
             }
         }
 
+        // During the warm-up, demand ramps in linearly from the first departure. People whose day
+        // starts then only show up with a probability growing over the warm-up; the ones left out
+        // still exist, but stay home.
+        let mut ramp = match (
+            sim.get_warm_up(),
+            self.people
+                .iter()
+                .filter_map(|p| p.trips.get(0).map(|t| t.depart))
+                .min(),
+        ) {
+            (Some(dt), Some(start)) => {
+                sim.start_recording_at(start + dt);
+                Some((start, dt, abstutil::fork_rng(rng)))
+            }
+            _ => None,
+        };
+        let mut stayed_home = 0;
+        // Separate from the main RNG, so scenarios without this setting don't change
+        let mut occupancy_rng = self.car_occupancy.as_ref().map(|_| abstutil::fork_rng(rng));
+
         timer.start_iter("trips for People", self.people.len());
         let mut spawner = sim.make_spawner();
         let mut parked_cars: Vec<(Vehicle, BuildingID)> = Vec::new();
@@ -103,18 +179,36 @@ impl Scenario {
                 panic!("{}", err);
             }
 
-            let (vehicle_specs, cars_initially_parked_at, vehicle_foreach_trip) =
+            let (mut vehicle_specs, cars_initially_parked_at, vehicle_foreach_trip) =
                 p.get_vehicles(rng);
+            if let Some(ref config) = self.driver_behavior {
+                config.assign(p.id, &mut vehicle_specs);
+            }
+            if let (Some(config), Some(occupancy_rng)) =
+                (&self.car_occupancy, occupancy_rng.as_mut())
+            {
+                config.assign(occupancy_rng, &mut vehicle_specs);
+            }
             sim.new_person(
                 p.id,
                 p.orig_id,
                 Scenario::rand_ped_speed(rng),
                 vehicle_specs,
             );
+            if let Some(ref config) = self.demographics {
+                sim.set_demographics(p.id, config);
+            }
             let person = sim.get_person(p.id);
             for (idx, b) in cars_initially_parked_at {
                 parked_cars.push((person.vehicles[idx].clone(), b));
             }
+            if let (Some((start, dt, ramp_rng)), Some(first)) = (&mut ramp, p.trips.get(0)) {
+                let depart = first.depart;
+                if depart < *start + *dt && !ramp_rng.gen_bool((depart - *start) / *dt) {
+                    stayed_home += 1;
+                    continue;
+                }
+            }
             for (t, maybe_idx) in p.trips.iter().zip(vehicle_foreach_trip) {
                 // The RNG call might change over edits for picking the spawning lane from a border
                 // with multiple choices for a vehicle type.
@@ -128,6 +222,13 @@ impl Scenario {
             }
         }
 
+        if stayed_home > 0 {
+            timer.note(format!(
+                "{} people stayed home during the warm-up",
+                prettyprint_usize(stayed_home)
+            ));
+        }
+
         // parked_cars is stable over map edits, so don't fork.
         parked_cars.shuffle(rng);
         seed_parked_cars(parked_cars, sim, map, rng, timer);
@@ -149,6 +250,10 @@ impl Scenario {
             map_name: map.get_name().to_string(),
             people: Vec::new(),
             only_seed_buses: Some(BTreeSet::new()),
+            demographics: None,
+            driver_behavior: None,
+            pricing: None,
+            car_occupancy: None,
         }
     }
 
@@ -158,6 +263,9 @@ impl Scenario {
             vehicle_type: VehicleType::Car,
             length,
             max_speed: None,
+            // The scenario's CarOccupancy might change this
+            occupancy: 1,
+            behavior: DriverBehavior::TYPICAL,
         }
     }
 
@@ -171,6 +279,8 @@ impl Scenario {
             vehicle_type: VehicleType::Bike,
             length: BIKE_LENGTH,
             max_speed,
+            occupancy: 1,
+            behavior: DriverBehavior::TYPICAL,
         }
     }
 
@@ -265,7 +375,13 @@ fn seed_parked_cars(
         BTreeMap::new();
     for spot in sim.get_all_parking_spots().1 {
         let (r, restriction) = match spot {
-            ParkingSpot::Onstreet(l, _) => (map.get_l(l).parent, None),
+            ParkingSpot::Onstreet(l, _) => {
+                // Don't start the day in violation of the curb regulations
+                if map.get_curb_regulation(l).usage_at(Time::START_OF_DAY) != CurbUse::Parking {
+                    continue;
+                }
+                (map.get_l(l).parent, None)
+            }
             ParkingSpot::Offstreet(b, _) => (
                 map.get_l(map.get_b(b).sidewalk()).parent,
                 if map
@@ -296,6 +412,11 @@ fn seed_parked_cars(
         }
     }
 
+    if parked_cars.is_empty() {
+        return;
+    }
+    let walking = WalkingDistances::load_or_compute(map, timer);
+
     timer.start_iter("seed parked cars", parked_cars.len());
     let mut ok = true;
     for (vehicle, b) in parked_cars {
@@ -303,7 +424,9 @@ fn seed_parked_cars(
         if !ok {
             continue;
         }
-        if let Some(spot) = find_spot_near_building(b, &mut open_spots_per_road, map, timer) {
+        if let Some(spot) =
+            find_spot_near_building(b, &mut open_spots_per_road, &walking, map, timer)
+        {
             sim.seed_parked_car(vehicle, spot);
         } else {
             timer.warn("Not enough room to seed parked cars.".to_string());
@@ -313,14 +436,30 @@ fn seed_parked_cars(
 }
 
 // Pick a parking spot for this building. If the building's road has a free spot, use it. If not,
-// start BFSing out from the road in a deterministic way until finding a nearby road with an open
-// spot.
+// try the parking within walking distance, closest first. As a last resort, start BFSing out from
+// the road in a deterministic way until finding a nearby road with an open spot.
 fn find_spot_near_building(
     b: BuildingID,
     open_spots_per_road: &mut BTreeMap<RoadID, Vec<(ParkingSpot, Option<BuildingID>)>>,
+    walking: &WalkingDistances,
     map: &Map,
     timer: &mut Timer,
 ) -> Option<ParkingSpot> {
+    let mut nearby: Vec<(Distance, RoadID)> = vec![(Distance::ZERO, map.building_to_road(b).id)];
+    for (l, dist) in walking.parking_lanes_near(b).into_iter().flatten() {
+        nearby.push((*dist, map.get_l(*l).parent));
+    }
+    for (pl, dist) in walking.parking_lots_near(b).into_iter().flatten() {
+        nearby.push((*dist, map.get_l(map.get_pl(*pl).driving_pos.lane()).parent));
+    }
+    // Stable, so ties keep the order from above
+    nearby.sort_by_key(|(dist, _)| *dist);
+    for (_, r) in nearby {
+        if let Some(spot) = take_spot(open_spots_per_road, r, b) {
+            return Some(spot);
+        }
+    }
+
     let mut roads_queue: VecDeque<RoadID> = VecDeque::new();
     let mut visited: HashSet<RoadID> = HashSet::new();
     {
@@ -339,21 +478,8 @@ fn find_spot_near_building(
             ));
         }
         let r = roads_queue.pop_front()?;
-        if let Some(spots) = open_spots_per_road.get_mut(&r) {
-            // Fill in all private parking first before
-            // TODO With some probability, skip this available spot and park farther away
-            if let Some(idx) = spots
-                .iter()
-                .position(|(_, restriction)| restriction == &Some(b))
-            {
-                return Some(spots.remove(idx).0);
-            }
-            if let Some(idx) = spots
-                .iter()
-                .position(|(_, restriction)| restriction.is_none())
-            {
-                return Some(spots.remove(idx).0);
-            }
+        if let Some(spot) = take_spot(open_spots_per_road, r, b) {
+            return Some(spot);
         }
 
         for next_r in map.get_next_roads(r).into_iter() {
@@ -365,6 +491,29 @@ fn find_spot_near_building(
     }
 }
 
+fn take_spot(
+    open_spots_per_road: &mut BTreeMap<RoadID, Vec<(ParkingSpot, Option<BuildingID>)>>,
+    r: RoadID,
+    b: BuildingID,
+) -> Option<ParkingSpot> {
+    let spots = open_spots_per_road.get_mut(&r)?;
+    // Fill in all private parking first before
+    // TODO With some probability, skip this available spot and park farther away
+    if let Some(idx) = spots
+        .iter()
+        .position(|(_, restriction)| restriction == &Some(b))
+    {
+        return Some(spots.remove(idx).0);
+    }
+    if let Some(idx) = spots
+        .iter()
+        .position(|(_, restriction)| restriction.is_none())
+    {
+        return Some(spots.remove(idx).0);
+    }
+    None
+}
+
 impl SpawnTrip {
     fn to_trip_spec(
         self,
@@ -446,6 +595,22 @@ impl SpawnTrip {
                 trip_time,
                 mode,
             },
+            SpawnTrip::ParkAndRide {
+                start,
+                lot,
+                goal,
+                route,
+                stop1,
+                stop2,
+            } => TripSpec::ParkAndRide {
+                car: use_vehicle.unwrap(),
+                start_bldg: start,
+                lot,
+                goal,
+                route,
+                stop1,
+                stop2,
+            },
         }
     }
 
@@ -457,7 +622,9 @@ impl SpawnTrip {
             SpawnTrip::FromBorder { dr, ref origin, .. } => {
                 TripEndpoint::Border(dr.src_i(map), origin.clone())
             }
-            SpawnTrip::UsingParkedCar(b, _) => TripEndpoint::Bldg(*b),
+            SpawnTrip::UsingParkedCar(b, _) | SpawnTrip::ParkAndRide { start: b, .. } => {
+                TripEndpoint::Bldg(*b)
+            }
             SpawnTrip::UsingBike(ref spot, _)
             | SpawnTrip::JustWalking(ref spot, _)
             | SpawnTrip::UsingTransit(ref spot, _, _, _, _) => match spot.connection {
@@ -483,14 +650,16 @@ impl SpawnTrip {
             | SpawnTrip::UsingBike(_, ref goal) => match goal {
                 DrivingGoal::ParkNear(b) => TripEndpoint::Bldg(*b),
                 DrivingGoal::Border(i, _, ref loc) => TripEndpoint::Border(*i, loc.clone()),
+                // PersonSpec::check_schedule rejects these before anything is spawned
+                DrivingGoal::ParkAtLot(_) => unreachable!(),
+            },
+            SpawnTrip::JustWalking(_, ref spot)
+            | SpawnTrip::UsingTransit(_, ref spot, _, _, _)
+            | SpawnTrip::ParkAndRide { goal: ref spot, .. } => match spot.connection {
+                SidewalkPOI::Building(b) => TripEndpoint::Bldg(b),
+                SidewalkPOI::Border(i, ref loc) => TripEndpoint::Border(i, loc.clone()),
+                _ => unreachable!(),
             },
-            SpawnTrip::JustWalking(_, ref spot) | SpawnTrip::UsingTransit(_, ref spot, _, _, _) => {
-                match spot.connection {
-                    SidewalkPOI::Building(b) => TripEndpoint::Bldg(b),
-                    SidewalkPOI::Border(i, ref loc) => TripEndpoint::Border(i, loc.clone()),
-                    _ => unreachable!(),
-                }
-            }
             // Pick an arbitrary border
             SpawnTrip::Remote { ref to, .. } => {
                 TripEndpoint::Border(map.all_incoming_borders()[0].id, Some(to.clone()))
@@ -498,6 +667,25 @@ impl SpawnTrip {
         }
     }
 
+    pub fn mode(&self) -> TripMode {
+        match self {
+            SpawnTrip::VehicleAppearing { is_bike, .. } | SpawnTrip::FromBorder { is_bike, .. } => {
+                if *is_bike {
+                    TripMode::Bike
+                } else {
+                    TripMode::Drive
+                }
+            }
+            SpawnTrip::UsingParkedCar(_, _) => TripMode::Drive,
+            SpawnTrip::UsingBike(_, _) => TripMode::Bike,
+            SpawnTrip::JustWalking(_, _) => TripMode::Walk,
+            SpawnTrip::UsingTransit(_, _, _, _, _) | SpawnTrip::ParkAndRide { .. } => {
+                TripMode::Transit
+            }
+            SpawnTrip::Remote { mode, .. } => *mode,
+        }
+    }
+
     pub fn new(from: TripEndpoint, to: TripEndpoint, mode: TripMode, map: &Map) -> SpawnTrip {
         match mode {
             TripMode::Drive => match from {
@@ -529,8 +717,10 @@ impl SpawnTrip {
             TripMode::Transit => {
                 let start = from.start_sidewalk_spot(map);
                 let goal = to.end_sidewalk_spot(map);
+                // Only the choice of bus matters here, so nothing gets pathfound yet
                 if let Some((stop1, stop2, route)) =
-                    map.should_use_transit(start.sidewalk_pos, goal.sidewalk_pos)
+                    Itinerary::walk_transit_walk(start.sidewalk_pos, goal.sidewalk_pos, map)
+                        .bus_ride()
                 {
                     SpawnTrip::UsingTransit(start, goal, route, stop1, stop2)
                 } else {
@@ -541,11 +731,59 @@ impl SpawnTrip {
             }
         }
     }
+
+    // Drive to one of the closest park-and-ride lots (from Map::park_and_ride_lots) with a bus
+    // heading towards the goal. None if no lot works.
+    pub fn park_and_ride(
+        start: BuildingID,
+        goal: SidewalkSpot,
+        lots: &Vec<(ParkingLotID, BusStopID)>,
+        map: &Map,
+    ) -> Option<SpawnTrip> {
+        let start_pt = map.get_b(start).polygon.center();
+        let mut lots: Vec<ParkingLotID> = lots.iter().map(|(pl, _)| *pl).collect();
+        lots.sort_by_key(|pl| map.get_pl(*pl).polygon.center().dist_to(start_pt));
+        // Each check pathfinds, so only try a few
+        for lot in lots.into_iter().take(3) {
+            if let Some((stop1, stop2, route)) =
+                map.should_use_transit(map.get_pl(lot).sidewalk_pos, goal.sidewalk_pos)
+            {
+                return Some(SpawnTrip::ParkAndRide {
+                    start,
+                    lot,
+                    goal,
+                    route,
+                    stop1,
+                    stop2,
+                });
+            }
+        }
+        None
+    }
 }
 
 impl PersonSpec {
     // Verify that the trip start/endpoints of the person match up
     fn check_schedule(&self, map: &Map) -> Result<(), String> {
+        // Parking at a lot is only a step in the middle of a ParkAndRide trip.
+        for t in &self.trips {
+            match t.trip {
+                SpawnTrip::VehicleAppearing { ref goal, .. }
+                | SpawnTrip::FromBorder { ref goal, .. }
+                | SpawnTrip::UsingParkedCar(_, ref goal)
+                | SpawnTrip::UsingBike(_, ref goal) => {
+                    if let DrivingGoal::ParkAtLot(lot) = goal {
+                        return Err(format!(
+                            "At {}, {} {:?} drives to {}, but only ParkAndRide trips can end at a \
+                             lot",
+                            t.depart, self.id, self.orig_id, lot
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
         for pair in self.trips.iter().zip(self.trips.iter().skip(1)) {
             if pair.0.depart >= pair.1.depart {
                 return Err(format!(
@@ -640,6 +878,8 @@ impl PersonSpec {
                             DrivingGoal::Border(_, _, _) => {
                                 car_locations.push((idx, None));
                             }
+                            // Left at the lot, where no later trip can find it
+                            DrivingGoal::ParkAtLot(_) => {}
                         }
 
                         Some(idx)
@@ -670,10 +910,30 @@ impl PersonSpec {
                         DrivingGoal::Border(_, _, _) => {
                             car_locations.push((idx, None));
                         }
+                        DrivingGoal::ParkAtLot(_) => {}
                     }
 
                     Some(idx)
                 }
+                SpawnTrip::ParkAndRide { start: b, .. } => {
+                    let idx = if let Some(idx) = car_locations
+                        .iter()
+                        .find(|(_, parked_at)| *parked_at == Some(b))
+                        .map(|(idx, _)| *idx)
+                    {
+                        idx
+                    } else {
+                        let idx = vehicle_specs.len();
+                        vehicle_specs.push(Scenario::rand_car(rng));
+                        cars_initially_parked_at.push((idx, b));
+                        idx
+                    };
+                    // The car stays at the lot. A later trip from home would need another car;
+                    // getting back to the lot to pick it up isn't modeled.
+                    car_locations.retain(|(i, _)| idx != *i);
+
+                    Some(idx)
+                }
                 SpawnTrip::UsingBike(_, _) => {
                     if bike_idx.is_none() {
                         bike_idx = Some(vehicle_specs.len());
@@ -705,3 +965,112 @@ impl PersonSpec {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map_model::raw::RawMap;
+    use map_model::LaneType;
+
+    // One free spot on every parking lane
+    fn open_spots(map: &Map) -> BTreeMap<RoadID, Vec<(ParkingSpot, Option<BuildingID>)>> {
+        let mut spots = BTreeMap::new();
+        for l in map.all_lanes() {
+            if l.lane_type == LaneType::Parking {
+                spots
+                    .entry(l.parent)
+                    .or_insert_with(Vec::new)
+                    .push((ParkingSpot::Onstreet(l.id, 0), None));
+            }
+        }
+        spots
+    }
+
+    fn road_of(spot: ParkingSpot, map: &Map) -> RoadID {
+        match spot {
+            ParkingSpot::Onstreet(l, _) => map.get_l(l).parent,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn seed_parked_cars_near_building() {
+        let mut timer = Timer::throwaway();
+        let map = Map::synthetic(RawMap::synthetic_grid("parking", 3, 3), false);
+        let walking = WalkingDistances::new(&map, &mut timer);
+        assert!(!map.all_buildings().is_empty());
+
+        for b in map.all_buildings() {
+            let home = map.building_to_road(b.id).id;
+
+            // The building's own road wins when there's room.
+            let mut spots = open_spots(&map);
+            let spot =
+                find_spot_near_building(b.id, &mut spots, &walking, &map, &mut timer).unwrap();
+            assert_eq!(road_of(spot, &map), home);
+
+            // Otherwise, the closest parking by walking distance.
+            let mut spots = open_spots(&map);
+            spots.remove(&home);
+            let closest = walking
+                .parking_lanes_near(b.id)
+                .unwrap()
+                .iter()
+                .map(|(l, _)| map.get_l(*l).parent)
+                .find(|r| *r != home)
+                .unwrap();
+            let spot =
+                find_spot_near_building(b.id, &mut spots, &walking, &map, &mut timer).unwrap();
+            assert_eq!(road_of(spot, &map), closest);
+        }
+    }
+
+    #[test]
+    fn only_park_and_ride_ends_at_a_lot() {
+        let map = Map::synthetic(RawMap::synthetic_grid("lots", 2, 2), false);
+        let b1 = map.all_buildings()[0].id;
+        let b2 = map.all_buildings()[1].id;
+        let person = |goal| PersonSpec {
+            id: PersonID(0),
+            orig_id: None,
+            trips: vec![IndividTrip {
+                depart: Time::START_OF_DAY,
+                trip: SpawnTrip::UsingParkedCar(b1, goal),
+            }],
+        };
+
+        assert!(person(DrivingGoal::ParkNear(b2))
+            .check_schedule(&map)
+            .is_ok());
+        assert!(person(DrivingGoal::ParkAtLot(ParkingLotID(0)))
+            .check_schedule(&map)
+            .is_err());
+    }
+
+    #[test]
+    fn car_occupancy() {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        let mut vehicles: Vec<VehicleSpec> = (0..1000)
+            .map(|_| Scenario::rand_car(&mut rng))
+            .chain(std::iter::once(Scenario::rand_bike(&mut rng)))
+            .collect();
+        // Without the setting, everybody drives alone
+        assert!(vehicles.iter().all(|v| v.occupancy == 1));
+
+        CarOccupancy {
+            pct_by_occupancy: vec![0.5, 0.5],
+        }
+        .assign(&mut rng, &mut vehicles);
+        let carpools = vehicles.iter().filter(|v| v.occupancy == 2).count();
+        assert!(carpools > 400 && carpools < 600);
+        assert!(vehicles.iter().all(|v| v.occupancy <= 2));
+        assert_eq!(vehicles.last().unwrap().occupancy, 1);
+
+        // Chances that don't add up leave the rest to the largest group
+        CarOccupancy {
+            pct_by_occupancy: vec![0.0, 0.0, 0.0],
+        }
+        .assign(&mut rng, &mut vehicles);
+        assert!(vehicles[..1000].iter().all(|v| v.occupancy == 3));
+    }
+}
@@ -5,7 +5,8 @@ use crate::{
 use abstutil::Timer;
 use geom::{Duration, Time, EPSILON_DIST};
 use map_model::{
-    BuildingID, BusRouteID, BusStopID, IntersectionID, Map, PathConstraints, PathRequest, Position,
+    BuildingID, BusRouteID, BusStopID, IntersectionID, Map, ParkingLotID, PathConstraints,
+    PathRequest, Position,
 };
 use serde::{Deserialize, Serialize};
 
@@ -58,6 +59,17 @@ pub enum TripSpec {
         trip_time: Duration,
         mode: TripMode,
     },
+    // Drive a parked car to a lot, then walk to stop1 and ride the bus the rest of the way.
+    ParkAndRide {
+        // This must be a currently parked vehicle owned by the person.
+        car: CarID,
+        start_bldg: BuildingID,
+        lot: ParkingLotID,
+        goal: SidewalkSpot,
+        route: BusRouteID,
+        stop1: BusStopID,
+        stop2: BusStopID,
+    },
 }
 
 // This structure is created temporarily by a Scenario or to interactively spawn agents.
@@ -79,6 +91,22 @@ impl TripSpawner {
         map: &Map,
     ) {
         // TODO We'll want to repeat this validation when we spawn stuff later for a second leg...
+        match &spec {
+            TripSpec::VehicleAppearing { goal, .. }
+            | TripSpec::NoRoomToSpawn { goal, .. }
+            | TripSpec::UsingParkedCar { goal, .. }
+            | TripSpec::UsingBike { goal, .. } => {
+                if let DrivingGoal::ParkAtLot(lot) = goal {
+                    println!(
+                        "Can't schedule a trip for {} ending at {}; only ParkAndRide trips can \
+                         drive to a lot",
+                        person.id, lot
+                    );
+                    return;
+                }
+            }
+            _ => {}
+        }
         match &spec {
             TripSpec::VehicleAppearing {
                 start_pos,
@@ -113,7 +141,7 @@ impl TripSpawner {
                             );
                         }
                     }
-                    DrivingGoal::ParkNear(_) => {}
+                    DrivingGoal::ParkNear(_) | DrivingGoal::ParkAtLot(_) => {}
                 }
             }
             TripSpec::NoRoomToSpawn { .. } => {}
@@ -171,6 +199,7 @@ impl TripSpawner {
             }
             TripSpec::UsingTransit { .. } => {}
             TripSpec::Remote { .. } => {}
+            TripSpec::ParkAndRide { .. } => {}
         };
 
         self.trips.push((person.id, start_time, spec, trip_start));
@@ -268,6 +297,8 @@ impl TripSpawner {
                             legs.push(TripLeg::Walk(SidewalkSpot::building(b, map)));
                         }
                         DrivingGoal::Border(_, _, _) => {}
+                        // schedule_trip rejects these
+                        DrivingGoal::ParkAtLot(_) => unreachable!(),
                     }
                     trips.new_trip(
                         person.id,
@@ -298,6 +329,8 @@ impl TripSpawner {
                             legs.push(TripLeg::Walk(SidewalkSpot::building(b, map)));
                         }
                         DrivingGoal::Border(_, _, _) => {}
+                        // schedule_trip rejects these
+                        DrivingGoal::ParkAtLot(_) => unreachable!(),
                     };
                     trips.new_trip(person.id, start_time, trip_start, TripMode::Bike, legs, map)
                 }
@@ -322,6 +355,28 @@ impl TripSpawner {
                         map,
                     )
                 }
+                TripSpec::ParkAndRide {
+                    car,
+                    lot,
+                    goal,
+                    route,
+                    stop1,
+                    stop2,
+                    ..
+                } => trips.new_trip(
+                    person.id,
+                    start_time,
+                    trip_start,
+                    TripMode::Transit,
+                    vec![
+                        TripLeg::Walk(SidewalkSpot::deferred_parking_spot()),
+                        TripLeg::Drive(car, DrivingGoal::ParkAtLot(lot)),
+                        TripLeg::Walk(SidewalkSpot::bus_stop(stop1, map)),
+                        TripLeg::RideBus(route, stop2),
+                        TripLeg::Walk(goal),
+                    ],
+                    map,
+                ),
                 TripSpec::Remote { to, mode, .. } => trips.new_trip(
                     person.id,
                     start_time,
@@ -379,7 +434,7 @@ impl TripSpec {
             }
             TripSpec::NoRoomToSpawn { .. } => None,
             // We don't know where the parked car will be
-            TripSpec::UsingParkedCar { .. } => None,
+            TripSpec::UsingParkedCar { .. } | TripSpec::ParkAndRide { .. } => None,
             TripSpec::JustWalking { start, goal, .. } => Some(PathRequest {
                 start: start.sidewalk_pos,
                 end: goal.sidewalk_pos,
@@ -401,3 +456,55 @@ impl TripSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Scenario, Sim, SimOptions};
+    use geom::Speed;
+    use map_model::raw::RawMap;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn only_park_and_ride_drives_to_a_lot() {
+        let mut timer = Timer::throwaway();
+        let map = Map::synthetic(RawMap::synthetic_grid("lots", 2, 2), false);
+        let mut sim = Sim::new(&map, SimOptions::new("test"), &mut timer);
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        let person = sim.random_person(
+            Speed::miles_per_hour(3.0),
+            vec![Scenario::rand_car(&mut rng)],
+        );
+        let car = person.vehicles[0].id;
+        let b1 = map.all_buildings()[0].id;
+        let b2 = map.all_buildings()[1].id;
+
+        let mut spawner = TripSpawner::new();
+        spawner.schedule_trip(
+            person,
+            Time::START_OF_DAY,
+            TripSpec::UsingParkedCar {
+                car,
+                start_bldg: b1,
+                goal: DrivingGoal::ParkAtLot(ParkingLotID(0)),
+            },
+            TripEndpoint::Bldg(b1),
+            &map,
+        );
+        assert!(spawner.trips.is_empty());
+
+        spawner.schedule_trip(
+            person,
+            Time::START_OF_DAY,
+            TripSpec::UsingParkedCar {
+                car,
+                start_bldg: b1,
+                goal: DrivingGoal::ParkNear(b2),
+            },
+            TripEndpoint::Bldg(b1),
+            &map,
+        );
+        assert_eq!(spawner.trips.len(), 1);
+    }
+}
@@ -44,10 +44,7 @@ impl Car {
         map: &Map,
     ) -> CarState {
         let on = self.router.head();
-        let mut speed = on.speed_limit(map);
-        if let Some(s) = self.vehicle.max_speed {
-            speed = speed.min(s);
-        }
+        let speed = self.vehicle.speed(on.speed_limit(map));
         let dt = (dist_int.end - dist_int.start) / speed;
         CarState::Crossing(TimeInterval::new(start_time, start_time + dt), dist_int)
     }
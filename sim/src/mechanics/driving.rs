@@ -2,19 +2,21 @@ use crate::mechanics::car::{Car, CarState};
 use crate::mechanics::Queue;
 use crate::{
     ActionAtEnd, AgentID, AgentProperties, CarID, Command, CreateCar, DistanceInterval,
-    DrawCarInput, Event, IntersectionSimState, ParkedCar, ParkingSimState, PersonID, Scheduler,
-    TimeInterval, TransitSimState, TripManager, TripPositions, UnzoomedAgent, Vehicle,
-    WalkingSimState, FOLLOWING_DISTANCE,
+    DrawCarInput, Event, IntersectionSimState, MessageSign, ParkedCar, ParkingSimState, PersonID,
+    Scheduler, SimGranularity, TimeInterval, TransitSimState, TripManager, TripPositions,
+    UnzoomedAgent, Vehicle, VehicleType, WalkingSimState,
 };
 use abstutil::{deserialize_btreemap, serialize_btreemap};
-use geom::{Distance, Duration, PolyLine, Time};
-use map_model::{LaneID, Map, Path, PathStep, Traversable};
+use geom::{Distance, Duration, PolyLine, Speed, Time};
+use map_model::{LaneID, Map, Path, PathStep, RoadID, Traversable};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashSet, VecDeque};
 
 const TIME_TO_UNPARK: Duration = Duration::const_seconds(10.0);
 const TIME_TO_PARK: Duration = Duration::const_seconds(15.0);
 const TIME_TO_WAIT_AT_STOP: Duration = Duration::const_seconds(10.0);
+// Stepping up from the curb slows everyone down, and deploying a ramp takes a while.
+const EXTRA_WAIT_WITHOUT_LEVEL_BOARDING: Duration = Duration::const_seconds(8.0);
 
 // TODO Do something else.
 pub(crate) const BLIND_RETRY_TO_CREEP_FORWARDS: Duration = Duration::const_seconds(0.1);
@@ -32,29 +34,41 @@ pub struct DrivingSimState {
         deserialize_with = "deserialize_btreemap"
     )]
     queues: BTreeMap<Traversable, Queue>,
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    message_signs: BTreeMap<RoadID, MessageSign>,
     events: Vec<Event>,
 
     recalc_lanechanging: bool,
+    granularity: SimGranularity,
 }
 
 impl DrivingSimState {
-    pub fn new(map: &Map, recalc_lanechanging: bool) -> DrivingSimState {
+    pub fn new(
+        map: &Map,
+        recalc_lanechanging: bool,
+        granularity: SimGranularity,
+    ) -> DrivingSimState {
         let mut sim = DrivingSimState {
             cars: BTreeMap::new(),
             queues: BTreeMap::new(),
+            message_signs: BTreeMap::new(),
             events: Vec::new(),
             recalc_lanechanging,
+            granularity,
         };
 
         for l in map.all_lanes() {
             if l.lane_type.is_for_moving_vehicles() {
-                let q = Queue::new(Traversable::Lane(l.id), map);
+                let q = Queue::new(Traversable::Lane(l.id), map, granularity);
                 sim.queues.insert(q.id, q);
             }
         }
         for t in map.all_turns().values() {
             if !t.between_sidewalks() {
-                let q = Queue::new(Traversable::Turn(t.id), map);
+                let q = Queue::new(Traversable::Turn(t.id), map, granularity);
                 sim.queues.insert(q.id, q);
             }
         }
@@ -80,6 +94,7 @@ impl DrivingSimState {
         if let Some(idx) = self.queues[&Traversable::Lane(first_lane)].get_idx_to_insert_car(
             params.start_dist,
             params.vehicle.length,
+            params.vehicle.behavior.following_distance,
             now,
             &self.cars,
             &self.queues,
@@ -107,6 +122,7 @@ impl DrivingSimState {
                         params.start_dist,
                         &car.vehicle,
                         parking,
+                        now,
                         map,
                         car.trip_and_person,
                         &mut self.events,
@@ -139,7 +155,8 @@ impl DrivingSimState {
                 queue.cars.insert(idx, car.vehicle.id);
                 // Don't use try_to_reserve_entry -- it's overly conservative.
                 // get_idx_to_insert_car does a more detailed check of the current space usage.
-                queue.reserved_length += car.vehicle.length + FOLLOWING_DISTANCE;
+                queue.reserved_length +=
+                    car.vehicle.length + car.vehicle.behavior.following_distance;
             }
             self.cars.insert(car.vehicle.id, car);
             return true;
@@ -242,7 +259,12 @@ impl DrivingSimState {
         scheduler: &mut Scheduler,
     ) -> bool {
         match car.state {
-            CarState::Crossing(_, _) => {
+            CarState::Crossing(ref time_int, ref dist_int) => {
+                let approach_speed = if time_int.end > time_int.start {
+                    Speed::from_dist_time(dist_int.length(), time_int.end - time_int.start)
+                } else {
+                    Speed::ZERO
+                };
                 car.state = CarState::Queued { blocked_since: now };
                 if car.router.last_step() {
                     // Immediately run update_car_with_distances.
@@ -250,10 +272,14 @@ impl DrivingSimState {
                 }
                 let queue = &self.queues[&car.router.head()];
                 if queue.cars[0] == car.vehicle.id && queue.laggy_head.is_none() {
+                    if let Traversable::Turn(t) = car.router.next() {
+                        intersections.car_arrived(car.vehicle.id, t, approach_speed, now, map);
+                    }
                     // Want to re-run, but no urgency about it happening immediately.
                     car.state = CarState::WaitingToAdvance { blocked_since: now };
                     if self.recalc_lanechanging {
-                        car.router.opportunistically_lanechange(&self.queues, map);
+                        car.router
+                            .opportunistically_lanechange(&car.vehicle, &self.queues, map);
                     }
                     scheduler.push(now, Command::UpdateCar(car.vehicle.id));
                 }
@@ -268,6 +294,7 @@ impl DrivingSimState {
                         front,
                         &car.vehicle,
                         parking,
+                        now,
                         map,
                         car.trip_and_person,
                         &mut self.events,
@@ -300,7 +327,8 @@ impl DrivingSimState {
                                 follower.total_blocked_time += now - blocked_since;
                                 follower.state = follower.crossing_state(
                                     // Since the follower was Queued, this must be where they are.
-                                    dist - car.vehicle.length - FOLLOWING_DISTANCE,
+                                    dist - car.vehicle.length
+                                        - follower.vehicle.behavior.following_distance,
                                     now,
                                     map,
                                 );
@@ -329,10 +357,7 @@ impl DrivingSimState {
                 assert!(from != goto);
 
                 if let Traversable::Turn(t) = goto {
-                    let mut speed = goto.speed_limit(map);
-                    if let Some(s) = car.vehicle.max_speed {
-                        speed = speed.min(s);
-                    }
+                    let speed = car.vehicle.speed(goto.speed_limit(map));
                     if !intersections.maybe_start_turn(
                         AgentID::Car(car.vehicle.id),
                         t,
@@ -359,6 +384,7 @@ impl DrivingSimState {
                 let last_step = car.router.advance(
                     &car.vehicle,
                     parking,
+                    now,
                     map,
                     car.trip_and_person,
                     &mut self.events,
@@ -369,12 +395,24 @@ impl DrivingSimState {
                 self.events.push(Event::AgentEntersTraversable(
                     AgentID::Car(car.vehicle.id),
                     goto,
+                    if car.vehicle.vehicle_type == VehicleType::Bus {
+                        // The driver, plus riders
+                        1 + transit.get_passengers(car.vehicle.id).len()
+                    } else {
+                        car.vehicle.occupancy
+                    },
                 ));
+                if let Traversable::Lane(l) = goto {
+                    if let Some(sign) = self.message_signs.get_mut(&map.get_l(l).parent) {
+                        sign.driver_passes(&car.vehicle, &mut car.router, map);
+                    }
+                }
 
                 // Don't mark turn_finished until our back is out of the turn.
                 car.last_steps.push_front(last_step);
 
-                // Optimistically assume we'll be out of the way ASAP.
+                // Optimistically assume we'll be out of the way ASAP. In the mesoscopic model,
+                // this is exactly when the queue we left can discharge the next vehicle.
                 // This is update, not push, because we might've scheduled a blind retry too
                 // late, and the car actually crosses an entire new traversable in the
                 // meantime.
@@ -382,7 +420,7 @@ impl DrivingSimState {
                     car.crossing_state_with_end_dist(
                         DistanceInterval::new_driving(
                             Distance::ZERO,
-                            car.vehicle.length + FOLLOWING_DISTANCE,
+                            car.vehicle.length + car.vehicle.behavior.following_distance,
                         ),
                         now,
                         map,
@@ -428,6 +466,7 @@ impl DrivingSimState {
                     our_dist,
                     &car.vehicle,
                     parking,
+                    now,
                     map,
                     car.trip_and_person,
                     &mut self.events,
@@ -494,7 +533,7 @@ impl DrivingSimState {
                         false
                     }
                     Some(ActionAtEnd::BusAtStop) => {
-                        transit.bus_arrived_at_stop(
+                        let stop = transit.bus_arrived_at_stop(
                             now,
                             car.vehicle.id,
                             trips,
@@ -503,10 +542,12 @@ impl DrivingSimState {
                             map,
                         );
                         car.total_blocked_time += now - blocked_since;
-                        car.state = CarState::Idling(
-                            our_dist,
-                            TimeInterval::new(now, now + TIME_TO_WAIT_AT_STOP),
-                        );
+                        let mut dwell = TIME_TO_WAIT_AT_STOP;
+                        // Only penalize stops known to be missing level boarding
+                        if map.get_bs(stop).amenities.level_boarding == Some(false) {
+                            dwell += EXTRA_WAIT_WITHOUT_LEVEL_BOARDING;
+                        }
+                        car.state = CarState::Idling(our_dist, TimeInterval::new(now, now + dwell));
                         scheduler
                             .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
                         true
@@ -578,7 +619,7 @@ impl DrivingSimState {
         // Hacks to delete cars that're mid-turn
         if let Traversable::Turn(_) = car.router.head() {
             let queue = self.queues.get_mut(&car.router.head()).unwrap();
-            queue.reserved_length += car.vehicle.length + FOLLOWING_DISTANCE;
+            queue.reserved_length += car.vehicle.length + car.vehicle.behavior.following_distance;
         }
         if let Some(Traversable::Turn(t)) = car.router.maybe_next() {
             intersections.cancel_request(AgentID::Car(c), t);
@@ -663,6 +704,50 @@ impl DrivingSimState {
         }
     }
 
+    // After live map edits, reroute every car whose route uses a closed lane or a turn that no
+    // longer exists. Returns the cars that can't be rerouted; the caller has to get rid of them.
+    pub fn handle_live_edits(
+        &mut self,
+        now: Time,
+        map: &Map,
+        scheduler: &mut Scheduler,
+        intersections: &mut IntersectionSimState,
+    ) -> Vec<CarID> {
+        let mut stuck = Vec::new();
+        for car in self.cars.values_mut() {
+            let old_next = car.router.maybe_next();
+            if !car
+                .router
+                .reroute_after_edits(car.vehicle.vehicle_type, map)
+            {
+                stuck.push(car.vehicle.id);
+                continue;
+            }
+            // The car might be waiting to do a turn that isn't on its route anymore.
+            if let (CarState::WaitingToAdvance { .. }, Some(Traversable::Turn(t))) =
+                (&car.state, old_next)
+            {
+                if car.router.maybe_next() != old_next {
+                    intersections.cancel_request(AgentID::Car(car.vehicle.id), t);
+                    scheduler.update(now, Command::UpdateCar(car.vehicle.id));
+                }
+            }
+        }
+        stuck
+    }
+
+    // Buses can't be removed partway through their route. Is there a way around any new closures
+    // for all of them?
+    pub fn can_reroute_buses_after_edits(&self, map: &Map) -> bool {
+        self.cars.values().all(|car| {
+            car.vehicle.vehicle_type != VehicleType::Bus
+                || car
+                    .router
+                    .clone()
+                    .reroute_after_edits(VehicleType::Bus, map)
+        })
+    }
+
     pub fn update_laggy_head(
         &mut self,
         id: CarID,
@@ -671,6 +756,16 @@ impl DrivingSimState {
         intersections: &mut IntersectionSimState,
         scheduler: &mut Scheduler,
     ) {
+        if self.granularity == SimGranularity::Mesoscopic {
+            // There's no back to track. This was scheduled for when the vehicle would clear its
+            // own length at free-flow speed, which sets how fast each queue drains.
+            let mut car = self.cars.remove(&id).unwrap();
+            let n = car.last_steps.len();
+            self.trim_last_steps(&mut car, now, n, map, intersections, scheduler);
+            self.cars.insert(id, car);
+            return;
+        }
+
         let currently_on = self.cars[&id].router.head();
         let current_dists =
             self.queues[&currently_on].get_car_positions(now, &self.cars, &self.queues);
@@ -687,7 +782,8 @@ impl DrivingSimState {
         };
 
         // Trim off as many of the oldest last_steps as we've made distance.
-        let mut dist_left_to_cleanup = self.cars[&id].vehicle.length + FOLLOWING_DISTANCE;
+        let mut dist_left_to_cleanup =
+            self.cars[&id].vehicle.length + self.cars[&id].vehicle.behavior.following_distance;
         dist_left_to_cleanup -= dist_along_last;
         let mut num_to_trim = None;
         for (idx, step) in self.cars[&id].last_steps.iter().enumerate() {
@@ -713,7 +809,8 @@ impl DrivingSimState {
                     // fine for correctness.
                     DistanceInterval::new_driving(
                         dist_along_last,
-                        self.cars[&id].vehicle.length + FOLLOWING_DISTANCE,
+                        self.cars[&id].vehicle.length
+                            + self.cars[&id].vehicle.behavior.following_distance,
                     ),
                     now,
                     map,
@@ -783,9 +880,11 @@ impl DrivingSimState {
                                 // immediately promote them to WaitingToAdvance.
                                 follower.state = CarState::WaitingToAdvance { blocked_since };
                                 if self.recalc_lanechanging {
-                                    follower
-                                        .router
-                                        .opportunistically_lanechange(&self.queues, map);
+                                    follower.router.opportunistically_lanechange(
+                                        &follower.vehicle,
+                                        &self.queues,
+                                        map,
+                                    );
                                 }
                                 scheduler.push(now, Command::UpdateCar(follower.vehicle.id));
                             }
@@ -822,6 +921,7 @@ impl DrivingSimState {
                     vehicle_type: Some(car.vehicle.vehicle_type),
                     pos: queue.id.dist_along(dist, map).0,
                     person: car.trip_and_person.map(|(_, p)| p),
+                    on: queue.id,
                 });
             }
         }
@@ -900,6 +1000,28 @@ impl DrivingSimState {
         }
     }
 
+    // Every vehicle on the lane right now, and how fast it's going. Anybody not moving counts as
+    // zero.
+    pub fn current_speeds(&self, l: LaneID) -> Vec<Speed> {
+        let mut speeds = Vec::new();
+        if let Some(queue) = self.queues.get(&Traversable::Lane(l)) {
+            for id in &queue.cars {
+                speeds.push(match self.cars[id].state {
+                    CarState::Crossing(ref time_int, ref dist_int) => {
+                        let dt = time_int.end - time_int.start;
+                        if dt == Duration::ZERO {
+                            Speed::ZERO
+                        } else {
+                            Speed::from_dist_time(dist_int.end - dist_int.start, dt)
+                        }
+                    }
+                    _ => Speed::ZERO,
+                });
+            }
+        }
+        speeds
+    }
+
     pub fn debug_car(&self, id: CarID) {
         if let Some(ref car) = self.cars.get(&id) {
             println!("{}", abstutil::to_json(car));
@@ -1036,6 +1158,26 @@ impl DrivingSimState {
         }
     }
 
+    pub fn place_message_sign(&mut self, sign: MessageSign) {
+        self.message_signs.insert(sign.road, sign);
+    }
+
+    pub fn remove_message_sign(&mut self, r: RoadID) -> Option<MessageSign> {
+        self.message_signs.remove(&r)
+    }
+
+    pub fn set_message_sign_active(&mut self, r: RoadID, active: bool) {
+        self.message_signs.get_mut(&r).unwrap().active = active;
+    }
+
+    pub fn get_message_sign(&self, r: RoadID) -> Option<&MessageSign> {
+        self.message_signs.get(&r)
+    }
+
+    pub fn get_message_signs(&self) -> Vec<&MessageSign> {
+        self.message_signs.values().collect()
+    }
+
     pub fn collect_events(&mut self) -> Vec<Event> {
         std::mem::replace(&mut self.events, Vec::new())
     }
@@ -12,6 +12,13 @@ use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 const WAIT_AT_STOP_SIGN: Duration = Duration::const_seconds(0.5);
 const WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL: Duration = Duration::const_seconds(0.2);
+// Cars retry constantly while a queue is full, so only report spillback from one lane this often.
+const SPILLBACK_REPORT_FREQUENCY: Duration = Duration::const_seconds(60.0);
+// Stopping for a signal that changed any more abruptly than this counts as hard braking. About
+// 0.3g.
+const HARD_BRAKING: f64 = 3.0;
+// Starting a turn this soon after a conflicting turn finished counts as a tight gap.
+const TIGHT_GAP: Duration = Duration::const_seconds(1.5);
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct IntersectionSimState {
@@ -22,6 +29,12 @@ pub struct IntersectionSimState {
     // (x, y) means x is blocked by y. It's a many-to-many relationship. TODO Better data
     // structure.
     blocked_by: BTreeSet<(CarID, CarID)>,
+    // When each full lane last had spillback reported
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    spillback_reported: BTreeMap<LaneID, Time>,
     events: Vec<Event>,
 }
 
@@ -35,6 +48,12 @@ struct State {
         deserialize_with = "deserialize_btreemap"
     )]
     waiting: BTreeMap<Request, Time>,
+    // When turns were last finished, for measuring gaps. Only recent ones are kept.
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    recently_cleared: BTreeMap<TurnID, Time>,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Clone, Debug)]
@@ -57,6 +76,7 @@ impl IntersectionSimState {
             dont_block_the_box,
             break_turn_conflict_cycles,
             blocked_by: BTreeSet::new(),
+            spillback_reported: BTreeMap::new(),
             events: Vec::new(),
         };
         for i in map.all_intersections() {
@@ -66,6 +86,7 @@ impl IntersectionSimState {
                     id: i.id,
                     accepted: BTreeSet::new(),
                     waiting: BTreeMap::new(),
+                    recently_cleared: BTreeMap::new(),
                 },
             );
             if i.is_traffic_signal() && !use_freeform_policy_everywhere {
@@ -92,6 +113,7 @@ impl IntersectionSimState {
     ) {
         let state = self.state.get_mut(&turn.parent).unwrap();
         assert!(state.accepted.remove(&Request { agent, turn }));
+        state.recently_cleared.insert(turn, now);
         if map.get_t(turn).turn_type != TurnType::SharedSidewalkCorner {
             self.wakeup_waiting(now, turn.parent, scheduler, map);
         }
@@ -208,6 +230,23 @@ impl IntersectionSimState {
         scheduler.push(now + remaining, Command::UpdateIntersection(id));
     }
 
+    // After live map edits, traffic signal timing might've changed, so the next phase could start
+    // sooner or later than scheduled.
+    pub fn handle_live_edits(&self, now: Time, map: &Map, scheduler: &mut Scheduler) {
+        if self.use_freeform_policy_everywhere {
+            return;
+        }
+        for i in map.all_intersections() {
+            if i.is_traffic_signal() {
+                self.wakeup_waiting(now, i.id, scheduler, map);
+                let (_, _, remaining) = map
+                    .get_traffic_signal(i.id)
+                    .current_phase_and_remaining_time(now);
+                scheduler.update(now + remaining, Command::UpdateIntersection(i.id));
+            }
+        }
+    }
+
     // For cars: The head car calls this when they're at the end of the lane WaitingToAdvance. If
     // this returns true, then the head car MUST actually start this turn.
     // For peds: Likewise -- only called when the ped is at the start of the turn. They must
@@ -257,9 +296,19 @@ impl IntersectionSimState {
             let queue = queues.get_mut(&Traversable::Lane(turn.dst)).unwrap();
             if !queue.try_to_reserve_entry(
                 car,
-                !self.dont_block_the_box
-                    || allow_block_the_box(map.get_i(turn.parent).orig_id.osm_node_id),
+                !self.dont_block_the_box || map.allow_block_the_box(turn.parent),
             ) {
+                // The intersection policy let this car go, so only the full queue is in the way.
+                if self
+                    .spillback_reported
+                    .get(&turn.dst)
+                    .map(|t| now - *t >= SPILLBACK_REPORT_FREQUENCY)
+                    .unwrap_or(true)
+                {
+                    self.spillback_reported.insert(turn.dst, now);
+                    self.events
+                        .push(Event::QueueSpillback(turn.parent, turn.dst));
+                }
                 if self.break_turn_conflict_cycles {
                     // TODO Should we run the detector here?
                     if let Some(c) = queue.laggy_head {
@@ -286,6 +335,8 @@ impl IntersectionSimState {
             }
         }
 
+        self.check_gap(&req, now, map);
+
         // TODO For now, we're only interested in signals, and there's too much raw data to store
         // for stop signs too.
         let state = self.state.get_mut(&turn.parent).unwrap();
@@ -307,6 +358,30 @@ impl IntersectionSimState {
         true
     }
 
+    // A car just reached the end of its lane, moving at approach_speed, and is about to ask to
+    // start this turn. If the signal turned against it too recently to stop comfortably, that's
+    // hard braking.
+    pub fn car_arrived(
+        &mut self,
+        car: CarID,
+        turn: TurnID,
+        approach_speed: Speed,
+        now: Time,
+        map: &Map,
+    ) {
+        if self.use_freeform_policy_everywhere {
+            return;
+        }
+        if let Some(signal) = map.maybe_get_traffic_signal(turn.parent) {
+            let (_, phase, _) = signal.current_phase_and_remaining_time(now);
+            if phase.get_priority_of_turn(turn, signal) == TurnPriority::Banned
+                && brakes_hard(approach_speed, time_banned(signal, turn, now))
+            {
+                self.events.push(Event::HardBraking(car, turn.parent));
+            }
+        }
+    }
+
     pub fn debug(&self, id: IntersectionID, map: &Map) {
         println!("{}", abstutil::to_json(&self.state[&id]));
         if let Some(ref sign) = map.maybe_get_stop_sign(id) {
@@ -422,14 +497,12 @@ impl IntersectionSimState {
         let our_priority = sign.get_priority(req.turn, map);
         assert!(our_priority != TurnPriority::Banned);
         let our_time = self.state[&req.turn.parent].waiting[req];
+        let wait = yield_wait(WAIT_AT_STOP_SIGN, req, maybe_cars_and_queues);
 
-        if our_priority == TurnPriority::Yield && now < our_time + WAIT_AT_STOP_SIGN {
+        if our_priority == TurnPriority::Yield && now < our_time + wait {
             // Since we have "ownership" of scheduling for req.agent, don't need to use
             // scheduler.update.
-            scheduler.push(
-                our_time + WAIT_AT_STOP_SIGN,
-                Command::update_agent(req.agent),
-            );
+            scheduler.push(our_time + wait, Command::update_agent(req.agent));
             return false;
         }
 
@@ -483,15 +556,15 @@ impl IntersectionSimState {
         }
 
         let our_time = self.state[&req.turn.parent].waiting[req];
-        if our_priority == TurnPriority::Yield
-            && now < our_time + WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL
-        {
+        let wait = yield_wait(
+            WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL,
+            req,
+            maybe_cars_and_queues,
+        );
+        if our_priority == TurnPriority::Yield && now < our_time + wait {
             // Since we have "ownership" of scheduling for req.agent, don't need to use
             // scheduler.update.
-            scheduler.push(
-                our_time + WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL,
-                Command::update_agent(req.agent),
-            );
+            scheduler.push(our_time + wait, Command::update_agent(req.agent));
             return false;
         }
 
@@ -525,6 +598,34 @@ impl IntersectionSimState {
         true
     }
 
+    // Post-encroachment time: how soon after a conflicting turn finished, or is still happening,
+    // this turn starts.
+    fn check_gap(&mut self, req: &Request, now: Time, map: &Map) {
+        let turn = map.get_t(req.turn);
+        if turn.turn_type == TurnType::SharedSidewalkCorner {
+            return;
+        }
+        let state = self.state.get_mut(&req.turn.parent).unwrap();
+        state.recently_cleared.retain(|_, t| now - *t < TIGHT_GAP);
+
+        let mut gap: Option<Duration> = None;
+        for other in &state.accepted {
+            if map.get_t(other.turn).conflicts_with(turn) {
+                gap = Some(Duration::ZERO);
+            }
+        }
+        for (t, cleared) in &state.recently_cleared {
+            if map.get_t(*t).conflicts_with(turn) {
+                let dt = now - *cleared;
+                gap = Some(gap.map(|g| g.min(dt)).unwrap_or(dt));
+            }
+        }
+        if let Some(gap) = gap {
+            self.events
+                .push(Event::TightGap(req.agent, req.turn.parent, gap));
+        }
+    }
+
     // If true, the request can go.
     fn handle_accepted_conflicts(
         &mut self,
@@ -619,34 +720,44 @@ impl IntersectionSimState {
     }
 }
 
-// TODO Sometimes a traffic signal is surrounded by tiny lanes with almost no capacity. Workaround
-// for now.
-fn allow_block_the_box(osm_node_id: i64) -> bool {
-    // 23rd and Madison
-    osm_node_id == 53211694 || osm_node_id == 53211693 ||
-    // 31st and S Jackson
-    osm_node_id == 53045512 ||
-    // 23rd and Rainier
-    osm_node_id == 53212741 || osm_node_id == 3496255252 ||
-    // Rainier and Dearborn
-    osm_node_id == 4607162574 ||
-    // WA 509 and WA 99
-    osm_node_id == 31327525 ||
-    // 45th and Union Bay
-    osm_node_id == 31192107 || osm_node_id == 4272330879 || osm_node_id == 53120147 ||
-    // 45th and Montlake
-    osm_node_id ==  31430639 || osm_node_id == 29977895 || osm_node_id == 3391705317 ||
-    osm_node_id == 29977897 ||
-    // Rainier and Brandon
-    osm_node_id == 53089293 ||
-    // Rainier and Letitia
-    osm_node_id == 1729797719 || osm_node_id == 4272388873 || osm_node_id == 53194882 ||
-    // Rainier and MLK
-    osm_node_id == 53131428 ||
-    // Montlake and 520
-    osm_node_id == 53128053 ||
-    // 12th and Boren
-    osm_node_id == 53165712
+// The simulation doesn't model deceleration, so estimate it: stopping from this speed within the
+// warning time needs at least speed / (2 * warning).
+fn brakes_hard(speed: Speed, warning: Duration) -> bool {
+    if speed == Speed::ZERO {
+        return false;
+    }
+    warning == Duration::ZERO
+        || speed.inner_meters_per_second() / (2.0 * warning.inner_seconds()) > HARD_BRAKING
+}
+
+// How long the signal has been stopping this turn, up to one cycle
+fn time_banned(signal: &ControlTrafficSignal, turn: TurnID, now: Time) -> Duration {
+    let (idx, phase, remaining) = signal.current_phase_and_remaining_time(now);
+    let mut banned = phase.duration - remaining;
+    let n = signal.phases.len();
+    for i in 1..n {
+        let prev = &signal.phases[(idx + n - i) % n];
+        if prev.get_priority_of_turn(turn, signal) != TurnPriority::Banned {
+            break;
+        }
+        banned += prev.duration;
+    }
+    banned
+}
+
+// Drivers might accept tighter gaps, or wait longer, than usual. Pedestrians and bikes always wait
+// the same.
+fn yield_wait(
+    base: Duration,
+    req: &Request,
+    maybe_cars_and_queues: Option<(&BTreeMap<CarID, Car>, &BTreeMap<Traversable, Queue>)>,
+) -> Duration {
+    if let (AgentID::Car(id), Some((cars, _))) = (req.agent, maybe_cars_and_queues) {
+        if let Some(car) = cars.get(&id) {
+            return car.vehicle.behavior.yield_wait(base);
+        }
+    }
+    base
 }
 
 // TODO Various problems (bad geometry, multi-intersection turn restrictions) cause
@@ -665,3 +776,60 @@ fn allow_conflicting_turns(osm_node_id: i64) -> bool {
     ]
     .contains(&osm_node_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map_model::raw::RawMap;
+    use map_model::{IntersectionType, Phase};
+
+    #[test]
+    fn hard_braking_depends_on_speed() {
+        // About 13 m/s, so stopping within 1s needs about 6.7 m/s^2, but 4s only needs about 1.7.
+        let fast = Speed::miles_per_hour(30.0);
+        assert!(brakes_hard(fast, Duration::ZERO));
+        assert!(brakes_hard(fast, Duration::seconds(1.0)));
+        assert!(!brakes_hard(fast, Duration::seconds(4.0)));
+
+        // Creeping up to a light that just changed is fine
+        assert!(!brakes_hard(
+            Speed::miles_per_hour(5.0),
+            Duration::seconds(1.0)
+        ));
+        assert!(!brakes_hard(Speed::ZERO, Duration::ZERO));
+    }
+
+    #[test]
+    fn warning_spans_banned_phases() {
+        let mut raw = RawMap::synthetic_grid("signal", 1, 1);
+        for i in raw.intersections.values_mut() {
+            if i.intersection_type == IntersectionType::StopSign {
+                i.intersection_type = IntersectionType::TrafficSignal;
+            }
+        }
+        let map = Map::synthetic(raw, false);
+        let i = map
+            .all_intersections()
+            .iter()
+            .find(|i| i.is_traffic_signal())
+            .unwrap();
+
+        // Only the middle of three phases lets the turn go
+        let mut signal = map.get_traffic_signal(i.id).clone();
+        let group = signal.turn_groups.values().next().unwrap();
+        let turn = group.members[0];
+        let mut go = Phase::new();
+        go.protected_groups.insert(group.id);
+        signal.phases = vec![Phase::new(), go, Phase::new()];
+        signal.offset = Duration::ZERO;
+        let at = |secs| Time::START_OF_DAY + Duration::seconds(secs);
+
+        // 5s into the last phase
+        assert_eq!(time_banned(&signal, turn, at(65.0)), Duration::seconds(5.0));
+        // 10s into the next cycle, after the whole last phase
+        assert_eq!(
+            time_banned(&signal, turn, at(100.0)),
+            Duration::seconds(40.0)
+        );
+    }
+}
@@ -7,6 +7,6 @@ mod walking;
 
 pub use self::driving::DrivingSimState;
 pub use self::intersection::IntersectionSimState;
-pub use self::parking::ParkingSimState;
+pub use self::parking::{CurbReport, ParkingSimState};
 pub use self::queue::Queue;
 pub use self::walking::WalkingSimState;
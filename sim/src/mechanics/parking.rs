@@ -3,10 +3,10 @@ use abstutil::{
     deserialize_btreemap, deserialize_multimap, serialize_btreemap, serialize_multimap, MultiMap,
     Timer,
 };
-use geom::{Distance, PolyLine, Pt2D};
+use geom::{Distance, PolyLine, Pt2D, Time};
 use map_model::{
-    BuildingID, Lane, LaneID, LaneType, Map, ParkingLotID, PathConstraints, PathStep, Position,
-    Traversable, TurnID,
+    BuildingID, CurbUse, Lane, LaneID, LaneType, Map, ParkingLotID, PathConstraints, PathStep,
+    Position, Traversable, TurnID,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
@@ -274,11 +274,15 @@ impl ParkingSimState {
         // Either the building where a seeded car starts or the target of a trip. For filtering
         // private spots.
         target: BuildingID,
+        now: Time,
         map: &Map,
     ) -> Vec<(ParkingSpot, Position)> {
         let mut candidates = Vec::new();
 
         for l in self.driving_to_parking_lanes.get(driving_pos.lane()) {
+            if !curb_allows_parking(*l, now, map) {
+                continue;
+            }
             let parking_dist = driving_pos
                 .equiv_pos(*l, driving_pos.dist_along(), map)
                 .dist_along();
@@ -401,6 +405,7 @@ impl ParkingSimState {
         start: LaneID,
         vehicle: &Vehicle,
         target: BuildingID,
+        now: Time,
         map: &Map,
     ) -> Option<(Vec<PathStep>, ParkingSpot, Position)> {
         let mut backrefs: HashMap<LaneID, TurnID> = HashMap::new();
@@ -422,6 +427,7 @@ impl ParkingSimState {
                         Position::new(current, Distance::ZERO),
                         vehicle,
                         target,
+                        now,
                         map,
                     )
                     .into_iter()
@@ -456,11 +462,40 @@ impl ParkingSimState {
         None
     }
 
+    // Breaks down on-street parking by what the curb is currently regulated for. Cars left in a
+    // spot after a no-parking, bus, or loading zone window starts are violations.
+    pub fn curb_report(&self, now: Time, map: &Map) -> CurbReport {
+        let mut report = CurbReport {
+            occupancy: BTreeMap::new(),
+            violations: Vec::new(),
+        };
+        for (l, lane) in &self.onstreet_lanes {
+            let usage = map.get_curb_regulation(*l).usage_at(now);
+            let entry = report.occupancy.entry(usage).or_insert((0, 0));
+            for spot in lane.spots() {
+                entry.1 += 1;
+                if let Some(car) = self.occupants.get(&spot) {
+                    entry.0 += 1;
+                    if usage != CurbUse::Parking {
+                        report.violations.push((*car, spot, usage));
+                    }
+                }
+            }
+        }
+        report
+    }
+
     pub fn collect_events(&mut self) -> Vec<Event> {
         std::mem::replace(&mut self.events, Vec::new())
     }
 }
 
+pub struct CurbReport {
+    // For each kind of curb use, (filled, total) on-street spots
+    pub occupancy: BTreeMap<CurbUse, (usize, usize)>,
+    pub violations: Vec<(CarID, ParkingSpot, CurbUse)>,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 struct ParkingLane {
     parking_lane: LaneID,
@@ -515,3 +550,12 @@ impl ParkingLane {
         spots
     }
 }
+
+// Can an ordinary car stop along this parking lane right now? Nothing in the simulation makes
+// deliveries yet, so loading zones are off limits, like bus zones.
+fn curb_allows_parking(l: LaneID, now: Time, map: &Map) -> bool {
+    match map.get_curb_regulation(l).usage_at(now) {
+        CurbUse::Parking => true,
+        CurbUse::NoParking | CurbUse::BusZone | CurbUse::LoadingZone => false,
+    }
+}
@@ -1,5 +1,5 @@
 use crate::mechanics::car::{Car, CarState};
-use crate::{CarID, FOLLOWING_DISTANCE};
+use crate::{CarID, SimGranularity};
 use geom::{Distance, Time};
 use map_model::{Map, Traversable};
 use serde::{Deserialize, Serialize};
@@ -13,22 +13,27 @@ pub struct Queue {
     pub laggy_head: Option<CarID>,
 
     pub geom_len: Distance,
-    // When a car's turn is accepted, reserve the vehicle length + FOLLOWING_DISTANCE for the
+    // When a car's turn is accepted, reserve the vehicle length + following distance for the
     // target lane. When the car completely leaves (stops being the laggy_head), free up that
     // space. To prevent blocking the box for possibly scary amounts of time, allocate some of this
     // length first. This is unused for turns themselves. This value can exceed geom_len (for the
     // edge case of ONE long car on a short queue).
     pub reserved_length: Distance,
+
+    // In the mesoscopic model, vehicles are points. Positions are only worked out for drawing and
+    // for finding where a vehicle ends its trip, so cars may overlap.
+    granularity: SimGranularity,
 }
 
 impl Queue {
-    pub fn new(id: Traversable, map: &Map) -> Queue {
+    pub fn new(id: Traversable, map: &Map, granularity: SimGranularity) -> Queue {
         Queue {
             id,
             cars: VecDeque::new(),
             laggy_head: None,
             geom_len: id.length(map),
             reserved_length: Distance::ZERO,
+            granularity,
         }
     }
 
@@ -56,12 +61,13 @@ impl Queue {
         let mut result: Vec<(CarID, Distance)> = Vec::new();
 
         for id in &self.cars {
+            // The follower decides how much room to leave
+            let gap = cars[id].vehicle.behavior.following_distance;
             let bound = match result.last() {
-                Some((leader, last_dist)) => {
-                    *last_dist - cars[leader].vehicle.length - FOLLOWING_DISTANCE
-                }
+                Some((leader, last_dist)) => *last_dist - cars[leader].vehicle.length - gap,
                 None => match self.laggy_head {
-                    Some(id) => {
+                    // A point has no back sticking out of the queue ahead.
+                    Some(id) if self.granularity == SimGranularity::Microscopic => {
                         // The simple but broken version:
                         //self.geom_len - cars[&id].vehicle.length - FOLLOWING_DISTANCE
 
@@ -104,21 +110,26 @@ impl Queue {
                             }
                             // They might actually be out of the way, but laggy_head hasn't been
                             // updated yet.
-                            if dist_away_from_this_queue
-                                < leader.vehicle.length + FOLLOWING_DISTANCE
-                            {
+                            if dist_away_from_this_queue < leader.vehicle.length + gap {
                                 self.geom_len
                                     - (cars[&id].vehicle.length - dist_away_from_this_queue)
-                                    - FOLLOWING_DISTANCE
+                                    - gap
                             } else {
                                 self.geom_len
                             }
                         }
                     }
-                    None => self.geom_len,
+                    _ => self.geom_len,
                 },
             };
 
+            // Points can pile up at the start of a queue that's over capacity.
+            let bound = if self.granularity == SimGranularity::Mesoscopic {
+                bound.max(Distance::ZERO)
+            } else {
+                bound
+            };
+
             // There's spillover and a car shouldn't have been able to enter yet.
             if bound < Distance::ZERO {
                 dump_cars(&result, cars, self.id, now);
@@ -155,6 +166,9 @@ impl Queue {
 
             result.push((*id, front));
         }
+        if self.granularity == SimGranularity::Mesoscopic {
+            return result;
+        }
         validate_positions(result, cars, now, self.id)
     }
 
@@ -162,6 +176,7 @@ impl Queue {
         &self,
         start_dist: Distance,
         vehicle_len: Distance,
+        following_distance: Distance,
         now: Time,
         cars: &BTreeMap<CarID, Car>,
         queues: &BTreeMap<Traversable, Queue>,
@@ -184,13 +199,16 @@ impl Queue {
 
         // Are we too close to the leader?
         if idx != 0
-            && dists[idx - 1].1 - cars[&dists[idx - 1].0].vehicle.length - FOLLOWING_DISTANCE
+            && dists[idx - 1].1 - cars[&dists[idx - 1].0].vehicle.length - following_distance
                 < start_dist
         {
             return None;
         }
         // Or the follower?
-        if idx != dists.len() && start_dist - vehicle_len - FOLLOWING_DISTANCE < dists[idx].1 {
+        if idx != dists.len()
+            && start_dist - vehicle_len - cars[&dists[idx].0].vehicle.behavior.following_distance
+                < dists[idx].1
+        {
             return None;
         }
 
@@ -200,11 +218,11 @@ impl Queue {
     // If true, there's room and the car must actually start the turn (because the space is
     // reserved).
     pub fn try_to_reserve_entry(&mut self, car: &Car, force_entry: bool) -> bool {
-        // Sometimes a car + FOLLOWING_DISTANCE might be longer than the geom_len entirely. In that
+        // Sometimes a car + following distance might be longer than the geom_len entirely. In that
         // case, it just means the car won't totally fit on the queue at once, which is fine.
         // Reserve the normal amount of space; the next car trying to enter will get rejected.
         // Also allow this don't-block-the-box prevention to be disabled.
-        let dist = car.vehicle.length + FOLLOWING_DISTANCE;
+        let dist = car.vehicle.length + car.vehicle.behavior.following_distance;
         if self.reserved_length + dist < self.geom_len
             || self.reserved_length == Distance::ZERO
             || force_entry
@@ -218,11 +236,12 @@ impl Queue {
     // TODO Refactor
     pub fn room_for_car(&self, car: &Car) -> bool {
         self.reserved_length == Distance::ZERO
-            || self.reserved_length + car.vehicle.length + FOLLOWING_DISTANCE < self.geom_len
+            || self.reserved_length + car.vehicle.length + car.vehicle.behavior.following_distance
+                < self.geom_len
     }
 
     pub fn free_reserved_space(&mut self, car: &Car) {
-        self.reserved_length -= car.vehicle.length + FOLLOWING_DISTANCE;
+        self.reserved_length -= car.vehicle.length + car.vehicle.behavior.following_distance;
         assert!(self.reserved_length >= Distance::ZERO);
     }
 }
@@ -234,7 +253,8 @@ fn validate_positions(
     id: Traversable,
 ) -> Vec<(CarID, Distance)> {
     for pair in dists.windows(2) {
-        if pair[0].1 - cars[&pair[0].0].vehicle.length - FOLLOWING_DISTANCE < pair[1].1 {
+        let gap = cars[&pair[1].0].vehicle.behavior.following_distance;
+        if pair[0].1 - cars[&pair[0].0].vehicle.length - gap < pair[1].1 {
             dump_cars(&dists, cars, id, now);
             panic!(
                 "get_car_positions wound up with bad positioning: {} then {}\n{:?}",
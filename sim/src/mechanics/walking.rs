@@ -379,6 +379,7 @@ impl WalkingSimState {
                 vehicle_type: None,
                 pos: ped.get_draw_ped(now, map).pos,
                 person: Some(ped.person),
+                on: ped.path.current_step().as_traversable(),
             });
         }
 
@@ -697,6 +698,7 @@ impl Pedestrian {
         events.push(Event::AgentEntersTraversable(
             AgentID::Pedestrian(self.id),
             self.path.current_step().as_traversable(),
+            1,
         ));
         true
     }
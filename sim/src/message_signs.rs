@@ -0,0 +1,57 @@
+use crate::{Router, Vehicle, VehicleType};
+use map_model::{Map, RoadID};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// A variable message sign along a road, warning drivers about trouble further ahead -- a closure,
+// an incident, a jam. Some of the drivers passing the sign whose route uses the troubled roads will
+// take a detour.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MessageSign {
+    // Drivers see the sign when they start down any lane of this road.
+    pub road: RoadID,
+    pub warns_about: BTreeSet<RoadID>,
+    // From 0 to 1, the fraction of drivers who read the sign and act on it
+    pub compliance: f64,
+    pub active: bool,
+    // How many drivers have changed their route because of this sign
+    pub diverted: usize,
+}
+
+impl MessageSign {
+    pub fn new(road: RoadID, warns_about: BTreeSet<RoadID>, compliance: f64) -> MessageSign {
+        assert!((0.0..=1.0).contains(&compliance));
+        MessageSign {
+            road,
+            warns_about,
+            compliance,
+            active: true,
+            diverted: 0,
+        }
+    }
+
+    pub(crate) fn driver_passes(&mut self, vehicle: &Vehicle, router: &mut Router, map: &Map) {
+        // Buses stick to their route.
+        if !self.active || vehicle.vehicle_type == VehicleType::Bus {
+            return;
+        }
+        if !self.reads_sign(vehicle) {
+            return;
+        }
+        if router.reroute_avoiding(
+            &self.warns_about,
+            vehicle.vehicle_type.to_constraints(),
+            map,
+        ) {
+            self.diverted += 1;
+        }
+    }
+
+    // Decide without an RNG, so the outcome doesn't depend on the order drivers pass the sign.
+    fn reads_sign(&self, vehicle: &Vehicle) -> bool {
+        let hash = (vehicle.id.0 as u64)
+            .wrapping_mul(2_654_435_761)
+            .wrapping_add((self.road.0 as u64).wrapping_mul(40_503));
+        ((hash % 1000) as f64) / 1000.0 < self.compliance
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Analytics, Sim, TripEndpoint};
+use geom::{Duration, Polygon, Pt2D};
+use map_model::Map;
+use std::collections::BTreeMap;
+
+// Without real neighborhood boundaries, split the map into squares of roughly this size.
+const CELL_SIZE: f64 = 1000.0;
+
+// How each neighborhood fares with the current edits, compared to the baseline. Trips are grouped
+// by where they start; intersection delay is grouped by where it happens, counting everybody
+// passing through.
+pub struct NeighborhoodScoreboard {
+    pub scores: Vec<NeighborhoodScore>,
+}
+
+#[derive(Clone)]
+pub struct NeighborhoodScore {
+    // Like "C4" -- column letter, row number
+    pub name: String,
+    pub polygon: Polygon,
+
+    // Only trips that finished both before and after the edits count.
+    pub num_trips: usize,
+    pub num_faster: usize,
+    pub num_slower: usize,
+    pub total_before: Duration,
+    pub total_after: Duration,
+
+    pub delay_before: Duration,
+    pub delay_after: Duration,
+}
+
+impl NeighborhoodScore {
+    pub fn avg_before(&self) -> Option<Duration> {
+        if self.num_trips == 0 {
+            None
+        } else {
+            Some(self.total_before / (self.num_trips as f64))
+        }
+    }
+
+    pub fn avg_after(&self) -> Option<Duration> {
+        if self.num_trips == 0 {
+            None
+        } else {
+            Some(self.total_after / (self.num_trips as f64))
+        }
+    }
+
+    // Change in total trip time, relative to before. Negative means people starting here got
+    // faster overall.
+    pub fn pct_change(&self) -> Option<f64> {
+        if self.total_before == Duration::ZERO {
+            None
+        } else {
+            Some((self.total_after - self.total_before) / self.total_before)
+        }
+    }
+}
+
+impl NeighborhoodScoreboard {
+    pub fn new(map: &Map, sim: &Sim, before: &Analytics) -> NeighborhoodScoreboard {
+        let bounds = map.get_bounds();
+
+        // Only cells with buildings are worth showing.
+        let mut scores: BTreeMap<(usize, usize), NeighborhoodScore> = BTreeMap::new();
+        for b in map.all_buildings() {
+            let (x, y) = cell(map, b.label_center);
+            scores.entry((x, y)).or_insert_with(|| NeighborhoodScore {
+                name: cell_name(x, y),
+                polygon: Polygon::rectangle(CELL_SIZE, CELL_SIZE).translate(
+                    bounds.min_x + (x as f64) * CELL_SIZE,
+                    bounds.min_y + (y as f64) * CELL_SIZE,
+                ),
+                num_trips: 0,
+                num_faster: 0,
+                num_slower: 0,
+                total_before: Duration::ZERO,
+                total_after: Duration::ZERO,
+                delay_before: Duration::ZERO,
+                delay_after: Duration::ZERO,
+            });
+        }
+
+        let now = sim.time();
+        for (id, dt_before, dt_after, _) in
+            sim.get_analytics().both_finished_trips_by_id(now, before)
+        {
+            let pt = endpoint_pt(map, &sim.trip_info(id).1);
+            // Trips starting from a border might not fall in any neighborhood
+            if let Some(score) = scores.get_mut(&cell(map, pt)) {
+                score.num_trips += 1;
+                if dt_after < dt_before {
+                    score.num_faster += 1;
+                } else if dt_after > dt_before {
+                    score.num_slower += 1;
+                }
+                score.total_before += dt_before;
+                score.total_after += dt_after;
+            }
+        }
+
+        for (analytics, is_before) in vec![(before, true), (sim.get_analytics(), false)] {
+            for (i, delays) in &analytics.intersection_delays {
+                if let Some(score) = scores.get_mut(&cell(map, map.get_i(*i).polygon.center())) {
+                    let sum = delays
+                        .iter()
+                        .filter(|(t, _, _)| *t <= now)
+                        .fold(Duration::ZERO, |sum, (_, dt, _)| sum + *dt);
+                    if is_before {
+                        score.delay_before += sum;
+                    } else {
+                        score.delay_after += sum;
+                    }
+                }
+            }
+        }
+
+        NeighborhoodScoreboard {
+            scores: scores.into_iter().map(|(_, score)| score).collect(),
+        }
+    }
+}
+
+pub(crate) fn cell(map: &Map, pt: Pt2D) -> (usize, usize) {
+    let bounds = map.get_bounds();
+    (
+        ((pt.x() - bounds.min_x) / CELL_SIZE) as usize,
+        ((pt.y() - bounds.min_y) / CELL_SIZE) as usize,
+    )
+}
+
+pub(crate) fn endpoint_pt(map: &Map, endpt: &TripEndpoint) -> Pt2D {
+    match endpt {
+        TripEndpoint::Bldg(b) => map.get_b(*b).label_center,
+        TripEndpoint::Border(i, _) => map.get_i(*i).polygon.center(),
+    }
+}
+
+pub(crate) fn cell_name(x: usize, y: usize) -> String {
+    let mut col = String::new();
+    let mut x = x + 1;
+    while x > 0 {
+        col.insert(0, (b'A' + ((x - 1) % 26) as u8) as char);
+        x = (x - 1) / 26;
+    }
+    format!("{}{}", col, y + 1)
+}
@@ -0,0 +1,182 @@
+use crate::neighborhoods::{cell, cell_name, endpoint_pt};
+use crate::{Sim, TripMode};
+use geom::Duration;
+use map_model::{Map, RoadID};
+use std::collections::BTreeMap;
+
+// Raw travel times from one run of a scenario. Averages hide how unpredictable a trip is; to judge
+// that, keep every sample and look at the tail.
+pub struct TravelTimeSamples {
+    // Keyed by the neighborhoods where trips start and end (see NeighborhoodScoreboard), and mode
+    pub per_od: BTreeMap<(String, String, TripMode), Vec<Duration>>,
+    // How long vehicles took to drive each road from end to end
+    pub per_corridor: BTreeMap<RoadID, Vec<Duration>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reliability {
+    pub count: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    // How much extra time, relative to the mean, somebody has to budget to be on time 95% of the
+    // time. (p95 - mean) / mean
+    pub buffer_index: f64,
+}
+
+impl Reliability {
+    // None if there are no samples
+    pub fn new(mut samples: Vec<Duration>) -> Option<Reliability> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        let count = samples.len();
+        let total = samples.iter().fold(Duration::ZERO, |sum, x| sum + *x);
+        let mean = total / (count as f64);
+        let p95 = percentile(&samples, 95.0);
+        Some(Reliability {
+            count,
+            mean,
+            p50: percentile(&samples, 50.0),
+            p95,
+            buffer_index: if mean == Duration::ZERO {
+                0.0
+            } else {
+                (p95 - mean) / mean
+            },
+        })
+    }
+}
+
+impl TravelTimeSamples {
+    // Only finished trips count. Aborted trips don't have a travel time to measure.
+    pub fn new(map: &Map, sim: &Sim) -> TravelTimeSamples {
+        let mut per_od = BTreeMap::new();
+        for (_, id, maybe_mode, dt) in &sim.get_analytics().finished_trips {
+            let mode = match maybe_mode {
+                Some(m) => *m,
+                None => continue,
+            };
+            let (_, from, to, _) = sim.trip_info(*id);
+            let (x1, y1) = cell(map, endpoint_pt(map, &from));
+            let (x2, y2) = cell(map, endpoint_pt(map, &to));
+            per_od
+                .entry((cell_name(x1, y1), cell_name(x2, y2), mode))
+                .or_insert_with(Vec::new)
+                .push(*dt);
+        }
+
+        TravelTimeSamples {
+            per_od,
+            per_corridor: sim
+                .get_analytics()
+                .road_traversals
+                .iter()
+                .map(|(r, samples)| (*r, samples.iter().map(|(_, dt)| *dt).collect()))
+                .collect(),
+        }
+    }
+
+    // Pool samples from another run, like the same scenario with a different RNG seed. One run
+    // rarely has enough trips between two neighborhoods to say much about the tail.
+    pub fn merge(&mut self, other: TravelTimeSamples) {
+        for (key, samples) in other.per_od {
+            self.per_od
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .extend(samples);
+        }
+        for (key, samples) in other.per_corridor {
+            self.per_corridor
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .extend(samples);
+        }
+    }
+
+    // Skips groups with fewer than min_samples; the tail of a handful of trips is just noise.
+    // Least reliable first.
+    pub fn od_reliability(
+        &self,
+        min_samples: usize,
+    ) -> Vec<((String, String, TripMode), Reliability)> {
+        rank(&self.per_od, min_samples)
+    }
+
+    pub fn corridor_reliability(&self, min_samples: usize) -> Vec<(RoadID, Reliability)> {
+        rank(&self.per_corridor, min_samples)
+    }
+}
+
+fn rank<K: Clone + Ord>(
+    groups: &BTreeMap<K, Vec<Duration>>,
+    min_samples: usize,
+) -> Vec<(K, Reliability)> {
+    let mut results: Vec<(K, Reliability)> = groups
+        .iter()
+        .filter(|(_, samples)| samples.len() >= min_samples)
+        .filter_map(|(key, samples)| Some((key.clone(), Reliability::new(samples.clone())?)))
+        .collect();
+    results.sort_by(|(_, a), (_, b)| b.buffer_index.partial_cmp(&a.buffer_index).unwrap());
+    results
+}
+
+// Nearest-rank. The samples must be sorted and non-empty.
+fn percentile(sorted: &Vec<Duration>, p: f64) -> Duration {
+    let rank = ((p / 100.0) * (sorted.len() as f64)).ceil() as usize;
+    sorted[rank.max(1).min(sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(od: Vec<f64>, corridor: Vec<f64>) -> TravelTimeSamples {
+        let secs = |list: Vec<f64>| list.into_iter().map(Duration::seconds).collect();
+        let mut per_od = BTreeMap::new();
+        per_od.insert(
+            ("A1".to_string(), "B2".to_string(), TripMode::Drive),
+            secs(od),
+        );
+        let mut per_corridor = BTreeMap::new();
+        per_corridor.insert(RoadID(0), secs(corridor));
+        TravelTimeSamples {
+            per_od,
+            per_corridor,
+        }
+    }
+
+    #[test]
+    fn merging_runs_pools_samples() {
+        // Neither run alone has enough samples to count.
+        let mut pooled = samples(vec![10.0, 10.0, 10.0], vec![5.0, 5.0]);
+        assert!(pooled.od_reliability(5).is_empty());
+        pooled.merge(samples(vec![10.0, 30.0], vec![]));
+
+        let od = pooled.od_reliability(5);
+        assert_eq!(od.len(), 1);
+        assert_eq!(
+            od[0].1,
+            Reliability {
+                count: 5,
+                mean: Duration::seconds(14.0),
+                p50: Duration::seconds(10.0),
+                p95: Duration::seconds(30.0),
+                buffer_index: (30.0 - 14.0) / 14.0,
+            }
+        );
+
+        // Corridors with nothing new in the other run keep their samples.
+        assert_eq!(pooled.per_corridor[&RoadID(0)].len(), 2);
+
+        // Groups only in the other run get added.
+        let mut per_corridor = BTreeMap::new();
+        per_corridor.insert(RoadID(1), vec![Duration::seconds(1.0)]);
+        pooled.merge(TravelTimeSamples {
+            per_od: BTreeMap::new(),
+            per_corridor,
+        });
+        assert_eq!(pooled.corridor_reliability(1).len(), 2);
+    }
+}
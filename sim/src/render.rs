@@ -53,6 +53,7 @@ pub struct UnzoomedAgent {
     pub pos: Pt2D,
     // None means a bus.
     pub person: Option<PersonID>,
+    pub on: Traversable,
 }
 
 // TODO Can we return borrows instead? Nice for time travel, not for main sim?
@@ -1,14 +1,15 @@
 use crate::mechanics::Queue;
 use crate::{
-    Event, ParkingSimState, ParkingSpot, PersonID, SidewalkSpot, TripID, TripPhaseType, Vehicle,
+    AlertLocation, Event, ParkingSimState, ParkingSpot, PersonID, SidewalkSpot, TripID,
+    TripPhaseType, Vehicle, VehicleType, MIN_HOV_OCCUPANCY,
 };
-use geom::Distance;
+use geom::{Distance, Time};
 use map_model::{
-    BuildingID, IntersectionID, Map, Path, PathConstraints, PathRequest, PathStep, Position,
-    Traversable, TurnID,
+    BuildingID, IntersectionID, LaneType, Map, ParkingLotID, Path, PathConstraints, PathRequest,
+    PathStep, Position, RoadID, Traversable, TurnID,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Router {
@@ -47,6 +48,10 @@ enum Goal {
     FollowBusRoute {
         end_dist: Distance,
     },
+    ParkAtLot {
+        lot: ParkingLotID,
+        end_dist: Distance,
+    },
 }
 
 impl Router {
@@ -68,6 +73,13 @@ impl Router {
         }
     }
 
+    pub fn park_at_lot(path: Path, lot: ParkingLotID, end_dist: Distance) -> Router {
+        Router {
+            path,
+            goal: Goal::ParkAtLot { lot, end_dist },
+        }
+    }
+
     pub fn bike_then_stop(path: Path, end_dist: Distance, map: &Map) -> Option<Router> {
         let last_lane = path.get_steps().iter().last().unwrap().as_lane();
         if map
@@ -124,6 +136,7 @@ impl Router {
             } => stuck_end_dist.unwrap_or_else(|| spot.unwrap().1),
             Goal::BikeThenStop { end_dist } => end_dist,
             Goal::FollowBusRoute { end_dist } => end_dist,
+            Goal::ParkAtLot { end_dist, .. } => end_dist,
         }
     }
 
@@ -136,6 +149,7 @@ impl Router {
         &mut self,
         vehicle: &Vehicle,
         parking: &ParkingSimState,
+        now: Time,
         map: &Map,
         trip_and_person: Option<(TripID, PersonID)>,
         events: &mut Vec<Event>,
@@ -147,6 +161,7 @@ impl Router {
                 Distance::ZERO,
                 vehicle,
                 parking,
+                now,
                 map,
                 trip_and_person,
                 events,
@@ -174,6 +189,7 @@ impl Router {
         front: Distance,
         vehicle: &Vehicle,
         parking: &ParkingSimState,
+        now: Time,
         map: &Map,
         // TODO Not so nice to plumb all of this here
         trip_and_person: Option<(TripID, PersonID)>,
@@ -210,6 +226,7 @@ impl Router {
                         Position::new(current_lane, front),
                         vehicle,
                         target,
+                        now,
                         map,
                     );
                     let best = if let Some(ref p) = map.get_b(target).parking {
@@ -246,8 +263,8 @@ impl Router {
                         }
                         *spot = Some((new_spot, new_pos.dist_along()));
                     } else {
-                        if let Some((new_path_steps, new_spot, new_pos)) =
-                            parking.path_to_free_parking_spot(current_lane, vehicle, target, map)
+                        if let Some((new_path_steps, new_spot, new_pos)) = parking
+                            .path_to_free_parking_spot(current_lane, vehicle, target, now, map)
                         {
                             *spot = Some((new_spot, new_pos.dist_along()));
                             for step in new_path_steps {
@@ -307,11 +324,29 @@ impl Router {
                     None
                 }
             }
+            Goal::ParkAtLot { lot, end_dist } => {
+                if end_dist != front {
+                    return None;
+                }
+                // Don't look anywhere else; the driver planned around this lot.
+                if let Some(spot) = parking.get_free_lot_spots(lot).into_iter().next() {
+                    Some(ActionAtEnd::StartParking(spot))
+                } else {
+                    events.push(Event::Alert(
+                        trip_and_person
+                            .map(|(_, p)| AlertLocation::Person(p))
+                            .unwrap_or(AlertLocation::Nil),
+                        format!("{} found park-and-ride {} full", vehicle.id, lot),
+                    ));
+                    Some(ActionAtEnd::GiveUpOnParking)
+                }
+            }
         }
     }
 
     pub fn opportunistically_lanechange(
         &mut self,
+        vehicle: &Vehicle,
         queues: &BTreeMap<Traversable, Queue>,
         map: &Map,
     ) {
@@ -334,7 +369,11 @@ impl Router {
 
         // Look for other candidate lanes. Must be the same lane type -- if there was a bus/bike
         // lane originally and pathfinding already decided to use it, stick with that decision.
+        // The exception is carpools, which can freely pick between driving and HOV lanes.
         let orig_lt = map.get_l(orig_target_lane).lane_type;
+        let hov_ok = vehicle.vehicle_type == VehicleType::Car
+            && vehicle.occupancy >= MIN_HOV_OCCUPANCY
+            && (orig_lt == LaneType::Driving || orig_lt == LaneType::HOV);
         let siblings = if parent.is_forwards(orig_target_lane) {
             &parent.children_forwards
         } else {
@@ -349,7 +388,9 @@ impl Router {
                     src: current_turn.src,
                     dst: *l,
                 };
-                if orig_lt == *lt && map.maybe_get_t(turn1).is_some() {
+                let same_lt = orig_lt == *lt
+                    || (hov_ok && (*lt == LaneType::Driving || *lt == LaneType::HOV));
+                if same_lt && map.maybe_get_t(turn1).is_some() {
                     // Now make sure we can go from this lane to next_lane.
                     let turn2 = TurnID {
                         parent: next_parent,
@@ -379,7 +420,204 @@ impl Router {
         self.path.modify_step(3, PathStep::Turn(turn2), map);
     }
 
+    // If the rest of the route uses any of these roads, try to detour around them. The current
+    // lane doesn't change. Returns true if the route changed.
+    pub fn reroute_avoiding(
+        &mut self,
+        avoid: &BTreeSet<RoadID>,
+        constraints: PathConstraints,
+        map: &Map,
+    ) -> bool {
+        let steps = self.path.get_steps();
+        let start = match steps[0] {
+            PathStep::Lane(l) => l,
+            _ => {
+                return false;
+            }
+        };
+        let end = match steps.back() {
+            Some(PathStep::Lane(l)) => *l,
+            _ => {
+                return false;
+            }
+        };
+        // There's no way around the destination itself
+        if avoid.contains(&map.get_l(end).parent)
+            || !steps.iter().skip(1).any(|step| match step {
+                PathStep::Lane(l) => avoid.contains(&map.get_l(*l).parent),
+                _ => false,
+            })
+        {
+            return false;
+        }
+
+        let req = PathRequest {
+            start: Position::new(start, Distance::ZERO),
+            end: Position::new(end, self.path.end_dist()),
+            constraints,
+        };
+        match map.pathfind_avoiding_roads(req, avoid) {
+            // Sometimes there's no way around, and the best path is the same as before
+            Some(path) if path.get_steps() != self.path.get_steps() => {
+                self.path = path;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // After live map edits, the rest of the route might use a closed lane or a turn that no
+    // longer exists. If so, the vehicle finishes the step it's on, even if that's closed now, and
+    // continues from there to the same place along whichever remaining turn is best. Returns false
+    // if there's no way around.
+    pub fn reroute_after_edits(&mut self, vehicle_type: VehicleType, map: &Map) -> bool {
+        let steps = self.path.get_steps();
+        if !steps
+            .iter()
+            .skip(1)
+            .any(|step| step_broken_by_edits(step, map))
+        {
+            return true;
+        }
+        let end = match steps.back() {
+            Some(PathStep::Lane(l)) => *l,
+            _ => {
+                return false;
+            }
+        };
+        let end_dist = self.path.end_dist();
+        let (mut new_steps, lane) = match steps[0] {
+            PathStep::Lane(l) => (vec![PathStep::Lane(l)], l),
+            PathStep::Turn(t) => (vec![PathStep::Turn(t), PathStep::Lane(t.dst)], t.dst),
+            PathStep::ContraflowLane(_) => {
+                return false;
+            }
+        };
+
+        if lane != end {
+            let constraints = vehicle_type.to_constraints();
+            let mut best: Option<(Distance, TurnID, Path)> = None;
+            for turn in map.get_turns_from_lane(lane) {
+                if !constraints.can_use(map.get_l(turn.id.dst), map) {
+                    continue;
+                }
+                if let Some(path) = map.pathfind(PathRequest {
+                    start: Position::new(turn.id.dst, Distance::ZERO),
+                    end: Position::new(end, end_dist),
+                    constraints,
+                }) {
+                    let dist = turn.geom.length() + path.total_length();
+                    if best.as_ref().map(|(d, _, _)| dist < *d).unwrap_or(true) {
+                        best = Some((dist, turn.id, path));
+                    }
+                }
+            }
+            if let Some((_, turn, path)) = best {
+                new_steps.push(PathStep::Turn(turn));
+                new_steps.extend(path.get_steps().iter().cloned());
+            } else {
+                return false;
+            }
+        }
+
+        self.path = Path::new(map, new_steps, end_dist);
+        true
+    }
+
     pub fn replace_path_for_serialization(&mut self, path: Path) -> Path {
         std::mem::replace(&mut self.path, path)
     }
 }
+
+// Lanes can be closed and turns removed by live map edits.
+pub(crate) fn path_broken_by_edits(path: &Path, map: &Map) -> bool {
+    path.get_steps()
+        .iter()
+        .any(|step| step_broken_by_edits(step, map))
+}
+
+fn step_broken_by_edits(step: &PathStep, map: &Map) -> bool {
+    match step {
+        PathStep::Lane(l) | PathStep::ContraflowLane(l) => {
+            map.get_l(*l).lane_type == LaneType::Construction
+        }
+        PathStep::Turn(t) => map.maybe_get_t(*t).is_none(),
+    }
+}
+
+// Trips that haven't started yet might have a route calculated before live map edits.
+pub(crate) fn repair_precomputed_path(
+    req: &Option<PathRequest>,
+    path: &mut Option<Path>,
+    map: &Map,
+) {
+    if let Some(req) = req {
+        if path
+            .as_ref()
+            .map(|p| path_broken_by_edits(p, map))
+            .unwrap_or(false)
+        {
+            *path = map.pathfind(req.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abstutil::Timer;
+    use map_model::raw::RawMap;
+    use map_model::{EditCmd, LaneID};
+
+    #[test]
+    fn reroute_around_closed_lane() {
+        let mut timer = Timer::throwaway();
+        let mut map = Map::synthetic(RawMap::synthetic_grid("reroute", 3, 3), true);
+        let driving: Vec<LaneID> = map
+            .all_lanes()
+            .iter()
+            .filter(|l| l.is_driving())
+            .map(|l| l.id)
+            .collect();
+        let route = |end: LaneID, map: &Map| {
+            map.pathfind(PathRequest {
+                start: Position::new(driving[0], Distance::ZERO),
+                end: Position::new(end, map.get_l(end).length() / 2.0),
+                constraints: PathConstraints::Car,
+            })
+        };
+        let path = driving
+            .iter()
+            .filter_map(|l| route(*l, &map))
+            .max_by_key(|p| p.get_steps().len())
+            .unwrap();
+        let steps: Vec<PathStep> = path.get_steps().iter().cloned().collect();
+        assert!(steps.len() >= 7);
+        let closed = steps[4].as_lane();
+        let end = steps.last().unwrap().as_lane();
+
+        // One car still on its first lane, another in the middle of its first turn
+        let mut on_lane = Router::follow_bus_route(path.clone(), path.end_dist());
+        let mut on_turn = Router::follow_bus_route(
+            Path::new(&map, steps[1..].to_vec(), path.end_dist()),
+            path.end_dist(),
+        );
+
+        let mut edits = map.get_edits().clone();
+        edits.commands.push(EditCmd::ChangeLaneType {
+            id: closed,
+            lt: LaneType::Construction,
+            orig_lt: LaneType::Driving,
+        });
+        map.apply_edits(edits, &mut timer);
+        map.recalculate_pathfinding_after_edits(&mut timer);
+
+        for (router, kept) in vec![(&mut on_lane, &steps[0..1]), (&mut on_turn, &steps[1..3])] {
+            assert!(router.reroute_after_edits(VehicleType::Car, &map));
+            let new_steps: Vec<PathStep> = router.path.get_steps().iter().cloned().collect();
+            assert_eq!(&new_steps[..kept.len()], kept);
+            assert!(!new_steps.contains(&PathStep::Lane(closed)));
+            assert_eq!(new_steps.last(), Some(&PathStep::Lane(end)));
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::{
-    pandemic, AgentID, CarID, CreateCar, CreatePedestrian, PedestrianID, TripID, TripSpec,
+    pandemic, repair_precomputed_path, AgentID, CarID, CreateCar, CreatePedestrian, PedestrianID,
+    TripID, TripSpec,
 };
 use derivative::Derivative;
 use geom::{Duration, Histogram, Time};
-use map_model::{IntersectionID, Path, PathRequest};
+use map_model::{IntersectionID, LaneID, Map, Path, PathRequest};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::btree_map::Entry;
@@ -23,6 +24,8 @@ pub enum Command {
     Callback(Duration),
     Pandemic(pandemic::Cmd),
     FinishRemoteTrip(TripID),
+    // Let the next vehicle waiting at a border onto this lane, if there's room
+    MeterBorder(LaneID),
 }
 
 impl Command {
@@ -46,6 +49,7 @@ impl Command {
             Command::Callback(_) => CommandType::Callback,
             Command::Pandemic(ref p) => CommandType::Pandemic(p.clone()),
             Command::FinishRemoteTrip(t) => CommandType::FinishRemoteTrip(*t),
+            Command::MeterBorder(l) => CommandType::MeterBorder(*l),
         }
     }
 }
@@ -62,6 +66,7 @@ pub enum CommandType {
     Callback,
     Pandemic(pandemic::Cmd),
     FinishRemoteTrip(TripID),
+    MeterBorder(LaneID),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -214,6 +219,36 @@ impl Scheduler {
         }
     }
 
+    // After live map edits, reroute vehicles that haven't spawned yet and recalculate broken
+    // routes for trips that haven't started. Vehicles without any route anymore are removed from
+    // the schedule and returned.
+    pub fn handle_live_edits(&mut self, map: &Map) -> Vec<CreateCar> {
+        let mut stuck = Vec::new();
+        for (cmd_type, (cmd, _)) in self.queued_commands.iter_mut() {
+            match cmd {
+                Command::SpawnCar(ref mut create_car, _) => {
+                    if !create_car
+                        .router
+                        .reroute_after_edits(create_car.vehicle.vehicle_type, map)
+                    {
+                        stuck.push(cmd_type.clone());
+                    }
+                }
+                Command::StartTrip(_, _, ref maybe_req, ref mut maybe_path) => {
+                    repair_precomputed_path(maybe_req, maybe_path, map);
+                }
+                _ => {}
+            }
+        }
+        stuck
+            .into_iter()
+            .filter_map(|cmd_type| match self.queued_commands.remove(&cmd_type) {
+                Some((Command::SpawnCar(create_car, _), _)) => Some(create_car),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn describe_stats(&self) -> String {
         format!("delta times for events: {}", self.delta_times.describe())
     }
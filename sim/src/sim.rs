@@ -1,10 +1,12 @@
 use crate::{
-    AgentID, AlertLocation, Analytics, CarID, Command, CreateCar, DrawCarInput, DrawPedCrowdInput,
-    DrawPedestrianInput, DrivingSimState, Event, GetDrawAgents, IntersectionSimState, OrigPersonID,
-    PandemicModel, ParkedCar, ParkingSimState, ParkingSpot, PedestrianID, Person, PersonID,
-    PersonState, Router, Scheduler, SidewalkPOI, SidewalkSpot, TransitSimState, TripEndpoint,
-    TripID, TripManager, TripMode, TripPhaseType, TripPositions, TripResult, TripSpawner,
-    UnzoomedAgent, Vehicle, VehicleSpec, VehicleType, WalkingSimState, BUS_LENGTH, MIN_CAR_LENGTH,
+    AgentID, AlertLocation, Analytics, BikeShareKind, BikeShareSystem, CarID, Command, CreateCar,
+    CurbReport, DemographicsConfig, DrawCarInput, DrawPedCrowdInput, DrawPedestrianInput,
+    DriverBehavior, DrivingSimState, Event, GetDrawAgents, IntersectionSimState, MessageSign,
+    OrigPersonID, PandemicModel, ParkedCar, ParkingSimState, ParkingSpot, PedestrianID, Person,
+    PersonID, PersonState, Pricing, PriorRoutes, RouteRecorder, Router, Scheduler, SidewalkPOI,
+    SidewalkSpot, TransitSimState, TripCosts, TripEndpoint, TripID, TripManager, TripMode,
+    TripPhaseType, TripPositions, TripResult, TripSpawner, UnzoomedAgent, Vehicle, VehicleSpec,
+    VehicleType, WalkingSimState, BUS_LENGTH, MIN_CAR_LENGTH,
 };
 use abstutil::Timer;
 use derivative::Derivative;
@@ -16,11 +18,14 @@ use map_model::{
 };
 use rand_xorshift::XorShiftRng;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::panic;
 
 // TODO Do something else.
 const BLIND_RETRY_TO_SPAWN: Duration = Duration::const_seconds(5.0);
+// Vehicles waiting offscreen enter a border lane at most this often. Roughly the saturation flow
+// of one lane, 1800 vehicles per hour.
+const BORDER_HEADWAY: Duration = Duration::const_seconds(2.0);
 
 #[derive(Serialize, Deserialize, Clone, Derivative)]
 #[derivative(PartialEq)]
@@ -36,6 +41,8 @@ pub struct Sim {
     pandemic: Option<PandemicModel>,
     scheduler: Scheduler,
     time: Time,
+    // Vehicles waiting offscreen to enter from a border, per lane they start on
+    border_queues: BTreeMap<LaneID, VecDeque<CreateCar>>,
 
     // TODO Reconsider these
     pub(crate) map_name: String,
@@ -60,6 +67,18 @@ pub struct Sim {
     #[derivative(PartialEq = "ignore")]
     #[serde(skip_serializing, skip_deserializing)]
     alerts: AlertHandler,
+
+    #[derivative(PartialEq = "ignore")]
+    #[serde(skip_serializing, skip_deserializing)]
+    warm_start: bool,
+    #[derivative(PartialEq = "ignore")]
+    #[serde(skip_serializing, skip_deserializing)]
+    warm_up: Option<Duration>,
+    #[derivative(PartialEq = "ignore")]
+    #[serde(skip_serializing, skip_deserializing)]
+    route_recorder: RouteRecorder,
+    #[derivative(PartialEq = "ignore")]
+    costs: TripCosts,
 }
 
 #[derive(Clone)]
@@ -72,6 +91,32 @@ pub struct SimOptions {
     pub enable_pandemic_model: Option<XorShiftRng>,
     pub alerts: AlertHandler,
     pub pathfinding_upfront: bool,
+    // Seed route choices from a previous run of the same scenario, if one was saved.
+    pub warm_start: bool,
+    // Long walking trips may switch to a shared bike.
+    pub bike_share: Option<BikeShareKind>,
+    pub granularity: SimGranularity,
+    // Scenario demand ramps up over this long after the first departure, and analytics only start
+    // recording once it's over, so the chaotic first minutes don't pollute comparisons.
+    pub warm_up: Option<Duration>,
+    // Analytics stop recording at this time, though the simulation keeps going.
+    pub cool_down: Option<Time>,
+}
+
+// How much detail to simulate vehicles with. Either way, the scenario and analytics look the same,
+// so results can be compared across runs.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SimGranularity {
+    // Vehicles have length, so they block lanes and intersections until their back clears, and
+    // they opportunistically change lanes.
+    Microscopic,
+    // Vehicles are points that move between FIFO queues on each lane and turn. They cross each
+    // queue at free-flow speed, then wait to leave in order. A lane only admits as many vehicles
+    // as fit along its length, and releases the next one after the last has had time to clear its
+    // own length. Nobody tracks where a vehicle's back actually is, so this is much cheaper, but
+    // a vehicle that stops just after a turn doesn't hold up the queue behind it, and spillback is
+    // underestimated.
+    Mesoscopic,
 }
 
 #[derive(Clone)]
@@ -101,6 +146,11 @@ impl SimOptions {
             enable_pandemic_model: None,
             alerts: AlertHandler::Print,
             pathfinding_upfront: false,
+            warm_start: false,
+            bike_share: None,
+            granularity: SimGranularity::Microscopic,
+            warm_up: None,
+            cool_down: None,
         }
     }
 }
@@ -109,8 +159,14 @@ impl SimOptions {
 impl Sim {
     pub fn new(map: &Map, opts: SimOptions, timer: &mut Timer) -> Sim {
         let mut scheduler = Scheduler::new();
+        let mut analytics = Analytics::new();
+        analytics.set_recording_window(None, opts.cool_down);
         Sim {
-            driving: DrivingSimState::new(map, opts.recalc_lanechanging),
+            driving: DrivingSimState::new(
+                map,
+                opts.recalc_lanechanging && opts.granularity == SimGranularity::Microscopic,
+                opts.granularity,
+            ),
             parking: ParkingSimState::new(map, timer),
             walking: WalkingSimState::new(),
             intersections: IntersectionSimState::new(
@@ -121,7 +177,10 @@ impl Sim {
                 opts.break_turn_conflict_cycles,
             ),
             transit: TransitSimState::new(),
-            trips: TripManager::new(opts.pathfinding_upfront),
+            trips: TripManager::new(
+                opts.pathfinding_upfront,
+                opts.bike_share.map(|kind| BikeShareSystem::new(map, kind)),
+            ),
             pandemic: if let Some(rng) = opts.enable_pandemic_model {
                 Some(PandemicModel::new(rng))
             } else {
@@ -129,6 +188,7 @@ impl Sim {
             },
             scheduler,
             time: Time::START_OF_DAY,
+            border_queues: BTreeMap::new(),
 
             map_name: map.get_name().to_string(),
             // TODO
@@ -137,8 +197,12 @@ impl Sim {
             step_count: 0,
             trip_positions: None,
             alerts: opts.alerts,
+            warm_start: opts.warm_start,
+            warm_up: opts.warm_up,
+            route_recorder: RouteRecorder::default(),
+            costs: TripCosts::default(),
 
-            analytics: Analytics::new(),
+            analytics,
         }
     }
 
@@ -172,6 +236,10 @@ impl Sim {
         self.parking.get_all_parking_spots()
     }
 
+    pub fn curb_report(&self, map: &Map) -> CurbReport {
+        self.parking.curb_report(self.time, map)
+    }
+
     // Also returns the start distance of the building. TODO Do that in the Path properly.
     pub fn walking_path_to_nearest_parking_spot(
         &self,
@@ -184,6 +252,8 @@ impl Sim {
             vehicle_type: VehicleType::Car,
             length: MIN_CAR_LENGTH,
             max_speed: None,
+            occupancy: 1,
+            behavior: DriverBehavior::TYPICAL,
         };
         let driving_lane = map.find_driving_lane_near_building(b);
 
@@ -196,15 +266,20 @@ impl Sim {
                 Position::new(driving_lane, Distance::ZERO),
                 &vehicle,
                 b,
+                self.time,
                 map,
             )
             .get(0)
         {
             spot.clone()
         } else {
-            let (_, spot, _) =
-                self.parking
-                    .path_to_free_parking_spot(driving_lane, &vehicle, b, map)?;
+            let (_, spot, _) = self.parking.path_to_free_parking_spot(
+                driving_lane,
+                &vehicle,
+                b,
+                self.time,
+                map,
+            )?;
             spot
         };
 
@@ -231,6 +306,12 @@ impl Sim {
     pub fn random_person(&mut self, ped_speed: Speed, vehicle_specs: Vec<VehicleSpec>) -> &Person {
         self.trips.random_person(ped_speed, vehicle_specs)
     }
+    pub(crate) fn set_demographics(&mut self, p: PersonID, config: &DemographicsConfig) {
+        self.trips.set_demographics(p, config);
+    }
+    pub(crate) fn set_pricing(&mut self, pricing: Pricing) {
+        self.costs.set_pricing(pricing);
+    }
     pub(crate) fn seed_parked_car(&mut self, vehicle: Vehicle, spot: ParkingSpot) {
         self.parking.reserve_spot(spot);
         self.parking.add_parked_car(ParkedCar { vehicle, spot });
@@ -239,17 +320,42 @@ impl Sim {
     pub fn seed_bus_route(&mut self, route: &BusRoute, map: &Map, timer: &mut Timer) -> Vec<CarID> {
         let mut results: Vec<CarID> = Vec::new();
 
-        // Try to spawn just ONE bus anywhere.
-        // TODO Be more realistic. One bus per stop is too much, one is too little.
-        for (next_stop_idx, req, mut path, end_dist) in
-            self.transit.create_empty_route(route, map).into_iter()
-        {
+        // Without a headway, try to spawn just ONE bus anywhere. Otherwise, spread out enough
+        // buses along the route to arrive that often.
+        let legs = self.transit.create_empty_route(route, map);
+        let num_buses = if let Some(headway) = route.headway {
+            let round_trip = legs.iter().fold(Duration::ZERO, |sum, (_, _, path, _)| {
+                sum + free_flow_time(path, map)
+            });
+            ((round_trip / headway).ceil() as usize).max(1)
+        } else {
+            1
+        };
+        let num_legs = legs.len();
+        if num_buses > num_legs {
+            // Each bus starts at a different stop
+            timer.warn(format!(
+                "{} ({}) needs {} buses for its headway, but only {} can start, one per stop",
+                route.name, route.id, num_buses, num_legs
+            ));
+        }
+        for (leg_idx, (next_stop_idx, req, mut path, end_dist)) in legs.into_iter().enumerate() {
+            if results.len() == num_buses {
+                break;
+            }
+            // Start each bus at an evenly spaced stop. If that fails, the next stop is tried.
+            if leg_idx < results.len() * num_legs / num_buses {
+                continue;
+            }
+
             // For now, no desire for randomness. Caller can pass in list of specs if that ever
             // changes.
             let vehicle = VehicleSpec {
                 vehicle_type: VehicleType::Bus,
                 length: BUS_LENGTH,
                 max_speed: None,
+                occupancy: 1,
+                behavior: DriverBehavior::TYPICAL,
             }
             .make(CarID(self.trips.new_car_id(), VehicleType::Bus), None);
             let id = vehicle.id;
@@ -294,7 +400,7 @@ impl Sim {
                     self.transit.bus_created(id, route.id, next_stop_idx);
                     self.analytics.record_demand(&path, map);
                     results.push(id);
-                    return results;
+                    break;
                 } else {
                     path.shift(map);
                 }
@@ -310,6 +416,45 @@ impl Sim {
     pub fn set_name(&mut self, name: String) {
         self.run_name = name;
     }
+
+    pub(crate) fn get_warm_up(&self) -> Option<Duration> {
+        self.warm_up
+    }
+
+    // Analytics ignore everything before this time. The end of the window stays the same.
+    pub(crate) fn start_recording_at(&mut self, time: Time) {
+        let until = self.analytics.record_until;
+        self.analytics.set_recording_window(Some(time), until);
+    }
+
+    // Only does anything if SimOptions::warm_start is set. Has to happen before trips start.
+    pub(crate) fn load_prior_routes(&mut self, scenario_name: &str, timer: &mut Timer) {
+        if !self.warm_start {
+            return;
+        }
+        if let Some(routes) = PriorRoutes::load(&self.map_name, scenario_name, timer) {
+            timer.note(format!(
+                "Warm-starting from {} routes after {} iterations",
+                abstutil::prettyprint_usize(routes.num_routes()),
+                routes.iteration
+            ));
+            self.trips.prior_routes = Some(routes);
+        } else {
+            timer.warn(format!(
+                "No prior routes for {}, so starting from free-flow paths",
+                scenario_name
+            ));
+        }
+    }
+
+    // The routes taken so far this run, for warm-starting the next one.
+    pub fn get_prior_routes(&self) -> PriorRoutes {
+        self.route_recorder.finish(
+            self.map_name.clone(),
+            self.run_name.clone(),
+            self.trips.prior_routes.as_ref(),
+        )
+    }
 }
 
 // Drawing
@@ -417,6 +562,87 @@ impl Sim {
         halt
     }
 
+    // Returns the car back if there's no room for it yet.
+    fn start_car(
+        &mut self,
+        create_car: CreateCar,
+        map: &Map,
+        events: &mut Vec<Event>,
+    ) -> Option<CreateCar> {
+        if !self.driving.start_car_on_lane(
+            self.time,
+            create_car.clone(),
+            map,
+            &self.intersections,
+            &self.parking,
+            &mut self.scheduler,
+        ) {
+            return Some(create_car);
+        }
+        if let Some((trip, _)) = create_car.trip_and_person {
+            self.trips
+                .agent_starting_trip_leg(AgentID::Car(create_car.vehicle.id), trip);
+            self.route_recorder
+                .record_route(trip, &create_car.req, create_car.router.get_path());
+        }
+        if let Some(parked_car) = create_car.maybe_parked_car {
+            if let ParkingSpot::Offstreet(b, _) = parked_car.spot {
+                // Buses don't start in parking garages, so trip must exist
+                events.push(Event::PersonLeavesBuilding(
+                    create_car.trip_and_person.unwrap().1,
+                    b,
+                ));
+            }
+            self.parking.remove_parked_car(parked_car);
+        }
+        if let Some((trip, person)) = create_car.trip_and_person {
+            events.push(Event::TripPhaseStarting(
+                trip,
+                person,
+                Some(create_car.req.clone()),
+                if create_car.vehicle.id.1 == VehicleType::Car {
+                    TripPhaseType::Driving
+                } else {
+                    TripPhaseType::Biking
+                },
+            ));
+        }
+        self.analytics
+            .record_demand(create_car.router.get_path(), map);
+        None
+    }
+
+    // Vehicles appearing (not leaving parking) at the start of a lane that begins at a border
+    fn border_lane(&self, create_car: &CreateCar, retry: bool, map: &Map) -> Option<LaneID> {
+        if !retry || create_car.maybe_parked_car.is_some() {
+            return None;
+        }
+        match create_car.router.head() {
+            Traversable::Lane(l) if map.get_i(map.get_l(l).src_i).is_border() => Some(l),
+            _ => None,
+        }
+    }
+
+    fn border_queue_changed(&self, i: IntersectionID, map: &Map, events: &mut Vec<Event>) {
+        let waiting = map
+            .get_i(i)
+            .outgoing_lanes
+            .iter()
+            .filter_map(|l| self.border_queues.get(l))
+            .map(|q| q.len())
+            .sum();
+        events.push(Event::BorderQueueChanged(i, waiting));
+    }
+
+    // How many vehicles are waiting offscreen to enter at each border
+    pub fn get_border_queues(&self, map: &Map) -> BTreeMap<IntersectionID, usize> {
+        let mut result = BTreeMap::new();
+        for (l, queue) in &self.border_queues {
+            *result.entry(map.get_l(*l).src_i).or_insert(0) += queue.len();
+        }
+        result
+    }
+
     // If true, halt simulation because the callback said so.
     fn do_step(
         &mut self,
@@ -442,63 +668,57 @@ impl Sim {
                 );
             }
             Command::SpawnCar(create_car, retry_if_no_room) => {
-                if self.driving.start_car_on_lane(
-                    self.time,
-                    create_car.clone(),
-                    map,
-                    &self.intersections,
-                    &self.parking,
-                    &mut self.scheduler,
-                ) {
-                    if let Some((trip, _)) = create_car.trip_and_person {
-                        self.trips
-                            .agent_starting_trip_leg(AgentID::Car(create_car.vehicle.id), trip);
+                // Vehicles entering from a border wait their turn offscreen, instead of each
+                // blindly retrying.
+                if let Some(l) = self.border_lane(&create_car, retry_if_no_room, map) {
+                    let queue = self.border_queues.entry(l).or_insert_with(VecDeque::new);
+                    queue.push_back(create_car);
+                    if queue.len() == 1 {
+                        self.scheduler.push(self.time, Command::MeterBorder(l));
                     }
-                    if let Some(parked_car) = create_car.maybe_parked_car {
-                        if let ParkingSpot::Offstreet(b, _) = parked_car.spot {
-                            // Buses don't start in parking garages, so trip must exist
-                            events.push(Event::PersonLeavesBuilding(
-                                create_car.trip_and_person.unwrap().1,
-                                b,
-                            ));
-                        }
-                        self.parking.remove_parked_car(parked_car);
-                    }
-                    if let Some((trip, person)) = create_car.trip_and_person {
-                        events.push(Event::TripPhaseStarting(
+                    self.border_queue_changed(map.get_l(l).src_i, map, &mut events);
+                } else if let Some(create_car) = self.start_car(create_car, map, &mut events) {
+                    if retry_if_no_room {
+                        // TODO Record this in the trip log
+                        self.scheduler.push(
+                            self.time + BLIND_RETRY_TO_SPAWN,
+                            Command::SpawnCar(create_car, retry_if_no_room),
+                        );
+                    } else {
+                        // Buses don't use Command::SpawnCar, so this must exist.
+                        let (trip, person) = create_car.trip_and_person.unwrap();
+                        println!(
+                            "No room to spawn car for {} by {}. Not retrying!",
+                            trip, person
+                        );
+                        self.trips.abort_trip(
+                            self.time,
                             trip,
-                            person,
-                            Some(create_car.req.clone()),
-                            if create_car.vehicle.id.1 == VehicleType::Car {
-                                TripPhaseType::Driving
-                            } else {
-                                TripPhaseType::Biking
-                            },
-                        ));
+                            Some(create_car.vehicle),
+                            &mut self.parking,
+                            &mut self.scheduler,
+                            map,
+                        );
                     }
-                    self.analytics
-                        .record_demand(create_car.router.get_path(), map);
-                } else if retry_if_no_room {
-                    // TODO Record this in the trip log
-                    self.scheduler.push(
-                        self.time + BLIND_RETRY_TO_SPAWN,
-                        Command::SpawnCar(create_car, retry_if_no_room),
-                    );
+                }
+            }
+            Command::MeterBorder(l) => {
+                let create_car = self.border_queues.get_mut(&l).unwrap().pop_front().unwrap();
+                if let Some(create_car) = self.start_car(create_car, map, &mut events) {
+                    self.border_queues
+                        .get_mut(&l)
+                        .unwrap()
+                        .push_front(create_car);
+                    self.scheduler
+                        .push(self.time + BORDER_HEADWAY, Command::MeterBorder(l));
                 } else {
-                    // Buses don't use Command::SpawnCar, so this must exist.
-                    let (trip, person) = create_car.trip_and_person.unwrap();
-                    println!(
-                        "No room to spawn car for {} by {}. Not retrying!",
-                        trip, person
-                    );
-                    self.trips.abort_trip(
-                        self.time,
-                        trip,
-                        Some(create_car.vehicle),
-                        &mut self.parking,
-                        &mut self.scheduler,
-                        map,
-                    );
+                    if self.border_queues[&l].is_empty() {
+                        self.border_queues.remove(&l);
+                    } else {
+                        self.scheduler
+                            .push(self.time + BORDER_HEADWAY, Command::MeterBorder(l));
+                    }
+                    self.border_queue_changed(map.get_l(l).src_i, map, &mut events);
                 }
             }
             Command::SpawnPed(create_ped) => {
@@ -619,6 +839,9 @@ impl Sim {
             if let Some(ref mut m) = self.pandemic {
                 m.handle_event(self.time, &ev, &mut self.scheduler);
             }
+            self.route_recorder.event(&ev, self.time);
+            self.costs
+                .event(&ev, self.time, map, &self.trips, &self.parking);
 
             self.analytics.event(ev, self.time, map);
         }
@@ -1131,6 +1354,31 @@ impl Sim {
         &self.analytics
     }
 
+    pub fn get_trip_costs(&self) -> &TripCosts {
+        &self.costs
+    }
+
+    pub fn get_bike_share(&self) -> Option<&BikeShareSystem> {
+        self.trips.get_bike_share()
+    }
+
+    // Every vehicle on the road right now, and how fast it's going
+    pub fn current_speeds_on_road(&self, r: RoadID, map: &Map) -> Vec<Speed> {
+        let mut speeds = Vec::new();
+        for l in map.get_r(r).all_lanes() {
+            speeds.extend(self.driving.current_speeds(l));
+        }
+        speeds
+    }
+
+    pub fn get_message_sign(&self, r: RoadID) -> Option<&MessageSign> {
+        self.driving.get_message_sign(r)
+    }
+
+    pub fn get_message_signs(&self) -> Vec<&MessageSign> {
+        self.driving.get_message_signs()
+    }
+
     pub fn find_blockage_front(&self, car: CarID, map: &Map) -> String {
         self.driving
             .find_blockage_front(car, map, &self.intersections)
@@ -1169,6 +1417,70 @@ impl Sim {
     }
 }
 
+// Live map edits
+impl Sim {
+    // Handles map edits made in the middle of a simulation, without resetting it. Only some edits
+    // are supported: traffic signal timing, speed limits, and closing lanes for vehicles. Routes
+    // using closed lanes are recalculated, and trips with no way around are aborted. The map must
+    // already have the edits applied and pathfinding recalculated. If a bus route becomes
+    // impossible, nothing changes and the caller should restart the simulation instead.
+    pub fn handle_live_edits(&mut self, map: &Map) -> Result<(), String> {
+        if !self.driving.can_reroute_buses_after_edits(map) {
+            return Err("A bus is stuck on a closed route".to_string());
+        }
+        self.transit.handle_live_edits(map)?;
+
+        for id in self.driving.handle_live_edits(
+            self.time,
+            map,
+            &mut self.scheduler,
+            &mut self.intersections,
+        ) {
+            self.kill_stuck_car(id, map);
+        }
+        // Only after stale turn requests are gone
+        self.intersections
+            .handle_live_edits(self.time, map, &mut self.scheduler);
+
+        // Vehicles that haven't spawned yet
+        let mut stuck = self.scheduler.handle_live_edits(map);
+        for (l, queue) in self.border_queues.iter_mut() {
+            let mut kept = VecDeque::new();
+            for mut create_car in queue.drain(..) {
+                if create_car
+                    .router
+                    .reroute_after_edits(create_car.vehicle.vehicle_type, map)
+                {
+                    kept.push_back(create_car);
+                } else {
+                    stuck.push(create_car);
+                }
+            }
+            if kept.is_empty() {
+                self.scheduler.cancel(Command::MeterBorder(*l));
+            }
+            *queue = kept;
+        }
+        self.border_queues.retain(|_, queue| !queue.is_empty());
+        for create_car in stuck {
+            if let Some((trip, _)) = create_car.trip_and_person {
+                println!("No route for {} after edits. Aborting!", trip);
+                self.trips.abort_trip(
+                    self.time,
+                    trip,
+                    Some(create_car.vehicle),
+                    &mut self.parking,
+                    &mut self.scheduler,
+                    map,
+                );
+            }
+        }
+
+        self.trips.handle_live_edits(map);
+        Ok(())
+    }
+}
+
 // Invasive debugging
 impl Sim {
     pub fn kill_stuck_car(&mut self, id: CarID, map: &Map) {
@@ -1194,6 +1506,21 @@ impl Sim {
         }
     }
 
+    // Replaces any existing sign on the same road. Only drivers who reach the road after this
+    // notice the sign.
+    pub fn place_message_sign(&mut self, sign: MessageSign) {
+        self.driving.place_message_sign(sign);
+    }
+
+    pub fn remove_message_sign(&mut self, r: RoadID) -> Option<MessageSign> {
+        self.driving.remove_message_sign(r)
+    }
+
+    // An inactive sign stays in place, but nobody reacts to it.
+    pub fn set_message_sign_active(&mut self, r: RoadID, active: bool) {
+        self.driving.set_message_sign_active(r, active);
+    }
+
     pub fn clear_alerts(&mut self) -> Vec<(Time, AlertLocation, String)> {
         std::mem::replace(&mut self.analytics.alerts, Vec::new())
     }
@@ -1233,3 +1560,14 @@ pub struct AgentProperties {
     pub lanes_crossed: usize,
     pub total_lanes: usize,
 }
+
+// How long a bus would take to follow the path with no traffic or stops
+fn free_flow_time(path: &Path, map: &Map) -> Duration {
+    let mut total = Duration::ZERO;
+    for step in path.get_steps() {
+        if let PathStep::Lane(l) = step {
+            total += map.get_l(*l).length() / map.get_parent(*l).speed_limit;
+        }
+    }
+    total
+}
@@ -1,6 +1,6 @@
 use crate::{
-    CarID, Event, PedestrianID, PersonID, Router, Scheduler, TripID, TripManager, TripPhaseType,
-    WalkingSimState,
+    path_broken_by_edits, CarID, Event, PedestrianID, PersonID, Router, Scheduler, TripID,
+    TripManager, TripPhaseType, WalkingSimState,
 };
 use abstutil::{deserialize_btreemap, serialize_btreemap};
 use geom::{Distance, Time};
@@ -134,6 +134,30 @@ impl TransitSimState {
         stops
     }
 
+    // After live map edits, the path between some stops might be broken. Find new ones, or fail
+    // without changing anything if a bus route has become impossible.
+    pub fn handle_live_edits(&mut self, map: &Map) -> Result<(), String> {
+        let mut fixed = Vec::new();
+        for (id, route) in &self.routes {
+            for (idx, stop) in route.stops.iter().enumerate() {
+                if path_broken_by_edits(&stop.path_to_next_stop, map) {
+                    if let Some(path) = map.pathfind(stop.req.clone()) {
+                        fixed.push((*id, idx, path));
+                    } else {
+                        return Err(format!(
+                            "{} can't get from {} to the next stop anymore",
+                            id, stop.id
+                        ));
+                    }
+                }
+            }
+        }
+        for (id, idx, path) in fixed {
+            self.routes.get_mut(&id).unwrap().stops[idx].path_to_next_stop = path;
+        }
+        Ok(())
+    }
+
     pub fn bus_created(&mut self, bus: CarID, route: BusRouteID, next_stop_idx: StopIdx) {
         self.routes.get_mut(&route).unwrap().buses.push(bus);
         self.buses.insert(
@@ -155,7 +179,7 @@ impl TransitSimState {
         walking: &mut WalkingSimState,
         scheduler: &mut Scheduler,
         map: &Map,
-    ) {
+    ) -> BusStopID {
         let mut bus = self.buses.get_mut(&id).unwrap();
         match bus.state {
             BusState::DrivingToStop(stop_idx) => {
@@ -204,9 +228,10 @@ impl TransitSimState {
                     }
                 }
                 self.peds_waiting.insert(stop1, still_waiting);
+                stop1
             }
             BusState::AtStop(_) => unreachable!(),
-        };
+        }
     }
 
     pub fn bus_departed_from_stop(&mut self, id: CarID) -> Router {
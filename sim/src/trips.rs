@@ -1,6 +1,7 @@
 use crate::{
-    AgentID, AlertLocation, CarID, Command, CreateCar, CreatePedestrian, DrivingGoal, Event,
-    OffMapLocation, OrigPersonID, ParkedCar, ParkingSimState, ParkingSpot, PedestrianID, PersonID,
+    repair_precomputed_path, AgentID, AlertLocation, BikeShareSystem, CarID, Command, CreateCar,
+    CreatePedestrian, Demographics, DemographicsConfig, DrivingGoal, Event, OffMapLocation,
+    OrigPersonID, ParkedCar, ParkingSimState, ParkingSpot, PedestrianID, PersonID, PriorRoutes,
     Scheduler, SidewalkPOI, SidewalkSpot, TransitSimState, TripID, TripPhaseType, TripSpec,
     Vehicle, VehicleSpec, VehicleType, WalkingSimState,
 };
@@ -25,22 +26,28 @@ pub struct TripManager {
     active_trip_mode: BTreeMap<AgentID, TripID>,
     unfinished_trips: usize,
     pub pathfinding_upfront: bool,
+    // Set when warm-starting from a previous run
+    #[serde(skip_serializing, skip_deserializing)]
+    pub prior_routes: Option<PriorRoutes>,
 
     car_id_counter: usize,
+    bike_share: Option<BikeShareSystem>,
 
     events: Vec<Event>,
 }
 
 impl TripManager {
-    pub fn new(pathfinding_upfront: bool) -> TripManager {
+    pub fn new(pathfinding_upfront: bool, bike_share: Option<BikeShareSystem>) -> TripManager {
         TripManager {
             trips: Vec::new(),
             people: Vec::new(),
             active_trip_mode: BTreeMap::new(),
             unfinished_trips: 0,
             car_id_counter: 0,
+            bike_share,
             events: Vec::new(),
             pathfinding_upfront,
+            prior_routes: None,
         }
     }
 
@@ -53,13 +60,18 @@ impl TripManager {
         vehicle_specs: Vec<VehicleSpec>,
     ) {
         assert_eq!(id.0, self.people.len());
-        let vehicles = vehicle_specs
+        let vehicles: Vec<Vehicle> = vehicle_specs
             .into_iter()
             .map(|v| {
                 let c = CarID(self.new_car_id(), v.vehicle_type);
                 v.make(c, Some(id))
             })
             .collect();
+        let demographics = Demographics {
+            income: None,
+            age: None,
+            owns_car: vehicles.iter().any(|v| v.vehicle_type == VehicleType::Car),
+        };
         self.people.push(Person {
             id,
             orig_id,
@@ -69,10 +81,15 @@ impl TripManager {
             ped: PedestrianID(id.0),
             ped_speed,
             vehicles,
+            demographics,
             delayed_trips: Vec::new(),
             on_bus: None,
         });
     }
+    pub fn set_demographics(&mut self, id: PersonID, config: &DemographicsConfig) {
+        let person = &mut self.people[id.0];
+        person.demographics = config.assign(id, person.demographics.owns_car);
+    }
     pub fn random_person(&mut self, ped_speed: Speed, vehicle_specs: Vec<VehicleSpec>) -> &Person {
         let id = PersonID(self.people.len());
         self.new_person(id, None, ped_speed, vehicle_specs);
@@ -107,6 +124,9 @@ impl TripManager {
             Some(TripLeg::Drive(_, ref goal)) => match goal {
                 DrivingGoal::ParkNear(b) => TripEndpoint::Bldg(*b),
                 DrivingGoal::Border(i, _, loc) => TripEndpoint::Border(*i, loc.clone()),
+                // TripSpawner::schedule_trip only lets ParkAndRide trips drive to a lot, and those
+                // always continue on foot.
+                DrivingGoal::ParkAtLot(_) => unreachable!(),
             },
             Some(TripLeg::Remote(ref to)) => {
                 TripEndpoint::Border(map.all_incoming_borders()[0].id, Some(to.clone()))
@@ -179,7 +199,8 @@ impl TripManager {
         trip.total_blocked_time += blocked_time;
 
         match trip.legs.pop_front() {
-            Some(TripLeg::Drive(c, DrivingGoal::ParkNear(_))) => {
+            Some(TripLeg::Drive(c, DrivingGoal::ParkNear(_)))
+            | Some(TripLeg::Drive(c, DrivingGoal::ParkAtLot(_))) => {
                 assert_eq!(car, c);
             }
             _ => unreachable!(),
@@ -269,7 +290,12 @@ impl TripManager {
             end,
             constraints: PathConstraints::Car,
         };
-        let path = if let Some(p) = map.pathfind(req.clone()) {
+        let path = if let Some(p) = self
+            .prior_routes
+            .as_ref()
+            .and_then(|r| r.pathfind(now, trip.id, &req, map))
+            .or_else(|| map.pathfind(req.clone()))
+        {
             p
         } else {
             self.events.push(Event::Alert(
@@ -338,15 +364,21 @@ impl TripManager {
             end,
             constraints: PathConstraints::Bike,
         };
-        if let Some(router) = map
-            .pathfind(req.clone())
+        if let Some(router) = self
+            .prior_routes
+            .as_ref()
+            .and_then(|r| r.pathfind(now, trip.id, &req, map))
+            .or_else(|| map.pathfind(req.clone()))
             .and_then(|path| drive_to.make_router(path, map, VehicleType::Bike))
         {
             scheduler.push(
                 now,
                 Command::SpawnCar(
                     CreateCar::for_appearing(
-                        self.people[trip.person.0].get_vehicle(bike),
+                        self.bike_share
+                            .as_ref()
+                            .and_then(|bs| bs.lookup_vehicle(bike))
+                            .unwrap_or_else(|| self.people[trip.person.0].get_vehicle(bike)),
                         driving_pos,
                         router,
                         req,
@@ -392,6 +424,9 @@ impl TripManager {
             }
             _ => unreachable!(),
         };
+        if let Some(ref mut bs) = self.bike_share {
+            bs.return_bike(bike);
+        }
 
         if !trip.spawn_ped(
             now,
@@ -668,6 +703,15 @@ impl TripManager {
         self.person_finished_trip(now, person, parking, scheduler, map);
     }
 
+    // Trips waiting on an earlier one might have a route calculated before live map edits.
+    pub fn handle_live_edits(&mut self, map: &Map) {
+        for person in &mut self.people {
+            for (_, _, maybe_req, maybe_path) in &mut person.delayed_trips {
+                repair_precomputed_path(maybe_req, maybe_path, map);
+            }
+        }
+    }
+
     pub fn abort_trip(
         &mut self,
         now: Time,
@@ -702,6 +746,14 @@ impl TripManager {
             TripEndpoint::Bldg(b) => PersonState::Inside(b),
             TripEndpoint::Border(_, _) => PersonState::OffMap,
         };
+        // Shared bikes warp to where they were headed, so the fleet doesn't shrink
+        if let Some(ref mut bs) = self.bike_share {
+            for leg in &trip.legs {
+                if let TripLeg::Drive(c, _) = leg {
+                    bs.return_bike(*c);
+                }
+            }
+        }
         // Don't forget the car!
         if let Some(vehicle) = abandoned_vehicle {
             if vehicle.vehicle_type == VehicleType::Car {
@@ -712,6 +764,7 @@ impl TripManager {
                             Position::new(driving_lane, Distance::ZERO),
                             &vehicle,
                             b,
+                            now,
                             map,
                         )
                         // TODO Could pick something closer, but meh, aborted trips are bugs anyway
@@ -719,7 +772,7 @@ impl TripManager {
                         .map(|(spot, _)| spot.clone())
                         .or_else(|| {
                             parking
-                                .path_to_free_parking_spot(driving_lane, &vehicle, b, map)
+                                .path_to_free_parking_spot(driving_lane, &vehicle, b, now, map)
                                 .map(|(_, spot, _)| spot)
                         })
                     {
@@ -878,6 +931,10 @@ impl TripManager {
         &self.people
     }
 
+    pub fn get_bike_share(&self) -> Option<&BikeShareSystem> {
+        self.bike_share.as_ref()
+    }
+
     pub fn trip_to_person(&self, id: TripID) -> PersonID {
         self.trips[id.0].person
     }
@@ -918,8 +975,17 @@ impl TripManager {
         scheduler: &mut Scheduler,
         map: &Map,
     ) {
-        if !self.pathfinding_upfront && maybe_path.is_none() && maybe_req.is_some() {
-            maybe_path = map.pathfind(maybe_req.clone().unwrap());
+        if let Some(ref req) = maybe_req {
+            // Prior routes only exist for vehicles, so walking requests fall through.
+            if let Some(path) = self
+                .prior_routes
+                .as_ref()
+                .and_then(|r| r.pathfind(now, trip, req, map))
+            {
+                maybe_path = Some(path);
+            } else if !self.pathfinding_upfront && maybe_path.is_none() {
+                maybe_path = map.pathfind(req.clone());
+            }
         }
 
         let person = &mut self.people[self.trips[trip.0].person.0];
@@ -1002,6 +1068,9 @@ impl TripManager {
             }
             TripSpec::UsingParkedCar {
                 car, start_bldg, ..
+            }
+            | TripSpec::ParkAndRide {
+                car, start_bldg, ..
             } => {
                 assert_eq!(person.state, PersonState::Inside(start_bldg));
                 person.state = PersonState::Trip(trip);
@@ -1090,25 +1159,39 @@ impl TripManager {
                     }
                 );
                 person.state = PersonState::Trip(trip);
+                let (ped, ped_speed, person) = (person.ped, person.ped_speed, person.id);
 
-                let req = maybe_req.unwrap();
+                let mut req = maybe_req.unwrap();
+                let goal = if let Some(walk_to) =
+                    self.maybe_use_bike_share(now, trip, &start, &goal, map)
+                {
+                    req = PathRequest {
+                        start: start.sidewalk_pos,
+                        end: walk_to.sidewalk_pos,
+                        constraints: PathConstraints::Pedestrian,
+                    };
+                    maybe_path = map.pathfind(req.clone());
+                    walk_to
+                } else {
+                    goal
+                };
                 if let Some(path) = maybe_path {
                     scheduler.push(
                         now,
                         Command::SpawnPed(CreatePedestrian {
-                            id: person.ped,
-                            speed: person.ped_speed,
+                            id: ped,
+                            speed: ped_speed,
                             start,
                             goal,
                             path,
                             req,
                             trip,
-                            person: person.id,
+                            person,
                         }),
                     );
                 } else {
                     self.events.push(Event::Alert(
-                        AlertLocation::Person(person.id),
+                        AlertLocation::Person(person),
                         format!("JustWalking trip couldn't find the first path {}", req),
                     ));
                     self.abort_trip(now, trip, None, parking, scheduler, map);
@@ -1242,6 +1325,46 @@ impl TripManager {
     }
 }
 
+impl TripManager {
+    // If a shared bike is convenient for a walking trip that's just starting, switch the trip to
+    // use it, and return where to walk to pick it up.
+    fn maybe_use_bike_share(
+        &mut self,
+        now: Time,
+        id: TripID,
+        start: &SidewalkSpot,
+        goal: &SidewalkSpot,
+        map: &Map,
+    ) -> Option<SidewalkSpot> {
+        let goal_bldg = match goal.connection {
+            SidewalkPOI::Building(b) => b,
+            _ => {
+                return None;
+            }
+        };
+        let bike = CarID(self.car_id_counter, VehicleType::Bike);
+        let (pickup, dropoff) = self.bike_share.as_mut()?.maybe_checkout(
+            now,
+            start.sidewalk_pos.pt(map),
+            goal_bldg,
+            bike,
+            map,
+        )?;
+        self.car_id_counter += 1;
+
+        // The bike share system only picks docks with a bike rack.
+        let walk_to = SidewalkSpot::bike_from_bike_rack(map.get_b(pickup).sidewalk(), map).unwrap();
+        let trip = &mut self.trips[id.0];
+        trip.mode = TripMode::Bike;
+        trip.legs = VecDeque::from(vec![
+            TripLeg::Walk(walk_to.clone()),
+            TripLeg::Drive(bike, DrivingGoal::ParkNear(dropoff)),
+            TripLeg::Walk(goal.clone()),
+        ]);
+        Some(walk_to)
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 struct Trip {
     id: TripID,
@@ -1438,6 +1561,7 @@ pub struct Person {
     pub ped_speed: Speed,
     // Both cars and bikes
     pub vehicles: Vec<Vehicle>,
+    pub demographics: Demographics,
 
     delayed_trips: Vec<(TripID, TripSpec, Option<PathRequest>, Option<Path>)>,
     on_bus: Option<CarID>,